@@ -9,7 +9,8 @@
 //!
 //! Ref: https://github.com/zeroclaw-labs/zeroclaw/issues/618 (item 7)
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
 use std::sync::{Arc, Mutex};
 
 use zeroclaw::agent::agent::Agent;
@@ -38,6 +39,8 @@ impl BenchProvider {
             responses: Mutex::new(vec![ChatResponse {
                 text: Some(text.into()),
                 tool_calls: vec![],
+                usage: None,
+                reasoning: None,
             }]),
         }
     }
@@ -52,10 +55,14 @@ impl BenchProvider {
                         name: "noop".into(),
                         arguments: "{}".into(),
                     }],
+                    usage: None,
+                    reasoning: None,
                 },
                 ChatResponse {
                     text: Some("done".into()),
                     tool_calls: vec![],
+                    usage: None,
+                    reasoning: None,
                 },
             ]),
         }
@@ -85,6 +92,8 @@ impl Provider for BenchProvider {
             return Ok(ChatResponse {
                 text: Some("done".into()),
                 tool_calls: vec![],
+                usage: None,
+                reasoning: None,
             });
         }
         Ok(guard.remove(0))
@@ -150,6 +159,8 @@ Let me know if you need more."#
                 .into(),
         ),
         tool_calls: vec![],
+        usage: None,
+        reasoning: None,
     };
 
     let multi_tool = ChatResponse {
@@ -166,6 +177,8 @@ Let me know if you need more."#
                 .into(),
         ),
         tool_calls: vec![],
+        usage: None,
+        reasoning: None,
     };
 
     c.bench_function("xml_parse_single_tool_call", |b| {
@@ -198,6 +211,8 @@ fn bench_native_parsing(c: &mut Criterion) {
                 arguments: r#"{"path": "src/main.rs"}"#.into(),
             },
         ],
+        usage: None,
+        reasoning: None,
     };
 
     c.bench_function("native_parse_tool_calls", |b| {
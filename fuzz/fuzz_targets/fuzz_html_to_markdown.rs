@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use zeroclaw::tools::web_fetch::html_to_markdown;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = html_to_markdown(s, "https://example.com/docs/page.html", true);
+        let _ = html_to_markdown(s, "https://example.com/docs/page.html", false);
+    }
+});
@@ -171,6 +171,18 @@ impl AgentBuilder {
         self
     }
 
+    /// Wrap the configured provider, e.g. to meter or record its calls.
+    pub fn map_provider(mut self, f: impl FnOnce(Box<dyn Provider>) -> Box<dyn Provider>) -> Self {
+        self.provider = self.provider.map(f);
+        self
+    }
+
+    /// Wrap each configured tool, e.g. to record its invocations.
+    pub fn map_tools(mut self, f: impl FnMut(Box<dyn Tool>) -> Box<dyn Tool>) -> Self {
+        self.tools = self.tools.map(|tools| tools.into_iter().map(f).collect());
+        self
+    }
+
     pub fn build(self) -> Result<Agent> {
         let tools = self
             .tools
@@ -231,6 +243,12 @@ impl Agent {
     }
 
     pub fn from_config(config: &Config) -> Result<Self> {
+        Self::builder_from_config(config)?.build()
+    }
+
+    /// Builder populated from `config`, so callers can swap or wrap parts
+    /// before building.
+    pub fn builder_from_config(config: &Config) -> Result<AgentBuilder> {
         let observer: Arc<dyn Observer> =
             Arc::from(observability::create_observer(&config.observability));
         let runtime: Arc<dyn runtime::RuntimeAdapter> =
@@ -282,27 +300,42 @@ impl Agent {
             .unwrap_or("anthropic/claude-sonnet-4-20250514")
             .to_string();
 
-        let provider: Box<dyn Provider> = providers::create_routed_provider(
+        let provider: Box<dyn Provider> = providers::create_routed_provider_with_options(
             provider_name,
             config.api_key.as_deref(),
             config.api_url.as_deref(),
             &config.reliability,
             &config.model_routes,
             &model_name,
+            &providers::ProviderRuntimeOptions::default(),
+            providers::router::RoutingOptions::from_config(config).with_observer(observer.clone()),
         )?;
+        let provider = crate::cost::wrap_provider(config, provider_name, provider);
 
         let dispatcher_choice = config.agent.tool_dispatcher.as_str();
         let tool_dispatcher: Box<dyn ToolDispatcher> = match dispatcher_choice {
             "native" => Box::new(NativeToolDispatcher),
             "xml" => Box::new(XmlToolDispatcher),
-            _ if provider.supports_native_tools() => Box::new(NativeToolDispatcher),
+            _ if providers::catalog::capabilities_for(
+                provider_name,
+                &model_name,
+                provider.as_ref(),
+            )
+            .native_tool_calling =>
+            {
+                Box::new(NativeToolDispatcher)
+            }
             _ => Box::new(XmlToolDispatcher),
         };
 
-        let available_hints: Vec<String> =
-            config.model_routes.iter().map(|r| r.hint.clone()).collect();
+        let available_hints: Vec<String> = config
+            .model_routes
+            .iter()
+            .map(|r| r.hint.clone())
+            .chain(config.routing_policies.iter().map(|p| p.name.clone()))
+            .collect();
 
-        Agent::builder()
+        Ok(Agent::builder()
             .provider(provider)
             .tools(tools)
             .memory(memory)
@@ -325,8 +358,7 @@ impl Agent {
                 config,
             ))
             .skills_prompt_mode(config.skills.prompt_injection_mode)
-            .auto_save(config.memory.auto_save)
-            .build()
+            .auto_save(config.memory.auto_save))
     }
 
     fn trim_history(&mut self) {
@@ -434,6 +466,12 @@ impl Agent {
         self.model_name.clone()
     }
 
+    /// Register additional tools (e.g. discovered at runtime from MCP servers).
+    pub fn extend_tools(&mut self, tools: Vec<Box<dyn Tool>>) {
+        self.tool_specs.extend(tools.iter().map(|tool| tool.spec()));
+        self.tools.extend(tools);
+    }
+
     pub async fn turn(&mut self, user_message: &str) -> Result<String> {
         if self.history.is_empty() {
             let system_prompt = self.build_system_prompt()?;
@@ -479,6 +517,7 @@ impl Agent {
                         } else {
                             None
                         },
+                        output_schema: None,
                     },
                     &effective_model,
                     self.temperature,
@@ -518,6 +557,7 @@ impl Agent {
             self.history.push(ConversationMessage::AssistantToolCalls {
                 text: response.text.clone(),
                 tool_calls: response.tool_calls.clone(),
+                reasoning: response.reasoning.clone(),
             });
 
             let results = self.execute_tools(&calls).await;
@@ -582,6 +622,11 @@ pub async fn run(
     effective_config.default_temperature = temperature;
 
     let mut agent = Agent::from_config(&effective_config)?;
+    let security = Arc::new(SecurityPolicy::from_config(
+        &effective_config.autonomy,
+        &effective_config.workspace_dir,
+    ));
+    agent.extend_tools(crate::mcp::create_mcp_tools(&effective_config, &security).await);
 
     let provider_name = effective_config
         .default_provider
@@ -651,6 +696,7 @@ mod tests {
                     text: Some("done".into()),
                     tool_calls: vec![],
                     usage: None,
+                    reasoning: None,
                 });
             }
             Ok(guard.remove(0))
@@ -689,6 +735,7 @@ mod tests {
                 text: Some("hello".into()),
                 tool_calls: vec![],
                 usage: None,
+                reasoning: None,
             }]),
         });
 
@@ -728,11 +775,13 @@ mod tests {
                         arguments: "{}".into(),
                     }],
                     usage: None,
+                    reasoning: None,
                 },
                 crate::providers::ChatResponse {
                     text: Some("done".into()),
                     tool_calls: vec![],
                     usage: None,
+                    reasoning: None,
                 },
             ]),
         });
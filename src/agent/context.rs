@@ -0,0 +1,446 @@
+//! Token-budgeted context management.
+//!
+//! Requests are budgeted against the model's context window: a share is
+//! reserved for the reply, tool schemas take what they need, and the system
+//! prompt plus conversation history must fit in the rest. Token counts are
+//! estimated (about four characters per token, a flat cost per media part)
+//! rather than tokenized per model, so budgets leave headroom.
+
+use crate::providers::catalog;
+use crate::providers::{ChatMessage, ContentPart};
+use crate::tools::ToolSpec;
+
+/// Context window assumed for models missing from [`MODEL_CONTEXT_WINDOWS`].
+pub const DEFAULT_CONTEXT_WINDOW_TOKENS: usize = 32_000;
+
+/// Rough characters-per-token ratio used for estimates.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Per-message framing cost (role, separators).
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Flat estimate for an image or audio part.
+const MEDIA_PART_TOKENS: usize = 1_000;
+
+/// Upper bound on the share of the window reserved for the reply.
+const MAX_RESERVED_OUTPUT_TOKENS: usize = 8_192;
+
+/// History budget floor, so tiny windows still leave room for a turn.
+const MIN_HISTORY_TOKENS: usize = 1_024;
+
+/// Messages are never shortened below this many tokens.
+const MIN_MESSAGE_TOKENS: usize = 256;
+
+/// Headroom for the truncation notice and JSON wrapper added back when a
+/// message is shortened.
+const SHORTEN_OVERHEAD_TOKENS: usize = 64;
+
+/// Compaction starts once history uses this percentage of its budget.
+const COMPACTION_TRIGGER_PERCENT: usize = 80;
+
+/// Recent messages kept verbatim by compaction use at most this percentage.
+const COMPACTION_KEEP_PERCENT: usize = 50;
+
+/// Recalled memory may use at most this percentage of the history budget.
+const MEMORY_CONTEXT_PERCENT: usize = 10;
+
+/// Known context windows by model-name fragment. The first fragment found in
+/// the lowercased model name wins, so more specific entries come first.
+const MODEL_CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4.1", 1_047_576),
+    ("gpt-5", 400_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("claude", 200_000),
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini", 1_048_576),
+    ("deepseek", 128_000),
+    ("llama3.1", 128_000),
+    ("llama-3.1", 128_000),
+    ("llama3.2", 128_000),
+    ("llama-3.2", 128_000),
+    ("llama3.3", 128_000),
+    ("llama-3.3", 128_000),
+    ("llama3", 8_192),
+    ("llama-3", 8_192),
+    ("mistral-large", 128_000),
+    ("mixtral", 32_768),
+    ("qwen", 32_768),
+    ("glm", 128_000),
+    ("grok", 131_072),
+    ("kimi", 131_072),
+];
+
+/// Context window for `model`: the model catalog's value when known, then
+/// [`MODEL_CONTEXT_WINDOWS`], then [`DEFAULT_CONTEXT_WINDOW_TOKENS`].
+/// Provider prefixes such as `anthropic/` are ignored.
+pub fn context_window_for_model(model: &str) -> usize {
+    if let Some(tokens) = catalog::model_info("", model).and_then(|info| info.context_window) {
+        return tokens;
+    }
+    let name = model
+        .rsplit('/')
+        .next()
+        .unwrap_or(model)
+        .to_ascii_lowercase();
+    MODEL_CONTEXT_WINDOWS
+        .iter()
+        .find(|(fragment, _)| name.contains(fragment))
+        .map_or(DEFAULT_CONTEXT_WINDOW_TOKENS, |(_, tokens)| *tokens)
+}
+
+pub fn estimate_text_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+pub fn estimate_message_tokens(message: &ChatMessage) -> usize {
+    let body = if message.parts.is_empty() && !message.content.contains("[IMAGE:") {
+        estimate_text_tokens(&message.content)
+    } else {
+        message
+            .content_parts()
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text { text } => estimate_text_tokens(text),
+                ContentPart::Image { .. } | ContentPart::Audio { .. } => MEDIA_PART_TOKENS,
+                ContentPart::File { path, .. } => estimate_text_tokens(path),
+            })
+            .sum()
+    };
+    body + MESSAGE_OVERHEAD_TOKENS
+}
+
+pub fn estimate_history_tokens(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(estimate_message_tokens).sum()
+}
+
+pub fn estimate_tool_tokens(tools: &[ToolSpec]) -> usize {
+    tools
+        .iter()
+        .map(|tool| {
+            estimate_text_tokens(&tool.name)
+                + estimate_text_tokens(&tool.description)
+                + estimate_text_tokens(&tool.parameters.to_string())
+        })
+        .sum()
+}
+
+/// How a model's context window is split for one request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    /// Model context window, in tokens.
+    pub context_window: usize,
+    /// Tokens kept free for the model's reply.
+    pub reserved_output: usize,
+    /// Tokens taken by tool schemas sent with the request.
+    pub tool_schemas: usize,
+}
+
+impl ContextBudget {
+    /// Budget for `model`; `window_override` (from `[agent]
+    /// context_window_tokens`) replaces the built-in table when set.
+    pub fn for_model(model: &str, window_override: Option<usize>) -> Self {
+        let context_window = window_override
+            .filter(|tokens| *tokens > 0)
+            .unwrap_or_else(|| context_window_for_model(model));
+        let max_output = catalog::model_info("", model)
+            .and_then(|info| info.max_output_tokens)
+            .unwrap_or(usize::MAX);
+        Self {
+            context_window,
+            reserved_output: (context_window / 8)
+                .min(MAX_RESERVED_OUTPUT_TOKENS)
+                .min(max_output),
+            tool_schemas: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn with_tools(mut self, tools: &[ToolSpec]) -> Self {
+        self.tool_schemas = estimate_tool_tokens(tools);
+        self
+    }
+
+    /// Tokens available for the system prompt and conversation history.
+    pub fn history_limit(&self) -> usize {
+        self.context_window
+            .saturating_sub(self.reserved_output)
+            .saturating_sub(self.tool_schemas)
+            .max(MIN_HISTORY_TOKENS)
+    }
+
+    /// History size at which summarizing older turns should start.
+    pub fn compaction_threshold(&self) -> usize {
+        self.history_limit() / 100 * COMPACTION_TRIGGER_PERCENT
+    }
+
+    /// Tokens that recent messages kept verbatim by compaction may use.
+    pub fn compaction_keep_tokens(&self) -> usize {
+        self.history_limit() / 100 * COMPACTION_KEEP_PERCENT
+    }
+
+    /// Character cap for recalled memory injected into a user message.
+    pub fn memory_context_chars(&self) -> usize {
+        self.history_limit() / 100 * MEMORY_CONTEXT_PERCENT * CHARS_PER_TOKEN
+    }
+}
+
+fn first_non_system(history: &[ChatMessage]) -> usize {
+    usize::from(history.first().is_some_and(|m| m.role == "system"))
+}
+
+/// Index of the latest user message, which starts the turn in progress.
+fn last_user_index(history: &[ChatMessage]) -> Option<usize> {
+    history.iter().rposition(|m| m.role == "user")
+}
+
+/// Align a cut point in `history[start..]` to the start of a turn (a user
+/// message) so tool calls and their results are never split. Moves forward
+/// first, then back; returns `start` when there is no earlier turn to cut.
+pub fn align_to_turn_start(history: &[ChatMessage], start: usize, cut: usize) -> usize {
+    if history.get(cut).is_some_and(|m| m.role == "user") {
+        return cut;
+    }
+    let last_user = last_user_index(history).unwrap_or(start);
+    if let Some(offset) = history
+        .get(cut..=last_user)
+        .and_then(|rest| rest.iter().position(|m| m.role == "user"))
+    {
+        return cut + offset;
+    }
+    history[start..cut.min(history.len())]
+        .iter()
+        .rposition(|m| m.role == "user")
+        .map_or(start, |offset| start + offset)
+}
+
+/// Number of trailing messages that fit in `tokens`.
+pub fn recent_messages_within(messages: &[ChatMessage], tokens: usize) -> usize {
+    let mut used = 0;
+    messages
+        .iter()
+        .rev()
+        .take_while(|message| {
+            used += estimate_message_tokens(message);
+            used <= tokens
+        })
+        .count()
+}
+
+/// Make `history` fit in `limit` tokens: drop the oldest turns, then shorten
+/// the largest remaining messages. The system prompt and the latest user
+/// message are always kept. Returns whether `history` changed.
+pub fn fit_history(history: &mut Vec<ChatMessage>, limit: usize) -> bool {
+    let start = first_non_system(history);
+    let mut changed = false;
+
+    while estimate_history_tokens(history) > limit {
+        let Some(last_user) = last_user_index(history).filter(|idx| *idx > start) else {
+            break;
+        };
+        let end = history[start + 1..=last_user]
+            .iter()
+            .position(|m| m.role == "user")
+            .map_or(last_user, |offset| start + 1 + offset);
+        history.drain(start..end);
+        changed = true;
+    }
+
+    loop {
+        let total = estimate_history_tokens(history);
+        if total <= limit {
+            break;
+        }
+        let Some((index, tokens)) = history
+            .iter()
+            .enumerate()
+            .skip(start)
+            .filter(|(_, message)| message.parts.is_empty())
+            .map(|(index, message)| (index, estimate_message_tokens(message)))
+            .filter(|(_, tokens)| *tokens > MIN_MESSAGE_TOKENS)
+            .max_by_key(|(_, tokens)| *tokens)
+        else {
+            break;
+        };
+        let keep = tokens
+            .saturating_sub(total - limit + SHORTEN_OVERHEAD_TOKENS)
+            .max(MIN_MESSAGE_TOKENS);
+        if !shorten_message(&mut history[index], keep) {
+            break;
+        }
+        changed = true;
+    }
+
+    changed
+}
+
+/// Shorten a message to about `tokens`. Native tool results (JSON with a
+/// `content` field) are shortened inside the JSON so they still parse;
+/// native assistant tool-call payloads are left alone.
+fn shorten_message(message: &mut ChatMessage, tokens: usize) -> bool {
+    if let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&message.content) {
+        if value.get("tool_calls").is_some() {
+            return false;
+        }
+        if let Some(inner) = value.get("content").and_then(serde_json::Value::as_str) {
+            let shortened = truncate_to_tokens(inner, tokens);
+            if shortened.len() >= inner.len() {
+                return false;
+            }
+            value["content"] = serde_json::Value::String(shortened);
+            message.content = value.to_string();
+            return true;
+        }
+    }
+
+    let shortened = truncate_to_tokens(&message.content, tokens);
+    if shortened.len() >= message.content.len() {
+        return false;
+    }
+    message.content = shortened;
+    true
+}
+
+fn truncate_to_tokens(text: &str, tokens: usize) -> String {
+    let max_chars = tokens * CHARS_PER_TOKEN;
+    let total_chars = text.chars().count();
+    if total_chars <= max_chars {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(max_chars).collect();
+    format!(
+        "{head}\n[... {} characters truncated to fit the context window]",
+        total_chars - max_chars
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(i: usize, size: usize) -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::user(format!("question {i} {}", "q".repeat(size))),
+            ChatMessage::assistant(format!("answer {i} {}", "a".repeat(size))),
+        ]
+    }
+
+    #[test]
+    fn catalog_limits_override_fragment_table() {
+        // The fragment table says 200k for `o1`; the catalog knows better.
+        assert_eq!(context_window_for_model("o1-preview"), 128_000);
+
+        let budget = ContextBudget::for_model("claude-3-haiku-20240307", None);
+        assert_eq!(budget.context_window, 200_000);
+        assert_eq!(budget.reserved_output, 4_096);
+    }
+
+    #[test]
+    fn context_window_lookup_matches_known_models_and_defaults() {
+        assert_eq!(
+            context_window_for_model("anthropic/claude-sonnet-4"),
+            200_000
+        );
+        assert_eq!(context_window_for_model("gpt-4o-mini"), 128_000);
+        assert_eq!(context_window_for_model("llama3:8b"), 8_192);
+        assert_eq!(context_window_for_model("llama3.1:70b"), 128_000);
+        assert_eq!(
+            context_window_for_model("my-custom-model"),
+            DEFAULT_CONTEXT_WINDOW_TOKENS
+        );
+    }
+
+    #[test]
+    fn budget_reserves_output_and_tool_schemas() {
+        let tools = vec![ToolSpec {
+            name: "shell".into(),
+            description: "d".repeat(400),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let budget = ContextBudget::for_model("llama3", None).with_tools(&tools);
+        assert_eq!(budget.context_window, 8_192);
+        assert_eq!(budget.reserved_output, 1_024);
+        assert!(budget.tool_schemas >= 100);
+        assert_eq!(budget.history_limit(), 8_192 - 1_024 - budget.tool_schemas);
+
+        let overridden = ContextBudget::for_model("llama3", Some(64_000));
+        assert_eq!(overridden.context_window, 64_000);
+        assert_eq!(overridden.reserved_output, 8_000);
+    }
+
+    #[test]
+    fn image_markers_cost_a_flat_amount() {
+        let marker = ChatMessage::user(format!(
+            "look [IMAGE:data:image/png;base64,{}]",
+            "A".repeat(100_000)
+        ));
+        assert!(estimate_message_tokens(&marker) < MEDIA_PART_TOKENS + 16);
+    }
+
+    #[test]
+    fn fit_history_drops_oldest_turns_and_keeps_system_and_latest_turn() {
+        let mut history = vec![ChatMessage::system("system prompt")];
+        for i in 0..10 {
+            history.extend(turn(i, 400));
+        }
+        history.push(ChatMessage::user("latest question"));
+
+        assert!(fit_history(&mut history, 600));
+        assert!(estimate_history_tokens(&history) <= 600);
+        assert_eq!(history[0].role, "system");
+        assert_eq!(history[1].role, "user");
+        assert_eq!(history.last().unwrap().content, "latest question");
+    }
+
+    #[test]
+    fn fit_history_shortens_oversized_tool_results_inside_json() {
+        let mut history = vec![
+            ChatMessage::system("system prompt"),
+            ChatMessage::user("read the log"),
+            ChatMessage::assistant(
+                r#"{"content":null,"tool_calls":[{"id":"c1","name":"file_read","arguments":"{}"}]}"#,
+            ),
+            ChatMessage::tool(
+                serde_json::json!({"tool_call_id": "c1", "content": "x".repeat(40_000)})
+                    .to_string(),
+            ),
+        ];
+
+        assert!(fit_history(&mut history, 2_000));
+        assert!(estimate_history_tokens(&history) <= 2_000);
+        let tool: serde_json::Value = serde_json::from_str(&history[3].content).unwrap();
+        assert_eq!(tool["tool_call_id"], "c1");
+        assert!(tool["content"]
+            .as_str()
+            .unwrap()
+            .contains("truncated to fit the context window"));
+    }
+
+    #[test]
+    fn fit_history_is_noop_within_budget() {
+        let mut history = vec![ChatMessage::system("s"), ChatMessage::user("hi")];
+        assert!(!fit_history(&mut history, 1_000));
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn align_to_turn_start_never_splits_tool_exchanges() {
+        let history = vec![
+            ChatMessage::system("s"),
+            ChatMessage::user("one"),
+            ChatMessage::assistant("calls tool"),
+            ChatMessage::tool("result"),
+            ChatMessage::user("two"),
+            ChatMessage::assistant("done"),
+        ];
+        assert_eq!(align_to_turn_start(&history, 1, 3), 4);
+        assert_eq!(align_to_turn_start(&history, 1, 4), 4);
+        assert_eq!(align_to_turn_start(&history, 1, 5), 4);
+        assert_eq!(align_to_turn_start(&history, 1, 2), 4);
+    }
+}
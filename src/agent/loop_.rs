@@ -1,16 +1,21 @@
+use crate::agent::context::{self, ContextBudget};
 use crate::approval::{ApprovalManager, ApprovalRequest, ApprovalResponse};
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::multimodal;
 use crate::observability::{self, Observer, ObserverEvent};
+use crate::providers::structured::{self, OutputSchema};
+use crate::providers::traits::{StreamAccumulator, StreamEvent};
 use crate::providers::{
-    self, ChatMessage, ChatRequest, Provider, ProviderCapabilityError, ToolCall,
+    self, ChatMessage, ChatRequest, ChatResponse, Provider, ProviderCapabilityError, Reasoning,
+    ToolCall,
 };
 use crate::runtime;
 use crate::security::SecurityPolicy;
 use crate::tools::{self, Tool};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
+use futures_util::StreamExt;
 use regex::{Regex, RegexSet};
 use std::fmt::Write;
 use std::io::Write as _;
@@ -98,6 +103,10 @@ const COMPACTION_MAX_SOURCE_CHARS: usize = 12_000;
 /// Max characters retained in stored compaction summary.
 const COMPACTION_MAX_SUMMARY_CHARS: usize = 2_000;
 
+/// Compact-and-resend rounds allowed per turn before a context window
+/// overflow is returned to the caller.
+const MAX_OVERFLOW_COMPACTIONS: usize = 3;
+
 /// Convert a tool registry to OpenAI function-calling format for native tool support.
 fn tools_to_openai_format(tools_registry: &[Box<dyn Tool>]) -> Vec<serde_json::Value> {
     tools_registry
@@ -163,11 +172,15 @@ fn apply_compaction_summary(
     history.splice(start..compact_end, std::iter::once(summary_msg));
 }
 
+/// Summarize older turns once history exceeds `max_history` messages or
+/// nears the token budget. Recent messages are kept verbatim, and the cut
+/// always lands on a turn boundary so tool calls stay with their results.
 async fn auto_compact_history(
     history: &mut Vec<ChatMessage>,
     provider: &dyn Provider,
     model: &str,
     max_history: usize,
+    budget: &ContextBudget,
 ) -> Result<bool> {
     let has_system = history.first().map_or(false, |m| m.role == "system");
     let non_system_count = if has_system {
@@ -176,18 +189,26 @@ async fn auto_compact_history(
         history.len()
     };
 
-    if non_system_count <= max_history {
+    let over_count = non_system_count > max_history;
+    let over_tokens = context::estimate_history_tokens(history) > budget.compaction_threshold();
+    if !over_count && !over_tokens {
         return Ok(false);
     }
 
     let start = if has_system { 1 } else { 0 };
-    let keep_recent = COMPACTION_KEEP_RECENT_MESSAGES.min(non_system_count);
-    let compact_count = non_system_count.saturating_sub(keep_recent);
-    if compact_count == 0 {
+    let keep_recent =
+        COMPACTION_KEEP_RECENT_MESSAGES
+            .min(non_system_count)
+            .min(context::recent_messages_within(
+                &history[start..],
+                budget.compaction_keep_tokens(),
+            ));
+    let compact_end =
+        context::align_to_turn_start(history, start, history.len().saturating_sub(keep_recent));
+    if compact_end <= start {
         return Ok(false);
     }
 
-    let compact_end = start + compact_count;
     let to_compact: Vec<ChatMessage> = history[start..compact_end].to_vec();
     let transcript = build_compaction_transcript(&to_compact);
 
@@ -212,6 +233,73 @@ async fn auto_compact_history(
     Ok(true)
 }
 
+/// Shrink `history` after the provider rejected it for exceeding the context
+/// window. Older turns are summarized first; once nothing is left to
+/// summarize, history is cut to half its estimated size. Returns whether
+/// anything changed.
+async fn compact_after_overflow(
+    history: &mut Vec<ChatMessage>,
+    provider: &dyn Provider,
+    model: &str,
+    budget: &ContextBudget,
+) -> bool {
+    // A zero message limit forces summarization regardless of the estimate,
+    // which the provider has just shown to be too optimistic.
+    match auto_compact_history(history, provider, model, 0, budget).await {
+        Ok(true) => return true,
+        Ok(false) => {}
+        Err(e) => tracing::warn!(model, "History compaction failed: {e}"),
+    }
+    let target = context::estimate_history_tokens(history) / 2;
+    context::fit_history(history, target)
+}
+
+/// Handle `/save [name]` and `/fork <name>` in interactive mode. Saving
+/// under a new name, or forking, switches the active session to that name;
+/// the previous session keeps what it had.
+fn save_cli_session(
+    config: &Config,
+    session: &mut Option<crate::sessions::CliSession>,
+    name: &str,
+    history: &[ChatMessage],
+    fork: bool,
+) {
+    if fork && name.is_empty() {
+        println!("Usage: /fork <name>\n");
+        return;
+    }
+    let switched = match (session.as_mut(), name) {
+        (Some(active), "") => Ok(active),
+        (Some(active), name) => active.rename(name).map(|()| active),
+        (None, "") => {
+            println!("Usage: /save <name> (no session is active yet)\n");
+            return;
+        }
+        (None, name) => {
+            crate::sessions::CliSession::create(config, name).map(|created| session.insert(created))
+        }
+    };
+    match switched.and_then(|active| active.save(history).map(|()| active.name().to_string())) {
+        Ok(name) if fork => println!("Forked conversation into session '{name}'.\n"),
+        Ok(name) => println!("Saved session '{name}'.\n"),
+        Err(e) => eprintln!("\nError saving session: {e}\n"),
+    }
+}
+
+/// Handle `/undo` in interactive mode: restore the files changed by the
+/// most recent CLI turn that has not been undone yet.
+fn undo_cli_checkpoint(config: &Config) {
+    if !config.checkpoints.enabled {
+        println!("Checkpoints are disabled ([checkpoints] enabled = false).\n");
+        return;
+    }
+    match crate::checkpoint::open_store(config).undo("cli") {
+        Ok(Some(report)) => println!("{}\n", report.summary()),
+        Ok(None) => println!("Nothing to undo.\n"),
+        Err(e) => eprintln!("\nError restoring checkpoint: {e}\n"),
+    }
+}
+
 /// Build context preamble by searching memory for relevant entries.
 /// Entries with a hybrid score below `min_relevance_score` are dropped to
 /// prevent unrelated memories from bleeding into the conversation.
@@ -1202,7 +1290,11 @@ fn parse_structured_tool_calls(tool_calls: &[ToolCall]) -> Vec<ParsedToolCall> {
 /// Build assistant history entry in JSON format for native tool-call APIs.
 /// `convert_messages` in the OpenRouter provider parses this JSON to reconstruct
 /// the proper `NativeMessage` with structured `tool_calls`.
-fn build_native_assistant_history(text: &str, tool_calls: &[ToolCall]) -> String {
+fn build_native_assistant_history(
+    text: &str,
+    tool_calls: &[ToolCall],
+    reasoning: Option<&Reasoning>,
+) -> String {
     let calls_json: Vec<serde_json::Value> = tool_calls
         .iter()
         .map(|tc| {
@@ -1220,11 +1312,15 @@ fn build_native_assistant_history(text: &str, tool_calls: &[ToolCall]) -> String
         serde_json::Value::String(text.trim().to_string())
     };
 
-    serde_json::json!({
+    let mut history = serde_json::json!({
         "content": content,
         "tool_calls": calls_json,
-    })
-    .to_string()
+    });
+    // Kept so providers that must replay signed reasoning with tool use can.
+    if let Some(reasoning) = reasoning {
+        history["reasoning"] = serde_json::json!(reasoning);
+    }
+    history.to_string()
 }
 
 fn build_assistant_history_with_tool_calls(text: &str, tool_calls: &[ToolCall]) -> String {
@@ -1269,42 +1365,6 @@ pub(crate) fn is_tool_loop_cancelled(err: &anyhow::Error) -> bool {
     err.chain().any(|source| source.is::<ToolLoopCancelled>())
 }
 
-/// Execute a single turn of the agent loop: send messages, parse tool calls,
-/// execute tools, and loop until the LLM produces a final text response.
-/// When `silent` is true, suppresses stdout (for channel use).
-#[allow(clippy::too_many_arguments)]
-pub(crate) async fn agent_turn(
-    provider: &dyn Provider,
-    history: &mut Vec<ChatMessage>,
-    tools_registry: &[Box<dyn Tool>],
-    observer: &dyn Observer,
-    provider_name: &str,
-    model: &str,
-    temperature: f64,
-    silent: bool,
-    multimodal_config: &crate::config::MultimodalConfig,
-    max_tool_iterations: usize,
-) -> Result<String> {
-    run_tool_call_loop(
-        provider,
-        history,
-        tools_registry,
-        observer,
-        provider_name,
-        model,
-        temperature,
-        silent,
-        None,
-        "channel",
-        multimodal_config,
-        max_tool_iterations,
-        None,
-        None,
-        None,
-    )
-    .await
-}
-
 async fn execute_one_tool(
     call_name: &str,
     call_arguments: serde_json::Value,
@@ -1415,11 +1475,7 @@ async fn execute_tools_sequential(
                     arguments: call.arguments.clone(),
                 };
 
-                let decision = if channel_name == "cli" {
-                    mgr.prompt_cli(&request)
-                } else {
-                    ApprovalResponse::No
-                };
+                let decision = mgr.request_approval(&request, channel_name).await;
 
                 mgr.record_decision(&call.name, &call.arguments, decision, channel_name);
 
@@ -1456,6 +1512,53 @@ async fn execute_tools_sequential(
 //   • max_iterations is reached (runaway safety), or
 //   • the cancellation token fires (external abort).
 
+/// Live progress relayed to an `on_delta` consumer while a turn runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TurnDelta {
+    /// A fragment of assistant text.
+    Text(String),
+    /// The model is calling the named tool.
+    ToolCall(String),
+    /// A fragment of model reasoning.
+    Reasoning(String),
+}
+
+/// Stream one model response through [`Provider::stream_chat`], relaying
+/// reasoning, tool-call starts (and text, when `relay_text`) to `on_delta`, and
+/// reassemble it. Also returns whether any text was relayed.
+async fn stream_chat_response(
+    provider: &dyn Provider,
+    request: ChatRequest<'_>,
+    model: &str,
+    temperature: f64,
+    on_delta: &tokio::sync::mpsc::Sender<TurnDelta>,
+    relay_text: bool,
+) -> Result<(ChatResponse, bool)> {
+    let mut events = provider.stream_chat(request, model, temperature);
+    let mut accumulator = StreamAccumulator::default();
+    let mut relayed_text = false;
+
+    while let Some(event) = events.next().await {
+        let event = event?;
+        match &event {
+            StreamEvent::TextDelta(text) if relay_text => {
+                relayed_text = true;
+                let _ = on_delta.send(TurnDelta::Text(text.clone())).await;
+            }
+            StreamEvent::ToolCallStart { name, .. } => {
+                let _ = on_delta.send(TurnDelta::ToolCall(name.clone())).await;
+            }
+            StreamEvent::ReasoningDelta(text) => {
+                let _ = on_delta.send(TurnDelta::Reasoning(text.clone())).await;
+            }
+            _ => {}
+        }
+        accumulator.push(&event);
+    }
+
+    Ok((accumulator.into_response(), relayed_text))
+}
+
 /// Execute a single turn of the agent loop: send messages, parse tool calls,
 /// execute tools, and loop until the LLM produces a final text response.
 #[allow(clippy::too_many_arguments)]
@@ -1471,10 +1574,12 @@ pub(crate) async fn run_tool_call_loop(
     approval: Option<&ApprovalManager>,
     channel_name: &str,
     multimodal_config: &crate::config::MultimodalConfig,
+    context_window_tokens: Option<usize>,
     max_tool_iterations: usize,
     cancellation_token: Option<CancellationToken>,
-    on_delta: Option<tokio::sync::mpsc::Sender<String>>,
+    on_delta: Option<tokio::sync::mpsc::Sender<TurnDelta>>,
     hooks: Option<&crate::hooks::HookRunner>,
+    output_schema: Option<&OutputSchema>,
 ) -> Result<String> {
     let max_iterations = if max_tool_iterations == 0 {
         DEFAULT_MAX_TOOL_ITERATIONS
@@ -1484,7 +1589,16 @@ pub(crate) async fn run_tool_call_loop(
 
     let tool_specs: Vec<crate::tools::ToolSpec> =
         tools_registry.iter().map(|tool| tool.spec()).collect();
-    let use_native_tools = provider.supports_native_tools() && !tool_specs.is_empty();
+    // The model catalog narrows provider capabilities for models known to
+    // lack vision or tool calling.
+    let capabilities = providers::catalog::capabilities_for(provider_name, model, provider);
+    let use_native_tools = capabilities.native_tool_calling && !tool_specs.is_empty();
+    // Prompt-guided tool instructions already live in the system prompt.
+    let budget = ContextBudget::for_model(model, context_window_tokens)
+        .with_tools(if use_native_tools { &tool_specs } else { &[] });
+
+    let mut repairs_left = structured::MAX_REPAIR_ATTEMPTS;
+    let mut overflow_compactions_left = MAX_OVERFLOW_COMPACTIONS;
 
     for _iteration in 0..max_iterations {
         if cancellation_token
@@ -1494,13 +1608,28 @@ pub(crate) async fn run_tool_call_loop(
             return Err(ToolLoopCancelled.into());
         }
 
+        // Keep the request inside the model's context window: summarize older
+        // turns as history nears the budget, then drop or shorten what is left.
+        if let Ok(true) = auto_compact_history(history, provider, model, usize::MAX, &budget).await
+        {
+            tracing::debug!(model, "Compacted history to fit the context budget");
+        }
+        if context::fit_history(history, budget.history_limit()) {
+            tracing::debug!(model, "Trimmed history to fit the context budget");
+        }
+
         let image_marker_count = multimodal::count_image_markers(history);
-        if image_marker_count > 0 && !provider.supports_vision() {
+        if image_marker_count > 0 && !capabilities.vision {
+            let subject = if provider.supports_vision() {
+                format!("model '{model}'")
+            } else {
+                "this provider".to_string()
+            };
             return Err(ProviderCapabilityError {
                 provider: provider_name.to_string(),
                 capability: "vision".to_string(),
                 message: format!(
-                    "received {image_marker_count} image marker(s), but this provider does not support vision input"
+                    "received {image_marker_count} image marker(s), but {subject} does not support vision input"
                 ),
             }
             .into());
@@ -1530,15 +1659,28 @@ pub(crate) async fn run_tool_call_loop(
             None
         };
 
-        let chat_future = provider.chat(
-            ChatRequest {
-                messages: &prepared_messages.messages,
-                tools: request_tools,
-            },
-            model,
-            temperature,
-        );
+        let request = ChatRequest {
+            messages: &prepared_messages.messages,
+            tools: request_tools,
+            output_schema,
+        };
+        // With a live consumer, stream the response. Text is relayed as it
+        // arrives unless prompt-guided tool markup could be mixed into it.
+        let chat_future = async {
+            match on_delta.as_ref() {
+                Some(tx) => {
+                    let relay_text = use_native_tools || tool_specs.is_empty();
+                    stream_chat_response(provider, request, model, temperature, tx, relay_text)
+                        .await
+                }
+                None => provider
+                    .chat(request, model, temperature)
+                    .await
+                    .map(|resp| (resp, false)),
+            }
+        };
 
+        let text_relayed;
         let chat_result = if let Some(token) = cancellation_token.as_ref() {
             tokio::select! {
                 () = token.cancelled() => return Err(ToolLoopCancelled.into()),
@@ -1550,7 +1692,8 @@ pub(crate) async fn run_tool_call_loop(
 
         let (response_text, parsed_text, tool_calls, assistant_history_content, native_tool_calls) =
             match chat_result {
-                Ok(resp) => {
+                Ok((resp, relayed)) => {
+                    text_relayed = relayed;
                     let (resp_input_tokens, resp_output_tokens) = resp
                         .usage
                         .as_ref()
@@ -1588,7 +1731,11 @@ pub(crate) async fn run_tool_call_loop(
                     let assistant_history_content = if resp.tool_calls.is_empty() {
                         response_text.clone()
                     } else {
-                        build_native_assistant_history(&response_text, &resp.tool_calls)
+                        build_native_assistant_history(
+                            &response_text,
+                            &resp.tool_calls,
+                            resp.reasoning.as_ref(),
+                        )
                     };
 
                     let native_calls = resp.tool_calls;
@@ -1610,6 +1757,20 @@ pub(crate) async fn run_tool_call_loop(
                         input_tokens: None,
                         output_tokens: None,
                     });
+                    // The request did not fit the model's context window:
+                    // compact the history in place and send it again.
+                    if overflow_compactions_left > 0
+                        && providers::reliable::is_context_window_exceeded(&e)
+                        && compact_after_overflow(history, provider, model, &budget).await
+                    {
+                        overflow_compactions_left -= 1;
+                        tracing::warn!(
+                            model,
+                            messages = history.len(),
+                            "Context window exceeded; retrying with compacted history"
+                        );
+                        continue;
+                    }
                     return Err(e);
                 }
             };
@@ -1621,10 +1782,31 @@ pub(crate) async fn run_tool_call_loop(
         };
 
         if tool_calls.is_empty() {
-            // No tool calls — this is the final response.
-            // If a streaming sender is provided, relay the text in small chunks
-            // so the channel can progressively update the draft message.
-            if let Some(ref tx) = on_delta {
+            // No tool calls — this is the final response. With an output
+            // schema it must validate; otherwise ask for a corrected answer
+            // while repair attempts remain.
+            let display_text = match output_schema {
+                None => display_text,
+                Some(schema) => match schema.parse_response(&display_text) {
+                    Ok(value) => serde_json::to_string_pretty(&value)?,
+                    Err(error) if repairs_left > 0 => {
+                        repairs_left -= 1;
+                        tracing::debug!(%error, "Final answer failed schema validation; retrying");
+                        history.push(ChatMessage::assistant(response_text));
+                        history.push(ChatMessage::user(schema.repair_prompt(&error)));
+                        continue;
+                    }
+                    Err(error) => anyhow::bail!(
+                        "Answer does not match output schema '{}': {error}",
+                        schema.name
+                    ),
+                },
+            };
+
+            // If a streaming sender is provided and the text was not streamed
+            // live, relay it in small chunks so the channel can progressively
+            // update the draft message.
+            if let Some(tx) = on_delta.as_ref().filter(|_| !text_relayed) {
                 // Split on whitespace boundaries, accumulating chunks of at least
                 // STREAM_CHUNK_MIN_CHARS characters for progressive draft updates.
                 let mut chunk = String::new();
@@ -1637,19 +1819,30 @@ pub(crate) async fn run_tool_call_loop(
                     }
                     chunk.push_str(word);
                     if chunk.len() >= STREAM_CHUNK_MIN_CHARS
-                        && tx.send(std::mem::take(&mut chunk)).await.is_err()
+                        && tx
+                            .send(TurnDelta::Text(std::mem::take(&mut chunk)))
+                            .await
+                            .is_err()
                     {
                         break; // receiver dropped
                     }
                 }
                 if !chunk.is_empty() {
-                    let _ = tx.send(chunk).await;
+                    let _ = tx.send(TurnDelta::Text(chunk)).await;
                 }
             }
             history.push(ChatMessage::assistant(response_text.clone()));
             return Ok(display_text);
         }
 
+        // Native calls were announced while streaming; prompt-guided ones are
+        // only known once parsed.
+        if let Some(tx) = on_delta.as_ref().filter(|_| native_tool_calls.is_empty()) {
+            for call in &tool_calls {
+                let _ = tx.send(TurnDelta::ToolCall(call.name.clone())).await;
+            }
+        }
+
         // Print any text the LLM produced alongside tool calls (unless silent)
         if !silent && !display_text.is_empty() {
             print!("{display_text}");
@@ -1695,12 +1888,8 @@ pub(crate) async fn run_tool_call_loop(
                         arguments: tool_args.clone(),
                     };
 
-                    // Only prompt interactively on CLI; auto-approve on other channels.
-                    let decision = if channel_name == "cli" {
-                        mgr.prompt_cli(&request)
-                    } else {
-                        ApprovalResponse::Yes
-                    };
+                    // CLI prompts on stdin; chat channels ask via their prompter.
+                    let decision = mgr.request_approval(&request, channel_name).await;
 
                     mgr.record_decision(&tool_name, &tool_args, decision, channel_name);
 
@@ -1827,7 +2016,7 @@ pub(crate) fn build_tool_instructions(tools_registry: &[Box<dyn Tool>]) -> Strin
 // interactive REPL mode. The interactive loop manages history compaction
 // and hard trimming to keep the context window bounded.
 
-#[allow(clippy::too_many_lines)]
+#[allow(clippy::too_many_lines, clippy::too_many_arguments)]
 pub async fn run(
     config: Config,
     message: Option<String>,
@@ -1835,6 +2024,8 @@ pub async fn run(
     model_override: Option<String>,
     temperature: f64,
     peripheral_overrides: Vec<String>,
+    mut session: Option<crate::sessions::CliSession>,
+    output_schema: Option<OutputSchema>,
 ) -> Result<String> {
     // ── Wire up agnostic subsystems ──────────────────────────────
     let base_observer = observability::create_observer(&config.observability);
@@ -1893,6 +2084,7 @@ pub async fn run(
         tracing::info!(count = peripheral_tools.len(), "Peripheral tools added");
         tools_registry.extend(peripheral_tools);
     }
+    tools_registry.extend(crate::mcp::create_mcp_tools(&config, &security).await);
 
     // ── Resolve provider ─────────────────────────────────────────
     let provider_name = provider_override
@@ -1910,6 +2102,7 @@ pub async fn run(
         zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
         secrets_encrypt: config.secrets.encrypt,
         reasoning_enabled: config.runtime.reasoning_enabled,
+        reasoning_budget_tokens: config.runtime.reasoning_budget_tokens,
     };
 
     let provider: Box<dyn Provider> = providers::create_routed_provider_with_options(
@@ -1920,7 +2113,9 @@ pub async fn run(
         &config.model_routes,
         model_name,
         &provider_runtime_options,
+        providers::router::RoutingOptions::from_config(&config).with_observer(observer.clone()),
     )?;
+    let provider = crate::cost::wrap_provider(&config, provider_name, provider);
 
     observer.record_event(&ObserverEvent::AgentStart {
         provider: provider_name.to_string(),
@@ -1958,10 +2153,18 @@ pub async fn run(
             "file_read",
             "Read file contents. Use when: inspecting project files, configs, logs. Don't use when: a targeted search is enough.",
         ),
+        (
+            "content_search",
+            "Search file contents by regex or literal text. Use when: locating definitions, call sites, config keys or log lines across the workspace. Don't use when: you already know the exact file to read.",
+        ),
         (
             "file_write",
             "Write file contents. Use when: applying focused edits, scaffolding files, updating docs/code. Don't use when: side effects are unclear or file ownership is uncertain.",
         ),
+        (
+            "apply_patch",
+            "Apply a unified diff or multi-file edit list atomically. Use when: a change spans several hunks or files, or creates, deletes or renames files. Don't use when: a single small edit is enough.",
+        ),
         (
             "memory_store",
             "Save to memory. Use when: preserving durable preferences, decisions, key context. Don't use when: information is transient/noisy/sensitive without need.",
@@ -2007,6 +2210,18 @@ pub async fn run(
             "Open approved HTTPS URLs in Brave Browser (allowlist-only, no scraping)",
         ));
     }
+    if config.web_fetch.enabled {
+        tool_descs.push((
+            "web_fetch",
+            "Fetch a web page and read its main content as Markdown with links preserved (HTML, PDF, text). Long pages are paged with offset. Use when: reading articles or docs from allowlisted domains.",
+        ));
+    }
+    if config.process.enabled {
+        tool_descs.push((
+            "process",
+            "Run long-running commands (builds, test suites, dev servers) in the background and poll their output. Use when: a command may outlive the shell timeout or must keep running. Don't use when: a quick shell command is enough.",
+        ));
+    }
     if config.composio.enabled {
         tool_descs.push((
             "composio",
@@ -2058,7 +2273,9 @@ pub async fn run(
     } else {
         None
     };
-    let native_tools = provider.supports_native_tools();
+    let native_tools =
+        providers::catalog::capabilities_for(provider_name, model_name, provider.as_ref())
+            .native_tool_calling;
     let mut system_prompt = crate::channels::build_system_prompt_with_mode(
         &config.workspace_dir,
         model_name,
@@ -2077,6 +2294,7 @@ pub async fn run(
 
     // ── Approval manager (supervised mode) ───────────────────────
     let approval_manager = ApprovalManager::from_config(&config.autonomy);
+    let context_budget = ContextBudget::for_model(model_name, config.agent.context_window_tokens);
 
     // ── Execute ──────────────────────────────────────────────────
     let start = Instant::now();
@@ -2093,8 +2311,10 @@ pub async fn run(
         }
 
         // Inject memory + hardware RAG context into user message
-        let mem_context =
-            build_context(mem.as_ref(), &msg, config.memory.min_relevance_score).await;
+        let mem_context = truncate_with_ellipsis(
+            &build_context(mem.as_ref(), &msg, config.memory.min_relevance_score).await,
+            context_budget.memory_context_chars(),
+        );
         let rag_limit = if config.agent.compact_context { 2 } else { 5 };
         let hw_context = hardware_rag
             .as_ref()
@@ -2107,32 +2327,51 @@ pub async fn run(
             format!("{context}{msg}")
         };
 
-        let mut history = vec![
-            ChatMessage::system(&system_prompt),
-            ChatMessage::user(&enriched),
-        ];
+        let mut history = vec![ChatMessage::system(&system_prompt)];
+        if let Some(session) = session.as_ref() {
+            history.extend(session.load()?);
+        }
+        history.push(ChatMessage::user(&enriched));
 
-        let response = run_tool_call_loop(
-            provider.as_ref(),
-            &mut history,
-            &tools_registry,
-            observer.as_ref(),
-            provider_name,
-            model_name,
-            temperature,
-            false,
-            Some(&approval_manager),
+        let response = crate::checkpoint::with_turn(
             "cli",
-            &config.multimodal,
-            config.agent.max_tool_iterations,
-            None,
-            None,
-            None,
+            &msg,
+            run_tool_call_loop(
+                provider.as_ref(),
+                &mut history,
+                &tools_registry,
+                observer.as_ref(),
+                provider_name,
+                model_name,
+                temperature,
+                false,
+                Some(&approval_manager),
+                "cli",
+                &config.multimodal,
+                config.agent.context_window_tokens,
+                config.agent.max_tool_iterations,
+                None,
+                None,
+                None,
+                output_schema.as_ref(),
+            ),
         )
         .await?;
         final_output = response.clone();
         println!("{response}");
         observer.record_event(&ObserverEvent::TurnComplete);
+        if let Some(session) = session.as_ref() {
+            let _ = auto_compact_history(
+                &mut history,
+                provider.as_ref(),
+                model_name,
+                config.agent.max_history_messages,
+                &context_budget,
+            )
+            .await;
+            trim_history(&mut history, config.agent.max_history_messages);
+            session.save(&history)?;
+        }
     } else {
         println!("🦀 ZeroClaw Interactive Mode");
         println!("Type /help for commands.\n");
@@ -2140,6 +2379,19 @@ pub async fn run(
 
         // Persistent conversation history across turns
         let mut history = vec![ChatMessage::system(&system_prompt)];
+        if let Some(session) = session.as_ref() {
+            let saved = session.load()?;
+            if saved.is_empty() {
+                println!("Session '{}' started.\n", session.name());
+            } else {
+                println!(
+                    "Resumed session '{}' ({} messages).\n",
+                    session.name(),
+                    saved.len()
+                );
+            }
+            history.extend(saved);
+        }
 
         loop {
             print!("> ");
@@ -2159,13 +2411,34 @@ pub async fn run(
             if user_input.is_empty() {
                 continue;
             }
+            if let Some(rest) = user_input.strip_prefix("/save") {
+                if rest.is_empty() || rest.starts_with(' ') {
+                    save_cli_session(&config, &mut session, rest.trim(), &history, false);
+                    continue;
+                }
+            }
+            if let Some(rest) = user_input.strip_prefix("/fork") {
+                if rest.is_empty() || rest.starts_with(' ') {
+                    save_cli_session(&config, &mut session, rest.trim(), &history, true);
+                    continue;
+                }
+            }
             match user_input.as_str() {
                 "/quit" | "/exit" => break,
                 "/help" => {
                     println!("Available commands:");
-                    println!("  /help        Show this help message");
-                    println!("  /clear /new  Clear conversation history");
-                    println!("  /quit /exit  Exit interactive mode\n");
+                    println!("  /help         Show this help message");
+                    println!("  /clear /new   Clear conversation history");
+                    println!("  /undo         Restore files changed by the last turn");
+                    println!("  /save [name]  Save this conversation as a named session");
+                    println!(
+                        "  /fork <name>  Copy this conversation to a new session and switch to it"
+                    );
+                    println!("  /quit /exit   Exit interactive mode\n");
+                    continue;
+                }
+                "/undo" => {
+                    undo_cli_checkpoint(&config);
                     continue;
                 }
                 "/clear" | "/new" => {
@@ -2187,6 +2460,11 @@ pub async fn run(
 
                     history.clear();
                     history.push(ChatMessage::system(&system_prompt));
+                    if let Some(session) = session.as_ref() {
+                        if let Err(e) = session.save(&history) {
+                            eprintln!("\nError clearing session '{}': {e}\n", session.name());
+                        }
+                    }
                     // Clear conversation and daily memory
                     let mut cleared = 0;
                     for category in [MemoryCategory::Conversation, MemoryCategory::Daily] {
@@ -2216,8 +2494,10 @@ pub async fn run(
             }
 
             // Inject memory + hardware RAG context into user message
-            let mem_context =
-                build_context(mem.as_ref(), &user_input, config.memory.min_relevance_score).await;
+            let mem_context = truncate_with_ellipsis(
+                &build_context(mem.as_ref(), &user_input, config.memory.min_relevance_score).await,
+                context_budget.memory_context_chars(),
+            );
             let rag_limit = if config.agent.compact_context { 2 } else { 5 };
             let hw_context = hardware_rag
                 .as_ref()
@@ -2232,22 +2512,28 @@ pub async fn run(
 
             history.push(ChatMessage::user(&enriched));
 
-            let response = match run_tool_call_loop(
-                provider.as_ref(),
-                &mut history,
-                &tools_registry,
-                observer.as_ref(),
-                provider_name,
-                model_name,
-                temperature,
-                false,
-                Some(&approval_manager),
+            let response = match crate::checkpoint::with_turn(
                 "cli",
-                &config.multimodal,
-                config.agent.max_tool_iterations,
-                None,
-                None,
-                None,
+                &user_input,
+                run_tool_call_loop(
+                    provider.as_ref(),
+                    &mut history,
+                    &tools_registry,
+                    observer.as_ref(),
+                    provider_name,
+                    model_name,
+                    temperature,
+                    false,
+                    Some(&approval_manager),
+                    "cli",
+                    &config.multimodal,
+                    config.agent.context_window_tokens,
+                    config.agent.max_tool_iterations,
+                    None,
+                    None,
+                    None,
+                    output_schema.as_ref(),
+                ),
             )
             .await
             {
@@ -2274,6 +2560,7 @@ pub async fn run(
                 provider.as_ref(),
                 model_name,
                 config.agent.max_history_messages,
+                &context_budget,
             )
             .await
             {
@@ -2284,6 +2571,12 @@ pub async fn run(
 
             // Hard cap as a safety net.
             trim_history(&mut history, config.agent.max_history_messages);
+
+            if let Some(session) = session.as_ref() {
+                if let Err(e) = session.save(&history) {
+                    eprintln!("\nError saving session '{}': {e}\n", session.name());
+                }
+            }
         }
     }
 
@@ -2300,180 +2593,318 @@ pub async fn run(
 }
 
 /// Process a single message through the full agent (with tools, peripherals, memory).
-/// Used by channels (Telegram, Discord, etc.) to enable hardware and tool use.
+/// Used by the gateway's webhook channels (WhatsApp, Linq, Nextcloud Talk) to
+/// enable hardware and tool use.
 pub async fn process_message(config: Config, message: &str) -> Result<String> {
-    let observer: Arc<dyn Observer> =
-        Arc::from(observability::create_observer(&config.observability));
-    let runtime: Arc<dyn runtime::RuntimeAdapter> =
-        Arc::from(runtime::create_runtime(&config.runtime)?);
-    let security = Arc::new(SecurityPolicy::from_config(
-        &config.autonomy,
-        &config.workspace_dir,
-    ));
-    let mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage(
-        &config.memory,
-        Some(&config.storage.provider.config),
-        &config.workspace_dir,
-        config.api_key.as_deref(),
-    )?);
-
-    let (composio_key, composio_entity_id) = if config.composio.enabled {
-        (
-            config.composio.api_key.as_deref(),
-            Some(config.composio.entity_id.as_str()),
-        )
-    } else {
-        (None, None)
-    };
-    let mut tools_registry = tools::all_tools_with_runtime(
-        Arc::new(config.clone()),
-        &security,
-        runtime,
-        mem.clone(),
-        composio_key,
-        composio_entity_id,
-        &config.browser,
-        &config.http_request,
-        &config.workspace_dir,
-        &config.agents,
-        config.api_key.as_deref(),
-        &config,
-    );
-    let peripheral_tools: Vec<Box<dyn Tool>> =
-        crate::peripherals::create_peripheral_tools(&config.peripherals).await?;
-    tools_registry.extend(peripheral_tools);
+    Box::pin(process_conversation(
+        config,
+        vec![ChatMessage::user(message)],
+        "gateway",
+        None,
+    ))
+    .await
+}
 
-    let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
-    let model_name = config
-        .default_model
-        .clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
-    let provider_runtime_options = providers::ProviderRuntimeOptions {
-        auth_profile_override: None,
-        zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
-        secrets_encrypt: config.secrets.encrypt,
-        reasoning_enabled: config.runtime.reasoning_enabled,
-    };
-    let provider: Box<dyn Provider> = providers::create_routed_provider_with_options(
-        provider_name,
-        config.api_key.as_deref(),
-        config.api_url.as_deref(),
-        &config.reliability,
-        &config.model_routes,
-        &model_name,
-        &provider_runtime_options,
-    )?;
+/// Agent state for running conversations: provider, memory, tools (including
+/// MCP connections), skills and hardware context. Long-lived callers such as
+/// the gateway build it once and reuse it for every request.
+pub struct ConversationRuntime {
+    config: Config,
+    observer: Arc<dyn Observer>,
+    mem: Arc<dyn Memory>,
+    tools_registry: Vec<Box<dyn Tool>>,
+    provider: Box<dyn Provider>,
+    provider_name: String,
+    skills: Vec<crate::skills::Skill>,
+    hardware_rag: Option<crate::rag::HardwareRag>,
+    board_names: Vec<String>,
+    audit: Option<Arc<crate::security::AuditLogger>>,
+}
 
-    let hardware_rag: Option<crate::rag::HardwareRag> = config
-        .peripherals
-        .datasheet_dir
-        .as_ref()
-        .filter(|d| !d.trim().is_empty())
-        .map(|dir| crate::rag::HardwareRag::load(&config.workspace_dir, dir.trim()))
-        .and_then(Result::ok)
-        .filter(|r: &crate::rag::HardwareRag| !r.is_empty());
-    let board_names: Vec<String> = config
-        .peripherals
-        .boards
-        .iter()
-        .map(|b| b.board.clone())
-        .collect();
+impl ConversationRuntime {
+    pub async fn new(config: Config) -> Result<Self> {
+        let observer: Arc<dyn Observer> =
+            Arc::from(observability::create_observer(&config.observability));
+        let runtime: Arc<dyn runtime::RuntimeAdapter> =
+            Arc::from(runtime::create_runtime(&config.runtime)?);
+        let security = Arc::new(SecurityPolicy::from_config(
+            &config.autonomy,
+            &config.workspace_dir,
+        ));
+        let mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage(
+            &config.memory,
+            Some(&config.storage.provider.config),
+            &config.workspace_dir,
+            config.api_key.as_deref(),
+        )?);
+
+        let (composio_key, composio_entity_id) = if config.composio.enabled {
+            (
+                config.composio.api_key.as_deref(),
+                Some(config.composio.entity_id.as_str()),
+            )
+        } else {
+            (None, None)
+        };
+        let mut tools_registry = tools::all_tools_with_runtime(
+            Arc::new(config.clone()),
+            &security,
+            runtime,
+            mem.clone(),
+            composio_key,
+            composio_entity_id,
+            &config.browser,
+            &config.http_request,
+            &config.workspace_dir,
+            &config.agents,
+            config.api_key.as_deref(),
+            &config,
+        );
+        let peripheral_tools: Vec<Box<dyn Tool>> =
+            crate::peripherals::create_peripheral_tools(&config.peripherals).await?;
+        tools_registry.extend(peripheral_tools);
+        tools_registry.extend(crate::mcp::create_mcp_tools(&config, &security).await);
+
+        let provider_name = config
+            .default_provider
+            .clone()
+            .unwrap_or_else(|| "openrouter".into());
+        let model_name = config
+            .default_model
+            .clone()
+            .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+        let provider_runtime_options = providers::ProviderRuntimeOptions {
+            auth_profile_override: None,
+            zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
+            secrets_encrypt: config.secrets.encrypt,
+            reasoning_enabled: config.runtime.reasoning_enabled,
+            reasoning_budget_tokens: config.runtime.reasoning_budget_tokens,
+        };
+        let provider: Box<dyn Provider> = providers::create_routed_provider_with_options(
+            &provider_name,
+            config.api_key.as_deref(),
+            config.api_url.as_deref(),
+            &config.reliability,
+            &config.model_routes,
+            &model_name,
+            &provider_runtime_options,
+            providers::router::RoutingOptions::from_config(&config).with_observer(observer.clone()),
+        )?;
+        let provider = crate::cost::wrap_provider(&config, &provider_name, provider);
+
+        let hardware_rag: Option<crate::rag::HardwareRag> = config
+            .peripherals
+            .datasheet_dir
+            .as_ref()
+            .filter(|d| !d.trim().is_empty())
+            .map(|dir| crate::rag::HardwareRag::load(&config.workspace_dir, dir.trim()))
+            .and_then(Result::ok)
+            .filter(|r: &crate::rag::HardwareRag| !r.is_empty());
+        let board_names: Vec<String> = config
+            .peripherals
+            .boards
+            .iter()
+            .map(|b| b.board.clone())
+            .collect();
+        let skills = crate::skills::load_skills_with_config(&config.workspace_dir, &config);
+        let audit = crate::security::audit::default_logger(&config);
 
-    let skills = crate::skills::load_skills_with_config(&config.workspace_dir, &config);
-    let mut tool_descs: Vec<(&str, &str)> = vec![
-        ("shell", "Execute terminal commands."),
-        ("file_read", "Read file contents."),
-        ("file_write", "Write file contents."),
-        ("memory_store", "Save to memory."),
-        ("memory_recall", "Search memory."),
-        ("memory_forget", "Delete a memory entry."),
-        ("screenshot", "Capture a screenshot."),
-        ("image_info", "Read image metadata."),
-    ];
-    if config.browser.enabled {
-        tool_descs.push(("browser_open", "Open approved URLs in browser."));
-    }
-    if config.composio.enabled {
-        tool_descs.push(("composio", "Execute actions on 1000+ apps via Composio."));
-    }
-    if config.peripherals.enabled && !config.peripherals.boards.is_empty() {
-        tool_descs.push(("gpio_read", "Read GPIO pin value on connected hardware."));
-        tool_descs.push((
-            "gpio_write",
-            "Set GPIO pin high or low on connected hardware.",
-        ));
-        tool_descs.push((
-            "arduino_upload",
-            "Upload Arduino sketch. Use for 'make a heart', custom patterns. You write full .ino code; ZeroClaw uploads it.",
-        ));
-        tool_descs.push((
-            "hardware_memory_map",
-            "Return flash and RAM address ranges. Use when user asks for memory addresses or memory map.",
-        ));
-        tool_descs.push((
-            "hardware_board_info",
-            "Return full board info (chip, architecture, memory map). Use when user asks for board info, what board, connected hardware, or chip info.",
-        ));
-        tool_descs.push((
-            "hardware_memory_read",
-            "Read actual memory/register values from Nucleo. Use when user asks to read registers, read memory, dump lower memory 0-126, or give address and value.",
-        ));
-        tool_descs.push((
-            "hardware_capabilities",
-            "Query connected hardware for reported GPIO pins and LED pin. Use when user asks what pins are available.",
-        ));
-    }
-    let bootstrap_max_chars = if config.agent.compact_context {
-        Some(6000)
-    } else {
-        None
-    };
-    let native_tools = provider.supports_native_tools();
-    let mut system_prompt = crate::channels::build_system_prompt_with_mode(
-        &config.workspace_dir,
-        &model_name,
-        &tool_descs,
-        &skills,
-        Some(&config.identity),
-        bootstrap_max_chars,
-        native_tools,
-        config.skills.prompt_injection_mode,
-    );
-    if !native_tools {
-        system_prompt.push_str(&build_tool_instructions(&tools_registry));
+        Ok(Self {
+            config,
+            observer,
+            mem,
+            tools_registry,
+            provider,
+            provider_name,
+            skills,
+            hardware_rag,
+            board_names,
+            audit,
+        })
     }
 
-    let mem_context = build_context(mem.as_ref(), message, config.memory.min_relevance_score).await;
-    let rag_limit = if config.agent.compact_context { 2 } else { 5 };
-    let hw_context = hardware_rag
-        .as_ref()
-        .map(|r| build_hardware_context(r, message, &board_names, rag_limit))
-        .unwrap_or_default();
-    let context = format!("{mem_context}{hw_context}");
-    let enriched = if context.is_empty() {
-        message.to_string()
-    } else {
-        format!("{context}{message}")
-    };
+    /// Model used when a caller does not pick one.
+    pub fn default_model(&self) -> &str {
+        self.config
+            .default_model
+            .as_deref()
+            .unwrap_or("anthropic/claude-sonnet-4-20250514")
+    }
+
+    /// Run a multi-turn conversation through the full agent (tools, memory,
+    /// security policy) with `model` and return the final answer.
+    ///
+    /// System messages in `messages` are appended to the agent's own system
+    /// prompt; memory and hardware context is attached to the last user
+    /// message. When `on_delta` is set, assistant text and tool calls are
+    /// relayed there as the turn progresses.
+    ///
+    /// Tool calls that need approval under `[autonomy]` are denied: there is
+    /// nobody on `channel_name` to ask.
+    pub async fn process(
+        &self,
+        messages: Vec<ChatMessage>,
+        model_name: &str,
+        temperature: f64,
+        channel_name: &str,
+        on_delta: Option<tokio::sync::mpsc::Sender<TurnDelta>>,
+    ) -> Result<String> {
+        let config = &self.config;
+        let mut tool_descs: Vec<(&str, &str)> = vec![
+            ("shell", "Execute terminal commands."),
+            ("file_read", "Read file contents."),
+            ("content_search", "Search file contents by regex."),
+            ("file_write", "Write file contents."),
+            ("apply_patch", "Apply a multi-file diff atomically."),
+            ("memory_store", "Save to memory."),
+            ("memory_recall", "Search memory."),
+            ("memory_forget", "Delete a memory entry."),
+            ("screenshot", "Capture a screenshot."),
+            ("image_info", "Read image metadata."),
+        ];
+        if config.browser.enabled {
+            tool_descs.push(("browser_open", "Open approved URLs in browser."));
+        }
+        if config.web_fetch.enabled {
+            tool_descs.push(("web_fetch", "Read a web page as Markdown."));
+        }
+        if config.process.enabled {
+            tool_descs.push(("process", "Run and poll background commands."));
+        }
+        if config.composio.enabled {
+            tool_descs.push(("composio", "Execute actions on 1000+ apps via Composio."));
+        }
+        if config.peripherals.enabled && !config.peripherals.boards.is_empty() {
+            tool_descs.push(("gpio_read", "Read GPIO pin value on connected hardware."));
+            tool_descs.push((
+                "gpio_write",
+                "Set GPIO pin high or low on connected hardware.",
+            ));
+            tool_descs.push((
+                "arduino_upload",
+                "Upload Arduino sketch. Use for 'make a heart', custom patterns. You write full .ino code; ZeroClaw uploads it.",
+            ));
+            tool_descs.push((
+                "hardware_memory_map",
+                "Return flash and RAM address ranges. Use when user asks for memory addresses or memory map.",
+            ));
+            tool_descs.push((
+                "hardware_board_info",
+                "Return full board info (chip, architecture, memory map). Use when user asks for board info, what board, connected hardware, or chip info.",
+            ));
+            tool_descs.push((
+                "hardware_memory_read",
+                "Read actual memory/register values from Nucleo. Use when user asks to read registers, read memory, dump lower memory 0-126, or give address and value.",
+            ));
+            tool_descs.push((
+                "hardware_capabilities",
+                "Query connected hardware for reported GPIO pins and LED pin. Use when user asks what pins are available.",
+            ));
+        }
+        let bootstrap_max_chars = if config.agent.compact_context {
+            Some(6000)
+        } else {
+            None
+        };
+        let native_tools = providers::catalog::capabilities_for(
+            &self.provider_name,
+            model_name,
+            self.provider.as_ref(),
+        )
+        .native_tool_calling;
+        let mut system_prompt = crate::channels::build_system_prompt_with_mode(
+            &config.workspace_dir,
+            model_name,
+            &tool_descs,
+            &self.skills,
+            Some(&config.identity),
+            bootstrap_max_chars,
+            native_tools,
+            config.skills.prompt_injection_mode,
+        );
+        if !native_tools {
+            system_prompt.push_str(&build_tool_instructions(&self.tools_registry));
+        }
 
-    let mut history = vec![
-        ChatMessage::system(&system_prompt),
-        ChatMessage::user(&enriched),
-    ];
+        let (caller_system, mut turns): (Vec<ChatMessage>, Vec<ChatMessage>) = messages
+            .into_iter()
+            .partition(|message| message.role == "system");
+        for message in caller_system {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&message.content);
+        }
 
-    agent_turn(
-        provider.as_ref(),
-        &mut history,
-        &tools_registry,
-        observer.as_ref(),
-        provider_name,
-        &model_name,
-        config.default_temperature,
-        true,
-        &config.multimodal,
-        config.agent.max_tool_iterations,
-    )
-    .await
+        let context_budget =
+            ContextBudget::for_model(model_name, config.agent.context_window_tokens);
+        if let Some(last_user) = turns.iter_mut().rev().find(|m| m.role == "user") {
+            let message = last_user.content.as_str();
+            let mem_context = truncate_with_ellipsis(
+                &build_context(
+                    self.mem.as_ref(),
+                    message,
+                    config.memory.min_relevance_score,
+                )
+                .await,
+                context_budget.memory_context_chars(),
+            );
+            let rag_limit = if config.agent.compact_context { 2 } else { 5 };
+            let hw_context = self
+                .hardware_rag
+                .as_ref()
+                .map(|r| build_hardware_context(r, message, &self.board_names, rag_limit))
+                .unwrap_or_default();
+            let context = format!("{mem_context}{hw_context}");
+            if !context.is_empty() {
+                last_user.content = format!("{context}{message}");
+            }
+        }
+
+        let mut history = Vec::with_capacity(1 + turns.len());
+        history.push(ChatMessage::system(&system_prompt));
+        history.extend(turns);
+
+        let mut approval = ApprovalManager::from_config(&config.autonomy);
+        if let Some(audit) = &self.audit {
+            approval = approval.with_audit_logger(Arc::clone(audit));
+        }
+
+        run_tool_call_loop(
+            self.provider.as_ref(),
+            &mut history,
+            &self.tools_registry,
+            self.observer.as_ref(),
+            &self.provider_name,
+            model_name,
+            temperature,
+            true,
+            Some(&approval),
+            channel_name,
+            &config.multimodal,
+            config.agent.context_window_tokens,
+            config.agent.max_tool_iterations,
+            None,
+            on_delta,
+            None,
+            None,
+        )
+        .await
+    }
+}
+
+/// Run a multi-turn conversation through a freshly built agent and return
+/// the final answer. See [`ConversationRuntime::process`].
+pub async fn process_conversation(
+    config: Config,
+    messages: Vec<ChatMessage>,
+    channel_name: &str,
+    on_delta: Option<tokio::sync::mpsc::Sender<TurnDelta>>,
+) -> Result<String> {
+    let temperature = config.default_temperature;
+    let runtime = ConversationRuntime::new(config).await?;
+    let model = runtime.default_model().to_string();
+    runtime
+        .process(messages, &model, temperature, channel_name, on_delta)
+        .await
 }
 
 #[cfg(test)]
@@ -2538,6 +2969,7 @@ mod tests {
             ProviderCapabilities {
                 native_tool_calling: false,
                 vision: true,
+                structured_output: false,
             }
         }
 
@@ -2572,6 +3004,7 @@ mod tests {
                 text: Some("vision-ok".to_string()),
                 tool_calls: Vec::new(),
                 usage: None,
+                reasoning: None,
             })
         }
     }
@@ -2588,6 +3021,7 @@ mod tests {
                     text: Some(text.to_string()),
                     tool_calls: Vec::new(),
                     usage: None,
+                    reasoning: None,
                 })
                 .collect();
             Self {
@@ -2717,9 +3151,12 @@ mod tests {
             None,
             "cli",
             &crate::config::MultimodalConfig::default(),
+            None,
             3,
             None,
             None,
+            None,
+            None,
         )
         .await
         .expect_err("provider without vision support should fail");
@@ -2761,9 +3198,12 @@ mod tests {
             None,
             "cli",
             &multimodal,
+            None,
             3,
             None,
             None,
+            None,
+            None,
         )
         .await
         .expect_err("oversized payload must fail");
@@ -2799,9 +3239,12 @@ mod tests {
             None,
             "cli",
             &crate::config::MultimodalConfig::default(),
+            None,
             3,
             None,
             None,
+            None,
+            None,
         )
         .await
         .expect("valid multimodal payload should pass");
@@ -2810,6 +3253,54 @@ mod tests {
         assert_eq!(calls.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn run_tool_call_loop_repairs_answer_that_fails_output_schema() {
+        let provider = crate::providers::scripted::ScriptedProvider::from_texts([
+            r#"{"count": "three"}"#,
+            r#"```json
+{"count": 3}
+```"#,
+        ]);
+        let schema = OutputSchema::new(serde_json::json!({
+            "type": "object",
+            "properties": {"count": {"type": "integer"}},
+            "required": ["count"]
+        }))
+        .unwrap();
+        let mut history = vec![ChatMessage::user("How many?")];
+        let tools_registry: Vec<Box<dyn Tool>> = Vec::new();
+
+        let result = run_tool_call_loop(
+            &provider,
+            &mut history,
+            &tools_registry,
+            &NoopObserver,
+            "scripted",
+            "mock-model",
+            0.0,
+            true,
+            None,
+            "cli",
+            &crate::config::MultimodalConfig::default(),
+            None,
+            5,
+            None,
+            None,
+            None,
+            Some(&schema),
+        )
+        .await
+        .expect("second answer validates");
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&result).unwrap(),
+            serde_json::json!({"count": 3})
+        );
+        assert!(history[2]
+            .content
+            .contains("$.count: expected integer, got string"));
+    }
+
     #[test]
     fn should_execute_tools_in_parallel_returns_false_for_single_call() {
         let calls = vec![ParsedToolCall {
@@ -2919,9 +3410,12 @@ mod tests {
             Some(&approval_mgr),
             "telegram",
             &crate::config::MultimodalConfig::default(),
+            None,
             4,
             None,
             None,
+            None,
+            None,
         )
         .await
         .expect("parallel execution should complete");
@@ -3420,6 +3914,125 @@ Done."#;
         assert!(history[3].content.contains("recent 2"));
     }
 
+    #[tokio::test]
+    async fn auto_compact_history_triggers_on_token_budget() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = NonVisionProvider {
+            calls: Arc::clone(&calls),
+        };
+        let mut history = vec![ChatMessage::system("sys")];
+        for i in 0..8 {
+            history.push(ChatMessage::user(format!(
+                "question {i} {}",
+                "q".repeat(2_000)
+            )));
+            history.push(ChatMessage::assistant(format!("answer {i}")));
+        }
+        history.push(ChatMessage::user("latest question"));
+        let budget = ContextBudget::for_model("mock-model", Some(4_096));
+
+        let compacted = auto_compact_history(&mut history, &provider, "mock-model", 50, &budget)
+            .await
+            .unwrap();
+
+        assert!(compacted);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(history[0].role, "system");
+        assert!(history[1].content.contains("Compaction summary"));
+        assert_eq!(history[2].role, "user");
+        assert_eq!(history.last().unwrap().content, "latest question");
+        assert!(context::estimate_history_tokens(&history) < budget.compaction_threshold());
+    }
+
+    /// Rejects requests whose history exceeds a size limit, like a model
+    /// with a smaller context window than the budget assumes.
+    struct SmallWindowProvider {
+        calls: Arc<AtomicUsize>,
+        max_chars: usize,
+    }
+
+    #[async_trait]
+    impl Provider for SmallWindowProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("- user asked numbered questions".to_string())
+        }
+
+        async fn chat_with_history(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let chars: usize = messages.iter().map(|m| m.content.len()).sum();
+            if chars > self.max_chars {
+                anyhow::bail!("This model's maximum context length is 8192 tokens");
+            }
+            Ok("fits now".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_compacts_history_after_context_overflow() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = crate::providers::reliable::ReliableProvider::new(
+            vec![(
+                "small".into(),
+                Box::new(SmallWindowProvider {
+                    calls: Arc::clone(&calls),
+                    max_chars: 20_000,
+                }),
+            )],
+            0,
+            1,
+        );
+        let mut history = vec![ChatMessage::system("sys")];
+        for i in 0..20 {
+            history.push(ChatMessage::user(format!(
+                "question {i} {}",
+                "q".repeat(2_000)
+            )));
+            history.push(ChatMessage::assistant(format!("answer {i}")));
+        }
+        history.push(ChatMessage::user("latest question"));
+        let tools_registry: Vec<Box<dyn Tool>> = Vec::new();
+
+        let result = run_tool_call_loop(
+            &provider,
+            &mut history,
+            &tools_registry,
+            &NoopObserver,
+            "small",
+            "mock-model",
+            0.0,
+            true,
+            None,
+            "cli",
+            &crate::config::MultimodalConfig::default(),
+            None,
+            3,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("compacted history should fit");
+
+        assert_eq!(result, "fits now");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(history[0].role, "system");
+        assert!(history[1].content.contains("Compaction summary"));
+        assert!(history.iter().any(|m| m.content == "latest question"));
+        assert!(history.len() < 42);
+    }
+
     #[test]
     fn autosave_memory_key_has_prefix_and_uniqueness() {
         let key1 = autosave_memory_key("user_msg");
@@ -4052,4 +4665,104 @@ Let me check the result."#;
             "Native prompt should contain task instructions"
         );
     }
+
+    /// Scripted provider that reports native tool-calling support.
+    struct NativeScriptedProvider(ScriptedProvider);
+
+    #[async_trait]
+    impl Provider for NativeScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            system_prompt: Option<&str>,
+            message: &str,
+            model: &str,
+            temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.0
+                .chat_with_system(system_prompt, message, model, temperature)
+                .await
+        }
+
+        fn supports_native_tools(&self) -> bool {
+            true
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            model: &str,
+            temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            self.0.chat(request, model, temperature).await
+        }
+    }
+
+    #[tokio::test]
+    async fn run_tool_call_loop_streams_text_and_tool_progress_to_on_delta() {
+        let responses = VecDeque::from(vec![
+            ChatResponse {
+                text: Some("Checking.".to_string()),
+                tool_calls: vec![ToolCall {
+                    id: "call_1".to_string(),
+                    name: "delay_a".to_string(),
+                    arguments: r#"{"value":"A"}"#.to_string(),
+                }],
+                usage: None,
+                reasoning: None,
+            },
+            ChatResponse {
+                text: Some("done".to_string()),
+                tool_calls: Vec::new(),
+                usage: None,
+                reasoning: None,
+            },
+        ]);
+        let provider = NativeScriptedProvider(ScriptedProvider {
+            responses: Arc::new(Mutex::new(responses)),
+        });
+        let tools_registry: Vec<Box<dyn Tool>> = vec![Box::new(DelayTool::new(
+            "delay_a",
+            0,
+            Arc::new(AtomicUsize::new(0)),
+            Arc::new(AtomicUsize::new(0)),
+        ))];
+        let mut history = vec![ChatMessage::user("check something")];
+        let (delta_tx, mut delta_rx) = tokio::sync::mpsc::channel(16);
+
+        let result = run_tool_call_loop(
+            &provider,
+            &mut history,
+            &tools_registry,
+            &NoopObserver,
+            "mock-provider",
+            "mock-model",
+            0.0,
+            true,
+            None,
+            "cli",
+            &crate::config::MultimodalConfig::default(),
+            None,
+            4,
+            None,
+            Some(delta_tx),
+            None,
+            None,
+        )
+        .await
+        .expect("streamed turn should complete");
+
+        let mut deltas = Vec::new();
+        while let Ok(delta) = delta_rx.try_recv() {
+            deltas.push(delta);
+        }
+        assert_eq!(result, "done");
+        assert_eq!(
+            deltas,
+            vec![
+                TurnDelta::Text("Checking.".to_string()),
+                TurnDelta::ToolCall("delay_a".to_string()),
+                TurnDelta::Text("done".to_string()),
+            ]
+        );
+    }
 }
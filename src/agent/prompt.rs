@@ -30,6 +30,8 @@ pub struct SystemPromptBuilder {
 }
 
 impl SystemPromptBuilder {
+    /// Sections run from most to least stable so providers with prompt
+    /// caching can reuse the longest possible prefix; the clock goes last.
     pub fn with_defaults() -> Self {
         Self {
             sections: vec![
@@ -38,8 +40,8 @@ impl SystemPromptBuilder {
                 Box::new(SafetySection),
                 Box::new(SkillsSection),
                 Box::new(WorkspaceSection),
-                Box::new(DateTimeSection),
                 Box::new(RuntimeSection),
+                Box::new(DateTimeSection),
             ],
         }
     }
@@ -335,6 +337,24 @@ mod tests {
         assert!(prompt.contains("instr"));
     }
 
+    #[test]
+    fn prompt_builder_puts_volatile_datetime_last() {
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(TestTool)];
+        let ctx = PromptContext {
+            workspace_dir: Path::new("/tmp"),
+            model_name: "test-model",
+            tools: &tools,
+            skills: &[],
+            skills_prompt_mode: crate::config::SkillsPromptInjectionMode::Full,
+            identity_config: None,
+            dispatcher_instructions: "",
+        };
+        let prompt = SystemPromptBuilder::with_defaults().build(&ctx).unwrap();
+        let datetime = prompt.find("## Current Date & Time").unwrap();
+        assert!(prompt.find("## Runtime").unwrap() < datetime);
+        assert!(prompt.find("## Tools").unwrap() < datetime);
+    }
+
     #[test]
     fn skills_section_includes_instructions_and_tools() {
         let tools: Vec<Box<dyn Tool>> = vec![];
@@ -1,15 +1,20 @@
 //! Interactive approval workflow for supervised mode.
 //!
 //! Provides a pre-execution hook that prompts the user before tool calls,
-//! with session-scoped "Always" allowlists and audit logging.
+//! with session-scoped "Always" allowlists and audit logging. The CLI prompts
+//! on stdin; chat channels plug in an [`ApprovalPrompter`] that asks the
+//! originating conversation (or an operator chat) and waits for a reply.
 
 use crate::config::AutonomyConfig;
+use crate::security::audit::{AuditEvent, AuditEventType, AuditLogger};
 use crate::security::AutonomyLevel;
+use async_trait::async_trait;
 use chrono::Utc;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::io::{self, BufRead, Write};
+use std::sync::Arc;
 
 // ── Types ────────────────────────────────────────────────────────
 
@@ -40,6 +45,51 @@ pub struct ApprovalLogEntry {
     pub arguments_summary: String,
     pub decision: ApprovalResponse,
     pub channel: String,
+    /// Sender whose message triggered the tool call (chat channels only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requester: Option<String>,
+}
+
+impl ApprovalResponse {
+    /// Parse a chat reply such as `yes`, `n`, or `always 3f9c2a1b`.
+    ///
+    /// Returns the decision and the optional approval id it refers to.
+    pub fn parse_reply(text: &str) -> Option<(Self, Option<&str>)> {
+        let mut words = text.trim().trim_start_matches('/').split_whitespace();
+        let decision = match words.next()?.to_ascii_lowercase().as_str() {
+            "y" | "yes" | "approve" => Self::Yes,
+            "n" | "no" | "deny" => Self::No,
+            "a" | "always" => Self::Always,
+            _ => return None,
+        };
+        let id = words.next();
+        if words.next().is_some() {
+            return None;
+        }
+        Some((decision, id))
+    }
+
+    /// Canonical reply text for `decision` on approval `id` (used by buttons).
+    pub fn reply_text(self, id: &str) -> String {
+        let word = match self {
+            Self::Yes => "yes",
+            Self::No => "no",
+            Self::Always => "always",
+        };
+        format!("{word} {id}")
+    }
+}
+
+/// Asks a human to approve a tool call outside the CLI (e.g. over chat).
+#[async_trait]
+pub trait ApprovalPrompter: Send + Sync {
+    /// Identity of the user whose message triggered the tool call.
+    fn requester(&self) -> Option<&str> {
+        None
+    }
+
+    /// Wait for a decision. Implementations deny on timeout or delivery failure.
+    async fn prompt(&self, request: &ApprovalRequest) -> ApprovalResponse;
 }
 
 // ── ApprovalManager ──────────────────────────────────────────────
@@ -56,10 +106,15 @@ pub struct ApprovalManager {
     always_ask: HashSet<String>,
     /// Autonomy level from config.
     autonomy_level: AutonomyLevel,
-    /// Session-scoped allowlist built from "Always" responses.
-    session_allowlist: Mutex<HashSet<String>>,
+    /// Session-scoped allowlist built from "Always" responses. Chat channels
+    /// share one list per sender across messages.
+    session_allowlist: Arc<Mutex<HashSet<String>>>,
     /// Audit trail of approval decisions.
     audit_log: Mutex<Vec<ApprovalLogEntry>>,
+    /// Asks for decisions on non-CLI channels.
+    prompter: Option<Arc<dyn ApprovalPrompter>>,
+    /// Persistent security audit log, if enabled.
+    audit_logger: Option<Arc<AuditLogger>>,
 }
 
 impl ApprovalManager {
@@ -69,11 +124,31 @@ impl ApprovalManager {
             auto_approve: config.auto_approve.iter().cloned().collect(),
             always_ask: config.always_ask.iter().cloned().collect(),
             autonomy_level: config.level,
-            session_allowlist: Mutex::new(HashSet::new()),
+            session_allowlist: Arc::new(Mutex::new(HashSet::new())),
             audit_log: Mutex::new(Vec::new()),
+            prompter: None,
+            audit_logger: None,
         }
     }
 
+    /// Ask `prompter` for decisions instead of the CLI prompt.
+    pub fn with_prompter(mut self, prompter: Arc<dyn ApprovalPrompter>) -> Self {
+        self.prompter = Some(prompter);
+        self
+    }
+
+    /// Use a shared "Always" allowlist (e.g. one kept per chat sender).
+    pub fn with_allowlist(mut self, allowlist: Arc<Mutex<HashSet<String>>>) -> Self {
+        self.session_allowlist = allowlist;
+        self
+    }
+
+    /// Also write decisions to the security audit log.
+    pub fn with_audit_logger(mut self, logger: Arc<AuditLogger>) -> Self {
+        self.audit_logger = Some(logger);
+        self
+    }
+
     /// Check whether a tool call requires interactive approval.
     ///
     /// Returns `true` if the call needs a prompt, `false` if it can proceed.
@@ -124,17 +199,57 @@ impl ApprovalManager {
 
         // Append to audit log.
         let summary = summarize_args(args);
+        let requester = self
+            .prompter
+            .as_ref()
+            .and_then(|p| p.requester())
+            .map(str::to_string);
+
+        if let Some(logger) = &self.audit_logger {
+            let approved = decision != ApprovalResponse::No;
+            let event = AuditEvent::new(AuditEventType::ApprovalDecision)
+                .with_actor(channel.to_string(), requester.clone(), None)
+                .with_action(
+                    format!("{tool_name}: {summary}"),
+                    "tool".into(),
+                    approved,
+                    approved,
+                );
+            if let Err(e) = logger.log(&event) {
+                tracing::warn!("Failed to write approval audit event: {e}");
+            }
+        }
+
         let entry = ApprovalLogEntry {
             timestamp: Utc::now().to_rfc3339(),
             tool_name: tool_name.to_string(),
             arguments_summary: summary,
             decision,
             channel: channel.to_string(),
+            requester,
         };
         let mut log = self.audit_log.lock();
         log.push(entry);
     }
 
+    /// Ask for a decision on `request` from wherever it can be answered.
+    ///
+    /// Uses the attached prompter when present, the stdin prompt on the CLI,
+    /// and denies otherwise since nobody can be asked.
+    pub async fn request_approval(
+        &self,
+        request: &ApprovalRequest,
+        channel: &str,
+    ) -> ApprovalResponse {
+        if let Some(prompter) = &self.prompter {
+            prompter.prompt(request).await
+        } else if channel == "cli" {
+            self.prompt_cli(request)
+        } else {
+            ApprovalResponse::No
+        }
+    }
+
     /// Get a snapshot of the audit log.
     pub fn audit_log(&self) -> Vec<ApprovalLogEntry> {
         self.audit_log.lock().clone()
@@ -179,7 +294,7 @@ fn prompt_cli_interactive(request: &ApprovalRequest) -> ApprovalResponse {
 }
 
 /// Produce a short human-readable summary of tool arguments.
-fn summarize_args(args: &serde_json::Value) -> String {
+pub(crate) fn summarize_args(args: &serde_json::Value) -> String {
     match args {
         serde_json::Value::Object(map) => {
             let parts: Vec<String> = map
@@ -401,6 +516,108 @@ mod tests {
         assert!(summary.contains("just a string"));
     }
 
+    // ── chat replies / prompter ──────────────────────────────
+
+    #[test]
+    fn parse_reply_accepts_decisions_with_optional_id() {
+        assert_eq!(
+            ApprovalResponse::parse_reply("Yes"),
+            Some((ApprovalResponse::Yes, None))
+        );
+        assert_eq!(
+            ApprovalResponse::parse_reply(" /deny 3f9c2a1b "),
+            Some((ApprovalResponse::No, Some("3f9c2a1b")))
+        );
+        assert_eq!(
+            ApprovalResponse::parse_reply(&ApprovalResponse::Always.reply_text("abc")),
+            Some((ApprovalResponse::Always, Some("abc")))
+        );
+        assert_eq!(ApprovalResponse::parse_reply("yes please do it"), None);
+        assert_eq!(ApprovalResponse::parse_reply("maybe"), None);
+        assert_eq!(ApprovalResponse::parse_reply(""), None);
+    }
+
+    struct FixedPrompter(ApprovalResponse);
+
+    #[async_trait]
+    impl ApprovalPrompter for FixedPrompter {
+        fn requester(&self) -> Option<&str> {
+            Some("alice")
+        }
+
+        async fn prompt(&self, _request: &ApprovalRequest) -> ApprovalResponse {
+            self.0
+        }
+    }
+
+    fn shell_request() -> ApprovalRequest {
+        ApprovalRequest {
+            tool_name: "file_write".into(),
+            arguments: serde_json::json!({"path": "a.txt"}),
+        }
+    }
+
+    #[tokio::test]
+    async fn request_approval_uses_prompter_and_records_requester() {
+        let mgr = ApprovalManager::from_config(&supervised_config())
+            .with_prompter(Arc::new(FixedPrompter(ApprovalResponse::Always)));
+        let request = shell_request();
+
+        let decision = mgr.request_approval(&request, "telegram").await;
+        assert_eq!(decision, ApprovalResponse::Always);
+
+        mgr.record_decision(&request.tool_name, &request.arguments, decision, "telegram");
+        assert_eq!(mgr.audit_log()[0].requester.as_deref(), Some("alice"));
+        assert!(!mgr.needs_approval("file_write"));
+    }
+
+    #[tokio::test]
+    async fn request_approval_denies_on_channels_without_prompter() {
+        let mgr = ApprovalManager::from_config(&supervised_config());
+        let decision = mgr.request_approval(&shell_request(), "discord").await;
+        assert_eq!(decision, ApprovalResponse::No);
+    }
+
+    #[test]
+    fn shared_allowlist_persists_across_managers() {
+        let allowlist = Arc::new(Mutex::new(HashSet::new()));
+        let first = ApprovalManager::from_config(&supervised_config())
+            .with_allowlist(Arc::clone(&allowlist));
+        first.record_decision(
+            "file_write",
+            &serde_json::json!({}),
+            ApprovalResponse::Always,
+            "telegram",
+        );
+
+        let second = ApprovalManager::from_config(&supervised_config()).with_allowlist(allowlist);
+        assert!(!second.needs_approval("file_write"));
+        assert!(ApprovalManager::from_config(&supervised_config()).needs_approval("file_write"));
+    }
+
+    #[test]
+    fn decisions_are_written_to_security_audit_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = AuditLogger::new(
+            crate::config::AuditConfig::default(),
+            dir.path().to_path_buf(),
+        )
+        .unwrap();
+        let mgr =
+            ApprovalManager::from_config(&supervised_config()).with_audit_logger(Arc::new(logger));
+        mgr.record_decision(
+            "shell",
+            &serde_json::json!({"command": "ls"}),
+            ApprovalResponse::No,
+            "slack",
+        );
+
+        let log = std::fs::read_to_string(dir.path().join("audit.log")).unwrap();
+        assert!(log.contains("approval_decision"));
+        assert!(log.contains("shell: command: ls"));
+        assert!(log.contains("\"approved\":false"));
+    }
+
     // ── ApprovalResponse serde ───────────────────────────────
 
     #[test]
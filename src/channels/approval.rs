@@ -0,0 +1,413 @@
+//! Tool-call approvals over chat channels.
+//!
+//! When a supervised agent wants to run a gated tool on behalf of a channel
+//! message, [`ChannelApprovals`] posts an approval prompt to the originating
+//! conversation (or the configured operator chat) and parks the tool loop
+//! until a matching `yes` / `no` / `always` reply arrives or the timeout
+//! expires. Replies are intercepted by the dispatch loop before they reach
+//! the agent. "Always" answers are remembered per sender.
+
+use super::traits::{Channel, ChannelMessage, SendMessage};
+use crate::approval::{
+    summarize_args, ApprovalManager, ApprovalPrompter, ApprovalRequest, ApprovalResponse,
+};
+use crate::config::AutonomyConfig;
+use crate::security::AuditLogger;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Conversation an approval prompt is posted to and answered from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ApprovalChat {
+    channel: String,
+    recipient: String,
+}
+
+struct PendingApproval {
+    seq: u64,
+    chat: ApprovalChat,
+    /// Only this sender may answer; `None` lets anyone in the chat answer
+    /// (operator chats).
+    approver: Option<String>,
+    reply: oneshot::Sender<ApprovalResponse>,
+}
+
+/// Daemon-wide approval state shared by all channel messages.
+pub(crate) struct ChannelApprovals {
+    autonomy: AutonomyConfig,
+    channels: Arc<HashMap<String, Arc<dyn Channel>>>,
+    operator_chat: Option<ApprovalChat>,
+    timeout: Duration,
+    audit_logger: Option<Arc<AuditLogger>>,
+    pending: Mutex<HashMap<String, PendingApproval>>,
+    sender_allowlists: Mutex<HashMap<String, Arc<Mutex<HashSet<String>>>>>,
+    next_seq: Mutex<u64>,
+}
+
+impl ChannelApprovals {
+    pub(crate) fn new(
+        autonomy: &AutonomyConfig,
+        channels: Arc<HashMap<String, Arc<dyn Channel>>>,
+        audit_logger: Option<Arc<AuditLogger>>,
+    ) -> Self {
+        let operator_chat = autonomy.approval_chat.as_deref().and_then(|raw| {
+            let parsed = raw.split_once(':').and_then(|(channel, recipient)| {
+                let (channel, recipient) = (channel.trim(), recipient.trim());
+                (!channel.is_empty() && !recipient.is_empty()).then(|| ApprovalChat {
+                    channel: channel.to_string(),
+                    recipient: recipient.to_string(),
+                })
+            });
+            if parsed.is_none() {
+                tracing::warn!(
+                    "Ignoring autonomy.approval_chat = {raw:?}; expected `<channel>:<recipient>`"
+                );
+            }
+            parsed
+        });
+
+        Self {
+            autonomy: autonomy.clone(),
+            channels,
+            operator_chat,
+            timeout: Duration::from_secs(autonomy.approval_timeout_secs.max(1)),
+            audit_logger,
+            pending: Mutex::new(HashMap::new()),
+            sender_allowlists: Mutex::new(HashMap::new()),
+            next_seq: Mutex::new(0),
+        }
+    }
+
+    /// Build the approval manager for one incoming channel message.
+    pub(crate) fn manager_for(self: &Arc<Self>, msg: &ChannelMessage) -> ApprovalManager {
+        let allowlist = {
+            let key = format!("{}:{}", msg.channel, msg.sender);
+            let mut lists = self.sender_allowlists.lock();
+            Arc::clone(lists.entry(key).or_default())
+        };
+
+        let mut manager = ApprovalManager::from_config(&self.autonomy)
+            .with_allowlist(allowlist)
+            .with_prompter(Arc::new(ChannelApprovalPrompter {
+                approvals: Arc::clone(self),
+                origin: msg.clone(),
+            }));
+        if let Some(logger) = &self.audit_logger {
+            manager = manager.with_audit_logger(Arc::clone(logger));
+        }
+        manager
+    }
+
+    /// Deliver `msg` to a pending approval if it is an answer to one.
+    ///
+    /// Returns `true` when the message was consumed as an approval reply and
+    /// must not be handed to the agent.
+    pub(crate) fn try_resolve(&self, msg: &ChannelMessage) -> bool {
+        let Some((decision, id)) = ApprovalResponse::parse_reply(&msg.content) else {
+            return false;
+        };
+
+        let mut pending = self.pending.lock();
+        let answerable = |entry: &PendingApproval| {
+            entry.chat.channel == msg.channel
+                && entry.chat.recipient == msg.reply_target
+                && entry
+                    .approver
+                    .as_ref()
+                    .is_none_or(|approver| approver == &msg.sender)
+        };
+
+        let key = match id {
+            Some(id) => pending
+                .get(id)
+                .filter(|entry| answerable(entry))
+                .map(|_| id.to_string()),
+            // A bare "yes" answers the oldest open prompt in this chat.
+            None => pending
+                .iter()
+                .filter(|(_, entry)| answerable(entry))
+                .min_by_key(|(_, entry)| entry.seq)
+                .map(|(id, _)| id.clone()),
+        };
+
+        let Some(entry) = key.and_then(|key| pending.remove(&key)) else {
+            return false;
+        };
+        drop(pending);
+
+        tracing::info!(
+            channel = %msg.channel,
+            sender = %msg.sender,
+            decision = ?decision,
+            "Channel approval answered"
+        );
+        let _ = entry.reply.send(decision);
+        true
+    }
+
+    async fn prompt(&self, origin: &ChannelMessage, request: &ApprovalRequest) -> ApprovalResponse {
+        let (chat, thread_ts, approver) = match &self.operator_chat {
+            Some(chat) => (chat.clone(), None, None),
+            None => (
+                ApprovalChat {
+                    channel: origin.channel.clone(),
+                    recipient: origin.reply_target.clone(),
+                },
+                origin.thread_ts.clone(),
+                Some(origin.sender.clone()),
+            ),
+        };
+
+        let Some(channel) = self.channels.get(&chat.channel).cloned() else {
+            tracing::warn!(
+                channel = %chat.channel,
+                "Approval chat channel is not running; denying tool call"
+            );
+            return ApprovalResponse::No;
+        };
+
+        let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+        let (reply_tx, reply_rx) = oneshot::channel();
+        {
+            let mut seq = self.next_seq.lock();
+            *seq += 1;
+            self.pending.lock().insert(
+                id.clone(),
+                PendingApproval {
+                    seq: *seq,
+                    chat: chat.clone(),
+                    approver,
+                    reply: reply_tx,
+                },
+            );
+        }
+
+        let requested_by = if self.operator_chat.is_some() {
+            format!(" (requested by {} on {})", origin.sender, origin.channel)
+        } else {
+            String::new()
+        };
+        let text = format!(
+            "🔧 Approval needed{requested_by}: {}\n{}\n\nReply `yes`, `no` or `always` (id {id}) within {}s.",
+            request.tool_name,
+            summarize_args(&request.arguments),
+            self.timeout.as_secs()
+        );
+        let message = SendMessage::new(text, &chat.recipient).in_thread(thread_ts.clone());
+
+        if let Err(e) = channel.send_approval_prompt(&message, &id).await {
+            self.pending.lock().remove(&id);
+            tracing::warn!(channel = %chat.channel, "Failed to send approval prompt: {e}");
+            return ApprovalResponse::No;
+        }
+
+        match tokio::time::timeout(self.timeout, reply_rx).await {
+            Ok(Ok(decision)) => decision,
+            _ => {
+                self.pending.lock().remove(&id);
+                let notice = SendMessage::new(
+                    format!(
+                        "⌛ Approval {id} for {} timed out; denied.",
+                        request.tool_name
+                    ),
+                    &chat.recipient,
+                )
+                .in_thread(thread_ts);
+                if let Err(e) = channel.send(&notice).await {
+                    tracing::debug!("Failed to send approval timeout notice: {e}");
+                }
+                ApprovalResponse::No
+            }
+        }
+    }
+}
+
+/// Per-message prompter bound to the conversation that triggered the call.
+struct ChannelApprovalPrompter {
+    approvals: Arc<ChannelApprovals>,
+    origin: ChannelMessage,
+}
+
+#[async_trait]
+impl ApprovalPrompter for ChannelApprovalPrompter {
+    fn requester(&self) -> Option<&str> {
+        Some(&self.origin.sender)
+    }
+
+    async fn prompt(&self, request: &ApprovalRequest) -> ApprovalResponse {
+        self.approvals.prompt(&self.origin, request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+
+    #[derive(Default)]
+    struct RecordingChannel {
+        sent: Mutex<Vec<SendMessage>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
+            self.sent.lock().push(message.clone());
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn message(channel: &str, sender: &str, reply_target: &str, content: &str) -> ChannelMessage {
+        ChannelMessage {
+            id: "1".into(),
+            sender: sender.into(),
+            reply_target: reply_target.into(),
+            content: content.into(),
+            channel: channel.into(),
+            timestamp: 0,
+            thread_ts: None,
+        }
+    }
+
+    fn setup(
+        approval_chat: Option<&str>,
+        timeout_secs: u64,
+    ) -> (
+        Arc<ChannelApprovals>,
+        Arc<RecordingChannel>,
+        Arc<RecordingChannel>,
+    ) {
+        let chat = Arc::new(RecordingChannel::default());
+        let ops = Arc::new(RecordingChannel::default());
+        let mut channels: HashMap<String, Arc<dyn Channel>> = HashMap::new();
+        channels.insert("test".into(), chat.clone());
+        channels.insert("ops".into(), ops.clone());
+
+        let autonomy = AutonomyConfig {
+            level: AutonomyLevel::Supervised,
+            approval_timeout_secs: timeout_secs,
+            approval_chat: approval_chat.map(str::to_string),
+            ..AutonomyConfig::default()
+        };
+        let approvals = Arc::new(ChannelApprovals::new(&autonomy, Arc::new(channels), None));
+        (approvals, chat, ops)
+    }
+
+    fn request() -> ApprovalRequest {
+        ApprovalRequest {
+            tool_name: "shell".into(),
+            arguments: serde_json::json!({"command": "ls"}),
+        }
+    }
+
+    async fn wait_for_prompt(channel: &RecordingChannel) -> SendMessage {
+        for _ in 0..100 {
+            if let Some(sent) = channel.sent.lock().first().cloned() {
+                return sent;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("approval prompt was never sent");
+    }
+
+    #[tokio::test]
+    async fn reply_from_requester_resolves_prompt() {
+        let (approvals, chat, _) = setup(None, 30);
+        let origin = message("test", "alice", "room-1", "run ls");
+        let manager = approvals.manager_for(&origin);
+
+        let pending =
+            tokio::spawn(async move { manager.request_approval(&request(), "test").await });
+        let prompt = wait_for_prompt(&chat).await;
+        assert_eq!(prompt.recipient, "room-1");
+        assert!(prompt.content.contains("shell"));
+
+        // Someone else in the chat cannot answer for alice.
+        assert!(!approvals.try_resolve(&message("test", "mallory", "room-1", "yes")));
+        assert!(approvals.try_resolve(&message("test", "alice", "room-1", "yes")));
+        assert_eq!(pending.await.unwrap(), ApprovalResponse::Yes);
+
+        // Nothing is pending any more, so ordinary messages pass through.
+        assert!(!approvals.try_resolve(&message("test", "alice", "room-1", "yes")));
+    }
+
+    #[tokio::test]
+    async fn always_is_remembered_per_sender() {
+        let (approvals, chat, _) = setup(None, 30);
+        let origin = message("test", "alice", "room-1", "run ls");
+        let manager = approvals.manager_for(&origin);
+        assert!(manager.needs_approval("shell"));
+
+        let pending = tokio::spawn(async move {
+            let decision = manager.request_approval(&request(), "test").await;
+            manager.record_decision("shell", &serde_json::json!({}), decision, "test");
+            decision
+        });
+        let prompt = wait_for_prompt(&chat).await;
+        let id = prompt
+            .content
+            .split("(id ")
+            .nth(1)
+            .and_then(|rest| rest.split(')').next())
+            .unwrap()
+            .to_string();
+        assert!(approvals.try_resolve(&message(
+            "test",
+            "alice",
+            "room-1",
+            &ApprovalResponse::Always.reply_text(&id)
+        )));
+        assert_eq!(pending.await.unwrap(), ApprovalResponse::Always);
+
+        assert!(!approvals.manager_for(&origin).needs_approval("shell"));
+        let bob = message("test", "bob", "room-1", "run ls");
+        assert!(approvals.manager_for(&bob).needs_approval("shell"));
+    }
+
+    #[tokio::test]
+    async fn operator_chat_receives_prompt_and_any_member_can_answer() {
+        let (approvals, chat, ops) = setup(Some("ops:admins"), 30);
+        let manager = approvals.manager_for(&message("test", "alice", "room-1", "run ls"));
+
+        let pending =
+            tokio::spawn(async move { manager.request_approval(&request(), "test").await });
+        let prompt = wait_for_prompt(&ops).await;
+        assert_eq!(prompt.recipient, "admins");
+        assert!(prompt.content.contains("requested by alice"));
+        assert!(chat.sent.lock().is_empty());
+
+        // Replies in the original chat do not count.
+        assert!(!approvals.try_resolve(&message("test", "alice", "room-1", "yes")));
+        assert!(approvals.try_resolve(&message("ops", "carol", "admins", "no")));
+        assert_eq!(pending.await.unwrap(), ApprovalResponse::No);
+    }
+
+    #[tokio::test]
+    async fn unanswered_prompt_times_out_as_denied() {
+        let (approvals, chat, _) = setup(None, 1);
+        let manager = approvals.manager_for(&message("test", "alice", "room-1", "run ls"));
+
+        let decision = manager.request_approval(&request(), "test").await;
+        assert_eq!(decision, ApprovalResponse::No);
+        let sent = chat.sent.lock();
+        assert_eq!(sent.len(), 2);
+        assert!(sent[1].content.contains("timed out"));
+        drop(sent);
+        assert!(approvals.pending.lock().is_empty());
+    }
+}
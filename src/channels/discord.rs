@@ -1,4 +1,5 @@
 use super::traits::{Channel, ChannelMessage, SendMessage};
+use crate::approval::ApprovalResponse;
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::Mutex;
@@ -46,6 +47,88 @@ impl DiscordChannel {
         self.allowed_users.iter().any(|u| u == "*" || u == user_id)
     }
 
+    /// Action row with approve / deny / always buttons for an approval prompt.
+    ///
+    /// Each button's `custom_id` is the text reply it stands for.
+    fn approval_components(approval_id: &str) -> serde_json::Value {
+        // Button styles: 1 = primary, 3 = success, 4 = danger
+        let button = |label: &str, style: u8, response: ApprovalResponse| {
+            json!({
+                "type": 2,
+                "style": style,
+                "label": label,
+                "custom_id": response.reply_text(approval_id),
+            })
+        };
+        json!([{
+            "type": 1,
+            "components": [
+                button("Approve", 3, ApprovalResponse::Yes),
+                button("Deny", 4, ApprovalResponse::No),
+                button("Always", 1, ApprovalResponse::Always),
+            ]
+        }])
+    }
+
+    /// Turn a button press (`INTERACTION_CREATE`, component type) into a
+    /// channel message carrying the button's `custom_id`.
+    fn parse_component_interaction(&self, d: &serde_json::Value) -> Option<ChannelMessage> {
+        // Interaction type 3 = MESSAGE_COMPONENT
+        if d.get("type").and_then(serde_json::Value::as_u64) != Some(3) {
+            return None;
+        }
+        let custom_id = d
+            .get("data")
+            .and_then(|data| data.get("custom_id"))
+            .and_then(serde_json::Value::as_str)?;
+        // Guild interactions carry `member.user`, DMs carry `user`
+        let user_id = d
+            .get("member")
+            .and_then(|m| m.get("user"))
+            .or_else(|| d.get("user"))
+            .and_then(|u| u.get("id"))
+            .and_then(serde_json::Value::as_str)?;
+        if !self.is_user_allowed(user_id) {
+            tracing::warn!("Discord: ignoring button press from unauthorized user: {user_id}");
+            return None;
+        }
+        let channel_id = d.get("channel_id").and_then(serde_json::Value::as_str)?;
+        let interaction_id = d
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+
+        Some(ChannelMessage {
+            id: format!("discord_interaction_{interaction_id}"),
+            sender: user_id.to_string(),
+            reply_target: channel_id.to_string(),
+            content: custom_id.to_string(),
+            channel: "discord".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            thread_ts: None,
+        })
+    }
+
+    /// Acknowledge a button press by updating the prompt message without its buttons.
+    async fn acknowledge_interaction(&self, d: &serde_json::Value) {
+        let id = d.get("id").and_then(serde_json::Value::as_str);
+        let token = d.get("token").and_then(serde_json::Value::as_str);
+        let (Some(id), Some(token)) = (id, token) else {
+            return;
+        };
+        // Callback type 7 = UPDATE_MESSAGE
+        let url = format!("https://discord.com/api/v10/interactions/{id}/{token}/callback");
+        let _ = self
+            .http_client()
+            .post(&url)
+            .json(&json!({ "type": 7, "data": { "components": [] } }))
+            .send()
+            .await;
+    }
+
     fn bot_user_id_from_token(token: &str) -> Option<String> {
         // Discord bot tokens are base64(bot_user_id).timestamp.hmac
         let part = token.split('.').next()?;
@@ -292,6 +375,40 @@ impl Channel for DiscordChannel {
         Ok(())
     }
 
+    async fn send_approval_prompt(
+        &self,
+        message: &SendMessage,
+        approval_id: &str,
+    ) -> anyhow::Result<()> {
+        let url = format!(
+            "https://discord.com/api/v10/channels/{}/messages",
+            message.recipient
+        );
+        let body = json!({
+            "content": message.content,
+            "components": Self::approval_components(approval_id),
+        });
+
+        let resp = self
+            .http_client()
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            anyhow::bail!("Discord send approval prompt failed ({status}): {err}");
+        }
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_lines)]
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
         let bot_user_id = Self::bot_user_id_from_token(&self.bot_token).unwrap_or_default();
@@ -416,8 +533,22 @@ impl Channel for DiscordChannel {
                         _ => {}
                     }
 
-                    // Only handle MESSAGE_CREATE (opcode 0, type "MESSAGE_CREATE")
                     let event_type = event.get("t").and_then(|t| t.as_str()).unwrap_or("");
+
+                    // Button presses on approval prompts
+                    if event_type == "INTERACTION_CREATE" {
+                        if let Some(d) = event.get("d") {
+                            if let Some(channel_msg) = self.parse_component_interaction(d) {
+                                self.acknowledge_interaction(d).await;
+                                if tx.send(channel_msg).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
+                    // Otherwise only handle MESSAGE_CREATE (opcode 0, type "MESSAGE_CREATE")
                     if event_type != "MESSAGE_CREATE" {
                         continue;
                     }
@@ -626,6 +757,56 @@ mod tests {
         assert_eq!(ch.name(), "discord");
     }
 
+    #[test]
+    fn approval_components_carry_reply_text() {
+        let components = DiscordChannel::approval_components("ab12cd34");
+        let ids: Vec<&str> = components[0]["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["custom_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["yes ab12cd34", "no ab12cd34", "always ab12cd34"]);
+    }
+
+    #[test]
+    fn parse_component_interaction_reads_guild_member() {
+        let ch = DiscordChannel::new("fake".into(), None, vec!["42".into()], false, false);
+        let d = json!({
+            "id": "900",
+            "type": 3,
+            "token": "tok",
+            "channel_id": "c1",
+            "member": { "user": { "id": "42" } },
+            "data": { "custom_id": "no ab12cd34" }
+        });
+
+        let msg = ch.parse_component_interaction(&d).unwrap();
+        assert_eq!(msg.sender, "42");
+        assert_eq!(msg.reply_target, "c1");
+        assert_eq!(msg.content, "no ab12cd34");
+    }
+
+    #[test]
+    fn parse_component_interaction_rejects_unlisted_user_and_other_types() {
+        let ch = DiscordChannel::new("fake".into(), None, vec!["42".into()], false, false);
+        let stranger = json!({
+            "type": 3,
+            "channel_id": "c1",
+            "user": { "id": "7" },
+            "data": { "custom_id": "yes ab12cd34" }
+        });
+        assert!(ch.parse_component_interaction(&stranger).is_none());
+
+        let slash_command = json!({
+            "type": 2,
+            "channel_id": "c1",
+            "user": { "id": "42" },
+            "data": { "name": "ping" }
+        });
+        assert!(ch.parse_component_interaction(&slash_command).is_none());
+    }
+
     #[test]
     fn base64_decode_bot_id() {
         // "MTIzNDU2" decodes to "123456"
@@ -14,6 +14,7 @@
 //! To add a new channel, implement [`Channel`] in a new submodule and wire it into
 //! [`start_channels`]. See `AGENTS.md` §7.2 for the full change playbook.
 
+mod approval;
 pub mod cli;
 pub mod dingtalk;
 pub mod discord;
@@ -63,8 +64,8 @@ pub use whatsapp::WhatsAppChannel;
 #[cfg(feature = "whatsapp-web")]
 pub use whatsapp_web::WhatsAppWebChannel;
 
-use crate::agent::loop_::{build_tool_instructions, run_tool_call_loop};
-use crate::config::Config;
+use crate::agent::loop_::{build_tool_instructions, run_tool_call_loop, TurnDelta};
+use crate::config::{Config, ReasoningDisplay};
 use crate::identity;
 use crate::memory::{self, Memory};
 use crate::observability::{self, Observer};
@@ -109,6 +110,8 @@ const CHANNEL_MIN_IN_FLIGHT_MESSAGES: usize = 8;
 const CHANNEL_MAX_IN_FLIGHT_MESSAGES: usize = 64;
 const CHANNEL_TYPING_REFRESH_INTERVAL_SECS: u64 = 4;
 const CHANNEL_HEALTH_HEARTBEAT_SECS: u64 = 30;
+/// How often `[sessions]` retention limits are re-applied while channels run.
+const SESSION_PRUNE_INTERVAL_SECS: u64 = 3600;
 const MODEL_CACHE_FILE: &str = "models_cache.json";
 const MODEL_CACHE_PREVIEW_LIMIT: usize = 10;
 const MEMORY_CONTEXT_MAX_ENTRIES: usize = 4;
@@ -147,6 +150,7 @@ enum ChannelRuntimeCommand {
     SetProvider(String),
     ShowModel,
     SetModel(String),
+    Undo,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -199,14 +203,20 @@ struct ChannelRuntimeContext {
     default_provider: Arc<String>,
     memory: Arc<dyn Memory>,
     tools_registry: Arc<Vec<Box<dyn Tool>>>,
+    /// Chat-based tool approvals for supervised mode; `None` skips approval gating.
+    approvals: Option<Arc<approval::ChannelApprovals>>,
     observer: Arc<dyn Observer>,
     system_prompt: Arc<String>,
     model: Arc<String>,
     temperature: f64,
     auto_save_memory: bool,
     max_tool_iterations: usize,
+    /// Override for the model context window used to budget history.
+    context_window_tokens: Option<usize>,
     min_relevance_score: f64,
     conversation_histories: ConversationHistoryMap,
+    session_store: Option<Arc<crate::sessions::SessionStore>>,
+    cost_guard: Option<crate::cost::CostGuard>,
     provider_cache: ProviderCacheMap,
     route_overrides: RouteSelectionMap,
     api_key: Option<String>,
@@ -217,7 +227,11 @@ struct ChannelRuntimeContext {
     message_timeout_secs: u64,
     interrupt_on_new_message: bool,
     multimodal: crate::config::MultimodalConfig,
+    /// Reasoning display per channel name; unlisted channels hide it.
+    reasoning_display: Arc<HashMap<String, crate::config::ReasoningDisplay>>,
     hooks: Option<Arc<crate::hooks::HookRunner>>,
+    /// Workspace checkpoints restored by `/undo`; `None` when disabled.
+    checkpoints: Option<Arc<crate::checkpoint::CheckpointStore>>,
 }
 
 #[derive(Clone)]
@@ -440,10 +454,6 @@ fn supports_runtime_model_switch(channel_name: &str) -> bool {
 }
 
 fn parse_runtime_command(channel_name: &str, content: &str) -> Option<ChannelRuntimeCommand> {
-    if !supports_runtime_model_switch(channel_name) {
-        return None;
-    }
-
     let trimmed = content.trim();
     if !trimmed.starts_with('/') {
         return None;
@@ -457,6 +467,14 @@ fn parse_runtime_command(channel_name: &str, content: &str) -> Option<ChannelRun
         .unwrap_or(command_token)
         .to_ascii_lowercase();
 
+    // `/undo` works on every channel; model switching is limited below.
+    if base_command == "/undo" && parts.next().is_none() {
+        return Some(ChannelRuntimeCommand::Undo);
+    }
+    if !supports_runtime_model_switch(channel_name) {
+        return None;
+    }
+
     match base_command.as_str() {
         "/models" => {
             if let Some(provider) = parts.next() {
@@ -623,6 +641,10 @@ async fn maybe_apply_runtime_config_update(ctx: &ChannelRuntimeContext) -> Resul
         &next_defaults.reliability,
         &ctx.provider_runtime_options,
     )?;
+    let next_default_provider = match ctx.cost_guard.as_ref() {
+        Some(guard) => guard.wrap(&next_defaults.default_provider, next_default_provider),
+        None => next_default_provider,
+    };
     let next_default_provider: Arc<dyn Provider> = Arc::from(next_default_provider);
 
     if let Err(err) = next_default_provider.warmup().await {
@@ -700,6 +722,13 @@ fn clear_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str) {
         .lock()
         .unwrap_or_else(|e| e.into_inner())
         .remove(sender_key);
+    crate::tools::process::end_session(sender_key);
+
+    if let Some(store) = ctx.session_store.as_ref() {
+        if let Err(e) = store.clear(sender_key) {
+            tracing::warn!("Failed to clear persisted session {sender_key}: {e}");
+        }
+    }
 }
 
 fn compact_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str) -> bool {
@@ -730,25 +759,161 @@ fn compact_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str) -> bool
 
     if compacted.is_empty() {
         turns.clear();
+        persist_sender_history(ctx, sender_key, &[]);
         return false;
     }
 
     *turns = compacted;
+    persist_sender_history(ctx, sender_key, turns);
     true
 }
 
+/// Write a full sender history through to the session store, if enabled.
+fn persist_sender_history(ctx: &ChannelRuntimeContext, sender_key: &str, turns: &[ChatMessage]) {
+    if let Some(store) = ctx.session_store.as_ref() {
+        if let Err(e) = store.replace(sender_key, turns) {
+            tracing::warn!("Failed to persist session {sender_key}: {e}");
+        }
+    }
+}
+
+/// After the tool loop compacted `history` to fit the model's context window,
+/// write the compacted earlier turns back as the sender's history so the next
+/// message starts from them instead of overflowing again. `before` is the
+/// history the loop started with; its last message is the current user turn.
+/// Returns where the loop's own messages begin in `history`.
+fn persist_compacted_history(
+    ctx: &ChannelRuntimeContext,
+    sender_key: &str,
+    history: &[ChatMessage],
+    before: &[ChatMessage],
+) -> Option<usize> {
+    let current = before.last()?;
+    let turn_index = history
+        .iter()
+        .rposition(|m| m.role == "user" && m.content == current.content)?;
+    let unchanged = turn_index + 1 == before.len()
+        && history
+            .iter()
+            .zip(before)
+            .all(|(a, b)| a.role == b.role && a.content == b.content);
+    if unchanged {
+        return Some(turn_index + 1);
+    }
+
+    let mut turns: Vec<ChatMessage> = history[..=turn_index]
+        .iter()
+        .filter(|m| m.role == "user" || m.role == "assistant")
+        .cloned()
+        .collect();
+    // Stored turns start with the user; keep a leading compaction summary
+    // as context on that turn rather than letting normalization drop it.
+    if let Some(first) = turns.first_mut().filter(|m| m.role == "assistant") {
+        first.role = "user".to_string();
+    }
+    let turns = normalize_cached_channel_turns(turns);
+    persist_sender_history(ctx, sender_key, &turns);
+    ctx.conversation_histories
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(sender_key.to_string(), turns);
+    Some(turn_index + 1)
+}
+
 fn append_sender_turn(ctx: &ChannelRuntimeContext, sender_key: &str, turn: ChatMessage) {
     let mut histories = ctx
         .conversation_histories
         .lock()
         .unwrap_or_else(|e| e.into_inner());
     let turns = histories.entry(sender_key.to_string()).or_default();
+    if let Some(store) = ctx.session_store.as_ref() {
+        if let Err(e) = store.append(sender_key, &turn) {
+            tracing::warn!("Failed to persist session turn for {sender_key}: {e}");
+        }
+    }
     turns.push(turn);
     while turns.len() > MAX_CHANNEL_HISTORY {
         turns.remove(0);
     }
 }
 
+/// Re-apply session retention limits and forget pruned senders' in-memory
+/// history and background processes, so an expired conversation is not
+/// written back on the sender's next message.
+fn prune_channel_sessions(ctx: &ChannelRuntimeContext) {
+    let Some(store) = ctx.session_store.as_ref() else {
+        return;
+    };
+    let pruned = match store.prune() {
+        Ok(pruned) => pruned,
+        Err(e) => {
+            tracing::warn!("Failed to prune conversation sessions: {e}");
+            return;
+        }
+    };
+    if pruned.is_empty() {
+        return;
+    }
+    {
+        let mut histories = ctx
+            .conversation_histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        for key in &pruned {
+            histories.remove(key);
+        }
+    }
+    for key in &pruned {
+        crate::tools::process::end_session(key);
+    }
+    tracing::info!("Pruned {} expired conversation sessions", pruned.len());
+}
+
+/// Open the persistent session store and load saved per-sender histories.
+///
+/// Failures are non-fatal: channels fall back to in-memory history only.
+fn restore_channel_sessions(
+    config: &Config,
+) -> (
+    Option<Arc<crate::sessions::SessionStore>>,
+    HashMap<String, Vec<ChatMessage>>,
+) {
+    if !config.sessions.persist_channel_history {
+        return (None, HashMap::new());
+    }
+
+    let store = match crate::sessions::SessionStore::open(&config.workspace_dir, &config.sessions) {
+        Ok(store) => store,
+        Err(e) => {
+            tracing::warn!("Session persistence disabled: {e}");
+            return (None, HashMap::new());
+        }
+    };
+
+    match store.prune() {
+        Ok(pruned) if pruned.is_empty() => {}
+        Ok(pruned) => tracing::info!("Pruned {} expired conversation sessions", pruned.len()),
+        Err(e) => tracing::warn!("Failed to prune conversation sessions: {e}"),
+    }
+
+    let histories = match store.load_all() {
+        Ok(sessions) => sessions
+            .into_iter()
+            .map(|(key, mut turns)| {
+                let excess = turns.len().saturating_sub(MAX_CHANNEL_HISTORY);
+                turns.drain(..excess);
+                (key, turns)
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to load persisted sessions: {e}");
+            HashMap::new()
+        }
+    };
+
+    (Some(Arc::new(store)), histories)
+}
+
 fn should_skip_memory_context_entry(key: &str, content: &str) -> bool {
     if memory::is_assistant_autosave_key(key) {
         return true;
@@ -833,6 +998,10 @@ async fn get_or_create_provider(
         ctx.provider_runtime_options.clone(),
     )
     .await?;
+    let provider = match ctx.cost_guard.as_ref() {
+        Some(guard) => guard.wrap(provider_name, provider),
+        None => provider,
+    };
     let provider: Arc<dyn Provider> = Arc::from(provider);
 
     if let Err(err) = provider.warmup().await {
@@ -985,6 +1154,18 @@ async fn handle_runtime_command_if_needed(
                 )
             }
         }
+        ChannelRuntimeCommand::Undo => match ctx.checkpoints.clone() {
+            Some(store) => {
+                let source = sender_key.clone();
+                match tokio::task::spawn_blocking(move || store.undo(&source)).await {
+                    Ok(Ok(Some(report))) => report.summary(),
+                    Ok(Ok(None)) => "Nothing to undo.".to_string(),
+                    Ok(Err(err)) => format!("Failed to restore checkpoint: {err}"),
+                    Err(err) => format!("Failed to restore checkpoint: {err}"),
+                }
+            }
+            None => "Checkpoints are disabled, so there is nothing to undo.".to_string(),
+        },
     };
 
     if let Err(err) = channel
@@ -1431,6 +1612,21 @@ fn spawn_scoped_typing_task(
     handle
 }
 
+/// Put the model's reasoning ahead of the answer in the channel's chosen form.
+fn render_reasoning(display: ReasoningDisplay, reasoning: &str, answer: &str) -> String {
+    let reasoning = reasoning.trim();
+    if reasoning.is_empty() {
+        return answer.to_string();
+    }
+    match display {
+        ReasoningDisplay::Hide => answer.to_string(),
+        ReasoningDisplay::Collapsed => format!(
+            "<details>\n<summary>Reasoning</summary>\n\n{reasoning}\n\n</details>\n\n{answer}"
+        ),
+        ReasoningDisplay::Spoiler => format!("||{reasoning}||\n\n{answer}"),
+    }
+}
+
 async fn process_channel_message(
     ctx: Arc<ChannelRuntimeContext>,
     msg: traits::ChannelMessage,
@@ -1544,9 +1740,15 @@ async fn process_channel_message(
     let use_streaming = target_channel
         .as_ref()
         .is_some_and(|ch| ch.supports_draft_updates());
+    let reasoning_display = ctx
+        .reasoning_display
+        .get(&msg.channel)
+        .copied()
+        .unwrap_or_default();
 
-    let (delta_tx, delta_rx) = if use_streaming {
-        let (tx, rx) = tokio::sync::mpsc::channel::<String>(64);
+    // Reasoning only reaches the channel through the delta stream.
+    let (delta_tx, delta_rx) = if use_streaming || reasoning_display != ReasoningDisplay::Hide {
+        let (tx, rx) = tokio::sync::mpsc::channel::<TurnDelta>(64);
         (Some(tx), Some(rx))
     } else {
         (None, None)
@@ -1573,29 +1775,38 @@ async fn process_channel_message(
         None
     };
 
-    let draft_updater = if let (Some(mut rx), Some(draft_id_ref), Some(channel_ref)) = (
-        delta_rx,
-        draft_message_id.as_deref(),
-        target_channel.as_ref(),
-    ) {
-        let channel = Arc::clone(channel_ref);
+    let draft_target = draft_message_id
+        .as_deref()
+        .zip(target_channel.as_ref())
+        .map(|(draft_id, channel)| (Arc::clone(channel), draft_id.to_string()));
+    let delta_consumer = delta_rx.map(|mut rx| {
         let reply_target = msg.reply_target.clone();
-        let draft_id = draft_id_ref.to_string();
-        Some(tokio::spawn(async move {
+        tokio::spawn(async move {
             let mut accumulated = String::new();
+            let mut reasoning = String::new();
             while let Some(delta) = rx.recv().await {
-                accumulated.push_str(&delta);
-                if let Err(e) = channel
-                    .update_draft(&reply_target, &draft_id, &accumulated)
-                    .await
-                {
-                    tracing::debug!("Draft update failed: {e}");
+                match delta {
+                    TurnDelta::Text(text) => accumulated.push_str(&text),
+                    TurnDelta::ToolCall(name) => {
+                        let _ = write!(accumulated, "\n\u{1F527} {name}\n");
+                    }
+                    TurnDelta::Reasoning(text) => {
+                        reasoning.push_str(&text);
+                        continue;
+                    }
+                }
+                if let Some((channel, draft_id)) = draft_target.as_ref() {
+                    if let Err(e) = channel
+                        .update_draft(&reply_target, draft_id, &accumulated)
+                        .await
+                    {
+                        tracing::debug!("Draft update failed: {e}");
+                    }
                 }
             }
-        }))
-    } else {
-        None
-    };
+            reasoning
+        })
+    });
 
     // React with 👀 to acknowledge the incoming message
     if let Some(channel) = target_channel.as_ref() {
@@ -1617,8 +1828,13 @@ async fn process_channel_message(
         _ => None,
     };
 
-    // Record history length before tool loop so we can extract tool context after.
-    let history_len_before_tools = history.len();
+    // Keep the history the tool loop starts from, so tool context can be
+    // extracted afterwards and any compaction the loop did can be persisted.
+    let history_before_tools = history.clone();
+    let approval_manager = ctx
+        .approvals
+        .as_ref()
+        .map(|approvals| approvals.manager_for(&msg));
 
     enum LlmExecutionResult {
         Completed(Result<Result<String, anyhow::Error>, tokio::time::error::Elapsed>),
@@ -1631,29 +1847,39 @@ async fn process_channel_message(
         () = cancellation_token.cancelled() => LlmExecutionResult::Cancelled,
         result = tokio::time::timeout(
             Duration::from_secs(timeout_budget_secs),
-            run_tool_call_loop(
-                active_provider.as_ref(),
-                &mut history,
-                ctx.tools_registry.as_ref(),
-                ctx.observer.as_ref(),
-                route.provider.as_str(),
-                route.model.as_str(),
-                runtime_defaults.temperature,
-                true,
-                None,
-                msg.channel.as_str(),
-                &ctx.multimodal,
-                ctx.max_tool_iterations,
-                Some(cancellation_token.clone()),
-                delta_tx,
-                ctx.hooks.as_deref(),
+            crate::tools::process::with_session(
+                &history_key,
+                crate::checkpoint::with_turn(
+                    &history_key,
+                    &msg.content,
+                    run_tool_call_loop(
+                        active_provider.as_ref(),
+                        &mut history,
+                        ctx.tools_registry.as_ref(),
+                        ctx.observer.as_ref(),
+                        route.provider.as_str(),
+                        route.model.as_str(),
+                        runtime_defaults.temperature,
+                        true,
+                        approval_manager.as_ref(),
+                        msg.channel.as_str(),
+                        &ctx.multimodal,
+                        ctx.context_window_tokens,
+                        ctx.max_tool_iterations,
+                        Some(cancellation_token.clone()),
+                        delta_tx,
+                        ctx.hooks.as_deref(),
+                        None,
+                    ),
+                ),
             ),
         ) => LlmExecutionResult::Completed(result),
     };
 
-    if let Some(handle) = draft_updater {
-        let _ = handle.await;
-    }
+    let reasoning = match delta_consumer {
+        Some(handle) => handle.await.unwrap_or_default(),
+        None => String::new(),
+    };
 
     if let Some(token) = typing_cancellation.as_ref() {
         token.cancel();
@@ -1751,10 +1977,18 @@ async fn process_channel_message(
                 sanitized_response
             };
 
-            // Extract condensed tool-use context from the history messages
-            // added during run_tool_call_loop, so the LLM retains awareness
-            // of what it did on subsequent turns.
-            let tool_summary = extract_tool_context_summary(&history, history_len_before_tools);
+            // Persist history the loop compacted to fit the context window,
+            // then extract condensed tool-use context from the messages added
+            // during run_tool_call_loop, so the LLM retains awareness of what
+            // it did on subsequent turns.
+            let tools_start = persist_compacted_history(
+                ctx.as_ref(),
+                &history_key,
+                &history,
+                &history_before_tools,
+            )
+            .unwrap_or(history_before_tools.len());
+            let tool_summary = extract_tool_context_summary(&history, tools_start);
             let history_response = if tool_summary.is_empty() {
                 delivered_response.clone()
             } else {
@@ -1771,23 +2005,24 @@ async fn process_channel_message(
                 started_at.elapsed().as_millis(),
                 truncate_with_ellipsis(&delivered_response, 80)
             );
+            let reply = render_reasoning(reasoning_display, &reasoning, &delivered_response);
             if let Some(channel) = target_channel.as_ref() {
                 if let Some(ref draft_id) = draft_message_id {
                     if let Err(e) = channel
-                        .finalize_draft(&msg.reply_target, draft_id, &delivered_response)
+                        .finalize_draft(&msg.reply_target, draft_id, &reply)
                         .await
                     {
                         tracing::warn!("Failed to finalize draft: {e}; sending as new message");
                         let _ = channel
                             .send(
-                                &SendMessage::new(&delivered_response, &msg.reply_target)
+                                &SendMessage::new(&reply, &msg.reply_target)
                                     .in_thread(msg.thread_ts.clone()),
                             )
                             .await;
                     }
                 } else if let Err(e) = channel
                     .send(
-                        &SendMessage::new(delivered_response, &msg.reply_target)
+                        &SendMessage::new(reply, &msg.reply_target)
                             .in_thread(msg.thread_ts.clone()),
                     )
                     .await
@@ -1912,6 +2147,16 @@ async fn run_message_dispatch_loop(
     let task_sequence = Arc::new(AtomicU64::new(1));
 
     while let Some(msg) = rx.recv().await {
+        // Approval replies go straight to the waiting tool loop; they must not
+        // queue behind (or interrupt) the very task that is waiting for them.
+        if ctx
+            .approvals
+            .as_ref()
+            .is_some_and(|approvals| approvals.try_resolve(&msg))
+        {
+            continue;
+        }
+
         let permit = match Arc::clone(&semaphore).acquire_owned().await {
             Ok(permit) => permit,
             Err(_) => break,
@@ -2527,11 +2772,14 @@ fn collect_configured_channels(
     if let Some(ref sl) = config.channels_config.slack {
         channels.push(ConfiguredChannel {
             display_name: "Slack",
-            channel: Arc::new(SlackChannel::new(
-                sl.bot_token.clone(),
-                sl.channel_id.clone(),
-                sl.allowed_users.clone(),
-            )),
+            channel: Arc::new(
+                SlackChannel::new(
+                    sl.bot_token.clone(),
+                    sl.channel_id.clone(),
+                    sl.allowed_users.clone(),
+                )
+                .with_app_token(sl.app_token.clone()),
+            ),
         });
     }
 
@@ -2798,17 +3046,21 @@ pub async fn start_channels(config: Config) -> Result<()> {
         zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
         secrets_encrypt: config.secrets.encrypt,
         reasoning_enabled: config.runtime.reasoning_enabled,
+        reasoning_budget_tokens: config.runtime.reasoning_budget_tokens,
     };
-    let provider: Arc<dyn Provider> = Arc::from(
-        create_resilient_provider_nonblocking(
-            &provider_name,
-            config.api_key.clone(),
-            config.api_url.clone(),
-            config.reliability.clone(),
-            provider_runtime_options.clone(),
-        )
-        .await?,
-    );
+    let cost_guard = crate::cost::CostGuard::from_config(&config);
+    let provider = create_resilient_provider_nonblocking(
+        &provider_name,
+        config.api_key.clone(),
+        config.api_url.clone(),
+        config.reliability.clone(),
+        provider_runtime_options.clone(),
+    )
+    .await?;
+    let provider: Arc<dyn Provider> = Arc::from(match cost_guard.as_ref() {
+        Some(guard) => guard.wrap(&provider_name, provider),
+        None => provider,
+    });
 
     // Warm up the provider connection pool (TLS handshake, DNS, HTTP/2 setup)
     // so the first real message doesn't hit a cold-start timeout.
@@ -2856,7 +3108,7 @@ pub async fn start_channels(config: Config) -> Result<()> {
     };
     // Build system prompt from workspace identity files + skills
     let workspace = config.workspace_dir.clone();
-    let tools_registry = Arc::new(tools::all_tools_with_runtime(
+    let mut built_tools = tools::all_tools_with_runtime(
         Arc::new(config.clone()),
         &security,
         runtime,
@@ -2869,7 +3121,9 @@ pub async fn start_channels(config: Config) -> Result<()> {
         &config.agents,
         config.api_key.as_deref(),
         &config,
-    ));
+    );
+    built_tools.extend(crate::mcp::create_mcp_tools(&config, &security).await);
+    let tools_registry = Arc::new(built_tools);
 
     let skills = crate::skills::load_skills_with_config(&workspace, &config);
 
@@ -2883,10 +3137,18 @@ pub async fn start_channels(config: Config) -> Result<()> {
             "file_read",
             "Read file contents. Use when: inspecting project files, configs, logs. Don't use when: a targeted search is enough.",
         ),
+        (
+            "content_search",
+            "Search file contents by regex or literal text. Use when: locating definitions, call sites, config keys or log lines across the workspace. Don't use when: you already know the exact file to read.",
+        ),
         (
             "file_write",
             "Write file contents. Use when: applying focused edits, scaffolding files, updating docs/code. Don't use when: side effects are unclear or file ownership is uncertain.",
         ),
+        (
+            "apply_patch",
+            "Apply a unified diff or multi-file edit list atomically. Use when: a change spans several hunks or files, or creates, deletes or renames files. Don't use when: a single small edit is enough.",
+        ),
         (
             "memory_store",
             "Save to memory. Use when: preserving durable preferences, decisions, key context. Don't use when: information is transient/noisy/sensitive without need.",
@@ -2907,6 +3169,18 @@ pub async fn start_channels(config: Config) -> Result<()> {
             "Open approved HTTPS URLs in Brave Browser (allowlist-only, no scraping)",
         ));
     }
+    if config.web_fetch.enabled {
+        tool_descs.push((
+            "web_fetch",
+            "Fetch a web page and read its main content as Markdown with links preserved (HTML, PDF, text). Long pages are paged with offset. Use when: reading articles or docs from allowlisted domains.",
+        ));
+    }
+    if config.process.enabled {
+        tool_descs.push((
+            "process",
+            "Run long-running commands (builds, test suites, dev servers) in the background and poll their output. Use when: a command may outlive the shell timeout or must keep running. Don't use when: a quick shell command is enough.",
+        ));
+    }
     if config.composio.enabled {
         tool_descs.push((
             "composio",
@@ -2933,7 +3207,9 @@ pub async fn start_channels(config: Config) -> Result<()> {
     } else {
         None
     };
-    let native_tools = provider.supports_native_tools();
+    let native_tools =
+        providers::catalog::capabilities_for(&provider_name, &model, provider.as_ref())
+            .native_tool_calling;
     let mut system_prompt = build_system_prompt_with_mode(
         &workspace,
         &model,
@@ -3046,20 +3322,36 @@ pub async fn start_channels(config: Config) -> Result<()> {
         .as_ref()
         .is_some_and(|tg| tg.interrupt_on_new_message);
 
+    let (session_store, restored_histories) = restore_channel_sessions(&config);
+    if !restored_histories.is_empty() {
+        println!(
+            "  💾 Restored {} conversation sessions",
+            restored_histories.len()
+        );
+    }
+
     let runtime_ctx = Arc::new(ChannelRuntimeContext {
-        channels_by_name,
+        channels_by_name: Arc::clone(&channels_by_name),
         provider: Arc::clone(&provider),
         default_provider: Arc::new(provider_name),
         memory: Arc::clone(&mem),
         tools_registry: Arc::clone(&tools_registry),
+        approvals: Some(Arc::new(approval::ChannelApprovals::new(
+            &config.autonomy,
+            Arc::clone(&channels_by_name),
+            crate::security::audit::default_logger(&config),
+        ))),
         observer,
         system_prompt: Arc::new(system_prompt),
         model: Arc::new(model.clone()),
         temperature,
         auto_save_memory: config.memory.auto_save,
         max_tool_iterations: config.agent.max_tool_iterations,
+        context_window_tokens: config.agent.context_window_tokens,
         min_relevance_score: config.memory.min_relevance_score,
-        conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+        conversation_histories: Arc::new(Mutex::new(restored_histories)),
+        session_store,
+        cost_guard,
         provider_cache: Arc::new(Mutex::new(provider_cache_seed)),
         route_overrides: Arc::new(Mutex::new(HashMap::new())),
         api_key: config.api_key.clone(),
@@ -3070,6 +3362,7 @@ pub async fn start_channels(config: Config) -> Result<()> {
         message_timeout_secs,
         interrupt_on_new_message,
         multimodal: config.multimodal.clone(),
+        reasoning_display: Arc::new(config.channels_config.reasoning_display.clone()),
         hooks: if config.hooks.enabled {
             let mut runner = crate::hooks::HookRunner::new();
             if config.hooks.builtin.command_logger {
@@ -3079,8 +3372,26 @@ pub async fn start_channels(config: Config) -> Result<()> {
         } else {
             None
         },
+        checkpoints: config
+            .checkpoints
+            .enabled
+            .then(|| Arc::new(crate::checkpoint::open_store(&config))),
     });
 
+    if runtime_ctx.session_store.is_some() {
+        let ctx = Arc::clone(&runtime_ctx);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(SESSION_PRUNE_INTERVAL_SECS));
+            // The first tick fires immediately; startup already pruned.
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                prune_channel_sessions(&ctx);
+            }
+        });
+    }
+
     run_message_dispatch_loop(rx, runtime_ctx, max_in_flight_messages).await;
 
     // Wait for all channel tasks
@@ -3103,6 +3414,25 @@ mod tests {
     use std::sync::Arc;
     use tempfile::TempDir;
 
+    #[test]
+    fn render_reasoning_follows_display_setting() {
+        assert_eq!(
+            render_reasoning(ReasoningDisplay::Hide, "why", "answer"),
+            "answer"
+        );
+        assert_eq!(
+            render_reasoning(ReasoningDisplay::Spoiler, "why", "answer"),
+            "||why||\n\nanswer"
+        );
+        let collapsed = render_reasoning(ReasoningDisplay::Collapsed, "why", "answer");
+        assert!(collapsed.starts_with("<details>"));
+        assert!(collapsed.ends_with("</details>\n\nanswer"));
+        assert_eq!(
+            render_reasoning(ReasoningDisplay::Spoiler, "  ", "answer"),
+            "answer"
+        );
+    }
+
     fn make_workspace() -> TempDir {
         let tmp = TempDir::new().unwrap();
         // Create minimal workspace files
@@ -3236,14 +3566,18 @@ mod tests {
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("system".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(histories)),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -3251,9 +3585,12 @@ mod tests {
             reliability: Arc::new(crate::config::ReliabilityConfig::default()),
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             provider_runtime_options: providers::ProviderRuntimeOptions::default(),
             workspace_dir: Arc::new(std::env::temp_dir()),
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            hooks: None,
+            checkpoints: None,
         };
 
         assert!(compact_sender_history(&ctx, &sender));
@@ -3274,6 +3611,217 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn persist_compacted_history_replaces_sender_turns() {
+        let sender = "telegram_u1".to_string();
+        let before = vec![
+            ChatMessage::system("system"),
+            ChatMessage::user("old question"),
+            ChatMessage::assistant("old answer"),
+            ChatMessage::user("latest question"),
+        ];
+        let ctx = ChannelRuntimeContext {
+            channels_by_name: Arc::new(HashMap::new()),
+            provider: Arc::new(DummyProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            approvals: None,
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("system".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            context_window_tokens: None,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::from([(
+                sender.clone(),
+                before[1..].to_vec(),
+            )]))),
+            session_store: None,
+            cost_guard: None,
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(std::env::temp_dir()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            hooks: None,
+            checkpoints: None,
+        };
+
+        let mut unchanged = before.clone();
+        unchanged.push(ChatMessage::assistant("tool call"));
+        assert_eq!(
+            persist_compacted_history(&ctx, &sender, &unchanged, &before),
+            Some(4)
+        );
+
+        let compacted = vec![
+            ChatMessage::system("system"),
+            ChatMessage::assistant("[Compaction summary]\n- asked an old question"),
+            ChatMessage::user("latest question"),
+            ChatMessage::assistant("tool call"),
+        ];
+        assert_eq!(
+            persist_compacted_history(&ctx, &sender, &compacted, &before),
+            Some(3)
+        );
+
+        let histories = ctx
+            .conversation_histories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let turns = histories
+            .get(&sender)
+            .expect("sender history should remain");
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].role, "user");
+        assert!(turns[0].content.starts_with("[Compaction summary]"));
+        assert!(turns[0].content.ends_with("latest question"));
+    }
+
+    #[test]
+    fn sender_history_writes_through_and_restores_from_session_store() {
+        let workspace = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.workspace_dir = workspace.path().to_path_buf();
+
+        let (session_store, restored) = restore_channel_sessions(&config);
+        assert!(session_store.is_some());
+        assert!(restored.is_empty());
+
+        let ctx = ChannelRuntimeContext {
+            channels_by_name: Arc::new(HashMap::new()),
+            provider: Arc::new(DummyProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            approvals: None,
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("system".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            context_window_tokens: None,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(restored)),
+            session_store,
+            cost_guard: None,
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(workspace.path().to_path_buf()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            hooks: None,
+            checkpoints: None,
+        };
+
+        append_sender_turn(
+            &ctx,
+            "telegram_alice",
+            ChatMessage::user("my name is alice"),
+        );
+        append_sender_turn(&ctx, "telegram_alice", ChatMessage::assistant("hi alice"));
+        append_sender_turn(&ctx, "telegram_bob", ChatMessage::user("hello"));
+        clear_sender_history(&ctx, "telegram_bob");
+        drop(ctx);
+
+        // Simulate a daemon restart: a fresh runtime restores from disk.
+        let (_store, restored) = restore_channel_sessions(&config);
+        assert_eq!(restored.len(), 1);
+        let alice = &restored["telegram_alice"];
+        assert_eq!(alice.len(), 2);
+        assert_eq!(alice[0].content, "my name is alice");
+        assert_eq!(alice[1].role, "assistant");
+    }
+
+    #[test]
+    fn pruning_sessions_forgets_in_memory_history() {
+        let workspace = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.workspace_dir = workspace.path().to_path_buf();
+        config.sessions.max_sessions = 1;
+
+        let (session_store, restored) = restore_channel_sessions(&config);
+        let ctx = ChannelRuntimeContext {
+            channels_by_name: Arc::new(HashMap::new()),
+            provider: Arc::new(DummyProvider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            approvals: None,
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("system".to_string()),
+            model: Arc::new("test-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            context_window_tokens: None,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(restored)),
+            session_store,
+            cost_guard: None,
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(workspace.path().to_path_buf()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            hooks: None,
+            checkpoints: None,
+        };
+
+        append_sender_turn(&ctx, "telegram_alice", ChatMessage::user("old news"));
+        std::thread::sleep(Duration::from_millis(5));
+        append_sender_turn(&ctx, "telegram_bob", ChatMessage::user("hello"));
+        prune_channel_sessions(&ctx);
+
+        {
+            let histories = ctx.conversation_histories.lock().unwrap();
+            assert!(!histories.contains_key("telegram_alice"));
+            assert_eq!(histories["telegram_bob"].len(), 1);
+        }
+
+        // Alice starts over instead of writing the pruned turns back.
+        append_sender_turn(&ctx, "telegram_alice", ChatMessage::user("hi again"));
+        let store = ctx.session_store.as_ref().unwrap();
+        let alice = store.load("telegram_alice").unwrap();
+        assert_eq!(alice.len(), 1);
+        assert_eq!(alice[0].content, "hi again");
+    }
+
+    #[test]
+    fn restore_channel_sessions_respects_disabled_persistence() {
+        let workspace = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.workspace_dir = workspace.path().to_path_buf();
+        config.sessions.persist_channel_history = false;
+
+        let (session_store, restored) = restore_channel_sessions(&config);
+        assert!(session_store.is_none());
+        assert!(restored.is_empty());
+        assert!(!workspace.path().join("memory").join("sessions.db").exists());
+    }
+
     struct DummyProvider;
 
     #[async_trait::async_trait]
@@ -3713,14 +4261,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 10,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -3731,7 +4283,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -3771,14 +4325,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 10,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -3789,6 +4347,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -3828,14 +4389,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 10,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -3846,7 +4411,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -3895,14 +4462,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("default-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(provider_cache_seed)),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -3913,7 +4484,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -3950,6 +4523,109 @@ BTC is currently around $65,000 based on latest tool output."#
         assert_eq!(fallback_provider_impl.call_count.load(Ordering::SeqCst), 0);
     }
 
+    #[tokio::test]
+    async fn process_channel_message_handles_undo_command_without_llm_call() {
+        let channel_impl = Arc::new(TelegramRecordingChannel::default());
+        let channel: Arc<dyn Channel> = channel_impl.clone();
+
+        let mut channels_by_name = HashMap::new();
+        channels_by_name.insert(channel.name().to_string(), channel);
+
+        let provider_impl = Arc::new(ModelCaptureProvider::default());
+        let provider: Arc<dyn Provider> = provider_impl.clone();
+
+        let workspace = tempfile::TempDir::new().unwrap();
+        std::fs::write(workspace.path().join("notes.md"), "edited by the agent\n").unwrap();
+        let store = Arc::new(crate::checkpoint::CheckpointStore::new(
+            workspace.path(),
+            &crate::config::CheckpointsConfig::default(),
+        ));
+        store
+            .record(
+                &crate::checkpoint::TurnContext::new("telegram_alice", "rewrite my notes"),
+                "file_write",
+                vec![(
+                    "notes.md".to_string(),
+                    crate::checkpoint::Before::Content(b"original\n".to_vec()),
+                )],
+            )
+            .unwrap();
+
+        let runtime_ctx = Arc::new(ChannelRuntimeContext {
+            channels_by_name: Arc::new(channels_by_name),
+            provider: Arc::clone(&provider),
+            default_provider: Arc::new("test-provider".to_string()),
+            memory: Arc::new(NoopMemory),
+            tools_registry: Arc::new(vec![]),
+            approvals: None,
+            observer: Arc::new(NoopObserver),
+            system_prompt: Arc::new("test-system-prompt".to_string()),
+            model: Arc::new("default-model".to_string()),
+            temperature: 0.0,
+            auto_save_memory: false,
+            max_tool_iterations: 5,
+            context_window_tokens: None,
+            min_relevance_score: 0.0,
+            conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
+            provider_cache: Arc::new(Mutex::new(HashMap::new())),
+            route_overrides: Arc::new(Mutex::new(HashMap::new())),
+            api_key: None,
+            api_url: None,
+            reliability: Arc::new(crate::config::ReliabilityConfig::default()),
+            provider_runtime_options: providers::ProviderRuntimeOptions::default(),
+            workspace_dir: Arc::new(workspace.path().to_path_buf()),
+            message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
+            interrupt_on_new_message: false,
+            multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: Some(store),
+        });
+
+        for id in ["msg-undo-1", "msg-undo-2"] {
+            process_channel_message(
+                runtime_ctx.clone(),
+                traits::ChannelMessage {
+                    id: id.to_string(),
+                    sender: "alice".to_string(),
+                    reply_target: "chat-1".to_string(),
+                    content: "/undo".to_string(),
+                    channel: "telegram".to_string(),
+                    timestamp: 1,
+                    thread_ts: None,
+                },
+                CancellationToken::new(),
+            )
+            .await;
+        }
+
+        let sent = channel_impl.sent_messages.lock().await;
+        assert_eq!(sent.len(), 2);
+        assert!(sent[0].contains("1 file(s) restored"));
+        assert!(sent[1].contains("Nothing to undo."));
+        assert_eq!(
+            std::fs::read_to_string(workspace.path().join("notes.md")).unwrap(),
+            "original\n"
+        );
+        assert_eq!(provider_impl.call_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn parse_runtime_command_accepts_undo_on_every_channel() {
+        assert_eq!(
+            parse_runtime_command("slack", "/undo"),
+            Some(ChannelRuntimeCommand::Undo)
+        );
+        assert_eq!(
+            parse_runtime_command("telegram", "/undo@zeroclaw_bot"),
+            Some(ChannelRuntimeCommand::Undo)
+        );
+        assert_eq!(parse_runtime_command("slack", "/undo that please"), None);
+        assert_eq!(parse_runtime_command("slack", "/models"), None);
+    }
+
     #[tokio::test]
     async fn process_channel_message_uses_route_override_provider_and_model() {
         let channel_impl = Arc::new(TelegramRecordingChannel::default());
@@ -3983,14 +4659,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("default-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(provider_cache_seed)),
             route_overrides: Arc::new(Mutex::new(route_overrides)),
             api_key: None,
@@ -4001,7 +4681,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -4053,14 +4735,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("default-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(provider_cache_seed)),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4071,6 +4757,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -4134,14 +4823,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("startup-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(provider_cache_seed)),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4155,6 +4848,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -4206,14 +4902,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 12,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4224,7 +4924,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -4265,14 +4967,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![Box::new(MockPriceTool)]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 3,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4283,7 +4989,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -4435,14 +5143,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 10,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4453,7 +5165,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(4);
@@ -4514,14 +5228,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 10,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4532,6 +5250,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: true,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: None,
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(8);
@@ -4604,14 +5325,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 10,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4622,6 +5347,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: true,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: None,
         });
 
         let (tx, rx) = tokio::sync::mpsc::channel::<traits::ChannelMessage>(8);
@@ -4676,14 +5404,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 10,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4694,7 +5426,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -4734,14 +5468,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 10,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -4752,6 +5490,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -5248,14 +5989,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -5266,7 +6011,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -5332,14 +6079,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(RecallMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -5350,7 +6101,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
             hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -5416,14 +6169,18 @@ BTC is currently around $65,000 based on latest tool output."#
             default_provider: Arc::new("test-provider".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("test-system-prompt".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(histories)),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -5434,6 +6191,9 @@ BTC is currently around $65,000 based on latest tool output."#
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: None,
         });
 
         process_channel_message(
@@ -5961,14 +6721,18 @@ This is an example JSON object for profile settings."#;
             default_provider: Arc::new("dummy".to_string()),
             memory: Arc::new(NoopMemory),
             tools_registry: Arc::new(vec![]),
+            approvals: None,
             observer: Arc::new(NoopObserver),
             system_prompt: Arc::new("You are a helpful assistant.".to_string()),
             model: Arc::new("test-model".to_string()),
             temperature: 0.0,
             auto_save_memory: false,
             max_tool_iterations: 5,
+            context_window_tokens: None,
             min_relevance_score: 0.0,
             conversation_histories: Arc::new(Mutex::new(HashMap::new())),
+            session_store: None,
+            cost_guard: None,
             provider_cache: Arc::new(Mutex::new(HashMap::new())),
             route_overrides: Arc::new(Mutex::new(HashMap::new())),
             api_key: None,
@@ -5979,6 +6743,9 @@ This is an example JSON object for profile settings."#;
             message_timeout_secs: CHANNEL_MESSAGE_TIMEOUT_SECS,
             interrupt_on_new_message: false,
             multimodal: crate::config::MultimodalConfig::default(),
+            reasoning_display: Arc::new(HashMap::new()),
+            hooks: None,
+            checkpoints: None,
         });
 
         // Simulate a photo attachment message with [IMAGE:] marker.
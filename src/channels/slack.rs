@@ -1,9 +1,17 @@
 use super::traits::{Channel, ChannelMessage, SendMessage};
+use crate::approval::ApprovalResponse;
 use async_trait::async_trait;
-
-/// Slack channel — polls conversations.history via Web API
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Slack channel — polls conversations.history via Web API.
+///
+/// With an app-level token, button presses on approval prompts arrive over
+/// Socket Mode alongside the polling loop.
 pub struct SlackChannel {
     bot_token: String,
+    app_token: Option<String>,
     channel_id: Option<String>,
     allowed_users: Vec<String>,
 }
@@ -12,11 +20,18 @@ impl SlackChannel {
     pub fn new(bot_token: String, channel_id: Option<String>, allowed_users: Vec<String>) -> Self {
         Self {
             bot_token,
+            app_token: None,
             channel_id,
             allowed_users,
         }
     }
 
+    /// Set the app-level token (xapp-...) used to receive button presses via Socket Mode.
+    pub fn with_app_token(mut self, app_token: Option<String>) -> Self {
+        self.app_token = app_token.filter(|t| !t.trim().is_empty());
+        self
+    }
+
     fn http_client(&self) -> reqwest::Client {
         crate::config::build_runtime_proxy_client("channel.slack")
     }
@@ -54,56 +69,201 @@ impl SlackChannel {
             .or(if ts.is_empty() { None } else { Some(ts) })
             .map(str::to_string)
     }
-}
 
-#[async_trait]
-impl Channel for SlackChannel {
-    fn name(&self) -> &str {
-        "slack"
+    /// Block Kit layout for an approval prompt: the prompt text plus
+    /// approve / deny / always buttons.
+    ///
+    /// Each button's `value` is the text reply it stands for.
+    fn approval_blocks(text: &str, approval_id: &str) -> serde_json::Value {
+        let button = |label: &str, style: Option<&str>, response: ApprovalResponse| {
+            let mut button = json!({
+                "type": "button",
+                "text": { "type": "plain_text", "text": label },
+                "action_id": format!("approval_{}", label.to_ascii_lowercase()),
+                "value": response.reply_text(approval_id),
+            });
+            if let Some(style) = style {
+                button["style"] = json!(style);
+            }
+            button
+        };
+        json!([
+            { "type": "section", "text": { "type": "mrkdwn", "text": text } },
+            {
+                "type": "actions",
+                "block_id": format!("approval_{approval_id}"),
+                "elements": [
+                    button("Approve", Some("primary"), ApprovalResponse::Yes),
+                    button("Deny", Some("danger"), ApprovalResponse::No),
+                    button("Always", None, ApprovalResponse::Always),
+                ]
+            }
+        ])
     }
 
-    async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
-        let mut body = serde_json::json!({
-            "channel": message.recipient,
-            "text": message.content
-        });
-
-        if let Some(ref ts) = message.thread_ts {
-            body["thread_ts"] = serde_json::json!(ts);
+    /// Turn a Socket Mode `block_actions` payload into a channel message
+    /// carrying the pressed button's `value`.
+    fn parse_block_action(&self, payload: &serde_json::Value) -> Option<ChannelMessage> {
+        if payload.get("type").and_then(serde_json::Value::as_str) != Some("block_actions") {
+            return None;
         }
+        let value = payload
+            .get("actions")
+            .and_then(|a| a.get(0))
+            .and_then(|a| a.get("value"))
+            .and_then(serde_json::Value::as_str)?;
+        let user_id = payload
+            .get("user")
+            .and_then(|u| u.get("id"))
+            .and_then(serde_json::Value::as_str)?;
+        if !self.is_user_allowed(user_id) {
+            tracing::warn!("Slack: ignoring button press from unauthorized user: {user_id}");
+            return None;
+        }
+        let channel_id = payload
+            .get("channel")
+            .and_then(|c| c.get("id"))
+            .and_then(serde_json::Value::as_str)?;
+        let message = payload.get("message");
+        let ts = message
+            .and_then(|m| m.get("ts"))
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+        let thread_ts = message
+            .and_then(|m| m.get("thread_ts"))
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string);
+
+        Some(ChannelMessage {
+            id: format!("slack_action_{channel_id}_{ts}_{user_id}"),
+            sender: user_id.to_string(),
+            reply_target: channel_id.to_string(),
+            content: value.to_string(),
+            channel: "slack".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            thread_ts,
+        })
+    }
 
-        let resp = self
+    /// Acknowledge a button press by replacing the prompt with its text alone.
+    async fn acknowledge_block_action(&self, payload: &serde_json::Value) {
+        let Some(response_url) = payload
+            .get("response_url")
+            .and_then(serde_json::Value::as_str)
+        else {
+            return;
+        };
+        let text = payload
+            .get("message")
+            .and_then(|m| m.get("text"))
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("");
+        let _ = self
             .http_client()
-            .post("https://slack.com/api/chat.postMessage")
-            .bearer_auth(&self.bot_token)
-            .json(&body)
+            .post(response_url)
+            .json(&json!({ "replace_original": true, "text": text }))
             .send()
-            .await?;
-
-        let status = resp.status();
-        let body = resp
-            .text()
-            .await
-            .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
-
-        if !status.is_success() {
-            anyhow::bail!("Slack chat.postMessage failed ({status}): {body}");
-        }
+            .await;
+    }
 
-        // Slack returns 200 for most app-level errors; check JSON "ok" field
-        let parsed: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
-        if parsed.get("ok") == Some(&serde_json::Value::Bool(false)) {
-            let err = parsed
+    /// Open a Socket Mode connection and return its WebSocket URL.
+    async fn open_socket_mode_url(&self, app_token: &str) -> anyhow::Result<String> {
+        let resp: serde_json::Value = self
+            .http_client()
+            .post("https://slack.com/api/apps.connections.open")
+            .bearer_auth(app_token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if resp.get("ok") != Some(&serde_json::Value::Bool(true)) {
+            let err = resp
                 .get("error")
                 .and_then(|e| e.as_str())
                 .unwrap_or("unknown");
-            anyhow::bail!("Slack chat.postMessage failed: {err}");
+            anyhow::bail!("Slack apps.connections.open failed: {err}");
         }
+        resp.get("url")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Slack apps.connections.open returned no url"))
+    }
 
-        Ok(())
+    /// Receive approval button presses over Socket Mode, reconnecting as Slack asks.
+    async fn listen_interactions(
+        &self,
+        app_token: &str,
+        tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
+        loop {
+            let ws_url = match self.open_socket_mode_url(app_token).await {
+                Ok(url) => url,
+                Err(e) => {
+                    tracing::warn!("Slack Socket Mode unavailable: {e}");
+                    tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                    continue;
+                }
+            };
+            let (ws_stream, _) = match tokio_tungstenite::connect_async(&ws_url).await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::warn!("Slack Socket Mode connect error: {e}");
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            let (mut write, mut read) = ws_stream.split();
+
+            while let Some(frame) = read.next().await {
+                let text = match frame {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Ping(data)) => {
+                        let _ = write.send(Message::Pong(data)).await;
+                        continue;
+                    }
+                    Ok(Message::Close(_)) | Err(_) => break,
+                    Ok(_) => continue,
+                };
+                let Ok(envelope) = serde_json::from_str::<serde_json::Value>(&text) else {
+                    continue;
+                };
+                if let Some(envelope_id) = envelope
+                    .get("envelope_id")
+                    .and_then(serde_json::Value::as_str)
+                {
+                    let ack = json!({ "envelope_id": envelope_id }).to_string();
+                    if write.send(Message::Text(ack.into())).await.is_err() {
+                        break;
+                    }
+                }
+                match envelope.get("type").and_then(serde_json::Value::as_str) {
+                    Some("disconnect") => break,
+                    Some("interactive") => {
+                        let Some(payload) = envelope.get("payload") else {
+                            continue;
+                        };
+                        if let Some(channel_msg) = self.parse_block_action(payload) {
+                            self.acknowledge_block_action(payload).await;
+                            if tx.send(channel_msg).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            tracing::debug!("Slack Socket Mode connection closed; reconnecting");
+        }
     }
 
-    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+    /// Poll conversations.history for new messages in the configured channel.
+    async fn poll_history(
+        &self,
+        tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
         let channel_id = self
             .channel_id
             .clone()
@@ -193,6 +353,106 @@ impl Channel for SlackChannel {
             }
         }
     }
+}
+
+#[async_trait]
+impl Channel for SlackChannel {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, message: &SendMessage) -> anyhow::Result<()> {
+        let mut body = serde_json::json!({
+            "channel": message.recipient,
+            "text": message.content
+        });
+
+        if let Some(ref ts) = message.thread_ts {
+            body["thread_ts"] = serde_json::json!(ts);
+        }
+
+        let resp = self
+            .http_client()
+            .post("https://slack.com/api/chat.postMessage")
+            .bearer_auth(&self.bot_token)
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+
+        if !status.is_success() {
+            anyhow::bail!("Slack chat.postMessage failed ({status}): {body}");
+        }
+
+        // Slack returns 200 for most app-level errors; check JSON "ok" field
+        let parsed: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+        if parsed.get("ok") == Some(&serde_json::Value::Bool(false)) {
+            let err = parsed
+                .get("error")
+                .and_then(|e| e.as_str())
+                .unwrap_or("unknown");
+            anyhow::bail!("Slack chat.postMessage failed: {err}");
+        }
+
+        Ok(())
+    }
+
+    async fn send_approval_prompt(
+        &self,
+        message: &SendMessage,
+        approval_id: &str,
+    ) -> anyhow::Result<()> {
+        // Button presses only reach us over Socket Mode; without it, keep the
+        // text prompt so replies still work.
+        if self.app_token.is_none() {
+            return self.send(message).await;
+        }
+
+        let mut body = json!({
+            "channel": message.recipient,
+            "text": message.content,
+            "blocks": Self::approval_blocks(&message.content, approval_id),
+        });
+        if let Some(ref ts) = message.thread_ts {
+            body["thread_ts"] = json!(ts);
+        }
+
+        let resp: serde_json::Value = self
+            .http_client()
+            .post("https://slack.com/api/chat.postMessage")
+            .bearer_auth(&self.bot_token)
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if resp.get("ok") != Some(&serde_json::Value::Bool(true)) {
+            let err = resp
+                .get("error")
+                .and_then(|e| e.as_str())
+                .unwrap_or("unknown");
+            anyhow::bail!("Slack send approval prompt failed: {err}");
+        }
+
+        Ok(())
+    }
+
+    async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        match self.app_token.as_deref() {
+            Some(app_token) => {
+                tokio::select! {
+                    result = self.poll_history(tx.clone()) => result,
+                    result = self.listen_interactions(app_token, tx) => result,
+                }
+            }
+            None => self.poll_history(tx).await,
+        }
+    }
 
     async fn health_check(&self) -> bool {
         self.http_client()
@@ -346,4 +606,63 @@ mod tests {
         let thread_ts = SlackChannel::inbound_thread_ts(&msg, "");
         assert_eq!(thread_ts, None);
     }
+
+    #[test]
+    fn approval_blocks_carry_reply_text() {
+        let blocks = SlackChannel::approval_blocks("Approve shell?", "ab12cd34");
+        assert_eq!(blocks[0]["text"]["text"], "Approve shell?");
+        let values: Vec<&str> = blocks[1]["elements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["value"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            vec!["yes ab12cd34", "no ab12cd34", "always ab12cd34"]
+        );
+    }
+
+    #[test]
+    fn parse_block_action_reads_user_channel_and_thread() {
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["U111".into()]);
+        let payload = serde_json::json!({
+            "type": "block_actions",
+            "user": { "id": "U111" },
+            "channel": { "id": "C123" },
+            "message": { "ts": "1700000000.000200", "thread_ts": "1700000000.000100" },
+            "actions": [{ "action_id": "approval_deny", "value": "no ab12cd34" }]
+        });
+        let msg = ch.parse_block_action(&payload).unwrap();
+        assert_eq!(msg.sender, "U111");
+        assert_eq!(msg.reply_target, "C123");
+        assert_eq!(msg.content, "no ab12cd34");
+        assert_eq!(msg.thread_ts.as_deref(), Some("1700000000.000100"));
+    }
+
+    #[test]
+    fn parse_block_action_rejects_unlisted_user_and_other_types() {
+        let ch = SlackChannel::new("xoxb-fake".into(), None, vec!["U111".into()]);
+        let stranger = serde_json::json!({
+            "type": "block_actions",
+            "user": { "id": "U999" },
+            "channel": { "id": "C123" },
+            "actions": [{ "value": "yes ab12cd34" }]
+        });
+        assert!(ch.parse_block_action(&stranger).is_none());
+
+        let shortcut = serde_json::json!({
+            "type": "shortcut",
+            "user": { "id": "U111" },
+            "callback_id": "anything"
+        });
+        assert!(ch.parse_block_action(&shortcut).is_none());
+    }
+
+    #[test]
+    fn blank_app_token_is_ignored() {
+        let ch =
+            SlackChannel::new("xoxb-fake".into(), None, vec![]).with_app_token(Some(" ".into()));
+        assert!(ch.app_token.is_none());
+    }
 }
@@ -1,4 +1,5 @@
 use super::traits::{Channel, ChannelMessage, SendMessage};
+use crate::approval::ApprovalResponse;
 use crate::config::{Config, StreamMode};
 use crate::security::pairing::PairingGuard;
 use anyhow::Context;
@@ -1207,6 +1208,108 @@ Allowlist Telegram username (without '@') or numeric user ID.",
         })
     }
 
+    /// Inline keyboard with approve / deny / always buttons for an approval prompt.
+    ///
+    /// The callback data is the same text a user could type as a reply, so
+    /// button presses and typed answers resolve prompts the same way.
+    fn approval_keyboard(approval_id: &str) -> serde_json::Value {
+        let button = |label: &str, response: ApprovalResponse| {
+            serde_json::json!({
+                "text": label,
+                "callback_data": response.reply_text(approval_id),
+            })
+        };
+        serde_json::json!({
+            "inline_keyboard": [[
+                button("✅ Approve", ApprovalResponse::Yes),
+                button("❌ Deny", ApprovalResponse::No),
+                button("♾️ Always", ApprovalResponse::Always),
+            ]]
+        })
+    }
+
+    /// Turn an inline-button press into a channel message carrying its callback data.
+    fn parse_callback_query(&self, update: &serde_json::Value) -> Option<ChannelMessage> {
+        let callback = update.get("callback_query")?;
+        let data = callback.get("data").and_then(serde_json::Value::as_str)?;
+
+        let (username, sender_id, sender_identity) = Self::extract_sender_info(callback);
+        let mut identities = vec![username.as_str()];
+        if let Some(id) = sender_id.as_deref() {
+            identities.push(id);
+        }
+        if !self.is_any_user_allowed(identities.iter().copied()) {
+            return None;
+        }
+
+        let message = callback.get("message")?;
+        let chat_id = message
+            .get("chat")
+            .and_then(|chat| chat.get("id"))
+            .and_then(serde_json::Value::as_i64)?;
+        let reply_target = match message
+            .get("message_thread_id")
+            .and_then(serde_json::Value::as_i64)
+        {
+            Some(tid) => format!("{chat_id}:{tid}"),
+            None => chat_id.to_string(),
+        };
+        let callback_id = callback
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+
+        Some(ChannelMessage {
+            id: format!("telegram_callback_{callback_id}"),
+            sender: sender_identity,
+            reply_target,
+            content: data.to_string(),
+            channel: "telegram".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            thread_ts: None,
+        })
+    }
+
+    /// Acknowledge a button press and remove the keyboard so it can't be pressed twice.
+    async fn acknowledge_callback_query(&self, update: &serde_json::Value) {
+        let Some(callback) = update.get("callback_query") else {
+            return;
+        };
+
+        if let Some(id) = callback.get("id").and_then(serde_json::Value::as_str) {
+            let _ = self
+                .http_client()
+                .post(self.api_url("answerCallbackQuery"))
+                .json(&serde_json::json!({ "callback_query_id": id }))
+                .send()
+                .await;
+        }
+
+        let message = callback.get("message");
+        let chat_id = message
+            .and_then(|m| m.get("chat"))
+            .and_then(|chat| chat.get("id"))
+            .and_then(serde_json::Value::as_i64);
+        let message_id = message
+            .and_then(|m| m.get("message_id"))
+            .and_then(serde_json::Value::as_i64);
+        if let (Some(chat_id), Some(message_id)) = (chat_id, message_id) {
+            let _ = self
+                .http_client()
+                .post(self.api_url("editMessageReplyMarkup"))
+                .json(&serde_json::json!({
+                    "chat_id": chat_id,
+                    "message_id": message_id,
+                    "reply_markup": { "inline_keyboard": [] },
+                }))
+                .send()
+                .await;
+        }
+    }
+
     /// Download a Telegram photo by file_id, resize to fit within 1024px, and return as base64 data URI.
     async fn resolve_photo_data_uri(&self, file_id: &str) -> anyhow::Result<String> {
         use base64::Engine as _;
@@ -2144,6 +2247,40 @@ impl Channel for TelegramChannel {
         self.send_text_chunks(&content, chat_id, thread_id).await
     }
 
+    async fn send_approval_prompt(
+        &self,
+        message: &SendMessage,
+        approval_id: &str,
+    ) -> anyhow::Result<()> {
+        let (chat_id, thread_id) = match message.recipient.split_once(':') {
+            Some((chat, thread)) => (chat, Some(thread)),
+            None => (message.recipient.as_str(), None),
+        };
+
+        let mut body = serde_json::json!({
+            "chat_id": chat_id,
+            "text": message.content,
+            "reply_markup": Self::approval_keyboard(approval_id),
+        });
+        if let Some(tid) = thread_id {
+            body["message_thread_id"] = serde_json::Value::String(tid.to_string());
+        }
+
+        let resp = self
+            .http_client()
+            .post(self.api_url("sendMessage"))
+            .json(&body)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let err = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Telegram sendMessage (approval prompt) failed ({status}): {err}");
+        }
+
+        Ok(())
+    }
+
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
         let mut offset: i64 = 0;
 
@@ -2165,7 +2302,7 @@ impl Channel for TelegramChannel {
             let body = serde_json::json!({
                 "offset": offset,
                 "timeout": 30,
-                "allowed_updates": ["message"]
+                "allowed_updates": ["message", "callback_query"]
             });
 
             let resp = match self.http_client().post(&url).json(&body).send().await {
@@ -2223,6 +2360,16 @@ Ensure only one `zeroclaw` process is using this bot token."
                         offset = uid + 1;
                     }
 
+                    if update.get("callback_query").is_some() {
+                        if let Some(msg) = self.parse_callback_query(update) {
+                            self.acknowledge_callback_query(update).await;
+                            if tx.send(msg).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                        continue;
+                    }
+
                     let msg = if let Some(m) = self.parse_update_message(update) {
                         m
                     } else if let Some(m) = self.try_parse_voice_message(update).await {
@@ -2601,7 +2748,6 @@ mod tests {
 
         let msg = ch
             .parse_update_message(&update)
-            .map(|(m, _)| m)
             .expect("message should parse");
 
         assert_eq!(msg.sender, "alice");
@@ -2629,7 +2775,6 @@ mod tests {
 
         let msg = ch
             .parse_update_message(&update)
-            .map(|(m, _)| m)
             .expect("numeric allowlist should pass");
 
         assert_eq!(msg.sender, "555");
@@ -2657,7 +2802,6 @@ mod tests {
 
         let msg = ch
             .parse_update_message(&update)
-            .map(|(m, _)| m)
             .expect("message with thread_id should parse");
 
         assert_eq!(msg.sender, "alice");
@@ -3271,7 +3415,6 @@ mod tests {
 
         let parsed = ch
             .parse_update_message(&update)
-            .map(|(m, _)| m)
             .expect("mention should parse");
         assert_eq!(parsed.content, "Hi status please");
 
@@ -3469,6 +3612,60 @@ mod tests {
     // extract_sender_info tests
     // ─────────────────────────────────────────────────────────────────────
 
+    #[test]
+    fn approval_keyboard_buttons_carry_reply_text() {
+        let keyboard = TelegramChannel::approval_keyboard("ab12cd34");
+        let data: Vec<&str> = keyboard["inline_keyboard"][0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["callback_data"].as_str().unwrap())
+            .collect();
+        assert_eq!(data, vec!["yes ab12cd34", "no ab12cd34", "always ab12cd34"]);
+    }
+
+    #[test]
+    fn parse_callback_query_uses_chat_and_thread_of_prompt() {
+        let ch = TelegramChannel::new("token".into(), vec!["alice".into()], false);
+        let update = serde_json::json!({
+            "update_id": 7,
+            "callback_query": {
+                "id": "cb1",
+                "from": { "id": 555, "username": "alice" },
+                "data": "yes ab12cd34",
+                "message": {
+                    "message_id": 40,
+                    "message_thread_id": 9,
+                    "chat": { "id": -100_200_300 }
+                }
+            }
+        });
+
+        let msg = ch
+            .parse_callback_query(&update)
+            .expect("callback should parse");
+        assert_eq!(msg.sender, "alice");
+        assert_eq!(msg.reply_target, "-100200300:9");
+        assert_eq!(msg.content, "yes ab12cd34");
+        assert_eq!(msg.id, "telegram_callback_cb1");
+    }
+
+    #[test]
+    fn parse_callback_query_rejects_unlisted_user() {
+        let ch = TelegramChannel::new("token".into(), vec!["alice".into()], false);
+        let update = serde_json::json!({
+            "update_id": 8,
+            "callback_query": {
+                "id": "cb2",
+                "from": { "id": 777, "username": "mallory" },
+                "data": "yes ab12cd34",
+                "message": { "message_id": 40, "chat": { "id": 1 } }
+            }
+        });
+
+        assert!(ch.parse_callback_query(&update).is_none());
+    }
+
     #[test]
     fn extract_sender_info_with_username() {
         let msg = serde_json::json!({
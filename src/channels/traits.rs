@@ -65,6 +65,20 @@ pub trait Channel: Send + Sync {
     /// Send a message through this channel
     async fn send(&self, message: &SendMessage) -> anyhow::Result<()>;
 
+    /// Send a tool-call approval prompt.
+    ///
+    /// The default sends the prompt as plain text and relies on a `yes` /
+    /// `no` / `always` reply. Channels with interactive components may render
+    /// buttons instead; a press must arrive as a [`ChannelMessage`] whose
+    /// content is the matching `ApprovalResponse::reply_text(approval_id)`.
+    async fn send_approval_prompt(
+        &self,
+        message: &SendMessage,
+        _approval_id: &str,
+    ) -> anyhow::Result<()> {
+        self.send(message).await
+    }
+
     /// Start listening for incoming messages (long-running)
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()>;
 
@@ -210,6 +224,10 @@ mod tests {
             .send(&SendMessage::new("hello", "bob"))
             .await
             .is_ok());
+        assert!(channel
+            .send_approval_prompt(&SendMessage::new("approve?", "bob"), "abc123")
+            .await
+            .is_ok());
     }
 
     #[tokio::test]
@@ -0,0 +1,160 @@
+//! Minimal line diff for `zeroclaw checkpoints diff`.
+
+use std::fmt::Write;
+
+const CONTEXT_LINES: usize = 3;
+/// Above this many line pairs the LCS table gets too large; fall back to a
+/// whole-file replacement.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Line-level edit script from `old` to `new` (longest common subsequence).
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<(Op, usize, usize)> {
+    // Trim the common prefix and suffix so the table only covers the
+    // changed middle.
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut script: Vec<(Op, usize, usize)> = (0..prefix).map(|i| (Op::Keep, i, i)).collect();
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        script.extend((0..old_mid.len()).map(|i| (Op::Remove, prefix + i, prefix)));
+        script.extend((0..new_mid.len()).map(|j| (Op::Add, prefix + old_mid.len(), prefix + j)));
+    } else {
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut table = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                table[i * (m + 1) + j] = if old_mid[i] == new_mid[j] {
+                    table[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    table[(i + 1) * (m + 1) + j].max(table[i * (m + 1) + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                script.push((Op::Keep, prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if i < n
+                && (j == m || table[(i + 1) * (m + 1) + j] >= table[i * (m + 1) + j + 1])
+            {
+                // Prefer removals first so replaced lines read `-old` then `+new`.
+                script.push((Op::Remove, prefix + i, prefix + j));
+                i += 1;
+            } else {
+                script.push((Op::Add, prefix + i, prefix + j));
+                j += 1;
+            }
+        }
+    }
+
+    let old_tail = old.len() - suffix;
+    let new_tail = new.len() - suffix;
+    script.extend((0..suffix).map(|k| (Op::Keep, old_tail + k, new_tail + k)));
+    script
+}
+
+/// Render a unified diff between two texts. Returns an empty string when
+/// they are identical.
+pub fn unified_diff(old_label: &str, new_label: &str, old: &str, new: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let script = edit_script(&old_lines, &new_lines);
+
+    let mut out = format!("--- {old_label}\n+++ {new_label}\n");
+    let changed: Vec<usize> = script
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _, _))| *op != Op::Keep)
+        .map(|(idx, _)| idx)
+        .collect();
+    if changed.is_empty() {
+        // Only line endings differ.
+        out.push_str("(line endings or trailing newline changed)\n");
+        return out;
+    }
+
+    // Group changes whose context windows overlap into hunks.
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &idx in &changed {
+        let start = idx.saturating_sub(CONTEXT_LINES);
+        let end = (idx + CONTEXT_LINES + 1).min(script.len());
+        match groups.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => groups.push((start, end)),
+        }
+    }
+
+    for (start, end) in groups {
+        let slice = &script[start..end];
+        let old_count = slice.iter().filter(|(op, _, _)| *op != Op::Add).count();
+        let new_count = slice.iter().filter(|(op, _, _)| *op != Op::Remove).count();
+        let (_, old_start, new_start) = slice[0];
+        let old_start = if old_count == 0 {
+            old_start
+        } else {
+            old_start + 1
+        };
+        let new_start = if new_count == 0 {
+            new_start
+        } else {
+            new_start + 1
+        };
+        let _ = writeln!(
+            out,
+            "@@ -{old_start},{old_count} +{new_start},{new_count} @@"
+        );
+        for &(op, i, j) in slice {
+            let _ = match op {
+                Op::Keep => writeln!(out, " {}", old_lines[i]),
+                Op::Remove => writeln!(out, "-{}", old_lines[i]),
+                Op::Add => writeln!(out, "+{}", new_lines[j]),
+            };
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff_renders_hunks_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let diff = unified_diff("a/x", "b/x", old, new);
+        assert_eq!(
+            diff,
+            "--- a/x\n+++ b/x\n@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n@@ -9,3 +9,4 @@\n i\n j\n k\n+l\n"
+        );
+    }
+
+    #[test]
+    fn unified_diff_handles_created_and_identical_files() {
+        assert!(unified_diff("a", "b", "same\n", "same\n").is_empty());
+        assert_eq!(
+            unified_diff("/dev/null", "b/x", "", "one\ntwo\n"),
+            "--- /dev/null\n+++ b/x\n@@ -0,0 +1,2 @@\n+one\n+two\n"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Workspace checkpoints.
+//!
+//! Before `file_write`, `file_edit`, `apply_patch` (and, when enabled,
+//! `shell` / `git_operations`) change anything, the previous content of the
+//! affected files is saved under `<workspace>/state/checkpoints`. Everything
+//! changed during one agent turn lands in a single checkpoint, which `/undo`
+//! in the CLI or a channel restores. This module owns the store, the tool
+//! wrapper and the `zeroclaw checkpoints` management commands.
+
+use crate::config::Config;
+use anyhow::Result;
+use console::style;
+use std::fmt::Write as _;
+use std::future::Future;
+
+mod diff;
+mod store;
+mod tool;
+
+#[allow(unused_imports)]
+pub use store::{Before, Checkpoint, CheckpointStore, RestoreReport};
+pub use tool::wrap_tools;
+
+/// Longest prompt excerpt stored with a checkpoint.
+const PROMPT_EXCERPT_CHARS: usize = 80;
+
+/// The agent turn a tool call belongs to.
+#[derive(Debug, Clone)]
+pub struct TurnContext {
+    /// Id of the checkpoint this turn writes to.
+    pub id: String,
+    pub source: String,
+    pub prompt: String,
+}
+
+impl TurnContext {
+    pub fn new(source: &str, prompt: &str) -> Self {
+        let first_line = prompt.lines().next().unwrap_or_default().trim();
+        let mut excerpt: String = first_line.chars().take(PROMPT_EXCERPT_CHARS).collect();
+        if first_line.chars().count() > PROMPT_EXCERPT_CHARS {
+            excerpt.push('…');
+        }
+        Self {
+            id: format!(
+                "{}-{:04x}",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+                rand::random::<u16>()
+            ),
+            source: source.to_string(),
+            prompt: excerpt,
+        }
+    }
+}
+
+tokio::task_local! {
+    static CURRENT_TURN: TurnContext;
+}
+
+/// Run one agent turn so every file change made by its tool calls is grouped
+/// into a single checkpoint attributed to `source`.
+pub async fn with_turn<F: Future>(source: &str, prompt: &str, fut: F) -> F::Output {
+    CURRENT_TURN
+        .scope(TurnContext::new(source, prompt), fut)
+        .await
+}
+
+/// The active turn, or a fresh one per tool call outside [`with_turn`].
+pub(crate) fn current_turn() -> TurnContext {
+    CURRENT_TURN
+        .try_with(Clone::clone)
+        .unwrap_or_else(|_| TurnContext::new("agent", ""))
+}
+
+/// Open the workspace checkpoint store.
+pub fn open_store(config: &Config) -> CheckpointStore {
+    CheckpointStore::new(&config.workspace_dir, &config.checkpoints)
+        .with_audit(crate::security::audit::default_logger(config))
+}
+
+pub fn handle_command(command: crate::CheckpointCommands, config: &Config) -> Result<()> {
+    let store = open_store(config);
+    match command {
+        crate::CheckpointCommands::List { limit } => handle_list(&store, limit),
+        crate::CheckpointCommands::Diff { id, path } => {
+            let checkpoint = store.resolve(&id)?;
+            print!("{}", render_diff(&store, &checkpoint, path.as_deref())?);
+            Ok(())
+        }
+        crate::CheckpointCommands::Restore { id, yes } => handle_restore(&store, &id, yes),
+    }
+}
+
+fn handle_list(store: &CheckpointStore, limit: usize) -> Result<()> {
+    let checkpoints = store.list()?;
+    if checkpoints.is_empty() {
+        println!("No checkpoints.");
+        return Ok(());
+    }
+
+    println!(
+        "Checkpoints (showing {} of {}):\n",
+        limit.min(checkpoints.len()),
+        checkpoints.len()
+    );
+    for checkpoint in checkpoints.iter().take(limit) {
+        let restored = if checkpoint.restored_at.is_some() {
+            " [restored]"
+        } else {
+            ""
+        };
+        println!(
+            "- {} {} {} file(s) via {}{restored}",
+            style(&checkpoint.id).white().bold(),
+            checkpoint.source,
+            checkpoint.files.len() + checkpoint.skipped.len(),
+            checkpoint.tools.join(", "),
+        );
+        if !checkpoint.prompt.is_empty() {
+            println!("    {}", style(&checkpoint.prompt).dim());
+        }
+    }
+    Ok(())
+}
+
+/// Diff each file's checkpointed content against the workspace as it is now.
+fn render_diff(
+    store: &CheckpointStore,
+    checkpoint: &Checkpoint,
+    path: Option<&str>,
+) -> Result<String> {
+    let mut out = String::new();
+    let mut matched = false;
+    for file in &checkpoint.files {
+        if path.is_some_and(|p| p != file.path) {
+            continue;
+        }
+        matched = true;
+        let before = match &file.before {
+            Some(hash) => Some(store.read_blob(hash)?),
+            None => None,
+        };
+        let current = std::fs::read(store.target_path(&file.path)?).ok();
+        if before == current {
+            continue;
+        }
+
+        let (Ok(old), Ok(new)) = (
+            String::from_utf8(before.clone().unwrap_or_default()),
+            String::from_utf8(current.clone().unwrap_or_default()),
+        ) else {
+            let _ = writeln!(out, "Binary file {} differs", file.path);
+            continue;
+        };
+        let old_label = if before.is_some() {
+            format!("a/{}", file.path)
+        } else {
+            "/dev/null".to_string()
+        };
+        let new_label = if current.is_some() {
+            format!("b/{}", file.path)
+        } else {
+            "/dev/null".to_string()
+        };
+        out.push_str(&diff::unified_diff(&old_label, &new_label, &old, &new));
+    }
+    for skipped in &checkpoint.skipped {
+        if path.is_none_or(|p| p == skipped) {
+            matched = true;
+            let _ = writeln!(
+                out,
+                "{skipped}: not snapshotted (larger than max_file_bytes)"
+            );
+        }
+    }
+
+    if let Some(path) = path.filter(|_| !matched) {
+        anyhow::bail!("Checkpoint {} does not include {path}", checkpoint.id);
+    }
+    if out.is_empty() {
+        out.push_str("No differences from the current workspace.\n");
+    }
+    Ok(out)
+}
+
+fn handle_restore(store: &CheckpointStore, id: &str, yes: bool) -> Result<()> {
+    let checkpoint = store.resolve(id)?;
+    if !yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "  Restore {} file(s) from checkpoint {}?",
+                checkpoint.files.len(),
+                checkpoint.id
+            ))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let report = store.restore(&checkpoint, "cli")?;
+    println!("{} {}", style("✓").green().bold(), report.summary());
+    if !checkpoint.skipped.is_empty() {
+        println!(
+            "  Not restored (too large to snapshot): {}",
+            checkpoint.skipped.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CheckpointsConfig;
+    use tempfile::TempDir;
+
+    #[test]
+    fn turn_context_keeps_a_short_first_line_of_the_prompt() {
+        let turn = TurnContext::new("cli", "  fix the build  \nand more details");
+        assert_eq!(turn.prompt, "fix the build");
+        assert_eq!(turn.source, "cli");
+
+        let long = "x".repeat(200);
+        let turn = TurnContext::new("cli", &long);
+        assert_eq!(turn.prompt.chars().count(), PROMPT_EXCERPT_CHARS + 1);
+        assert!(turn.prompt.ends_with('…'));
+    }
+
+    #[tokio::test]
+    async fn current_turn_is_scoped_to_with_turn() {
+        let (first, second) = with_turn("telegram_bob", "hello", async {
+            (current_turn(), current_turn())
+        })
+        .await;
+        assert_eq!(first.source, "telegram_bob");
+        assert_eq!(first.id, second.id);
+        assert_eq!(current_turn().source, "agent");
+    }
+
+    #[test]
+    fn render_diff_compares_checkpoint_with_workspace() {
+        let dir = TempDir::new().unwrap();
+        let store = CheckpointStore::new(dir.path(), &CheckpointsConfig::default());
+        std::fs::write(dir.path().join("notes.md"), "one\nthree\n").unwrap();
+        std::fs::write(dir.path().join("added.txt"), "new\n").unwrap();
+
+        let turn = TurnContext::new("cli", "edit notes");
+        store
+            .record(
+                &turn,
+                "file_write",
+                vec![
+                    ("notes.md".into(), Before::Content(b"one\ntwo\n".to_vec())),
+                    ("added.txt".into(), Before::Absent),
+                    ("huge.bin".into(), Before::TooLarge),
+                ],
+            )
+            .unwrap();
+        let checkpoint = store.resolve(&turn.id[..15]).unwrap();
+
+        let all = render_diff(&store, &checkpoint, None).unwrap();
+        assert!(all.contains("--- a/notes.md\n+++ b/notes.md\n"));
+        assert!(all.contains("-two\n+three\n"));
+        assert!(all.contains("--- /dev/null\n+++ b/added.txt\n"));
+        assert!(all.contains("huge.bin: not snapshotted"));
+
+        let one = render_diff(&store, &checkpoint, Some("added.txt")).unwrap();
+        assert!(!one.contains("notes.md"));
+        assert!(render_diff(&store, &checkpoint, Some("other.txt")).is_err());
+
+        std::fs::write(dir.path().join("notes.md"), "one\ntwo\n").unwrap();
+        let same = render_diff(&store, &checkpoint, Some("notes.md")).unwrap();
+        assert_eq!(same, "No differences from the current workspace.\n");
+    }
+}
@@ -0,0 +1,641 @@
+use crate::config::CheckpointsConfig;
+use crate::security::AuditLogger;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use super::TurnContext;
+
+/// Directories never scanned when tracking shell commands: VCS metadata,
+/// build output and dependency caches. The workspace `state/` directory
+/// (which holds this store) is skipped at the top level only.
+const UNTRACKED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+/// Serializes manifest and index updates across every store in the process.
+static STORE_LOCK: parking_lot::Mutex<()> = parking_lot::Mutex::new(());
+
+/// Pre-change state of one file in a checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileRecord {
+    /// Workspace-relative path with `/` separators.
+    pub path: String,
+    /// Blob hash of the previous content; `None` when the file did not exist.
+    pub before: Option<String>,
+}
+
+/// Files touched during one agent turn, captured before their first change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub id: String,
+    /// Conversation that produced the checkpoint (`cli`, a channel sender key,
+    /// `restore`, ...). `/undo` only considers its own source.
+    pub source: String,
+    /// First line of the user message that started the turn.
+    #[serde(default)]
+    pub prompt: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub files: Vec<FileRecord>,
+    /// Files that changed but were too large to snapshot.
+    #[serde(default)]
+    pub skipped: Vec<String>,
+    #[serde(default)]
+    pub restored_at: Option<DateTime<Utc>>,
+}
+
+/// Previous state of a changed file, as captured by a tool wrapper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Before {
+    Absent,
+    Content(Vec<u8>),
+    /// Already in the blob store (shell tracking).
+    Blob(String),
+    TooLarge,
+}
+
+/// Metadata-only view of a tracked workspace file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub len: u64,
+    pub modified_ns: u128,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    stamp: FileStamp,
+    hash: Option<String>,
+}
+
+/// Workspace state captured before a shell command, used to find what it changed.
+pub struct TreeSnapshot {
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+/// Result of restoring a checkpoint.
+#[derive(Debug, Clone)]
+pub struct RestoreReport {
+    pub checkpoint_id: String,
+    pub restored: Vec<String>,
+    pub removed: Vec<String>,
+    /// Checkpoint holding the pre-restore state, so a restore can be undone.
+    pub backup_id: Option<String>,
+}
+
+impl RestoreReport {
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "Restored checkpoint {}: {} file(s) restored, {} created file(s) removed.",
+            self.checkpoint_id,
+            self.restored.len(),
+            self.removed.len()
+        );
+        if let Some(backup) = &self.backup_id {
+            let _ = write!(
+                out,
+                " Previous state saved as checkpoint {backup} (`zeroclaw checkpoints restore {backup}` to redo)."
+            );
+        }
+        out
+    }
+}
+
+/// Content-addressed checkpoint store under `<workspace>/state/checkpoints`.
+///
+/// File contents live once in `objects/<hh>/<sha256>`; each checkpoint is a
+/// small JSON manifest in `manifests/` pointing at the blobs. Blobs no
+/// manifest (or the shell-tracking index) refers to are removed when old
+/// checkpoints are pruned.
+pub struct CheckpointStore {
+    workspace_dir: PathBuf,
+    root: PathBuf,
+    config: CheckpointsConfig,
+    audit: Option<Arc<AuditLogger>>,
+}
+
+impl CheckpointStore {
+    pub fn new(workspace_dir: &Path, config: &CheckpointsConfig) -> Self {
+        Self {
+            workspace_dir: workspace_dir.to_path_buf(),
+            root: workspace_dir.join("state").join("checkpoints"),
+            config: config.clone(),
+            audit: None,
+        }
+    }
+
+    /// Record checkpoint creation and restores in the security audit log.
+    pub fn with_audit(mut self, audit: Option<Arc<AuditLogger>>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    pub fn config(&self) -> &CheckpointsConfig {
+        &self.config
+    }
+
+    pub fn workspace_dir(&self) -> &Path {
+        &self.workspace_dir
+    }
+
+    // ── Blobs ────────────────────────────────────────────────────────
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        let prefix = hash.get(..2).unwrap_or("00");
+        self.root.join("objects").join(prefix).join(hash)
+    }
+
+    fn put_blob(&self, content: &[u8]) -> Result<String> {
+        let hash = hex::encode(Sha256::digest(content));
+        let path = self.blob_path(&hash);
+        if !path.exists() {
+            let parent = path.parent().context("blob path has no parent")?;
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, content)?;
+            std::fs::rename(&tmp, &path)?;
+        }
+        Ok(hash)
+    }
+
+    pub fn read_blob(&self, hash: &str) -> Result<Vec<u8>> {
+        let path = self.blob_path(hash);
+        std::fs::read(&path).with_context(|| format!("Missing checkpoint blob {hash}"))
+    }
+
+    // ── Manifests ────────────────────────────────────────────────────
+
+    fn manifest_dir(&self) -> PathBuf {
+        self.root.join("manifests")
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.manifest_dir().join(format!("{id}.json"))
+    }
+
+    fn load(&self, id: &str) -> Result<Option<Checkpoint>> {
+        let path = self.manifest_path(id);
+        match std::fs::read_to_string(&path) {
+            Ok(raw) => Ok(Some(serde_json::from_str(&raw).with_context(|| {
+                format!("Corrupt checkpoint manifest {}", path.display())
+            })?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, checkpoint: &Checkpoint) -> Result<()> {
+        let dir = self.manifest_dir();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = self.manifest_path(&checkpoint.id);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(checkpoint)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// All checkpoints, newest first.
+    pub fn list(&self) -> Result<Vec<Checkpoint>> {
+        let dir = self.manifest_dir();
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut checkpoints = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|raw| Ok(serde_json::from_str::<Checkpoint>(&raw)?))
+            {
+                Ok(checkpoint) => checkpoints.push(checkpoint),
+                Err(e) => tracing::warn!("Skipping checkpoint {}: {e}", path.display()),
+            }
+        }
+        checkpoints.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.id.cmp(&a.id))
+        });
+        Ok(checkpoints)
+    }
+
+    /// Look up a checkpoint by exact id or unique prefix.
+    pub fn resolve(&self, id: &str) -> Result<Checkpoint> {
+        if let Some(checkpoint) = self.load(id)? {
+            return Ok(checkpoint);
+        }
+        let mut matches: Vec<Checkpoint> = self
+            .list()?
+            .into_iter()
+            .filter(|c| c.id.starts_with(id))
+            .collect();
+        match matches.len() {
+            0 => bail!("No checkpoint matching '{id}'"),
+            1 => Ok(matches.remove(0)),
+            n => bail!("'{id}' matches {n} checkpoints; use a longer prefix"),
+        }
+    }
+
+    // ── Recording ────────────────────────────────────────────────────
+
+    /// Add pre-change file states to the turn's checkpoint, creating it on
+    /// first use. Files already recorded for the turn keep their earliest
+    /// state. Returns the checkpoint id when anything was recorded.
+    pub fn record(
+        &self,
+        turn: &TurnContext,
+        tool: &str,
+        changes: Vec<(String, Before)>,
+    ) -> Result<Option<String>> {
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        let _guard = STORE_LOCK.lock();
+        let existing = self.load(&turn.id)?;
+        let created = existing.is_none();
+        let mut checkpoint = existing.unwrap_or_else(|| Checkpoint {
+            id: turn.id.clone(),
+            source: turn.source.clone(),
+            prompt: turn.prompt.clone(),
+            created_at: Utc::now(),
+            tools: Vec::new(),
+            files: Vec::new(),
+            skipped: Vec::new(),
+            restored_at: None,
+        });
+
+        for (path, before) in changes {
+            if checkpoint.files.iter().any(|f| f.path == path) || checkpoint.skipped.contains(&path)
+            {
+                continue;
+            }
+            let before = match before {
+                Before::Absent => None,
+                Before::Content(content) => Some(self.put_blob(&content)?),
+                Before::Blob(hash) => Some(hash),
+                Before::TooLarge => {
+                    checkpoint.skipped.push(path);
+                    continue;
+                }
+            };
+            checkpoint.files.push(FileRecord { path, before });
+        }
+        if !checkpoint.tools.iter().any(|t| t == tool) {
+            checkpoint.tools.push(tool.to_string());
+        }
+        self.save(&checkpoint)?;
+
+        if created {
+            self.audit(
+                &checkpoint.source,
+                &format!(
+                    "checkpoint create {} ({} file(s), tool {tool})",
+                    checkpoint.id,
+                    checkpoint.files.len()
+                ),
+            );
+            self.prune_locked()?;
+        }
+        Ok(Some(checkpoint.id))
+    }
+
+    /// Drop checkpoints beyond `max_checkpoints` and garbage-collect blobs.
+    pub fn prune(&self) -> Result<usize> {
+        let _guard = STORE_LOCK.lock();
+        self.prune_locked()
+    }
+
+    fn prune_locked(&self) -> Result<usize> {
+        let checkpoints = self.list()?;
+        let keep = self.config.max_checkpoints.max(1);
+        let mut removed = 0;
+        for checkpoint in checkpoints.iter().skip(keep) {
+            std::fs::remove_file(self.manifest_path(&checkpoint.id))?;
+            removed += 1;
+        }
+        if removed == 0 {
+            return Ok(0);
+        }
+
+        let mut referenced: BTreeSet<String> = checkpoints
+            .iter()
+            .take(keep)
+            .flat_map(|c| c.files.iter().filter_map(|f| f.before.clone()))
+            .collect();
+        referenced.extend(self.load_index().into_values().filter_map(|e| e.hash));
+
+        let objects = self.root.join("objects");
+        for prefix in std::fs::read_dir(&objects).into_iter().flatten().flatten() {
+            for blob in std::fs::read_dir(prefix.path())
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                let name = blob.file_name().to_string_lossy().into_owned();
+                if !referenced.contains(&name) {
+                    let _ = std::fs::remove_file(blob.path());
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    // ── Shell tracking ───────────────────────────────────────────────
+
+    fn index_path(&self) -> PathBuf {
+        self.root.join("index.json")
+    }
+
+    fn load_index(&self) -> BTreeMap<String, IndexEntry> {
+        std::fs::read_to_string(self.index_path())
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_index(&self, index: &BTreeMap<String, IndexEntry>) -> Result<()> {
+        std::fs::create_dir_all(&self.root)?;
+        let tmp = self.index_path().with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(index)?)?;
+        std::fs::rename(&tmp, self.index_path())?;
+        Ok(())
+    }
+
+    /// Stamp every tracked workspace file. Returns `None` when the workspace
+    /// holds more than `max_tracked_files` files.
+    fn scan(&self) -> Option<BTreeMap<String, FileStamp>> {
+        let mut stamps = BTreeMap::new();
+        let mut pending = vec![(self.workspace_dir.clone(), String::new())];
+        while let Some((dir, prefix)) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let rel = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{prefix}/{name}")
+                };
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                if file_type.is_dir() {
+                    let untracked = UNTRACKED_DIRS.contains(&name.as_str())
+                        || (prefix.is_empty() && name == "state");
+                    if !untracked {
+                        pending.push((entry.path(), rel));
+                    }
+                } else if file_type.is_file() {
+                    let Ok(meta) = entry.metadata() else {
+                        continue;
+                    };
+                    stamps.insert(rel, stamp(&meta));
+                    if stamps.len() > self.config.max_tracked_files {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some(stamps)
+    }
+
+    /// Snapshot the workspace before a shell command. Files whose size or
+    /// mtime changed since the last snapshot are copied into the blob store.
+    pub fn snapshot_tree(&self) -> Result<Option<TreeSnapshot>> {
+        let Some(stamps) = self.scan() else {
+            tracing::warn!(
+                "Workspace has more than {} files; shell commands are not checkpointed",
+                self.config.max_tracked_files
+            );
+            return Ok(None);
+        };
+
+        let _guard = STORE_LOCK.lock();
+        let mut index = self.load_index();
+        index.retain(|path, _| stamps.contains_key(path));
+        for (path, file_stamp) in &stamps {
+            let fresh = index.get(path).is_some_and(|entry| {
+                entry.stamp == *file_stamp
+                    && entry
+                        .hash
+                        .as_ref()
+                        .is_none_or(|hash| self.blob_path(hash).exists())
+            });
+            if fresh {
+                continue;
+            }
+            let hash = if file_stamp.len > self.config.max_file_bytes {
+                None
+            } else {
+                match std::fs::read(self.workspace_dir.join(path)) {
+                    Ok(content) => Some(self.put_blob(&content)?),
+                    Err(_) => continue,
+                }
+            };
+            index.insert(
+                path.clone(),
+                IndexEntry {
+                    stamp: *file_stamp,
+                    hash,
+                },
+            );
+        }
+        self.save_index(&index)?;
+        Ok(Some(TreeSnapshot { entries: index }))
+    }
+
+    /// Compare the workspace with `snapshot` and describe what changed.
+    pub fn tree_changes(&self, snapshot: &TreeSnapshot) -> Vec<(String, Before)> {
+        let Some(now) = self.scan() else {
+            return Vec::new();
+        };
+        let mut changes = Vec::new();
+        for (path, entry) in &snapshot.entries {
+            if now.get(path) != Some(&entry.stamp) {
+                let before = match &entry.hash {
+                    Some(hash) => Before::Blob(hash.clone()),
+                    None => Before::TooLarge,
+                };
+                changes.push((path.clone(), before));
+            }
+        }
+        for path in now.keys() {
+            if !snapshot.entries.contains_key(path) {
+                changes.push((path.clone(), Before::Absent));
+            }
+        }
+        changes
+    }
+
+    // ── Restoring ────────────────────────────────────────────────────
+
+    /// The newest not-yet-restored checkpoint from `source`.
+    pub fn latest_for(&self, source: &str) -> Result<Option<Checkpoint>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .find(|c| c.source == source && c.restored_at.is_none()))
+    }
+
+    /// Put every file in `checkpoint` back to its recorded state. The current
+    /// contents are saved first as a `restore` checkpoint.
+    pub fn restore(&self, checkpoint: &Checkpoint, actor: &str) -> Result<RestoreReport> {
+        let mut backup = Vec::new();
+        let mut planned = Vec::new();
+        for file in &checkpoint.files {
+            let target = self.target_path(&file.path)?;
+            self.ensure_parent_in_workspace(&target, &file.path)?;
+            let before = match &file.before {
+                Some(hash) => Some(self.read_blob(hash)?),
+                None => None,
+            };
+            let current = match std::fs::read(&target) {
+                Ok(content) => Before::Content(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Before::Absent,
+                Err(e) => return Err(e).with_context(|| format!("Failed to read {}", file.path)),
+            };
+            backup.push((file.path.clone(), current));
+            planned.push((file.path.clone(), target, before));
+        }
+
+        let backup_turn = TurnContext::new("restore", &format!("restore {}", checkpoint.id));
+        let backup_id = self.record(&backup_turn, "restore", backup)?;
+
+        let mut report = RestoreReport {
+            checkpoint_id: checkpoint.id.clone(),
+            restored: Vec::new(),
+            removed: Vec::new(),
+            backup_id,
+        };
+        for (path, target, before) in planned {
+            match before {
+                Some(content) => {
+                    self.ensure_parent_in_workspace(&target, &path)?;
+                    if let Some(parent) = target.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    // Replace a symlink created since the checkpoint rather
+                    // than writing through it.
+                    if std::fs::symlink_metadata(&target).is_ok_and(|m| m.file_type().is_symlink())
+                    {
+                        std::fs::remove_file(&target)?;
+                    }
+                    std::fs::write(&target, content)
+                        .with_context(|| format!("Failed to restore {path}"))?;
+                    report.restored.push(path);
+                }
+                None => {
+                    self.ensure_parent_in_workspace(&target, &path)?;
+                    match std::fs::remove_file(&target) {
+                        Ok(()) => {}
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                        Err(e) => {
+                            return Err(e).with_context(|| format!("Failed to remove {path}"))
+                        }
+                    }
+                    report.removed.push(path);
+                }
+            }
+        }
+
+        {
+            let _guard = STORE_LOCK.lock();
+            if let Some(mut stored) = self.load(&checkpoint.id)? {
+                stored.restored_at = Some(Utc::now());
+                self.save(&stored)?;
+            }
+        }
+        self.audit(
+            actor,
+            &format!(
+                "checkpoint restore {} ({} file(s))",
+                checkpoint.id,
+                checkpoint.files.len()
+            ),
+        );
+        Ok(report)
+    }
+
+    /// Restore the newest checkpoint from `source`, for `/undo`.
+    pub fn undo(&self, source: &str) -> Result<Option<RestoreReport>> {
+        match self.latest_for(source)? {
+            Some(checkpoint) => Ok(Some(self.restore(&checkpoint, source)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Workspace path for a recorded file, refusing anything that would
+    /// leave the workspace.
+    pub fn target_path(&self, rel: &str) -> Result<PathBuf> {
+        let path = Path::new(rel);
+        if path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            bail!("Refusing checkpoint path outside the workspace: {rel}");
+        }
+        Ok(self.workspace_dir.join(path))
+    }
+
+    /// Refuse `target` when its nearest existing parent directory resolves
+    /// outside the workspace, so a directory replaced by a symlink since the
+    /// checkpoint cannot redirect a restore elsewhere.
+    fn ensure_parent_in_workspace(&self, target: &Path, rel: &str) -> Result<()> {
+        let workspace = std::fs::canonicalize(&self.workspace_dir)
+            .context("Failed to resolve workspace directory")?;
+        let mut existing = target
+            .parent()
+            .with_context(|| format!("Invalid checkpoint path: {rel}"))?;
+        while !existing.exists() {
+            existing = existing
+                .parent()
+                .with_context(|| format!("Invalid checkpoint path: {rel}"))?;
+        }
+        let resolved = std::fs::canonicalize(existing)
+            .with_context(|| format!("Failed to resolve parent of {rel}"))?;
+        if !resolved.starts_with(&workspace) {
+            bail!(
+                "Refusing to restore {rel}: {} resolves outside the workspace",
+                resolved.display()
+            );
+        }
+        Ok(())
+    }
+
+    fn audit(&self, actor: &str, action: &str) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let event = crate::security::AuditEvent::new(crate::security::AuditEventType::Checkpoint)
+            .with_actor(actor.to_string(), None, None)
+            .with_action(action.to_string(), "low".to_string(), true, true);
+        if let Err(e) = audit.log(&event) {
+            tracing::warn!("Failed to write checkpoint audit event: {e}");
+        }
+    }
+}
+
+pub(super) fn stamp(meta: &std::fs::Metadata) -> FileStamp {
+    FileStamp {
+        len: meta.len(),
+        modified_ns: meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos()),
+    }
+}
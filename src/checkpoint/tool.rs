@@ -0,0 +1,443 @@
+use super::store::{stamp, Before, CheckpointStore, FileStamp};
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use std::path::{Component, Path};
+use std::sync::Arc;
+
+/// How a wrapped tool's file changes are discovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tracking {
+    /// The tool names the files it writes in its arguments.
+    Paths,
+    /// The tool can touch anything; compare the workspace before and after.
+    Tree,
+}
+
+fn tracking_for(name: &str, track_shell: bool) -> Option<Tracking> {
+    match name {
+        "file_write" | "file_edit" | "apply_patch" => Some(Tracking::Paths),
+        "shell" | "git_operations" if track_shell => Some(Tracking::Tree),
+        _ => None,
+    }
+}
+
+/// Wrap the file-modifying tools in `tools` so their changes are checkpointed.
+pub fn wrap_tools(tools: Vec<Arc<dyn Tool>>, store: Arc<CheckpointStore>) -> Vec<Arc<dyn Tool>> {
+    let track_shell = store.config().track_shell;
+    tools
+        .into_iter()
+        .map(|tool| match tracking_for(tool.name(), track_shell) {
+            Some(tracking) => Arc::new(CheckpointedTool {
+                inner: tool,
+                store: store.clone(),
+                tracking,
+            }) as Arc<dyn Tool>,
+            None => tool,
+        })
+        .collect()
+}
+
+/// Saves the previous content of files a tool changes into the current
+/// turn's checkpoint. Checkpoint failures are logged and never fail the tool.
+struct CheckpointedTool {
+    inner: Arc<dyn Tool>,
+    store: Arc<CheckpointStore>,
+    tracking: Tracking,
+}
+
+/// File state captured before the wrapped tool ran.
+enum Captured {
+    Absent,
+    Content(Vec<u8>),
+    TooLarge(FileStamp),
+    /// Symlinks and other non-regular files are left alone.
+    Untracked,
+}
+
+fn workspace_relative(workspace: &Path, raw: &str) -> Option<String> {
+    let path = Path::new(raw);
+    let rel = if path.is_absolute() {
+        path.strip_prefix(workspace).ok()?
+    } else {
+        path
+    };
+    let mut parts = Vec::new();
+    for component in rel.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn touched_paths(tool: &str, args: &serde_json::Value) -> Vec<String> {
+    match tool {
+        "apply_patch" => crate::tools::apply_patch::touched_paths(args),
+        _ => args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .map(|path| vec![path.to_string()])
+            .unwrap_or_default(),
+    }
+}
+
+fn capture(store: &CheckpointStore, rel: &str) -> Captured {
+    let Ok(path) = store.target_path(rel) else {
+        return Captured::Untracked;
+    };
+    match std::fs::symlink_metadata(&path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Captured::Absent,
+        Err(_) => Captured::Untracked,
+        Ok(meta) if !meta.file_type().is_file() => Captured::Untracked,
+        Ok(meta) if meta.len() > store.config().max_file_bytes => Captured::TooLarge(stamp(&meta)),
+        Ok(_) => match std::fs::read(&path) {
+            Ok(content) => Captured::Content(content),
+            Err(_) => Captured::Untracked,
+        },
+    }
+}
+
+/// Compare a captured state with the file now; `Some` when it changed.
+fn change_since(store: &CheckpointStore, rel: &str, captured: Captured) -> Option<Before> {
+    let path = store.target_path(rel).ok()?;
+    let now = std::fs::symlink_metadata(&path).ok();
+    match captured {
+        Captured::Untracked => None,
+        Captured::Absent => now.map(|_| Before::Absent),
+        Captured::TooLarge(before) => {
+            (now.as_ref().map(stamp) != Some(before)).then_some(Before::TooLarge)
+        }
+        Captured::Content(content) => {
+            let unchanged = now.is_some_and(|meta| meta.is_file())
+                && std::fs::read(&path).is_ok_and(|current| current == content);
+            (!unchanged).then_some(Before::Content(content))
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for CheckpointedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.inner.parameters_schema()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let turn = super::current_turn();
+        let tool = self.inner.name().to_string();
+
+        let changes = match self.tracking {
+            Tracking::Paths => {
+                let paths: Vec<String> = touched_paths(&tool, &args)
+                    .iter()
+                    .filter_map(|raw| workspace_relative(self.store.workspace_dir(), raw))
+                    .collect();
+                let store = self.store.clone();
+                let captured = tokio::task::spawn_blocking(move || {
+                    paths
+                        .into_iter()
+                        .map(|rel| {
+                            let state = capture(&store, &rel);
+                            (rel, state)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await?;
+
+                let result = self.inner.execute(args).await;
+
+                let store = self.store.clone();
+                let changes = tokio::task::spawn_blocking(move || {
+                    captured
+                        .into_iter()
+                        .filter_map(|(rel, state)| {
+                            change_since(&store, &rel, state).map(|before| (rel, before))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await?;
+                (result, changes)
+            }
+            Tracking::Tree => {
+                let store = self.store.clone();
+                let snapshot = tokio::task::spawn_blocking(move || store.snapshot_tree())
+                    .await?
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Checkpoint snapshot before {tool} failed: {e}");
+                        None
+                    });
+
+                let result = self.inner.execute(args).await;
+
+                let changes = match snapshot {
+                    Some(snapshot) => {
+                        let store = self.store.clone();
+                        tokio::task::spawn_blocking(move || store.tree_changes(&snapshot)).await?
+                    }
+                    None => Vec::new(),
+                };
+                (result, changes)
+            }
+        };
+        let (result, changes) = changes;
+
+        if !changes.is_empty() {
+            let store = self.store.clone();
+            let recorded =
+                tokio::task::spawn_blocking(move || store.record(&turn, &tool, changes)).await?;
+            if let Err(e) = recorded {
+                tracing::warn!("Failed to record checkpoint for {}: {e}", self.name());
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CheckpointsConfig;
+    use crate::security::{AutonomyLevel, SecurityPolicy};
+    use crate::tools::{ApplyPatchTool, FileEditTool, FileWriteTool, ShellTool};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn security(dir: &TempDir) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: dir.path().to_path_buf(),
+            allowed_commands: vec!["mv".into()],
+            ..SecurityPolicy::default()
+        })
+    }
+
+    fn wrapped(
+        dir: &TempDir,
+        config: &CheckpointsConfig,
+    ) -> (Vec<Arc<dyn Tool>>, Arc<CheckpointStore>) {
+        let security = security(dir);
+        let store = Arc::new(CheckpointStore::new(dir.path(), config));
+        let tools: Vec<Arc<dyn Tool>> = vec![
+            Arc::new(FileWriteTool::new(security.clone())),
+            Arc::new(FileEditTool::new(security.clone())),
+            Arc::new(ApplyPatchTool::new(security.clone())),
+            Arc::new(ShellTool::new(
+                security,
+                Arc::new(crate::runtime::NativeRuntime::new()),
+            )),
+        ];
+        (wrap_tools(tools, store.clone()), store)
+    }
+
+    fn tool<'a>(tools: &'a [Arc<dyn Tool>], name: &str) -> &'a Arc<dyn Tool> {
+        tools.iter().find(|t| t.name() == name).unwrap()
+    }
+
+    fn read(dir: &TempDir, rel: &str) -> Option<String> {
+        std::fs::read_to_string(dir.path().join(rel)).ok()
+    }
+
+    #[tokio::test]
+    async fn turn_changes_share_one_checkpoint_and_undo_restores_them() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "original\n").unwrap();
+        let (tools, store) = wrapped(&dir, &CheckpointsConfig::default());
+
+        super::super::with_turn("cli", "edit things", async {
+            tool(&tools, "file_edit")
+                .execute(json!({"path": "a.txt", "old_string": "original", "new_string": "edited"}))
+                .await
+                .unwrap();
+            tool(&tools, "file_write")
+                .execute(json!({"path": "new/b.txt", "content": "fresh"}))
+                .await
+                .unwrap();
+            // A second change to a.txt keeps the earliest content.
+            tool(&tools, "file_write")
+                .execute(json!({"path": "a.txt", "content": "rewritten\n"}))
+                .await
+                .unwrap();
+            // Failed edits record nothing.
+            tool(&tools, "file_edit")
+                .execute(json!({"path": "missing.txt", "old_string": "x", "new_string": "y"}))
+                .await
+                .unwrap();
+        })
+        .await;
+
+        let checkpoints = store.list().unwrap();
+        assert_eq!(checkpoints.len(), 1);
+        let checkpoint = &checkpoints[0];
+        assert_eq!(checkpoint.source, "cli");
+        assert_eq!(checkpoint.prompt, "edit things");
+        assert_eq!(checkpoint.tools, vec!["file_edit", "file_write"]);
+        let paths: Vec<&str> = checkpoint.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.txt", "new/b.txt"]);
+
+        let report = store.undo("cli").unwrap().unwrap();
+        assert_eq!(report.restored, vec!["a.txt"]);
+        assert_eq!(report.removed, vec!["new/b.txt"]);
+        assert_eq!(read(&dir, "a.txt").as_deref(), Some("original\n"));
+        assert!(read(&dir, "new/b.txt").is_none());
+
+        // The restore is itself checkpointed so it can be redone, but /undo
+        // for the same source does not pick it up.
+        let backup = store.resolve(report.backup_id.as_deref().unwrap()).unwrap();
+        assert_eq!(backup.source, "restore");
+        assert!(store.undo("cli").unwrap().is_none());
+        store.restore(&backup, "test").unwrap();
+        assert_eq!(read(&dir, "a.txt").as_deref(), Some("rewritten\n"));
+        assert_eq!(read(&dir, "new/b.txt").as_deref(), Some("fresh"));
+    }
+
+    #[tokio::test]
+    async fn apply_patch_paths_are_checkpointed() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("old.txt"), "one\n").unwrap();
+        let (tools, store) = wrapped(&dir, &CheckpointsConfig::default());
+
+        super::super::with_turn("telegram_alice", "rename it", async {
+            let result = tool(&tools, "apply_patch")
+                .execute(json!({"edits": [
+                    {"action": "rename", "path": "old.txt", "new_path": "renamed.txt"}
+                ]}))
+                .await
+                .unwrap();
+            assert!(result.success, "{:?}", result.error);
+        })
+        .await;
+
+        assert!(store.undo("cli").unwrap().is_none());
+        store.undo("telegram_alice").unwrap().unwrap();
+        assert_eq!(read(&dir, "old.txt").as_deref(), Some("one\n"));
+        assert!(read(&dir, "renamed.txt").is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn shell_changes_are_found_by_scanning_the_workspace() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("keep.txt"), "keep\n").unwrap();
+        std::fs::write(dir.path().join("doomed.txt"), "doomed\n").unwrap();
+        let (tools, store) = wrapped(&dir, &CheckpointsConfig::default());
+
+        super::super::with_turn("cli", "tidy up", async {
+            let result = tool(&tools, "shell")
+                .execute(json!({"command": "mv doomed.txt moved.txt", "approved": true}))
+                .await
+                .unwrap();
+            assert!(result.success, "{:?}", result.error);
+        })
+        .await;
+
+        let checkpoint = store.latest_for("cli").unwrap().unwrap();
+        assert_eq!(checkpoint.tools, vec!["shell"]);
+        let paths: Vec<&str> = checkpoint.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["doomed.txt", "moved.txt"]);
+
+        store.undo("cli").unwrap();
+        assert_eq!(read(&dir, "doomed.txt").as_deref(), Some("doomed\n"));
+        assert!(read(&dir, "moved.txt").is_none());
+        assert_eq!(read(&dir, "keep.txt").as_deref(), Some("keep\n"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn restore_refuses_parents_symlinked_out_of_the_workspace() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.txt"), "original\n").unwrap();
+        let (tools, store) = wrapped(&dir, &CheckpointsConfig::default());
+
+        super::super::with_turn("cli", "edit sub", async {
+            for (path, content) in [("sub/a.txt", "edited\n"), ("sub/b.txt", "new\n")] {
+                tool(&tools, "file_write")
+                    .execute(json!({"path": path, "content": content}))
+                    .await
+                    .unwrap();
+            }
+        })
+        .await;
+
+        // Swap the directory for a symlink to somewhere outside the workspace.
+        std::fs::remove_dir_all(dir.path().join("sub")).unwrap();
+        std::fs::write(outside.path().join("a.txt"), "outside a\n").unwrap();
+        std::fs::write(outside.path().join("b.txt"), "outside b\n").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("sub")).unwrap();
+
+        let err = store.undo("cli").unwrap_err().to_string();
+        assert!(err.contains("outside the workspace"), "{err}");
+        assert_eq!(
+            std::fs::read_to_string(outside.path().join("a.txt")).unwrap(),
+            "outside a\n"
+        );
+        assert!(outside.path().join("b.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn retention_prunes_old_checkpoints_and_blobs() {
+        let dir = TempDir::new().unwrap();
+        let config = CheckpointsConfig {
+            max_checkpoints: 2,
+            ..CheckpointsConfig::default()
+        };
+        let (tools, store) = wrapped(&dir, &config);
+
+        for round in 0..4 {
+            super::super::with_turn("cli", &format!("round {round}"), async {
+                tool(&tools, "file_write")
+                    .execute(json!({"path": "f.txt", "content": format!("v{}", round + 1)}))
+                    .await
+                    .unwrap();
+            })
+            .await;
+        }
+
+        let checkpoints = store.list().unwrap();
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].prompt, "round 3");
+        assert_eq!(checkpoints[1].prompt, "round 2");
+        let blobs = walk_files(&dir.path().join("state/checkpoints/objects"));
+        assert_eq!(blobs, 2, "only blobs for the kept checkpoints remain");
+    }
+
+    #[tokio::test]
+    async fn calls_outside_a_turn_get_their_own_checkpoint() {
+        let dir = TempDir::new().unwrap();
+        let (tools, store) = wrapped(&dir, &CheckpointsConfig::default());
+        for name in ["a.txt", "b.txt"] {
+            tool(&tools, "file_write")
+                .execute(json!({"path": name, "content": "x"}))
+                .await
+                .unwrap();
+        }
+        let checkpoints = store.list().unwrap();
+        assert_eq!(checkpoints.len(), 2);
+        assert!(checkpoints.iter().all(|c| c.source == "agent"));
+    }
+
+    fn walk_files(dir: &Path) -> usize {
+        std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| {
+                if entry.path().is_dir() {
+                    walk_files(&entry.path())
+                } else {
+                    1
+                }
+            })
+            .sum()
+    }
+}
@@ -36,6 +36,7 @@ const SUPPORTED_PROXY_SERVICE_KEYS: &[&str] = &[
     "tool.composio",
     "tool.http_request",
     "tool.pushover",
+    "tool.web_fetch",
     "memory.embeddings",
     "tunnel.custom",
     "transcription.groq",
@@ -110,6 +111,10 @@ pub struct Config {
     #[serde(default)]
     pub model_routes: Vec<ModelRouteConfig>,
 
+    /// Routing policies — pick a model route per request by capability, price and latency.
+    #[serde(default)]
+    pub routing_policies: Vec<RoutingPolicyConfig>,
+
     /// Embedding routing rules — route `hint:<name>` to specific provider+model combos.
     #[serde(default)]
     pub embedding_routes: Vec<EmbeddingRouteConfig>,
@@ -126,6 +131,14 @@ pub struct Config {
     #[serde(default)]
     pub cron: CronConfig,
 
+    /// Persistent conversation session storage (`[sessions]`).
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+
+    /// MCP client servers whose tools are imported (`[mcp]`).
+    #[serde(default)]
+    pub mcp: McpConfig,
+
     /// Channel configurations: Telegram, Discord, Slack, etc. (`[channels_config]`).
     #[serde(default)]
     pub channels_config: ChannelsConfig,
@@ -162,6 +175,14 @@ pub struct Config {
     #[serde(default)]
     pub http_request: HttpRequestConfig,
 
+    /// Background process tool configuration (`[process]`).
+    #[serde(default)]
+    pub process: ProcessConfig,
+
+    /// Workspace checkpoint configuration (`[checkpoints]`).
+    #[serde(default)]
+    pub checkpoints: CheckpointsConfig,
+
     /// Multimodal (image) handling configuration (`[multimodal]`).
     #[serde(default)]
     pub multimodal: MultimodalConfig,
@@ -170,6 +191,10 @@ pub struct Config {
     #[serde(default)]
     pub web_search: WebSearchConfig,
 
+    /// Web page reader configuration (`[web_fetch]`).
+    #[serde(default)]
+    pub web_fetch: WebFetchConfig,
+
     /// Proxy configuration for outbound HTTP/HTTPS/SOCKS5 traffic (`[proxy]`).
     #[serde(default)]
     pub proxy: ProxyConfig,
@@ -372,6 +397,10 @@ pub struct AgentConfig {
     /// Maximum conversation history messages retained per session. Default: `50`.
     #[serde(default = "default_agent_max_history_messages")]
     pub max_history_messages: usize,
+    /// Override the model context window, in tokens, used to budget prompts
+    /// and history. Default: unset (looked up from the model name).
+    #[serde(default)]
+    pub context_window_tokens: Option<usize>,
     /// Enable parallel tool execution within a single iteration. Default: `false`.
     #[serde(default)]
     pub parallel_tools: bool,
@@ -398,6 +427,7 @@ impl Default for AgentConfig {
             compact_context: false,
             max_tool_iterations: default_agent_max_tool_iterations(),
             max_history_messages: default_agent_max_history_messages(),
+            context_window_tokens: None,
             parallel_tools: false,
             tool_dispatcher: default_agent_tool_dispatcher(),
         }
@@ -548,13 +578,18 @@ pub struct CostConfig {
     #[serde(default)]
     pub allow_override: bool,
 
+    /// Model to fall back to when a limit would be exceeded (default: none).
+    /// When unset, over-budget requests are refused instead.
+    #[serde(default)]
+    pub downgrade_model: Option<String>,
+
     /// Per-model pricing (USD per 1M tokens)
     #[serde(default)]
     pub prices: std::collections::HashMap<String, ModelPricing>,
 }
 
 /// Per-model pricing entry (USD per 1M tokens).
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ModelPricing {
     /// Input price per 1M tokens
     #[serde(default)]
@@ -563,6 +598,26 @@ pub struct ModelPricing {
     /// Output price per 1M tokens
     #[serde(default)]
     pub output: f64,
+
+    /// Price per 1M input tokens read from the prompt cache (default: `input`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+
+    /// Price per 1M input tokens written to the prompt cache (default: `input`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPricing {
+    /// Effective price for cache reads.
+    pub fn cache_read_price(&self) -> f64 {
+        self.cache_read.unwrap_or(self.input)
+    }
+
+    /// Effective price for cache writes.
+    pub fn cache_write_price(&self) -> f64 {
+        self.cache_write.unwrap_or(self.input)
+    }
 }
 
 fn default_daily_limit() -> f64 {
@@ -585,6 +640,7 @@ impl Default for CostConfig {
             monthly_limit_usd: default_monthly_limit(),
             warn_at_percent: default_warn_percent(),
             allow_override: false,
+            downgrade_model: None,
             prices: get_default_pricing(),
         }
     }
@@ -600,6 +656,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 3.0,
             output: 15.0,
+            cache_read: Some(0.3),
+            cache_write: Some(3.75),
         },
     );
     prices.insert(
@@ -607,6 +665,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 15.0,
             output: 75.0,
+            cache_read: Some(1.5),
+            cache_write: Some(18.75),
         },
     );
     prices.insert(
@@ -614,6 +674,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 3.0,
             output: 15.0,
+            cache_read: Some(0.3),
+            cache_write: Some(3.75),
         },
     );
     prices.insert(
@@ -621,6 +683,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.25,
             output: 1.25,
+            cache_read: Some(0.025),
+            cache_write: Some(0.3125),
         },
     );
 
@@ -630,6 +694,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 5.0,
             output: 15.0,
+            cache_read: Some(2.5),
+            cache_write: None,
         },
     );
     prices.insert(
@@ -637,6 +703,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.15,
             output: 0.60,
+            cache_read: Some(0.075),
+            cache_write: None,
         },
     );
     prices.insert(
@@ -644,6 +712,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 15.0,
             output: 60.0,
+            cache_read: Some(7.5),
+            cache_write: None,
         },
     );
 
@@ -653,6 +723,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 0.10,
             output: 0.40,
+            cache_read: None,
+            cache_write: None,
         },
     );
     prices.insert(
@@ -660,6 +732,8 @@ fn get_default_pricing() -> std::collections::HashMap<String, ModelPricing> {
         ModelPricing {
             input: 1.25,
             output: 5.0,
+            cache_read: None,
+            cache_write: None,
         },
     );
 
@@ -725,6 +799,7 @@ impl Default for PeripheralBoardConfig {
 /// Gateway server configuration (`[gateway]` section).
 ///
 /// Controls the HTTP gateway for webhook and pairing endpoints.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GatewayConfig {
     /// Gateway port (default: 3000)
@@ -767,6 +842,11 @@ pub struct GatewayConfig {
     /// Maximum distinct idempotency keys retained in memory.
     #[serde(default = "default_gateway_idempotency_max_keys")]
     pub idempotency_max_keys: usize,
+
+    /// Serve zeroclaw's tools and memory over MCP at `POST /mcp`.
+    /// Disabled by default; requests are subject to pairing auth.
+    #[serde(default)]
+    pub mcp_enabled: bool,
 }
 
 fn default_gateway_port() -> u16 {
@@ -815,6 +895,7 @@ impl Default for GatewayConfig {
             rate_limit_max_keys: default_gateway_rate_limit_max_keys(),
             idempotency_ttl_secs: default_idempotency_ttl_secs(),
             idempotency_max_keys: default_gateway_idempotency_max_keys(),
+            mcp_enabled: false,
         }
     }
 }
@@ -1002,6 +1083,100 @@ fn default_http_timeout_secs() -> u64 {
     30
 }
 
+// ── Background processes ─────────────────────────────────────────
+
+/// Background process tool configuration (`[process]` section).
+///
+/// Commands started by the `process` tool pass the same command policy as
+/// `shell`; these limits apply per agent session.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProcessConfig {
+    /// Enable the `process` tool (default: true)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Maximum concurrently running processes per session (default: 4)
+    #[serde(default = "default_process_max_running")]
+    pub max_running: usize,
+    /// Kill a process after this many seconds; 0 disables the limit (default: 3600)
+    #[serde(default = "default_process_max_runtime_secs")]
+    pub max_runtime_secs: u64,
+    /// Output kept per stream; older output is dropped (default: 1MB)
+    #[serde(default = "default_process_output_limit_bytes")]
+    pub output_limit_bytes: usize,
+}
+
+fn default_process_max_running() -> usize {
+    4
+}
+
+fn default_process_max_runtime_secs() -> u64 {
+    3600
+}
+
+fn default_process_output_limit_bytes() -> usize {
+    1_048_576
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_running: default_process_max_running(),
+            max_runtime_secs: default_process_max_runtime_secs(),
+            output_limit_bytes: default_process_output_limit_bytes(),
+        }
+    }
+}
+
+// ── Checkpoints ──────────────────────────────────────────────────
+
+/// Workspace checkpoint configuration (`[checkpoints]` section).
+///
+/// Before a file-modifying tool runs, the files it is about to change are
+/// copied into `<workspace>/state/checkpoints` so the turn can be undone.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckpointsConfig {
+    /// Record checkpoints for file-modifying tools (default: true)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Checkpoints kept before the oldest are pruned (default: 50)
+    #[serde(default = "default_checkpoints_max_checkpoints")]
+    pub max_checkpoints: usize,
+    /// Files larger than this are listed but not snapshotted (default: 5MB)
+    #[serde(default = "default_checkpoints_max_file_bytes")]
+    pub max_file_bytes: u64,
+    /// Scan the workspace around `shell` and `git_operations` calls (default: true)
+    #[serde(default = "default_true")]
+    pub track_shell: bool,
+    /// Skip shell tracking when the workspace holds more files than this (default: 10000)
+    #[serde(default = "default_checkpoints_max_tracked_files")]
+    pub max_tracked_files: usize,
+}
+
+fn default_checkpoints_max_checkpoints() -> usize {
+    50
+}
+
+fn default_checkpoints_max_file_bytes() -> u64 {
+    5_242_880
+}
+
+fn default_checkpoints_max_tracked_files() -> usize {
+    10_000
+}
+
+impl Default for CheckpointsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_checkpoints: default_checkpoints_max_checkpoints(),
+            max_file_bytes: default_checkpoints_max_file_bytes(),
+            track_shell: true,
+            max_tracked_files: default_checkpoints_max_tracked_files(),
+        }
+    }
+}
+
 // ── Web search ───────────────────────────────────────────────────
 
 /// Web search tool configuration (`[web_search]` section).
@@ -1048,6 +1223,63 @@ impl Default for WebSearchConfig {
     }
 }
 
+// ── Web fetch ────────────────────────────────────────────────────
+
+/// Web page reader configuration (`[web_fetch]` section).
+///
+/// Hosts are checked against `[http_request].allowed_domains`, and requests
+/// go through the `tool.web_fetch` proxy service.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebFetchConfig {
+    /// Enable the `web_fetch` tool
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maximum bytes downloaded per page (default: 5MB)
+    #[serde(default = "default_web_fetch_max_response_size")]
+    pub max_response_size: usize,
+    /// Request timeout in seconds (default: 30)
+    #[serde(default = "default_http_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Characters returned per call; longer pages are read with `offset` (default: 20000)
+    #[serde(default = "default_web_fetch_max_chars")]
+    pub max_chars: usize,
+    /// How long fetched pages are reused; 0 disables the cache (default: 900)
+    #[serde(default = "default_web_fetch_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// Pages kept in the cache (default: 32)
+    #[serde(default = "default_web_fetch_cache_max_entries")]
+    pub cache_max_entries: usize,
+}
+
+fn default_web_fetch_max_response_size() -> usize {
+    5_000_000
+}
+
+fn default_web_fetch_max_chars() -> usize {
+    20_000
+}
+
+fn default_web_fetch_cache_ttl_secs() -> u64 {
+    900
+}
+
+fn default_web_fetch_cache_max_entries() -> usize {
+    32
+}
+
+impl Default for WebFetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_response_size: default_web_fetch_max_response_size(),
+            timeout_secs: default_http_timeout_secs(),
+            max_chars: default_web_fetch_max_chars(),
+            cache_ttl_secs: default_web_fetch_cache_ttl_secs(),
+            cache_max_entries: default_web_fetch_cache_max_entries(),
+        }
+    }
+}
+
 // ── Proxy ───────────────────────────────────────────────────────
 
 /// Proxy application scope — determines which outbound traffic uses the proxy.
@@ -1831,6 +2063,19 @@ pub struct AutonomyConfig {
     /// Tools that always require interactive approval, even after "Always".
     #[serde(default = "default_always_ask")]
     pub always_ask: Vec<String>,
+
+    /// Seconds to wait for a chat-channel approval reply before denying the call.
+    #[serde(default = "default_approval_timeout_secs")]
+    pub approval_timeout_secs: u64,
+
+    /// Send chat-channel approval prompts to this operator chat instead of the
+    /// originating conversation, as `<channel>:<recipient>` (e.g. `telegram:123456789`).
+    #[serde(default)]
+    pub approval_chat: Option<String>,
+}
+
+fn default_approval_timeout_secs() -> u64 {
+    120
 }
 
 fn default_auto_approve() -> Vec<String> {
@@ -1886,6 +2131,8 @@ impl Default for AutonomyConfig {
             block_high_risk_commands: true,
             auto_approve: default_auto_approve(),
             always_ask: default_always_ask(),
+            approval_timeout_secs: default_approval_timeout_secs(),
+            approval_chat: None,
         }
     }
 }
@@ -1895,7 +2142,7 @@ impl Default for AutonomyConfig {
 /// Runtime adapter configuration (`[runtime]` section).
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RuntimeConfig {
-    /// Runtime kind (`native` | `docker`).
+    /// Runtime kind (`native` | `docker` | `wasm`).
     #[serde(default = "default_runtime_kind")]
     pub kind: String,
 
@@ -1903,12 +2150,21 @@ pub struct RuntimeConfig {
     #[serde(default)]
     pub docker: DockerRuntimeConfig,
 
+    /// WASM sandbox settings for `*.wasm` tool plugins (`[runtime.wasm]`).
+    #[serde(default)]
+    pub wasm: WasmRuntimeConfig,
+
     /// Global reasoning override for providers that expose explicit controls.
     /// - `None`: provider default behavior
     /// - `Some(true)`: request reasoning/thinking when supported
     /// - `Some(false)`: disable reasoning/thinking when supported
     #[serde(default)]
     pub reasoning_enabled: Option<bool>,
+
+    /// Token budget for extended thinking on providers that take one
+    /// (Anthropic). Unset leaves thinking off.
+    #[serde(default)]
+    pub reasoning_budget_tokens: Option<u32>,
 }
 
 /// Docker runtime configuration (`[runtime.docker]` section).
@@ -1943,6 +2199,62 @@ pub struct DockerRuntimeConfig {
     pub allowed_workspace_roots: Vec<String>,
 }
 
+/// WASM sandbox configuration (`[runtime.wasm]` section).
+///
+/// Modules in `tools_dir` are registered as tools when the binary is built
+/// with the `runtime-wasm` feature.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WasmRuntimeConfig {
+    /// Directory (relative to workspace) containing `*.wasm` tool modules.
+    #[serde(default = "default_wasm_tools_dir")]
+    pub tools_dir: String,
+
+    /// Instruction fuel budget per invocation.
+    #[serde(default = "default_wasm_fuel_limit")]
+    pub fuel_limit: u64,
+
+    /// Linear memory ceiling per invocation in MB.
+    #[serde(default = "default_wasm_memory_limit_mb")]
+    pub memory_limit_mb: u64,
+
+    /// Allow modules to read workspace files (reserved; not yet exposed).
+    #[serde(default)]
+    pub allow_workspace_read: bool,
+
+    /// Allow modules to write workspace files (reserved; not yet exposed).
+    #[serde(default)]
+    pub allow_workspace_write: bool,
+
+    /// Hosts modules may reach over HTTP (reserved; not yet exposed).
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+}
+
+fn default_wasm_tools_dir() -> String {
+    "tools/wasm".into()
+}
+
+fn default_wasm_fuel_limit() -> u64 {
+    1_000_000
+}
+
+fn default_wasm_memory_limit_mb() -> u64 {
+    64
+}
+
+impl Default for WasmRuntimeConfig {
+    fn default() -> Self {
+        Self {
+            tools_dir: default_wasm_tools_dir(),
+            fuel_limit: default_wasm_fuel_limit(),
+            memory_limit_mb: default_wasm_memory_limit_mb(),
+            allow_workspace_read: false,
+            allow_workspace_write: false,
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
 fn default_runtime_kind() -> String {
     "native".into()
 }
@@ -1982,7 +2294,9 @@ impl Default for RuntimeConfig {
         Self {
             kind: default_runtime_kind(),
             docker: DockerRuntimeConfig::default(),
+            wasm: WasmRuntimeConfig::default(),
             reasoning_enabled: None,
+            reasoning_budget_tokens: None,
         }
     }
 }
@@ -2011,6 +2325,15 @@ pub struct ReliabilityConfig {
     /// Example: `{ "claude-opus-4-20250514" = ["claude-sonnet-4-20250514", "gpt-4o"] }`
     #[serde(default)]
     pub model_fallbacks: std::collections::HashMap<String, Vec<String>>,
+    /// Consecutive failures that open a provider/model circuit breaker (`0` disables).
+    #[serde(default = "default_circuit_failure_threshold")]
+    pub circuit_failure_threshold: u32,
+    /// Seconds an open circuit rejects calls before a half-open probe.
+    #[serde(default = "default_circuit_open_secs")]
+    pub circuit_open_secs: u64,
+    /// Reorder the fallback chain by rolling error rate and latency.
+    #[serde(default = "default_true")]
+    pub health_ordering: bool,
     /// Initial backoff for channel/daemon restarts.
     #[serde(default = "default_channel_backoff_secs")]
     pub channel_initial_backoff_secs: u64,
@@ -2033,6 +2356,14 @@ fn default_provider_backoff_ms() -> u64 {
     500
 }
 
+fn default_circuit_failure_threshold() -> u32 {
+    5
+}
+
+fn default_circuit_open_secs() -> u64 {
+    30
+}
+
 fn default_channel_backoff_secs() -> u64 {
     2
 }
@@ -2057,6 +2388,9 @@ impl Default for ReliabilityConfig {
             fallback_providers: Vec::new(),
             api_keys: Vec::new(),
             model_fallbacks: std::collections::HashMap::new(),
+            circuit_failure_threshold: default_circuit_failure_threshold(),
+            circuit_open_secs: default_circuit_open_secs(),
+            health_ordering: true,
             channel_initial_backoff_secs: default_channel_backoff_secs(),
             channel_max_backoff_secs: default_channel_backoff_max_secs(),
             scheduler_poll_secs: default_scheduler_poll_secs(),
@@ -2131,6 +2465,80 @@ pub struct ModelRouteConfig {
     /// Optional API key override for this route's provider
     #[serde(default)]
     pub api_key: Option<String>,
+    /// Context window in tokens (default: inferred from the model name)
+    #[serde(default)]
+    pub context_window: Option<usize>,
+    /// Whether the model accepts images (default: the provider's vision support)
+    #[serde(default)]
+    pub vision: Option<bool>,
+    /// Whether the model supports native tool calling (default: the provider's support)
+    #[serde(default)]
+    pub native_tools: Option<bool>,
+}
+
+/// Pick one of the `[[model_routes]]` per request instead of a fixed model.
+///
+/// ```toml
+/// [[routing_policies]]
+/// name = "vision-cheap"
+/// prefer = "cheapest"
+/// require_vision = true
+/// min_context_tokens = 128000
+///
+/// [[routing_policies]]
+/// name = "auto"
+/// candidates = ["fast", "reasoning"]
+/// escalate_to = "reasoning"
+/// ```
+///
+/// Usage: pass `hint:vision-cheap` as the model parameter, or target the
+/// policy name from a `[query_classification]` rule.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RoutingPolicyConfig {
+    /// Policy name, used as a route hint
+    pub name: String,
+    /// Route hints to choose from, in order of preference (default: all routes)
+    #[serde(default)]
+    pub candidates: Vec<String>,
+    /// How to rank routes that meet the requirements
+    #[serde(default)]
+    pub prefer: RoutingPreference,
+    /// Only consider routes that accept images
+    #[serde(default)]
+    pub require_vision: bool,
+    /// Only consider routes with native tool calling
+    #[serde(default)]
+    pub require_tools: bool,
+    /// Minimum context window in tokens
+    #[serde(default)]
+    pub min_context_tokens: Option<usize>,
+    /// Prefer the cheapest route once less than this share (percent) of the
+    /// daily `[cost]` budget remains
+    #[serde(default)]
+    pub low_budget_percent: Option<u8>,
+    /// Route hint to switch to after consecutive failed tool rounds
+    #[serde(default)]
+    pub escalate_to: Option<String>,
+    /// Failed tool rounds that trigger `escalate_to`
+    #[serde(default = "default_escalate_after")]
+    pub escalate_after: usize,
+}
+
+fn default_escalate_after() -> usize {
+    2
+}
+
+/// Ranking used by a routing policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingPreference {
+    /// First candidate in configured order
+    #[default]
+    First,
+    /// Lowest estimated request cost from `[cost.prices]`
+    Cheapest,
+    /// Lowest observed latency
+    Fastest,
 }
 
 // ── Embedding routing ───────────────────────────────────────────
@@ -2245,6 +2653,131 @@ impl Default for CronConfig {
     }
 }
 
+// ── Sessions ────────────────────────────────────────────────────
+
+/// Persistent conversation session configuration (`[sessions]` section).
+///
+/// Channel conversation history is written through to `memory/sessions.db`
+/// in the workspace and rehydrated when channels start, so senders keep
+/// their context across daemon restarts.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionsConfig {
+    /// Persist per-sender channel conversation history. Default: `true`.
+    #[serde(default = "default_true")]
+    pub persist_channel_history: bool,
+    /// Maximum messages retained per session. Default: `50`.
+    #[serde(default = "default_session_max_messages")]
+    pub max_messages_per_session: usize,
+    /// Maximum number of sessions retained; least recently active sessions are
+    /// pruned first. `0` disables the cap. Default: `1000`.
+    #[serde(default = "default_session_max_sessions")]
+    pub max_sessions: usize,
+    /// Drop sessions idle for longer than this many days. `0` keeps sessions
+    /// forever. Default: `30`.
+    #[serde(default = "default_session_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_session_max_messages() -> usize {
+    50
+}
+
+fn default_session_max_sessions() -> usize {
+    1000
+}
+
+fn default_session_retention_days() -> u32 {
+    30
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            persist_channel_history: true,
+            max_messages_per_session: default_session_max_messages(),
+            max_sessions: default_session_max_sessions(),
+            retention_days: default_session_retention_days(),
+        }
+    }
+}
+
+// ── MCP ─────────────────────────────────────────────────────────
+
+/// Model Context Protocol client configuration (`[mcp]` section).
+///
+/// Each entry under `[mcp.servers.<name>]` is launched (stdio) or connected
+/// to (streamable HTTP) at startup, and its tools are registered as
+/// `<name>__<tool>`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct McpConfig {
+    /// MCP servers keyed by name.
+    #[serde(default)]
+    pub servers: HashMap<String, McpServerConfig>,
+}
+
+/// MCP server transport.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum McpTransport {
+    /// Launch `command` and speak JSON-RPC over stdin/stdout.
+    #[default]
+    Stdio,
+    /// POST JSON-RPC to `url` (streamable HTTP transport).
+    Http,
+}
+
+/// A single MCP server (`[mcp.servers.<name>]`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct McpServerConfig {
+    /// Set to `false` to keep the entry without connecting. Default: `true`.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Transport: `"stdio"` (default) or `"http"`.
+    #[serde(default)]
+    pub transport: McpTransport,
+    /// Executable to launch (stdio transport).
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Arguments for `command` (stdio transport).
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables for the server process (stdio transport).
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Endpoint URL (http transport).
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Extra HTTP headers, e.g. `Authorization` (http transport).
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Per-request timeout in seconds. Default: `30`.
+    #[serde(default = "default_mcp_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Only register these remote tools (empty = all).
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+}
+
+fn default_mcp_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for McpServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            transport: McpTransport::default(),
+            command: None,
+            args: Vec::new(),
+            env: HashMap::new(),
+            url: None,
+            headers: HashMap::new(),
+            timeout_secs: default_mcp_timeout_secs(),
+            allowed_tools: Vec::new(),
+        }
+    }
+}
+
 // ── Tunnel ──────────────────────────────────────────────────────
 
 /// Tunnel configuration for exposing the gateway publicly (`[tunnel]` section).
@@ -2368,6 +2901,10 @@ pub struct ChannelsConfig {
     /// Default: 300s for on-device LLMs (Ollama) which are slower than cloud APIs.
     #[serde(default = "default_channel_message_timeout_secs")]
     pub message_timeout_secs: u64,
+    /// How model reasoning is shown, keyed by channel name (e.g.
+    /// `telegram = "spoiler"`). Channels not listed hide it.
+    #[serde(default)]
+    pub reasoning_display: HashMap<String, ReasoningDisplay>,
 }
 
 fn default_channel_message_timeout_secs() -> u64 {
@@ -2396,10 +2933,24 @@ impl Default for ChannelsConfig {
             qq: None,
             nostr: None,
             message_timeout_secs: default_channel_message_timeout_secs(),
+            reasoning_display: HashMap::new(),
         }
     }
 }
 
+/// How a channel shows model reasoning alongside the answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningDisplay {
+    /// Send only the answer (default).
+    #[default]
+    Hide,
+    /// Prefix the answer with a collapsible `<details>` block.
+    Collapsed,
+    /// Prefix the answer with the reasoning behind `||spoiler||` markup.
+    Spoiler,
+}
+
 /// Streaming mode for channels that support progressive message updates.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "lowercase")]
@@ -2949,9 +3500,12 @@ impl Default for Config {
             agent: AgentConfig::default(),
             skills: SkillsConfig::default(),
             model_routes: Vec::new(),
+            routing_policies: Vec::new(),
             embedding_routes: Vec::new(),
             heartbeat: HeartbeatConfig::default(),
             cron: CronConfig::default(),
+            sessions: SessionsConfig::default(),
+            mcp: McpConfig::default(),
             channels_config: ChannelsConfig::default(),
             memory: MemoryConfig::default(),
             storage: StorageConfig::default(),
@@ -2961,8 +3515,11 @@ impl Default for Config {
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
+            process: ProcessConfig::default(),
+            checkpoints: CheckpointsConfig::default(),
             multimodal: MultimodalConfig::default(),
             web_search: WebSearchConfig::default(),
+            web_fetch: WebFetchConfig::default(),
             proxy: ProxyConfig::default(),
             identity: IdentityConfig::default(),
             cost: CostConfig::default(),
@@ -4106,6 +4663,8 @@ default_temperature = 0.7
                 block_high_risk_commands: true,
                 auto_approve: vec!["file_read".into()],
                 always_ask: vec![],
+                approval_timeout_secs: 120,
+                approval_chat: None,
             },
             runtime: RuntimeConfig {
                 kind: "docker".into(),
@@ -4115,6 +4674,7 @@ default_temperature = 0.7
             scheduler: SchedulerConfig::default(),
             skills: SkillsConfig::default(),
             model_routes: Vec::new(),
+            routing_policies: Vec::new(),
             embedding_routes: Vec::new(),
             query_classification: QueryClassificationConfig::default(),
             heartbeat: HeartbeatConfig {
@@ -4122,6 +4682,8 @@ default_temperature = 0.7
                 interval_minutes: 15,
             },
             cron: CronConfig::default(),
+            sessions: SessionsConfig::default(),
+            mcp: McpConfig::default(),
             channels_config: ChannelsConfig {
                 cli: true,
                 telegram: Some(TelegramConfig {
@@ -4149,6 +4711,7 @@ default_temperature = 0.7
                 qq: None,
                 nostr: None,
                 message_timeout_secs: 300,
+                reasoning_display: HashMap::new(),
             },
             memory: MemoryConfig::default(),
             storage: StorageConfig::default(),
@@ -4158,8 +4721,11 @@ default_temperature = 0.7
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
+            process: ProcessConfig::default(),
+            checkpoints: CheckpointsConfig::default(),
             multimodal: MultimodalConfig::default(),
             web_search: WebSearchConfig::default(),
+            web_fetch: WebFetchConfig::default(),
             proxy: ProxyConfig::default(),
             agent: AgentConfig::default(),
             identity: IdentityConfig::default(),
@@ -4316,10 +4882,13 @@ tool_dispatcher = "xml"
             scheduler: SchedulerConfig::default(),
             skills: SkillsConfig::default(),
             model_routes: Vec::new(),
+            routing_policies: Vec::new(),
             embedding_routes: Vec::new(),
             query_classification: QueryClassificationConfig::default(),
             heartbeat: HeartbeatConfig::default(),
             cron: CronConfig::default(),
+            sessions: SessionsConfig::default(),
+            mcp: McpConfig::default(),
             channels_config: ChannelsConfig::default(),
             memory: MemoryConfig::default(),
             storage: StorageConfig::default(),
@@ -4329,8 +4898,11 @@ tool_dispatcher = "xml"
             secrets: SecretsConfig::default(),
             browser: BrowserConfig::default(),
             http_request: HttpRequestConfig::default(),
+            process: ProcessConfig::default(),
+            checkpoints: CheckpointsConfig::default(),
             multimodal: MultimodalConfig::default(),
             web_search: WebSearchConfig::default(),
+            web_fetch: WebFetchConfig::default(),
             proxy: ProxyConfig::default(),
             agent: AgentConfig::default(),
             identity: IdentityConfig::default(),
@@ -4698,6 +5270,7 @@ allowed_users = ["@ops:matrix.org"]
             qq: None,
             nostr: None,
             message_timeout_secs: 300,
+            reasoning_display: HashMap::new(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -4909,6 +5482,7 @@ channel_id = "C123"
             qq: None,
             nostr: None,
             message_timeout_secs: 300,
+            reasoning_display: HashMap::new(),
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -4924,6 +5498,32 @@ channel_id = "C123"
         assert!(c.whatsapp.is_none());
     }
 
+    #[test]
+    async fn reasoning_settings_parse_from_toml() {
+        let toml_str = r#"
+workspace_dir = "/tmp/workspace"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[runtime]
+reasoning_budget_tokens = 4096
+
+[channels_config]
+cli = true
+
+[channels_config.reasoning_display]
+telegram = "spoiler"
+slack = "collapsed"
+"#;
+
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.runtime.reasoning_budget_tokens, Some(4096));
+        let display = &parsed.channels_config.reasoning_display;
+        assert_eq!(display.get("telegram"), Some(&ReasoningDisplay::Spoiler));
+        assert_eq!(display.get("slack"), Some(&ReasoningDisplay::Collapsed));
+        assert!(!display.contains_key("discord"));
+    }
+
     #[test]
     async fn channels_config_default_has_no_nextcloud_talk() {
         let c = ChannelsConfig::default();
@@ -4993,6 +5593,7 @@ channel_id = "C123"
             rate_limit_max_keys: 2048,
             idempotency_ttl_secs: 600,
             idempotency_max_keys: 4096,
+            mcp_enabled: false,
         };
         let toml_str = toml::to_string(&g).unwrap();
         let parsed: GatewayConfig = toml::from_str(&toml_str).unwrap();
@@ -1,8 +1,135 @@
+pub mod provider;
 pub mod tracker;
 pub mod types;
 
 // Re-exported for potential external use (public API)
 #[allow(unused_imports)]
+pub use provider::{
+    lookup_pricing, resolve_pricing, wrap_provider, BudgetExceededError, CostGuard,
+};
+#[allow(unused_imports)]
 pub use tracker::CostTracker;
 #[allow(unused_imports)]
 pub use types::{BudgetCheck, CostRecord, CostSummary, ModelStats, TokenUsage, UsagePeriod};
+
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, NaiveDate, Utc};
+use std::collections::HashMap;
+
+pub fn handle_command(command: crate::CostCommands, config: &Config) -> Result<()> {
+    if !config.cost.enabled {
+        println!("Note: cost tracking is disabled; set [cost] enabled = true to record usage.\n");
+    }
+
+    let tracker = CostTracker::new(config.cost.clone(), &config.workspace_dir)?;
+
+    match command {
+        crate::CostCommands::Summary => handle_summary(&tracker, config),
+        crate::CostCommands::Daily { date } => {
+            let date = match date {
+                Some(raw) => NaiveDate::parse_from_str(&raw, "%Y-%m-%d")
+                    .with_context(|| format!("Invalid --date '{raw}', expected YYYY-MM-DD"))?,
+                None => Utc::now().date_naive(),
+            };
+            let total = tracker.get_daily_cost(date)?;
+            println!("Spend for {date}: ${total:.4}");
+            print_model_stats(&tracker.get_model_stats_for_date(date)?);
+            Ok(())
+        }
+        crate::CostCommands::Monthly { month } => {
+            let (year, month) = match month {
+                Some(raw) => parse_month(&raw)?,
+                None => {
+                    let today = Utc::now().date_naive();
+                    (today.year(), today.month())
+                }
+            };
+            let total = tracker.get_monthly_cost(year, month)?;
+            println!("Spend for {year}-{month:02}: ${total:.4}");
+            print_model_stats(&tracker.get_model_stats_for_month(year, month)?);
+            Ok(())
+        }
+        crate::CostCommands::ByModel { period } => {
+            let today = Utc::now().date_naive();
+            let stats = match period.trim().to_ascii_lowercase().as_str() {
+                "day" => tracker.get_model_stats_for_date(today)?,
+                "month" => tracker.get_model_stats_for_month(today.year(), today.month())?,
+                "all" => tracker.get_model_stats_all_time()?,
+                other => bail!("Invalid --period '{other}', expected day, month, or all"),
+            };
+            println!("Spend by model ({}):", period.trim().to_ascii_lowercase());
+            print_model_stats(&stats);
+            Ok(())
+        }
+    }
+}
+
+fn handle_summary(tracker: &CostTracker, config: &Config) -> Result<()> {
+    let summary = tracker.get_summary()?;
+    println!(
+        "Today:      ${:.4} / ${:.2} ({:.0}%)",
+        summary.daily_cost_usd,
+        config.cost.daily_limit_usd,
+        percent_of(summary.daily_cost_usd, config.cost.daily_limit_usd)
+    );
+    println!(
+        "This month: ${:.4} / ${:.2} ({:.0}%)",
+        summary.monthly_cost_usd,
+        config.cost.monthly_limit_usd,
+        percent_of(summary.monthly_cost_usd, config.cost.monthly_limit_usd)
+    );
+    println!("Warn at:    {}%", config.cost.warn_at_percent);
+    if let Some(model) = config.cost.downgrade_model.as_deref() {
+        println!("Downgrade:  {model}");
+    }
+    Ok(())
+}
+
+fn parse_month(raw: &str) -> Result<(i32, u32)> {
+    let first_day = NaiveDate::parse_from_str(&format!("{}-01", raw.trim()), "%Y-%m-%d")
+        .with_context(|| format!("Invalid --month '{raw}', expected YYYY-MM"))?;
+    Ok((first_day.year(), first_day.month()))
+}
+
+fn percent_of(value: f64, limit: f64) -> f64 {
+    if limit > 0.0 {
+        value / limit * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn print_model_stats(stats: &HashMap<String, ModelStats>) {
+    if stats.is_empty() {
+        println!("  (no recorded usage)");
+        return;
+    }
+
+    let mut rows: Vec<&ModelStats> = stats.values().collect();
+    rows.sort_by(|a, b| {
+        b.cost_usd
+            .partial_cmp(&a.cost_usd)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+
+    for row in rows {
+        println!(
+            "  {:<48} ${:>10.4}  {:>10} tokens  {:>6} requests",
+            row.model, row.cost_usd, row.total_tokens, row.request_count
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_month_accepts_year_month() {
+        assert_eq!(parse_month("2026-02").unwrap(), (2026, 2));
+        assert!(parse_month("2026-13").is_err());
+        assert!(parse_month("February").is_err());
+    }
+}
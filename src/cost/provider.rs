@@ -0,0 +1,789 @@
+use super::tracker::CostTracker;
+use super::types::{BudgetCheck, TokenUsage, UsagePeriod};
+use crate::config::schema::{CostConfig, ModelPricing};
+use crate::config::Config;
+use crate::providers::traits::{
+    ChatMessage, ChatRequest, ChatResponse, ProviderCapabilities, StreamAccumulator, StreamChunk,
+    StreamError, StreamEvent, StreamOptions, StreamResult, TokenUsage as ProviderTokenUsage,
+    ToolsPayload,
+};
+use crate::providers::Provider;
+use crate::tools::ToolSpec;
+use async_trait::async_trait;
+use futures_util::{stream, StreamExt};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Rough characters-per-token ratio used when a provider reports no usage.
+const CHARS_PER_TOKEN_ESTIMATE: usize = 4;
+
+/// Returned when a provider call is refused because a spending limit is reached.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+    "cost_budget_exceeded period={period} spent=${current_usd:.4} limit=${limit_usd:.2}: request refused by [cost] budget"
+)]
+pub struct BudgetExceededError {
+    pub period: &'static str,
+    pub current_usd: f64,
+    pub limit_usd: f64,
+}
+
+fn period_label(period: UsagePeriod) -> &'static str {
+    match period {
+        UsagePeriod::Session => "session",
+        UsagePeriod::Day => "day",
+        UsagePeriod::Month => "month",
+    }
+}
+
+/// Process-wide trackers keyed by workspace so every provider in one process
+/// shares the same daily/monthly aggregates.
+fn tracker_registry() -> &'static Mutex<HashMap<PathBuf, Arc<CostTracker>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<PathBuf, Arc<CostTracker>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn shared_tracker(config: &Config) -> anyhow::Result<Arc<CostTracker>> {
+    let mut registry = tracker_registry().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(existing) = registry.get(&config.workspace_dir) {
+        return Ok(Arc::clone(existing));
+    }
+    let tracker = Arc::new(CostTracker::new(
+        config.cost.clone(),
+        &config.workspace_dir,
+    )?);
+    registry.insert(config.workspace_dir.clone(), Arc::clone(&tracker));
+    Ok(tracker)
+}
+
+/// Budget enforcement handle shared by every provider built from one config.
+///
+/// Cheap to clone; wraps providers in a [`CostTrackingProvider`].
+#[derive(Clone)]
+pub struct CostGuard {
+    tracker: Arc<CostTracker>,
+    config: Arc<CostConfig>,
+}
+
+impl CostGuard {
+    /// Build a guard when `[cost] enabled = true`. Storage failures disable
+    /// tracking with a warning rather than blocking the runtime.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if !config.cost.enabled {
+            return None;
+        }
+
+        match shared_tracker(config) {
+            Ok(tracker) => Some(Self {
+                tracker,
+                config: Arc::new(config.cost.clone()),
+            }),
+            Err(e) => {
+                tracing::warn!("Cost tracking disabled: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn tracker(&self) -> &Arc<CostTracker> {
+        &self.tracker
+    }
+
+    /// Wrap a provider so every call is budget-checked and recorded.
+    pub fn wrap(&self, provider_name: &str, inner: Box<dyn Provider>) -> Box<dyn Provider> {
+        Box::new(CostTrackingProvider {
+            inner,
+            provider_name: provider_name.to_string(),
+            guard: self.clone(),
+        })
+    }
+
+    /// Share of the daily limit not yet spent, from `0.0` to `1.0`.
+    pub fn daily_budget_remaining(&self) -> anyhow::Result<f64> {
+        self.tracker.daily_budget_remaining()
+    }
+
+    /// Resolve per-1M-token pricing for a model. Unknown models are priced at zero.
+    fn pricing_for(&self, provider_name: &str, model: &str) -> ModelPricing {
+        match resolve_pricing(&self.config.prices, provider_name, model) {
+            Some(pricing) => pricing,
+            None => {
+                tracing::debug!(
+                    provider = provider_name,
+                    model,
+                    "No [cost.prices] or catalog price for model; recording usage at zero cost"
+                );
+                ModelPricing::default()
+            }
+        }
+    }
+
+    fn estimate_input_cost(&self, provider_name: &str, model: &str, input_chars: usize) -> f64 {
+        let input_price = self.pricing_for(provider_name, model).input;
+        estimate_tokens(input_chars) as f64 / 1_000_000.0 * input_price.max(0.0)
+    }
+
+    /// Check the budget for a pending call and pick the model to use.
+    ///
+    /// Returns the requested model when allowed, the configured
+    /// `downgrade_model` when the budget is exceeded but the cheaper model
+    /// still fits, or a [`BudgetExceededError`].
+    fn admit(
+        &self,
+        provider_name: &str,
+        model: &str,
+        input_chars: usize,
+    ) -> anyhow::Result<String> {
+        let estimate = self.estimate_input_cost(provider_name, model, input_chars);
+        let (current_usd, limit_usd, period) = match self.tracker.check_budget(estimate)? {
+            BudgetCheck::Allowed => return Ok(model.to_string()),
+            BudgetCheck::Warning {
+                current_usd,
+                limit_usd,
+                period,
+            } => {
+                tracing::warn!(
+                    period = period_label(period),
+                    "Cost budget warning: ${current_usd:.4} of ${limit_usd:.2} spent"
+                );
+                return Ok(model.to_string());
+            }
+            BudgetCheck::Exceeded {
+                current_usd,
+                limit_usd,
+                period,
+            } => (current_usd, limit_usd, period),
+        };
+
+        if let Some(downgrade) = self
+            .config
+            .downgrade_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty() && *m != model)
+        {
+            let downgrade_estimate =
+                self.estimate_input_cost(provider_name, downgrade, input_chars);
+            if !matches!(
+                self.tracker.check_budget(downgrade_estimate)?,
+                BudgetCheck::Exceeded { .. }
+            ) {
+                tracing::warn!(
+                    provider = provider_name,
+                    from = model,
+                    to = downgrade,
+                    "Cost budget exceeded; downgrading model"
+                );
+                return Ok(downgrade.to_string());
+            }
+        }
+
+        Err(BudgetExceededError {
+            period: period_label(period),
+            current_usd,
+            limit_usd,
+        }
+        .into())
+    }
+
+    fn record(&self, provider_name: &str, model: &str, usage: &ProviderTokenUsage) {
+        let pricing = self.pricing_for(provider_name, model);
+        let usage = TokenUsage::with_cache(
+            format!("{provider_name}/{model}"),
+            usage.input_tokens.unwrap_or(0),
+            usage.output_tokens.unwrap_or(0),
+            usage.cache_read_tokens.unwrap_or(0),
+            usage.cache_write_tokens.unwrap_or(0),
+            &pricing,
+        );
+        if let Err(e) = self.tracker.record_usage(usage) {
+            tracing::warn!("Failed to record cost usage: {e}");
+        }
+    }
+}
+
+/// Find the `[cost.prices]` entry for a model.
+///
+/// Tries the exact model ID, then `<provider>/<model>`, then any entry whose
+/// ID ends with `/<model>`.
+pub fn lookup_pricing<'a>(
+    prices: &'a HashMap<String, ModelPricing>,
+    provider_name: &str,
+    model: &str,
+) -> Option<&'a ModelPricing> {
+    prices
+        .get(model)
+        .or_else(|| prices.get(&format!("{provider_name}/{model}")))
+        .or_else(|| {
+            let suffix = format!("/{model}");
+            let mut candidates: Vec<_> = prices
+                .iter()
+                .filter(|(id, _)| id.ends_with(&suffix))
+                .collect();
+            candidates.sort_by(|a, b| a.0.cmp(b.0));
+            candidates.first().map(|(_, pricing)| *pricing)
+        })
+}
+
+/// Price for a model: the `[cost.prices]` entry when present, otherwise the
+/// model catalog's price.
+pub fn resolve_pricing(
+    prices: &HashMap<String, ModelPricing>,
+    provider_name: &str,
+    model: &str,
+) -> Option<ModelPricing> {
+    lookup_pricing(prices, provider_name, model)
+        .cloned()
+        .or_else(|| crate::providers::catalog::model_info(provider_name, model)?.pricing)
+}
+
+/// Build a [`CostGuard`] and wrap `provider`, or return it unchanged when
+/// cost tracking is disabled.
+pub fn wrap_provider(
+    config: &Config,
+    provider_name: &str,
+    provider: Box<dyn Provider>,
+) -> Box<dyn Provider> {
+    match CostGuard::from_config(config) {
+        Some(guard) => guard.wrap(provider_name, provider),
+        None => provider,
+    }
+}
+
+fn estimated_usage(input_chars: usize, output_chars: usize) -> ProviderTokenUsage {
+    ProviderTokenUsage {
+        input_tokens: Some(estimate_tokens(input_chars)),
+        output_tokens: Some(estimate_tokens(output_chars)),
+        cache_read_tokens: None,
+        cache_write_tokens: None,
+    }
+}
+
+fn estimate_tokens(chars: usize) -> u64 {
+    chars.div_ceil(CHARS_PER_TOKEN_ESTIMATE) as u64
+}
+
+fn message_chars(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(|m| m.content.chars().count()).sum()
+}
+
+fn tool_spec_chars(tools: Option<&[ToolSpec]>) -> usize {
+    tools.map_or(0, |tools| {
+        tools
+            .iter()
+            .map(|t| t.name.len() + t.description.chars().count() + t.parameters.to_string().len())
+            .sum()
+    })
+}
+
+/// Provider decorator that enforces `[cost]` budgets and records token usage
+/// for every call made through it.
+pub struct CostTrackingProvider {
+    inner: Box<dyn Provider>,
+    provider_name: String,
+    guard: CostGuard,
+}
+
+#[async_trait]
+impl Provider for CostTrackingProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.inner.supports_native_tools()
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.inner.supports_vision()
+    }
+
+    fn supports_structured_output(&self) -> bool {
+        self.inner.supports_structured_output()
+    }
+
+    fn convert_tools(&self, tools: &[ToolSpec]) -> ToolsPayload {
+        self.inner.convert_tools(tools)
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let input_chars = system_prompt.map_or(0, |s| s.chars().count()) + message.chars().count();
+        let model = self.guard.admit(&self.provider_name, model, input_chars)?;
+        let text = self
+            .inner
+            .chat_with_system(system_prompt, message, &model, temperature)
+            .await?;
+        self.guard.record(
+            &self.provider_name,
+            &model,
+            &estimated_usage(input_chars, text.chars().count()),
+        );
+        Ok(text)
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let input_chars = message_chars(messages);
+        let model = self.guard.admit(&self.provider_name, model, input_chars)?;
+        let text = self
+            .inner
+            .chat_with_history(messages, &model, temperature)
+            .await?;
+        self.guard.record(
+            &self.provider_name,
+            &model,
+            &estimated_usage(input_chars, text.chars().count()),
+        );
+        Ok(text)
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let input_chars = message_chars(request.messages) + tool_spec_chars(request.tools);
+        let model = self.guard.admit(&self.provider_name, model, input_chars)?;
+        let response = self.inner.chat(request, &model, temperature).await?;
+        record_response(
+            &self.guard,
+            &self.provider_name,
+            &model,
+            input_chars,
+            &response,
+        );
+        Ok(response)
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let input_chars =
+            message_chars(messages) + tools.iter().map(|t| t.to_string().len()).sum::<usize>();
+        let model = self.guard.admit(&self.provider_name, model, input_chars)?;
+        let response = self
+            .inner
+            .chat_with_tools(messages, tools, &model, temperature)
+            .await?;
+        record_response(
+            &self.guard,
+            &self.provider_name,
+            &model,
+            input_chars,
+            &response,
+        );
+        Ok(response)
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.inner.warmup().await
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn stream_chat<'a>(
+        &'a self,
+        request: ChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+    ) -> stream::BoxStream<'a, StreamResult<StreamEvent>> {
+        let input_chars = message_chars(request.messages) + tool_spec_chars(request.tools);
+        let model = match self.guard.admit(&self.provider_name, model, input_chars) {
+            Ok(model) => model,
+            Err(e) => {
+                return stream::once(async move { Err(StreamError::Provider(e.to_string())) })
+                    .boxed()
+            }
+        };
+
+        // Record spend once the response finishes, from the reassembled response.
+        let mut accumulator = Some(StreamAccumulator::default());
+        self.inner
+            .stream_chat(request, &model, temperature)
+            .inspect(move |event| {
+                let (Ok(event), Some(acc)) = (event, accumulator.as_mut()) else {
+                    return;
+                };
+                acc.push(event);
+                if matches!(event, StreamEvent::Finish(_)) {
+                    if let Some(acc) = accumulator.take() {
+                        record_response(
+                            &self.guard,
+                            &self.provider_name,
+                            &model,
+                            input_chars,
+                            &acc.into_response(),
+                        );
+                    }
+                }
+            })
+            .boxed()
+    }
+
+    fn stream_chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        let input_chars = system_prompt.map_or(0, |s| s.chars().count()) + message.chars().count();
+        match self.guard.admit(&self.provider_name, model, input_chars) {
+            Ok(model) => self.inner.stream_chat_with_system(
+                system_prompt,
+                message,
+                &model,
+                temperature,
+                options,
+            ),
+            Err(e) => stream::once(async move { Ok(StreamChunk::error(e.to_string())) }).boxed(),
+        }
+    }
+
+    fn stream_chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        options: StreamOptions,
+    ) -> stream::BoxStream<'static, StreamResult<StreamChunk>> {
+        match self
+            .guard
+            .admit(&self.provider_name, model, message_chars(messages))
+        {
+            Ok(model) => {
+                self.inner
+                    .stream_chat_with_history(messages, &model, temperature, options)
+            }
+            Err(e) => stream::once(async move { Ok(StreamChunk::error(e.to_string())) }).boxed(),
+        }
+    }
+}
+
+fn record_response(
+    guard: &CostGuard,
+    provider_name: &str,
+    model: &str,
+    input_chars: usize,
+    response: &ChatResponse,
+) {
+    let reported = response.usage.as_ref();
+    let input_tokens = reported
+        .and_then(|u| u.input_tokens)
+        .unwrap_or_else(|| estimate_tokens(input_chars));
+    let output_tokens = reported.and_then(|u| u.output_tokens).unwrap_or_else(|| {
+        let tool_call_chars: usize = response
+            .tool_calls
+            .iter()
+            .map(|c| c.name.len() + c.arguments.len())
+            .sum();
+        estimate_tokens(response.text_or_empty().chars().count() + tool_call_chars)
+    });
+    guard.record(
+        provider_name,
+        model,
+        &ProviderTokenUsage {
+            input_tokens: Some(input_tokens),
+            output_tokens: Some(output_tokens),
+            cache_read_tokens: reported.and_then(|u| u.cache_read_tokens),
+            cache_write_tokens: reported.and_then(|u| u.cache_write_tokens),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    struct UsageProvider {
+        calls: Arc<AtomicUsize>,
+        last_model: Arc<Mutex<String>>,
+    }
+
+    #[async_trait]
+    impl Provider for UsageProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            *self.last_model.lock().unwrap() = model.to_string();
+            Ok("ok".into())
+        }
+
+        async fn chat(
+            &self,
+            _request: ChatRequest<'_>,
+            model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            *self.last_model.lock().unwrap() = model.to_string();
+            Ok(ChatResponse {
+                text: Some("done".into()),
+                tool_calls: Vec::new(),
+                usage: Some(ProviderTokenUsage {
+                    input_tokens: Some(1_000_000),
+                    output_tokens: Some(100_000),
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
+                }),
+                reasoning: None,
+            })
+        }
+    }
+
+    fn test_config(tmp: &TempDir, daily_limit_usd: f64) -> Config {
+        let mut config = Config::default();
+        config.workspace_dir = tmp.path().to_path_buf();
+        config.cost.enabled = true;
+        config.cost.daily_limit_usd = daily_limit_usd;
+        config.cost.prices.insert(
+            "test/expensive".into(),
+            ModelPricing {
+                input: 2.0,
+                output: 10.0,
+                ..ModelPricing::default()
+            },
+        );
+        config.cost.prices.insert(
+            "test/cheap".into(),
+            ModelPricing {
+                input: 0.0,
+                output: 0.0,
+                ..ModelPricing::default()
+            },
+        );
+        config
+    }
+
+    fn wrapped(config: &Config) -> (Box<dyn Provider>, Arc<AtomicUsize>, Arc<Mutex<String>>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let last_model = Arc::new(Mutex::new(String::new()));
+        let provider = wrap_provider(
+            config,
+            "test",
+            Box::new(UsageProvider {
+                calls: Arc::clone(&calls),
+                last_model: Arc::clone(&last_model),
+            }),
+        );
+        (provider, calls, last_model)
+    }
+
+    #[tokio::test]
+    async fn records_reported_usage_with_model_pricing() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp, 100.0);
+        let (provider, _calls, _) = wrapped(&config);
+
+        let messages = [ChatMessage::user("hello")];
+        provider
+            .chat(
+                ChatRequest {
+                    messages: &messages,
+                    tools: None,
+                    output_schema: None,
+                },
+                "expensive",
+                0.0,
+            )
+            .await
+            .unwrap();
+
+        let guard = CostGuard::from_config(&config).unwrap();
+        let summary = guard.tracker().get_summary().unwrap();
+        // 1M input * $2 + 0.1M output * $10 = $3
+        assert!((summary.daily_cost_usd - 3.0).abs() < 1e-9);
+        assert!(summary.by_model.contains_key("test/expensive"));
+    }
+
+    #[test]
+    fn records_cache_tokens_at_cache_prices() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp, 100.0);
+        config.cost.prices.insert(
+            "test/cached".into(),
+            ModelPricing {
+                input: 2.0,
+                output: 10.0,
+                cache_read: Some(0.2),
+                cache_write: Some(2.5),
+            },
+        );
+        let guard = CostGuard::from_config(&config).unwrap();
+        guard.record(
+            "test",
+            "cached",
+            &ProviderTokenUsage {
+                input_tokens: Some(1_000_000),
+                output_tokens: Some(0),
+                cache_read_tokens: Some(800_000),
+                cache_write_tokens: Some(100_000),
+            },
+        );
+
+        let summary = guard.tracker().get_summary().unwrap();
+        // 0.1M * $2 + 0.8M * $0.2 + 0.1M * $2.5 = $0.61
+        assert!((summary.daily_cost_usd - 0.61).abs() < 1e-9);
+        assert_eq!(summary.by_model["test/cached"].cache_read_tokens, 800_000);
+    }
+
+    #[tokio::test]
+    async fn refuses_calls_once_budget_is_exhausted() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp, 1.0);
+        let (provider, calls, _) = wrapped(&config);
+        let messages = [ChatMessage::user("hello")];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: None,
+            output_schema: None,
+        };
+
+        provider.chat(request, "expensive", 0.0).await.unwrap();
+        let err = provider.chat(request, "expensive", 0.0).await.unwrap_err();
+
+        assert!(err.downcast_ref::<BudgetExceededError>().is_some());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn downgrades_model_when_request_would_exceed_budget() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp, 4.0);
+        config.cost.downgrade_model = Some("cheap".into());
+        let (provider, calls, last_model) = wrapped(&config);
+
+        let small = [ChatMessage::user("hello")];
+        provider
+            .chat(
+                ChatRequest {
+                    messages: &small,
+                    tools: None,
+                    output_schema: None,
+                },
+                "expensive",
+                0.0,
+            )
+            .await
+            .unwrap();
+
+        // $3 spent; ~1M estimated input tokens at $2/1M would exceed the $4
+        // limit, while the zero-priced downgrade model still fits.
+        let large = [ChatMessage::user("x".repeat(4_000_000))];
+        provider
+            .chat(
+                ChatRequest {
+                    messages: &large,
+                    tools: None,
+                    output_schema: None,
+                },
+                "expensive",
+                0.0,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(last_model.lock().unwrap().as_str(), "cheap");
+    }
+
+    #[test]
+    fn pricing_lookup_prefers_exact_then_provider_prefixed_then_suffix() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp, 100.0);
+        config.cost.prices.insert(
+            "vendor/shared-model".into(),
+            ModelPricing {
+                input: 5.0,
+                output: 6.0,
+                ..ModelPricing::default()
+            },
+        );
+        let guard = CostGuard::from_config(&config).unwrap();
+
+        assert_eq!(guard.pricing_for("test", "test/expensive").input, 2.0);
+        assert_eq!(guard.pricing_for("test", "expensive").output, 10.0);
+        assert_eq!(guard.pricing_for("openrouter", "shared-model").input, 5.0);
+        assert_eq!(
+            guard.pricing_for("test", "unknown"),
+            ModelPricing::default()
+        );
+    }
+
+    struct CapableProvider;
+
+    #[async_trait]
+    impl Provider for CapableProvider {
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                native_tool_calling: true,
+                vision: true,
+                structured_output: true,
+            }
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("ok".into())
+        }
+    }
+
+    #[test]
+    fn forwards_capabilities_of_reliable_inner_provider() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp, 100.0);
+        let reliable = crate::providers::reliable::ReliableProvider::new(
+            vec![("test".into(), Box::new(CapableProvider))],
+            0,
+            50,
+        );
+        let provider = wrap_provider(&config, "test", Box::new(reliable));
+
+        assert!(provider.supports_native_tools());
+        assert!(provider.supports_vision());
+        assert!(provider.supports_structured_output());
+        let caps =
+            crate::providers::catalog::capabilities_for("test", "test/cheap", provider.as_ref());
+        assert!(caps.native_tool_calling);
+        assert!(caps.vision);
+    }
+
+    #[test]
+    fn disabled_cost_config_leaves_provider_unwrapped() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp, 100.0);
+        config.cost.enabled = false;
+        assert!(CostGuard::from_config(&config).is_none());
+    }
+}
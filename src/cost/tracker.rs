@@ -1,7 +1,7 @@
 use super::types::{BudgetCheck, CostRecord, CostSummary, ModelStats, TokenUsage, UsagePeriod};
 use crate::config::schema::CostConfig;
 use anyhow::{anyhow, Context, Result};
-use chrono::{Datelike, NaiveDate, Utc};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Utc};
 use parking_lot::{Mutex, MutexGuard};
 use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
@@ -106,6 +106,18 @@ impl CostTracker {
         Ok(BudgetCheck::Allowed)
     }
 
+    /// Share of the daily limit not yet spent, from `0.0` to `1.0`.
+    pub fn daily_budget_remaining(&self) -> Result<f64> {
+        if self.config.daily_limit_usd <= 0.0 {
+            return Ok(0.0);
+        }
+        let (daily_cost, _) = self.lock_storage().get_aggregated_costs()?;
+        Ok(
+            ((self.config.daily_limit_usd - daily_cost) / self.config.daily_limit_usd)
+                .clamp(0.0, 1.0),
+        )
+    }
+
     /// Record a usage event.
     pub fn record_usage(&self, usage: TokenUsage) -> Result<()> {
         if !self.config.enabled {
@@ -150,7 +162,7 @@ impl CostTracker {
             .map(|record| record.usage.total_tokens)
             .sum();
         let request_count = session_costs.len();
-        let by_model = build_session_model_stats(&session_costs);
+        let by_model = build_model_stats(&session_costs);
 
         Ok(CostSummary {
             session_cost_usd: session_cost,
@@ -173,6 +185,30 @@ impl CostTracker {
         let storage = self.lock_storage();
         storage.get_cost_for_month(year, month)
     }
+
+    /// Get per-model statistics for a specific date.
+    pub fn get_model_stats_for_date(&self, date: NaiveDate) -> Result<HashMap<String, ModelStats>> {
+        let storage = self.lock_storage();
+        storage.get_model_stats_where(|timestamp| timestamp.date() == date)
+    }
+
+    /// Get per-model statistics for a specific month.
+    pub fn get_model_stats_for_month(
+        &self,
+        year: i32,
+        month: u32,
+    ) -> Result<HashMap<String, ModelStats>> {
+        let storage = self.lock_storage();
+        storage.get_model_stats_where(|timestamp| {
+            timestamp.year() == year && timestamp.month() == month
+        })
+    }
+
+    /// Get per-model statistics across all recorded usage.
+    pub fn get_model_stats_all_time(&self) -> Result<HashMap<String, ModelStats>> {
+        let storage = self.lock_storage();
+        storage.get_model_stats_where(|_| true)
+    }
 }
 
 fn resolve_storage_path(workspace_dir: &Path) -> Result<PathBuf> {
@@ -204,7 +240,7 @@ fn resolve_storage_path(workspace_dir: &Path) -> Result<PathBuf> {
     Ok(storage_path)
 }
 
-fn build_session_model_stats(session_costs: &[CostRecord]) -> HashMap<String, ModelStats> {
+fn build_model_stats(session_costs: &[CostRecord]) -> HashMap<String, ModelStats> {
     let mut by_model: HashMap<String, ModelStats> = HashMap::new();
 
     for record in session_costs {
@@ -214,11 +250,13 @@ fn build_session_model_stats(session_costs: &[CostRecord]) -> HashMap<String, Mo
                 model: record.usage.model.clone(),
                 cost_usd: 0.0,
                 total_tokens: 0,
+                cache_read_tokens: 0,
                 request_count: 0,
             });
 
         entry.cost_usd += record.usage.cost_usd;
         entry.total_tokens += record.usage.total_tokens;
+        entry.cache_read_tokens += record.usage.cache_read_tokens;
         entry.request_count += 1;
     }
 
@@ -399,6 +437,22 @@ impl CostStorage {
 
         Ok(cost)
     }
+
+    /// Aggregate per-model statistics for records whose timestamp matches.
+    fn get_model_stats_where<F>(&self, matches: F) -> Result<HashMap<String, ModelStats>>
+    where
+        F: Fn(NaiveDateTime) -> bool,
+    {
+        let mut records = Vec::new();
+
+        self.for_each_record(|record| {
+            if matches(record.usage.timestamp.naive_utc()) {
+                records.push(record);
+            }
+        })?;
+
+        Ok(build_model_stats(&records))
+    }
 }
 
 #[cfg(test)]
@@ -466,6 +520,23 @@ mod tests {
         assert!(matches!(check, BudgetCheck::Exceeded { .. }));
     }
 
+    #[test]
+    fn daily_budget_remaining_tracks_spend() {
+        let tmp = TempDir::new().unwrap();
+        let config = CostConfig {
+            enabled: true,
+            daily_limit_usd: 4.0,
+            ..Default::default()
+        };
+        let tracker = CostTracker::new(config, tmp.path()).unwrap();
+        assert!((tracker.daily_budget_remaining().unwrap() - 1.0).abs() < f64::EPSILON);
+
+        // 1M input tokens at $1/M
+        let usage = TokenUsage::new("test/model", 1_000_000, 0, 1.0, 2.0);
+        tracker.record_usage(usage).unwrap();
+        assert!((tracker.daily_budget_remaining().unwrap() - 0.75).abs() < 1e-9);
+    }
+
     #[test]
     fn summary_by_model_is_session_scoped() {
         let tmp = TempDir::new().unwrap();
@@ -533,4 +604,37 @@ mod tests {
             .to_string()
             .contains("Estimated cost must be a finite, non-negative value"));
     }
+
+    #[test]
+    fn model_stats_for_period_include_persisted_records() {
+        let tmp = TempDir::new().unwrap();
+        {
+            let tracker = CostTracker::new(enabled_config(), tmp.path()).unwrap();
+            tracker
+                .record_usage(TokenUsage::new("a/model", 1000, 0, 1.0, 0.0))
+                .unwrap();
+            tracker
+                .record_usage(TokenUsage::new("a/model", 1000, 0, 1.0, 0.0))
+                .unwrap();
+        }
+
+        // A fresh tracker has an empty session but still sees stored history.
+        let tracker = CostTracker::new(enabled_config(), tmp.path()).unwrap();
+        let today = Utc::now().date_naive();
+
+        let daily = tracker.get_model_stats_for_date(today).unwrap();
+        assert_eq!(daily["a/model"].request_count, 2);
+
+        let monthly = tracker
+            .get_model_stats_for_month(today.year(), today.month())
+            .unwrap();
+        assert_eq!(monthly["a/model"].total_tokens, 2000);
+
+        let yesterday = today.pred_opt().unwrap();
+        assert!(tracker
+            .get_model_stats_for_date(yesterday)
+            .unwrap()
+            .is_empty());
+        assert_eq!(tracker.get_model_stats_all_time().unwrap().len(), 1);
+    }
 }
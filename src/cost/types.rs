@@ -1,3 +1,4 @@
+use crate::config::schema::ModelPricing;
 use serde::{Deserialize, Serialize};
 
 /// Token usage information from a single API call.
@@ -9,6 +10,12 @@ pub struct TokenUsage {
     pub input_tokens: u64,
     /// Output/completion tokens
     pub output_tokens: u64,
+    /// Input tokens served from the provider's prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    /// Input tokens written to the provider's prompt cache
+    #[serde(default)]
+    pub cache_write_tokens: u64,
     /// Total tokens
     pub total_tokens: u64,
     /// Calculated cost in USD
@@ -33,21 +40,49 @@ impl TokenUsage {
         output_tokens: u64,
         input_price_per_million: f64,
         output_price_per_million: f64,
+    ) -> Self {
+        let pricing = ModelPricing {
+            input: input_price_per_million,
+            output: output_price_per_million,
+            ..ModelPricing::default()
+        };
+        Self::with_cache(model, input_tokens, output_tokens, 0, 0, &pricing)
+    }
+
+    /// Create a usage record where part of the input went through the
+    /// prompt cache. `cache_read_tokens` and `cache_write_tokens` are counted
+    /// within `input_tokens` but billed at the cache rates.
+    pub fn with_cache(
+        model: impl Into<String>,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+        pricing: &ModelPricing,
     ) -> Self {
         let model = model.into();
-        let input_price_per_million = Self::sanitize_price(input_price_per_million);
-        let output_price_per_million = Self::sanitize_price(output_price_per_million);
+        let input_price_per_million = Self::sanitize_price(pricing.input);
+        let output_price_per_million = Self::sanitize_price(pricing.output);
+        let cache_read_price_per_million = Self::sanitize_price(pricing.cache_read_price());
+        let cache_write_price_per_million = Self::sanitize_price(pricing.cache_write_price());
         let total_tokens = input_tokens.saturating_add(output_tokens);
+        let cache_read_tokens = cache_read_tokens.min(input_tokens);
+        let cache_write_tokens = cache_write_tokens.min(input_tokens - cache_read_tokens);
+        let uncached_tokens = input_tokens - cache_read_tokens - cache_write_tokens;
 
         // Calculate cost: (tokens / 1M) * price_per_million
-        let input_cost = (input_tokens as f64 / 1_000_000.0) * input_price_per_million;
-        let output_cost = (output_tokens as f64 / 1_000_000.0) * output_price_per_million;
-        let cost_usd = input_cost + output_cost;
+        let per_million = |tokens: u64, price: f64| (tokens as f64 / 1_000_000.0) * price;
+        let cost_usd = per_million(uncached_tokens, input_price_per_million)
+            + per_million(cache_read_tokens, cache_read_price_per_million)
+            + per_million(cache_write_tokens, cache_write_price_per_million)
+            + per_million(output_tokens, output_price_per_million);
 
         Self {
             model,
             input_tokens,
             output_tokens,
+            cache_read_tokens,
+            cache_write_tokens,
             total_tokens,
             cost_usd,
             timestamp: chrono::Utc::now(),
@@ -135,6 +170,9 @@ pub struct ModelStats {
     pub cost_usd: f64,
     /// Total tokens for this model
     pub total_tokens: u64,
+    /// Input tokens served from the prompt cache for this model
+    #[serde(default)]
+    pub cache_read_tokens: u64,
     /// Number of requests for this model
     pub request_count: usize,
 }
@@ -181,6 +219,44 @@ mod tests {
         assert_eq!(usage.total_tokens, 2000);
     }
 
+    #[test]
+    fn token_usage_prices_cache_reads_and_writes_separately() {
+        let pricing = ModelPricing {
+            input: 3.0,
+            output: 15.0,
+            cache_read: Some(0.3),
+            cache_write: Some(3.75),
+        };
+        let usage = TokenUsage::with_cache("test/model", 1_000_000, 0, 600_000, 200_000, &pricing);
+
+        // 200k uncached * 3 + 600k read * 0.3 + 200k write * 3.75 = 0.6 + 0.18 + 0.75
+        assert!((usage.cost_usd - 1.53).abs() < 0.0001);
+        assert_eq!(usage.cache_read_tokens, 600_000);
+        assert_eq!(usage.cache_write_tokens, 200_000);
+        assert_eq!(usage.total_tokens, 1_000_000);
+    }
+
+    #[test]
+    fn token_usage_cache_tokens_default_to_input_price_and_are_clamped() {
+        let pricing = ModelPricing {
+            input: 2.0,
+            output: 0.0,
+            ..ModelPricing::default()
+        };
+        let usage = TokenUsage::with_cache("test/model", 1_000, 0, 5_000, 5_000, &pricing);
+        assert_eq!(usage.cache_read_tokens, 1_000);
+        assert_eq!(usage.cache_write_tokens, 0);
+        assert!((usage.cost_usd - 0.002).abs() < 0.000_001);
+    }
+
+    #[test]
+    fn token_usage_records_without_cache_fields_still_deserialize() {
+        let json = r#"{"model":"m","input_tokens":10,"output_tokens":5,"total_tokens":15,"cost_usd":0.0,"timestamp":"2026-01-01T00:00:00Z"}"#;
+        let usage: TokenUsage = serde_json::from_str(json).unwrap();
+        assert_eq!(usage.cache_read_tokens, 0);
+        assert_eq!(usage.cache_write_tokens, 0);
+    }
+
     #[test]
     fn cost_record_creation() {
         let usage = TokenUsage::new("test/model", 100, 50, 1.0, 2.0);
@@ -6,6 +6,7 @@ use crate::cron::{
     due_jobs, next_run_for_schedule, record_last_run, record_run, remove_job, reschedule_after_run,
     update_job, CronJob, CronJobPatch, DeliveryConfig, JobType, Schedule, SessionTarget,
 };
+use crate::providers::OutputSchema;
 use crate::security::SecurityPolicy;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -164,6 +165,10 @@ async fn run_agent_job(
     let prompt = job.prompt.clone().unwrap_or_default();
     let prefixed_prompt = format!("[cron:{} {name}] {prompt}", job.id);
     let model_override = job.model.clone();
+    let output_schema = match job.output_schema.clone().map(OutputSchema::new).transpose() {
+        Ok(schema) => schema,
+        Err(e) => return (false, format!("agent job failed: {e}")),
+    };
 
     let run_result = match job.session_target {
         SessionTarget::Main | SessionTarget::Isolated => {
@@ -174,6 +179,8 @@ async fn run_agent_job(
                 model_override,
                 config.default_temperature,
                 vec![],
+                None,
+                output_schema,
             )
             .await
         }
@@ -544,6 +551,7 @@ mod tests {
             job_type: JobType::Shell,
             session_target: SessionTarget::Isolated,
             model: None,
+            output_schema: None,
             enabled: true,
             delivery: DeliveryConfig::default(),
             delete_after_run: false,
@@ -809,6 +817,7 @@ mod tests {
             None,
             None,
             true,
+            None,
         )
         .unwrap();
         let started = Utc::now();
@@ -834,6 +843,7 @@ mod tests {
             None,
             None,
             true,
+            None,
         )
         .unwrap();
         let started = Utc::now();
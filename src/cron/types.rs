@@ -112,6 +112,9 @@ pub struct CronJob {
     pub job_type: JobType,
     pub session_target: SessionTarget,
     pub model: Option<String>,
+    /// JSON schema the agent's final answer must satisfy (agent jobs only).
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
     pub enabled: bool,
     pub delivery: DeliveryConfig,
     pub delete_after_run: bool,
@@ -144,6 +147,7 @@ pub struct CronJobPatch {
     pub model: Option<String>,
     pub session_target: Option<SessionTarget>,
     pub delete_after_run: Option<bool>,
+    pub output_schema: Option<serde_json::Value>,
 }
 
 #[cfg(test)]
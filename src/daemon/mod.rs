@@ -101,6 +101,7 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
     for handle in handles {
         let _ = handle.await;
     }
+    crate::tools::process::terminate_all();
 
     Ok(())
 }
@@ -196,8 +197,17 @@ async fn run_heartbeat_worker(config: Config) -> Result<()> {
         for task in tasks {
             let prompt = format!("[Heartbeat Task] {task}");
             let temp = config.default_temperature;
-            if let Err(e) =
-                crate::agent::run(config.clone(), Some(prompt), None, None, temp, vec![]).await
+            if let Err(e) = crate::agent::run(
+                config.clone(),
+                Some(prompt),
+                None,
+                None,
+                temp,
+                vec![],
+                None,
+                None,
+            )
+            .await
             {
                 crate::health::mark_component_error("heartbeat", e.to_string());
                 tracing::warn!("Heartbeat task failed: {e}");
@@ -1,6 +1,7 @@
 use crate::config::Config;
+use crate::providers::catalog::ModelCatalog;
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use std::io::Write;
 use std::path::Path;
 
@@ -8,6 +9,7 @@ const DAEMON_STALE_SECONDS: i64 = 30;
 const SCHEDULER_STALE_SECONDS: i64 = 120;
 const CHANNEL_STALE_SECONDS: i64 = 300;
 const COMMAND_VERSION_PREVIEW_CHARS: usize = 60;
+const MODEL_RETIREMENT_WARN_DAYS: i64 = 30;
 
 // ── Diagnostic item ──────────────────────────────────────────────
 
@@ -222,6 +224,19 @@ pub async fn run_models(
         );
     }
 
+    let mut items = Vec::new();
+    check_model_catalog(
+        config,
+        &crate::providers::catalog::snapshot(),
+        Utc::now().date_naive(),
+        &mut items,
+    );
+    println!();
+    println!("  [configured models]");
+    for item in &items {
+        println!("    {} {}", item.icon(), item.message);
+    }
+
     if provider_override.is_some() && ok_count == 0 {
         anyhow::bail!("Model probe failed for target provider")
     }
@@ -229,6 +244,113 @@ pub async fn run_models(
     Ok(())
 }
 
+/// Warn when configured models are retired, unknown to a provider whose
+/// catalog is known, or configured beyond what the catalog says they support.
+fn check_model_catalog(
+    config: &Config,
+    catalog: &ModelCatalog,
+    today: NaiveDate,
+    items: &mut Vec<DiagItem>,
+) {
+    let cat = "models";
+    let default_provider = config.default_provider.as_deref().unwrap_or("openrouter");
+    let mut targets = Vec::new();
+    if let Some(model) = config.default_model.as_deref() {
+        targets.push(("default model".to_string(), default_provider, model, None));
+    }
+    for route in &config.model_routes {
+        targets.push((
+            format!("route '{}'", route.hint),
+            route.provider.as_str(),
+            route.model.as_str(),
+            Some(route),
+        ));
+    }
+
+    let before = items.len();
+    for (label, provider, model, route) in targets {
+        if model.starts_with("hint:") {
+            continue;
+        }
+        let Some(info) = catalog.lookup(provider, model) else {
+            if catalog.has_provider(provider) {
+                items.push(DiagItem::warn(
+                    cat,
+                    format!(
+                        "{label}: '{model}' is not in the {provider} catalog \
+                         (run `zeroclaw models refresh --force --provider {provider}` if it is new)"
+                    ),
+                ));
+            }
+            continue;
+        };
+
+        if let Some(retired) = info.deprecated_on {
+            if retired <= today {
+                items.push(DiagItem::error(
+                    cat,
+                    format!("{label}: '{model}' was retired on {retired}"),
+                ));
+            } else if (retired - today).num_days() <= MODEL_RETIREMENT_WARN_DAYS {
+                items.push(DiagItem::warn(
+                    cat,
+                    format!("{label}: '{model}' is scheduled for retirement on {retired}"),
+                ));
+            }
+        }
+
+        let window = info.context_window;
+        let configured_window = match route {
+            Some(route) => route.context_window,
+            None => config.agent.context_window_tokens,
+        };
+        if let (Some(configured), Some(window)) = (configured_window, window) {
+            if configured > window {
+                items.push(DiagItem::warn(
+                    cat,
+                    format!(
+                        "{label}: context window {configured} exceeds '{model}' limit of {window} tokens"
+                    ),
+                ));
+            }
+        }
+
+        if let Some(route) = route {
+            if route.vision == Some(true) && info.vision == Some(false) {
+                items.push(DiagItem::warn(
+                    cat,
+                    format!("{label}: vision = true but '{model}' does not accept images"),
+                ));
+            }
+            if route.native_tools == Some(true) && info.tools == Some(false) {
+                items.push(DiagItem::warn(
+                    cat,
+                    format!(
+                        "{label}: native_tools = true but '{model}' does not support tool calling"
+                    ),
+                ));
+            }
+        }
+
+        if config.cost.enabled
+            && info.pricing.is_none()
+            && crate::cost::lookup_pricing(&config.cost.prices, provider, model).is_none()
+        {
+            items.push(DiagItem::warn(
+                cat,
+                format!("{label}: no price for '{model}'; usage will be recorded at zero cost"),
+            ));
+        }
+    }
+
+    if items.len() == before {
+        items.push(DiagItem::ok(
+            cat,
+            "configured models match the model catalog",
+        ));
+    }
+}
+
 // ── Config semantic validation ───────────────────────────────────
 
 fn check_config_semantics(config: &Config, items: &mut Vec<DiagItem>) {
@@ -348,6 +470,33 @@ fn check_config_semantics(config: &Config, items: &mut Vec<DiagItem>) {
         }
     }
 
+    // Routing policies validation
+    for policy in &config.routing_policies {
+        if policy.name.trim().is_empty() {
+            items.push(DiagItem::warn(cat, "routing policy with empty name"));
+        }
+        if config.model_routes.iter().any(|r| r.hint == policy.name) {
+            items.push(DiagItem::warn(
+                cat,
+                format!(
+                    "routing policy \"{}\" shadows the model route with the same hint",
+                    policy.name
+                ),
+            ));
+        }
+        for hint in policy.candidates.iter().chain(&policy.escalate_to) {
+            if !config.model_routes.iter().any(|r| &r.hint == hint) {
+                items.push(DiagItem::warn(
+                    cat,
+                    format!(
+                        "routing policy \"{}\" references unknown model route \"{hint}\"",
+                        policy.name
+                    ),
+                ));
+            }
+        }
+    }
+
     // Embedding routes validation
     for route in &config.embedding_routes {
         if route.hint.trim().is_empty() {
@@ -723,6 +872,48 @@ fn check_daemon_state(config: &Config, items: &mut Vec<DiagItem>) {
             ));
         }
     }
+
+    check_provider_circuits(&snapshot, items);
+}
+
+/// Report circuit-breaker state for every provider/model pair the daemon has called.
+fn check_provider_circuits(snapshot: &serde_json::Value, items: &mut Vec<DiagItem>) {
+    let cat = "providers";
+    let Some(providers) = snapshot
+        .get("providers")
+        .and_then(serde_json::Value::as_object)
+    else {
+        return;
+    };
+
+    for (key, health) in providers {
+        let circuit = health
+            .get("circuit")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("closed");
+        let failures = health
+            .get("consecutive_failures")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(0);
+        let error_rate = health
+            .get("error_rate")
+            .and_then(serde_json::Value::as_f64)
+            .unwrap_or(0.0);
+        let latency = health
+            .get("latency_ms")
+            .and_then(serde_json::Value::as_u64)
+            .map_or_else(|| "n/a".to_string(), |ms| format!("{ms}ms"));
+        let detail = format!(
+            "{key} circuit {circuit} (failures={failures}, error_rate={:.0}%, latency={latency})",
+            error_rate * 100.0
+        );
+
+        items.push(match circuit {
+            "open" => DiagItem::error(cat, detail),
+            "half_open" => DiagItem::warn(cat, detail),
+            _ => DiagItem::ok(cat, detail),
+        });
+    }
 }
 
 // ── Environment checks ───────────────────────────────────────────
@@ -965,6 +1156,9 @@ mod tests {
             provider: "groq".into(),
             model: String::new(),
             api_key: None,
+            context_window: None,
+            vision: None,
+            native_tools: None,
         }];
         let mut items = Vec::new();
         check_config_semantics(&config, &mut items);
@@ -973,6 +1167,95 @@ mod tests {
         assert_eq!(route_item.unwrap().severity, Severity::Warn);
     }
 
+    #[test]
+    fn config_validation_warns_unknown_routing_policy_candidate() {
+        let mut config = Config::default();
+        config.model_routes = vec![crate::config::ModelRouteConfig {
+            hint: "fast".into(),
+            provider: "groq".into(),
+            model: "llama-3.3-70b".into(),
+            api_key: None,
+            context_window: None,
+            vision: None,
+            native_tools: None,
+        }];
+        config.routing_policies = vec![crate::config::RoutingPolicyConfig {
+            name: "auto".into(),
+            candidates: vec!["fast".into(), "missing".into()],
+            prefer: crate::config::RoutingPreference::Cheapest,
+            require_vision: false,
+            require_tools: false,
+            min_context_tokens: None,
+            low_budget_percent: None,
+            escalate_to: None,
+            escalate_after: 2,
+        }];
+        let mut items = Vec::new();
+        check_config_semantics(&config, &mut items);
+        let warnings: Vec<_> = items
+            .iter()
+            .filter(|item| item.message.contains("routing policy"))
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("\"missing\""));
+    }
+
+    #[test]
+    fn model_catalog_check_flags_retired_unknown_and_overconfigured_models() {
+        let mut config = Config::default();
+        config.default_provider = Some("openai".into());
+        config.default_model = Some("o1-preview".into());
+        config.cost.enabled = true;
+        config.model_routes = vec![
+            crate::config::ModelRouteConfig {
+                hint: "cheap".into(),
+                provider: "deepseek".into(),
+                model: "deepseek-chat".into(),
+                api_key: None,
+                context_window: Some(1_000_000),
+                vision: Some(true),
+                native_tools: None,
+            },
+            crate::config::ModelRouteConfig {
+                hint: "typo".into(),
+                provider: "openai".into(),
+                model: "gpt-4oo".into(),
+                api_key: None,
+                context_window: None,
+                vision: None,
+                native_tools: None,
+            },
+        ];
+        let today = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let mut items = Vec::new();
+        check_model_catalog(&config, &ModelCatalog::bundled(), today, &mut items);
+
+        let find = |needle: &str| {
+            items
+                .iter()
+                .find(|item| item.message.contains(needle))
+                .unwrap_or_else(|| panic!("no item containing {needle:?}"))
+        };
+        assert_eq!(find("was retired").severity, Severity::Error);
+        assert_eq!(find("exceeds 'deepseek-chat'").severity, Severity::Warn);
+        assert_eq!(find("does not accept images").severity, Severity::Warn);
+        assert_eq!(find("not in the openai catalog").severity, Severity::Warn);
+        assert!(!items.iter().any(|item| item.message.contains("no price")));
+    }
+
+    #[test]
+    fn model_catalog_check_passes_known_models() {
+        let mut config = Config::default();
+        config.default_provider = Some("anthropic".into());
+        config.default_model = Some("claude-sonnet-4-20250514".into());
+        let today = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let mut items = Vec::new();
+        check_model_catalog(&config, &ModelCatalog::bundled(), today, &mut items);
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].severity, Severity::Ok);
+    }
+
     #[test]
     fn config_validation_warns_empty_embedding_route_model() {
         let mut config = Config::default();
@@ -1029,6 +1312,31 @@ mod tests {
         assert_eq!(route_item.unwrap().severity, Severity::Warn);
     }
 
+    #[test]
+    fn provider_circuits_map_state_to_severity() {
+        let snapshot = serde_json::json!({
+            "providers": {
+                "openai/gpt-4o": {"circuit": "open", "consecutive_failures": 5, "error_rate": 0.9},
+                "anthropic/claude": {"circuit": "closed", "error_rate": 0.0, "latency_ms": 840},
+            }
+        });
+        let mut items = Vec::new();
+        check_provider_circuits(&snapshot, &mut items);
+
+        let open = items
+            .iter()
+            .find(|item| item.message.contains("openai/gpt-4o"))
+            .unwrap();
+        assert_eq!(open.severity, Severity::Error);
+        assert!(open.message.contains("failures=5"));
+        let closed = items
+            .iter()
+            .find(|item| item.message.contains("anthropic/claude"))
+            .unwrap();
+        assert_eq!(closed.severity, Severity::Ok);
+        assert!(closed.message.contains("latency=840ms"));
+    }
+
     #[test]
     fn environment_check_finds_git() {
         let mut items = Vec::new();
@@ -0,0 +1,72 @@
+//! Agent evaluation harness (`zeroclaw eval run`).
+//!
+//! Scenario files describe user messages, seeded workspace files and memory,
+//! and expectations on tool calls, the final answer and files produced. Each
+//! scenario runs through [`Agent`](crate::agent::agent::Agent) in a fresh
+//! temporary workspace, once per variant, so two models or two prompt
+//! directories can be compared side by side. Reports carry pass rates, token
+//! usage and latency as a table, JSON or JUnit XML.
+
+pub mod report;
+pub mod runner;
+pub mod scenario;
+
+#[allow(unused_imports)]
+pub use runner::{run_scenario, run_suite, EvalReport, ScenarioResult, Variant, VariantReport};
+#[allow(unused_imports)]
+pub use scenario::{load_scenarios, Scenario};
+
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+
+pub async fn handle_command(command: crate::EvalCommands, config: &Config) -> Result<()> {
+    match command {
+        crate::EvalCommands::Run {
+            paths,
+            provider,
+            model,
+            variants,
+            format,
+            output,
+        } => {
+            let format = report::ReportFormat::parse(&format)?;
+            let scenarios = load_scenarios(&paths)?;
+            let mut variants = variants
+                .iter()
+                .map(|spec| Variant::parse(spec))
+                .collect::<Result<Vec<_>>>()?;
+            if variants.is_empty() {
+                variants.push(Variant {
+                    name: "default".into(),
+                    ..Variant::default()
+                });
+            }
+            for variant in &mut variants {
+                if variant.provider.is_none() {
+                    variant.provider.clone_from(&provider);
+                }
+                if variant.model.is_none() {
+                    variant.model.clone_from(&model);
+                }
+            }
+
+            let report = run_suite(config, &scenarios, &variants).await;
+            let rendered = report::render(&report, format)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, rendered)
+                        .with_context(|| format!("Failed to write report: {}", path.display()))?;
+                    println!("Report written to {}", path.display());
+                }
+                None => print!("{rendered}"),
+            }
+
+            let failed = report.failed();
+            if failed > 0 {
+                let total: usize = report.variants.iter().map(|v| v.total).sum();
+                bail!("{failed} of {total} scenario runs failed");
+            }
+            Ok(())
+        }
+    }
+}
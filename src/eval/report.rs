@@ -0,0 +1,258 @@
+//! Render an [`EvalReport`] as a table, JSON or JUnit XML.
+
+use super::runner::{EvalReport, ScenarioResult};
+use anyhow::{bail, Result};
+use std::fmt::Write as _;
+
+/// Output format for `zeroclaw eval run --format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Table,
+    Json,
+    Junit,
+}
+
+impl ReportFormat {
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "junit" => Ok(Self::Junit),
+            other => bail!("Unknown report format '{other}'. Use table, json or junit."),
+        }
+    }
+}
+
+pub fn render(report: &EvalReport, format: ReportFormat) -> Result<String> {
+    match format {
+        ReportFormat::Table => Ok(render_table(report)),
+        ReportFormat::Json => Ok(serde_json::to_string_pretty(report)? + "\n"),
+        ReportFormat::Junit => Ok(render_junit(report)),
+    }
+}
+
+fn status(result: &ScenarioResult) -> &'static str {
+    if result.passed {
+        "PASS"
+    } else if result.error.is_some() {
+        "ERROR"
+    } else {
+        "FAIL"
+    }
+}
+
+/// One row per scenario, one column per variant, followed by totals and the
+/// reasons for each failure.
+fn render_table(report: &EvalReport) -> String {
+    let mut out = String::new();
+    let names: Vec<&str> = report
+        .variants
+        .first()
+        .map(|v| v.scenarios.iter().map(|s| s.scenario.as_str()).collect())
+        .unwrap_or_default();
+    let name_width = names
+        .iter()
+        .map(|n| n.chars().count())
+        .chain(std::iter::once("scenario".len()))
+        .max()
+        .unwrap_or(0);
+    const CELL: usize = 28;
+
+    let _ = write!(out, "{:<name_width$}", "scenario");
+    for variant in &report.variants {
+        let _ = write!(out, "  {:<CELL$}", variant.variant);
+    }
+    out.push('\n');
+
+    for (idx, name) in names.iter().enumerate() {
+        let _ = write!(out, "{name:<name_width$}");
+        for variant in &report.variants {
+            let cell = variant.scenarios.get(idx).map_or_else(String::new, |r| {
+                format!(
+                    "{} {}+{}tok {}ms",
+                    status(r),
+                    r.input_tokens,
+                    r.output_tokens,
+                    r.latency_ms
+                )
+            });
+            let _ = write!(out, "  {cell:<CELL$}");
+        }
+        out.push('\n');
+    }
+
+    out.push('\n');
+    for variant in &report.variants {
+        let _ = writeln!(
+            out,
+            "{} ({} / {}): {}/{} passed ({:.0}%), {} input + {} output tokens, {}ms",
+            variant.variant,
+            variant.provider,
+            if variant.model.is_empty() {
+                "(default)"
+            } else {
+                &variant.model
+            },
+            variant.passed,
+            variant.total,
+            variant.pass_rate * 100.0,
+            variant.input_tokens,
+            variant.output_tokens,
+            variant.latency_ms
+        );
+        for result in variant.scenarios.iter().filter(|r| !r.passed) {
+            if let Some(error) = &result.error {
+                let _ = writeln!(out, "  ✗ {}: {error}", result.scenario);
+            }
+            for failure in &result.failures {
+                let _ = writeln!(out, "  ✗ {}: {failure}", result.scenario);
+            }
+        }
+    }
+    out
+}
+
+/// One `<testsuite>` per variant, one `<testcase>` per scenario.
+fn render_junit(report: &EvalReport) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let tests: usize = report.variants.iter().map(|v| v.total).sum();
+    let _ = writeln!(
+        out,
+        "<testsuites name=\"zeroclaw-eval\" tests=\"{tests}\" failures=\"{}\">",
+        report.failed()
+    );
+    for variant in &report.variants {
+        let errors = variant
+            .scenarios
+            .iter()
+            .filter(|r| r.error.is_some())
+            .count();
+        let _ = writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{errors}\" time=\"{:.3}\">",
+            xml_escape(&variant.variant),
+            variant.total,
+            variant.total - variant.passed - errors,
+            millis_to_secs(variant.latency_ms)
+        );
+        let _ = writeln!(out, "    <properties>");
+        for (name, value) in [
+            ("provider", variant.provider.clone()),
+            ("model", variant.model.clone()),
+            ("input_tokens", variant.input_tokens.to_string()),
+            ("output_tokens", variant.output_tokens.to_string()),
+        ] {
+            let _ = writeln!(
+                out,
+                "      <property name=\"{name}\" value=\"{}\"/>",
+                xml_escape(&value)
+            );
+        }
+        let _ = writeln!(out, "    </properties>");
+        for result in &variant.scenarios {
+            let _ = write!(
+                out,
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+                xml_escape(&variant.variant),
+                xml_escape(&result.scenario),
+                millis_to_secs(result.latency_ms)
+            );
+            if result.passed {
+                out.push_str("/>\n");
+                continue;
+            }
+            out.push_str(">\n");
+            if let Some(error) = &result.error {
+                let _ = writeln!(out, "      <error message=\"{}\"/>", xml_escape(error));
+            } else {
+                let _ = writeln!(
+                    out,
+                    "      <failure message=\"{}\">{}</failure>",
+                    xml_escape(result.failures.first().map_or("", String::as_str)),
+                    xml_escape(&result.failures.join("\n"))
+                );
+            }
+            let _ = writeln!(
+                out,
+                "      <system-out>{}</system-out>",
+                xml_escape(&result.answer)
+            );
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+fn millis_to_secs(ms: u64) -> f64 {
+    ms as f64 / 1000.0
+}
+
+fn xml_escape(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::runner::VariantReport;
+
+    fn result(name: &str, passed: bool, failures: &[&str]) -> ScenarioResult {
+        ScenarioResult {
+            scenario: name.into(),
+            passed,
+            failures: failures.iter().map(|f| (*f).to_string()).collect(),
+            error: None,
+            answer: "<done>".into(),
+            tool_calls: Vec::new(),
+            provider_calls: 1,
+            input_tokens: 10,
+            output_tokens: 2,
+            latency_ms: 1500,
+        }
+    }
+
+    fn report() -> EvalReport {
+        EvalReport {
+            variants: vec![VariantReport {
+                variant: "a&b".into(),
+                provider: "openrouter".into(),
+                model: "m".into(),
+                passed: 1,
+                total: 2,
+                pass_rate: 0.5,
+                input_tokens: 20,
+                output_tokens: 4,
+                latency_ms: 3000,
+                scenarios: vec![
+                    result("reads", true, &[]),
+                    result("writes", false, &["file out.txt: missing"]),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn junit_marks_failures_and_escapes() {
+        let xml = render(&report(), ReportFormat::Junit).unwrap();
+        assert!(xml.contains(
+            "<testsuite name=\"a&amp;b\" tests=\"2\" failures=\"1\" errors=\"0\" time=\"3.000\">"
+        ));
+        assert!(xml.contains("<testcase classname=\"a&amp;b\" name=\"reads\" time=\"1.500\"/>"));
+        assert!(xml.contains("<failure message=\"file out.txt: missing\">"));
+        assert!(xml.contains("<system-out>&lt;done&gt;</system-out>"));
+    }
+
+    #[test]
+    fn table_lists_totals_and_failure_reasons() {
+        let table = render(&report(), ReportFormat::Table).unwrap();
+        assert!(table.contains("1/2 passed (50%)"));
+        assert!(table.contains("✗ writes: file out.txt: missing"));
+        assert!(ReportFormat::parse("yaml").is_err());
+    }
+}
@@ -0,0 +1,605 @@
+//! Runs scenarios through [`Agent`] in throwaway workspaces.
+
+use super::scenario::{workspace_relative, RecordedToolCall, Scenario};
+use crate::agent::agent::Agent;
+use crate::config::Config;
+use crate::memory::{self, MemoryCategory};
+use crate::providers::traits::{
+    ChatMessage, ChatRequest, ChatResponse, ProviderCapabilities, ToolsPayload,
+};
+use crate::providers::Provider;
+use crate::security::SecurityPolicy;
+use crate::tools::{Tool, ToolResult, ToolSpec};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Workspace files that shape the system prompt, copied from the variant's
+/// prompt directory into every scenario workspace.
+const PROMPT_FILES: [&str; 8] = [
+    "AGENTS.md",
+    "SOUL.md",
+    "TOOLS.md",
+    "IDENTITY.md",
+    "USER.md",
+    "HEARTBEAT.md",
+    "BOOTSTRAP.md",
+    "MEMORY.md",
+];
+
+/// A provider/model/prompt combination to evaluate.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// Directory holding the prompt files and `skills/`; defaults to the
+    /// configured workspace.
+    pub prompt_dir: Option<PathBuf>,
+    pub temperature: Option<f64>,
+}
+
+impl Variant {
+    /// Parse `name=<label>,provider=<id>,model=<id>,prompt_dir=<path>,temperature=<t>`.
+    /// Every key is optional; a bare value without `=` is taken as the name.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut variant = Self::default();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((key, value)) = part.split_once('=') else {
+                variant.name = part.to_string();
+                continue;
+            };
+            let value = value.trim().to_string();
+            match key.trim() {
+                "name" => variant.name = value,
+                "provider" => variant.provider = Some(value),
+                "model" => variant.model = Some(value),
+                "prompt_dir" => variant.prompt_dir = Some(PathBuf::from(value)),
+                "temperature" => {
+                    let t: f64 = value
+                        .parse()
+                        .with_context(|| format!("Invalid temperature '{value}' in variant"))?;
+                    variant.temperature = Some(t);
+                }
+                other => bail!(
+                    "Unknown variant key '{other}'. Use name, provider, model, prompt_dir or temperature."
+                ),
+            }
+        }
+        if variant.name.is_empty() {
+            variant.name = variant
+                .model
+                .clone()
+                .or_else(|| variant.provider.clone())
+                .unwrap_or_else(|| "default".into());
+        }
+        Ok(variant)
+    }
+}
+
+/// Outcome of one scenario under one variant.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioResult {
+    pub scenario: String,
+    pub passed: bool,
+    pub failures: Vec<String>,
+    /// Set when the run itself failed (provider error, timeout, ...).
+    pub error: Option<String>,
+    pub answer: String,
+    pub tool_calls: Vec<RecordedToolCall>,
+    pub provider_calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub latency_ms: u64,
+}
+
+/// All scenario results for one variant, with totals.
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantReport {
+    pub variant: String,
+    pub provider: String,
+    pub model: String,
+    pub passed: usize,
+    pub total: usize,
+    pub pass_rate: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub latency_ms: u64,
+    pub scenarios: Vec<ScenarioResult>,
+}
+
+/// Results for every variant, in the order they were given.
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub variants: Vec<VariantReport>,
+}
+
+impl EvalReport {
+    pub fn failed(&self) -> usize {
+        self.variants.iter().map(|v| v.total - v.passed).sum()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    calls: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// Counts calls and token usage on the way through.
+struct MeteredProvider {
+    inner: Box<dyn Provider>,
+    usage: Arc<Mutex<Usage>>,
+}
+
+impl MeteredProvider {
+    fn record(&self, response: Option<&ChatResponse>) {
+        let mut usage = self.usage.lock();
+        usage.calls += 1;
+        if let Some(reported) = response.and_then(|r| r.usage.as_ref()) {
+            usage.input_tokens += reported.input_tokens.unwrap_or(0);
+            usage.output_tokens += reported.output_tokens.unwrap_or(0);
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for MeteredProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.inner.supports_native_tools()
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.inner.supports_vision()
+    }
+
+    fn supports_structured_output(&self) -> bool {
+        self.inner.supports_structured_output()
+    }
+
+    fn convert_tools(&self, tools: &[ToolSpec]) -> ToolsPayload {
+        self.inner.convert_tools(tools)
+    }
+
+    async fn warmup(&self) -> Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> Result<String> {
+        let text = self
+            .inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await?;
+        self.record(None);
+        Ok(text)
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> Result<String> {
+        let text = self
+            .inner
+            .chat_with_history(messages, model, temperature)
+            .await?;
+        self.record(None);
+        Ok(text)
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        model: &str,
+        temperature: f64,
+    ) -> Result<ChatResponse> {
+        let response = self
+            .inner
+            .chat_with_tools(messages, tools, model, temperature)
+            .await?;
+        self.record(Some(&response));
+        Ok(response)
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> Result<ChatResponse> {
+        let response = self.inner.chat(request, model, temperature).await?;
+        self.record(Some(&response));
+        Ok(response)
+    }
+}
+
+/// Records every invocation of the wrapped tool.
+struct RecordingTool {
+    inner: Box<dyn Tool>,
+    calls: Arc<Mutex<Vec<RecordedToolCall>>>,
+}
+
+#[async_trait]
+impl Tool for RecordingTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.inner.parameters_schema()
+    }
+
+    fn spec(&self) -> ToolSpec {
+        self.inner.spec()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<ToolResult> {
+        let result = self.inner.execute(args.clone()).await;
+        self.calls.lock().push(RecordedToolCall {
+            name: self.inner.name().to_string(),
+            arguments: args,
+            success: result.as_ref().is_ok_and(|r| r.success),
+        });
+        result
+    }
+}
+
+/// Run every scenario under every variant.
+pub async fn run_suite(
+    config: &Config,
+    scenarios: &[Scenario],
+    variants: &[Variant],
+) -> EvalReport {
+    let mut reports = Vec::with_capacity(variants.len());
+    for variant in variants {
+        let mut results = Vec::with_capacity(scenarios.len());
+        for scenario in scenarios {
+            tracing::info!(variant = %variant.name, scenario = %scenario.name, "Running eval scenario");
+            results.push(run_scenario(config, scenario, variant).await);
+        }
+        let passed = results.iter().filter(|r| r.passed).count();
+        reports.push(VariantReport {
+            variant: variant.name.clone(),
+            provider: variant
+                .provider
+                .clone()
+                .or_else(|| config.default_provider.clone())
+                .unwrap_or_else(|| "openrouter".into()),
+            model: variant
+                .model
+                .clone()
+                .or_else(|| config.default_model.clone())
+                .unwrap_or_default(),
+            passed,
+            total: results.len(),
+            pass_rate: if results.is_empty() {
+                0.0
+            } else {
+                passed as f64 / results.len() as f64
+            },
+            input_tokens: results.iter().map(|r| r.input_tokens).sum(),
+            output_tokens: results.iter().map(|r| r.output_tokens).sum(),
+            latency_ms: results.iter().map(|r| r.latency_ms).sum(),
+            scenarios: results,
+        });
+    }
+    EvalReport { variants: reports }
+}
+
+/// Run one scenario in a fresh workspace that is removed afterwards.
+pub async fn run_scenario(
+    config: &Config,
+    scenario: &Scenario,
+    variant: &Variant,
+) -> ScenarioResult {
+    let workspace = std::env::temp_dir().join(format!("zeroclaw-eval-{}", uuid::Uuid::new_v4()));
+    let result = run_in_workspace(config, scenario, variant, &workspace).await;
+    if let Err(e) = std::fs::remove_dir_all(&workspace) {
+        tracing::debug!(workspace = %workspace.display(), "Failed to remove eval workspace: {e}");
+    }
+    result
+}
+
+async fn run_in_workspace(
+    config: &Config,
+    scenario: &Scenario,
+    variant: &Variant,
+    workspace: &Path,
+) -> ScenarioResult {
+    let usage = Arc::new(Mutex::new(Usage::default()));
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let started = Instant::now();
+
+    let outcome = match prepare_agent(config, scenario, variant, workspace, &usage, &calls).await {
+        Ok(mut agent) => {
+            let turns = async {
+                let mut answer = String::new();
+                for message in &scenario.messages {
+                    answer = agent.turn(message).await?;
+                }
+                anyhow::Ok(answer)
+            };
+            match tokio::time::timeout(Duration::from_secs(scenario.timeout_secs), turns).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!(
+                    "timed out after {}s",
+                    scenario.timeout_secs
+                )),
+            }
+        }
+        Err(e) => Err(e),
+    };
+    let latency_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+    let tool_calls = calls.lock().clone();
+    let (answer, error, failures) = match outcome {
+        Ok(answer) => {
+            let failures = scenario.expect.check(&answer, &tool_calls, workspace);
+            (answer, None, failures)
+        }
+        Err(e) => (String::new(), Some(format!("{e:#}")), Vec::new()),
+    };
+    let usage = usage.lock();
+
+    ScenarioResult {
+        scenario: scenario.name.clone(),
+        passed: error.is_none() && failures.is_empty(),
+        failures,
+        error,
+        answer,
+        tool_calls,
+        provider_calls: usage.calls,
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        latency_ms,
+    }
+}
+
+async fn prepare_agent(
+    config: &Config,
+    scenario: &Scenario,
+    variant: &Variant,
+    workspace: &Path,
+    usage: &Arc<Mutex<Usage>>,
+    calls: &Arc<Mutex<Vec<RecordedToolCall>>>,
+) -> Result<Agent> {
+    std::fs::create_dir_all(workspace)
+        .with_context(|| format!("Failed to create eval workspace: {}", workspace.display()))?;
+    let prompt_dir = variant
+        .prompt_dir
+        .clone()
+        .unwrap_or_else(|| config.workspace_dir.clone());
+    copy_prompt_files(&prompt_dir, workspace)?;
+    for (rel, content) in &scenario.files {
+        let path = workspace.join(workspace_relative(rel)?);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to seed scenario file: {rel}"))?;
+    }
+
+    let mut cfg = config.clone();
+    cfg.workspace_dir = workspace.to_path_buf();
+    if let Some(script) = scenario.script_path() {
+        cfg.default_provider = Some(format!("scripted:{}", script.display()));
+    } else if let Some(provider) = &variant.provider {
+        cfg.default_provider = Some(provider.clone());
+    }
+    if let Some(model) = &variant.model {
+        cfg.default_model = Some(model.clone());
+    }
+    if let Some(temperature) = variant.temperature {
+        cfg.default_temperature = temperature;
+    }
+
+    if !scenario.memory.is_empty() {
+        let mem = memory::create_memory_with_storage_and_routes(
+            &cfg.memory,
+            &cfg.embedding_routes,
+            Some(&cfg.storage.provider.config),
+            &cfg.workspace_dir,
+            cfg.api_key.as_deref(),
+        )?;
+        for entry in &scenario.memory {
+            mem.store(
+                &entry.key,
+                &entry.content,
+                parse_category(entry.category.as_deref()),
+                None,
+            )
+            .await?;
+        }
+    }
+
+    let wrap = |tool: Box<dyn Tool>| -> Box<dyn Tool> {
+        Box::new(RecordingTool {
+            inner: tool,
+            calls: Arc::clone(calls),
+        })
+    };
+    let mut agent = Agent::builder_from_config(&cfg)?
+        .map_provider(|inner| {
+            Box::new(MeteredProvider {
+                inner,
+                usage: Arc::clone(usage),
+            })
+        })
+        .map_tools(wrap)
+        .build()?;
+    let security = Arc::new(SecurityPolicy::from_config(
+        &cfg.autonomy,
+        &cfg.workspace_dir,
+    ));
+    let mcp_tools = crate::mcp::create_mcp_tools(&cfg, &security).await;
+    agent.extend_tools(mcp_tools.into_iter().map(wrap).collect());
+    Ok(agent)
+}
+
+fn parse_category(raw: Option<&str>) -> MemoryCategory {
+    match raw.map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("" | "core") => MemoryCategory::Core,
+        Some("daily") => MemoryCategory::Daily,
+        Some("conversation") => MemoryCategory::Conversation,
+        Some(other) => MemoryCategory::Custom(other.to_string()),
+    }
+}
+
+fn copy_prompt_files(prompt_dir: &Path, workspace: &Path) -> Result<()> {
+    for file in PROMPT_FILES {
+        let src = prompt_dir.join(file);
+        if src.is_file() {
+            std::fs::copy(&src, workspace.join(file))
+                .with_context(|| format!("Failed to copy prompt file: {}", src.display()))?;
+        }
+    }
+    let skills = prompt_dir.join("skills");
+    if skills.is_dir() {
+        copy_dir_recursive(&skills, &workspace.join("skills"))?;
+    }
+    Ok(())
+}
+
+fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dest_path = dest.join(entry.file_name());
+        if src_path.is_dir() {
+            copy_dir_recursive(&src_path, &dest_path)?;
+        } else {
+            std::fs::copy(&src_path, &dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn variant_parse_reads_keys_and_defaults_name() {
+        let variant = Variant::parse(
+            "name=b, provider=custom:https://x.test/v1?a=b ,model=m2,temperature=0.2",
+        )
+        .unwrap();
+        assert_eq!(variant.name, "b");
+        assert_eq!(
+            variant.provider.as_deref(),
+            Some("custom:https://x.test/v1?a=b")
+        );
+        assert_eq!(variant.model.as_deref(), Some("m2"));
+        assert_eq!(variant.temperature, Some(0.2));
+
+        assert_eq!(Variant::parse("model=gpt-4o").unwrap().name, "gpt-4o");
+        assert_eq!(Variant::parse("baseline").unwrap().name, "baseline");
+        assert!(Variant::parse("color=red").is_err());
+    }
+
+    #[tokio::test]
+    async fn scripted_scenario_passes_and_records_tools_and_usage() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("SOUL.md"), "Be terse.").unwrap();
+        std::fs::write(
+            tmp.path().join("script.yaml"),
+            r#"
+responses:
+  - tool_calls:
+      - name: file_read
+        arguments: { path: "notes.txt" }
+    usage: { input_tokens: 100, output_tokens: 5 }
+  - text: "The notes say: remember the milk"
+    usage: { input_tokens: 150, output_tokens: 9 }
+"#,
+        )
+        .unwrap();
+        let scenario_path = tmp.path().join("notes.yaml");
+        std::fs::write(
+            &scenario_path,
+            r#"
+messages: ["What do my notes say?"]
+files: { "notes.txt": "remember the milk" }
+script: script.yaml
+expect:
+  tool_calls:
+    - name: file_read
+      arguments: { path: notes.txt }
+  forbidden_tools: [shell]
+  answer:
+    - contains: "remember the milk"
+  files:
+    - path: notes.txt
+      contains: milk
+"#,
+        )
+        .unwrap();
+        let scenario = Scenario::from_file(&scenario_path).unwrap();
+
+        let mut config = Config::default();
+        config.workspace_dir = tmp.path().to_path_buf();
+        config.memory.backend = "none".into();
+        config.reliability.provider_retries = 0;
+        let variants = [Variant::parse("name=scripted").unwrap()];
+
+        let report = run_suite(&config, &[scenario], &variants).await;
+        let result = &report.variants[0].scenarios[0];
+        assert!(result.passed, "{result:?}");
+        assert_eq!(result.tool_calls[0].name, "file_read");
+        assert!(result.tool_calls[0].success);
+        assert_eq!(result.provider_calls, 2);
+        assert_eq!(result.input_tokens, 250);
+        assert_eq!(result.output_tokens, 14);
+        assert_eq!(report.variants[0].pass_rate, 1.0);
+        assert_eq!(report.failed(), 0);
+    }
+
+    #[tokio::test]
+    async fn provider_errors_fail_the_scenario() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(
+            tmp.path().join("script.json"),
+            r#"{"responses":[{"error":"simulated outage"}]}"#,
+        )
+        .unwrap();
+        let scenario_path = tmp.path().join("outage.json");
+        std::fs::write(
+            &scenario_path,
+            r#"{"messages":["hi"],"script":"script.json"}"#,
+        )
+        .unwrap();
+        let scenario = Scenario::from_file(&scenario_path).unwrap();
+
+        let mut config = Config::default();
+        config.workspace_dir = tmp.path().to_path_buf();
+        config.memory.backend = "none".into();
+        config.reliability.provider_retries = 0;
+
+        let result = run_scenario(&config, &scenario, &Variant::parse("x").unwrap()).await;
+        assert!(!result.passed);
+        assert!(result.error.unwrap().contains("simulated outage"));
+    }
+}
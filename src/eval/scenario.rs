@@ -0,0 +1,469 @@
+//! Scenario files and the assertions they make about a run.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Default per-scenario timeout when the file does not set `timeout_secs`.
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// One evaluation scenario, loaded from a YAML or JSON file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    /// Scenario name; defaults to the file stem.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// User messages sent in order, one agent turn each.
+    pub messages: Vec<String>,
+    /// Files written into the scenario workspace before the run
+    /// (workspace-relative path → content).
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    /// Memory entries stored before the run.
+    #[serde(default)]
+    pub memory: Vec<SeedMemory>,
+    /// Script for the `scripted` provider, relative to the scenario file.
+    /// Overrides the variant's provider.
+    #[serde(default)]
+    pub script: Option<PathBuf>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default)]
+    pub expect: Expectations,
+    /// File the scenario was loaded from.
+    #[serde(skip)]
+    pub source: PathBuf,
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+/// A memory entry seeded before the run.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedMemory {
+    pub key: String,
+    pub content: String,
+    /// core, daily, conversation or a custom name. Default: core.
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// What a passing run must satisfy.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Expectations {
+    /// Tool calls that must each occur at least once.
+    #[serde(default)]
+    pub tool_calls: Vec<ExpectedToolCall>,
+    /// Tools that must not be called.
+    #[serde(default)]
+    pub forbidden_tools: Vec<String>,
+    #[serde(default)]
+    pub max_tool_calls: Option<usize>,
+    /// Assertions on the final answer.
+    #[serde(default)]
+    pub answer: Vec<TextAssertion>,
+    /// Assertions on files in the workspace after the run.
+    #[serde(default)]
+    pub files: Vec<FileAssertion>,
+}
+
+/// An expected tool call. `arguments`, when set, must be a subset of the
+/// arguments the tool was called with.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExpectedToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Option<serde_json::Value>,
+}
+
+/// Checks on a piece of text. Every field that is set must hold.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TextAssertion {
+    #[serde(default)]
+    pub contains: Option<String>,
+    #[serde(default)]
+    pub not_contains: Option<String>,
+    #[serde(default)]
+    pub regex: Option<String>,
+    /// JSON pointer (e.g. `/status`) into the text parsed as JSON. Without
+    /// `equals` the pointer only has to resolve.
+    #[serde(default)]
+    pub json_pointer: Option<String>,
+    #[serde(default)]
+    pub equals: Option<serde_json::Value>,
+}
+
+/// Checks on a workspace file after the run.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileAssertion {
+    pub path: String,
+    #[serde(default = "default_exists")]
+    pub exists: bool,
+    #[serde(flatten)]
+    pub content: TextAssertion,
+}
+
+fn default_exists() -> bool {
+    true
+}
+
+/// A tool invocation observed during a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+    pub success: bool,
+}
+
+impl Scenario {
+    /// Load a scenario, parsing `.yaml` / `.yml` files as YAML and anything
+    /// else as JSON.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario: {}", path.display()))?;
+        let mut scenario: Self = if is_yaml(path) {
+            serde_yaml_ng::from_str(&raw)
+                .with_context(|| format!("Invalid YAML scenario: {}", path.display()))?
+        } else {
+            serde_json::from_str(&raw)
+                .with_context(|| format!("Invalid JSON scenario: {}", path.display()))?
+        };
+        if scenario.name.trim().is_empty() {
+            scenario.name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "scenario".into());
+        }
+        if scenario.messages.is_empty() {
+            bail!("Scenario {} has no messages", path.display());
+        }
+        for rel in scenario
+            .files
+            .keys()
+            .chain(scenario.expect.files.iter().map(|f| &f.path))
+        {
+            workspace_relative(rel)
+                .with_context(|| format!("Invalid file path in {}", path.display()))?;
+        }
+        scenario.source = path.to_path_buf();
+        Ok(scenario)
+    }
+
+    /// Absolute path of the scripted-provider script, if any.
+    pub fn script_path(&self) -> Option<PathBuf> {
+        let script = self.script.as_ref()?;
+        if script.is_absolute() {
+            return Some(script.clone());
+        }
+        let base = self.source.parent().unwrap_or_else(|| Path::new("."));
+        Some(base.join(script))
+    }
+}
+
+fn is_yaml(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"))
+}
+
+fn is_scenario_file(path: &Path) -> bool {
+    is_yaml(path)
+        || path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Load scenarios from files and directories. Directories contribute their
+/// `*.yaml`, `*.yml` and `*.json` files (not recursive), sorted by name.
+pub fn load_scenarios(paths: &[PathBuf]) -> Result<Vec<Scenario>> {
+    let mut scenarios = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+                .with_context(|| format!("Failed to read directory: {}", path.display()))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.is_file() && is_scenario_file(p))
+                .collect();
+            files.sort();
+            for file in files {
+                scenarios.push(Scenario::from_file(&file)?);
+            }
+        } else {
+            scenarios.push(Scenario::from_file(path)?);
+        }
+    }
+    if scenarios.is_empty() {
+        bail!("No scenario files found");
+    }
+    Ok(scenarios)
+}
+
+/// Validate a scenario file path: relative and without `..`.
+pub fn workspace_relative(raw: &str) -> Result<&Path> {
+    let path = Path::new(raw);
+    if raw.trim().is_empty()
+        || !path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        bail!("'{raw}' must be a relative path inside the workspace");
+    }
+    Ok(path)
+}
+
+impl Expectations {
+    /// Every failed expectation, as a human-readable message.
+    pub fn check(
+        &self,
+        answer: &str,
+        tool_calls: &[RecordedToolCall],
+        workspace: &Path,
+    ) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        for expected in &self.tool_calls {
+            let matched = tool_calls.iter().any(|call| {
+                call.name == expected.name
+                    && expected
+                        .arguments
+                        .as_ref()
+                        .is_none_or(|args| json_contains(&call.arguments, args))
+            });
+            if !matched {
+                match &expected.arguments {
+                    Some(args) => failures.push(format!(
+                        "expected tool call {} with arguments {args}",
+                        expected.name
+                    )),
+                    None => failures.push(format!("expected tool call {}", expected.name)),
+                }
+            }
+        }
+
+        for forbidden in &self.forbidden_tools {
+            if tool_calls.iter().any(|call| &call.name == forbidden) {
+                failures.push(format!("forbidden tool {forbidden} was called"));
+            }
+        }
+
+        if let Some(max) = self.max_tool_calls {
+            if tool_calls.len() > max {
+                failures.push(format!(
+                    "expected at most {max} tool calls, got {}",
+                    tool_calls.len()
+                ));
+            }
+        }
+
+        for assertion in &self.answer {
+            failures.extend(
+                assertion
+                    .check(answer)
+                    .into_iter()
+                    .map(|f| format!("answer: {f}")),
+            );
+        }
+
+        for file in &self.files {
+            let path = workspace.join(&file.path);
+            match (file.exists, path.is_file()) {
+                (false, true) => failures.push(format!("file {}: should not exist", file.path)),
+                (false, false) => {}
+                (true, false) => failures.push(format!("file {}: missing", file.path)),
+                (true, true) => match std::fs::read_to_string(&path) {
+                    Ok(content) => failures.extend(
+                        file.content
+                            .check(&content)
+                            .into_iter()
+                            .map(|f| format!("file {}: {f}", file.path)),
+                    ),
+                    Err(e) => failures.push(format!("file {}: unreadable: {e}", file.path)),
+                },
+            }
+        }
+
+        failures
+    }
+}
+
+impl TextAssertion {
+    /// Every failed check on `text`.
+    pub fn check(&self, text: &str) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        if let Some(needle) = &self.contains {
+            if !text.contains(needle.as_str()) {
+                failures.push(format!("does not contain {needle:?}"));
+            }
+        }
+        if let Some(needle) = &self.not_contains {
+            if text.contains(needle.as_str()) {
+                failures.push(format!("unexpectedly contains {needle:?}"));
+            }
+        }
+        if let Some(pattern) = &self.regex {
+            match regex::Regex::new(pattern) {
+                Ok(re) if re.is_match(text) => {}
+                Ok(_) => failures.push(format!("does not match /{pattern}/")),
+                Err(e) => failures.push(format!("invalid regex /{pattern}/: {e}")),
+            }
+        }
+        if self.json_pointer.is_some() || self.equals.is_some() {
+            let pointer = self.json_pointer.as_deref().unwrap_or("");
+            match parse_embedded_json(text) {
+                None => failures.push("is not valid JSON".into()),
+                Some(value) => match (value.pointer(pointer), &self.equals) {
+                    (None, _) => failures.push(format!("JSON has no value at {pointer:?}")),
+                    (Some(actual), Some(expected)) if actual != expected => failures.push(format!(
+                        "JSON value at {pointer:?} is {actual}, expected {expected}"
+                    )),
+                    _ => {}
+                },
+            }
+        }
+
+        failures
+    }
+}
+
+/// Parse `text` as JSON, falling back to the outermost object or array in it
+/// so answers wrapped in prose or code fences still parse.
+fn parse_embedded_json(text: &str) -> Option<serde_json::Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+    let start = trimmed.find(['{', '['])?;
+    let end = trimmed.rfind(['}', ']'])?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&trimmed[start..=end]).ok()
+}
+
+/// Whether `actual` contains `expected`: objects match key-by-key
+/// recursively, everything else must be equal.
+fn json_contains(actual: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    match (actual, expected) {
+        (serde_json::Value::Object(actual), serde_json::Value::Object(expected)) => {
+            expected.iter().all(|(key, value)| {
+                actual
+                    .get(key)
+                    .is_some_and(|actual| json_contains(actual, value))
+            })
+        }
+        _ => actual == expected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn call(name: &str, arguments: serde_json::Value) -> RecordedToolCall {
+        RecordedToolCall {
+            name: name.into(),
+            arguments,
+            success: true,
+        }
+    }
+
+    #[test]
+    fn yaml_scenario_loads_with_defaults() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("summarize.yaml");
+        std::fs::write(
+            &path,
+            r#"
+messages: ["Summarize README.md"]
+files: { "README.md": "hello" }
+script: scripts/summarize.yaml
+expect:
+  tool_calls:
+    - name: file_read
+      arguments: { path: README.md }
+  answer:
+    - contains: hello
+"#,
+        )
+        .unwrap();
+
+        let scenario = Scenario::from_file(&path).unwrap();
+        assert_eq!(scenario.name, "summarize");
+        assert_eq!(scenario.timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(
+            scenario.script_path().unwrap(),
+            tmp.path().join("scripts/summarize.yaml")
+        );
+        assert_eq!(scenario.expect.tool_calls[0].name, "file_read");
+    }
+
+    #[test]
+    fn scenario_rejects_paths_outside_workspace() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("escape.json");
+        std::fs::write(
+            &path,
+            r#"{"messages":["hi"],"files":{"../outside.txt":"x"}}"#,
+        )
+        .unwrap();
+        let err = Scenario::from_file(&path).unwrap_err();
+        assert!(format!("{err:#}").contains("relative path inside the workspace"));
+    }
+
+    #[test]
+    fn expectations_report_each_failure() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("out.json"), r#"{"status":"ok"}"#).unwrap();
+        let expect: Expectations = serde_json::from_value(json!({
+            "tool_calls": [
+                {"name": "file_read", "arguments": {"path": "a.txt"}},
+                {"name": "memory_recall"}
+            ],
+            "forbidden_tools": ["shell"],
+            "max_tool_calls": 1,
+            "answer": [{"regex": "^Done"}, {"not_contains": "sorry"}],
+            "files": [
+                {"path": "out.json", "json_pointer": "/status", "equals": "ok"},
+                {"path": "missing.txt"}
+            ]
+        }))
+        .unwrap();
+        let calls = [
+            call("file_read", json!({"path": "a.txt", "limit": 10})),
+            call("shell", json!({"command": "ls"})),
+        ];
+
+        let failures = expect.check("Sorry, not done", &calls, tmp.path());
+        assert_eq!(
+            failures,
+            vec![
+                "expected tool call memory_recall".to_string(),
+                "forbidden tool shell was called".into(),
+                "expected at most 1 tool calls, got 2".into(),
+                "answer: does not match /^Done/".into(),
+                "file missing.txt: missing".into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_assertions_accept_fenced_answers() {
+        let assertion = TextAssertion {
+            json_pointer: Some("/items/0/id".into()),
+            equals: Some(json!(7)),
+            ..TextAssertion::default()
+        };
+        assert!(assertion
+            .check("Here you go:\n```json\n{\"items\":[{\"id\":7}]}\n```")
+            .is_empty());
+        assert_eq!(assertion.check("no json"), vec!["is not valid JSON"]);
+    }
+}
@@ -7,9 +7,12 @@
 //! - Request timeouts (30s) to prevent slow-loris attacks
 //! - Header sanitization (handled by axum/hyper)
 
+mod openai;
+
 use crate::channels::{Channel, LinqChannel, NextcloudTalkChannel, SendMessage, WhatsAppChannel};
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
+use crate::providers::structured::{self, OutputSchema};
 use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
 use crate::security::pairing::{constant_time_eq, is_public_bind, PairingGuard};
@@ -38,6 +41,9 @@ use uuid::Uuid;
 pub const MAX_BODY_SIZE: usize = 65_536;
 /// Request timeout (30s) — prevents slow-loris attacks
 pub const REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Timeout (5 min) for `POST /v1/chat/completions`, which may run a full
+/// agent turn with tool calls before responding
+pub const AGENT_REQUEST_TIMEOUT_SECS: u64 = 300;
 /// Sliding window used by gateway rate limiting.
 pub const RATE_LIMIT_WINDOW_SECS: u64 = 60;
 /// Fallback max distinct client keys tracked in gateway rate limiter.
@@ -290,6 +296,10 @@ pub struct AppState {
     pub nextcloud_talk_webhook_secret: Option<Arc<str>>,
     /// Observability backend for metrics scraping
     pub observer: Arc<dyn crate::observability::Observer>,
+    /// MCP server backing `POST /mcp` (when `gateway.mcp_enabled`)
+    pub mcp: Option<Arc<crate::mcp::McpServer>>,
+    /// Agent runtime for `POST /v1/chat/completions`, built on first use
+    pub agent: Arc<tokio::sync::OnceCell<Arc<crate::agent::ConversationRuntime>>>,
 }
 
 /// Run the HTTP gateway using axum with proper HTTP/1.1 compliance.
@@ -318,22 +328,35 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     let actual_port = listener.local_addr()?.port();
     let display_addr = format!("{host}:{actual_port}");
 
-    let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider_with_options(
-        config.default_provider.as_deref().unwrap_or("openrouter"),
-        config.api_key.as_deref(),
-        config.api_url.as_deref(),
-        &config.reliability,
-        &providers::ProviderRuntimeOptions {
-            auth_profile_override: None,
-            zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
-            secrets_encrypt: config.secrets.encrypt,
-            reasoning_enabled: config.runtime.reasoning_enabled,
-        },
-    )?);
+    let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
     let model = config
         .default_model
         .clone()
         .unwrap_or_else(|| "anthropic/claude-sonnet-4".into());
+    let observer: Arc<dyn crate::observability::Observer> =
+        Arc::from(crate::observability::create_observer(&config.observability));
+
+    // Routed so `hint:<name>` models (listed by `/v1/models`) resolve.
+    let provider: Arc<dyn Provider> = Arc::from(crate::cost::wrap_provider(
+        &config,
+        provider_name,
+        providers::create_routed_provider_with_options(
+            provider_name,
+            config.api_key.as_deref(),
+            config.api_url.as_deref(),
+            &config.reliability,
+            &config.model_routes,
+            &model,
+            &providers::ProviderRuntimeOptions {
+                auth_profile_override: None,
+                zeroclaw_dir: config.config_path.parent().map(std::path::PathBuf::from),
+                secrets_encrypt: config.secrets.encrypt,
+                reasoning_enabled: config.runtime.reasoning_enabled,
+                reasoning_budget_tokens: config.runtime.reasoning_budget_tokens,
+            },
+            providers::router::RoutingOptions::from_config(&config).with_observer(observer.clone()),
+        )?,
+    ));
     let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage(
         &config.memory,
@@ -500,6 +523,13 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         idempotency_max_keys,
     ));
 
+    // ── MCP server (optional) ─────────────────────────────────
+    let mcp_server = if config.gateway.mcp_enabled {
+        Some(Arc::new(crate::mcp::McpServer::from_config(&config).await?))
+    } else {
+        None
+    };
+
     // ── Tunnel ────────────────────────────────────────────────
     let tunnel = crate::tunnel::create_tunnel(&config.tunnel)?;
     let mut tunnel_url: Option<String> = None;
@@ -524,6 +554,8 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     }
     println!("  POST /pair      — pair a new client (X-Pairing-Code header)");
     println!("  POST /webhook   — {{\"message\": \"your prompt\"}}");
+    println!("  POST /v1/chat/completions — OpenAI-compatible chat (agent, SSE, tools)");
+    println!("  GET  /v1/models — models and route hints");
     if whatsapp_channel.is_some() {
         println!("  GET  /whatsapp  — Meta webhook verification");
         println!("  POST /whatsapp  — WhatsApp message webhook");
@@ -534,6 +566,9 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     if nextcloud_talk_channel.is_some() {
         println!("  POST /nextcloud-talk — Nextcloud Talk bot webhook");
     }
+    if mcp_server.is_some() {
+        println!("  POST /mcp       — MCP JSON-RPC (tools and memory resources)");
+    }
     println!("  GET  /health    — health check");
     println!("  GET  /metrics   — Prometheus metrics");
     if let Some(code) = pairing.pairing_code() {
@@ -558,9 +593,6 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     }
 
     // Build shared state
-    let observer: Arc<dyn crate::observability::Observer> =
-        Arc::from(crate::observability::create_observer(&config.observability));
-
     let state = AppState {
         config: config_state,
         provider,
@@ -580,24 +612,40 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         nextcloud_talk: nextcloud_talk_channel,
         nextcloud_talk_webhook_secret,
         observer,
+        mcp: mcp_server,
+        agent: Arc::new(tokio::sync::OnceCell::new()),
     };
 
+    // Agent runs get a longer timeout than the other routes.
+    let agent_routes = Router::new()
+        .route(
+            "/v1/chat/completions",
+            post(openai::handle_chat_completions),
+        )
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            Duration::from_secs(AGENT_REQUEST_TIMEOUT_SECS),
+        ));
+
     // Build router with middleware
     let app = Router::new()
         .route("/health", get(handle_health))
         .route("/metrics", get(handle_metrics))
         .route("/pair", post(handle_pair))
         .route("/webhook", post(handle_webhook))
+        .route("/v1/models", get(openai::handle_models))
         .route("/whatsapp", get(handle_whatsapp_verify))
         .route("/whatsapp", post(handle_whatsapp_message))
         .route("/linq", post(handle_linq_webhook))
         .route("/nextcloud-talk", post(handle_nextcloud_talk_webhook))
-        .with_state(state)
-        .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
+        .route("/mcp", post(handle_mcp))
         .layer(TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(REQUEST_TIMEOUT_SECS),
-        ));
+        ))
+        .merge(agent_routes)
+        .with_state(state)
+        .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE));
 
     // Run the server
     axum::serve(
@@ -730,7 +778,8 @@ async fn run_gateway_chat_simple(
     state: &AppState,
     provider_label: &str,
     message: &str,
-) -> anyhow::Result<String> {
+    output_schema: Option<&OutputSchema>,
+) -> anyhow::Result<(String, Option<serde_json::Value>)> {
     let user_messages = vec![ChatMessage::user(message)];
 
     // Keep webhook/gateway prompts aligned with channel behavior by injecting
@@ -755,22 +804,38 @@ async fn run_gateway_chat_simple(
     let prepared =
         crate::multimodal::prepare_messages_for_provider(&messages, &multimodal_config).await?;
 
-    state
-        .provider
-        .chat_with_history(&prepared.messages, &state.model, state.temperature)
+    match output_schema {
+        Some(schema) => structured::chat_with_repair(
+            state.provider.as_ref(),
+            &prepared.messages,
+            schema,
+            &state.model,
+            state.temperature,
+        )
         .await
+        .map(|(text, output)| (text, Some(output))),
+        None => state
+            .provider
+            .chat_with_history(&prepared.messages, &state.model, state.temperature)
+            .await
+            .map(|text| (text, None)),
+    }
 }
 
 /// Full-featured chat with tools for channel handlers (WhatsApp, Linq, Nextcloud Talk).
 async fn run_gateway_chat_with_tools(state: &AppState, message: &str) -> anyhow::Result<String> {
     let config = state.config.lock().clone();
-    crate::agent::process_message(config, message).await
+    Box::pin(crate::agent::process_message(config, message)).await
 }
 
 /// Webhook request body
 #[derive(serde::Deserialize)]
 pub struct WebhookBody {
     pub message: String,
+    /// JSON schema the response must validate against; the parsed document
+    /// is returned as `output`.
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 /// POST /webhook — main webhook endpoint
@@ -837,6 +902,19 @@ async fn handle_webhook(
         }
     };
 
+    let output_schema = match webhook_body
+        .output_schema
+        .clone()
+        .map(OutputSchema::new)
+        .transpose()
+    {
+        Ok(schema) => schema,
+        Err(e) => {
+            let err = serde_json::json!({ "error": format!("Invalid output_schema: {e}") });
+            return (StatusCode::BAD_REQUEST, Json(err));
+        }
+    };
+
     // ── Idempotency (optional) ──
     if let Some(idempotency_key) = headers
         .get("X-Idempotency-Key")
@@ -888,8 +966,8 @@ async fn handle_webhook(
             messages_count: 1,
         });
 
-    match run_gateway_chat_simple(&state, &provider_label, message).await {
-        Ok(response) => {
+    match run_gateway_chat_simple(&state, &provider_label, message, output_schema.as_ref()).await {
+        Ok((response, output)) => {
             let duration = started_at.elapsed();
             state
                 .observer
@@ -915,7 +993,10 @@ async fn handle_webhook(
                     cost_usd: None,
                 });
 
-            let body = serde_json::json!({"response": response, "model": state.model});
+            let mut body = serde_json::json!({"response": response, "model": state.model});
+            if let Some(output) = output {
+                body["output"] = output;
+            }
             (StatusCode::OK, Json(body))
         }
         Err(e) => {
@@ -1335,6 +1416,54 @@ async fn handle_nextcloud_talk_webhook(
     (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
 }
 
+/// POST /mcp — MCP JSON-RPC over HTTP (tools and memory resources)
+async fn handle_mcp(
+    State(state): State<AppState>,
+    ConnectInfo(peer_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: String,
+) -> axum::response::Response {
+    let Some(server) = state.mcp.clone() else {
+        let err = serde_json::json!({
+            "error": "MCP endpoint disabled — set [gateway] mcp_enabled = true"
+        });
+        return (StatusCode::NOT_FOUND, Json(err)).into_response();
+    };
+
+    let rate_key =
+        client_key_from_request(Some(peer_addr), &headers, state.trust_forwarded_headers);
+    if !state.rate_limiter.allow_webhook(&rate_key) {
+        tracing::warn!("/mcp rate limit exceeded");
+        let err = serde_json::json!({
+            "error": "Too many MCP requests. Please retry later.",
+            "retry_after": RATE_LIMIT_WINDOW_SECS,
+        });
+        return (StatusCode::TOO_MANY_REQUESTS, Json(err)).into_response();
+    }
+
+    // ── Bearer token auth (pairing) ──
+    if state.pairing.require_pairing() {
+        let auth = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        let token = auth.strip_prefix("Bearer ").unwrap_or("");
+        if !state.pairing.is_authenticated(token) {
+            tracing::warn!("MCP: rejected — not paired / invalid bearer token");
+            let err = serde_json::json!({
+                "error": "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>"
+            });
+            return (StatusCode::UNAUTHORIZED, Json(err)).into_response();
+        }
+    }
+
+    match server.handle_message(&body).await {
+        Some(response) => (StatusCode::OK, Json(response)).into_response(),
+        // Notifications carry no response body.
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1413,6 +1542,8 @@ mod tests {
             nextcloud_talk: None,
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let response = handle_metrics(State(state)).await.into_response();
@@ -1458,6 +1589,8 @@ mod tests {
             nextcloud_talk: None,
             nextcloud_talk_webhook_secret: None,
             observer,
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let response = handle_metrics(State(state)).await.into_response();
@@ -1820,6 +1953,8 @@ mod tests {
             nextcloud_talk: None,
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let mut headers = HeaderMap::new();
@@ -1827,6 +1962,7 @@ mod tests {
 
         let body = Ok(Json(WebhookBody {
             message: "hello".into(),
+            output_schema: None,
         }));
         let first = handle_webhook(
             State(state.clone()),
@@ -1840,6 +1976,7 @@ mod tests {
 
         let body = Ok(Json(WebhookBody {
             message: "hello".into(),
+            output_schema: None,
         }));
         let second = handle_webhook(State(state), test_connect_info(), headers, body)
             .await
@@ -1880,12 +2017,15 @@ mod tests {
             nextcloud_talk: None,
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let headers = HeaderMap::new();
 
         let body1 = Ok(Json(WebhookBody {
             message: "hello one".into(),
+            output_schema: None,
         }));
         let first = handle_webhook(
             State(state.clone()),
@@ -1899,6 +2039,7 @@ mod tests {
 
         let body2 = Ok(Json(WebhookBody {
             message: "hello two".into(),
+            output_schema: None,
         }));
         let second = handle_webhook(State(state), test_connect_info(), headers, body2)
             .await
@@ -1913,6 +2054,71 @@ mod tests {
         assert_eq!(provider_impl.calls.load(Ordering::SeqCst), 2);
     }
 
+    #[tokio::test]
+    async fn webhook_output_schema_repairs_and_returns_parsed_output() {
+        let provider: Arc<dyn Provider> =
+            Arc::new(crate::providers::scripted::ScriptedProvider::from_texts([
+                "Sure! The answer is yes.",
+                r#"{"ok": true}"#,
+            ]));
+        let state = AppState {
+            config: Arc::new(Mutex::new(Config::default())),
+            provider,
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(MockMemory),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(false, &[])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
+        };
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {"ok": {"type": "boolean"}},
+            "required": ["ok"]
+        });
+        let response = handle_webhook(
+            State(state.clone()),
+            test_connect_info(),
+            HeaderMap::new(),
+            Ok(Json(WebhookBody {
+                message: "is it working?".into(),
+                output_schema: Some(schema),
+            })),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let parsed: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(parsed["output"], serde_json::json!({"ok": true}));
+
+        let response = handle_webhook(
+            State(state),
+            test_connect_info(),
+            HeaderMap::new(),
+            Ok(Json(WebhookBody {
+                message: "bad schema".into(),
+                output_schema: Some(serde_json::json!(["not", "an", "object"])),
+            })),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[test]
     fn webhook_secret_hash_is_deterministic_and_nonempty() {
         let secret_a = generate_test_secret();
@@ -1952,6 +2158,8 @@ mod tests {
             nextcloud_talk: None,
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let response = handle_webhook(
@@ -1960,6 +2168,7 @@ mod tests {
             HeaderMap::new(),
             Ok(Json(WebhookBody {
                 message: "hello".into(),
+                output_schema: None,
             })),
         )
         .await
@@ -1996,6 +2205,8 @@ mod tests {
             nextcloud_talk: None,
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let mut headers = HeaderMap::new();
@@ -2010,6 +2221,7 @@ mod tests {
             headers,
             Ok(Json(WebhookBody {
                 message: "hello".into(),
+                output_schema: None,
             })),
         )
         .await
@@ -2045,6 +2257,8 @@ mod tests {
             nextcloud_talk: None,
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let mut headers = HeaderMap::new();
@@ -2056,6 +2270,7 @@ mod tests {
             headers,
             Ok(Json(WebhookBody {
                 message: "hello".into(),
+                output_schema: None,
             })),
         )
         .await
@@ -2099,6 +2314,8 @@ mod tests {
             nextcloud_talk: None,
             nextcloud_talk_webhook_secret: None,
             observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let response = handle_nextcloud_talk_webhook(
@@ -2149,6 +2366,8 @@ mod tests {
             nextcloud_talk: Some(channel),
             nextcloud_talk_webhook_secret: Some(Arc::from(secret)),
             observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
         };
 
         let mut headers = HeaderMap::new();
@@ -2409,4 +2628,89 @@ mod tests {
         assert!(!keys.contains_key("old-key"));
         assert!(keys.contains_key("new-key"));
     }
+
+    fn mcp_test_state(mcp: Option<Arc<crate::mcp::McpServer>>) -> AppState {
+        AppState {
+            config: Arc::new(Mutex::new(Config::default())),
+            provider: Arc::new(MockProvider::default()),
+            model: "test-model".into(),
+            temperature: 0.0,
+            mem: Arc::new(TrackingMemory::default()),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(true, &["zc_mcp_token".into()])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            mcp,
+            agent: Arc::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn mcp_endpoint_is_disabled_by_default() {
+        let response = handle_mcp(
+            State(mcp_test_state(None)),
+            test_connect_info(),
+            HeaderMap::new(),
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#.into(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn mcp_endpoint_requires_pairing_and_serves_json_rpc() {
+        let server = crate::mcp::McpServer::new(
+            Vec::new(),
+            Arc::new(TrackingMemory::default()),
+            crate::approval::ApprovalManager::from_config(&crate::config::AutonomyConfig::default()),
+            None,
+        );
+        let state = mcp_test_state(Some(Arc::new(server)));
+        let request = r#"{"jsonrpc":"2.0","id":7,"method":"tools/list"}"#;
+
+        let unauthorized = handle_mcp(
+            State(state.clone()),
+            test_connect_info(),
+            HeaderMap::new(),
+            request.into(),
+        )
+        .await;
+        assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer zc_mcp_token"),
+        );
+        let response = handle_mcp(
+            State(state.clone()),
+            test_connect_info(),
+            headers.clone(),
+            request.into(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let payload = response.into_body().collect().await.unwrap().to_bytes();
+        let parsed: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(parsed["id"], 7);
+        assert_eq!(parsed["result"]["tools"], serde_json::json!([]));
+
+        let notification = handle_mcp(
+            State(state),
+            test_connect_info(),
+            headers,
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#.into(),
+        )
+        .await;
+        assert_eq!(notification.status(), StatusCode::ACCEPTED);
+    }
 }
@@ -0,0 +1,1061 @@
+//! OpenAI-compatible chat API: `POST /v1/chat/completions` and `GET /v1/models`.
+//!
+//! Lets OpenAI SDKs, IDE plugins and chat UIs talk to zeroclaw. Both routes
+//! use the pairing bearer token and share the webhook rate limit.
+//!
+//! - Without `tools`, the conversation runs through the full agent loop
+//!   (memory context, zeroclaw tools, security policy). The agent is built
+//!   once per gateway and shared by requests. With `stream: true` the
+//!   agent's text is sent as `chat.completion.chunk` SSE events as the model
+//!   produces it. Tools that would need approval are denied, since nobody
+//!   can be asked mid-request.
+//! - With `tools`, the client's tool definitions are passed to the model and
+//!   its tool calls are returned for the client to execute
+//!   (`finish_reason: "tool_calls"`). zeroclaw's own tools are not offered
+//!   in that mode.
+
+use super::{client_key_from_request, AppState, RATE_LIMIT_WINDOW_SECS};
+use crate::agent::{ConversationRuntime, TurnDelta};
+use crate::config::{Config, ReasoningDisplay};
+use crate::memory::MemoryCategory;
+use crate::providers::{
+    self, ChatMessage, ChatRequest, ChatResponse, ContentPart, MediaSource, ToolCall,
+};
+use crate::tools::ToolSpec;
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Model id that always maps to the configured default model.
+const DEFAULT_MODEL_ALIAS: &str = "zeroclaw";
+
+/// Channel name agent requests run under: approvals are recorded against it
+/// and `channels_config.reasoning_display.gateway` decides whether reasoning
+/// is streamed.
+const GATEWAY_CHANNEL: &str = "gateway";
+
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionRequest {
+    #[serde(default)]
+    model: Option<String>,
+    messages: Vec<RequestMessage>,
+    #[serde(default)]
+    stream: bool,
+    #[serde(default)]
+    temperature: Option<f64>,
+    #[serde(default)]
+    tools: Option<Vec<RequestTool>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestMessage {
+    role: String,
+    /// A string, or an array of `text` / `image_url` / `input_audio` content parts.
+    #[serde(default)]
+    content: Option<Value>,
+    #[serde(default)]
+    tool_calls: Option<Vec<RequestToolCall>>,
+    #[serde(default)]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestToolCall {
+    id: String,
+    function: RequestFunctionCall,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestTool {
+    function: RequestFunction,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestFunction {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    parameters: Option<Value>,
+}
+
+/// GET /v1/models — default model plus one `hint:<name>` id per model route
+pub(super) async fn handle_models(
+    State(state): State<AppState>,
+    ConnectInfo(peer_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(rejection) = reject_request(&state, peer_addr, &headers) {
+        return rejection;
+    }
+
+    let data = list_models(&state.config.lock(), &state.model);
+    Json(json!({ "object": "list", "data": data })).into_response()
+}
+
+/// POST /v1/chat/completions — OpenAI-compatible chat completions
+pub(super) async fn handle_chat_completions(
+    State(state): State<AppState>,
+    ConnectInfo(peer_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Result<Json<ChatCompletionRequest>, axum::extract::rejection::JsonRejection>,
+) -> Response {
+    if let Some(rejection) = reject_request(&state, peer_addr, &headers) {
+        return rejection;
+    }
+
+    let Json(request) = match body {
+        Ok(b) => b,
+        Err(e) => {
+            tracing::warn!("/v1/chat/completions JSON parse error: {e}");
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "invalid_request_error",
+                &format!("Invalid JSON body: {e}"),
+            );
+        }
+    };
+
+    let messages = match to_chat_messages(&request.messages) {
+        Ok(messages) => messages,
+        Err(message) => {
+            return error_response(StatusCode::BAD_REQUEST, "invalid_request_error", &message)
+        }
+    };
+    let Some(last_user) = messages.iter().rev().find(|m| m.role == "user") else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid_request_error",
+            "`messages` must contain at least one user message",
+        );
+    };
+
+    if state.auto_save {
+        let key = format!("openai_msg_{}", Uuid::new_v4());
+        let _ = state
+            .mem
+            .store(&key, &last_user.content, MemoryCategory::Conversation, None)
+            .await;
+    }
+
+    let model = resolve_model(request.model.as_deref(), &state.model);
+    let temperature = request.temperature.unwrap_or(state.temperature);
+    let completion = Completion::new(model);
+
+    match request.tools.filter(|tools| !tools.is_empty()) {
+        Some(tools) => {
+            let result = complete_with_client_tools(
+                &state,
+                &messages,
+                &tools,
+                &completion.model,
+                temperature,
+            )
+            .await;
+            match result {
+                Ok(response) if request.stream => completion.stream_response(&response),
+                Ok(response) => Json(completion.body(&response)).into_response(),
+                Err(e) => provider_error(&e),
+            }
+        }
+        None => {
+            let agent = match agent_runtime(&state).await {
+                Ok(agent) => agent,
+                Err(e) => return provider_error(&e),
+            };
+            if request.stream {
+                let show_reasoning = shows_reasoning(&state.config.lock());
+                completion.stream_agent(agent, messages, temperature, show_reasoning)
+            } else {
+                let model = completion.model.clone();
+                match agent
+                    .process(messages, &model, temperature, GATEWAY_CHANNEL, None)
+                    .await
+                {
+                    Ok(text) => Json(completion.body(&ChatResponse {
+                        text: Some(text),
+                        tool_calls: Vec::new(),
+                        usage: None,
+                        reasoning: None,
+                    }))
+                    .into_response(),
+                    Err(e) => provider_error(&e),
+                }
+            }
+        }
+    }
+}
+
+/// Apply the webhook rate limit and pairing bearer auth; `Some` is the error
+/// response to return.
+fn reject_request(
+    state: &AppState,
+    peer_addr: SocketAddr,
+    headers: &HeaderMap,
+) -> Option<Response> {
+    let rate_key = client_key_from_request(Some(peer_addr), headers, state.trust_forwarded_headers);
+    if !state.rate_limiter.allow_webhook(&rate_key) {
+        tracing::warn!("/v1 rate limit exceeded");
+        return Some(error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "rate_limit_error",
+            &format!("Too many requests. Please retry in {RATE_LIMIT_WINDOW_SECS}s."),
+        ));
+    }
+
+    if state.pairing.require_pairing() {
+        let auth = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        let token = auth.strip_prefix("Bearer ").unwrap_or("");
+        if !state.pairing.is_authenticated(token) {
+            tracing::warn!("/v1: rejected — not paired / invalid bearer token");
+            return Some(error_response(
+                StatusCode::UNAUTHORIZED,
+                "authentication_error",
+                "Unauthorized — pair first via POST /pair, then send Authorization: Bearer <token>",
+            ));
+        }
+    }
+
+    None
+}
+
+fn error_response(status: StatusCode, kind: &str, message: &str) -> Response {
+    let body = json!({ "error": { "message": message, "type": kind } });
+    (status, Json(body)).into_response()
+}
+
+fn provider_error(error: &anyhow::Error) -> Response {
+    let sanitized = providers::sanitize_api_error(&error.to_string());
+    tracing::error!("/v1/chat/completions failed: {sanitized}");
+    error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "server_error",
+        "LLM request failed",
+    )
+}
+
+/// Models advertised by `/v1/models`: the `zeroclaw` alias, the default
+/// model, and the `hint:<name>` ids `RouterProvider` resolves from
+/// `[[model_routes]]` and `[[routing_policies]]`.
+fn list_models(config: &Config, default_model: &str) -> Vec<Value> {
+    let default_provider = config.default_provider.as_deref().unwrap_or("openrouter");
+    let entry = |id: &str, owned_by: &str| json!({ "id": id, "object": "model", "created": 0, "owned_by": owned_by });
+
+    let mut models = vec![
+        entry(DEFAULT_MODEL_ALIAS, "zeroclaw"),
+        entry(default_model, default_provider),
+    ];
+    models.extend(
+        config
+            .model_routes
+            .iter()
+            .map(|route| entry(&format!("hint:{}", route.hint), &route.provider)),
+    );
+    models.extend(
+        config
+            .routing_policies
+            .iter()
+            .map(|policy| entry(&format!("hint:{}", policy.name), "zeroclaw")),
+    );
+    models
+}
+
+/// Map the request's `model` onto the model passed to the provider. Empty or
+/// `zeroclaw` selects the default; anything else (including `hint:*`) is
+/// passed through.
+fn resolve_model(requested: Option<&str>, default_model: &str) -> String {
+    match requested.map(str::trim) {
+        None | Some("" | DEFAULT_MODEL_ALIAS) => default_model.to_string(),
+        Some(model) => model.to_string(),
+    }
+}
+
+/// The gateway's agent runtime, built from its config on first use and
+/// reused by every later request. A failed build is retried next time.
+async fn agent_runtime(state: &AppState) -> anyhow::Result<Arc<ConversationRuntime>> {
+    state
+        .agent
+        .get_or_try_init(|| {
+            let config = state.config.lock().clone();
+            async move { ConversationRuntime::new(config).await.map(Arc::new) }
+        })
+        .await
+        .cloned()
+}
+
+/// Whether agent streams include the model's reasoning; hidden unless
+/// `channels_config.reasoning_display.gateway` says otherwise.
+fn shows_reasoning(config: &Config) -> bool {
+    config
+        .channels_config
+        .reasoning_display
+        .get(GATEWAY_CHANNEL)
+        .is_some_and(|display| *display != ReasoningDisplay::Hide)
+}
+
+/// Flatten OpenAI message content (a string or an array of parts) into text.
+fn content_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| match part.get("type").and_then(Value::as_str) {
+                Some("text") => part
+                    .get("text")
+                    .and_then(Value::as_str)
+                    .map(ToString::to_string),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Convert a user message, keeping `image_url` and `input_audio` parts as
+/// typed content for the multimodal pipeline.
+fn user_message(content: Option<&Value>) -> ChatMessage {
+    let Some(Value::Array(items)) = content else {
+        return ChatMessage::user(content_text(content));
+    };
+
+    let parts: Vec<ContentPart> = items
+        .iter()
+        .filter_map(|item| match item.get("type").and_then(Value::as_str) {
+            Some("text") => item
+                .get("text")
+                .and_then(Value::as_str)
+                .map(ContentPart::text),
+            Some("image_url") => item
+                .get("image_url")
+                .and_then(|image| image.get("url"))
+                .and_then(Value::as_str)
+                .map(|url| crate::multimodal::image_part_from_reference(url.to_string())),
+            Some("input_audio") => {
+                let audio = item.get("input_audio")?;
+                let data = audio.get("data").and_then(Value::as_str)?;
+                let format = audio.get("format").and_then(Value::as_str).unwrap_or("wav");
+                Some(ContentPart::Audio {
+                    mime_type: Some(format!("audio/{format}")),
+                    source: MediaSource::Base64(data.to_string()),
+                })
+            }
+            _ => None,
+        })
+        .collect();
+
+    if parts
+        .iter()
+        .all(|part| matches!(part, ContentPart::Text { .. }))
+    {
+        ChatMessage::user(content_text(content))
+    } else {
+        ChatMessage::with_parts("user", parts)
+    }
+}
+
+/// Convert OpenAI messages to history entries, using the JSON encoding the
+/// agent loop uses for native tool calls and tool results.
+fn to_chat_messages(messages: &[RequestMessage]) -> Result<Vec<ChatMessage>, String> {
+    messages
+        .iter()
+        .map(|message| {
+            let text = content_text(message.content.as_ref());
+            match message.role.as_str() {
+                "system" | "developer" => Ok(ChatMessage::system(text)),
+                "user" => Ok(user_message(message.content.as_ref())),
+                "assistant" => match message.tool_calls.as_deref() {
+                    Some(calls) if !calls.is_empty() => {
+                        let calls: Vec<Value> = calls
+                            .iter()
+                            .map(|call| {
+                                json!({
+                                    "id": call.id,
+                                    "name": call.function.name,
+                                    "arguments": call.function.arguments,
+                                })
+                            })
+                            .collect();
+                        let content = if text.is_empty() {
+                            Value::Null
+                        } else {
+                            Value::String(text)
+                        };
+                        Ok(ChatMessage::assistant(
+                            json!({ "content": content, "tool_calls": calls }).to_string(),
+                        ))
+                    }
+                    _ => Ok(ChatMessage::assistant(text)),
+                },
+                "tool" => Ok(ChatMessage::tool(
+                    json!({ "tool_call_id": message.tool_call_id, "content": text }).to_string(),
+                )),
+                other => Err(format!("Unsupported message role `{other}`")),
+            }
+        })
+        .collect()
+}
+
+/// One provider call with the client's tools; tool calls go back to the client.
+async fn complete_with_client_tools(
+    state: &AppState,
+    messages: &[ChatMessage],
+    tools: &[RequestTool],
+    model: &str,
+    temperature: f64,
+) -> anyhow::Result<ChatResponse> {
+    let specs: Vec<ToolSpec> = tools
+        .iter()
+        .map(|tool| ToolSpec {
+            name: tool.function.name.clone(),
+            description: tool.function.description.clone(),
+            parameters: tool
+                .function
+                .parameters
+                .clone()
+                .unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+        })
+        .collect();
+
+    let multimodal_config = state.config.lock().multimodal.clone();
+    let prepared =
+        crate::multimodal::prepare_messages_for_provider(messages, &multimodal_config).await?;
+
+    state
+        .provider
+        .chat(
+            ChatRequest {
+                messages: &prepared.messages,
+                tools: Some(&specs),
+                output_schema: None,
+            },
+            model,
+            temperature,
+        )
+        .await
+}
+
+fn tool_calls_json(calls: &[ToolCall]) -> Vec<Value> {
+    calls
+        .iter()
+        .enumerate()
+        .map(|(index, call)| {
+            json!({
+                "index": index,
+                "id": call.id,
+                "type": "function",
+                "function": { "name": call.name, "arguments": call.arguments },
+            })
+        })
+        .collect()
+}
+
+/// Identity shared by the body or every chunk of one completion.
+struct Completion {
+    id: String,
+    model: String,
+    created: u64,
+}
+
+impl Completion {
+    fn new(model: String) -> Self {
+        Self {
+            id: format!("chatcmpl-{}", Uuid::new_v4().simple()),
+            model,
+            created: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    fn body(&self, response: &ChatResponse) -> Value {
+        let mut message = json!({ "role": "assistant", "content": response.text });
+        let finish_reason = if response.has_tool_calls() {
+            message["tool_calls"] = Value::Array(tool_calls_json(&response.tool_calls));
+            "tool_calls"
+        } else {
+            "stop"
+        };
+
+        let mut body = json!({
+            "id": self.id,
+            "object": "chat.completion",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{ "index": 0, "message": message, "finish_reason": finish_reason }],
+        });
+        if let Some(usage) = &response.usage {
+            let prompt = usage.input_tokens.unwrap_or(0);
+            let completion = usage.output_tokens.unwrap_or(0);
+            body["usage"] = json!({
+                "prompt_tokens": prompt,
+                "completion_tokens": completion,
+                "total_tokens": prompt + completion,
+            });
+        }
+        body
+    }
+
+    fn chunk(&self, delta: Value, finish_reason: Option<&str>) -> Event {
+        let chunk = json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        });
+        Event::default().data(chunk.to_string())
+    }
+
+    /// Stream an already complete response as role, content, tool-call and
+    /// finish chunks.
+    fn stream_response(&self, response: &ChatResponse) -> Response {
+        let mut events = vec![self.chunk(json!({ "role": "assistant" }), None)];
+        if let Some(text) = response.text.as_deref().filter(|t| !t.is_empty()) {
+            events.push(self.chunk(json!({ "content": text }), None));
+        }
+        let finish_reason = if response.has_tool_calls() {
+            events.push(self.chunk(
+                json!({ "tool_calls": tool_calls_json(&response.tool_calls) }),
+                None,
+            ));
+            "tool_calls"
+        } else {
+            "stop"
+        };
+        events.push(self.chunk(json!({}), Some(finish_reason)));
+        events.push(Event::default().data("[DONE]"));
+
+        let stream = futures_util::stream::iter(events.into_iter().map(Ok::<_, Infallible>));
+        Sse::new(stream).into_response()
+    }
+
+    /// Run the agent in the background and stream its answer as it is relayed.
+    ///
+    /// Reasoning is streamed as `reasoning_content` only when `show_reasoning`.
+    fn stream_agent(
+        self,
+        agent: Arc<ConversationRuntime>,
+        messages: Vec<ChatMessage>,
+        temperature: f64,
+        show_reasoning: bool,
+    ) -> Response {
+        let (event_tx, event_rx) = tokio::sync::mpsc::channel::<Event>(64);
+
+        tokio::spawn(async move {
+            let (delta_tx, mut delta_rx) = tokio::sync::mpsc::channel::<TurnDelta>(64);
+            let model = self.model.clone();
+            let run = tokio::spawn(async move {
+                agent
+                    .process(
+                        messages,
+                        &model,
+                        temperature,
+                        GATEWAY_CHANNEL,
+                        Some(delta_tx),
+                    )
+                    .await
+            });
+
+            let _ = event_tx
+                .send(self.chunk(json!({ "role": "assistant" }), None))
+                .await;
+            // Tool activity is internal to the agent; it only separates the
+            // text around it.
+            let (mut sent_text, mut after_tool_call) = (false, false);
+            while let Some(delta) = delta_rx.recv().await {
+                let text = match delta {
+                    TurnDelta::Text(text) if sent_text && after_tool_call => {
+                        format!("\n\n{text}")
+                    }
+                    TurnDelta::Text(text) => text,
+                    TurnDelta::ToolCall(_) => {
+                        after_tool_call = true;
+                        continue;
+                    }
+                    TurnDelta::Reasoning(_) if !show_reasoning => continue,
+                    // Same field DeepSeek-style clients already read.
+                    TurnDelta::Reasoning(reasoning) => {
+                        if event_tx
+                            .send(self.chunk(json!({ "reasoning_content": reasoning }), None))
+                            .await
+                            .is_err()
+                        {
+                            run.abort();
+                            return;
+                        }
+                        continue;
+                    }
+                };
+                (sent_text, after_tool_call) = (true, false);
+                if event_tx
+                    .send(self.chunk(json!({ "content": text }), None))
+                    .await
+                    .is_err()
+                {
+                    // Client went away; stop the agent.
+                    run.abort();
+                    return;
+                }
+            }
+
+            let last = match run.await {
+                Ok(Ok(_)) => self.chunk(json!({}), Some("stop")),
+                Ok(Err(e)) => {
+                    let sanitized = providers::sanitize_api_error(&e.to_string());
+                    tracing::error!("/v1/chat/completions stream failed: {sanitized}");
+                    let error = json!({
+                        "error": { "message": "LLM request failed", "type": "server_error" }
+                    });
+                    Event::default().data(error.to_string())
+                }
+                Err(e) => {
+                    tracing::error!("/v1/chat/completions agent task failed: {e}");
+                    let error = json!({
+                        "error": { "message": "LLM request failed", "type": "server_error" }
+                    });
+                    Event::default().data(error.to_string())
+                }
+            };
+            let _ = event_tx.send(last).await;
+            let _ = event_tx.send(Event::default().data("[DONE]")).await;
+        });
+
+        let stream = futures_util::stream::unfold(event_rx, |mut rx| async move {
+            rx.recv()
+                .await
+                .map(|event| (Ok::<_, Infallible>(event), rx))
+        });
+        Sse::new(stream)
+            .keep_alive(KeepAlive::default())
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelRouteConfig;
+    use crate::gateway::{GatewayRateLimiter, IdempotencyStore};
+    use crate::providers::Provider;
+    use crate::security::pairing::PairingGuard;
+    use async_trait::async_trait;
+    use axum::http::HeaderValue;
+    use http_body_util::BodyExt;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Calls the first tool it is offered and records what it received.
+    #[derive(Default)]
+    struct ToolCallingProvider {
+        seen: Mutex<Vec<(Vec<ChatMessage>, Vec<String>, String)>>,
+    }
+
+    #[async_trait]
+    impl Provider for ToolCallingProvider {
+        fn supports_native_tools(&self) -> bool {
+            true
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("plain".into())
+        }
+
+        async fn chat(
+            &self,
+            request: ChatRequest<'_>,
+            model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            let tools: Vec<String> = request
+                .tools
+                .unwrap_or_default()
+                .iter()
+                .map(|t| t.name.clone())
+                .collect();
+            self.seen
+                .lock()
+                .push((request.messages.to_vec(), tools.clone(), model.to_string()));
+            Ok(ChatResponse {
+                text: None,
+                tool_calls: vec![ToolCall {
+                    id: "call_1".into(),
+                    name: tools.first().cloned().unwrap_or_default(),
+                    arguments: r#"{"city":"Paris"}"#.into(),
+                }],
+                usage: Some(crate::providers::traits::TokenUsage {
+                    input_tokens: Some(12),
+                    output_tokens: Some(3),
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
+                }),
+                reasoning: None,
+            })
+        }
+    }
+
+    fn test_state(provider: Arc<ToolCallingProvider>) -> AppState {
+        let mut config = Config::default();
+        config.default_provider = Some("openrouter".into());
+        config.model_routes = vec![ModelRouteConfig {
+            hint: "fast".into(),
+            provider: "groq".into(),
+            model: "llama-3.3-70b".into(),
+            api_key: None,
+            context_window: None,
+            vision: None,
+            native_tools: None,
+        }];
+        AppState {
+            config: Arc::new(Mutex::new(config)),
+            provider,
+            model: "test-model".into(),
+            temperature: 0.7,
+            mem: Arc::new(crate::memory::NoneMemory::new()),
+            auto_save: false,
+            webhook_secret_hash: None,
+            pairing: Arc::new(PairingGuard::new(true, &["zc_openai_token".into()])),
+            trust_forwarded_headers: false,
+            rate_limiter: Arc::new(GatewayRateLimiter::new(100, 100, 100)),
+            idempotency_store: Arc::new(IdempotencyStore::new(Duration::from_secs(300), 1000)),
+            whatsapp: None,
+            whatsapp_app_secret: None,
+            linq: None,
+            linq_signing_secret: None,
+            nextcloud_talk: None,
+            nextcloud_talk_webhook_secret: None,
+            observer: Arc::new(crate::observability::NoopObserver),
+            mcp: None,
+            agent: Arc::default(),
+        }
+    }
+
+    fn connect_info() -> ConnectInfo<SocketAddr> {
+        ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 30_300)))
+    }
+
+    fn auth_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer zc_openai_token"),
+        );
+        headers
+    }
+
+    fn request(
+        body: Value,
+    ) -> Result<Json<ChatCompletionRequest>, axum::extract::rejection::JsonRejection> {
+        Ok(Json(serde_json::from_value(body).unwrap()))
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    fn weather_request(stream: bool) -> Value {
+        json!({
+            "model": "hint:fast",
+            "stream": stream,
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": [{ "type": "text", "text": "Weather?" }] }
+            ],
+            "tools": [{
+                "type": "function",
+                "function": {
+                    "name": "get_weather",
+                    "description": "Look up weather",
+                    "parameters": { "type": "object", "properties": { "city": { "type": "string" } } }
+                }
+            }]
+        })
+    }
+
+    #[test]
+    fn to_chat_messages_encodes_tool_history_like_the_agent_loop() {
+        let messages: Vec<RequestMessage> = serde_json::from_value(json!([
+            { "role": "developer", "content": "rules" },
+            { "role": "user", "content": [
+                { "type": "text", "text": "What is this?" },
+                { "type": "image_url", "image_url": { "url": "https://example.com/sky.png" } }
+            ]},
+            { "role": "assistant", "content": null, "tool_calls": [{
+                "id": "call_9", "type": "function",
+                "function": { "name": "lookup", "arguments": "{\"q\":1}" }
+            }]},
+            { "role": "tool", "tool_call_id": "call_9", "content": "42" }
+        ]))
+        .unwrap();
+
+        let converted = to_chat_messages(&messages).unwrap();
+        assert_eq!(converted[0].role, "system");
+        assert_eq!(
+            converted[1].parts,
+            vec![
+                ContentPart::text("What is this?"),
+                ContentPart::image_url("https://example.com/sky.png"),
+            ]
+        );
+
+        let assistant: Value = serde_json::from_str(&converted[2].content).unwrap();
+        assert_eq!(assistant["content"], Value::Null);
+        assert_eq!(assistant["tool_calls"][0]["id"], "call_9");
+        assert_eq!(assistant["tool_calls"][0]["name"], "lookup");
+
+        let tool: Value = serde_json::from_str(&converted[3].content).unwrap();
+        assert_eq!(converted[3].role, "tool");
+        assert_eq!(tool["tool_call_id"], "call_9");
+        assert_eq!(tool["content"], "42");
+    }
+
+    #[test]
+    fn to_chat_messages_rejects_unknown_roles() {
+        let messages: Vec<RequestMessage> =
+            serde_json::from_value(json!([{ "role": "function", "content": "x" }])).unwrap();
+        assert!(to_chat_messages(&messages).is_err());
+    }
+
+    #[test]
+    fn resolve_model_maps_alias_and_passes_hints_through() {
+        assert_eq!(resolve_model(None, "default"), "default");
+        assert_eq!(resolve_model(Some(" zeroclaw "), "default"), "default");
+        assert_eq!(resolve_model(Some("hint:fast"), "default"), "hint:fast");
+        assert_eq!(resolve_model(Some("gpt-4o"), "default"), "gpt-4o");
+    }
+
+    #[tokio::test]
+    async fn models_endpoint_lists_default_model_and_routes() {
+        let state = test_state(Arc::new(ToolCallingProvider::default()));
+
+        let unauthorized =
+            handle_models(State(state.clone()), connect_info(), HeaderMap::new()).await;
+        assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+        let response = handle_models(State(state), connect_info(), auth_headers()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed: Value = serde_json::from_str(&body_text(response).await).unwrap();
+        let ids: Vec<&str> = parsed["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["zeroclaw", "test-model", "hint:fast"]);
+        assert_eq!(parsed["data"][2]["owned_by"], "groq");
+    }
+
+    #[tokio::test]
+    async fn chat_completions_requires_bearer_token() {
+        let state = test_state(Arc::new(ToolCallingProvider::default()));
+        let response = handle_chat_completions(
+            State(state),
+            connect_info(),
+            HeaderMap::new(),
+            request(weather_request(false)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let parsed: Value = serde_json::from_str(&body_text(response).await).unwrap();
+        assert_eq!(parsed["error"]["type"], "authentication_error");
+    }
+
+    #[tokio::test]
+    async fn chat_completions_requires_a_user_message() {
+        let state = test_state(Arc::new(ToolCallingProvider::default()));
+        let response = handle_chat_completions(
+            State(state),
+            connect_info(),
+            auth_headers(),
+            request(json!({ "messages": [{ "role": "system", "content": "hi" }] })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn client_tools_are_passed_through_and_calls_returned() {
+        let provider = Arc::new(ToolCallingProvider::default());
+        let state = test_state(Arc::clone(&provider));
+
+        let response = handle_chat_completions(
+            State(state),
+            connect_info(),
+            auth_headers(),
+            request(weather_request(false)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed: Value = serde_json::from_str(&body_text(response).await).unwrap();
+
+        assert_eq!(parsed["object"], "chat.completion");
+        assert_eq!(parsed["model"], "hint:fast");
+        let choice = &parsed["choices"][0];
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert_eq!(choice["message"]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(
+            choice["message"]["tool_calls"][0]["function"]["name"],
+            "get_weather"
+        );
+        assert_eq!(parsed["usage"]["total_tokens"], 15);
+
+        let seen = provider.seen.lock();
+        let (messages, tools, model) = &seen[0];
+        assert_eq!(tools, &vec!["get_weather".to_string()]);
+        assert_eq!(model, "hint:fast");
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(messages[1].content, "Weather?");
+    }
+
+    #[tokio::test]
+    async fn client_tool_calls_stream_as_sse_chunks() {
+        let state = test_state(Arc::new(ToolCallingProvider::default()));
+        let response = handle_chat_completions(
+            State(state),
+            connect_info(),
+            auth_headers(),
+            request(weather_request(true)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+
+        let text = body_text(response).await;
+        let events: Vec<&str> = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .collect();
+        assert_eq!(events.last(), Some(&"[DONE]"));
+
+        let chunks: Vec<Value> = events[..events.len() - 1]
+            .iter()
+            .map(|e| serde_json::from_str(e).unwrap())
+            .collect();
+        assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+        assert!(chunks
+            .iter()
+            .all(|c| c["object"] == "chat.completion.chunk" && c["id"] == chunks[0]["id"]));
+        assert_eq!(
+            chunks[1]["choices"][0]["delta"]["tool_calls"][0]["function"]["arguments"],
+            r#"{"city":"Paris"}"#
+        );
+        assert_eq!(
+            chunks.last().unwrap()["choices"][0]["finish_reason"],
+            "tool_calls"
+        );
+    }
+
+    #[tokio::test]
+    async fn agent_runtime_is_built_once_and_reused() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let script = tmp.path().join("script.json");
+        std::fs::write(
+            &script,
+            r#"{"responses":[{"text":"first answer"},{"text":"second answer"}]}"#,
+        )
+        .unwrap();
+        let state = test_state(Arc::new(ToolCallingProvider::default()));
+        {
+            let mut config = state.config.lock();
+            config.default_provider = Some(format!("scripted:{}", script.display()));
+            config.workspace_dir = tmp.path().to_path_buf();
+            config.config_path = tmp.path().join("config.toml");
+            config.memory.backend = "none".into();
+            config.reliability.provider_retries = 0;
+        }
+
+        // A rebuilt agent would reload the script and answer "first" again.
+        for expected in ["first answer", "second answer"] {
+            let response = handle_chat_completions(
+                State(state.clone()),
+                connect_info(),
+                auth_headers(),
+                request(json!({ "messages": [{ "role": "user", "content": "hi" }] })),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let parsed: Value = serde_json::from_str(&body_text(response).await).unwrap();
+            assert_eq!(parsed["choices"][0]["message"]["content"], expected);
+        }
+        assert!(state.agent.initialized());
+    }
+
+    #[tokio::test]
+    async fn agent_denies_tools_that_need_approval() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let script = tmp.path().join("script.json");
+        std::fs::write(
+            &script,
+            r#"{"responses":[
+                {"tool_calls":[{"name":"file_write","arguments":{"path":"note.txt","content":"x"}}]},
+                {"text":"done"}
+            ]}"#,
+        )
+        .unwrap();
+        let state = test_state(Arc::new(ToolCallingProvider::default()));
+        {
+            let mut config = state.config.lock();
+            config.default_provider = Some(format!("scripted:{}", script.display()));
+            config.workspace_dir = tmp.path().to_path_buf();
+            config.config_path = tmp.path().join("config.toml");
+            config.memory.backend = "none".into();
+            config.reliability.provider_retries = 0;
+        }
+
+        let response = handle_chat_completions(
+            State(state.clone()),
+            connect_info(),
+            auth_headers(),
+            request(json!({ "messages": [{ "role": "user", "content": "write a note" }] })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!tmp.path().join("note.txt").exists());
+        let audit = std::fs::read_to_string(tmp.path().join("audit.log")).unwrap();
+        assert!(audit.contains(r#""channel":"gateway""#));
+    }
+
+    #[test]
+    fn reasoning_is_streamed_only_when_the_gateway_shows_it() {
+        let mut config = Config::default();
+        assert!(!shows_reasoning(&config));
+        config
+            .channels_config
+            .reasoning_display
+            .insert("gateway".into(), ReasoningDisplay::Collapsed);
+        assert!(shows_reasoning(&config));
+    }
+}
@@ -14,17 +14,34 @@ pub struct ComponentHealth {
     pub restart_count: u64,
 }
 
+/// Circuit-breaker and rolling health state of one provider/model pair.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub model: String,
+    /// `closed`, `open` or `half_open`.
+    pub circuit: String,
+    pub consecutive_failures: u32,
+    pub error_rate: f64,
+    pub latency_ms: Option<u64>,
+    pub opened_at: Option<String>,
+    pub updated_at: String,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct HealthSnapshot {
     pub pid: u32,
     pub updated_at: String,
     pub uptime_seconds: u64,
     pub components: BTreeMap<String, ComponentHealth>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub providers: BTreeMap<String, ProviderHealth>,
 }
 
 struct HealthRegistry {
     started_at: Instant,
     components: Mutex<BTreeMap<String, ComponentHealth>>,
+    providers: Mutex<BTreeMap<String, ProviderHealth>>,
 }
 
 static REGISTRY: OnceLock<HealthRegistry> = OnceLock::new();
@@ -33,6 +50,7 @@ fn registry() -> &'static HealthRegistry {
     REGISTRY.get_or_init(|| HealthRegistry {
         started_at: Instant::now(),
         components: Mutex::new(BTreeMap::new()),
+        providers: Mutex::new(BTreeMap::new()),
     })
 }
 
@@ -82,14 +100,32 @@ pub fn bump_component_restart(component: &str) {
     });
 }
 
+/// Publish the latest state of a provider/model pair, keyed `provider/model`.
+pub fn record_provider_health(mut health: ProviderHealth) {
+    health.updated_at = now_rfc3339();
+    let key = format!("{}/{}", health.provider, health.model);
+    registry().providers.lock().insert(key, health);
+}
+
+/// Latest published state of a provider/model pair, if it has been called.
+pub fn provider_health(provider: &str, model: &str) -> Option<ProviderHealth> {
+    registry()
+        .providers
+        .lock()
+        .get(&format!("{provider}/{model}"))
+        .cloned()
+}
+
 pub fn snapshot() -> HealthSnapshot {
     let components = registry().components.lock().clone();
+    let providers = registry().providers.lock().clone();
 
     HealthSnapshot {
         pid: std::process::id(),
         updated_at: now_rfc3339(),
         uptime_seconds: registry().started_at.elapsed().as_secs(),
         components,
+        providers,
     }
 }
 
@@ -181,4 +217,26 @@ mod tests {
         assert!(component_json["last_ok"].as_str().is_some());
         assert!(json["uptime_seconds"].as_u64().is_some());
     }
+
+    #[test]
+    fn record_provider_health_is_keyed_by_provider_and_model() {
+        let provider = unique_component("health-provider");
+
+        record_provider_health(ProviderHealth {
+            provider: provider.clone(),
+            model: "model-a".into(),
+            circuit: "open".into(),
+            consecutive_failures: 3,
+            error_rate: 0.5,
+            latency_ms: Some(120),
+            opened_at: Some(now_rfc3339()),
+            updated_at: String::new(),
+        });
+
+        let json = snapshot_json();
+        let entry = &json["providers"][format!("{provider}/model-a")];
+        assert_eq!(entry["circuit"], "open");
+        assert_eq!(entry["consecutive_failures"], 3);
+        assert!(!entry["updated_at"].as_str().unwrap().is_empty());
+    }
 }
@@ -42,11 +42,13 @@ pub mod agent;
 pub(crate) mod approval;
 pub(crate) mod auth;
 pub mod channels;
+pub(crate) mod checkpoint;
 pub mod config;
 pub(crate) mod cost;
 pub(crate) mod cron;
 pub(crate) mod daemon;
 pub(crate) mod doctor;
+pub(crate) mod eval;
 pub mod gateway;
 pub(crate) mod hardware;
 pub(crate) mod health;
@@ -54,6 +56,7 @@ pub(crate) mod heartbeat;
 pub mod hooks;
 pub(crate) mod identity;
 pub(crate) mod integrations;
+pub(crate) mod mcp;
 pub mod memory;
 pub(crate) mod migration;
 pub(crate) mod multimodal;
@@ -65,6 +68,7 @@ pub mod rag;
 pub mod runtime;
 pub(crate) mod security;
 pub(crate) mod service;
+pub(crate) mod sessions;
 pub(crate) mod skills;
 pub mod tools;
 pub(crate) mod tunnel;
@@ -324,6 +328,130 @@ pub enum MemoryCommands {
     },
 }
 
+/// Conversation session subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SessionCommands {
+    /// List persisted conversation sessions
+    List,
+    /// Show the stored history for a session
+    Show {
+        /// Session key (e.g. telegram_alice); unique prefixes are accepted
+        key: String,
+    },
+    /// Export a session transcript as Markdown or JSONL
+    Export {
+        /// Session key (e.g. cli_refactor); unique prefixes are accepted
+        key: String,
+        /// Transcript format: markdown or jsonl
+        #[arg(long, default_value = "markdown")]
+        format: String,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Delete one session
+    Delete {
+        /// Session key to delete (supports prefix match)
+        key: String,
+        /// Skip confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Clear one session, or all sessions when no key is given
+    Clear {
+        /// Session key to clear (supports prefix match)
+        key: Option<String>,
+        /// Skip confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// Workspace checkpoint subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CheckpointCommands {
+    /// List checkpoints, newest first
+    List {
+        /// Maximum number of checkpoints to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+    /// Show how the workspace changed since a checkpoint
+    Diff {
+        /// Checkpoint id (unique prefixes are accepted)
+        id: String,
+        /// Only diff this workspace-relative path
+        #[arg(long)]
+        path: Option<String>,
+    },
+    /// Restore the files saved in a checkpoint
+    Restore {
+        /// Checkpoint id (unique prefixes are accepted)
+        id: String,
+        /// Skip confirmation prompt
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// Cost reporting subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CostCommands {
+    /// Show today's and this month's spend against the configured limits
+    Summary,
+    /// Show spend for a single day, broken down by model
+    Daily {
+        /// Date in YYYY-MM-DD format (UTC, defaults to today)
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Show spend for a calendar month, broken down by model
+    Monthly {
+        /// Month in YYYY-MM format (UTC, defaults to the current month)
+        #[arg(long)]
+        month: Option<String>,
+    },
+    /// Show spend per model for a period
+    ByModel {
+        /// Period to aggregate: day, month, or all
+        #[arg(long, default_value = "month")]
+        period: String,
+    },
+}
+
+/// Agent evaluation subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EvalCommands {
+    /// Run scenario files and report pass rates, token usage and latency
+    Run {
+        /// Scenario files or directories of *.yaml / *.yml / *.json scenarios
+        #[arg(required = true)]
+        paths: Vec<std::path::PathBuf>,
+        /// Provider for variants that do not set one
+        #[arg(short, long)]
+        provider: Option<String>,
+        /// Model for variants that do not set one
+        #[arg(long)]
+        model: Option<String>,
+        /// Variant to compare (repeatable): name=<label>,provider=<id>,model=<id>,prompt_dir=<path>,temperature=<t>
+        #[arg(long = "variant")]
+        variants: Vec<String>,
+        /// Report format: table, json or junit
+        #[arg(long, default_value = "table")]
+        format: String,
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+}
+
+/// MCP (Model Context Protocol) subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum McpCommands {
+    /// Serve zeroclaw's tools and memory to an MCP client over stdio
+    Serve,
+}
+
 /// Integration subcommands
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum IntegrationCommands {
@@ -52,13 +52,16 @@ mod agent;
 mod approval;
 mod auth;
 mod channels;
+mod checkpoint;
 mod rag {
     pub use zeroclaw::rag::*;
 }
 mod config;
+mod cost;
 mod cron;
 mod daemon;
 mod doctor;
+mod eval;
 mod gateway;
 mod hardware;
 mod health;
@@ -66,6 +69,7 @@ mod heartbeat;
 mod hooks;
 mod identity;
 mod integrations;
+mod mcp;
 mod memory;
 mod migration;
 mod multimodal;
@@ -76,6 +80,7 @@ mod providers;
 mod runtime;
 mod security;
 mod service;
+mod sessions;
 mod skillforge;
 mod skills;
 mod tools;
@@ -86,8 +91,9 @@ use config::Config;
 
 // Re-export so binary modules can use crate::<CommandEnum> while keeping a single source of truth.
 pub use zeroclaw::{
-    ChannelCommands, CronCommands, HardwareCommands, IntegrationCommands, MigrateCommands,
-    PeripheralCommands, ServiceCommands, SkillCommands,
+    ChannelCommands, CheckpointCommands, CostCommands, CronCommands, EvalCommands,
+    HardwareCommands, IntegrationCommands, McpCommands, MigrateCommands, PeripheralCommands,
+    ServiceCommands, SessionCommands, SkillCommands,
 };
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
@@ -181,6 +187,18 @@ Examples:
         /// Attach a peripheral (board:path, e.g. nucleo-f401re:/dev/ttyACM0)
         #[arg(long)]
         peripheral: Vec<String>,
+
+        /// Save the conversation as a named session, continuing it if it exists
+        #[arg(long)]
+        session: Option<String>,
+
+        /// Resume the most recent session (or the one named by --session)
+        #[arg(long)]
+        resume: bool,
+
+        /// JSON schema file the final answer must validate against
+        #[arg(long)]
+        output_schema: Option<std::path::PathBuf>,
     },
 
     /// Start the gateway server (webhooks, websockets)
@@ -383,6 +401,101 @@ Examples:
         memory_command: MemoryCommands,
     },
 
+    /// Manage persisted conversation sessions (list, show, clear)
+    #[command(long_about = "\
+Manage persisted conversation sessions.
+
+Channel conversations (Telegram, Discord, Slack, ...) are stored per \
+sender in the workspace so context survives daemon restarts. Session \
+keys have the form <channel>_<sender>.
+
+Examples:
+  zeroclaw sessions list
+  zeroclaw sessions show telegram_alice
+  zeroclaw sessions clear telegram_alice
+  zeroclaw sessions clear --yes")]
+    Sessions {
+        #[command(subcommand)]
+        session_command: SessionCommands,
+    },
+
+    /// List, diff and restore workspace checkpoints
+    #[command(long_about = "\
+List, diff and restore workspace checkpoints.
+
+Before file_write, file_edit, apply_patch (and shell / git_operations \
+when [checkpoints] track_shell = true) change files, their previous \
+content is saved under <workspace>/state/checkpoints. All changes from \
+one agent turn share a checkpoint; /undo in the CLI or a channel \
+restores the latest one for that conversation. Ids accept unique \
+prefixes.
+
+Examples:
+  zeroclaw checkpoints list
+  zeroclaw checkpoints diff 20260301-142210-3fa2
+  zeroclaw checkpoints diff 20260301-142210 --path src/main.rs
+  zeroclaw checkpoints restore 20260301-142210-3fa2 --yes")]
+    Checkpoints {
+        #[command(subcommand)]
+        checkpoint_command: CheckpointCommands,
+    },
+
+    /// Report API spend tracked by the [cost] budget
+    #[command(long_about = "\
+Report API spend recorded by cost tracking.
+
+Usage is recorded for every provider call when [cost] enabled = true \
+and stored in the workspace. Costs are estimated from [cost.prices]; \
+dates and months are UTC.
+
+Examples:
+  zeroclaw cost summary
+  zeroclaw cost daily --date 2026-01-15
+  zeroclaw cost monthly --month 2026-01
+  zeroclaw cost by-model --period all")]
+    Cost {
+        #[command(subcommand)]
+        cost_command: CostCommands,
+    },
+
+    /// Run agent evaluation scenarios
+    #[command(long_about = "\
+Run agent evaluation scenarios.
+
+Each scenario file (YAML or JSON) lists user messages, optional seeded \
+workspace files and memory, and expectations on tool calls, the final \
+answer and files produced. Scenarios run in throwaway workspaces that \
+receive the prompt files (AGENTS.md, SOUL.md, ...) and skills of the \
+configured workspace or a variant's prompt_dir. Repeat --variant to \
+compare models or prompt variants side by side. Exits non-zero when \
+any scenario fails.
+
+Examples:
+  zeroclaw eval run evals/
+  zeroclaw eval run evals/ --format junit --output eval.xml
+  zeroclaw eval run evals/ --variant name=a,model=gpt-4o \\
+      --variant name=b,model=claude-sonnet-4,prompt_dir=prompts/b")]
+    Eval {
+        #[command(subcommand)]
+        eval_command: EvalCommands,
+    },
+
+    /// Serve zeroclaw's tools to MCP clients
+    #[command(long_about = "\
+Model Context Protocol integration.
+
+'serve' speaks MCP over stdin/stdout so editors and other agents can \
+call zeroclaw's tools with the same security policy, sandboxing and \
+audit logging as the agent. Memory entries are exposed as memory:// \
+resources. Logs are written to stderr.
+
+Examples:
+  zeroclaw mcp serve")]
+    Mcp {
+        #[command(subcommand)]
+        mcp_command: McpCommands,
+    },
+
     /// Manage configuration
     #[command(long_about = "\
 Manage ZeroClaw configuration.
@@ -590,11 +703,19 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Initialize logging - respects RUST_LOG env var, defaults to INFO
+    // Initialize logging - respects RUST_LOG env var, defaults to INFO.
+    // `mcp serve` owns stdout for JSON-RPC and `eval` prints reports there,
+    // so their logs go to stderr.
+    let log_writer = if matches!(cli.command, Commands::Mcp { .. } | Commands::Eval { .. }) {
+        fmt::writer::BoxMakeWriter::new(std::io::stderr)
+    } else {
+        fmt::writer::BoxMakeWriter::new(std::io::stdout)
+    };
     let subscriber = fmt::Subscriber::builder()
         .with_env_filter(
             EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
         )
+        .with_writer(log_writer)
         .finish();
 
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
@@ -656,6 +777,7 @@ async fn main() -> Result<()> {
     // All other commands need config loaded first
     let mut config = Config::load_or_init().await?;
     config.apply_env_overrides();
+    providers::catalog::load_workspace_cache(&config.workspace_dir);
 
     match cli.command {
         Commands::Onboard { .. } => unreachable!(),
@@ -667,9 +789,28 @@ async fn main() -> Result<()> {
             model,
             temperature,
             peripheral,
-        } => agent::run(config, message, provider, model, temperature, peripheral)
+            session,
+            resume,
+            output_schema,
+        } => {
+            let session = sessions::CliSession::from_flags(&config, session.as_deref(), resume)?;
+            let output_schema = output_schema
+                .as_deref()
+                .map(providers::OutputSchema::from_file)
+                .transpose()?;
+            agent::run(
+                config,
+                message,
+                provider,
+                model,
+                temperature,
+                peripheral,
+                session,
+                output_schema,
+            )
             .await
-            .map(|_| ()),
+            .map(|_| ())
+        }
 
         Commands::Gateway { port, host } => {
             let port = port.unwrap_or(config.gateway.port);
@@ -855,6 +996,20 @@ async fn main() -> Result<()> {
             memory::cli::handle_command(memory_command, &config).await
         }
 
+        Commands::Sessions { session_command } => {
+            sessions::handle_command(session_command, &config)
+        }
+
+        Commands::Checkpoints { checkpoint_command } => {
+            checkpoint::handle_command(checkpoint_command, &config)
+        }
+
+        Commands::Cost { cost_command } => cost::handle_command(cost_command, &config),
+
+        Commands::Eval { eval_command } => eval::handle_command(eval_command, &config).await,
+
+        Commands::Mcp { mcp_command } => mcp::handle_command(mcp_command, &config).await,
+
         Commands::Auth { auth_command } => handle_auth_command(auth_command, &config).await,
 
         Commands::Hardware { hardware_command } => {
@@ -0,0 +1,227 @@
+//! MCP client: connection lifecycle, handshake, and tool calls for one server.
+
+use super::protocol::{JsonRpcRequest, McpToolInfo, PROTOCOL_VERSION};
+use super::transport::{HttpTransport, StdioTransport, Transport};
+use crate::config::{McpServerConfig, McpTransport};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Upper bound on `tools/list` pages, guarding against cursor loops.
+const MAX_TOOL_LIST_PAGES: usize = 50;
+
+/// Client for a single configured MCP server.
+///
+/// The connection is established lazily and re-established on the next call
+/// after the transport fails (e.g. the server process exits). Connection
+/// state is reported to the health registry as `mcp:<name>`.
+pub struct McpClient {
+    name: String,
+    config: McpServerConfig,
+    connection: tokio::sync::Mutex<Option<Arc<dyn Transport>>>,
+    next_id: AtomicU64,
+    connected_once: std::sync::atomic::AtomicBool,
+}
+
+impl McpClient {
+    pub fn new(name: impl Into<String>, config: McpServerConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            connection: tokio::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+            connected_once: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn health_component(&self) -> String {
+        format!("mcp:{}", self.name)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs.max(1))
+    }
+
+    fn next_request(&self, method: &str, params: Option<Value>) -> JsonRpcRequest {
+        JsonRpcRequest::new(self.next_id.fetch_add(1, Ordering::SeqCst), method, params)
+    }
+
+    fn open_transport(&self) -> Result<Arc<dyn Transport>> {
+        match self.config.transport {
+            McpTransport::Stdio => {
+                let command = self
+                    .config
+                    .command
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .context("stdio MCP server requires `command`")?;
+                Ok(Arc::new(StdioTransport::spawn(
+                    &self.name,
+                    command,
+                    &self.config.args,
+                    &self.config.env,
+                )?))
+            }
+            McpTransport::Http => {
+                let url = self
+                    .config
+                    .url
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|u| !u.is_empty())
+                    .context("http MCP server requires `url`")?;
+                Ok(Arc::new(HttpTransport::new(
+                    url,
+                    &self.config.headers,
+                    self.timeout(),
+                )?))
+            }
+        }
+    }
+
+    /// Open a transport and run the `initialize` handshake.
+    async fn establish(&self) -> Result<Arc<dyn Transport>> {
+        let transport = self.open_transport()?;
+
+        let init = self.next_request(
+            "initialize",
+            Some(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "zeroclaw",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }
+            })),
+        );
+        let result = transport
+            .request(init, self.timeout())
+            .await?
+            .into_result()
+            .context("MCP initialize failed")?;
+
+        if let Some(version) = result.get("protocolVersion").and_then(Value::as_str) {
+            tracing::debug!(server = %self.name, version, "MCP server initialized");
+        }
+
+        transport
+            .notify(JsonRpcRequest::notification(
+                "notifications/initialized",
+                None,
+            ))
+            .await?;
+
+        Ok(transport)
+    }
+
+    /// Return a live transport, reconnecting if the previous one died.
+    async fn transport(&self) -> Result<Arc<dyn Transport>> {
+        let mut guard = self.connection.lock().await;
+        if let Some(existing) = guard.as_ref() {
+            if existing.is_alive() {
+                return Ok(Arc::clone(existing));
+            }
+            tracing::warn!(server = %self.name, "MCP connection lost; reconnecting");
+            *guard = None;
+        }
+
+        let reconnecting = self.connected_once.load(Ordering::SeqCst);
+        if reconnecting {
+            crate::health::bump_component_restart(&self.health_component());
+        }
+
+        match self.establish().await {
+            Ok(transport) => {
+                self.connected_once.store(true, Ordering::SeqCst);
+                crate::health::mark_component_ok(&self.health_component());
+                *guard = Some(Arc::clone(&transport));
+                Ok(transport)
+            }
+            Err(e) => {
+                crate::health::mark_component_error(&self.health_component(), &e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Connect eagerly (used at startup to surface configuration errors).
+    pub async fn connect(&self) -> Result<()> {
+        self.transport().await.map(|_| ())
+    }
+
+    /// Send a request; transport failures drop the connection so the next
+    /// call reconnects. Calls are not retried to avoid duplicate side effects.
+    async fn request(&self, method: &str, params: Option<Value>) -> Result<Value> {
+        let transport = self.transport().await?;
+        let request = self.next_request(method, params);
+
+        match transport.request(request, self.timeout()).await {
+            Ok(response) => {
+                crate::health::mark_component_ok(&self.health_component());
+                response.into_result()
+            }
+            Err(e) => {
+                crate::health::mark_component_error(&self.health_component(), &e);
+                let mut guard = self.connection.lock().await;
+                if guard
+                    .as_ref()
+                    .is_some_and(|current| Arc::ptr_eq(current, &transport))
+                {
+                    *guard = None;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// List all tools, following pagination cursors.
+    pub async fn list_tools(&self) -> Result<Vec<McpToolInfo>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_TOOL_LIST_PAGES {
+            let params = cursor.as_ref().map(|c| json!({ "cursor": c }));
+            let result = self.request("tools/list", params).await?;
+
+            let page: Vec<McpToolInfo> =
+                serde_json::from_value(result.get("tools").cloned().unwrap_or_else(|| json!([])))
+                    .context("invalid tools/list response")?;
+            tools.extend(page);
+
+            cursor = result
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+
+        bail!(
+            "MCP server '{}' returned too many tools/list pages",
+            self.name
+        )
+    }
+
+    /// Invoke a remote tool and return the raw `tools/call` result.
+    pub async fn call_tool(&self, tool: &str, arguments: Value) -> Result<Value> {
+        let arguments = if arguments.is_object() {
+            arguments
+        } else {
+            json!({})
+        };
+        self.request(
+            "tools/call",
+            Some(json!({ "name": tool, "arguments": arguments })),
+        )
+        .await
+    }
+}
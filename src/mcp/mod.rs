@@ -0,0 +1,250 @@
+//! Model Context Protocol (MCP) support, in both directions.
+//!
+//! **Client:** servers configured under `[mcp.servers.<name>]` are launched
+//! over stdio or reached over streamable HTTP. After the `initialize`
+//! handshake their `tools/list` entries are wrapped as [`Tool`]s named
+//! `<server>__<tool>` and appended to the agent's tool registry. Connection
+//! state is reported to the health registry as `mcp:<server>`.
+//!
+//! **Server:** `zeroclaw mcp serve` exposes zeroclaw's own tool registry and
+//! memory backend to MCP clients (see [`server`]).
+
+mod client;
+pub mod protocol;
+pub mod server;
+mod tool;
+mod transport;
+
+pub use client::McpClient;
+pub use server::McpServer;
+pub use tool::McpTool;
+
+use crate::config::Config;
+use crate::security::SecurityPolicy;
+use crate::tools::Tool;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Connect to every enabled MCP server and build tools for their remote tools.
+///
+/// Servers that fail to connect or list tools are skipped with a warning and
+/// marked unhealthy; they never prevent the agent from starting.
+pub async fn create_mcp_tools(
+    config: &Config,
+    security: &Arc<SecurityPolicy>,
+) -> Vec<Box<dyn Tool>> {
+    let mut server_names: Vec<&String> = config
+        .mcp
+        .servers
+        .iter()
+        .filter(|(_, server)| server.enabled)
+        .map(|(name, _)| name)
+        .collect();
+    server_names.sort();
+
+    let listings = futures_util::future::join_all(server_names.into_iter().map(|name| {
+        let server = config.mcp.servers[name].clone();
+        async move {
+            let client = Arc::new(McpClient::new(name.clone(), server));
+            let listed = client.list_tools().await;
+            (client, listed)
+        }
+    }))
+    .await;
+
+    let mut seen = HashSet::new();
+    let mut tools: Vec<Box<dyn Tool>> = Vec::new();
+
+    for (client, listed) in listings {
+        let infos = match listed {
+            Ok(infos) => infos,
+            Err(e) => {
+                tracing::warn!(server = %client.name(), "MCP server unavailable: {e:#}");
+                continue;
+            }
+        };
+
+        let allowed = &config.mcp.servers[client.name()].allowed_tools;
+        let mut registered = 0usize;
+        for info in infos {
+            if !allowed.is_empty() && !allowed.contains(&info.name) {
+                continue;
+            }
+            let tool = McpTool::new(Arc::clone(&client), info, security.clone());
+            if !seen.insert(tool.name().to_string()) {
+                tracing::warn!(tool = %tool.name(), "Skipping duplicate MCP tool name");
+                continue;
+            }
+            tools.push(Box::new(tool));
+            registered += 1;
+        }
+
+        tracing::info!(server = %client.name(), count = registered, "MCP tools registered");
+    }
+
+    tools
+}
+
+pub async fn handle_command(command: crate::McpCommands, config: &Config) -> anyhow::Result<()> {
+    match command {
+        crate::McpCommands::Serve => {
+            let server = McpServer::from_config(config).await?;
+            tracing::info!(tools = server.tool_names().len(), "Serving MCP over stdio");
+            server::serve_stdio(&server).await
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::config::McpServerConfig;
+    use crate::security::AutonomyLevel;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    /// Minimal newline-delimited JSON-RPC MCP server written in POSIX sh.
+    const TEST_SERVER: &str = r#"#!/bin/sh
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"test","version":"0"}}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"echo","description":"Echo text","inputSchema":{"type":"object","properties":{"text":{"type":"string"}},"required":["text"]}},{"name":"quit","inputSchema":{"type":"object"}}]}}\n' "$id" ;;
+    *'"name":"quit"'*)
+      exit 0 ;;
+    *'"method":"tools/call"'*)
+      text=$(printf '%s' "$line" | sed -n 's/.*"text":"\([^"]*\)".*/\1/p')
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"echo: %s"}]}}\n' "$id" "$text" ;;
+  esac
+done
+"#;
+
+    fn test_config(dir: &std::path::Path) -> Config {
+        let script = dir.join("server.sh");
+        std::fs::write(&script, TEST_SERVER).unwrap();
+
+        let mut config = Config::default();
+        config.workspace_dir = dir.to_path_buf();
+        config.mcp.servers.insert(
+            "local".into(),
+            McpServerConfig {
+                command: Some("sh".into()),
+                args: vec![script.display().to_string()],
+                timeout_secs: 5,
+                ..McpServerConfig::default()
+            },
+        );
+        config
+    }
+
+    fn test_security(dir: &std::path::Path) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: dir.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    fn find<'a>(tools: &'a [Box<dyn Tool>], name: &str) -> &'a dyn Tool {
+        tools
+            .iter()
+            .find(|t| t.name() == name)
+            .map(AsRef::as_ref)
+            .unwrap_or_else(|| panic!("tool {name} not registered"))
+    }
+
+    #[tokio::test]
+    async fn stdio_server_tools_are_registered_and_callable() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(dir.path());
+        let tools = create_mcp_tools(&config, &test_security(dir.path())).await;
+
+        assert_eq!(tools.len(), 2);
+        let echo = find(&tools, "local__echo");
+        assert_eq!(echo.parameters_schema()["required"], json!(["text"]));
+
+        let result = echo.execute(json!({ "text": "hi" })).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "echo: hi");
+    }
+
+    #[tokio::test]
+    async fn client_reconnects_after_server_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(dir.path());
+        let tools = create_mcp_tools(&config, &test_security(dir.path())).await;
+
+        let quit = find(&tools, "local__quit");
+        let result = quit.execute(json!({})).await.unwrap();
+        assert!(!result.success);
+
+        let echo = find(&tools, "local__echo");
+        let result = echo.execute(json!({ "text": "again" })).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "echo: again");
+
+        let health = crate::health::snapshot();
+        assert!(health.components["mcp:local"].restart_count >= 1);
+    }
+
+    #[tokio::test]
+    async fn allowed_tools_filter_and_disabled_servers_are_respected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(dir.path());
+        config.mcp.servers.get_mut("local").unwrap().allowed_tools = vec!["echo".into()];
+        config.mcp.servers.insert(
+            "off".into(),
+            McpServerConfig {
+                enabled: false,
+                command: Some("does-not-exist".into()),
+                ..McpServerConfig::default()
+            },
+        );
+
+        let tools = create_mcp_tools(&config, &test_security(dir.path())).await;
+        let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
+        assert_eq!(names, vec!["local__echo"]);
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.mcp.servers.insert(
+            "missing".into(),
+            McpServerConfig {
+                command: Some("/nonexistent/mcp-server".into()),
+                env: HashMap::new(),
+                ..McpServerConfig::default()
+            },
+        );
+
+        let tools = create_mcp_tools(&config, &test_security(dir.path())).await;
+        assert!(tools.is_empty());
+        assert_eq!(
+            crate::health::snapshot().components["mcp:missing"].status,
+            "error"
+        );
+    }
+
+    #[tokio::test]
+    async fn read_only_autonomy_blocks_mcp_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(dir.path());
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: dir.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tools = create_mcp_tools(&config, &security).await;
+
+        let result = find(&tools, "local__echo")
+            .execute(json!({ "text": "hi" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+}
@@ -0,0 +1,214 @@
+//! JSON-RPC 2.0 message types and MCP method constants.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// MCP protocol revision advertised during `initialize`.
+pub const PROTOCOL_VERSION: &str = "2025-03-26";
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// MCP-specific error code for an unknown resource URI.
+pub const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// A JSON-RPC request (or notification when `id` is `None`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcRequest {
+    pub fn new(id: u64, method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id: Some(Value::from(id)),
+            method: method.into(),
+            params,
+        }
+    }
+
+    pub fn notification(method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id: None,
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+/// JSON-RPC error object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// A JSON-RPC response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+impl JsonRpcResponse {
+    pub fn success(id: Option<Value>, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn failure(id: Option<Value>, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code,
+                message: message.into(),
+                data: None,
+            }),
+        }
+    }
+
+    /// Convert into the `result` payload, surfacing JSON-RPC errors.
+    pub fn into_result(self) -> anyhow::Result<Value> {
+        if let Some(error) = self.error {
+            anyhow::bail!("MCP error {}: {}", error.code, error.message);
+        }
+        Ok(self.result.unwrap_or(Value::Null))
+    }
+}
+
+/// A tool advertised by a server in `tools/list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpToolInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "inputSchema", default)]
+    pub input_schema: Value,
+}
+
+/// Render a `tools/call` result as plain text for the agent.
+///
+/// Text content items are concatenated; other content types are summarized,
+/// and `structuredContent` is used when no text is present.
+pub fn render_tool_call_result(result: &Value) -> (bool, String) {
+    let is_error = result
+        .get("isError")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut parts = Vec::new();
+    if let Some(items) = result.get("content").and_then(Value::as_array) {
+        for item in items {
+            match item.get("type").and_then(Value::as_str) {
+                Some("text") => {
+                    if let Some(text) = item.get("text").and_then(Value::as_str) {
+                        parts.push(text.to_string());
+                    }
+                }
+                Some("resource") => {
+                    let resource = item.get("resource");
+                    if let Some(text) = resource.and_then(|r| r.get("text")).and_then(Value::as_str)
+                    {
+                        parts.push(text.to_string());
+                    } else if let Some(uri) =
+                        resource.and_then(|r| r.get("uri")).and_then(Value::as_str)
+                    {
+                        parts.push(format!("[resource: {uri}]"));
+                    }
+                }
+                Some(other) => {
+                    let mime = item
+                        .get("mimeType")
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown");
+                    parts.push(format!("[{other} content: {mime}]"));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if parts.is_empty() {
+        if let Some(structured) = result.get("structuredContent") {
+            parts.push(structured.to_string());
+        }
+    }
+
+    (!is_error, parts.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn notification_omits_id() {
+        let raw = serde_json::to_value(JsonRpcRequest::notification(
+            "notifications/initialized",
+            None,
+        ))
+        .unwrap();
+        assert!(raw.get("id").is_none());
+        assert!(raw.get("params").is_none());
+    }
+
+    #[test]
+    fn response_error_is_surfaced() {
+        let response: JsonRpcResponse = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -32601, "message": "Method not found" }
+        }))
+        .unwrap();
+        let err = response.into_result().unwrap_err();
+        assert!(err.to_string().contains("Method not found"));
+    }
+
+    #[test]
+    fn render_tool_call_result_joins_text_and_flags_errors() {
+        let (ok, text) = render_tool_call_result(&json!({
+            "content": [
+                { "type": "text", "text": "line one" },
+                { "type": "image", "data": "...", "mimeType": "image/png" },
+                { "type": "text", "text": "line two" }
+            ],
+            "isError": true
+        }));
+        assert!(!ok);
+        assert_eq!(text, "line one\n[image content: image/png]\nline two");
+    }
+
+    #[test]
+    fn render_tool_call_result_falls_back_to_structured_content() {
+        let (ok, text) = render_tool_call_result(&json!({
+            "content": [],
+            "structuredContent": { "count": 3 }
+        }));
+        assert!(ok);
+        assert_eq!(text, r#"{"count":3}"#);
+    }
+}
@@ -0,0 +1,556 @@
+//! MCP server: exposes zeroclaw's tool registry and memory to MCP clients.
+//!
+//! Tools are served exactly as the agent sees them, so every call still goes
+//! through each tool's `SecurityPolicy` checks and runtime sandbox. Because
+//! MCP offers no way to prompt the operator mid-call, tools that would need
+//! interactive approval under the configured autonomy are refused.
+
+use super::protocol::{
+    JsonRpcRequest, JsonRpcResponse, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND,
+    PARSE_ERROR, PROTOCOL_VERSION, RESOURCE_NOT_FOUND,
+};
+use crate::approval::{ApprovalManager, ApprovalResponse};
+use crate::config::Config;
+use crate::memory::{self, Memory};
+use crate::runtime;
+use crate::security::audit::CommandExecutionLog;
+use crate::security::{AuditLogger, SecurityPolicy};
+use crate::tools::{self, Tool};
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// URI scheme used for memory entries exposed as MCP resources.
+const MEMORY_URI_PREFIX: &str = "memory://";
+
+/// Maximum characters of tool arguments recorded per audit entry.
+const AUDIT_ARGS_MAX_CHARS: usize = 200;
+
+pub struct McpServer {
+    tools: Vec<Box<dyn Tool>>,
+    memory: Arc<dyn Memory>,
+    approval: ApprovalManager,
+    audit: Option<Arc<AuditLogger>>,
+}
+
+impl McpServer {
+    pub fn new(
+        tools: Vec<Box<dyn Tool>>,
+        memory: Arc<dyn Memory>,
+        approval: ApprovalManager,
+        audit: Option<Arc<AuditLogger>>,
+    ) -> Self {
+        Self {
+            tools,
+            memory,
+            approval,
+            audit,
+        }
+    }
+
+    /// Build the same tool registry the agent uses (without MCP-imported
+    /// tools, to avoid proxy loops between zeroclaw instances).
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        let runtime: Arc<dyn runtime::RuntimeAdapter> =
+            Arc::from(runtime::create_runtime(&config.runtime)?);
+        let security = Arc::new(SecurityPolicy::from_config(
+            &config.autonomy,
+            &config.workspace_dir,
+        ));
+        let mem: Arc<dyn Memory> = Arc::from(memory::create_memory_with_storage_and_routes(
+            &config.memory,
+            &config.embedding_routes,
+            Some(&config.storage.provider.config),
+            &config.workspace_dir,
+            config.api_key.as_deref(),
+        )?);
+        let (composio_key, composio_entity_id) = if config.composio.enabled {
+            (
+                config.composio.api_key.as_deref(),
+                Some(config.composio.entity_id.as_str()),
+            )
+        } else {
+            (None, None)
+        };
+
+        let mut tools_registry = tools::all_tools_with_runtime(
+            Arc::new(config.clone()),
+            &security,
+            runtime,
+            mem.clone(),
+            composio_key,
+            composio_entity_id,
+            &config.browser,
+            &config.http_request,
+            &config.workspace_dir,
+            &config.agents,
+            config.api_key.as_deref(),
+            config,
+        );
+        tools_registry
+            .extend(crate::peripherals::create_peripheral_tools(&config.peripherals).await?);
+
+        Ok(Self::new(
+            tools_registry,
+            mem,
+            ApprovalManager::from_config(&config.autonomy),
+            crate::security::audit::default_logger(config),
+        ))
+    }
+
+    pub fn tool_names(&self) -> Vec<&str> {
+        self.tools.iter().map(|tool| tool.name()).collect()
+    }
+
+    /// Handle one raw JSON-RPC message. Returns `None` for notifications.
+    pub async fn handle_message(&self, raw: &str) -> Option<JsonRpcResponse> {
+        let value: Value = match serde_json::from_str(raw) {
+            Ok(value) => value,
+            Err(e) => {
+                return Some(JsonRpcResponse::failure(
+                    None,
+                    PARSE_ERROR,
+                    format!("Parse error: {e}"),
+                ))
+            }
+        };
+        let id = value.get("id").cloned();
+        match serde_json::from_value::<JsonRpcRequest>(value) {
+            Ok(request) => self.handle(request).await,
+            Err(e) => Some(JsonRpcResponse::failure(
+                id,
+                INVALID_REQUEST,
+                format!("Invalid request: {e}"),
+            )),
+        }
+    }
+
+    /// Dispatch a parsed request. Returns `None` for notifications.
+    pub async fn handle(&self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        let Some(id) = request.id else {
+            tracing::debug!(method = %request.method, "MCP notification received");
+            return None;
+        };
+        let params = request.params.unwrap_or(Value::Null);
+
+        let outcome = match request.method.as_str() {
+            "initialize" => Ok(Self::initialize_result()),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools()),
+            "tools/call" => self.call_tool(&params).await,
+            "resources/list" => self.list_resources().await,
+            "resources/templates/list" => Ok(json!({ "resourceTemplates": [] })),
+            "resources/read" => self.read_resource(&params).await,
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {other}"))),
+        };
+
+        Some(match outcome {
+            Ok(result) => JsonRpcResponse::success(Some(id), result),
+            Err((code, message)) => JsonRpcResponse::failure(Some(id), code, message),
+        })
+    }
+
+    fn initialize_result() -> Value {
+        json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {
+                "tools": { "listChanged": false },
+                "resources": { "subscribe": false, "listChanged": false }
+            },
+            "serverInfo": {
+                "name": "zeroclaw",
+                "version": env!("CARGO_PKG_VERSION"),
+            }
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|tool| {
+                let spec = tool.spec();
+                json!({
+                    "name": spec.name,
+                    "description": spec.description,
+                    "inputSchema": spec.parameters,
+                })
+            })
+            .collect();
+        json!({ "tools": tools })
+    }
+
+    async fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let tool = self
+            .tools
+            .iter()
+            .find(|tool| tool.name() == name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool: {name}")))?;
+        let args = params
+            .get("arguments")
+            .cloned()
+            .filter(Value::is_object)
+            .unwrap_or_else(|| json!({}));
+
+        if self.approval.needs_approval(name) {
+            self.approval
+                .record_decision(name, &args, ApprovalResponse::No, "mcp");
+            self.audit_call(name, &args, false, false, 0);
+            return Ok(tool_call_result(
+                false,
+                &format!(
+                    "Tool '{name}' requires interactive approval under the current autonomy level; \
+                     add it to autonomy.auto_approve to allow it over MCP"
+                ),
+            ));
+        }
+
+        let started = Instant::now();
+        let (success, text) = match tool.execute(args.clone()).await {
+            Ok(result) if result.success => (true, result.output),
+            Ok(result) => {
+                let error = result.error.unwrap_or_default();
+                let text = match (result.output.is_empty(), error.is_empty()) {
+                    (true, _) => error,
+                    (false, true) => result.output,
+                    (false, false) => format!("{}\n{error}", result.output),
+                };
+                (false, text)
+            }
+            Err(e) => (false, format!("Error executing {name}: {e}")),
+        };
+        let duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        self.audit_call(name, &args, true, success, duration_ms);
+
+        Ok(tool_call_result(success, &text))
+    }
+
+    fn audit_call(&self, name: &str, args: &Value, allowed: bool, success: bool, duration_ms: u64) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let command = format!(
+            "{name} {}",
+            crate::util::truncate_with_ellipsis(&args.to_string(), AUDIT_ARGS_MAX_CHARS)
+        );
+        if let Err(e) = audit.log_command_event(CommandExecutionLog {
+            channel: "mcp",
+            command: &command,
+            risk_level: "tool",
+            approved: allowed,
+            allowed,
+            success,
+            duration_ms,
+        }) {
+            tracing::warn!("Failed to write MCP audit event: {e}");
+        }
+    }
+
+    async fn list_resources(&self) -> std::result::Result<Value, (i64, String)> {
+        let entries = self
+            .memory
+            .list(None, None)
+            .await
+            .map_err(|e| (INVALID_REQUEST, format!("Failed to list memory: {e}")))?;
+        let resources: Vec<Value> = entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "uri": memory_uri(&entry.key),
+                    "name": entry.key,
+                    "description": format!("{} memory ({})", entry.category, entry.timestamp),
+                    "mimeType": "text/plain",
+                })
+            })
+            .collect();
+        Ok(json!({ "resources": resources }))
+    }
+
+    async fn read_resource(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let uri = params
+            .get("uri")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing resource uri".to_string()))?;
+        let key = uri
+            .strip_prefix(MEMORY_URI_PREFIX)
+            .and_then(|encoded| urlencoding::decode(encoded).ok())
+            .ok_or_else(|| (RESOURCE_NOT_FOUND, format!("Resource not found: {uri}")))?;
+
+        let entry = self
+            .memory
+            .get(&key)
+            .await
+            .map_err(|e| (INVALID_REQUEST, format!("Failed to read memory: {e}")))?
+            .ok_or_else(|| (RESOURCE_NOT_FOUND, format!("Resource not found: {uri}")))?;
+
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "text/plain",
+                "text": entry.content,
+            }]
+        }))
+    }
+}
+
+fn memory_uri(key: &str) -> String {
+    format!("{MEMORY_URI_PREFIX}{}", urlencoding::encode(key))
+}
+
+fn tool_call_result(success: bool, text: &str) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": !success,
+    })
+}
+
+/// Serve MCP over newline-delimited JSON-RPC on stdin/stdout until EOF.
+pub async fn serve_stdio(server: &McpServer) -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_message(&line).await {
+            let mut payload = serde_json::to_vec(&response)?;
+            payload.push(b'\n');
+            stdout.write_all(&payload).await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AuditConfig, AutonomyConfig, MemoryConfig};
+    use crate::memory::MemoryCategory;
+    use crate::security::AutonomyLevel;
+    use crate::tools::ToolResult;
+    use async_trait::async_trait;
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the input text"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"]
+            })
+        }
+
+        async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+            match args.get("text").and_then(Value::as_str) {
+                Some(text) => Ok(ToolResult {
+                    success: true,
+                    output: text.to_string(),
+                    error: None,
+                }),
+                None => Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some("missing text".into()),
+                }),
+            }
+        }
+    }
+
+    fn test_server(dir: &std::path::Path, autonomy: &AutonomyConfig) -> McpServer {
+        let mem_config = MemoryConfig {
+            backend: "sqlite".into(),
+            ..MemoryConfig::default()
+        };
+        let mem: Arc<dyn Memory> =
+            Arc::from(memory::create_memory(&mem_config, dir, None).unwrap());
+        let audit = AuditLogger::new(AuditConfig::default(), dir.to_path_buf()).unwrap();
+        McpServer::new(
+            vec![Box::new(EchoTool)],
+            mem,
+            ApprovalManager::from_config(autonomy),
+            Some(Arc::new(audit)),
+        )
+    }
+
+    fn full_autonomy() -> AutonomyConfig {
+        AutonomyConfig {
+            level: AutonomyLevel::Full,
+            ..AutonomyConfig::default()
+        }
+    }
+
+    async fn call(server: &McpServer, method: &str, params: Value) -> JsonRpcResponse {
+        server
+            .handle(JsonRpcRequest::new(1, method, Some(params)))
+            .await
+            .expect("requests with an id get a response")
+    }
+
+    #[tokio::test]
+    async fn initialize_advertises_tools_and_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = test_server(dir.path(), &full_autonomy());
+        let result = call(&server, "initialize", json!({}))
+            .await
+            .into_result()
+            .unwrap();
+        assert_eq!(result["protocolVersion"], PROTOCOL_VERSION);
+        assert!(result["capabilities"]["tools"].is_object());
+        assert!(result["capabilities"]["resources"].is_object());
+        assert_eq!(result["serverInfo"]["name"], "zeroclaw");
+    }
+
+    #[tokio::test]
+    async fn notifications_get_no_response() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = test_server(dir.path(), &full_autonomy());
+        let response = server
+            .handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await;
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn malformed_messages_and_unknown_methods_return_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = test_server(dir.path(), &full_autonomy());
+
+        let parse = server.handle_message("{not json").await.unwrap();
+        assert_eq!(parse.error.unwrap().code, PARSE_ERROR);
+
+        let unknown = call(&server, "prompts/list", json!({})).await;
+        assert_eq!(unknown.error.unwrap().code, METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn tools_list_and_call_translate_tool_specs_and_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = test_server(dir.path(), &full_autonomy());
+
+        let listed = call(&server, "tools/list", json!({}))
+            .await
+            .into_result()
+            .unwrap();
+        assert_eq!(listed["tools"][0]["name"], "echo");
+        assert_eq!(
+            listed["tools"][0]["inputSchema"]["required"],
+            json!(["text"])
+        );
+
+        let ok = call(
+            &server,
+            "tools/call",
+            json!({ "name": "echo", "arguments": { "text": "hello" } }),
+        )
+        .await
+        .into_result()
+        .unwrap();
+        assert_eq!(ok["isError"], false);
+        assert_eq!(ok["content"][0]["text"], "hello");
+
+        let failed = call(
+            &server,
+            "tools/call",
+            json!({ "name": "echo", "arguments": {} }),
+        )
+        .await
+        .into_result()
+        .unwrap();
+        assert_eq!(failed["isError"], true);
+        assert_eq!(failed["content"][0]["text"], "missing text");
+
+        let unknown = call(&server, "tools/call", json!({ "name": "nope" })).await;
+        assert_eq!(unknown.error.unwrap().code, INVALID_PARAMS);
+
+        let audit_log = std::fs::read_to_string(dir.path().join("audit.log")).unwrap();
+        assert_eq!(audit_log.lines().count(), 2);
+        assert!(audit_log.contains(r#""channel":"mcp""#));
+    }
+
+    #[tokio::test]
+    async fn supervised_tools_without_auto_approve_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = test_server(dir.path(), &AutonomyConfig::default());
+
+        let result = call(
+            &server,
+            "tools/call",
+            json!({ "name": "echo", "arguments": { "text": "hello" } }),
+        )
+        .await
+        .into_result()
+        .unwrap();
+        assert_eq!(result["isError"], true);
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("auto_approve"));
+        assert_eq!(server.approval.audit_log().len(), 1);
+
+        let approved = AutonomyConfig {
+            auto_approve: vec!["echo".into()],
+            ..AutonomyConfig::default()
+        };
+        let server = test_server(dir.path(), &approved);
+        let result = call(
+            &server,
+            "tools/call",
+            json!({ "name": "echo", "arguments": { "text": "hello" } }),
+        )
+        .await
+        .into_result()
+        .unwrap();
+        assert_eq!(result["isError"], false);
+    }
+
+    #[tokio::test]
+    async fn memory_entries_are_exposed_as_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = test_server(dir.path(), &full_autonomy());
+        server
+            .memory
+            .store("user prefs", "likes tea", MemoryCategory::Core, None)
+            .await
+            .unwrap();
+
+        let listed = call(&server, "resources/list", json!({}))
+            .await
+            .into_result()
+            .unwrap();
+        let uri = listed["resources"][0]["uri"].as_str().unwrap().to_string();
+        assert_eq!(uri, "memory://user%20prefs");
+
+        let read = call(&server, "resources/read", json!({ "uri": uri }))
+            .await
+            .into_result()
+            .unwrap();
+        assert_eq!(read["contents"][0]["text"], "likes tea");
+
+        let missing = call(
+            &server,
+            "resources/read",
+            json!({ "uri": "memory://absent" }),
+        )
+        .await;
+        assert_eq!(missing.error.unwrap().code, RESOURCE_NOT_FOUND);
+    }
+}
@@ -0,0 +1,130 @@
+//! Adapter exposing a remote MCP tool through the [`Tool`] trait.
+
+use super::client::McpClient;
+use super::protocol::{render_tool_call_result, McpToolInfo};
+use crate::security::SecurityPolicy;
+use crate::tools::schema::{CleaningStrategy, SchemaCleanr};
+use crate::tools::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// A tool served by an MCP server, registered as `<server>__<tool>`.
+///
+/// Calls honour the autonomy level and action budget of [`SecurityPolicy`];
+/// supervised-mode approval is applied by the agent loop like any other tool.
+pub struct McpTool {
+    client: Arc<McpClient>,
+    remote_name: String,
+    name: String,
+    description: String,
+    schema: Value,
+    security: Arc<SecurityPolicy>,
+}
+
+impl McpTool {
+    pub fn new(client: Arc<McpClient>, info: McpToolInfo, security: Arc<SecurityPolicy>) -> Self {
+        let name = crate::tools::skill_tool::sanitize_tool_name(&format!(
+            "{}__{}",
+            client.name(),
+            info.name
+        ));
+        let description = match info.description.as_deref().map(str::trim) {
+            Some(d) if !d.is_empty() => format!("{d} (MCP server '{}')", client.name()),
+            _ => format!("Tool '{}' from MCP server '{}'", info.name, client.name()),
+        };
+
+        Self {
+            schema: clean_input_schema(info.input_schema),
+            remote_name: info.name,
+            name,
+            description,
+            client,
+            security,
+        }
+    }
+}
+
+/// Normalize a server-provided `inputSchema` for LLM tool calling.
+fn clean_input_schema(schema: Value) -> Value {
+    let cleaned = SchemaCleanr::clean(schema, CleaningStrategy::Conservative);
+    if SchemaCleanr::validate(&cleaned).is_ok() {
+        cleaned
+    } else {
+        json!({ "type": "object", "properties": {} })
+    }
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.schema.clone()
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        if !self.security.can_act() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+            });
+        }
+
+        if !self.security.record_action() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: rate limit exceeded".into()),
+            });
+        }
+
+        match self.client.call_tool(&self.remote_name, args).await {
+            Ok(result) => {
+                let (success, output) = render_tool_call_result(&result);
+                Ok(ToolResult {
+                    success,
+                    error: (!success)
+                        .then(|| format!("MCP tool '{}' reported an error", self.name)),
+                    output,
+                })
+            }
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("MCP call to '{}' failed: {e}", self.name)),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_input_schema_falls_back_for_invalid_schema() {
+        assert_eq!(
+            clean_input_schema(Value::Null),
+            json!({ "type": "object", "properties": {} })
+        );
+    }
+
+    #[test]
+    fn clean_input_schema_inlines_refs() {
+        let cleaned = clean_input_schema(json!({
+            "type": "object",
+            "properties": { "item": { "$ref": "#/$defs/Item" } },
+            "$defs": { "Item": { "type": "string" } }
+        }));
+        assert!(!cleaned.to_string().contains("$ref"));
+        assert_eq!(cleaned["type"], "object");
+    }
+}
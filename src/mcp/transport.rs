@@ -0,0 +1,428 @@
+//! MCP transports: stdio child processes and streamable HTTP endpoints.
+
+use super::protocol::{JsonRpcRequest, JsonRpcResponse, JSONRPC_VERSION};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin};
+use tokio::sync::oneshot;
+
+/// Header carrying the server-assigned session for streamable HTTP.
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// A connected MCP transport capable of request/response exchanges.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Send a request and wait for the matching response.
+    async fn request(&self, request: JsonRpcRequest, timeout: Duration) -> Result<JsonRpcResponse>;
+
+    /// Send a notification (no response expected).
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<()>;
+
+    /// Whether the transport can still carry requests.
+    fn is_alive(&self) -> bool;
+}
+
+type PendingMap = Arc<Mutex<HashMap<String, oneshot::Sender<JsonRpcResponse>>>>;
+
+/// JSON-RPC over newline-delimited stdin/stdout of a child process.
+pub struct StdioTransport {
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    pending: PendingMap,
+    alive: Arc<AtomicBool>,
+    // Held so the process is killed when the transport is dropped.
+    _child: Child,
+}
+
+impl StdioTransport {
+    pub fn spawn(
+        server_name: &str,
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let mut child = server_command(command, args, env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to launch MCP server '{server_name}' ({command})"))?;
+
+        let stdin = child.stdin.take().context("MCP server stdin unavailable")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("MCP server stdout unavailable")?;
+        let stderr = child.stderr.take();
+
+        let stdin = Arc::new(tokio::sync::Mutex::new(stdin));
+        let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
+        let alive = Arc::new(AtomicBool::new(true));
+
+        if let Some(stderr) = stderr {
+            let server = server_name.to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    tracing::debug!(server = %server, "MCP stderr: {line}");
+                }
+            });
+        }
+
+        {
+            let server = server_name.to_string();
+            let stdin = Arc::clone(&stdin);
+            let pending = Arc::clone(&pending);
+            let alive = Arc::clone(&alive);
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stdout).lines();
+                loop {
+                    match lines.next_line().await {
+                        Ok(Some(line)) => handle_stdio_line(&server, &line, &stdin, &pending).await,
+                        Ok(None) => break,
+                        Err(e) => {
+                            tracing::warn!(server = %server, "MCP stdout read failed: {e}");
+                            break;
+                        }
+                    }
+                }
+                alive.store(false, Ordering::SeqCst);
+                // Dropping the senders fails any in-flight requests.
+                pending.lock().clear();
+                tracing::warn!(server = %server, "MCP server closed its stdout");
+            });
+        }
+
+        Ok(Self {
+            stdin,
+            pending,
+            alive,
+            _child: child,
+        })
+    }
+
+    async fn write_message(&self, message: &JsonRpcRequest) -> Result<()> {
+        write_line(&self.stdin, &serde_json::to_value(message)?).await
+    }
+}
+
+/// Command for a stdio MCP server. Like the shell tool, the environment is
+/// cleared so provider API keys and other secrets are not inherited (CWE-200);
+/// only functional variables and the server's configured `env` are passed.
+fn server_command(
+    command: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new(command);
+    cmd.args(args).env_clear();
+    for var in crate::tools::shell::SAFE_ENV_VARS {
+        if let Ok(value) = std::env::var(var) {
+            cmd.env(var, value);
+        }
+    }
+    cmd.envs(env);
+    cmd
+}
+
+async fn write_line(stdin: &tokio::sync::Mutex<ChildStdin>, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    let mut stdin = stdin.lock().await;
+    stdin
+        .write_all(line.as_bytes())
+        .await
+        .context("failed to write to MCP server stdin")?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+/// Route one line from the server: responses go to waiting callers, server
+/// requests get a minimal reply, notifications are logged.
+async fn handle_stdio_line(
+    server: &str,
+    line: &str,
+    stdin: &tokio::sync::Mutex<ChildStdin>,
+    pending: &PendingMap,
+) {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+
+    let Ok(message) = serde_json::from_str::<Value>(trimmed) else {
+        tracing::debug!(server = %server, "Ignoring non-JSON MCP output: {trimmed}");
+        return;
+    };
+
+    let method = message.get("method").and_then(Value::as_str);
+    let id = message.get("id").filter(|id| !id.is_null());
+
+    match (method, id) {
+        (Some(method), Some(id)) => {
+            let reply = if method == "ping" {
+                json!({ "jsonrpc": JSONRPC_VERSION, "id": id, "result": {} })
+            } else {
+                json!({
+                    "jsonrpc": JSONRPC_VERSION,
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("Method not supported by client: {method}") }
+                })
+            };
+            if let Err(e) = write_line(stdin, &reply).await {
+                tracing::warn!(server = %server, "Failed to answer MCP server request: {e}");
+            }
+        }
+        (Some(method), None) => {
+            tracing::debug!(server = %server, "MCP notification: {method}");
+        }
+        (None, Some(id)) => match serde_json::from_value::<JsonRpcResponse>(message.clone()) {
+            Ok(response) => {
+                if let Some(sender) = pending.lock().remove(&id.to_string()) {
+                    let _ = sender.send(response);
+                }
+            }
+            Err(e) => tracing::debug!(server = %server, "Malformed MCP response: {e}"),
+        },
+        (None, None) => {}
+    }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn request(&self, request: JsonRpcRequest, timeout: Duration) -> Result<JsonRpcResponse> {
+        if !self.is_alive() {
+            bail!("MCP server process has exited");
+        }
+
+        let key = request
+            .id
+            .as_ref()
+            .map(ToString::to_string)
+            .context("MCP request is missing an id")?;
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().insert(key.clone(), tx);
+
+        if let Err(e) = self.write_message(&request).await {
+            self.pending.lock().remove(&key);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => bail!(
+                "MCP server exited before responding to '{}'",
+                request.method
+            ),
+            Err(_) => {
+                self.pending.lock().remove(&key);
+                bail!(
+                    "MCP request '{}' timed out after {}s",
+                    request.method,
+                    timeout.as_secs()
+                )
+            }
+        }
+    }
+
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<()> {
+        self.write_message(&notification).await
+    }
+
+    fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+}
+
+/// Streamable HTTP transport: each message is POSTed to a single endpoint and
+/// answered with either a JSON body or an SSE stream.
+pub struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+    session_id: Mutex<Option<String>>,
+}
+
+impl HttpTransport {
+    pub fn new(url: &str, headers: &HashMap<String, String>, timeout: Duration) -> Result<Self> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            bail!("MCP server url must start with http:// or https://");
+        }
+        let builder = reqwest::Client::builder()
+            .timeout(timeout)
+            .connect_timeout(Duration::from_secs(10));
+        let builder = crate::config::apply_runtime_proxy_to_builder(builder, "mcp");
+        Ok(Self {
+            client: builder.build()?,
+            url: url.to_string(),
+            headers: headers.clone(),
+            session_id: Mutex::new(None),
+        })
+    }
+
+    async fn post(&self, message: &JsonRpcRequest) -> Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(message);
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
+        }
+        if let Some(session) = self.session_id.lock().clone() {
+            request = request.header(SESSION_HEADER, session);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("MCP HTTP request '{}' failed", message.method))?;
+
+        if let Some(session) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.lock() = Some(session.to_string());
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!(
+                "MCP HTTP '{}' returned {status}: {}",
+                message.method,
+                crate::util::truncate_with_ellipsis(body.trim(), 200)
+            );
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn request(&self, request: JsonRpcRequest, timeout: Duration) -> Result<JsonRpcResponse> {
+        let id = request.id.clone().context("MCP request is missing an id")?;
+        let exchange = async {
+            let response = self.post(&request).await?;
+            let is_sse = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|ct| ct.starts_with("text/event-stream"));
+            let body = response.text().await?;
+            let messages = if is_sse {
+                parse_sse_messages(&body)
+            } else {
+                match serde_json::from_str::<Value>(&body)? {
+                    Value::Array(items) => items,
+                    single => vec![single],
+                }
+            };
+            find_response(messages, &id).with_context(|| {
+                format!(
+                    "MCP HTTP response did not contain a reply to '{}'",
+                    request.method
+                )
+            })
+        };
+
+        match tokio::time::timeout(timeout, exchange).await {
+            Ok(result) => result,
+            Err(_) => bail!(
+                "MCP request '{}' timed out after {}s",
+                request.method,
+                timeout.as_secs()
+            ),
+        }
+    }
+
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<()> {
+        self.post(&notification).await.map(|_| ())
+    }
+
+    fn is_alive(&self) -> bool {
+        true
+    }
+}
+
+/// Extract JSON payloads from `data:` fields of an SSE body.
+fn parse_sse_messages(body: &str) -> Vec<Value> {
+    let mut messages = Vec::new();
+    for event in body.split("\n\n") {
+        let data: Vec<&str> = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim_start)
+            .collect();
+        if data.is_empty() {
+            continue;
+        }
+        if let Ok(value) = serde_json::from_str::<Value>(&data.join("\n")) {
+            messages.push(value);
+        }
+    }
+    messages
+}
+
+fn find_response(messages: Vec<Value>, id: &Value) -> Option<JsonRpcResponse> {
+    messages
+        .into_iter()
+        .filter(|m| m.get("id") == Some(id) && m.get("method").is_none())
+        .find_map(|m| serde_json::from_value(m).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sse_messages_reads_data_fields() {
+        let body = "event: message\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\"}\n\n\
+                    event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{\"ok\":true}}\n\n";
+        let messages = parse_sse_messages(body);
+        assert_eq!(messages.len(), 2);
+
+        let response = find_response(messages, &json!(7)).unwrap();
+        assert_eq!(response.result.unwrap()["ok"], true);
+    }
+
+    #[test]
+    fn find_response_ignores_other_ids() {
+        let messages = vec![json!({ "jsonrpc": "2.0", "id": 1, "result": {} })];
+        assert!(find_response(messages, &json!(2)).is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stdio_servers_get_a_scrubbed_environment() {
+        let env = HashMap::from([("MCP_SERVER_TOKEN".to_string(), "t0k3n".to_string())]);
+        let output = server_command("env", &[], &env).output().await.unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(stdout.lines().any(|line| line == "MCP_SERVER_TOKEN=t0k3n"));
+        for line in stdout.lines() {
+            let key = line.split('=').next().unwrap_or_default();
+            assert!(
+                key == "MCP_SERVER_TOKEN" || crate::tools::shell::SAFE_ENV_VARS.contains(&key),
+                "unexpected variable passed to MCP server: {key}"
+            );
+        }
+    }
+
+    #[test]
+    fn http_transport_rejects_non_http_urls() {
+        assert!(
+            HttpTransport::new("ftp://example.com", &HashMap::new(), Duration::from_secs(1))
+                .is_err()
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::config::{build_runtime_proxy_client_with_timeouts, MultimodalConfig};
-use crate::providers::ChatMessage;
+use crate::providers::{ChatMessage, ContentPart, MediaSource};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use reqwest::Client;
 use std::path::Path;
@@ -85,11 +85,58 @@ pub fn parse_image_markers(content: &str) -> (String, Vec<String>) {
     (cleaned.trim().to_string(), refs)
 }
 
+/// Split legacy `[IMAGE:...]` markers out of message text into typed parts.
+///
+/// `data:` URIs become inline payloads; any other reference (URL or local
+/// path) is kept as a [`MediaSource::Url`] until the message is prepared.
+pub fn parts_from_markers(content: &str) -> Vec<ContentPart> {
+    let (cleaned, refs) = parse_image_markers(content);
+    if refs.is_empty() {
+        if content.is_empty() {
+            return Vec::new();
+        }
+        return vec![ContentPart::text(content)];
+    }
+
+    let mut parts = Vec::with_capacity(refs.len() + 1);
+    if !cleaned.is_empty() {
+        parts.push(ContentPart::text(cleaned));
+    }
+    for reference in refs {
+        parts.push(image_part_from_reference(reference));
+    }
+    parts
+}
+
+/// Image part for a marker or request reference: inline for base64 `data:`
+/// URIs, a URL (or path) otherwise.
+pub fn image_part_from_reference(reference: String) -> ContentPart {
+    if let Some(rest) = reference.strip_prefix("data:") {
+        if let Some((header, payload)) = rest.split_once(',') {
+            if header.contains(";base64") {
+                let mime = header.split(';').next().unwrap_or_default().trim();
+                return ContentPart::Image {
+                    mime_type: Some(mime.to_ascii_lowercase()).filter(|m| !m.is_empty()),
+                    source: MediaSource::Base64(payload.trim().to_string()),
+                };
+            }
+        }
+    }
+    ContentPart::image_url(reference)
+}
+
+/// Count images in user messages, whether carried as typed parts or as
+/// `[IMAGE:...]` markers.
 pub fn count_image_markers(messages: &[ChatMessage]) -> usize {
     messages
         .iter()
         .filter(|m| m.role == "user")
-        .map(|m| parse_image_markers(&m.content).1.len())
+        .map(|m| {
+            m.content_parts()
+                .iter()
+                .filter(|part| matches!(part, ContentPart::Image { .. }))
+                .count()
+        })
         .sum()
 }
 
@@ -112,6 +159,8 @@ pub fn extract_ollama_image_payload(image_ref: &str) -> Option<String> {
     }
 }
 
+/// Resolve the media in user messages into typed parts with inline base64
+/// payloads, enforcing the configured image count, size and MIME limits.
 pub async fn prepare_messages_for_provider(
     messages: &[ChatMessage],
     config: &MultimodalConfig,
@@ -128,67 +177,71 @@ pub async fn prepare_messages_for_provider(
         .into());
     }
 
-    if found_images == 0 {
-        return Ok(PreparedMessages {
-            messages: messages.to_vec(),
-            contains_images: false,
-        });
-    }
-
     let remote_client = build_runtime_proxy_client_with_timeouts("provider.ollama", 30, 10);
 
     let mut normalized_messages = Vec::with_capacity(messages.len());
     for message in messages {
-        if message.role != "user" {
-            normalized_messages.push(message.clone());
-            continue;
-        }
-
-        let (cleaned_text, refs) = parse_image_markers(&message.content);
-        if refs.is_empty() {
+        if message.role != "user" || !message.has_media() {
             normalized_messages.push(message.clone());
             continue;
         }
 
-        let mut normalized_refs = Vec::with_capacity(refs.len());
-        for reference in refs {
-            let data_uri =
-                normalize_image_reference(&reference, config, max_bytes, &remote_client).await?;
-            normalized_refs.push(data_uri);
+        let mut parts = Vec::new();
+        for part in message.content_parts() {
+            parts.push(normalize_part(part, config, max_bytes, &remote_client).await?);
         }
-
-        let content = compose_multimodal_message(&cleaned_text, &normalized_refs);
-        normalized_messages.push(ChatMessage {
-            role: message.role.clone(),
-            content,
-        });
+        normalized_messages.push(ChatMessage::with_parts(message.role.clone(), parts));
     }
 
     Ok(PreparedMessages {
         messages: normalized_messages,
-        contains_images: true,
+        contains_images: found_images > 0,
     })
 }
 
-fn compose_multimodal_message(text: &str, data_uris: &[String]) -> String {
-    let mut content = String::new();
-    let trimmed = text.trim();
-
-    if !trimmed.is_empty() {
-        content.push_str(trimmed);
-        content.push_str("\n\n");
-    }
-
-    for (index, data_uri) in data_uris.iter().enumerate() {
-        if index > 0 {
-            content.push('\n');
+async fn normalize_part(
+    part: ContentPart,
+    config: &MultimodalConfig,
+    max_bytes: usize,
+    remote_client: &Client,
+) -> anyhow::Result<ContentPart> {
+    match part {
+        ContentPart::Image { mime_type, source } => {
+            let (mime, data) = match source {
+                MediaSource::Base64(data) => {
+                    normalize_base64(&data, mime_type.as_deref(), max_bytes)?
+                }
+                MediaSource::Url(reference) => {
+                    normalize_image_reference(&reference, config, max_bytes, remote_client).await?
+                }
+            };
+            Ok(ContentPart::Image {
+                mime_type: Some(mime),
+                source: MediaSource::Base64(data),
+            })
+        }
+        ContentPart::Audio {
+            mime_type,
+            source: MediaSource::Base64(data),
+        } => {
+            let input = "inline audio";
+            let decoded = decode_base64(input, &data)?;
+            validate_size(input, decoded.len(), max_bytes)?;
+            let mime = mime_type.unwrap_or_default().to_ascii_lowercase();
+            if !mime.starts_with("audio/") {
+                return Err(MultimodalError::UnsupportedMime {
+                    input: input.to_string(),
+                    mime,
+                }
+                .into());
+            }
+            Ok(ContentPart::Audio {
+                mime_type: Some(mime),
+                source: MediaSource::Base64(data),
+            })
         }
-        content.push_str(IMAGE_MARKER_PREFIX);
-        content.push_str(data_uri);
-        content.push(']');
+        other => Ok(other),
     }
-
-    content
 }
 
 async fn normalize_image_reference(
@@ -196,7 +249,7 @@ async fn normalize_image_reference(
     config: &MultimodalConfig,
     max_bytes: usize,
     remote_client: &Client,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<(String, String)> {
     if source.starts_with("data:") {
         return normalize_data_uri(source, max_bytes);
     }
@@ -215,7 +268,7 @@ async fn normalize_image_reference(
     normalize_local_image(source, max_bytes).await
 }
 
-fn normalize_data_uri(source: &str, max_bytes: usize) -> anyhow::Result<String> {
+fn normalize_data_uri(source: &str, max_bytes: usize) -> anyhow::Result<(String, String)> {
     let Some(comma_idx) = source.find(',') else {
         return Err(MultimodalError::InvalidMarker {
             input: source.to_string(),
@@ -245,23 +298,51 @@ fn normalize_data_uri(source: &str, max_bytes: usize) -> anyhow::Result<String>
 
     validate_mime(source, &mime)?;
 
-    let decoded = STANDARD
+    let decoded = decode_base64(source, payload)?;
+    validate_size(source, decoded.len(), max_bytes)?;
+
+    Ok((mime, STANDARD.encode(decoded)))
+}
+
+/// Validate an inline image part; a missing MIME type is sniffed from the
+/// payload.
+fn normalize_base64(
+    data: &str,
+    mime_type: Option<&str>,
+    max_bytes: usize,
+) -> anyhow::Result<(String, String)> {
+    let input = "inline image";
+    let decoded = decode_base64(input, data.trim())?;
+    validate_size(input, decoded.len(), max_bytes)?;
+
+    let mime = match mime_type {
+        Some(mime) => mime.trim().to_ascii_lowercase(),
+        None => {
+            detect_mime(None, &decoded, None).ok_or_else(|| MultimodalError::UnsupportedMime {
+                input: input.to_string(),
+                mime: "unknown".to_string(),
+            })?
+        }
+    };
+    validate_mime(input, &mime)?;
+
+    Ok((mime, STANDARD.encode(decoded)))
+}
+
+fn decode_base64(source: &str, payload: &str) -> anyhow::Result<Vec<u8>> {
+    Ok(STANDARD
         .decode(payload)
         .map_err(|error| MultimodalError::InvalidMarker {
             input: source.to_string(),
             reason: format!("invalid base64 payload: {error}"),
-        })?;
-
-    validate_size(source, decoded.len(), max_bytes)?;
-
-    Ok(format!("data:{mime};base64,{}", STANDARD.encode(decoded)))
+        })?)
 }
 
 async fn normalize_remote_image(
     source: &str,
     max_bytes: usize,
     remote_client: &Client,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<(String, String)> {
     let response = remote_client.get(source).send().await.map_err(|error| {
         MultimodalError::RemoteFetchFailed {
             input: source.to_string(),
@@ -308,10 +389,10 @@ async fn normalize_remote_image(
 
     validate_mime(source, &mime)?;
 
-    Ok(format!("data:{mime};base64,{}", STANDARD.encode(bytes)))
+    Ok((mime, STANDARD.encode(bytes)))
 }
 
-async fn normalize_local_image(source: &str, max_bytes: usize) -> anyhow::Result<String> {
+async fn normalize_local_image(source: &str, max_bytes: usize) -> anyhow::Result<(String, String)> {
     let path = Path::new(source);
     if !path.exists() || !path.is_file() {
         return Err(MultimodalError::ImageSourceNotFound {
@@ -351,7 +432,7 @@ async fn normalize_local_image(source: &str, max_bytes: usize) -> anyhow::Result
 
     validate_mime(source, &mime)?;
 
-    Ok(format!("data:{mime};base64,{}", STANDARD.encode(bytes)))
+    Ok((mime, STANDARD.encode(bytes)))
 }
 
 fn validate_size(source: &str, size_bytes: usize, max_bytes: usize) -> anyhow::Result<()> {
@@ -497,6 +578,74 @@ mod tests {
         assert!(refs[0].starts_with("data:image/png;base64,"));
     }
 
+    #[test]
+    fn parts_from_markers_inlines_data_uris() {
+        let parts = parts_from_markers("see [IMAGE:data:image/PNG;base64,abcd] and [IMAGE:b.jpg]");
+
+        assert_eq!(
+            parts,
+            vec![
+                ContentPart::text("see  and"),
+                ContentPart::image_base64("image/png", "abcd"),
+                ContentPart::image_url("b.jpg"),
+            ]
+        );
+        assert!(parts_from_markers("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn prepare_messages_validates_typed_image_parts() {
+        let png = STANDARD.encode([0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+        let messages = vec![ChatMessage::with_parts(
+            "user",
+            vec![
+                ContentPart::text("look"),
+                ContentPart::Image {
+                    mime_type: None,
+                    source: MediaSource::Base64(png.clone()),
+                },
+            ],
+        )];
+
+        let prepared = prepare_messages_for_provider(&messages, &MultimodalConfig::default())
+            .await
+            .unwrap();
+
+        assert!(prepared.contains_images);
+        assert_eq!(
+            prepared.messages[0].parts[1],
+            ContentPart::image_base64("image/png", png)
+        );
+
+        let svg = vec![ChatMessage::with_parts(
+            "user",
+            vec![ContentPart::image_base64("image/svg+xml", "PHN2Zz4=")],
+        )];
+        let error = prepare_messages_for_provider(&svg, &MultimodalConfig::default())
+            .await
+            .expect_err("svg should not be allowed");
+        assert!(error.to_string().contains("MIME type is not allowed"));
+    }
+
+    #[tokio::test]
+    async fn prepare_messages_counts_typed_parts_toward_image_limit() {
+        let messages = vec![
+            ChatMessage::with_parts("user", vec![ContentPart::image_url("/tmp/1.png")]),
+            ChatMessage::user("[IMAGE:/tmp/2.png]"),
+        ];
+        let config = MultimodalConfig {
+            max_images: 1,
+            max_image_size_mb: 5,
+            allow_remote_fetch: false,
+        };
+
+        let error = prepare_messages_for_provider(&messages, &config)
+            .await
+            .expect_err("should reject image count overflow");
+
+        assert!(error.to_string().contains("found=2"));
+    }
+
     #[tokio::test]
     async fn prepare_messages_rejects_too_many_images() {
         let messages = vec![ChatMessage::user(
@@ -47,6 +47,14 @@ impl Observer for LogObserver {
             ObserverEvent::HeartbeatTick => {
                 info!("heartbeat.tick");
             }
+            ObserverEvent::RouteSelected {
+                hint,
+                provider,
+                model,
+                reason,
+            } => {
+                info!(hint = %hint, provider = %provider, model = %model, reason = %reason, "route.selected");
+            }
             ObserverEvent::Error { component, message } => {
                 info!(component = %component, error = %message, "error");
             }
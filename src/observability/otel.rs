@@ -23,6 +23,7 @@ pub struct OtelObserver {
     channel_messages: Counter<u64>,
     heartbeat_ticks: Counter<u64>,
     errors: Counter<u64>,
+    route_decisions: Counter<u64>,
     request_latency: Histogram<f64>,
     tokens_used: Counter<u64>,
     active_sessions: Gauge<u64>,
@@ -129,6 +130,11 @@ impl OtelObserver {
             .with_description("Total errors by component")
             .build();
 
+        let route_decisions = meter
+            .u64_counter("zeroclaw.route.decisions")
+            .with_description("Model router decisions by hint and selected route")
+            .build();
+
         let request_latency = meter
             .f64_histogram("zeroclaw.request.latency")
             .with_description("Request latency in seconds")
@@ -162,6 +168,7 @@ impl OtelObserver {
             channel_messages,
             heartbeat_ticks,
             errors,
+            route_decisions,
             request_latency,
             tokens_used,
             active_sessions,
@@ -317,6 +324,22 @@ impl Observer for OtelObserver {
             ObserverEvent::HeartbeatTick => {
                 self.heartbeat_ticks.add(1, &[]);
             }
+            ObserverEvent::RouteSelected {
+                hint,
+                provider,
+                model,
+                reason,
+            } => {
+                self.route_decisions.add(
+                    1,
+                    &[
+                        KeyValue::new("hint", hint.clone()),
+                        KeyValue::new("provider", provider.clone()),
+                        KeyValue::new("model", model.clone()),
+                        KeyValue::new("reason", reason.clone()),
+                    ],
+                );
+            }
             ObserverEvent::Error { component, message } => {
                 // Create an error span for visibility in trace backends
                 let mut span = tracer.build(
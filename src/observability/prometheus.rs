@@ -16,6 +16,7 @@ pub struct PrometheusObserver {
     channel_messages: IntCounterVec,
     heartbeat_ticks: prometheus::IntCounter,
     errors: IntCounterVec,
+    route_decisions: IntCounterVec,
 
     // Histograms
     agent_duration: HistogramVec,
@@ -26,6 +27,11 @@ pub struct PrometheusObserver {
     tokens_used: prometheus::IntGauge,
     active_sessions: GaugeVec,
     queue_depth: GaugeVec,
+
+    // Provider health (refreshed from the health registry on encode)
+    provider_circuit_state: GaugeVec,
+    provider_error_rate: GaugeVec,
+    provider_latency: GaugeVec,
 }
 
 impl PrometheusObserver {
@@ -81,6 +87,15 @@ impl PrometheusObserver {
         )
         .expect("valid metric");
 
+        let route_decisions = IntCounterVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_route_decisions_total",
+                "Model router decisions by hint and selected route",
+            ),
+            &["hint", "provider", "model", "reason"],
+        )
+        .expect("valid metric");
+
         let agent_duration = HistogramVec::new(
             HistogramOpts::new(
                 "zeroclaw_agent_duration_seconds",
@@ -128,6 +143,33 @@ impl PrometheusObserver {
         )
         .expect("valid metric");
 
+        let provider_circuit_state = GaugeVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_provider_circuit_state",
+                "Provider circuit breaker state (0 = closed, 1 = half-open, 2 = open)",
+            ),
+            &["provider", "model"],
+        )
+        .expect("valid metric");
+
+        let provider_error_rate = GaugeVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_provider_error_rate",
+                "Rolling provider error rate (0.0 - 1.0)",
+            ),
+            &["provider", "model"],
+        )
+        .expect("valid metric");
+
+        let provider_latency = GaugeVec::new(
+            prometheus::Opts::new(
+                "zeroclaw_provider_latency_seconds",
+                "Rolling provider response latency in seconds",
+            ),
+            &["provider", "model"],
+        )
+        .expect("valid metric");
+
         // Register all metrics
         registry.register(Box::new(agent_starts.clone())).ok();
         registry.register(Box::new(llm_requests.clone())).ok();
@@ -139,12 +181,20 @@ impl PrometheusObserver {
         registry.register(Box::new(channel_messages.clone())).ok();
         registry.register(Box::new(heartbeat_ticks.clone())).ok();
         registry.register(Box::new(errors.clone())).ok();
+        registry.register(Box::new(route_decisions.clone())).ok();
         registry.register(Box::new(agent_duration.clone())).ok();
         registry.register(Box::new(tool_duration.clone())).ok();
         registry.register(Box::new(request_latency.clone())).ok();
         registry.register(Box::new(tokens_used.clone())).ok();
         registry.register(Box::new(active_sessions.clone())).ok();
         registry.register(Box::new(queue_depth.clone())).ok();
+        registry
+            .register(Box::new(provider_circuit_state.clone()))
+            .ok();
+        registry
+            .register(Box::new(provider_error_rate.clone()))
+            .ok();
+        registry.register(Box::new(provider_latency.clone())).ok();
 
         Self {
             registry,
@@ -156,17 +206,46 @@ impl PrometheusObserver {
             channel_messages,
             heartbeat_ticks,
             errors,
+            route_decisions,
             agent_duration,
             tool_duration,
             request_latency,
             tokens_used,
             active_sessions,
             queue_depth,
+            provider_circuit_state,
+            provider_error_rate,
+            provider_latency,
+        }
+    }
+
+    /// Copy circuit-breaker state from [`crate::health`] into the provider gauges.
+    fn refresh_provider_health(&self) {
+        for health in crate::health::snapshot().providers.values() {
+            let labels = [health.provider.as_str(), health.model.as_str()];
+            let state = match health.circuit.as_str() {
+                "open" => 2.0,
+                "half_open" => 1.0,
+                _ => 0.0,
+            };
+            self.provider_circuit_state
+                .with_label_values(&labels)
+                .set(state);
+            self.provider_error_rate
+                .with_label_values(&labels)
+                .set(health.error_rate);
+            if let Some(ms) = health.latency_ms {
+                #[allow(clippy::cast_precision_loss)]
+                self.provider_latency
+                    .with_label_values(&labels)
+                    .set(ms as f64 / 1000.0);
+            }
         }
     }
 
     /// Encode all registered metrics into Prometheus text exposition format.
     pub fn encode(&self) -> String {
+        self.refresh_provider_health();
         let encoder = TextEncoder::new();
         let families = self.registry.gather();
         let mut buf = Vec::new();
@@ -245,6 +324,16 @@ impl Observer for PrometheusObserver {
             ObserverEvent::HeartbeatTick => {
                 self.heartbeat_ticks.inc();
             }
+            ObserverEvent::RouteSelected {
+                hint,
+                provider,
+                model,
+                reason,
+            } => {
+                self.route_decisions
+                    .with_label_values(&[hint, provider, model, reason])
+                    .inc();
+            }
             ObserverEvent::Error {
                 component,
                 message: _,
@@ -436,6 +525,29 @@ mod tests {
         assert!(output.contains("zeroclaw_tokens_used_last 200"));
     }
 
+    #[test]
+    fn encode_exports_provider_circuit_state() {
+        crate::health::record_provider_health(crate::health::ProviderHealth {
+            provider: "prom-circuit".into(),
+            model: "m".into(),
+            circuit: "open".into(),
+            consecutive_failures: 5,
+            error_rate: 0.75,
+            latency_ms: Some(1500),
+            opened_at: None,
+            updated_at: String::new(),
+        });
+
+        let output = PrometheusObserver::new().encode();
+        assert!(output
+            .contains(r#"zeroclaw_provider_circuit_state{model="m",provider="prom-circuit"} 2"#));
+        assert!(output
+            .contains(r#"zeroclaw_provider_error_rate{model="m",provider="prom-circuit"} 0.75"#));
+        assert!(output.contains(
+            r#"zeroclaw_provider_latency_seconds{model="m",provider="prom-circuit"} 1.5"#
+        ));
+    }
+
     #[test]
     fn llm_response_tracks_request_count_and_tokens() {
         let obs = PrometheusObserver::new();
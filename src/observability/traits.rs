@@ -58,6 +58,15 @@ pub enum ObserverEvent {
     },
     /// Periodic heartbeat tick from the runtime keep-alive loop.
     HeartbeatTick,
+    /// The model router picked a route for a `hint:<name>` request.
+    RouteSelected {
+        /// Requested hint (route or routing policy name).
+        hint: String,
+        provider: String,
+        model: String,
+        /// Why the route was chosen (e.g. `"cheapest"`, `"escalated"`).
+        reason: String,
+    },
     /// An error occurred in a named component.
     Error {
         /// Subsystem where the error originated (e.g., `"provider"`, `"gateway"`).
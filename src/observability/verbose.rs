@@ -44,6 +44,14 @@ impl Observer for VerboseObserver {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
                 eprintln!("< Tool {tool} (success={success}, duration_ms={ms})");
             }
+            ObserverEvent::RouteSelected {
+                hint,
+                provider,
+                model,
+                reason,
+            } => {
+                eprintln!("> Route hint:{hint} -> {provider}/{model} ({reason})");
+            }
             ObserverEvent::TurnComplete => {
                 eprintln!("< Complete");
             }
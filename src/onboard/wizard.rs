@@ -11,6 +11,7 @@ use crate::hardware::{self, HardwareConfig};
 use crate::memory::{
     default_memory_backend_key, memory_backend_profile, selectable_memory_backends,
 };
+use crate::providers::catalog::{self, ModelInfo};
 use crate::providers::{
     canonical_china_provider_name, is_glm_alias, is_glm_cn_alias, is_minimax_alias,
     is_moonshot_alias, is_qianfan_alias, is_qwen_alias, is_qwen_oauth_alias, is_zai_alias,
@@ -165,9 +166,12 @@ pub async fn run_wizard(force: bool) -> Result<Config> {
         agent: crate::config::schema::AgentConfig::default(),
         skills: crate::config::SkillsConfig::default(),
         model_routes: Vec::new(),
+        routing_policies: Vec::new(),
         embedding_routes: Vec::new(),
         heartbeat: HeartbeatConfig::default(),
         cron: crate::config::CronConfig::default(),
+        sessions: crate::config::SessionsConfig::default(),
+        mcp: crate::config::McpConfig::default(),
         channels_config,
         memory: memory_config, // User-selected memory backend
         storage: StorageConfig::default(),
@@ -177,8 +181,11 @@ pub async fn run_wizard(force: bool) -> Result<Config> {
         secrets: secrets_config,
         browser: BrowserConfig::default(),
         http_request: crate::config::HttpRequestConfig::default(),
+        process: crate::config::ProcessConfig::default(),
+        checkpoints: crate::config::CheckpointsConfig::default(),
         multimodal: crate::config::MultimodalConfig::default(),
         web_search: crate::config::WebSearchConfig::default(),
+        web_fetch: crate::config::WebFetchConfig::default(),
         proxy: crate::config::ProxyConfig::default(),
         identity: crate::config::IdentityConfig::default(),
         cost: crate::config::CostConfig::default(),
@@ -412,9 +419,12 @@ async fn run_quick_setup_with_home(
         agent: crate::config::schema::AgentConfig::default(),
         skills: crate::config::SkillsConfig::default(),
         model_routes: Vec::new(),
+        routing_policies: Vec::new(),
         embedding_routes: Vec::new(),
         heartbeat: HeartbeatConfig::default(),
         cron: crate::config::CronConfig::default(),
+        sessions: crate::config::SessionsConfig::default(),
+        mcp: crate::config::McpConfig::default(),
         channels_config: ChannelsConfig::default(),
         memory: memory_config,
         storage: StorageConfig::default(),
@@ -424,8 +434,11 @@ async fn run_quick_setup_with_home(
         secrets: SecretsConfig::default(),
         browser: BrowserConfig::default(),
         http_request: crate::config::HttpRequestConfig::default(),
+        process: crate::config::ProcessConfig::default(),
+        checkpoints: crate::config::CheckpointsConfig::default(),
         multimodal: crate::config::MultimodalConfig::default(),
         web_search: crate::config::WebSearchConfig::default(),
+        web_fetch: crate::config::WebFetchConfig::default(),
         proxy: crate::config::ProxyConfig::default(),
         identity: crate::config::IdentityConfig::default(),
         cost: crate::config::CostConfig::default(),
@@ -1069,52 +1082,31 @@ fn normalize_model_ids(ids: Vec<String>) -> Vec<String> {
     unique.into_iter().collect()
 }
 
-fn parse_openai_compatible_model_ids(payload: &Value) -> Vec<String> {
-    let mut models = Vec::new();
-
-    if let Some(data) = payload.get("data").and_then(Value::as_array) {
-        for model in data {
-            if let Some(id) = model.get("id").and_then(Value::as_str) {
-                models.push(id.to_string());
-            }
-        }
-    } else if let Some(data) = payload.as_array() {
-        for model in data {
-            if let Some(id) = model.get("id").and_then(Value::as_str) {
-                models.push(id.to_string());
-            }
+/// Trim, de-duplicate and sort fetched models by ID; the first entry for
+/// an ID wins.
+fn normalize_models(models: Vec<ModelInfo>) -> Vec<ModelInfo> {
+    let mut unique = std::collections::BTreeMap::new();
+    for mut model in models {
+        let trimmed = model.id.trim();
+        if trimmed.is_empty() {
+            continue;
         }
+        model.id = trimmed.to_string();
+        unique.entry(model.id.clone()).or_insert(model);
     }
-
-    normalize_model_ids(models)
+    unique.into_values().collect()
 }
 
-fn parse_gemini_model_ids(payload: &Value) -> Vec<String> {
-    let Some(models) = payload.get("models").and_then(Value::as_array) else {
-        return Vec::new();
-    };
-
-    let mut ids = Vec::new();
-    for model in models {
-        let supports_generate_content = model
-            .get("supportedGenerationMethods")
-            .and_then(Value::as_array)
-            .is_none_or(|methods| {
-                methods
-                    .iter()
-                    .any(|method| method.as_str() == Some("generateContent"))
-            });
-
-        if !supports_generate_content {
-            continue;
-        }
+fn model_ids(models: &[ModelInfo]) -> Vec<String> {
+    models.iter().map(|model| model.id.clone()).collect()
+}
 
-        if let Some(name) = model.get("name").and_then(Value::as_str) {
-            ids.push(name.trim_start_matches("models/").to_string());
-        }
-    }
+fn parse_openai_compatible_models(payload: &Value) -> Vec<ModelInfo> {
+    normalize_models(catalog::parse_openai_compatible_models(payload))
+}
 
-    normalize_model_ids(ids)
+fn parse_gemini_models(payload: &Value) -> Vec<ModelInfo> {
+    normalize_models(catalog::parse_gemini_models(payload))
 }
 
 fn parse_ollama_model_ids(payload: &Value) -> Vec<String> {
@@ -1136,7 +1128,7 @@ fn fetch_openai_compatible_models(
     endpoint: &str,
     api_key: Option<&str>,
     allow_unauthenticated: bool,
-) -> Result<Vec<String>> {
+) -> Result<Vec<ModelInfo>> {
     let client = build_model_fetch_client()?;
     let mut request = client.get(endpoint);
 
@@ -1153,10 +1145,10 @@ fn fetch_openai_compatible_models(
         .json()
         .context("failed to parse model list response")?;
 
-    Ok(parse_openai_compatible_model_ids(&payload))
+    Ok(parse_openai_compatible_models(&payload))
 }
 
-fn fetch_openrouter_models(api_key: Option<&str>) -> Result<Vec<String>> {
+fn fetch_openrouter_models(api_key: Option<&str>) -> Result<Vec<ModelInfo>> {
     let client = build_model_fetch_client()?;
     let mut request = client.get("https://openrouter.ai/api/v1/models");
     if let Some(api_key) = api_key {
@@ -1170,10 +1162,10 @@ fn fetch_openrouter_models(api_key: Option<&str>) -> Result<Vec<String>> {
         .json()
         .context("failed to parse OpenRouter model list response")?;
 
-    Ok(parse_openai_compatible_model_ids(&payload))
+    Ok(parse_openai_compatible_models(&payload))
 }
 
-fn fetch_anthropic_models(api_key: Option<&str>) -> Result<Vec<String>> {
+fn fetch_anthropic_models(api_key: Option<&str>) -> Result<Vec<ModelInfo>> {
     let Some(api_key) = api_key else {
         bail!("Anthropic model fetch requires API key or OAuth token");
     };
@@ -1205,10 +1197,10 @@ fn fetch_anthropic_models(api_key: Option<&str>) -> Result<Vec<String>> {
         .json()
         .context("failed to parse Anthropic model list response")?;
 
-    Ok(parse_openai_compatible_model_ids(&payload))
+    Ok(parse_openai_compatible_models(&payload))
 }
 
-fn fetch_gemini_models(api_key: Option<&str>) -> Result<Vec<String>> {
+fn fetch_gemini_models(api_key: Option<&str>) -> Result<Vec<ModelInfo>> {
     let Some(api_key) = api_key else {
         bail!("Gemini model fetch requires API key");
     };
@@ -1223,10 +1215,10 @@ fn fetch_gemini_models(api_key: Option<&str>) -> Result<Vec<String>> {
         .json()
         .context("failed to parse Gemini model list response")?;
 
-    Ok(parse_gemini_model_ids(&payload))
+    Ok(parse_gemini_models(&payload))
 }
 
-fn fetch_ollama_models() -> Result<Vec<String>> {
+fn fetch_ollama_models() -> Result<Vec<ModelInfo>> {
     let client = build_model_fetch_client()?;
     let payload: Value = client
         .get("http://localhost:11434/api/tags")
@@ -1236,7 +1228,23 @@ fn fetch_ollama_models() -> Result<Vec<String>> {
         .json()
         .context("failed to parse Ollama model list response")?;
 
-    Ok(parse_ollama_model_ids(&payload))
+    // `/api/show` carries the context length and capabilities; a model that
+    // fails to describe itself is still listed.
+    Ok(parse_ollama_model_ids(&payload)
+        .into_iter()
+        .map(|name| {
+            client
+                .post("http://localhost:11434/api/show")
+                .json(&serde_json::json!({ "model": name }))
+                .send()
+                .and_then(reqwest::blocking::Response::error_for_status)
+                .and_then(reqwest::blocking::Response::json::<Value>)
+                .map_or_else(
+                    |_| ModelInfo::new(&name),
+                    |show| catalog::parse_ollama_show(&name, &show),
+                )
+        })
+        .collect())
 }
 
 fn normalize_ollama_endpoint_url(raw_url: &str) -> String {
@@ -1295,7 +1303,7 @@ fn fetch_live_models_for_provider(
     provider_name: &str,
     api_key: &str,
     provider_api_url: Option<&str>,
-) -> Result<Vec<String>> {
+) -> Result<Vec<ModelInfo>> {
     let requested_provider_name = provider_name;
     let provider_name = canonical_provider_name(provider_name);
     let ollama_remote = provider_name == "ollama" && ollama_uses_remote_endpoint(provider_api_url);
@@ -1330,23 +1338,26 @@ fn fetch_live_models_for_provider(
             if ollama_remote {
                 // Remote Ollama endpoints can serve cloud-routed models.
                 // Keep this curated list aligned with current Ollama cloud catalog.
-                vec![
-                    "glm-5:cloud".to_string(),
-                    "glm-4.7:cloud".to_string(),
-                    "gpt-oss:20b:cloud".to_string(),
-                    "gpt-oss:120b:cloud".to_string(),
-                    "gemini-3-flash-preview:cloud".to_string(),
-                    "qwen3-coder-next:cloud".to_string(),
-                    "qwen3-coder:480b:cloud".to_string(),
-                    "kimi-k2.5:cloud".to_string(),
-                    "minimax-m2.5:cloud".to_string(),
-                    "deepseek-v3.1:671b:cloud".to_string(),
+                [
+                    "glm-5:cloud",
+                    "glm-4.7:cloud",
+                    "gpt-oss:20b:cloud",
+                    "gpt-oss:120b:cloud",
+                    "gemini-3-flash-preview:cloud",
+                    "qwen3-coder-next:cloud",
+                    "qwen3-coder:480b:cloud",
+                    "kimi-k2.5:cloud",
+                    "minimax-m2.5:cloud",
+                    "deepseek-v3.1:671b:cloud",
                 ]
+                .into_iter()
+                .map(ModelInfo::new)
+                .collect()
             } else {
                 // Local endpoints should not surface cloud-only suffixes.
                 fetch_ollama_models()?
                     .into_iter()
-                    .filter(|model_id| !model_id.ends_with(":cloud"))
+                    .filter(|model| !model.id.ends_with(":cloud"))
                     .collect()
             }
         }
@@ -1375,6 +1386,9 @@ struct ModelCacheEntry {
     provider: String,
     fetched_at_unix: u64,
     models: Vec<String>,
+    /// Catalog metadata for `models`; see [`catalog::ModelInfo`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    details: Vec<ModelInfo>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -1436,12 +1450,14 @@ async fn save_model_cache_state(workspace_dir: &Path, state: &ModelCacheState) -
 async fn cache_live_models_for_provider(
     workspace_dir: &Path,
     provider_name: &str,
-    models: &[String],
+    models: &[ModelInfo],
 ) -> Result<()> {
-    let normalized_models = normalize_model_ids(models.to_vec());
-    if normalized_models.is_empty() {
+    let details = normalize_models(models.to_vec());
+    if details.is_empty() {
         return Ok(());
     }
+    let normalized_models = model_ids(&details);
+    catalog::record_provider_models(provider_name, details.clone());
 
     let mut state = load_model_cache_state(workspace_dir).await?;
     let now = now_unix_secs();
@@ -1453,11 +1469,13 @@ async fn cache_live_models_for_provider(
     {
         entry.fetched_at_unix = now;
         entry.models = normalized_models;
+        entry.details = details;
     } else {
         state.entries.push(ModelCacheEntry {
             provider: provider_name.to_string(),
             fetched_at_unix: now,
             models: normalized_models,
+            details,
         });
     }
 
@@ -1596,7 +1614,7 @@ pub async fn run_models_refresh(
                 provider_name,
                 models.len()
             );
-            print_model_preview(&models);
+            print_model_preview(&model_ids(&models));
             Ok(())
         }
         Ok(_) => {
@@ -2280,14 +2298,11 @@ async fn setup_provider(workspace_dir: &Path) -> Result<(String, String, String,
                     &api_key,
                     provider_api_url.as_deref(),
                 ) {
-                    Ok(live_model_ids) if !live_model_ids.is_empty() => {
-                        cache_live_models_for_provider(
-                            workspace_dir,
-                            provider_name,
-                            &live_model_ids,
-                        )
-                        .await?;
+                    Ok(live_models) if !live_models.is_empty() => {
+                        cache_live_models_for_provider(workspace_dir, provider_name, &live_models)
+                            .await?;
 
+                        let live_model_ids = model_ids(&live_models);
                         let fetched_count = live_model_ids.len();
                         let shown_count = fetched_count.min(LIVE_MODEL_MAX_OPTIONS);
                         let shown_models: Vec<String> = live_model_ids
@@ -6107,7 +6122,7 @@ mod tests {
             ]
         });
 
-        let ids = parse_openai_compatible_model_ids(&payload);
+        let ids = model_ids(&parse_openai_compatible_models(&payload));
         assert_eq!(ids, vec!["gpt-5-mini".to_string(), "gpt-5.1".to_string()]);
     }
 
@@ -6119,7 +6134,7 @@ mod tests {
             {"id": "alpha"}
         ]);
 
-        let ids = parse_openai_compatible_model_ids(&payload);
+        let ids = model_ids(&parse_openai_compatible_models(&payload));
         assert_eq!(ids, vec!["alpha".to_string(), "beta".to_string()]);
     }
 
@@ -6142,7 +6157,7 @@ mod tests {
             ]
         });
 
-        let ids = parse_gemini_model_ids(&payload);
+        let ids = model_ids(&parse_gemini_models(&payload));
         assert_eq!(
             ids,
             vec!["gemini-2.5-flash".to_string(), "gemini-2.5-pro".to_string()]
@@ -6169,7 +6184,7 @@ mod tests {
     #[tokio::test]
     async fn model_cache_round_trip_returns_fresh_entry() {
         let tmp = TempDir::new().unwrap();
-        let models = vec!["gpt-5.1".to_string(), "gpt-5-mini".to_string()];
+        let models = vec![ModelInfo::new("gpt-5.1"), ModelInfo::new("gpt-5-mini")];
 
         cache_live_models_for_provider(tmp.path(), "openai", &models)
             .await
@@ -6193,6 +6208,7 @@ mod tests {
                 provider: "openai".to_string(),
                 fetched_at_unix: now_unix_secs().saturating_sub(MODEL_CACHE_TTL_SECS + 120),
                 models: vec!["gpt-5.1".to_string()],
+                details: Vec::new(),
             }],
         };
 
@@ -6213,7 +6229,7 @@ mod tests {
     async fn run_models_refresh_uses_fresh_cache_without_network() {
         let tmp = TempDir::new().unwrap();
 
-        cache_live_models_for_provider(tmp.path(), "openai", &["gpt-5.1".to_string()])
+        cache_live_models_for_provider(tmp.path(), "openai", &[ModelInfo::new("gpt-5.1")])
             .await
             .unwrap();
 
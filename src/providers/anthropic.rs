@@ -1,15 +1,24 @@
+use crate::providers::sse::{self, StreamDecoder};
+use crate::providers::structured::OutputSchema;
 use crate::providers::traits::{
-    ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, TokenUsage, ToolCall as ProviderToolCall,
+    chat_response_stream, ChatMessage, ChatRequest as ProviderChatRequest,
+    ChatResponse as ProviderChatResponse, ContentPart, MediaSource, Provider, ProviderCapabilities,
+    Reasoning, StreamError, StreamEvent, StreamResult, TokenUsage, ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use futures_util::stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+const STRUCTURED_OUTPUT_TOOL_DESCRIPTION: &str =
+    "Give your final answer by calling this tool. Its input is the answer.";
 
 pub struct AnthropicProvider {
     credential: Option<String>,
     base_url: String,
+    thinking_budget_tokens: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -51,6 +60,10 @@ struct NativeChatRequest<'a> {
     temperature: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<NativeToolSpec<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -68,6 +81,12 @@ enum NativeContentOut {
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
+    #[serde(rename = "image")]
+    Image {
+        source: NativeImageSource,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
     #[serde(rename = "tool_use")]
     ToolUse {
         id: String,
@@ -83,6 +102,15 @@ enum NativeContentOut {
         #[serde(skip_serializing_if = "Option::is_none")]
         cache_control: Option<CacheControl>,
     },
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum NativeImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
 }
 
 #[derive(Debug, Serialize)]
@@ -138,6 +166,25 @@ struct AnthropicUsage {
     input_tokens: Option<u64>,
     #[serde(default)]
     output_tokens: Option<u64>,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u64>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
+}
+
+impl AnthropicUsage {
+    /// Anthropic reports cache reads and writes alongside `input_tokens`
+    /// rather than inside it; fold them back into the prompt total.
+    fn into_token_usage(self) -> TokenUsage {
+        let cached = self.cache_read_input_tokens.unwrap_or(0)
+            + self.cache_creation_input_tokens.unwrap_or(0);
+        TokenUsage {
+            input_tokens: self.input_tokens.map(|input| input + cached),
+            output_tokens: self.output_tokens,
+            cache_read_tokens: self.cache_read_input_tokens,
+            cache_write_tokens: self.cache_creation_input_tokens,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -152,6 +199,191 @@ struct NativeContentIn {
     name: Option<String>,
     #[serde(default)]
     input: Option<serde_json::Value>,
+    #[serde(default)]
+    thinking: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+// ─── Streaming (server-sent events) ───────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamMessageEvent {
+    MessageStart {
+        message: StreamMessageStart,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: NativeContentIn,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: StreamBlockDelta,
+    },
+    ContentBlockStop {
+        index: usize,
+    },
+    MessageDelta {
+        #[serde(default)]
+        delta: StreamMessageDelta,
+        #[serde(default)]
+        usage: Option<AnthropicUsage>,
+    },
+    MessageStop,
+    Error {
+        error: StreamErrorBody,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamMessageStart {
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamBlockDelta {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    partial_json: Option<String>,
+    #[serde(default)]
+    thinking: Option<String>,
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StreamMessageDelta {
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamErrorBody {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Decoder for the Messages API event stream (`message_start`,
+/// `content_block_*`, `message_delta`, `message_stop`).
+#[derive(Debug, Default)]
+struct MessageStreamDecoder {
+    tool_blocks: BTreeSet<usize>,
+    usage: TokenUsage,
+    stop_reason: Option<String>,
+    finished: bool,
+}
+
+impl StreamDecoder for MessageStreamDecoder {
+    fn decode_line(&mut self, line: &str) -> StreamResult<Vec<StreamEvent>> {
+        let Some(data) = sse::sse_data(line) else {
+            return Ok(Vec::new());
+        };
+        let event: StreamMessageEvent = serde_json::from_str(data).map_err(StreamError::Json)?;
+
+        let mut events = Vec::new();
+        match event {
+            StreamMessageEvent::MessageStart { message } => {
+                if let Some(usage) = message.usage {
+                    self.usage = usage.into_token_usage();
+                }
+            }
+            StreamMessageEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => match content_block.kind.as_str() {
+                "text" => {
+                    if let Some(text) = content_block.text.filter(|t| !t.is_empty()) {
+                        events.push(StreamEvent::TextDelta(text));
+                    }
+                }
+                "thinking" => {
+                    if let Some(thinking) = content_block.thinking.filter(|t| !t.is_empty()) {
+                        events.push(StreamEvent::ReasoningDelta(thinking));
+                    }
+                }
+                "tool_use" => {
+                    self.tool_blocks.insert(index);
+                    events.push(StreamEvent::ToolCallStart {
+                        index,
+                        id: content_block.id.unwrap_or_default(),
+                        name: content_block.name.unwrap_or_default(),
+                    });
+                }
+                _ => {}
+            },
+            StreamMessageEvent::ContentBlockDelta { index, delta } => match delta.kind.as_str() {
+                "text_delta" => {
+                    if let Some(text) = delta.text.filter(|t| !t.is_empty()) {
+                        events.push(StreamEvent::TextDelta(text));
+                    }
+                }
+                "input_json_delta" => {
+                    if let Some(arguments) = delta.partial_json.filter(|a| !a.is_empty()) {
+                        events.push(StreamEvent::ToolCallDelta { index, arguments });
+                    }
+                }
+                "thinking_delta" => {
+                    if let Some(thinking) = delta.thinking.filter(|t| !t.is_empty()) {
+                        events.push(StreamEvent::ReasoningDelta(thinking));
+                    }
+                }
+                "signature_delta" => {
+                    if let Some(signature) = delta.signature.filter(|s| !s.is_empty()) {
+                        events.push(StreamEvent::ReasoningSignature(signature));
+                    }
+                }
+                _ => {}
+            },
+            StreamMessageEvent::ContentBlockStop { index } => {
+                if self.tool_blocks.remove(&index) {
+                    events.push(StreamEvent::ToolCallEnd { index });
+                }
+            }
+            StreamMessageEvent::MessageDelta { delta, usage } => {
+                if delta.stop_reason.is_some() {
+                    self.stop_reason = delta.stop_reason;
+                }
+                if let Some(output_tokens) = usage.and_then(|u| u.output_tokens) {
+                    self.usage.output_tokens = Some(output_tokens);
+                }
+            }
+            StreamMessageEvent::MessageStop => events.extend(self.finish()),
+            StreamMessageEvent::Error { error } => {
+                return Err(StreamError::Provider(format!(
+                    "Anthropic stream error ({}): {}",
+                    error.kind, error.message
+                )));
+            }
+            StreamMessageEvent::Other => {}
+        }
+        Ok(events)
+    }
+
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = true;
+
+        let mut events: Vec<StreamEvent> = std::mem::take(&mut self.tool_blocks)
+            .into_iter()
+            .map(|index| StreamEvent::ToolCallEnd { index })
+            .collect();
+        if self.usage.input_tokens.is_some() || self.usage.output_tokens.is_some() {
+            events.push(StreamEvent::Usage(std::mem::take(&mut self.usage)));
+        }
+        events.push(StreamEvent::Finish(self.stop_reason.take()));
+        events
+    }
 }
 
 impl AnthropicProvider {
@@ -170,9 +402,17 @@ impl AnthropicProvider {
                 .filter(|k| !k.is_empty())
                 .map(ToString::to_string),
             base_url,
+            thinking_budget_tokens: None,
         }
     }
 
+    /// Enable extended thinking with the given token budget (`None` leaves
+    /// it off).
+    pub fn with_thinking_budget(mut self, budget_tokens: Option<u32>) -> Self {
+        self.thinking_budget_tokens = budget_tokens.filter(|budget| *budget > 0);
+        self
+    }
+
     fn is_setup_token(token: &str) -> bool {
         token.starts_with("sk-ant-oat01-")
     }
@@ -207,10 +447,11 @@ impl AnthropicProvider {
             if let Some(last_content) = last_msg.content.last_mut() {
                 match last_content {
                     NativeContentOut::Text { cache_control, .. }
+                    | NativeContentOut::Image { cache_control, .. }
                     | NativeContentOut::ToolResult { cache_control, .. } => {
                         *cache_control = Some(CacheControl::ephemeral());
                     }
-                    NativeContentOut::ToolUse { .. } => {}
+                    NativeContentOut::ToolUse { .. } | NativeContentOut::Thinking { .. } => {}
                 }
             }
         }
@@ -246,6 +487,19 @@ impl AnthropicProvider {
             .and_then(|v| serde_json::from_value::<Vec<ProviderToolCall>>(v.clone()).ok())?;
 
         let mut blocks = Vec::new();
+        // With extended thinking on, the signed thinking block must lead the
+        // assistant turn that requested the tools.
+        if let Some(reasoning) = value
+            .get("reasoning")
+            .and_then(|v| serde_json::from_value::<Reasoning>(v.clone()).ok())
+        {
+            if let Some(signature) = reasoning.signature {
+                blocks.push(NativeContentOut::Thinking {
+                    thinking: reasoning.text,
+                    signature,
+                });
+            }
+        }
         if let Some(text) = value
             .get("content")
             .and_then(serde_json::Value::as_str)
@@ -332,12 +586,17 @@ impl AnthropicProvider {
                     }
                 }
                 _ => {
-                    native_messages.push(NativeMessage {
-                        role: "user".to_string(),
-                        content: vec![NativeContentOut::Text {
+                    let content = if msg.has_media() {
+                        Self::convert_parts(&msg.content_parts())
+                    } else {
+                        vec![NativeContentOut::Text {
                             text: msg.content.clone(),
                             cache_control: None,
-                        }],
+                        }]
+                    };
+                    native_messages.push(NativeMessage {
+                        role: "user".to_string(),
+                        content,
                     });
                 }
             }
@@ -359,6 +618,102 @@ impl AnthropicProvider {
         (system_prompt, native_messages)
     }
 
+    /// Images map to `image` blocks; parts the Messages API cannot take
+    /// inline (audio, file references) are described as text.
+    fn convert_parts(parts: &[ContentPart]) -> Vec<NativeContentOut> {
+        parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Image { mime_type, source } => NativeContentOut::Image {
+                    source: match source {
+                        MediaSource::Base64(data) => NativeImageSource::Base64 {
+                            media_type: mime_type
+                                .clone()
+                                .unwrap_or_else(|| "image/png".to_string()),
+                            data: data.clone(),
+                        },
+                        MediaSource::Url(url) => NativeImageSource::Url { url: url.clone() },
+                    },
+                    cache_control: None,
+                },
+                other => NativeContentOut::Text {
+                    text: other.fallback_text(),
+                    cache_control: None,
+                },
+            })
+            .collect()
+    }
+
+    fn native_request<'a>(
+        request: ProviderChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+        thinking_budget_tokens: Option<u32>,
+    ) -> NativeChatRequest<'a> {
+        let (system_prompt, mut messages) = Self::convert_messages(request.messages);
+
+        // Auto-cache last message if conversation is long
+        if Self::should_cache_conversation(request.messages) {
+            Self::apply_cache_to_last_message(&mut messages);
+        }
+
+        let mut tools = Self::convert_tools(request.tools);
+        let mut tool_choice = None;
+        // Structured output rides on a forced tool call: the answer is the
+        // tool input. With other tools present the model may still call them
+        // first, but every turn must end in some tool call.
+        if let Some(schema) = request.output_schema {
+            // Extended thinking rejects forced tool use; leave the choice to
+            // the model and let the caller validate a plain-text answer.
+            if thinking_budget_tokens.is_none() {
+                tool_choice = Some(if tools.is_some() {
+                    serde_json::json!({"type": "any"})
+                } else {
+                    serde_json::json!({"type": "tool", "name": schema.name})
+                });
+            }
+            tools.get_or_insert_with(Vec::new).push(NativeToolSpec {
+                name: &schema.name,
+                description: STRUCTURED_OUTPUT_TOOL_DESCRIPTION,
+                input_schema: &schema.schema,
+                cache_control: None,
+            });
+        }
+
+        // Thinking needs room beyond its budget and only runs at temperature 1.
+        let (max_tokens, temperature, thinking) = match thinking_budget_tokens {
+            Some(budget) => (
+                budget.saturating_add(4096),
+                1.0,
+                Some(serde_json::json!({"type": "enabled", "budget_tokens": budget})),
+            ),
+            None => (4096, temperature, None),
+        };
+
+        NativeChatRequest {
+            model: model.to_string(),
+            max_tokens,
+            system: system_prompt,
+            messages,
+            temperature,
+            tools,
+            tool_choice,
+            thinking,
+        }
+    }
+
+    /// Turn the forced structured-output tool call back into the answer text.
+    fn unwrap_structured_output(response: &mut ProviderChatResponse, schema: &OutputSchema) {
+        if let Some(idx) = response
+            .tool_calls
+            .iter()
+            .position(|call| call.name == schema.name)
+        {
+            let call = response.tool_calls.remove(idx);
+            response.text = Some(call.arguments);
+        }
+    }
+
     fn parse_text_response(response: ChatResponse) -> anyhow::Result<String> {
         response
             .content
@@ -371,14 +726,21 @@ impl AnthropicProvider {
     fn parse_native_response(response: NativeChatResponse) -> ProviderChatResponse {
         let mut text_parts = Vec::new();
         let mut tool_calls = Vec::new();
+        let mut reasoning: Option<Reasoning> = None;
 
-        let usage = response.usage.map(|u| TokenUsage {
-            input_tokens: u.input_tokens,
-            output_tokens: u.output_tokens,
-        });
+        let usage = response.usage.map(AnthropicUsage::into_token_usage);
 
         for block in response.content {
             match block.kind.as_str() {
+                "thinking" => {
+                    let entry = reasoning.get_or_insert_with(Reasoning::default);
+                    entry
+                        .text
+                        .push_str(block.thinking.as_deref().unwrap_or_default());
+                    if block.signature.is_some() {
+                        entry.signature = block.signature;
+                    }
+                }
                 "text" => {
                     if let Some(text) = block.text.map(|t| t.trim().to_string()) {
                         if !text.is_empty() {
@@ -412,6 +774,7 @@ impl AnthropicProvider {
             },
             tool_calls,
             usage,
+            reasoning,
         }
     }
 
@@ -477,21 +840,9 @@ impl Provider for AnthropicProvider {
             )
         })?;
 
-        let (system_prompt, mut messages) = Self::convert_messages(request.messages);
-
-        // Auto-cache last message if conversation is long
-        if Self::should_cache_conversation(request.messages) {
-            Self::apply_cache_to_last_message(&mut messages);
-        }
-
-        let native_request = NativeChatRequest {
-            model: model.to_string(),
-            max_tokens: 4096,
-            system: system_prompt,
-            messages,
-            temperature,
-            tools: Self::convert_tools(request.tools),
-        };
+        let output_schema = request.output_schema;
+        let native_request =
+            Self::native_request(request, model, temperature, self.thinking_budget_tokens);
 
         let req = self
             .http_client()
@@ -506,13 +857,77 @@ impl Provider for AnthropicProvider {
         }
 
         let native_response: NativeChatResponse = response.json().await?;
-        Ok(Self::parse_native_response(native_response))
+        let mut result = Self::parse_native_response(native_response);
+        if let Some(schema) = output_schema {
+            Self::unwrap_structured_output(&mut result, schema);
+        }
+        Ok(result)
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_tool_calling: true,
+            vision: true,
+            structured_output: true,
+        }
     }
 
     fn supports_native_tools(&self) -> bool {
         true
     }
 
+    fn stream_chat<'a>(
+        &'a self,
+        request: ProviderChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+    ) -> stream::BoxStream<'a, StreamResult<StreamEvent>> {
+        // The structured answer arrives as tool input; answer in one piece.
+        if request.output_schema.is_some() {
+            let model = model.to_string();
+            return chat_response_stream(
+                async move { self.chat(request, &model, temperature).await },
+            );
+        }
+        let body = serde_json::to_value(Self::native_request(
+            request,
+            model,
+            temperature,
+            self.thinking_budget_tokens,
+        ));
+        sse::open_stream(async move {
+            let credential = self.credential.as_ref().ok_or_else(|| {
+                StreamError::Provider(
+                    "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token).".into(),
+                )
+            })?;
+            let mut body = body.map_err(StreamError::Json)?;
+            body["stream"] = serde_json::Value::Bool(true);
+
+            let req = self
+                .http_client()
+                .post(format!("{}/v1/messages", self.base_url))
+                .header("anthropic-version", "2023-06-01")
+                .header("content-type", "application/json")
+                .json(&body);
+
+            let response = self
+                .apply_auth(req, credential)
+                .send()
+                .await
+                .map_err(StreamError::Http)?;
+            if !response.status().is_success() {
+                let error = super::api_error("Anthropic", response).await;
+                return Err(StreamError::Provider(error.to_string()));
+            }
+
+            Ok(sse::decode_response(
+                response,
+                MessageStreamDecoder::default(),
+            ))
+        })
+    }
+
     async fn chat_with_tools(
         &self,
         messages: &[ChatMessage],
@@ -556,6 +971,7 @@ impl Provider for AnthropicProvider {
             } else {
                 Some(&tool_specs)
             },
+            output_schema: None,
         };
         self.chat(request, model, temperature).await
     }
@@ -944,14 +1360,17 @@ mod tests {
             ChatMessage {
                 role: "system".to_string(),
                 content: "System prompt".to_string(),
+                parts: Vec::new(),
             },
             ChatMessage {
                 role: "user".to_string(),
                 content: "Hello".to_string(),
+                parts: Vec::new(),
             },
             ChatMessage {
                 role: "assistant".to_string(),
                 content: "Hi".to_string(),
+                parts: Vec::new(),
             },
         ];
         // Only 2 non-system messages
@@ -963,12 +1382,14 @@ mod tests {
         let mut messages = vec![ChatMessage {
             role: "system".to_string(),
             content: "System prompt".to_string(),
+            parts: Vec::new(),
         }];
         // Add 5 non-system messages
         for i in 0..5 {
             messages.push(ChatMessage {
                 role: if i % 2 == 0 { "user" } else { "assistant" }.to_string(),
                 content: format!("Message {i}"),
+                parts: Vec::new(),
             });
         }
         assert!(AnthropicProvider::should_cache_conversation(&messages));
@@ -982,6 +1403,7 @@ mod tests {
             messages.push(ChatMessage {
                 role: if i % 2 == 0 { "user" } else { "assistant" }.to_string(),
                 content: format!("Message {i}"),
+                parts: Vec::new(),
             });
         }
         assert!(!AnthropicProvider::should_cache_conversation(&messages));
@@ -990,6 +1412,7 @@ mod tests {
         messages.push(ChatMessage {
             role: "user".to_string(),
             content: "One more".to_string(),
+            parts: Vec::new(),
         });
         assert!(AnthropicProvider::should_cache_conversation(&messages));
     }
@@ -1102,11 +1525,30 @@ mod tests {
         assert!(native_tools[0].cache_control.is_some());
     }
 
+    #[test]
+    fn convert_messages_maps_image_parts_to_image_blocks() {
+        let messages = vec![ChatMessage::user(
+            "What is in this picture? [IMAGE:data:image/jpeg;base64,abcd]",
+        )];
+
+        let (_, native) = AnthropicProvider::convert_messages(&messages);
+        let json = serde_json::to_value(&native[0]).unwrap();
+
+        assert_eq!(
+            json["content"],
+            serde_json::json!([
+                {"type": "text", "text": "What is in this picture?"},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/jpeg", "data": "abcd"}}
+            ])
+        );
+    }
+
     #[test]
     fn convert_messages_small_system_prompt() {
         let messages = vec![ChatMessage {
             role: "system".to_string(),
             content: "Short system prompt".to_string(),
+            parts: Vec::new(),
         }];
 
         let (system_prompt, _) = AnthropicProvider::convert_messages(&messages);
@@ -1125,6 +1567,7 @@ mod tests {
         let messages = vec![ChatMessage {
             role: "system".to_string(),
             content: large_content.clone(),
+            parts: Vec::new(),
         }];
 
         let (system_prompt, _) = AnthropicProvider::convert_messages(&messages);
@@ -1155,6 +1598,8 @@ mod tests {
             }],
             temperature: 0.7,
             tools: None,
+            tool_choice: None,
+            thinking: None,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -1162,6 +1607,43 @@ mod tests {
         assert!(json.contains(r#""system":"System""#));
     }
 
+    #[test]
+    fn output_schema_forces_answer_tool_and_unwraps_it() {
+        let schema = OutputSchema::named(
+            "weather",
+            serde_json::json!({"type": "object", "properties": {"temp": {"type": "number"}}}),
+        )
+        .unwrap();
+        let messages = [ChatMessage::user("How warm is it?")];
+        let request = ProviderChatRequest {
+            messages: &messages,
+            tools: None,
+            output_schema: Some(&schema),
+        };
+        let json = serde_json::to_value(AnthropicProvider::native_request(request, "m", 0.0, None))
+            .unwrap();
+        assert_eq!(
+            json["tool_choice"],
+            serde_json::json!({"type": "tool", "name": "weather"})
+        );
+        assert_eq!(json["tools"][0]["name"], "weather");
+        assert_eq!(json["tools"][0]["input_schema"], schema.schema);
+
+        let mut response = ProviderChatResponse {
+            text: None,
+            tool_calls: vec![ProviderToolCall {
+                id: "t1".into(),
+                name: "weather".into(),
+                arguments: r#"{"temp":21.5}"#.into(),
+            }],
+            usage: None,
+            reasoning: None,
+        };
+        AnthropicProvider::unwrap_structured_output(&mut response, &schema);
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(response.text.as_deref(), Some(r#"{"temp":21.5}"#));
+    }
+
     #[tokio::test]
     async fn warmup_without_key_is_noop() {
         let provider = AnthropicProvider::new(None);
@@ -1175,18 +1657,22 @@ mod tests {
             ChatMessage {
                 role: "system".to_string(),
                 content: "You are helpful.".to_string(),
+                parts: Vec::new(),
             },
             ChatMessage {
                 role: "user".to_string(),
                 content: "gen a 2 sum in golang".to_string(),
+                parts: Vec::new(),
             },
             ChatMessage {
                 role: "assistant".to_string(),
                 content: "```go\nfunc twoSum(nums []int) {}\n```".to_string(),
+                parts: Vec::new(),
             },
             ChatMessage {
                 role: "user".to_string(),
                 content: "what's meaning of make here?".to_string(),
+                parts: Vec::new(),
             },
         ];
 
@@ -1244,6 +1730,7 @@ mod tests {
         let provider = AnthropicProvider {
             credential: Some("test-key".to_string()),
             base_url: format!("http://{addr}"),
+            thinking_budget_tokens: None,
         };
 
         // Multi-turn conversation: system → user (Go code) → assistant (code response) → user (follow-up)
@@ -1345,6 +1832,135 @@ mod tests {
         assert_eq!(usage.output_tokens, Some(75));
     }
 
+    #[test]
+    fn native_response_folds_cache_tokens_into_input() {
+        let json = r#"{
+            "content": [{"type": "text", "text": "Hello"}],
+            "usage": {
+                "input_tokens": 20,
+                "output_tokens": 5,
+                "cache_creation_input_tokens": 100,
+                "cache_read_input_tokens": 4000
+            }
+        }"#;
+        let resp: NativeChatResponse = serde_json::from_str(json).unwrap();
+        let usage = AnthropicProvider::parse_native_response(resp)
+            .usage
+            .unwrap();
+        assert_eq!(usage.input_tokens, Some(4120));
+        assert_eq!(usage.cache_read_tokens, Some(4000));
+        assert_eq!(usage.cache_write_tokens, Some(100));
+    }
+
+    #[test]
+    fn native_request_marks_system_tools_and_history_for_caching() {
+        let mut messages = vec![ChatMessage::system("x".repeat(4000))];
+        for i in 0..5 {
+            messages.push(if i % 2 == 0 {
+                ChatMessage::user(format!("Message {i}"))
+            } else {
+                ChatMessage::assistant(format!("Message {i}"))
+            });
+        }
+        let tools = vec![ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let request = AnthropicProvider::native_request(
+            ProviderChatRequest {
+                messages: &messages,
+                tools: Some(&tools),
+                output_schema: None,
+            },
+            "claude-sonnet-4",
+            0.0,
+            None,
+        );
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["system"][0]["cache_control"]["type"], "ephemeral");
+        assert_eq!(json["tools"][0]["cache_control"]["type"], "ephemeral");
+        let last = json["messages"].as_array().unwrap().last().unwrap();
+        assert_eq!(last["content"][0]["cache_control"]["type"], "ephemeral");
+    }
+
+    #[test]
+    fn native_response_separates_thinking_from_text() {
+        let json = r#"{"content": [
+            {"type": "thinking", "thinking": "Check the clock.", "signature": "sig-1"},
+            {"type": "text", "text": "It is noon."}
+        ]}"#;
+        let resp: NativeChatResponse = serde_json::from_str(json).unwrap();
+        let result = AnthropicProvider::parse_native_response(resp);
+        assert_eq!(result.text.as_deref(), Some("It is noon."));
+        assert_eq!(
+            result.reasoning,
+            Some(Reasoning {
+                text: "Check the clock.".into(),
+                signature: Some("sig-1".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn native_request_with_thinking_budget_enables_thinking() {
+        let messages = vec![ChatMessage::user("hi")];
+        let schema = OutputSchema::named("answer", serde_json::json!({"type": "object"})).unwrap();
+        let request = AnthropicProvider::native_request(
+            ProviderChatRequest {
+                messages: &messages,
+                tools: None,
+                output_schema: Some(&schema),
+            },
+            "claude-sonnet-4",
+            0.2,
+            Some(2048),
+        );
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["thinking"]["type"], "enabled");
+        assert_eq!(json["thinking"]["budget_tokens"], 2048);
+        assert_eq!(json["max_tokens"], 2048 + 4096);
+        assert_eq!(json["temperature"], 1.0);
+        assert!(json.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn convert_messages_replays_signed_thinking_before_tool_use() {
+        let messages = vec![ChatMessage::assistant(
+            r#"{"content":null,"tool_calls":[{"id":"toolu_1","name":"shell","arguments":"{}"}],"reasoning":{"text":"Need the date.","signature":"sig-1"}}"#,
+        )];
+        let (_, native) = AnthropicProvider::convert_messages(&messages);
+        let json = serde_json::to_value(&native[0].content).unwrap();
+
+        assert_eq!(json[0]["type"], "thinking");
+        assert_eq!(json[0]["thinking"], "Need the date.");
+        assert_eq!(json[0]["signature"], "sig-1");
+        assert_eq!(json[1]["type"], "tool_use");
+    }
+
+    #[test]
+    fn stream_decoder_emits_reasoning_events() {
+        let mut decoder = MessageStreamDecoder::default();
+        let mut events = Vec::new();
+        for line in [
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Hmm."}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"sig-1"}}"#,
+            r#"data: {"type":"content_block_stop","index":0}"#,
+        ] {
+            events.extend(decoder.decode_line(line).unwrap());
+        }
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::ReasoningDelta("Hmm.".into()),
+                StreamEvent::ReasoningSignature("sig-1".into()),
+            ]
+        );
+    }
+
     #[test]
     fn native_response_parses_without_usage() {
         let json = r#"{"content": [{"type": "text", "text": "Hello"}]}"#;
@@ -1352,4 +1968,118 @@ mod tests {
         let result = AnthropicProvider::parse_native_response(resp);
         assert!(result.usage.is_none());
     }
+
+    #[tokio::test]
+    async fn stream_chat_decodes_text_tool_use_and_usage() {
+        use axum::{routing::post, Json, Router};
+        use futures_util::StreamExt;
+        use std::sync::{Arc, Mutex};
+        use tokio::net::TcpListener;
+
+        let captured: Arc<Mutex<Option<serde_json::Value>>> = Arc::new(Mutex::new(None));
+        let captured_clone = captured.clone();
+        let sse_body = [
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":42,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"ping"}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Let me check."}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"shell","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\":"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"date\"}"}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":17}}"#,
+            r#"{"type":"message_stop"}"#,
+        ]
+        .iter()
+        .map(|data| format!("event: message\ndata: {data}\n\n"))
+        .collect::<String>();
+
+        let app = Router::new().route(
+            "/v1/messages",
+            post(move |Json(body): Json<serde_json::Value>| {
+                let cap = captured_clone.clone();
+                let sse_body = sse_body.clone();
+                async move {
+                    *cap.lock().unwrap() = Some(body);
+                    ([("content-type", "text/event-stream")], sse_body)
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server_handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let provider = AnthropicProvider {
+            credential: Some("test-key".to_string()),
+            base_url: format!("http://{addr}"),
+            thinking_budget_tokens: None,
+        };
+        let messages = vec![ChatMessage::user("what time is it?")];
+        let tools = vec![ToolSpec {
+            name: "shell".to_string(),
+            description: "Run a shell command".to_string(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let request = ProviderChatRequest {
+            messages: &messages,
+            tools: Some(&tools),
+            output_schema: None,
+        };
+
+        let events: Vec<StreamEvent> = provider
+            .stream_chat(request, "claude-sonnet-4", 0.0)
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::TextDelta("Let me check.".into()),
+                StreamEvent::ToolCallStart {
+                    index: 1,
+                    id: "toolu_1".into(),
+                    name: "shell".into(),
+                },
+                StreamEvent::ToolCallDelta {
+                    index: 1,
+                    arguments: r#"{"command":"#.into(),
+                },
+                StreamEvent::ToolCallDelta {
+                    index: 1,
+                    arguments: r#""date"}"#.into(),
+                },
+                StreamEvent::ToolCallEnd { index: 1 },
+                StreamEvent::Usage(TokenUsage {
+                    input_tokens: Some(42),
+                    output_tokens: Some(17),
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
+                }),
+                StreamEvent::Finish(Some("tool_use".into())),
+            ]
+        );
+
+        let body = captured
+            .lock()
+            .unwrap()
+            .take()
+            .expect("No request captured");
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["tools"][0]["name"], "shell");
+
+        server_handle.abort();
+    }
+
+    #[test]
+    fn stream_decoder_surfaces_error_events() {
+        let mut decoder = MessageStreamDecoder::default();
+        let err = decoder
+            .decode_line(r#"data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("overloaded_error"));
+    }
 }
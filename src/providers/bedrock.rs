@@ -4,9 +4,12 @@
 //! via environment variables. SigV4 signing is implemented manually
 //! using hmac/sha2 crates — no AWS SDK dependency.
 
+use crate::multimodal;
+use crate::providers::structured;
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, ProviderCapabilities, TokenUsage, ToolCall as ProviderToolCall, ToolsPayload,
+    ContentPart, MediaSource, Provider, ProviderCapabilities, TokenUsage,
+    ToolCall as ProviderToolCall, ToolsPayload,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
@@ -414,6 +417,10 @@ struct BedrockUsage {
     input_tokens: Option<u64>,
     #[serde(default)]
     output_tokens: Option<u64>,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u64>,
+    #[serde(default)]
+    cache_write_input_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -566,7 +573,7 @@ impl BedrockProvider {
                     }
                 }
                 _ => {
-                    let content_blocks = Self::parse_user_content_blocks(&msg.content);
+                    let content_blocks = Self::user_content_blocks(&msg.content_parts());
                     converse_messages.push(ConverseMessage {
                         role: "user".to_string(),
                         content: content_blocks,
@@ -583,79 +590,46 @@ impl BedrockProvider {
         (system, converse_messages)
     }
 
-    /// Parse user message content, extracting [IMAGE:data:...] markers into image blocks.
-    fn parse_user_content_blocks(content: &str) -> Vec<ContentBlock> {
-        let mut blocks: Vec<ContentBlock> = Vec::new();
-        let mut remaining = content;
-        let has_image = content.contains("[IMAGE:");
-        tracing::info!(
-            "parse_user_content_blocks called, len={}, has_image={}",
-            content.len(),
-            has_image
-        );
-
-        while let Some(start) = remaining.find("[IMAGE:") {
-            // Add any text before the marker
-            let text_before = &remaining[..start];
-            if !text_before.trim().is_empty() {
-                blocks.push(ContentBlock::Text(TextBlock {
-                    text: text_before.to_string(),
-                }));
-            }
-
-            let after = &remaining[start + 7..]; // skip "[IMAGE:"
-            if let Some(end) = after.find(']') {
-                let src = &after[..end];
-                remaining = &after[end + 1..];
-
-                // Only handle data URIs (base64 encoded images)
-                if let Some(rest) = src.strip_prefix("data:") {
-                    if let Some(semi) = rest.find(';') {
-                        let mime = &rest[..semi];
-                        let after_semi = &rest[semi + 1..];
-                        if let Some(b64) = after_semi.strip_prefix("base64,") {
-                            let format = match mime {
-                                "image/jpeg" | "image/jpg" => "jpeg",
-                                "image/png" => "png",
-                                "image/gif" => "gif",
-                                "image/webp" => "webp",
-                                _ => "jpeg",
-                            };
-                            blocks.push(ContentBlock::Image(ImageWrapper {
-                                image: ImageBlock {
-                                    format: format.to_string(),
-                                    source: ImageSource {
-                                        bytes: b64.to_string(),
-                                    },
-                                },
-                            }));
-                            continue;
-                        }
-                    }
+    /// Convert user message parts into Converse blocks. Inline images become
+    /// image blocks; anything else is sent as text.
+    fn user_content_blocks(parts: &[ContentPart]) -> Vec<ContentBlock> {
+        let mut blocks: Vec<ContentBlock> = parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Image {
+                    mime_type,
+                    source: MediaSource::Base64(data),
+                } => {
+                    let format = match mime_type.as_deref() {
+                        Some("image/png") => "png",
+                        Some("image/gif") => "gif",
+                        Some("image/webp") => "webp",
+                        _ => "jpeg",
+                    };
+                    ContentBlock::Image(ImageWrapper {
+                        image: ImageBlock {
+                            format: format.to_string(),
+                            source: ImageSource {
+                                bytes: data.clone(),
+                            },
+                        },
+                    })
                 }
-                // Non-data-uri image: just include as text reference
-                blocks.push(ContentBlock::Text(TextBlock {
-                    text: format!("[image: {}]", src),
-                }));
-            } else {
-                // No closing bracket, treat rest as text
-                blocks.push(ContentBlock::Text(TextBlock {
-                    text: remaining.to_string(),
-                }));
-                break;
-            }
-        }
-
-        // Add any remaining text
-        if !remaining.trim().is_empty() {
-            blocks.push(ContentBlock::Text(TextBlock {
-                text: remaining.to_string(),
-            }));
-        }
+                ContentPart::Image {
+                    source: MediaSource::Url(url),
+                    ..
+                } => ContentBlock::Text(TextBlock {
+                    text: format!("[image: {url}]"),
+                }),
+                other => ContentBlock::Text(TextBlock {
+                    text: other.fallback_text(),
+                }),
+            })
+            .collect();
 
         if blocks.is_empty() {
             blocks.push(ContentBlock::Text(TextBlock {
-                text: content.to_string(),
+                text: String::new(),
             }));
         }
 
@@ -746,9 +720,16 @@ impl BedrockProvider {
         let mut text_parts = Vec::new();
         let mut tool_calls = Vec::new();
 
-        let usage = response.usage.map(|u| TokenUsage {
-            input_tokens: u.input_tokens,
-            output_tokens: u.output_tokens,
+        // Converse reports cache tokens outside `inputTokens`.
+        let usage = response.usage.map(|u| {
+            let cached =
+                u.cache_read_input_tokens.unwrap_or(0) + u.cache_write_input_tokens.unwrap_or(0);
+            TokenUsage {
+                input_tokens: u.input_tokens.map(|input| input + cached),
+                output_tokens: u.output_tokens,
+                cache_read_tokens: u.cache_read_input_tokens,
+                cache_write_tokens: u.cache_write_input_tokens,
+            }
         });
 
         if let Some(output) = response.output {
@@ -784,6 +765,7 @@ impl BedrockProvider {
             },
             tool_calls,
             usage,
+            reasoning: None,
         }
     }
 
@@ -883,6 +865,7 @@ impl Provider for BedrockProvider {
         ProviderCapabilities {
             native_tool_calling: true,
             vision: true,
+            structured_output: false,
         }
     }
 
@@ -931,7 +914,7 @@ impl Provider for BedrockProvider {
             system,
             messages: vec![ConverseMessage {
                 role: "user".to_string(),
-                content: Self::parse_user_content_blocks(message),
+                content: Self::user_content_blocks(&multimodal::parts_from_markers(message)),
             }],
             inference_config: Some(InferenceConfig {
                 max_tokens: DEFAULT_MAX_TOKENS,
@@ -957,7 +940,9 @@ impl Provider for BedrockProvider {
     ) -> anyhow::Result<ProviderChatResponse> {
         let credentials = self.resolve_credentials().await?;
 
-        let (system_blocks, mut converse_messages) = Self::convert_messages(request.messages);
+        let schema_messages = structured::prompt_guided_messages(&request);
+        let messages = schema_messages.as_deref().unwrap_or(request.messages);
+        let (system_blocks, mut converse_messages) = Self::convert_messages(messages);
 
         // Apply cachePoint to system if large.
         let system = system_blocks.map(|mut blocks| {
@@ -973,7 +958,7 @@ impl Provider for BedrockProvider {
         });
 
         // Apply cachePoint to last message if conversation is long.
-        if Self::should_cache_conversation(request.messages) {
+        if Self::should_cache_conversation(messages) {
             if let Some(last_msg) = converse_messages.last_mut() {
                 last_msg
                     .content
@@ -1275,6 +1260,26 @@ mod tests {
         assert!(matches!(msgs[0].content[0], ContentBlock::Text(_)));
     }
 
+    #[test]
+    fn convert_messages_user_image_parts_become_image_blocks() {
+        let messages = vec![ChatMessage::with_parts(
+            "user",
+            vec![
+                ContentPart::text("What is this?"),
+                ContentPart::image_base64("image/webp", "abcd"),
+            ],
+        )];
+        let (_, msgs) = BedrockProvider::convert_messages(&messages);
+        assert_eq!(msgs[0].content.len(), 2);
+        match &msgs[0].content[1] {
+            ContentBlock::Image(wrapper) => {
+                assert_eq!(wrapper.image.format, "webp");
+                assert_eq!(wrapper.image.source.bytes, "abcd");
+            }
+            other => panic!("expected image block, got {other:?}"),
+        }
+    }
+
     // ── Cache tests ─────────────────────────────────────────────
 
     #[test]
@@ -1311,6 +1316,7 @@ mod tests {
             messages.push(ChatMessage {
                 role: if i % 2 == 0 { "user" } else { "assistant" }.to_string(),
                 content: format!("Message {i}"),
+                parts: Vec::new(),
             });
         }
         assert!(BedrockProvider::should_cache_conversation(&messages));
@@ -0,0 +1,543 @@
+//! Model catalog: context windows, output limits, capabilities, prices and
+//! deprecation dates per provider model.
+//!
+//! The catalog starts from a bundled default file and is overlaid with the
+//! metadata `zeroclaw models refresh` stores in `state/models_cache.json`
+//! (OpenRouter and OpenAI-compatible `/models`, Gemini `models`, Ollama
+//! `/api/show`). The context manager, cost tracking, the router and
+//! `zeroclaw doctor models` read it; explicit config (`[cost.prices]`,
+//! `[agent] context_window_tokens`, route capability keys) always wins.
+
+use crate::config::schema::ModelPricing;
+use crate::providers::traits::{Provider, ProviderCapabilities};
+use chrono::NaiveDate;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Catalog shipped with the binary.
+const BUNDLED_CATALOG: &str = include_str!("catalog.json");
+
+/// Model cache written by `zeroclaw models refresh`, under `<workspace>/state`.
+const MODEL_CACHE_FILE: &str = "models_cache.json";
+
+/// Metadata for one model. Unknown fields stay `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vision: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<bool>,
+    /// USD per 1M tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+    /// Date the provider retires (or retired) the model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_on: Option<NaiveDate>,
+}
+
+impl ModelInfo {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Replace fields with the ones `other` knows about.
+    fn overlay(&mut self, other: Self) {
+        if other.context_window.is_some() {
+            self.context_window = other.context_window;
+        }
+        if other.max_output_tokens.is_some() {
+            self.max_output_tokens = other.max_output_tokens;
+        }
+        if other.vision.is_some() {
+            self.vision = other.vision;
+        }
+        if other.tools.is_some() {
+            self.tools = other.tools;
+        }
+        if other.reasoning.is_some() {
+            self.reasoning = other.reasoning;
+        }
+        if other.pricing.is_some() {
+            self.pricing = other.pricing;
+        }
+        if other.deprecated_on.is_some() {
+            self.deprecated_on = other.deprecated_on;
+        }
+    }
+
+    /// Narrow provider-level capabilities to what this model supports.
+    pub fn restrict(&self, capabilities: ProviderCapabilities) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_tool_calling: capabilities.native_tool_calling && self.tools != Some(false),
+            vision: capabilities.vision && self.vision != Some(false),
+            ..capabilities
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct CatalogFile {
+    providers: Vec<CatalogFileEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct CatalogFileEntry {
+    provider: String,
+    #[serde(default)]
+    models: Vec<ModelInfo>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ModelCacheState {
+    #[serde(default)]
+    entries: Vec<ModelCacheEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ModelCacheEntry {
+    provider: String,
+    #[serde(default)]
+    models: Vec<String>,
+    #[serde(default)]
+    details: Vec<ModelInfo>,
+}
+
+/// Models grouped by provider name.
+#[derive(Debug, Clone, Default)]
+pub struct ModelCatalog {
+    providers: BTreeMap<String, Vec<ModelInfo>>,
+}
+
+impl ModelCatalog {
+    /// The catalog bundled with the binary.
+    pub fn bundled() -> Self {
+        let file: CatalogFile =
+            serde_json::from_str(BUNDLED_CATALOG).expect("bundled model catalog is valid JSON");
+        let mut catalog = Self::default();
+        for entry in file.providers {
+            catalog.merge(&entry.provider, entry.models);
+        }
+        catalog
+    }
+
+    /// Add or update models for `provider`; known fields in `models`
+    /// replace existing values.
+    pub fn merge(&mut self, provider: &str, models: Vec<ModelInfo>) {
+        let entries = self.providers.entry(provider.to_string()).or_default();
+        for model in models {
+            match entries.iter_mut().find(|entry| entry.id == model.id) {
+                Some(existing) => existing.overlay(model),
+                None => entries.push(model),
+            }
+        }
+    }
+
+    /// Overlay the details stored in the workspace model cache. Entries
+    /// written before details were cached contribute their model IDs.
+    pub fn merge_model_cache(&mut self, raw: &str) {
+        let Ok(state) = serde_json::from_str::<ModelCacheState>(raw) else {
+            return;
+        };
+        for entry in state.entries {
+            let models = if entry.details.is_empty() {
+                entry.models.into_iter().map(ModelInfo::new).collect()
+            } else {
+                entry.details
+            };
+            self.merge(&entry.provider, models);
+        }
+    }
+
+    pub fn has_provider(&self, provider: &str) -> bool {
+        self.providers
+            .get(provider)
+            .is_some_and(|models| !models.is_empty())
+    }
+
+    /// Find a model: first among `provider`'s models (by full ID or the ID
+    /// without a `vendor/` prefix), then across all providers.
+    pub fn lookup(&self, provider: &str, model: &str) -> Option<&ModelInfo> {
+        let model = model.trim();
+        let bare = model.rsplit('/').next().unwrap_or(model);
+        if let Some(info) = self
+            .providers
+            .get(provider)
+            .and_then(|models| models.iter().find(|m| m.id == model || m.id == bare))
+        {
+            return Some(info);
+        }
+
+        let all = || self.providers.values().flatten();
+        all()
+            .find(|m| m.id == model)
+            .or_else(|| all().find(|m| m.id.rsplit('/').next() == Some(bare)))
+    }
+}
+
+static CATALOG: OnceLock<RwLock<ModelCatalog>> = OnceLock::new();
+
+fn catalog() -> &'static RwLock<ModelCatalog> {
+    CATALOG.get_or_init(|| RwLock::new(ModelCatalog::bundled()))
+}
+
+/// Overlay the process-wide catalog with the workspace model cache.
+pub fn load_workspace_cache(workspace_dir: &Path) {
+    let path = workspace_dir.join("state").join(MODEL_CACHE_FILE);
+    if let Ok(raw) = std::fs::read_to_string(path) {
+        catalog().write().merge_model_cache(&raw);
+    }
+}
+
+/// Record freshly fetched models in the process-wide catalog.
+pub fn record_provider_models(provider: &str, models: Vec<ModelInfo>) {
+    catalog().write().merge(provider, models);
+}
+
+/// Look up a model in the process-wide catalog. Pass an empty `provider`
+/// when it is unknown.
+pub fn model_info(provider: &str, model: &str) -> Option<ModelInfo> {
+    catalog().read().lookup(provider, model).cloned()
+}
+
+/// Copy of the process-wide catalog.
+pub fn snapshot() -> ModelCatalog {
+    catalog().read().clone()
+}
+
+/// Capabilities of `provider` narrowed by what the catalog knows about
+/// `model`.
+pub fn capabilities_for(
+    provider_name: &str,
+    model: &str,
+    provider: &dyn Provider,
+) -> ProviderCapabilities {
+    let capabilities = ProviderCapabilities {
+        native_tool_calling: provider.supports_native_tools(),
+        vision: provider.supports_vision(),
+        structured_output: provider.supports_structured_output(),
+    };
+    match model_info(provider_name, model) {
+        Some(info) => info.restrict(capabilities),
+        None => capabilities,
+    }
+}
+
+// ── Listing API parsers ─────────────────────────────────────────
+
+fn value_usize(value: &Value) -> Option<usize> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+        .and_then(|n| usize::try_from(n).ok())
+        .filter(|n| *n > 0)
+}
+
+fn first_usize(model: &Value, pointers: &[&str]) -> Option<usize> {
+    pointers
+        .iter()
+        .find_map(|pointer| model.pointer(pointer).and_then(value_usize))
+}
+
+/// OpenRouter prices are USD per token, as strings.
+fn per_token_price(value: Option<&Value>) -> Option<f64> {
+    let price = match value? {
+        Value::String(s) => s.trim().parse::<f64>().ok()?,
+        other => other.as_f64()?,
+    };
+    (price >= 0.0).then(|| (price * 1_000_000.0 * 1_000_000.0).round() / 1_000_000.0)
+}
+
+fn parse_pricing(model: &Value) -> Option<ModelPricing> {
+    let pricing = model.get("pricing")?;
+    Some(ModelPricing {
+        input: per_token_price(pricing.get("prompt"))?,
+        output: per_token_price(pricing.get("completion"))?,
+        cache_read: per_token_price(pricing.get("input_cache_read")),
+        cache_write: per_token_price(pricing.get("input_cache_write")),
+    })
+}
+
+fn parse_date(value: Option<&Value>) -> Option<NaiveDate> {
+    let raw = value?.as_str()?;
+    NaiveDate::parse_from_str(raw.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn string_list(value: Option<&Value>) -> Option<Vec<&str>> {
+    value?
+        .as_array()
+        .map(|items| items.iter().filter_map(Value::as_str).collect())
+}
+
+fn parse_openai_compatible_model(model: &Value) -> Option<ModelInfo> {
+    let id = model.get("id")?.as_str()?.trim();
+    if id.is_empty() {
+        return None;
+    }
+
+    let input_modalities = string_list(model.pointer("/architecture/input_modalities"));
+    let vision = input_modalities
+        .map(|modalities| modalities.contains(&"image"))
+        .or_else(|| {
+            let modality = model.pointer("/architecture/modality")?.as_str()?;
+            Some(modality.split("->").next()?.contains("image"))
+        });
+    let parameters = string_list(model.get("supported_parameters"));
+
+    Some(ModelInfo {
+        id: id.to_string(),
+        context_window: first_usize(
+            model,
+            &[
+                "/context_length",
+                "/context_window",
+                "/max_model_len",
+                "/max_context_length",
+                "/top_provider/context_length",
+            ],
+        ),
+        max_output_tokens: first_usize(
+            model,
+            &[
+                "/top_provider/max_completion_tokens",
+                "/max_completion_tokens",
+                "/max_output_tokens",
+            ],
+        ),
+        vision,
+        tools: parameters.as_ref().map(|params| params.contains(&"tools")),
+        reasoning: parameters
+            .as_ref()
+            .map(|params| params.contains(&"reasoning") || params.contains(&"include_reasoning")),
+        pricing: parse_pricing(model),
+        deprecated_on: parse_date(
+            model
+                .get("expiration_date")
+                .or_else(|| model.get("deprecation_date")),
+        ),
+    })
+}
+
+/// Parse an OpenAI-compatible `/models` payload, including the richer
+/// OpenRouter fields (context length, modalities, supported parameters,
+/// per-token pricing, expiration date).
+pub fn parse_openai_compatible_models(payload: &Value) -> Vec<ModelInfo> {
+    let Some(data) = payload
+        .get("data")
+        .and_then(Value::as_array)
+        .or_else(|| payload.as_array())
+    else {
+        return Vec::new();
+    };
+    data.iter()
+        .filter_map(parse_openai_compatible_model)
+        .collect()
+}
+
+/// Parse a Gemini `models` payload, keeping models that support
+/// `generateContent`.
+pub fn parse_gemini_models(payload: &Value) -> Vec<ModelInfo> {
+    let Some(models) = payload.get("models").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+
+    models
+        .iter()
+        .filter(|model| {
+            string_list(model.get("supportedGenerationMethods"))
+                .is_none_or(|methods| methods.contains(&"generateContent"))
+        })
+        .filter_map(|model| {
+            let name = model.get("name")?.as_str()?;
+            Some(ModelInfo {
+                context_window: first_usize(model, &["/inputTokenLimit"]),
+                max_output_tokens: first_usize(model, &["/outputTokenLimit"]),
+                reasoning: model.get("thinking").and_then(Value::as_bool),
+                ..ModelInfo::new(name.trim_start_matches("models/"))
+            })
+        })
+        .collect()
+}
+
+/// Parse an Ollama `/api/show` payload for `name`.
+pub fn parse_ollama_show(name: &str, payload: &Value) -> ModelInfo {
+    let context_window = payload
+        .get("model_info")
+        .and_then(Value::as_object)
+        .and_then(|info| {
+            info.iter()
+                .find(|(key, _)| key.ends_with(".context_length"))
+                .and_then(|(_, value)| value_usize(value))
+        });
+    let capabilities = string_list(payload.get("capabilities"));
+
+    ModelInfo {
+        context_window,
+        vision: capabilities.as_ref().map(|caps| caps.contains(&"vision")),
+        tools: capabilities.as_ref().map(|caps| caps.contains(&"tools")),
+        reasoning: capabilities.as_ref().map(|caps| caps.contains(&"thinking")),
+        ..ModelInfo::new(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bundled_catalog_parses_and_resolves_prefixed_ids() {
+        let catalog = ModelCatalog::bundled();
+        assert!(catalog.has_provider("anthropic"));
+
+        let info = catalog
+            .lookup("openrouter", "anthropic/claude-sonnet-4-20250514")
+            .expect("found by bare id");
+        assert_eq!(info.context_window, Some(200_000));
+        assert_eq!(info.pricing.as_ref().map(|p| p.input), Some(3.0));
+
+        assert!(catalog.lookup("openai", "my-custom-model").is_none());
+    }
+
+    #[test]
+    fn merge_overlays_known_fields_and_keeps_the_rest() {
+        let mut catalog = ModelCatalog::bundled();
+        catalog.merge(
+            "openai",
+            vec![ModelInfo {
+                context_window: Some(64_000),
+                ..ModelInfo::new("gpt-4o")
+            }],
+        );
+
+        let info = catalog.lookup("openai", "gpt-4o").unwrap();
+        assert_eq!(info.context_window, Some(64_000));
+        assert_eq!(info.vision, Some(true));
+        assert!(info.pricing.is_some());
+    }
+
+    #[test]
+    fn merge_model_cache_reads_details_and_legacy_ids() {
+        let mut catalog = ModelCatalog::default();
+        catalog.merge_model_cache(
+            &json!({
+                "entries": [
+                    {
+                        "provider": "ollama",
+                        "fetched_at_unix": 1,
+                        "models": ["llava:7b"],
+                        "details": [{ "id": "llava:7b", "context_window": 4096, "vision": true }]
+                    },
+                    { "provider": "groq", "fetched_at_unix": 1, "models": ["llama-3.3-70b"] }
+                ]
+            })
+            .to_string(),
+        );
+
+        let llava = catalog.lookup("ollama", "llava:7b").unwrap();
+        assert_eq!(llava.context_window, Some(4096));
+        assert_eq!(llava.vision, Some(true));
+        assert!(catalog.lookup("groq", "llama-3.3-70b").is_some());
+    }
+
+    #[test]
+    fn parse_openrouter_models_reads_limits_capabilities_and_prices() {
+        let payload = json!({
+            "data": [
+                {
+                    "id": "openai/gpt-4o",
+                    "context_length": 128000,
+                    "architecture": { "input_modalities": ["text", "image"] },
+                    "top_provider": { "max_completion_tokens": 16384 },
+                    "supported_parameters": ["tools", "temperature"],
+                    "pricing": { "prompt": "0.0000025", "completion": "0.00001" },
+                    "expiration_date": "2026-03-01"
+                },
+                {
+                    "id": "openrouter/auto",
+                    "pricing": { "prompt": "-1", "completion": "-1" }
+                }
+            ]
+        });
+
+        let models = parse_openai_compatible_models(&payload);
+        assert_eq!(models.len(), 2);
+
+        let gpt = &models[0];
+        assert_eq!(gpt.context_window, Some(128_000));
+        assert_eq!(gpt.max_output_tokens, Some(16_384));
+        assert_eq!(gpt.vision, Some(true));
+        assert_eq!(gpt.tools, Some(true));
+        assert_eq!(gpt.reasoning, Some(false));
+        let pricing = gpt.pricing.as_ref().unwrap();
+        assert!((pricing.input - 2.5).abs() < 1e-9);
+        assert!((pricing.output - 10.0).abs() < 1e-9);
+        assert_eq!(gpt.deprecated_on, NaiveDate::from_ymd_opt(2026, 3, 1));
+
+        assert_eq!(models[1], ModelInfo::new("openrouter/auto"));
+    }
+
+    #[test]
+    fn parse_gemini_and_ollama_details() {
+        let gemini = parse_gemini_models(&json!({
+            "models": [
+                {
+                    "name": "models/gemini-2.5-flash",
+                    "inputTokenLimit": 1048576,
+                    "outputTokenLimit": 65536,
+                    "thinking": true,
+                    "supportedGenerationMethods": ["generateContent"]
+                },
+                { "name": "models/text-embedding-004", "supportedGenerationMethods": ["embedContent"] }
+            ]
+        }));
+        assert_eq!(gemini.len(), 1);
+        assert_eq!(gemini[0].id, "gemini-2.5-flash");
+        assert_eq!(gemini[0].context_window, Some(1_048_576));
+        assert_eq!(gemini[0].reasoning, Some(true));
+
+        let ollama = parse_ollama_show(
+            "qwen3:8b",
+            &json!({
+                "model_info": { "general.architecture": "qwen3", "qwen3.context_length": 40960 },
+                "capabilities": ["completion", "tools", "thinking"]
+            }),
+        );
+        assert_eq!(ollama.context_window, Some(40_960));
+        assert_eq!(ollama.tools, Some(true));
+        assert_eq!(ollama.vision, Some(false));
+        assert_eq!(ollama.reasoning, Some(true));
+    }
+
+    #[test]
+    fn restrict_only_narrows_capabilities() {
+        let caps = ProviderCapabilities {
+            native_tool_calling: true,
+            vision: true,
+            structured_output: true,
+        };
+        let info = ModelInfo {
+            vision: Some(false),
+            ..ModelInfo::new("deepseek-chat")
+        };
+
+        let restricted = info.restrict(caps.clone());
+        assert!(!restricted.vision);
+        assert!(restricted.native_tool_calling);
+        assert!(restricted.structured_output);
+        assert_eq!(ModelInfo::new("unknown").restrict(caps.clone()), caps);
+    }
+}
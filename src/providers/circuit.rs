@@ -0,0 +1,391 @@
+//! Circuit breakers and rolling health scores for provider/model pairs.
+//!
+//! [`ReliableProvider`](super::reliable::ReliableProvider) consults a
+//! [`HealthBoard`] before each attempt: pairs whose breaker is open are skipped
+//! until their cooldown elapses, then a single half-open probe decides whether
+//! the breaker closes again. Error-rate and latency scores reorder the fallback
+//! chain so a degraded primary stops taxing every request with its full
+//! retry/backoff budget. State is published to [`crate::health`] for `/health`,
+//! Prometheus and `zeroclaw doctor`.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Weight of the newest sample in the rolling error rate and latency.
+const EWMA_ALPHA: f64 = 0.3;
+/// Idle time after which a recorded error rate has halved.
+const ERROR_RATE_HALF_LIFE_SECS: f64 = 120.0;
+/// Latency samples older than this no longer affect ordering.
+const LATENCY_FRESH_SECS: u64 = 600;
+/// Latency differences below one bucket do not reorder providers.
+const LATENCY_BUCKET_MS: f64 = 5_000.0;
+
+/// Breaker and ordering settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitConfig {
+    /// Consecutive failures that open a breaker; `0` disables breakers.
+    pub failure_threshold: u32,
+    /// How long an open breaker rejects calls before allowing a probe.
+    pub open_duration: Duration,
+    /// Reorder the fallback chain by health score.
+    pub reorder: bool,
+}
+
+impl Default for CircuitConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+            reorder: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Self::Closed => 0,
+            Self::HalfOpen => 1,
+            Self::Open => 2,
+        }
+    }
+}
+
+/// Whether a call to a provider/model pair may go ahead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Allowed,
+    /// The breaker is open (or a probe is already out); retry after the delay.
+    Rejected {
+        retry_in: Duration,
+    },
+}
+
+#[derive(Debug)]
+struct Entry {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<(Instant, DateTime<Utc>)>,
+    probe_started: Option<Instant>,
+    error_rate: f64,
+    latency_ms: Option<f64>,
+    updated: Instant,
+}
+
+impl Entry {
+    fn new() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            probe_started: None,
+            error_rate: 0.0,
+            latency_ms: None,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Error rate decayed by idle time, so a demoted provider earns another try.
+    fn current_error_rate(&self) -> f64 {
+        let idle = self.updated.elapsed().as_secs_f64();
+        self.error_rate * 0.5_f64.powf(idle / ERROR_RATE_HALF_LIFE_SECS)
+    }
+
+    fn sort_key(&self) -> (u8, u8, u64) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let error_bucket = (self.current_error_rate() * 4.0).floor().min(4.0) as u8;
+        let latency_bucket = match self.latency_ms {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Some(ms) if self.updated.elapsed().as_secs() < LATENCY_FRESH_SECS => {
+                (ms / LATENCY_BUCKET_MS) as u64
+            }
+            _ => 0,
+        };
+        (self.state.rank(), error_bucket, latency_bucket)
+    }
+}
+
+/// Health state of every provider/model pair one `ReliableProvider` has called.
+#[derive(Debug)]
+pub struct HealthBoard {
+    config: CircuitConfig,
+    entries: Mutex<HashMap<(String, String), Entry>>,
+}
+
+impl Default for HealthBoard {
+    fn default() -> Self {
+        Self::new(CircuitConfig::default())
+    }
+}
+
+impl HealthBoard {
+    pub fn new(config: CircuitConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Decide whether `provider`/`model` may be called now. Moves an open
+    /// breaker to half-open once its cooldown has passed and lets exactly one
+    /// probe through per cooldown window.
+    pub fn admit(&self, provider: &str, model: &str) -> Admission {
+        if self.config.failure_threshold == 0 {
+            return Admission::Allowed;
+        }
+        let mut entries = self.entries.lock();
+        let Some(entry) = entries.get_mut(&(provider.to_string(), model.to_string())) else {
+            return Admission::Allowed;
+        };
+
+        let cooldown = self.config.open_duration;
+        match entry.state {
+            CircuitState::Closed => Admission::Allowed,
+            CircuitState::Open => {
+                let elapsed = entry.opened_at.map_or(cooldown, |(at, _)| at.elapsed());
+                if elapsed < cooldown {
+                    return Admission::Rejected {
+                        retry_in: cooldown.saturating_sub(elapsed),
+                    };
+                }
+                entry.state = CircuitState::HalfOpen;
+                entry.probe_started = Some(Instant::now());
+                publish(provider, model, entry);
+                Admission::Allowed
+            }
+            CircuitState::HalfOpen => match entry.probe_started {
+                Some(started) if started.elapsed() < cooldown => Admission::Rejected {
+                    retry_in: cooldown.saturating_sub(started.elapsed()),
+                },
+                _ => {
+                    entry.probe_started = Some(Instant::now());
+                    Admission::Allowed
+                }
+            },
+        }
+    }
+
+    pub fn record_success(&self, provider: &str, model: &str, latency: Duration) {
+        let mut entries = self.entries.lock();
+        let entry = entries
+            .entry((provider.to_string(), model.to_string()))
+            .or_insert_with(Entry::new);
+
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        entry.latency_ms = Some(match entry.latency_ms {
+            Some(prev) => prev + EWMA_ALPHA * (latency_ms - prev),
+            None => latency_ms,
+        });
+        entry.error_rate = entry.current_error_rate() * (1.0 - EWMA_ALPHA);
+        entry.consecutive_failures = 0;
+        if entry.state != CircuitState::Closed {
+            tracing::info!(provider, model, "Circuit closed after successful probe");
+        }
+        entry.state = CircuitState::Closed;
+        entry.opened_at = None;
+        entry.probe_started = None;
+        entry.updated = Instant::now();
+        publish(provider, model, entry);
+    }
+
+    pub fn record_failure(&self, provider: &str, model: &str) {
+        let mut entries = self.entries.lock();
+        let entry = entries
+            .entry((provider.to_string(), model.to_string()))
+            .or_insert_with(Entry::new);
+
+        entry.error_rate = entry.current_error_rate() * (1.0 - EWMA_ALPHA) + EWMA_ALPHA;
+        entry.consecutive_failures = entry.consecutive_failures.saturating_add(1);
+        entry.updated = Instant::now();
+
+        let threshold = self.config.failure_threshold;
+        let trips = match entry.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => threshold > 0 && entry.consecutive_failures >= threshold,
+            CircuitState::Open => false,
+        };
+        if trips {
+            tracing::warn!(
+                provider,
+                model,
+                consecutive_failures = entry.consecutive_failures,
+                open_secs = self.config.open_duration.as_secs(),
+                "Circuit opened"
+            );
+            entry.state = CircuitState::Open;
+            entry.opened_at = Some((Instant::now(), Utc::now()));
+            entry.probe_started = None;
+        }
+        publish(provider, model, entry);
+    }
+
+    pub fn state(&self, provider: &str, model: &str) -> CircuitState {
+        self.entries
+            .lock()
+            .get(&(provider.to_string(), model.to_string()))
+            .map_or(CircuitState::Closed, |entry| entry.state)
+    }
+
+    /// `items` in the order they should be tried for `model`: healthy pairs
+    /// keep their configured order, degraded and open ones move back.
+    pub fn order<'a, T>(&self, model: &str, items: &'a [(String, T)]) -> Vec<&'a (String, T)> {
+        let mut ordered: Vec<&(String, T)> = items.iter().collect();
+        if !self.config.reorder {
+            return ordered;
+        }
+        let entries = self.entries.lock();
+        ordered.sort_by_key(|(name, _)| {
+            entries
+                .get(&(name.clone(), model.to_string()))
+                .map_or((0, 0, 0), Entry::sort_key)
+        });
+        ordered
+    }
+}
+
+fn publish(provider: &str, model: &str, entry: &Entry) {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    crate::health::record_provider_health(crate::health::ProviderHealth {
+        provider: provider.to_string(),
+        model: model.to_string(),
+        circuit: entry.state.as_str().to_string(),
+        consecutive_failures: entry.consecutive_failures,
+        error_rate: entry.error_rate,
+        latency_ms: entry.latency_ms.map(|ms| ms.round() as u64),
+        opened_at: entry.opened_at.map(|(_, at)| at.to_rfc3339()),
+        updated_at: String::new(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(threshold: u32, open_duration: Duration) -> HealthBoard {
+        HealthBoard::new(CircuitConfig {
+            failure_threshold: threshold,
+            open_duration,
+            reorder: true,
+        })
+    }
+
+    #[test]
+    fn opens_after_threshold_and_rejects_until_cooldown() {
+        let board = board(2, Duration::from_secs(60));
+        board.record_failure("circuit-a", "m");
+        assert_eq!(board.admit("circuit-a", "m"), Admission::Allowed);
+        board.record_failure("circuit-a", "m");
+
+        assert_eq!(board.state("circuit-a", "m"), CircuitState::Open);
+        assert!(matches!(
+            board.admit("circuit-a", "m"),
+            Admission::Rejected { .. }
+        ));
+        // Other models on the same provider are unaffected.
+        assert_eq!(board.admit("circuit-a", "other"), Admission::Allowed);
+    }
+
+    #[test]
+    fn half_open_allows_one_probe_and_closes_on_success() {
+        let board = board(1, Duration::from_millis(50));
+        board.record_failure("circuit-b", "m");
+        std::thread::sleep(Duration::from_millis(70));
+
+        assert_eq!(board.admit("circuit-b", "m"), Admission::Allowed);
+        assert_eq!(board.state("circuit-b", "m"), CircuitState::HalfOpen);
+        assert!(matches!(
+            board.admit("circuit-b", "m"),
+            Admission::Rejected { .. }
+        ));
+
+        board.record_success("circuit-b", "m", Duration::from_millis(5));
+        assert_eq!(board.state("circuit-b", "m"), CircuitState::Closed);
+        assert_eq!(board.admit("circuit-b", "m"), Admission::Allowed);
+    }
+
+    #[test]
+    fn failed_probe_reopens_breaker() {
+        let board = board(1, Duration::from_millis(50));
+        board.record_failure("circuit-c", "m");
+        std::thread::sleep(Duration::from_millis(70));
+        assert_eq!(board.admit("circuit-c", "m"), Admission::Allowed);
+
+        board.record_failure("circuit-c", "m");
+        assert_eq!(board.state("circuit-c", "m"), CircuitState::Open);
+        assert!(matches!(
+            board.admit("circuit-c", "m"),
+            Admission::Rejected { .. }
+        ));
+    }
+
+    #[test]
+    fn zero_threshold_disables_breaker() {
+        let board = board(0, Duration::from_secs(60));
+        for _ in 0..10 {
+            board.record_failure("circuit-d", "m");
+        }
+        assert_eq!(board.state("circuit-d", "m"), CircuitState::Closed);
+        assert_eq!(board.admit("circuit-d", "m"), Admission::Allowed);
+    }
+
+    #[test]
+    fn order_demotes_failing_and_slow_providers_but_keeps_healthy_order() {
+        let board = board(10, Duration::from_secs(60));
+        let items = vec![
+            ("circuit-primary".to_string(), ()),
+            ("circuit-second".to_string(), ()),
+            ("circuit-third".to_string(), ()),
+        ];
+        let names = |board: &HealthBoard| -> Vec<String> {
+            board
+                .order("m", &items)
+                .into_iter()
+                .map(|(name, _)| name.clone())
+                .collect()
+        };
+
+        board.record_success("circuit-second", "m", Duration::from_millis(200));
+        board.record_success("circuit-primary", "m", Duration::from_millis(900));
+        assert_eq!(
+            names(&board),
+            ["circuit-primary", "circuit-second", "circuit-third"]
+        );
+
+        board.record_failure("circuit-primary", "m");
+        board.record_failure("circuit-primary", "m");
+        board.record_success("circuit-third", "m", Duration::from_secs(12));
+        assert_eq!(
+            names(&board),
+            ["circuit-second", "circuit-third", "circuit-primary"]
+        );
+    }
+
+    #[test]
+    fn state_is_published_to_health_registry() {
+        let board = board(1, Duration::from_secs(60));
+        board.record_failure("circuit-published", "m");
+
+        let snapshot = crate::health::snapshot();
+        let entry = &snapshot.providers["circuit-published/m"];
+        assert_eq!(entry.circuit, "open");
+        assert_eq!(entry.consecutive_failures, 1);
+        assert!(entry.opened_at.is_some());
+    }
+}
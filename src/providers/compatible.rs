@@ -2,10 +2,12 @@
 //! Most LLM APIs follow the same `/v1/chat/completions` format.
 //! This module provides a single implementation that works for all of them.
 
+use crate::providers::sse::{self, OpenAiChunkDecoder};
+use crate::providers::structured;
 use crate::providers::traits::{
-    ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, StreamChunk, StreamError, StreamOptions, StreamResult, TokenUsage,
-    ToolCall as ProviderToolCall,
+    chat_response_stream, ChatMessage, ChatRequest as ProviderChatRequest,
+    ChatResponse as ProviderChatResponse, Provider, Reasoning, StreamChunk, StreamError,
+    StreamEvent, StreamOptions, StreamResult, TokenUsage, ToolCall as ProviderToolCall,
 };
 use async_trait::async_trait;
 use futures_util::{stream, StreamExt};
@@ -283,6 +285,15 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// Cached prefix tokens, reported as a subset of `prompt_tokens`.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -314,6 +325,28 @@ fn strip_think_tags(s: &str) -> String {
     result.trim().to_string()
 }
 
+/// Collect the bodies of `<think>...</think>` blocks that
+/// [`strip_think_tags`] removes, so they can be kept as reasoning.
+fn think_tag_contents(s: &str) -> String {
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("<think>") {
+        let body = &rest[start + "<think>".len()..];
+        match body.find("</think>") {
+            Some(end) => {
+                parts.push(body[..end].trim());
+                rest = &body[end + "</think>".len()..];
+            }
+            None => {
+                parts.push(body.trim());
+                break;
+            }
+        }
+    }
+    parts.retain(|p| !p.is_empty());
+    parts.join("\n\n")
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct ResponseMessage {
     #[serde(default)]
@@ -347,6 +380,30 @@ impl ResponseMessage {
             .unwrap_or_default()
     }
 
+    /// Reasoning kept apart from the answer: `reasoning_content` when the
+    /// answer is in `content`, plus any inline `<think>` blocks.
+    fn reasoning(&self) -> Option<Reasoning> {
+        let content = self.content.as_deref().unwrap_or_default();
+        let mut text = think_tag_contents(content);
+        if !strip_think_tags(content).is_empty() {
+            if let Some(reasoning) = self
+                .reasoning_content
+                .as_deref()
+                .map(str::trim)
+                .filter(|r| !r.is_empty())
+            {
+                if !text.is_empty() {
+                    text.push_str("\n\n");
+                }
+                text.push_str(reasoning);
+            }
+        }
+        (!text.is_empty()).then(|| Reasoning {
+            text,
+            signature: None,
+        })
+    }
+
     fn effective_content_optional(&self) -> Option<String> {
         if let Some(content) = self.content.as_ref().filter(|c| !c.is_empty()) {
             let stripped = strip_think_tags(content);
@@ -910,6 +967,7 @@ impl OpenAiCompatibleProvider {
 
     fn parse_native_response(message: ResponseMessage) -> ProviderChatResponse {
         let text = message.effective_content_optional();
+        let reasoning = message.reasoning();
         let tool_calls = message
             .tool_calls
             .unwrap_or_default()
@@ -940,6 +998,7 @@ impl OpenAiCompatibleProvider {
             text,
             tool_calls,
             usage: None,
+            reasoning,
         }
     }
 
@@ -971,6 +1030,7 @@ impl Provider for OpenAiCompatibleProvider {
         crate::providers::traits::ProviderCapabilities {
             native_tool_calling: true,
             vision: false,
+            structured_output: false,
         }
     }
 
@@ -1270,6 +1330,7 @@ impl Provider for OpenAiCompatibleProvider {
                     text: Some(text),
                     tool_calls: vec![],
                     usage: None,
+                    reasoning: None,
                 });
             }
         };
@@ -1283,6 +1344,8 @@ impl Provider for OpenAiCompatibleProvider {
         let usage = chat_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            cache_write_tokens: None,
         });
         let choice = chat_response
             .choices
@@ -1291,6 +1354,7 @@ impl Provider for OpenAiCompatibleProvider {
             .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))?;
 
         let text = choice.message.effective_content_optional();
+        let reasoning = choice.message.reasoning();
         let tool_calls = choice
             .message
             .tool_calls
@@ -1312,6 +1376,7 @@ impl Provider for OpenAiCompatibleProvider {
             text,
             tool_calls,
             usage,
+            reasoning,
         })
     }
 
@@ -1329,10 +1394,12 @@ impl Provider for OpenAiCompatibleProvider {
         })?;
 
         let tools = Self::convert_tool_specs(request.tools);
+        let schema_messages = structured::prompt_guided_messages(&request);
+        let messages = schema_messages.as_deref().unwrap_or(request.messages);
         let effective_messages = if self.merge_system_into_user {
-            Self::flatten_system_messages(request.messages)
+            Self::flatten_system_messages(messages)
         } else {
-            request.messages.to_vec()
+            messages.to_vec()
         };
         let native_request = NativeChatRequest {
             model: model.to_string(),
@@ -1363,6 +1430,7 @@ impl Provider for OpenAiCompatibleProvider {
                             text: Some(text),
                             tool_calls: vec![],
                             usage: None,
+                            reasoning: None,
                         })
                         .map_err(|responses_err| {
                             anyhow::anyhow!(
@@ -1383,7 +1451,7 @@ impl Provider for OpenAiCompatibleProvider {
 
             if Self::is_native_tool_schema_unsupported(status, &sanitized) {
                 let fallback_messages =
-                    Self::with_prompt_guided_tool_instructions(request.messages, request.tools);
+                    Self::with_prompt_guided_tool_instructions(messages, request.tools);
                 let text = self
                     .chat_with_history(&fallback_messages, model, temperature)
                     .await?;
@@ -1391,6 +1459,7 @@ impl Provider for OpenAiCompatibleProvider {
                     text: Some(text),
                     tool_calls: vec![],
                     usage: None,
+                    reasoning: None,
                 });
             }
 
@@ -1402,6 +1471,7 @@ impl Provider for OpenAiCompatibleProvider {
                         text: Some(text),
                         tool_calls: vec![],
                         usage: None,
+                        reasoning: None,
                     })
                     .map_err(|responses_err| {
                         anyhow::anyhow!(
@@ -1418,6 +1488,8 @@ impl Provider for OpenAiCompatibleProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -1543,6 +1615,81 @@ impl Provider for OpenAiCompatibleProvider {
         .boxed()
     }
 
+    fn stream_chat<'a>(
+        &'a self,
+        request: ProviderChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+    ) -> stream::BoxStream<'a, StreamResult<StreamEvent>> {
+        let model = model.to_string();
+        sse::open_stream(async move {
+            let credential = self.credential.as_ref().ok_or_else(|| {
+                StreamError::Provider(format!(
+                    "{} API key not set. Run `zeroclaw onboard` or set the appropriate env var.",
+                    self.name
+                ))
+            })?;
+
+            let tools = Self::convert_tool_specs(request.tools);
+            let effective_messages = if self.merge_system_into_user {
+                Self::flatten_system_messages(request.messages)
+            } else {
+                request.messages.to_vec()
+            };
+            let native_request = NativeChatRequest {
+                model: model.clone(),
+                messages: Self::convert_messages_for_native(&effective_messages),
+                temperature,
+                stream: Some(true),
+                tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+                tools,
+            };
+
+            let url = self.chat_completions_url();
+            let sent = self
+                .apply_auth_header(
+                    self.http_client().post(&url).json(&native_request),
+                    credential,
+                )
+                .header("Accept", "text/event-stream")
+                .send()
+                .await;
+
+            // The Responses and prompt-guided fallbacks are not streamed;
+            // replay them through `chat`, which owns that logic.
+            let replay = move || {
+                chat_response_stream(async move { self.chat(request, &model, temperature).await })
+            };
+
+            let response = match sent {
+                Ok(response) => response,
+                Err(_) if self.supports_responses_fallback => return Ok(replay()),
+                Err(e) => return Err(StreamError::Http(e)),
+            };
+
+            let status = response.status();
+            if !status.is_success() {
+                let error = response.text().await.unwrap_or_default();
+                let sanitized = super::sanitize_api_error(&error);
+                if Self::is_native_tool_schema_unsupported(status, &sanitized)
+                    || (status == reqwest::StatusCode::NOT_FOUND
+                        && self.supports_responses_fallback)
+                {
+                    return Ok(replay());
+                }
+                return Err(StreamError::Provider(format!(
+                    "{} API error ({status}): {sanitized}",
+                    self.name
+                )));
+            }
+
+            Ok(sse::decode_response(
+                response,
+                OpenAiChunkDecoder::default(),
+            ))
+        })
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         if let Some(credential) = self.credential.as_ref() {
             // Hit the chat completions URL with a GET to establish the connection pool.
@@ -2292,6 +2439,7 @@ mod tests {
         let messages = vec![ChatMessage {
             role: "user".to_string(),
             content: "hello".to_string(),
+            parts: Vec::new(),
         }];
         let tools = vec![serde_json::json!({
             "type": "function",
@@ -2373,6 +2521,22 @@ mod tests {
     // Reasoning model fallback tests (reasoning_content)
     // ----------------------------------------------------------
 
+    #[test]
+    fn reasoning_kept_apart_when_content_present() {
+        let json = r#"{"choices":[{"message":{"content":"<think>plan</think>Answer","reasoning_content":"more thought"}}]}"#;
+        let resp: ApiChatResponse = serde_json::from_str(json).unwrap();
+        let msg = &resp.choices[0].message;
+        assert_eq!(msg.effective_content(), "Answer");
+        assert_eq!(msg.reasoning().unwrap().text, "plan\n\nmore thought");
+    }
+
+    #[test]
+    fn reasoning_content_used_as_answer_is_not_repeated_as_reasoning() {
+        let json = r#"{"choices":[{"message":{"content":"","reasoning_content":"Only output"}}]}"#;
+        let resp: ApiChatResponse = serde_json::from_str(json).unwrap();
+        assert!(resp.choices[0].message.reasoning().is_none());
+    }
+
     #[test]
     fn reasoning_content_fallback_when_content_empty() {
         // Reasoning models (Qwen3, GLM-4) return content: "" with reasoning_content populated
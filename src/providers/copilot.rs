@@ -11,6 +11,7 @@
 //! GitHub could change or revoke this at any time, which would break all
 //! third-party integrations simultaneously.
 
+use crate::providers::structured;
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
     Provider, TokenUsage, ToolCall as ProviderToolCall,
@@ -144,6 +145,15 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// Cached prefix tokens, reported as a subset of `prompt_tokens`.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -353,6 +363,8 @@ impl CopilotProvider {
         let usage = api_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            cache_write_tokens: None,
         });
         let choice = api_response
             .choices
@@ -378,6 +390,7 @@ impl CopilotProvider {
             text: choice.message.content,
             tool_calls,
             usage,
+            reasoning: None,
         })
     }
 
@@ -644,8 +657,9 @@ impl Provider for CopilotProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ProviderChatResponse> {
+        let schema_messages = structured::prompt_guided_messages(&request);
         self.send_chat_request(
-            Self::convert_messages(request.messages),
+            Self::convert_messages(schema_messages.as_deref().unwrap_or(request.messages)),
             request.tools,
             model,
             temperature,
@@ -3,7 +3,11 @@
 //! - Gemini CLI OAuth tokens (reuse existing ~/.gemini/ authentication)
 //! - Google Cloud ADC (`GOOGLE_APPLICATION_CREDENTIALS`)
 
-use crate::providers::traits::{ChatMessage, ChatResponse, Provider, TokenUsage};
+use crate::providers::structured::{self, OutputSchema};
+use crate::providers::traits::{
+    ChatMessage, ChatResponse, ContentPart, MediaSource, Provider, ProviderCapabilities, Reasoning,
+    TokenUsage,
+};
 use async_trait::async_trait;
 use directories::UserDirs;
 use reqwest::Client;
@@ -125,8 +129,34 @@ struct Content {
 }
 
 #[derive(Debug, Serialize, Clone)]
-struct Part {
-    text: String,
+#[serde(untagged)]
+enum Part {
+    Text {
+        text: String,
+    },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: Blob,
+    },
+    FileData {
+        #[serde(rename = "fileData")]
+        file_data: FileData,
+    },
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct Blob {
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct FileData {
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    mime_type: Option<String>,
+    #[serde(rename = "fileUri")]
+    file_uri: String,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -134,6 +164,10 @@ struct GenerationConfig {
     temperature: f64,
     #[serde(rename = "maxOutputTokens")]
     max_output_tokens: u32,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(rename = "responseSchema", skip_serializing_if = "Option::is_none")]
+    response_schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -152,6 +186,8 @@ struct GeminiUsageMetadata {
     prompt_token_count: Option<u64>,
     #[serde(default, rename = "candidatesTokenCount")]
     candidates_token_count: Option<u64>,
+    #[serde(default, rename = "cachedContentTokenCount")]
+    cached_content_token_count: Option<u64>,
 }
 
 /// Response envelope for the internal cloudcode-pa API.
@@ -191,12 +227,12 @@ impl CandidateContent {
     ///
     /// Returns the non-thinking text, falling back to thinking text only when
     /// no non-thinking content is available.
-    fn effective_text(self) -> Option<String> {
+    fn effective_text(&self) -> Option<String> {
         let mut answer_parts: Vec<String> = Vec::new();
         let mut first_thinking: Option<String> = None;
 
-        for part in self.parts {
-            if let Some(text) = part.text {
+        for part in &self.parts {
+            if let Some(text) = part.text.clone() {
                 if text.is_empty() {
                     continue;
                 }
@@ -214,6 +250,27 @@ impl CandidateContent {
             Some(answer_parts.join(""))
         }
     }
+
+    /// Join the `thought: true` parts. Empty when the answer itself fell
+    /// back to thinking text, so nothing is shown twice.
+    fn reasoning(&self) -> Option<Reasoning> {
+        let mut thoughts = Vec::new();
+        let mut has_answer = false;
+        for part in &self.parts {
+            match part.text.as_deref() {
+                Some(text) if !text.is_empty() && part.thought => thoughts.push(text),
+                Some(text) if !text.is_empty() => has_answer = true,
+                _ => {}
+            }
+        }
+        if thoughts.is_empty() || !has_answer {
+            return None;
+        }
+        Some(Reasoning {
+            text: thoughts.join(""),
+            signature: None,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -787,13 +844,50 @@ impl GeminiProvider {
 }
 
 impl GeminiProvider {
+    /// User message content as Gemini parts: inline media becomes
+    /// `inlineData`, URLs become `fileData`.
+    fn user_parts(message: &ChatMessage) -> Vec<Part> {
+        if !message.has_media() {
+            return vec![Part::Text {
+                text: message.content.clone(),
+            }];
+        }
+
+        message
+            .content_parts()
+            .into_iter()
+            .map(|part| match part {
+                ContentPart::Image { mime_type, source }
+                | ContentPart::Audio { mime_type, source } => match source {
+                    MediaSource::Base64(data) => Part::InlineData {
+                        inline_data: Blob {
+                            mime_type: mime_type
+                                .unwrap_or_else(|| "application/octet-stream".to_string()),
+                            data,
+                        },
+                    },
+                    MediaSource::Url(file_uri) => Part::FileData {
+                        file_data: FileData {
+                            mime_type,
+                            file_uri,
+                        },
+                    },
+                },
+                other => Part::Text {
+                    text: other.fallback_text(),
+                },
+            })
+            .collect()
+    }
+
     async fn send_generate_content(
         &self,
         contents: Vec<Content>,
         system_instruction: Option<Content>,
         model: &str,
         temperature: f64,
-    ) -> anyhow::Result<(String, Option<TokenUsage>)> {
+        output_schema: Option<&OutputSchema>,
+    ) -> anyhow::Result<(String, Option<Reasoning>, Option<TokenUsage>)> {
         let auth = self.auth.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "Gemini API key not found. Options:\n\
@@ -824,6 +918,9 @@ impl GeminiProvider {
             generation_config: GenerationConfig {
                 temperature,
                 max_output_tokens: 8192,
+                response_mime_type: output_schema.map(|_| "application/json".to_string()),
+                response_schema: output_schema
+                    .map(|schema| structured::gemini_response_schema(&schema.schema)),
             },
         };
 
@@ -938,21 +1035,34 @@ impl GeminiProvider {
         let usage = result.usage_metadata.map(|u| TokenUsage {
             input_tokens: u.prompt_token_count,
             output_tokens: u.candidates_token_count,
+            cache_read_tokens: u.cached_content_token_count,
+            cache_write_tokens: None,
         });
 
-        let text = result
+        let content = result
             .candidates
             .and_then(|c| c.into_iter().next())
-            .and_then(|c| c.content)
-            .and_then(|c| c.effective_text())
+            .and_then(|c| c.content);
+        let text = content
+            .as_ref()
+            .and_then(CandidateContent::effective_text)
             .ok_or_else(|| anyhow::anyhow!("No response from Gemini"))?;
+        let reasoning = content.as_ref().and_then(CandidateContent::reasoning);
 
-        Ok((text, usage))
+        Ok((text, reasoning, usage))
     }
 }
 
 #[async_trait]
 impl Provider for GeminiProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_tool_calling: false,
+            vision: true,
+            structured_output: true,
+        }
+    }
+
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
@@ -962,20 +1072,20 @@ impl Provider for GeminiProvider {
     ) -> anyhow::Result<String> {
         let system_instruction = system_prompt.map(|sys| Content {
             role: None,
-            parts: vec![Part {
+            parts: vec![Part::Text {
                 text: sys.to_string(),
             }],
         });
 
         let contents = vec![Content {
             role: Some("user".to_string()),
-            parts: vec![Part {
+            parts: vec![Part::Text {
                 text: message.to_string(),
             }],
         }];
 
-        let (text, _usage) = self
-            .send_generate_content(contents, system_instruction, model, temperature)
+        let (text, _reasoning, _usage) = self
+            .send_generate_content(contents, system_instruction, model, temperature, None)
             .await?;
         Ok(text)
     }
@@ -997,16 +1107,14 @@ impl Provider for GeminiProvider {
                 "user" => {
                     contents.push(Content {
                         role: Some("user".to_string()),
-                        parts: vec![Part {
-                            text: msg.content.clone(),
-                        }],
+                        parts: Self::user_parts(msg),
                     });
                 }
                 "assistant" => {
                     // Gemini API uses "model" role instead of "assistant"
                     contents.push(Content {
                         role: Some("model".to_string()),
-                        parts: vec![Part {
+                        parts: vec![Part::Text {
                             text: msg.content.clone(),
                         }],
                     });
@@ -1020,14 +1128,14 @@ impl Provider for GeminiProvider {
         } else {
             Some(Content {
                 role: None,
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: system_parts.join("\n\n"),
                 }],
             })
         };
 
-        let (text, _usage) = self
-            .send_generate_content(contents, system_instruction, model, temperature)
+        let (text, _reasoning, _usage) = self
+            .send_generate_content(contents, system_instruction, model, temperature, None)
             .await?;
         Ok(text)
     }
@@ -1046,13 +1154,11 @@ impl Provider for GeminiProvider {
                 "system" => system_parts.push(&msg.content),
                 "user" => contents.push(Content {
                     role: Some("user".to_string()),
-                    parts: vec![Part {
-                        text: msg.content.clone(),
-                    }],
+                    parts: Self::user_parts(msg),
                 }),
                 "assistant" => contents.push(Content {
                     role: Some("model".to_string()),
-                    parts: vec![Part {
+                    parts: vec![Part::Text {
                         text: msg.content.clone(),
                     }],
                 }),
@@ -1065,20 +1171,27 @@ impl Provider for GeminiProvider {
         } else {
             Some(Content {
                 role: None,
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: system_parts.join("\n\n"),
                 }],
             })
         };
 
-        let (text, usage) = self
-            .send_generate_content(contents, system_instruction, model, temperature)
+        let (text, reasoning, usage) = self
+            .send_generate_content(
+                contents,
+                system_instruction,
+                model,
+                temperature,
+                request.output_schema,
+            )
             .await?;
 
         Ok(ChatResponse {
             text: Some(text),
             tool_calls: Vec::new(),
             usage,
+            reasoning,
         })
     }
 
@@ -1247,7 +1360,7 @@ mod tests {
         let body = GenerateContentRequest {
             contents: vec![Content {
                 role: Some("user".into()),
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "hello".into(),
                 }],
             }],
@@ -1255,6 +1368,8 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
         };
 
@@ -1288,7 +1403,7 @@ mod tests {
         let body = GenerateContentRequest {
             contents: vec![Content {
                 role: Some("user".into()),
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "hello".into(),
                 }],
             }],
@@ -1296,6 +1411,8 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
         };
 
@@ -1332,7 +1449,7 @@ mod tests {
         let body = GenerateContentRequest {
             contents: vec![Content {
                 role: Some("user".into()),
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "hello".into(),
                 }],
             }],
@@ -1340,6 +1457,8 @@ mod tests {
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
         };
 
@@ -1364,19 +1483,21 @@ mod tests {
         let request = GenerateContentRequest {
             contents: vec![Content {
                 role: Some("user".to_string()),
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "Hello".to_string(),
                 }],
             }],
             system_instruction: Some(Content {
                 role: None,
-                parts: vec![Part {
+                parts: vec![Part::Text {
                     text: "You are helpful".to_string(),
                 }],
             }),
             generation_config: GenerationConfig {
                 temperature: 0.7,
                 max_output_tokens: 8192,
+                response_mime_type: None,
+                response_schema: None,
             },
         };
 
@@ -1398,7 +1519,7 @@ mod tests {
             request: InternalGenerateContentRequest {
                 contents: vec![Content {
                     role: Some("user".to_string()),
-                    parts: vec![Part {
+                    parts: vec![Part::Text {
                         text: "Hello".to_string(),
                     }],
                 }],
@@ -1406,6 +1527,8 @@ mod tests {
                 generation_config: Some(GenerationConfig {
                     temperature: 0.7,
                     max_output_tokens: 8192,
+                    response_mime_type: None,
+                    response_schema: None,
                 }),
             },
         };
@@ -1430,7 +1553,7 @@ mod tests {
             request: InternalGenerateContentRequest {
                 contents: vec![Content {
                     role: Some("user".to_string()),
-                    parts: vec![Part {
+                    parts: vec![Part::Text {
                         text: "Hello".to_string(),
                     }],
                 }],
@@ -1453,7 +1576,7 @@ mod tests {
             request: InternalGenerateContentRequest {
                 contents: vec![Content {
                     role: Some("user".to_string()),
-                    parts: vec![Part {
+                    parts: vec![Part::Text {
                         text: "Hello".to_string(),
                     }],
                 }],
@@ -1466,6 +1589,34 @@ mod tests {
         assert!(json.contains("\"project\":\"my-gcp-project-id\""));
     }
 
+    #[test]
+    fn user_parts_serialize_media_as_inline_and_file_data() {
+        let message = ChatMessage::with_parts(
+            "user",
+            vec![
+                ContentPart::text("Transcribe and describe"),
+                ContentPart::Audio {
+                    mime_type: Some("audio/wav".into()),
+                    source: MediaSource::Base64("UklGRg==".into()),
+                },
+                ContentPart::Image {
+                    mime_type: Some("image/png".into()),
+                    source: MediaSource::Url("gs://bucket/cat.png".into()),
+                },
+            ],
+        );
+
+        let json = serde_json::to_value(GeminiProvider::user_parts(&message)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"text": "Transcribe and describe"},
+                {"inlineData": {"mimeType": "audio/wav", "data": "UklGRg=="}},
+                {"fileData": {"mimeType": "image/png", "fileUri": "gs://bucket/cat.png"}}
+            ])
+        );
+    }
+
     #[test]
     fn internal_response_deserialize_nested() {
         let json = r#"{
@@ -1633,8 +1784,15 @@ mod tests {
 
         let response: GenerateContentResponse = serde_json::from_str(json).unwrap();
         let candidate = response.candidates.unwrap().into_iter().next().unwrap();
-        let text = candidate.content.unwrap().effective_text();
-        assert_eq!(text, Some("The answer is 42.".to_string()));
+        let content = candidate.content.unwrap();
+        assert_eq!(
+            content.effective_text(),
+            Some("The answer is 42.".to_string())
+        );
+        assert_eq!(
+            content.reasoning().unwrap().text,
+            "Let me think about this..."
+        );
     }
 
     #[test]
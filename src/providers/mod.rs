@@ -18,6 +18,8 @@
 
 pub mod anthropic;
 pub mod bedrock;
+pub mod catalog;
+pub mod circuit;
 pub mod compatible;
 pub mod copilot;
 pub mod gemini;
@@ -26,13 +28,20 @@ pub mod openai;
 pub mod openai_codex;
 pub mod openrouter;
 pub mod reliable;
+pub mod replay;
 pub mod router;
+pub mod scripted;
+pub(crate) mod sse;
+pub mod structured;
 pub mod traits;
 
+#[allow(unused_imports)]
+pub use structured::OutputSchema;
 #[allow(unused_imports)]
 pub use traits::{
-    ChatMessage, ChatRequest, ChatResponse, ConversationMessage, Provider, ProviderCapabilityError,
-    ToolCall, ToolResultMessage,
+    ChatMessage, ChatRequest, ChatResponse, ContentPart, ContextWindowExceededError,
+    ConversationMessage, MediaSource, Provider, ProviderCapabilityError, Reasoning, ToolCall,
+    ToolResultMessage,
 };
 
 use compatible::{AuthStyle, OpenAiCompatibleProvider};
@@ -684,6 +693,7 @@ pub struct ProviderRuntimeOptions {
     pub zeroclaw_dir: Option<PathBuf>,
     pub secrets_encrypt: bool,
     pub reasoning_enabled: Option<bool>,
+    pub reasoning_budget_tokens: Option<u32>,
 }
 
 impl Default for ProviderRuntimeOptions {
@@ -693,10 +703,20 @@ impl Default for ProviderRuntimeOptions {
             zeroclaw_dir: None,
             secrets_encrypt: true,
             reasoning_enabled: None,
+            reasoning_budget_tokens: None,
         }
     }
 }
 
+impl ProviderRuntimeOptions {
+    /// Thinking budget for providers that take one; an explicit
+    /// `reasoning_enabled = false` turns it off.
+    fn thinking_budget_tokens(&self) -> Option<u32> {
+        self.reasoning_budget_tokens
+            .filter(|_| self.reasoning_enabled != Some(false))
+    }
+}
+
 fn is_secret_char(c: char) -> bool {
     c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':')
 }
@@ -960,7 +980,10 @@ fn create_provider_with_url_and_options(
     match name {
         // ── Primary providers (custom implementations) ───────
         "openrouter" => Ok(Box::new(openrouter::OpenRouterProvider::new(key))),
-        "anthropic" => Ok(Box::new(anthropic::AnthropicProvider::new(key))),
+        "anthropic" => Ok(Box::new(
+            anthropic::AnthropicProvider::new(key)
+                .with_thinking_budget(options.thinking_budget_tokens()),
+        )),
         "openai" => Ok(Box::new(openai::OpenAiProvider::with_base_url(api_url, key))),
         // Ollama uses api_url for custom base URL (e.g. remote Ollama instance)
         "ollama" => Ok(Box::new(ollama::OllamaProvider::new_with_reasoning(
@@ -1163,6 +1186,21 @@ fn create_provider_with_url_and_options(
             )))
         }
 
+        // ── Offline providers for deterministic runs ────────
+        // Format: "replay:<cassette.jsonl>" or "scripted:<script.yaml|json>"
+        name if name.starts_with("replay:") => {
+            let path = name.strip_prefix("replay:").unwrap_or("").trim();
+            Ok(Box::new(replay::ReplayProvider::from_file(
+                std::path::Path::new(path),
+            )?))
+        }
+        name if name.starts_with("scripted:") => {
+            let path = name.strip_prefix("scripted:").unwrap_or("").trim();
+            Ok(Box::new(scripted::ScriptedProvider::from_file(
+                std::path::Path::new(path),
+            )?))
+        }
+
         // ── Anthropic-compatible custom endpoints ───────────
         // Format: "anthropic-custom:https://your-api.com"
         name if name.starts_with("anthropic-custom:") => {
@@ -1171,10 +1209,10 @@ fn create_provider_with_url_and_options(
                 "Anthropic-custom provider",
                 "anthropic-custom:https://your-api.com",
             )?;
-            Ok(Box::new(anthropic::AnthropicProvider::with_base_url(
-                key,
-                Some(&base_url),
-            )))
+            Ok(Box::new(
+                anthropic::AnthropicProvider::with_base_url(key, Some(&base_url))
+                    .with_thinking_budget(options.thinking_budget_tokens()),
+            ))
         }
 
         _ => anyhow::bail!(
@@ -1190,9 +1228,13 @@ fn create_provider_with_url_and_options(
 /// Returns `(provider_name, Some(profile))` when the entry contains a colon-
 /// delimited profile, or `(original_str, None)` otherwise.  Entries starting
 /// with `custom:` or `anthropic-custom:` are left untouched because the colon
-/// is part of the URL scheme.
+/// is part of the URL scheme, as are `replay:` / `scripted:` file paths.
 fn parse_provider_profile(s: &str) -> (&str, Option<&str>) {
-    if s.starts_with("custom:") || s.starts_with("anthropic-custom:") {
+    if s.starts_with("custom:")
+        || s.starts_with("anthropic-custom:")
+        || s.starts_with("replay:")
+        || s.starts_with("scripted:")
+    {
         return (s, None);
     }
     match s.split_once(':') {
@@ -1277,9 +1319,14 @@ pub fn create_resilient_provider_with_options(
         reliability.provider_backoff_ms,
     )
     .with_api_keys(reliability.api_keys.clone())
-    .with_model_fallbacks(reliability.model_fallbacks.clone());
+    .with_model_fallbacks(reliability.model_fallbacks.clone())
+    .with_circuit_config(circuit::CircuitConfig {
+        failure_threshold: reliability.circuit_failure_threshold,
+        open_duration: std::time::Duration::from_secs(reliability.circuit_open_secs),
+        reorder: reliability.health_ordering,
+    });
 
-    Ok(Box::new(reliable))
+    Ok(replay::RecordingProvider::wrap_from_env(Box::new(reliable)))
 }
 
 /// Create a RouterProvider if model routes are configured, otherwise return a
@@ -1301,10 +1348,12 @@ pub fn create_routed_provider(
         model_routes,
         default_model,
         &ProviderRuntimeOptions::default(),
+        router::RoutingOptions::default(),
     )
 }
 
-/// Create a routed provider using explicit runtime options.
+/// Create a routed provider using explicit runtime options and routing policies.
+#[allow(clippy::too_many_arguments)]
 pub fn create_routed_provider_with_options(
     primary_name: &str,
     api_key: Option<&str>,
@@ -1313,6 +1362,7 @@ pub fn create_routed_provider_with_options(
     model_routes: &[crate::config::ModelRouteConfig],
     default_model: &str,
     options: &ProviderRuntimeOptions,
+    routing: router::RoutingOptions,
 ) -> anyhow::Result<Box<dyn Provider>> {
     if model_routes.is_empty() {
         return create_resilient_provider_with_options(
@@ -1370,16 +1420,18 @@ pub fn create_routed_provider_with_options(
                 router::Route {
                     provider_name: r.provider.clone(),
                     model: r.model.clone(),
+                    context_window: r.context_window,
+                    vision: r.vision,
+                    native_tools: r.native_tools,
                 },
             )
         })
         .collect();
 
-    Ok(Box::new(router::RouterProvider::new(
-        providers,
-        routes,
-        default_model.to_string(),
-    )))
+    Ok(Box::new(
+        router::RouterProvider::new(providers, routes, default_model.to_string())
+            .with_routing(routing),
+    ))
 }
 
 /// Information about a supported provider for display purposes.
@@ -2280,6 +2332,9 @@ mod tests {
             ],
             api_keys: Vec::new(),
             model_fallbacks: std::collections::HashMap::new(),
+            circuit_failure_threshold: 5,
+            circuit_open_secs: 30,
+            health_ordering: true,
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
@@ -2319,6 +2374,9 @@ mod tests {
             fallback_providers: vec!["lmstudio".into(), "ollama".into()],
             api_keys: Vec::new(),
             model_fallbacks: std::collections::HashMap::new(),
+            circuit_failure_threshold: 5,
+            circuit_open_secs: 30,
+            health_ordering: true,
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
@@ -2341,6 +2399,9 @@ mod tests {
             fallback_providers: vec!["custom:http://host.docker.internal:1234/v1".into()],
             api_keys: Vec::new(),
             model_fallbacks: std::collections::HashMap::new(),
+            circuit_failure_threshold: 5,
+            circuit_open_secs: 30,
+            health_ordering: true,
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
@@ -2367,6 +2428,9 @@ mod tests {
             ],
             api_keys: Vec::new(),
             model_fallbacks: std::collections::HashMap::new(),
+            circuit_failure_threshold: 5,
+            circuit_open_secs: 30,
+            health_ordering: true,
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
@@ -2669,6 +2733,9 @@ mod tests {
             fallback_providers: vec!["openai-codex:second".into()],
             api_keys: Vec::new(),
             model_fallbacks: std::collections::HashMap::new(),
+            circuit_failure_threshold: 5,
+            circuit_open_secs: 30,
+            health_ordering: true,
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
@@ -2698,6 +2765,9 @@ mod tests {
             ],
             api_keys: Vec::new(),
             model_fallbacks: std::collections::HashMap::new(),
+            circuit_failure_threshold: 5,
+            circuit_open_secs: 30,
+            health_ordering: true,
             channel_initial_backoff_secs: 2,
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
@@ -1,8 +1,12 @@
 use crate::multimodal;
+use crate::providers::sse::{self, StreamDecoder};
 use crate::providers::traits::{
-    ChatMessage, ChatResponse, Provider, ProviderCapabilities, TokenUsage, ToolCall,
+    ChatMessage, ChatResponse, ContentPart, MediaSource, Provider, ProviderCapabilities, Reasoning,
+    StreamError, StreamEvent, StreamResult, TokenUsage, ToolCall,
 };
+use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use futures_util::stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -25,6 +29,9 @@ struct ChatRequest {
     think: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<serde_json::Value>>,
+    /// JSON schema constraining the reply (structured outputs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -69,6 +76,23 @@ struct ApiChatResponse {
     eval_count: Option<u64>,
 }
 
+/// One line of a streamed (`"stream": true`) `/api/chat` response.
+#[derive(Debug, Deserialize)]
+struct ApiChatChunk {
+    #[serde(default)]
+    message: Option<ResponseMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ResponseMessage {
     #[serde(default)]
@@ -80,6 +104,18 @@ struct ResponseMessage {
     thinking: Option<String>,
 }
 
+impl ResponseMessage {
+    fn reasoning(&self) -> Option<Reasoning> {
+        self.thinking
+            .as_ref()
+            .filter(|t| !t.trim().is_empty())
+            .map(|text| Reasoning {
+                text: text.clone(),
+                signature: None,
+            })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct OllamaToolCall {
     id: Option<String>,
@@ -93,6 +129,72 @@ struct OllamaFunction {
     arguments: serde_json::Value,
 }
 
+/// Decoder for Ollama's newline-delimited JSON chat stream. Tool calls arrive
+/// whole, so each one is emitted as a start/arguments/end triple.
+struct ChatStreamDecoder<'a> {
+    provider: &'a OllamaProvider,
+    next_tool_index: usize,
+    finished: bool,
+}
+
+impl StreamDecoder for ChatStreamDecoder<'_> {
+    fn decode_line(&mut self, line: &str) -> StreamResult<Vec<StreamEvent>> {
+        let chunk: ApiChatChunk = serde_json::from_str(line).map_err(StreamError::Json)?;
+        if let Some(error) = chunk.error {
+            return Err(StreamError::Provider(format!(
+                "Ollama stream error: {}",
+                super::sanitize_api_error(&error)
+            )));
+        }
+
+        let mut events = Vec::new();
+        if let Some(message) = chunk.message {
+            if let Some(thinking) = message.thinking.filter(|t| !t.is_empty()) {
+                events.push(StreamEvent::ReasoningDelta(thinking));
+            }
+            if !message.content.is_empty() {
+                events.push(StreamEvent::TextDelta(message.content));
+            }
+            for call in &message.tool_calls {
+                let (name, args) = self.provider.extract_tool_name_and_args(call);
+                let index = self.next_tool_index;
+                self.next_tool_index += 1;
+                events.push(StreamEvent::ToolCallStart {
+                    index,
+                    id: call.id.clone().unwrap_or_default(),
+                    name,
+                });
+                events.push(StreamEvent::ToolCallDelta {
+                    index,
+                    arguments: serde_json::to_string(&args).unwrap_or_else(|_| "{}".to_string()),
+                });
+                events.push(StreamEvent::ToolCallEnd { index });
+            }
+        }
+
+        if chunk.done && !self.finished {
+            self.finished = true;
+            if chunk.prompt_eval_count.is_some() || chunk.eval_count.is_some() {
+                events.push(StreamEvent::Usage(TokenUsage {
+                    input_tokens: chunk.prompt_eval_count,
+                    output_tokens: chunk.eval_count,
+                    ..TokenUsage::default()
+                }));
+            }
+            events.push(StreamEvent::Finish(chunk.done_reason));
+        }
+        Ok(events)
+    }
+
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = true;
+        vec![StreamEvent::Finish(None)]
+    }
+}
+
 // ─── Implementation ───────────────────────────────────────────────────────────
 
 impl OllamaProvider {
@@ -168,6 +270,22 @@ impl OllamaProvider {
         serde_json::from_str(arguments).unwrap_or_else(|_| serde_json::json!({}))
     }
 
+    fn tool_specs_to_json(specs: &[ToolSpec]) -> Vec<serde_json::Value> {
+        specs
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "type": "function",
+                    "function": {
+                        "name": s.name,
+                        "description": s.description,
+                        "parameters": s.parameters
+                    }
+                })
+            })
+            .collect()
+    }
+
     fn build_chat_request(
         &self,
         messages: Vec<Message>,
@@ -182,25 +300,36 @@ impl OllamaProvider {
             options: Options { temperature },
             think: self.reasoning_enabled,
             tools: tools.map(|t| t.to_vec()),
+            format: None,
         }
     }
 
-    fn convert_user_message_content(&self, content: &str) -> (Option<String>, Option<Vec<String>>) {
-        let (cleaned, image_refs) = multimodal::parse_image_markers(content);
-        if image_refs.is_empty() {
-            return (Some(content.to_string()), None);
+    fn convert_user_message_content(
+        &self,
+        parts: &[ContentPart],
+    ) -> (Option<String>, Option<Vec<String>>) {
+        let mut text = Vec::new();
+        let mut images = Vec::new();
+        for part in parts {
+            match part {
+                ContentPart::Image {
+                    source: MediaSource::Base64(data),
+                    ..
+                } => images.push(data.trim().to_string()),
+                ContentPart::Image {
+                    source: MediaSource::Url(reference),
+                    ..
+                } => images.extend(multimodal::extract_ollama_image_payload(reference)),
+                other => text.push(other.fallback_text()),
+            }
         }
 
-        let images: Vec<String> = image_refs
-            .iter()
-            .filter_map(|reference| multimodal::extract_ollama_image_payload(reference))
-            .collect();
-
+        let text = text.join("\n\n");
         if images.is_empty() {
-            return (Some(content.to_string()), None);
+            return (Some(text), None);
         }
 
-        let cleaned = cleaned.trim();
+        let cleaned = text.trim();
         let content = if cleaned.is_empty() {
             None
         } else {
@@ -291,7 +420,8 @@ impl OllamaProvider {
                 }
 
                 if message.role == "user" {
-                    let (content, images) = self.convert_user_message_content(&message.content);
+                    let (content, images) =
+                        self.convert_user_message_content(&message.content_parts());
                     return Message {
                         role: "user".to_string(),
                         content,
@@ -313,7 +443,8 @@ impl OllamaProvider {
     }
 
     /// Send a request to Ollama and get the parsed response.
-    /// Pass `tools` to enable native function-calling for models that support it.
+    /// Pass `tools` to enable native function-calling for models that support it,
+    /// and `format` to constrain the reply to a JSON schema.
     async fn send_request(
         &self,
         messages: Vec<Message>,
@@ -321,8 +452,10 @@ impl OllamaProvider {
         temperature: f64,
         should_auth: bool,
         tools: Option<&[serde_json::Value]>,
+        format: Option<&serde_json::Value>,
     ) -> anyhow::Result<ApiChatResponse> {
-        let request = self.build_chat_request(messages, model, temperature, tools);
+        let mut request = self.build_chat_request(messages, model, temperature, tools);
+        request.format = format.cloned();
 
         let url = format!("{}/api/chat", self.base_url);
 
@@ -382,6 +515,107 @@ impl OllamaProvider {
         Ok(chat_response)
     }
 
+    /// Native `/api/chat` call returning structured tool calls, optionally
+    /// constrained to a JSON schema via `format`.
+    async fn chat_native(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        format: Option<&serde_json::Value>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let (normalized_model, should_auth) = self.resolve_request_details(model)?;
+
+        let api_messages = self.convert_messages(messages);
+
+        // Tools arrive pre-formatted in OpenAI/Ollama-compatible JSON from
+        // tools_to_openai_format() in loop_.rs — pass them through directly.
+        let tools_opt = if tools.is_empty() { None } else { Some(tools) };
+
+        let response = self
+            .send_request(
+                api_messages,
+                &normalized_model,
+                temperature,
+                should_auth,
+                tools_opt,
+                format,
+            )
+            .await?;
+
+        let usage = if response.prompt_eval_count.is_some() || response.eval_count.is_some() {
+            Some(TokenUsage {
+                input_tokens: response.prompt_eval_count,
+                output_tokens: response.eval_count,
+                ..TokenUsage::default()
+            })
+        } else {
+            None
+        };
+
+        let reasoning = response.message.reasoning();
+
+        // Native tool calls returned by the model.
+        if !response.message.tool_calls.is_empty() {
+            let tool_calls: Vec<ToolCall> = response
+                .message
+                .tool_calls
+                .iter()
+                .map(|tc| {
+                    let (name, args) = self.extract_tool_name_and_args(tc);
+                    ToolCall {
+                        id: tc
+                            .id
+                            .clone()
+                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                        name,
+                        arguments: serde_json::to_string(&args)
+                            .unwrap_or_else(|_| "{}".to_string()),
+                    }
+                })
+                .collect();
+            let text = if response.message.content.is_empty() {
+                None
+            } else {
+                Some(response.message.content)
+            };
+            return Ok(ChatResponse {
+                text,
+                tool_calls,
+                usage,
+                reasoning,
+            });
+        }
+
+        // Plain text response.
+        let content = response.message.content;
+        if content.is_empty() {
+            if let Some(thinking) = &response.message.thinking {
+                tracing::warn!(
+                    "Ollama returned empty content with only thinking: '{}'. Model may have stopped prematurely.",
+                    if thinking.len() > 100 { &thinking[..100] } else { thinking }
+                );
+                return Ok(ChatResponse {
+                    text: Some(format!(
+                        "I was thinking about this: {}... but I didn't complete my response. Could you try asking again?",
+                        if thinking.len() > 200 { &thinking[..200] } else { thinking }
+                    )),
+                    tool_calls: vec![],
+                    usage,
+                    reasoning: None,
+                });
+            }
+            tracing::warn!("Ollama returned empty content with no tool calls");
+        }
+        Ok(ChatResponse {
+            text: Some(content),
+            tool_calls: vec![],
+            usage,
+            reasoning,
+        })
+    }
+
     /// Convert Ollama tool calls to the JSON format expected by parse_tool_calls in loop_.rs
     ///
     /// Handles quirky model behavior where tool calls are wrapped:
@@ -460,6 +694,7 @@ impl Provider for OllamaProvider {
         ProviderCapabilities {
             native_tool_calling: true,
             vision: true,
+            structured_output: true,
         }
     }
 
@@ -484,7 +719,8 @@ impl Provider for OllamaProvider {
             });
         }
 
-        let (user_content, user_images) = self.convert_user_message_content(message);
+        let (user_content, user_images) =
+            self.convert_user_message_content(&multimodal::parts_from_markers(message));
         messages.push(Message {
             role: "user".to_string(),
             content: user_content,
@@ -494,7 +730,14 @@ impl Provider for OllamaProvider {
         });
 
         let response = self
-            .send_request(messages, &normalized_model, temperature, should_auth, None)
+            .send_request(
+                messages,
+                &normalized_model,
+                temperature,
+                should_auth,
+                None,
+                None,
+            )
             .await?;
 
         // If model returned tool calls, format them for loop_.rs's parse_tool_calls
@@ -544,6 +787,7 @@ impl Provider for OllamaProvider {
                 temperature,
                 should_auth,
                 None,
+                None,
             )
             .await?;
 
@@ -586,88 +830,8 @@ impl Provider for OllamaProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (normalized_model, should_auth) = self.resolve_request_details(model)?;
-
-        let api_messages = self.convert_messages(messages);
-
-        // Tools arrive pre-formatted in OpenAI/Ollama-compatible JSON from
-        // tools_to_openai_format() in loop_.rs — pass them through directly.
-        let tools_opt = if tools.is_empty() { None } else { Some(tools) };
-
-        let response = self
-            .send_request(
-                api_messages,
-                &normalized_model,
-                temperature,
-                should_auth,
-                tools_opt,
-            )
-            .await?;
-
-        let usage = if response.prompt_eval_count.is_some() || response.eval_count.is_some() {
-            Some(TokenUsage {
-                input_tokens: response.prompt_eval_count,
-                output_tokens: response.eval_count,
-            })
-        } else {
-            None
-        };
-
-        // Native tool calls returned by the model.
-        if !response.message.tool_calls.is_empty() {
-            let tool_calls: Vec<ToolCall> = response
-                .message
-                .tool_calls
-                .iter()
-                .map(|tc| {
-                    let (name, args) = self.extract_tool_name_and_args(tc);
-                    ToolCall {
-                        id: tc
-                            .id
-                            .clone()
-                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
-                        name,
-                        arguments: serde_json::to_string(&args)
-                            .unwrap_or_else(|_| "{}".to_string()),
-                    }
-                })
-                .collect();
-            let text = if response.message.content.is_empty() {
-                None
-            } else {
-                Some(response.message.content)
-            };
-            return Ok(ChatResponse {
-                text,
-                tool_calls,
-                usage,
-            });
-        }
-
-        // Plain text response.
-        let content = response.message.content;
-        if content.is_empty() {
-            if let Some(thinking) = &response.message.thinking {
-                tracing::warn!(
-                    "Ollama returned empty content with only thinking: '{}'. Model may have stopped prematurely.",
-                    if thinking.len() > 100 { &thinking[..100] } else { thinking }
-                );
-                return Ok(ChatResponse {
-                    text: Some(format!(
-                        "I was thinking about this: {}... but I didn't complete my response. Could you try asking again?",
-                        if thinking.len() > 200 { &thinking[..200] } else { thinking }
-                    )),
-                    tool_calls: vec![],
-                    usage,
-                });
-            }
-            tracing::warn!("Ollama returned empty content with no tool calls");
-        }
-        Ok(ChatResponse {
-            text: Some(content),
-            tool_calls: vec![],
-            usage,
-        })
+        self.chat_native(messages, tools, None, model, temperature)
+            .await
     }
 
     fn supports_native_tools(&self) -> bool {
@@ -683,26 +847,22 @@ impl Provider for OllamaProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        // Convert ToolSpec to OpenAI-compatible JSON and delegate to chat_with_tools.
-        if let Some(specs) = request.tools {
-            if !specs.is_empty() {
-                let tools: Vec<serde_json::Value> = specs
-                    .iter()
-                    .map(|s| {
-                        serde_json::json!({
-                            "type": "function",
-                            "function": {
-                                "name": s.name,
-                                "description": s.description,
-                                "parameters": s.parameters
-                            }
-                        })
-                    })
-                    .collect();
-                return self
-                    .chat_with_tools(request.messages, &tools, model, temperature)
-                    .await;
-            }
+        // Convert ToolSpec to OpenAI-compatible JSON and use the native call
+        // whenever tools or an output schema are present.
+        let tools = request
+            .tools
+            .filter(|specs| !specs.is_empty())
+            .map(Self::tool_specs_to_json);
+        if tools.is_some() || request.output_schema.is_some() {
+            return self
+                .chat_native(
+                    request.messages,
+                    tools.as_deref().unwrap_or_default(),
+                    request.output_schema.map(|schema| &schema.schema),
+                    model,
+                    temperature,
+                )
+                .await;
         }
 
         // No tools — fall back to plain text chat.
@@ -713,6 +873,64 @@ impl Provider for OllamaProvider {
             text: Some(text),
             tool_calls: vec![],
             usage: None,
+            reasoning: None,
+        })
+    }
+
+    fn stream_chat<'a>(
+        &'a self,
+        request: crate::providers::traits::ChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+    ) -> stream::BoxStream<'a, StreamResult<StreamEvent>> {
+        let prepared =
+            self.resolve_request_details(model)
+                .map(|(normalized_model, should_auth)| {
+                    let tools = request
+                        .tools
+                        .filter(|specs| !specs.is_empty())
+                        .map(Self::tool_specs_to_json);
+                    let mut chat_request = self.build_chat_request(
+                        self.convert_messages(request.messages),
+                        &normalized_model,
+                        temperature,
+                        tools.as_deref(),
+                    );
+                    chat_request.stream = true;
+                    chat_request.format = request.output_schema.map(|s| s.schema.clone());
+                    (chat_request, should_auth)
+                });
+
+        sse::open_stream(async move {
+            let (chat_request, should_auth) =
+                prepared.map_err(|e| StreamError::Provider(e.to_string()))?;
+
+            let url = format!("{}/api/chat", self.base_url);
+            let mut request_builder = self.http_client().post(&url).json(&chat_request);
+            if should_auth {
+                if let Some(key) = self.api_key.as_ref() {
+                    request_builder = request_builder.bearer_auth(key);
+                }
+            }
+
+            let response = request_builder.send().await.map_err(StreamError::Http)?;
+            let status = response.status();
+            if !status.is_success() {
+                let raw = response.text().await.unwrap_or_default();
+                let sanitized = super::sanitize_api_error(&raw);
+                return Err(StreamError::Provider(format!(
+                    "Ollama API error ({status}): {sanitized}. Is Ollama running? (brew install ollama && ollama serve)"
+                )));
+            }
+
+            Ok(sse::decode_response(
+                response,
+                ChatStreamDecoder {
+                    provider: self,
+                    next_tool_index: 0,
+                    finished: false,
+                },
+            ))
         })
     }
 }
@@ -872,6 +1090,7 @@ mod tests {
             r#"{"message":{"role":"assistant","content":"hello","thinking":"internal reasoning"}}"#;
         let resp: ApiChatResponse = serde_json::from_str(json).unwrap();
         assert_eq!(resp.message.content, "hello");
+        assert_eq!(resp.message.reasoning().unwrap().text, "internal reasoning");
     }
 
     #[test]
@@ -961,6 +1180,7 @@ mod tests {
         let messages = vec![ChatMessage {
             role: "assistant".into(),
             content: r#"{"content":null,"tool_calls":[{"id":"call_1","name":"shell","arguments":"{\"command\":\"ls\"}"}]}"#.into(),
+            parts: Vec::new(),
         }];
 
         let converted = provider.convert_messages(&messages);
@@ -985,10 +1205,12 @@ mod tests {
             ChatMessage {
                 role: "assistant".into(),
                 content: r#"{"content":null,"tool_calls":[{"id":"call_7","name":"file_read","arguments":"{\"path\":\"README.md\"}"}]}"#.into(),
+                parts: Vec::new(),
             },
             ChatMessage {
                 role: "tool".into(),
                 content: r#"{"tool_call_id":"call_7","content":"ok"}"#.into(),
+                parts: Vec::new(),
             },
         ];
 
@@ -1007,6 +1229,7 @@ mod tests {
         let messages = vec![ChatMessage {
             role: "user".into(),
             content: "Inspect this screenshot [IMAGE:data:image/png;base64,abcd==]".into(),
+            parts: Vec::new(),
         }];
 
         let converted = provider.convert_messages(&messages);
@@ -1050,4 +1273,66 @@ mod tests {
         assert!(resp.prompt_eval_count.is_none());
         assert!(resp.eval_count.is_none());
     }
+
+    #[test]
+    fn stream_decoder_emits_text_tool_calls_and_usage() {
+        let provider = OllamaProvider::new(None, None);
+        let mut decoder = ChatStreamDecoder {
+            provider: &provider,
+            next_tool_index: 0,
+            finished: false,
+        };
+
+        let mut events = Vec::new();
+        for line in [
+            r#"{"message":{"role":"assistant","content":"Let me"},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":" check."},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"tool.shell","arguments":{"command":"date"}}}]},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":30,"eval_count":12}"#,
+        ] {
+            events.extend(decoder.decode_line(line).unwrap());
+        }
+        assert!(decoder.finish().is_empty());
+
+        assert_eq!(events[0], StreamEvent::TextDelta("Let me".into()));
+        assert_eq!(
+            events[2],
+            StreamEvent::ToolCallStart {
+                index: 0,
+                id: String::new(),
+                name: "shell".into(),
+            }
+        );
+        assert_eq!(
+            events[3],
+            StreamEvent::ToolCallDelta {
+                index: 0,
+                arguments: r#"{"command":"date"}"#.into(),
+            }
+        );
+        assert_eq!(events[4], StreamEvent::ToolCallEnd { index: 0 });
+        assert_eq!(
+            events[5],
+            StreamEvent::Usage(TokenUsage {
+                input_tokens: Some(30),
+                output_tokens: Some(12),
+                cache_read_tokens: None,
+                cache_write_tokens: None,
+            })
+        );
+        assert_eq!(events[6], StreamEvent::Finish(Some("stop".into())));
+    }
+
+    #[test]
+    fn stream_decoder_surfaces_error_lines() {
+        let provider = OllamaProvider::new(None, None);
+        let mut decoder = ChatStreamDecoder {
+            provider: &provider,
+            next_tool_index: 0,
+            finished: false,
+        };
+        assert!(decoder
+            .decode_line(r#"{"error":"model 'missing' not found"}"#)
+            .is_err());
+    }
 }
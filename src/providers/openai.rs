@@ -1,9 +1,13 @@
+use crate::providers::sse::{self, OpenAiChunkDecoder};
+use crate::providers::structured::OutputSchema;
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, TokenUsage, ToolCall as ProviderToolCall,
+    ContentPart, MediaSource, Provider, ProviderCapabilities, StreamError, StreamEvent,
+    StreamResult, TokenUsage, ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use futures_util::stream;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
@@ -62,19 +66,56 @@ struct NativeChatRequest {
     tools: Option<Vec<NativeToolSpec>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
 struct NativeMessage {
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<NativeContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<NativeToolCall>>,
 }
 
+/// Message content: a plain string, or typed parts for multimodal input.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum NativeContent {
+    Text(String),
+    Parts(Vec<NativeContentPart>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum NativeContentPart {
+    Text { text: String },
+    ImageUrl { image_url: NativeImageUrl },
+    InputAudio { input_audio: NativeInputAudio },
+}
+
+#[derive(Debug, Serialize)]
+struct NativeImageUrl {
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct NativeInputAudio {
+    data: String,
+    format: String,
+}
+
+/// `input_audio.format` for an audio MIME type; the API accepts wav and mp3.
+fn audio_format(mime_type: Option<&str>) -> &'static str {
+    match mime_type {
+        Some("audio/mpeg" | "audio/mp3") => "mp3",
+        _ => "wav",
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct NativeToolSpec {
     #[serde(rename = "type")]
@@ -131,6 +172,15 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// Cached prefix tokens, reported as a subset of `prompt_tokens`.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -216,7 +266,7 @@ impl OpenAiProvider {
                                 let content = value
                                     .get("content")
                                     .and_then(serde_json::Value::as_str)
-                                    .map(ToString::to_string);
+                                    .map(|text| NativeContent::Text(text.to_string()));
                                 return NativeMessage {
                                     role: "assistant".to_string(),
                                     content,
@@ -237,7 +287,7 @@ impl OpenAiProvider {
                         let content = value
                             .get("content")
                             .and_then(serde_json::Value::as_str)
-                            .map(ToString::to_string);
+                            .map(|text| NativeContent::Text(text.to_string()));
                         return NativeMessage {
                             role: "tool".to_string(),
                             content,
@@ -247,9 +297,14 @@ impl OpenAiProvider {
                     }
                 }
 
+                let content = if m.role == "user" && m.has_media() {
+                    NativeContent::Parts(Self::convert_parts(&m.content_parts()))
+                } else {
+                    NativeContent::Text(m.content.clone())
+                };
                 NativeMessage {
                     role: m.role.clone(),
-                    content: Some(m.content.clone()),
+                    content: Some(content),
                     tool_call_id: None,
                     tool_calls: None,
                 }
@@ -257,6 +312,49 @@ impl OpenAiProvider {
             .collect()
     }
 
+    fn convert_parts(parts: &[ContentPart]) -> Vec<NativeContentPart> {
+        parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Image { mime_type, source } => NativeContentPart::ImageUrl {
+                    image_url: NativeImageUrl {
+                        url: source.to_uri(mime_type.as_deref()),
+                    },
+                },
+                ContentPart::Audio {
+                    mime_type,
+                    source: MediaSource::Base64(data),
+                } => NativeContentPart::InputAudio {
+                    input_audio: NativeInputAudio {
+                        data: data.clone(),
+                        format: audio_format(mime_type.as_deref()).to_string(),
+                    },
+                },
+                other => NativeContentPart::Text {
+                    text: other.fallback_text(),
+                },
+            })
+            .collect()
+    }
+
+    fn native_request(
+        request: ProviderChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> NativeChatRequest {
+        let tools = Self::convert_tools(request.tools);
+        NativeChatRequest {
+            model: model.to_string(),
+            messages: Self::convert_messages(request.messages),
+            temperature,
+            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
+            tools,
+            response_format: request
+                .output_schema
+                .map(OutputSchema::openai_response_format),
+        }
+    }
+
     fn parse_native_response(message: NativeResponseMessage) -> ProviderChatResponse {
         let text = message.effective_content();
         let tool_calls = message
@@ -274,6 +372,7 @@ impl OpenAiProvider {
             text,
             tool_calls,
             usage: None,
+            reasoning: None,
         }
     }
 
@@ -347,14 +446,7 @@ impl Provider for OpenAiProvider {
             anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
         })?;
 
-        let tools = Self::convert_tools(request.tools);
-        let native_request = NativeChatRequest {
-            model: model.to_string(),
-            messages: Self::convert_messages(request.messages),
-            temperature,
-            tool_choice: tools.as_ref().map(|_| "auto".to_string()),
-            tools,
-        };
+        let native_request = Self::native_request(request, model, temperature);
 
         let response = self
             .http_client()
@@ -372,6 +464,8 @@ impl Provider for OpenAiProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -384,6 +478,14 @@ impl Provider for OpenAiProvider {
         Ok(result)
     }
 
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            native_tool_calling: true,
+            vision: true,
+            structured_output: true,
+        }
+    }
+
     fn supports_native_tools(&self) -> bool {
         true
     }
@@ -417,6 +519,7 @@ impl Provider for OpenAiProvider {
             temperature,
             tool_choice: native_tools.as_ref().map(|_| "auto".to_string()),
             tools: native_tools,
+            response_format: None,
         };
 
         let response = self
@@ -435,6 +538,8 @@ impl Provider for OpenAiProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -447,6 +552,45 @@ impl Provider for OpenAiProvider {
         Ok(result)
     }
 
+    fn stream_chat<'a>(
+        &'a self,
+        request: ProviderChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+    ) -> stream::BoxStream<'a, StreamResult<StreamEvent>> {
+        let native_request = Self::native_request(request, model, temperature);
+        sse::open_stream(async move {
+            let credential = self.credential.as_ref().ok_or_else(|| {
+                StreamError::Provider(
+                    "OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.".into(),
+                )
+            })?;
+
+            let mut body = serde_json::to_value(&native_request).map_err(StreamError::Json)?;
+            body["stream"] = serde_json::Value::Bool(true);
+            body["stream_options"] = serde_json::json!({ "include_usage": true });
+
+            let response = self
+                .http_client()
+                .post(format!("{}/chat/completions", self.base_url))
+                .header("Authorization", format!("Bearer {credential}"))
+                .json(&body)
+                .send()
+                .await
+                .map_err(StreamError::Http)?;
+
+            if !response.status().is_success() {
+                let error = super::api_error("OpenAI", response).await;
+                return Err(StreamError::Provider(error.to_string()));
+            }
+
+            Ok(sse::decode_response(
+                response,
+                OpenAiChunkDecoder::default(),
+            ))
+        })
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         if let Some(credential) = self.credential.as_ref() {
             self.http_client()
@@ -713,10 +857,78 @@ mod tests {
         assert_eq!(usage.completion_tokens, Some(50));
     }
 
+    #[test]
+    fn native_response_parses_cached_prompt_tokens() {
+        let json = r#"{
+            "choices": [{"message": {"content": "Hello"}}],
+            "usage": {
+                "prompt_tokens": 2048,
+                "completion_tokens": 10,
+                "prompt_tokens_details": {"cached_tokens": 1920}
+            }
+        }"#;
+        let resp: NativeChatResponse = serde_json::from_str(json).unwrap();
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, Some(2048));
+        assert_eq!(
+            usage.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            Some(1920)
+        );
+    }
+
     #[test]
     fn native_response_parses_without_usage() {
         let json = r#"{"choices": [{"message": {"content": "Hello"}}]}"#;
         let resp: NativeChatResponse = serde_json::from_str(json).unwrap();
         assert!(resp.usage.is_none());
     }
+
+    #[test]
+    fn convert_messages_serializes_media_parts_natively() {
+        let messages = vec![
+            ChatMessage::system("be brief"),
+            ChatMessage::with_parts(
+                "user",
+                vec![
+                    ContentPart::text("Describe both"),
+                    ContentPart::image_base64("image/png", "abcd"),
+                    ContentPart::Audio {
+                        mime_type: Some("audio/mpeg".into()),
+                        source: MediaSource::Base64("efgh".into()),
+                    },
+                ],
+            ),
+        ];
+
+        let json = serde_json::to_value(OpenAiProvider::convert_messages(&messages)).unwrap();
+        assert_eq!(json[0]["content"], "be brief");
+        assert_eq!(
+            json[1]["content"],
+            serde_json::json!([
+                {"type": "text", "text": "Describe both"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,abcd"}},
+                {"type": "input_audio", "input_audio": {"data": "efgh", "format": "mp3"}}
+            ])
+        );
+    }
+
+    #[test]
+    fn native_request_maps_output_schema_to_response_format() {
+        let schema = OutputSchema::named("answer", serde_json::json!({"type": "object"})).unwrap();
+        let messages = [ChatMessage::user("hi")];
+        let request = ProviderChatRequest {
+            messages: &messages,
+            tools: None,
+            output_schema: Some(&schema),
+        };
+        let json =
+            serde_json::to_value(OpenAiProvider::native_request(request, "gpt-4o", 0.0)).unwrap();
+        assert_eq!(
+            json["response_format"],
+            serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {"name": "answer", "schema": {"type": "object"}}
+            })
+        );
+    }
 }
@@ -1,6 +1,7 @@
+use crate::providers::structured::OutputSchema;
 use crate::providers::traits::{
     ChatMessage, ChatRequest as ProviderChatRequest, ChatResponse as ProviderChatResponse,
-    Provider, TokenUsage, ToolCall as ProviderToolCall,
+    Provider, Reasoning, TokenUsage, ToolCall as ProviderToolCall,
 };
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
@@ -48,6 +49,8 @@ struct NativeChatRequest {
     tools: Option<Vec<NativeToolSpec>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -103,6 +106,15 @@ struct UsageInfo {
     prompt_tokens: Option<u64>,
     #[serde(default)]
     completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// Cached prefix tokens, reported as a subset of `prompt_tokens`.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -114,6 +126,9 @@ struct NativeChoice {
 struct NativeResponseMessage {
     #[serde(default)]
     content: Option<String>,
+    /// Reasoning text for models that expose their thinking.
+    #[serde(default)]
+    reasoning: Option<String>,
     #[serde(default)]
     tool_calls: Option<Vec<NativeToolCall>>,
 }
@@ -228,6 +243,13 @@ impl OpenRouterProvider {
             text: message.content,
             tool_calls,
             usage: None,
+            reasoning: message
+                .reasoning
+                .filter(|r| !r.trim().is_empty())
+                .map(|text| Reasoning {
+                    text,
+                    signature: None,
+                }),
         }
     }
 
@@ -378,6 +400,9 @@ impl Provider for OpenRouterProvider {
             temperature,
             tool_choice: tools.as_ref().map(|_| "auto".to_string()),
             tools,
+            response_format: request
+                .output_schema
+                .map(OutputSchema::openai_response_format),
         };
 
         let response = self
@@ -401,6 +426,8 @@ impl Provider for OpenRouterProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -417,6 +444,10 @@ impl Provider for OpenRouterProvider {
         true
     }
 
+    fn supports_structured_output(&self) -> bool {
+        true
+    }
+
     async fn chat_with_tools(
         &self,
         messages: &[ChatMessage],
@@ -472,6 +503,7 @@ impl Provider for OpenRouterProvider {
             temperature,
             tool_choice: native_tools.as_ref().map(|_| "auto".to_string()),
             tools: native_tools,
+            response_format: None,
         };
 
         let response = self
@@ -495,6 +527,8 @@ impl Provider for OpenRouterProvider {
         let usage = native_response.usage.map(|u| TokenUsage {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
+            cache_read_tokens: u.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            cache_write_tokens: None,
         });
         let message = native_response
             .choices
@@ -553,10 +587,12 @@ mod tests {
             ChatMessage {
                 role: "system".into(),
                 content: "be concise".into(),
+                parts: Vec::new(),
             },
             ChatMessage {
                 role: "user".into(),
                 content: "hello".into(),
+                parts: Vec::new(),
             },
         ];
 
@@ -599,10 +635,12 @@ mod tests {
             ChatMessage {
                 role: "assistant".into(),
                 content: "Previous answer".into(),
+                parts: Vec::new(),
             },
             ChatMessage {
                 role: "user".into(),
                 content: "Follow-up".into(),
+                parts: Vec::new(),
             },
         ];
 
@@ -649,6 +687,7 @@ mod tests {
         let messages = vec![ChatMessage {
             role: "user".into(),
             content: "What is the date?".into(),
+            parts: Vec::new(),
         }];
         let tools = vec![serde_json::json!({
             "type": "function",
@@ -719,6 +758,7 @@ mod tests {
     fn parse_native_response_converts_to_chat_response() {
         let message = NativeResponseMessage {
             content: Some("Here you go.".into()),
+            reasoning: None,
             tool_calls: Some(vec![NativeToolCall {
                 id: Some("call_789".into()),
                 kind: Some("function".into()),
@@ -737,12 +777,24 @@ mod tests {
         assert_eq!(response.tool_calls[0].name, "file_read");
     }
 
+    #[test]
+    fn parse_native_response_keeps_reasoning_separate() {
+        let message: NativeResponseMessage =
+            serde_json::from_str(r#"{"content":"42","reasoning":"6 times 7"}"#).unwrap();
+
+        let response = OpenRouterProvider::parse_native_response(message);
+
+        assert_eq!(response.text.as_deref(), Some("42"));
+        assert_eq!(response.reasoning.unwrap().text, "6 times 7");
+    }
+
     #[test]
     fn convert_messages_parses_assistant_tool_call_payload() {
         let messages = vec![ChatMessage {
             role: "assistant".into(),
             content: r#"{"content":"Using tool","tool_calls":[{"id":"call_abc","name":"shell","arguments":"{\"command\":\"pwd\"}"}]}"#
                 .into(),
+            parts: Vec::new(),
         }];
 
         let converted = OpenRouterProvider::convert_messages(&messages);
@@ -762,6 +814,7 @@ mod tests {
         let messages = vec![ChatMessage {
             role: "tool".into(),
             content: r#"{"tool_call_id":"call_xyz","content":"done"}"#.into(),
+            parts: Vec::new(),
         }];
 
         let converted = OpenRouterProvider::convert_messages(&messages);
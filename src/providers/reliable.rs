@@ -1,12 +1,14 @@
+use super::circuit::{Admission, CircuitConfig, HealthBoard};
 use super::traits::{
-    ChatMessage, ChatRequest, ChatResponse, StreamChunk, StreamOptions, StreamResult,
+    ChatMessage, ChatRequest, ChatResponse, ContextWindowExceededError, StreamChunk, StreamError,
+    StreamEvent, StreamOptions, StreamResult,
 };
 use super::Provider;
 use async_trait::async_trait;
 use futures_util::{stream, StreamExt};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // ── Error Classification ─────────────────────────────────────────────────
 // Errors are split into retryable (transient server/network failures) and
@@ -42,6 +44,19 @@ fn is_non_retryable(err: &anyhow::Error) -> bool {
     // Heuristic: detect auth/model failures by keyword when no HTTP status
     // is available (e.g. gRPC or custom transport errors).
     let msg_lower = msg.to_lowercase();
+    if has_auth_failure_hint(&msg_lower) {
+        return true;
+    }
+
+    msg_lower.contains("model")
+        && (msg_lower.contains("not found")
+            || msg_lower.contains("unknown")
+            || msg_lower.contains("unsupported")
+            || msg_lower.contains("does not exist")
+            || msg_lower.contains("invalid"))
+}
+
+fn has_auth_failure_hint(msg_lower: &str) -> bool {
     let auth_failure_hints = [
         "invalid api key",
         "incorrect api key",
@@ -56,22 +71,32 @@ fn is_non_retryable(err: &anyhow::Error) -> bool {
         "invalid token",
     ];
 
-    if auth_failure_hints
+    auth_failure_hints
         .iter()
         .any(|hint| msg_lower.contains(hint))
-    {
-        return true;
-    }
+}
 
-    msg_lower.contains("model")
-        && (msg_lower.contains("not found")
-            || msg_lower.contains("unknown")
-            || msg_lower.contains("unsupported")
-            || msg_lower.contains("does not exist")
-            || msg_lower.contains("invalid"))
+/// Check if an error is an authentication/authorization failure (401/403).
+/// These are not retried, but they do say the provider is unusable with the
+/// configured credentials, so they count against its circuit breaker.
+fn is_auth_failure(err: &anyhow::Error) -> bool {
+    if let Some(reqwest_err) = err.downcast_ref::<reqwest::Error>() {
+        if let Some(status) = reqwest_err.status() {
+            return matches!(status.as_u16(), 401 | 403);
+        }
+    }
+    let msg = err.to_string();
+    msg.split(|c: char| !c.is_ascii_digit())
+        .any(|word| matches!(word, "401" | "403"))
+        || has_auth_failure_hint(&msg.to_lowercase())
 }
 
-fn is_context_window_exceeded(err: &anyhow::Error) -> bool {
+/// Check if an error means the request does not fit the model's context
+/// window. The agent loop uses this to compact history and resend.
+pub fn is_context_window_exceeded(err: &anyhow::Error) -> bool {
+    if err.downcast_ref::<ContextWindowExceededError>().is_some() {
+        return true;
+    }
     let lower = err.to_string().to_lowercase();
     let hints = [
         "exceeds the context window",
@@ -211,6 +236,30 @@ fn push_failure(
     ));
 }
 
+/// Typed error for a request rejected as too large for the model's context
+/// window. Retries and fallbacks are skipped; the caller owns the history.
+fn context_overflow(
+    provider: &str,
+    model: &str,
+    failures: &[String],
+) -> ContextWindowExceededError {
+    ContextWindowExceededError {
+        provider: provider.to_string(),
+        model: model.to_string(),
+        attempts: failures.join("\n"),
+    }
+}
+
+/// How the retry loop proceeds after a failed attempt.
+enum AttemptOutcome {
+    /// Retry the same provider/model after waiting.
+    Retry { wait_ms: u64 },
+    /// Give up on this provider/model and try the next one.
+    NextProvider,
+    /// Stop entirely: the request itself cannot succeed anywhere.
+    Abort,
+}
+
 // ── Resilient Provider Wrapper ────────────────────────────────────────────
 // Three-level failover strategy: model chain → provider chain → retry loop.
 //   Outer loop:  iterate model fallback chain (original model first, then
@@ -220,6 +269,8 @@ fn push_failure(
 //                backoff, rotating API keys on rate-limit errors.
 // Loop invariant: `failures` accumulates every failed attempt so the final
 // error message gives operators a complete diagnostic trail.
+// Within each model, providers are tried healthiest first and pairs with an
+// open circuit breaker are skipped (see `circuit.rs`).
 
 /// Provider wrapper with retry, fallback, auth rotation, and model failover.
 pub struct ReliableProvider {
@@ -231,6 +282,8 @@ pub struct ReliableProvider {
     key_index: AtomicUsize,
     /// Per-model fallback chains: model_name → [fallback_model_1, fallback_model_2, ...]
     model_fallbacks: HashMap<String, Vec<String>>,
+    /// Circuit breakers and health scores per (provider, model).
+    health: HealthBoard,
 }
 
 impl ReliableProvider {
@@ -246,6 +299,7 @@ impl ReliableProvider {
             api_keys: Vec::new(),
             key_index: AtomicUsize::new(0),
             model_fallbacks: HashMap::new(),
+            health: HealthBoard::default(),
         }
     }
 
@@ -261,6 +315,37 @@ impl ReliableProvider {
         self
     }
 
+    /// Set circuit-breaker thresholds and health-based ordering.
+    pub fn with_circuit_config(mut self, config: CircuitConfig) -> Self {
+        self.health = HealthBoard::new(config);
+        self
+    }
+
+    /// Providers in the order to try them for `model`, healthiest first.
+    fn ordered_providers(&self, model: &str) -> Vec<&(String, Box<dyn Provider>)> {
+        self.health.order(model, &self.providers)
+    }
+
+    /// Check the circuit breaker for a pair, noting skipped pairs in `failures`.
+    fn admit(&self, provider_name: &str, model: &str, failures: &mut Vec<String>) -> bool {
+        match self.health.admit(provider_name, model) {
+            Admission::Allowed => true,
+            Admission::Rejected { retry_in } => {
+                tracing::debug!(
+                    provider = provider_name,
+                    model,
+                    retry_in_secs = retry_in.as_secs(),
+                    "Circuit open, skipping provider"
+                );
+                failures.push(format!(
+                    "provider={provider_name} model={model}: skipped; circuit open (next probe in {}s)",
+                    retry_in.as_secs().max(1)
+                ));
+                false
+            }
+        }
+    }
+
     /// Build the list of models to try: [original, fallback1, fallback2, ...]
     fn model_chain<'a>(&'a self, model: &'a str) -> Vec<&'a str> {
         let mut chain = vec![model];
@@ -279,6 +364,82 @@ impl ReliableProvider {
         Some(&self.api_keys[idx])
     }
 
+    /// Record and log a failed attempt, and decide what the retry loop does next.
+    fn on_attempt_failure(
+        &self,
+        err: &anyhow::Error,
+        failures: &mut Vec<String>,
+        provider_name: &str,
+        model: &str,
+        attempt: u32,
+        backoff_ms: u64,
+    ) -> AttemptOutcome {
+        let non_retryable_rate_limit = is_non_retryable_rate_limit(err);
+        let non_retryable = is_non_retryable(err) || non_retryable_rate_limit;
+        let rate_limited = is_rate_limited(err);
+        let failure_reason = failure_reason(rate_limited, non_retryable);
+        let error_detail = compact_error_detail(err);
+
+        // Server, network, rate-limit and auth failures say something about
+        // the provider's health; a rejected request (400/404, context
+        // overflow) would fail the same way anywhere.
+        if rate_limited || is_auth_failure(err) || !is_non_retryable(err) {
+            self.health.record_failure(provider_name, model);
+        }
+
+        push_failure(
+            failures,
+            provider_name,
+            model,
+            attempt + 1,
+            self.max_retries + 1,
+            failure_reason,
+            &error_detail,
+        );
+
+        if rate_limited && !non_retryable_rate_limit {
+            if let Some(new_key) = self.rotate_key() {
+                tracing::warn!(
+                    provider = provider_name,
+                    error = %error_detail,
+                    "Rate limited; key rotation selected key ending ...{} \
+                     but cannot apply (Provider trait has no set_api_key). \
+                     Retrying with original key.",
+                    &new_key[new_key.len().saturating_sub(4)..]
+                );
+            }
+        }
+
+        if non_retryable {
+            tracing::warn!(
+                provider = provider_name,
+                model,
+                error = %error_detail,
+                "Non-retryable error, moving on"
+            );
+            if is_context_window_exceeded(err) {
+                return AttemptOutcome::Abort;
+            }
+            return AttemptOutcome::NextProvider;
+        }
+
+        if attempt < self.max_retries {
+            let wait_ms = self.compute_backoff(backoff_ms, err);
+            tracing::warn!(
+                provider = provider_name,
+                model,
+                attempt = attempt + 1,
+                backoff_ms = wait_ms,
+                reason = failure_reason,
+                error = %error_detail,
+                "Provider call failed, retrying"
+            );
+            return AttemptOutcome::Retry { wait_ms };
+        }
+
+        AttemptOutcome::NextProvider
+    }
+
     /// Compute backoff duration, respecting Retry-After if present.
     fn compute_backoff(&self, base: u64, err: &anyhow::Error) -> u64 {
         if let Some(retry_after) = parse_retry_after_ms(err) {
@@ -317,15 +478,24 @@ impl Provider for ReliableProvider {
         // immediately. On non-retryable error, break to next provider. On
         // retryable error, sleep with exponential backoff and retry.
         for current_model in &models {
-            for (provider_name, provider) in &self.providers {
+            for (provider_name, provider) in self.ordered_providers(current_model) {
+                if !self.admit(provider_name, current_model, &mut failures) {
+                    continue;
+                }
                 let mut backoff_ms = self.base_backoff_ms;
 
                 for attempt in 0..=self.max_retries {
+                    let started = Instant::now();
                     match provider
                         .chat_with_system(system_prompt, message, current_model, temperature)
                         .await
                     {
                         Ok(resp) => {
+                            self.health.record_success(
+                                provider_name,
+                                current_model,
+                                started.elapsed(),
+                            );
                             if attempt > 0 || *current_model != model {
                                 tracing::info!(
                                     provider = provider_name,
@@ -337,71 +507,28 @@ impl Provider for ReliableProvider {
                             }
                             return Ok(resp);
                         }
-                        Err(e) => {
-                            let non_retryable_rate_limit = is_non_retryable_rate_limit(&e);
-                            let non_retryable = is_non_retryable(&e) || non_retryable_rate_limit;
-                            let rate_limited = is_rate_limited(&e);
-                            let failure_reason = failure_reason(rate_limited, non_retryable);
-                            let error_detail = compact_error_detail(&e);
-
-                            push_failure(
-                                &mut failures,
-                                provider_name,
-                                current_model,
-                                attempt + 1,
-                                self.max_retries + 1,
-                                failure_reason,
-                                &error_detail,
-                            );
-
-                            // Rate-limit with rotatable keys: cycle to the next API key
-                            // so the retry hits a different quota bucket.
-                            if rate_limited && !non_retryable_rate_limit {
-                                if let Some(new_key) = self.rotate_key() {
-                                    tracing::warn!(
-                                        provider = provider_name,
-                                        error = %error_detail,
-                                        "Rate limited; key rotation selected key ending ...{} \
-                                         but cannot apply (Provider trait has no set_api_key). \
-                                         Retrying with original key.",
-                                        &new_key[new_key.len().saturating_sub(4)..]
-                                    );
-                                }
-                            }
-
-                            if non_retryable {
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    error = %error_detail,
-                                    "Non-retryable error, moving on"
-                                );
-
-                                if is_context_window_exceeded(&e) {
-                                    anyhow::bail!(
-                                        "Request exceeds model context window; retries and fallbacks were skipped. Attempts:\n{}",
-                                        failures.join("\n")
-                                    );
-                                }
-
-                                break;
-                            }
-
-                            if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    attempt = attempt + 1,
-                                    backoff_ms = wait,
-                                    reason = failure_reason,
-                                    error = %error_detail,
-                                    "Provider call failed, retrying"
-                                );
-                                tokio::time::sleep(Duration::from_millis(wait)).await;
+                        Err(e) => match self.on_attempt_failure(
+                            &e,
+                            &mut failures,
+                            provider_name,
+                            current_model,
+                            attempt,
+                            backoff_ms,
+                        ) {
+                            AttemptOutcome::Retry { wait_ms } => {
+                                tokio::time::sleep(Duration::from_millis(wait_ms)).await;
                                 backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
                             }
-                        }
+                            AttemptOutcome::NextProvider => break,
+                            AttemptOutcome::Abort => {
+                                return Err(context_overflow(
+                                    provider_name,
+                                    current_model,
+                                    &failures,
+                                )
+                                .into())
+                            }
+                        },
                     }
                 }
 
@@ -437,15 +564,24 @@ impl Provider for ReliableProvider {
         let mut failures = Vec::new();
 
         for current_model in &models {
-            for (provider_name, provider) in &self.providers {
+            for (provider_name, provider) in self.ordered_providers(current_model) {
+                if !self.admit(provider_name, current_model, &mut failures) {
+                    continue;
+                }
                 let mut backoff_ms = self.base_backoff_ms;
 
                 for attempt in 0..=self.max_retries {
+                    let started = Instant::now();
                     match provider
                         .chat_with_history(messages, current_model, temperature)
                         .await
                     {
                         Ok(resp) => {
+                            self.health.record_success(
+                                provider_name,
+                                current_model,
+                                started.elapsed(),
+                            );
                             if attempt > 0 || *current_model != model {
                                 tracing::info!(
                                     provider = provider_name,
@@ -457,69 +593,28 @@ impl Provider for ReliableProvider {
                             }
                             return Ok(resp);
                         }
-                        Err(e) => {
-                            let non_retryable_rate_limit = is_non_retryable_rate_limit(&e);
-                            let non_retryable = is_non_retryable(&e) || non_retryable_rate_limit;
-                            let rate_limited = is_rate_limited(&e);
-                            let failure_reason = failure_reason(rate_limited, non_retryable);
-                            let error_detail = compact_error_detail(&e);
-
-                            push_failure(
-                                &mut failures,
-                                provider_name,
-                                current_model,
-                                attempt + 1,
-                                self.max_retries + 1,
-                                failure_reason,
-                                &error_detail,
-                            );
-
-                            if rate_limited && !non_retryable_rate_limit {
-                                if let Some(new_key) = self.rotate_key() {
-                                    tracing::warn!(
-                                        provider = provider_name,
-                                        error = %error_detail,
-                                        "Rate limited; key rotation selected key ending ...{} \
-                                         but cannot apply (Provider trait has no set_api_key). \
-                                         Retrying with original key.",
-                                        &new_key[new_key.len().saturating_sub(4)..]
-                                    );
-                                }
-                            }
-
-                            if non_retryable {
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    error = %error_detail,
-                                    "Non-retryable error, moving on"
-                                );
-
-                                if is_context_window_exceeded(&e) {
-                                    anyhow::bail!(
-                                        "Request exceeds model context window; retries and fallbacks were skipped. Attempts:\n{}",
-                                        failures.join("\n")
-                                    );
-                                }
-
-                                break;
-                            }
-
-                            if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    attempt = attempt + 1,
-                                    backoff_ms = wait,
-                                    reason = failure_reason,
-                                    error = %error_detail,
-                                    "Provider call failed, retrying"
-                                );
-                                tokio::time::sleep(Duration::from_millis(wait)).await;
+                        Err(e) => match self.on_attempt_failure(
+                            &e,
+                            &mut failures,
+                            provider_name,
+                            current_model,
+                            attempt,
+                            backoff_ms,
+                        ) {
+                            AttemptOutcome::Retry { wait_ms } => {
+                                tokio::time::sleep(Duration::from_millis(wait_ms)).await;
                                 backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
                             }
-                        }
+                            AttemptOutcome::NextProvider => break,
+                            AttemptOutcome::Abort => {
+                                return Err(context_overflow(
+                                    provider_name,
+                                    current_model,
+                                    &failures,
+                                )
+                                .into())
+                            }
+                        },
                     }
                 }
 
@@ -550,6 +645,13 @@ impl Provider for ReliableProvider {
             .any(|(_, provider)| provider.supports_vision())
     }
 
+    fn supports_structured_output(&self) -> bool {
+        self.providers
+            .first()
+            .map(|(_, p)| p.supports_structured_output())
+            .unwrap_or(false)
+    }
+
     async fn chat_with_tools(
         &self,
         messages: &[ChatMessage],
@@ -561,15 +663,24 @@ impl Provider for ReliableProvider {
         let mut failures = Vec::new();
 
         for current_model in &models {
-            for (provider_name, provider) in &self.providers {
+            for (provider_name, provider) in self.ordered_providers(current_model) {
+                if !self.admit(provider_name, current_model, &mut failures) {
+                    continue;
+                }
                 let mut backoff_ms = self.base_backoff_ms;
 
                 for attempt in 0..=self.max_retries {
+                    let started = Instant::now();
                     match provider
                         .chat_with_tools(messages, tools, current_model, temperature)
                         .await
                     {
                         Ok(resp) => {
+                            self.health.record_success(
+                                provider_name,
+                                current_model,
+                                started.elapsed(),
+                            );
                             if attempt > 0 || *current_model != model {
                                 tracing::info!(
                                     provider = provider_name,
@@ -581,68 +692,101 @@ impl Provider for ReliableProvider {
                             }
                             return Ok(resp);
                         }
-                        Err(e) => {
-                            let non_retryable_rate_limit = is_non_retryable_rate_limit(&e);
-                            let non_retryable = is_non_retryable(&e) || non_retryable_rate_limit;
-                            let rate_limited = is_rate_limited(&e);
-                            let failure_reason = failure_reason(rate_limited, non_retryable);
-                            let error_detail = compact_error_detail(&e);
-
-                            push_failure(
-                                &mut failures,
-                                provider_name,
-                                current_model,
-                                attempt + 1,
-                                self.max_retries + 1,
-                                failure_reason,
-                                &error_detail,
-                            );
-
-                            if rate_limited && !non_retryable_rate_limit {
-                                if let Some(new_key) = self.rotate_key() {
-                                    tracing::warn!(
-                                        provider = provider_name,
-                                        error = %error_detail,
-                                        "Rate limited; key rotation selected key ending ...{} \
-                                         but cannot apply (Provider trait has no set_api_key). \
-                                         Retrying with original key.",
-                                        &new_key[new_key.len().saturating_sub(4)..]
-                                    );
-                                }
+                        Err(e) => match self.on_attempt_failure(
+                            &e,
+                            &mut failures,
+                            provider_name,
+                            current_model,
+                            attempt,
+                            backoff_ms,
+                        ) {
+                            AttemptOutcome::Retry { wait_ms } => {
+                                tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
                             }
+                            AttemptOutcome::NextProvider => break,
+                            AttemptOutcome::Abort => {
+                                return Err(context_overflow(
+                                    provider_name,
+                                    current_model,
+                                    &failures,
+                                )
+                                .into())
+                            }
+                        },
+                    }
+                }
 
-                            if non_retryable {
-                                tracing::warn!(
-                                    provider = provider_name,
-                                    model = *current_model,
-                                    error = %error_detail,
-                                    "Non-retryable error, moving on"
-                                );
+                tracing::warn!(
+                    provider = provider_name,
+                    model = *current_model,
+                    "Exhausted retries, trying next provider/model"
+                );
+            }
+        }
 
-                                if is_context_window_exceeded(&e) {
-                                    anyhow::bail!(
-                                        "Request exceeds model context window; retries and fallbacks were skipped. Attempts:\n{}",
-                                        failures.join("\n")
-                                    );
-                                }
+        anyhow::bail!(
+            "All providers/models failed. Attempts:\n{}",
+            failures.join("\n")
+        )
+    }
 
-                                break;
-                            }
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let models = self.model_chain(model);
+        let mut failures = Vec::new();
 
-                            if attempt < self.max_retries {
-                                let wait = self.compute_backoff(backoff_ms, &e);
-                                tracing::warn!(
+        for current_model in &models {
+            for (provider_name, provider) in self.ordered_providers(current_model) {
+                if !self.admit(provider_name, current_model, &mut failures) {
+                    continue;
+                }
+                let mut backoff_ms = self.base_backoff_ms;
+
+                for attempt in 0..=self.max_retries {
+                    let started = Instant::now();
+                    let e = match provider.chat(request, current_model, temperature).await {
+                        Ok(resp) => {
+                            self.health.record_success(
+                                provider_name,
+                                current_model,
+                                started.elapsed(),
+                            );
+                            if attempt > 0 || *current_model != model {
+                                tracing::info!(
                                     provider = provider_name,
                                     model = *current_model,
-                                    attempt = attempt + 1,
-                                    backoff_ms = wait,
-                                    reason = failure_reason,
-                                    error = %error_detail,
-                                    "Provider call failed, retrying"
+                                    attempt,
+                                    original_model = model,
+                                    "Provider recovered (failover/retry)"
                                 );
-                                tokio::time::sleep(Duration::from_millis(wait)).await;
-                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
                             }
+                            return Ok(resp);
+                        }
+                        Err(e) => e,
+                    };
+
+                    match self.on_attempt_failure(
+                        &e,
+                        &mut failures,
+                        provider_name,
+                        current_model,
+                        attempt,
+                        backoff_ms,
+                    ) {
+                        AttemptOutcome::Retry { wait_ms } => {
+                            tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+                            backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
+                        }
+                        AttemptOutcome::NextProvider => break,
+                        AttemptOutcome::Abort => {
+                            return Err(
+                                context_overflow(provider_name, current_model, &failures).into()
+                            )
                         }
                     }
                 }
@@ -661,6 +805,90 @@ impl Provider for ReliableProvider {
         )
     }
 
+    /// Fails over like [`Provider::chat`] until a provider yields its first
+    /// event; once output has started the stream is committed to that provider.
+    fn stream_chat<'a>(
+        &'a self,
+        request: ChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+    ) -> stream::BoxStream<'a, StreamResult<StreamEvent>> {
+        let model = model.to_string();
+        super::sse::open_stream(async move {
+            let models = self.model_chain(&model);
+            let mut failures = Vec::new();
+
+            for current_model in &models {
+                for (provider_name, provider) in self.ordered_providers(current_model) {
+                    if !self.admit(provider_name, current_model, &mut failures) {
+                        continue;
+                    }
+                    let mut backoff_ms = self.base_backoff_ms;
+
+                    for attempt in 0..=self.max_retries {
+                        let started = Instant::now();
+                        let mut events = provider.stream_chat(request, current_model, temperature);
+                        let e = match events.next().await {
+                            Some(Ok(first)) => {
+                                self.health.record_success(
+                                    provider_name,
+                                    current_model,
+                                    started.elapsed(),
+                                );
+                                if attempt > 0 || *current_model != model {
+                                    tracing::info!(
+                                        provider = provider_name,
+                                        model = *current_model,
+                                        attempt,
+                                        original_model = model,
+                                        "Provider recovered (failover/retry)"
+                                    );
+                                }
+                                return Ok(stream::once(async move { Ok(first) })
+                                    .chain(events)
+                                    .boxed());
+                            }
+                            None => return Ok(events),
+                            Some(Err(e)) => anyhow::Error::from(e),
+                        };
+
+                        match self.on_attempt_failure(
+                            &e,
+                            &mut failures,
+                            provider_name,
+                            current_model,
+                            attempt,
+                            backoff_ms,
+                        ) {
+                            AttemptOutcome::Retry { wait_ms } => {
+                                tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+                                backoff_ms = (backoff_ms.saturating_mul(2)).min(10_000);
+                            }
+                            AttemptOutcome::NextProvider => break,
+                            AttemptOutcome::Abort => {
+                                return Err(StreamError::Provider(
+                                    context_overflow(provider_name, current_model, &failures)
+                                        .to_string(),
+                                ))
+                            }
+                        }
+                    }
+
+                    tracing::warn!(
+                        provider = provider_name,
+                        model = *current_model,
+                        "Exhausted retries, trying next provider/model"
+                    );
+                }
+            }
+
+            Err(StreamError::Provider(format!(
+                "All providers/models failed. Attempts:\n{}",
+                failures.join("\n")
+            )))
+        })
+    }
+
     fn supports_streaming(&self) -> bool {
         self.providers.iter().any(|(_, p)| p.supports_streaming())
     }
@@ -885,6 +1113,152 @@ mod tests {
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn open_circuit_skips_failing_primary_on_later_requests() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response: "never",
+                        error: "primary down",
+                    }),
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 0,
+                        response: "from fallback",
+                        error: "fallback down",
+                    }),
+                ),
+            ],
+            1,
+            1,
+        )
+        .with_circuit_config(CircuitConfig {
+            failure_threshold: 2,
+            open_duration: Duration::from_secs(60),
+            reorder: false,
+        });
+
+        let first = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+        assert_eq!(first, "from fallback");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+
+        let second = provider.simple_chat("hello", "test", 0.0).await.unwrap();
+        assert_eq!(second, "from fallback");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn all_circuits_open_fails_fast_with_skip_reason() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![(
+                "only".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: usize::MAX,
+                    response: "never",
+                    error: "boom",
+                }),
+            )],
+            0,
+            1,
+        )
+        .with_circuit_config(CircuitConfig {
+            failure_threshold: 1,
+            open_duration: Duration::from_secs(60),
+            reorder: true,
+        });
+
+        assert!(provider.simple_chat("hello", "test", 0.0).await.is_err());
+        let err = provider
+            .simple_chat("hello", "test", 0.0)
+            .await
+            .expect_err("open circuit should fail fast")
+            .to_string();
+        assert!(err.contains("circuit open"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn client_errors_do_not_trip_the_circuit() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![(
+                "only".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: usize::MAX,
+                    response: "never",
+                    error: "API error (400 Bad Request): invalid tool schema",
+                }),
+            )],
+            0,
+            1,
+        )
+        .with_circuit_config(CircuitConfig {
+            failure_threshold: 1,
+            open_duration: Duration::from_secs(60),
+            reorder: true,
+        });
+
+        for _ in 0..3 {
+            let err = provider
+                .simple_chat("hello", "test", 0.0)
+                .await
+                .expect_err("400 should fail")
+                .to_string();
+            assert!(!err.contains("circuit open"));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn auth_errors_trip_the_circuit() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![(
+                "only".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: usize::MAX,
+                    response: "never",
+                    error: "API error (401 Unauthorized): invalid api key",
+                }),
+            )],
+            0,
+            1,
+        )
+        .with_circuit_config(CircuitConfig {
+            failure_threshold: 1,
+            open_duration: Duration::from_secs(60),
+            reorder: true,
+        });
+
+        assert!(provider.simple_chat("hello", "test", 0.0).await.is_err());
+        let err = provider
+            .simple_chat("hello", "test", 0.0)
+            .await
+            .expect_err("open circuit should fail fast")
+            .to_string();
+        assert!(err.contains("circuit open"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(is_auth_failure(&anyhow::anyhow!("403 Forbidden")));
+        assert!(!is_auth_failure(&anyhow::anyhow!(
+            "API error (400 Bad Request): invalid tool schema"
+        )));
+    }
+
     #[tokio::test]
     async fn returns_aggregated_error_when_all_providers_fail() {
         let provider = ReliableProvider::new(
@@ -989,6 +1363,11 @@ mod tests {
 
         assert!(msg.contains("context window"));
         assert!(msg.contains("skipped"));
+        let overflow = err
+            .downcast_ref::<ContextWindowExceededError>()
+            .expect("overflow should be typed for the agent loop");
+        assert_eq!(overflow.model, "gpt-5.3-codex");
+        assert!(is_context_window_exceeded(&err));
         assert_eq!(calls.load(Ordering::SeqCst), 1);
     }
 
@@ -1551,6 +1930,7 @@ mod tests {
                 text: Some(self.response_text.to_string()),
                 tool_calls: self.tool_calls.clone(),
                 usage: None,
+                reasoning: None,
             })
         }
     }
@@ -1582,6 +1962,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            output_schema: None,
         };
         let result = provider.chat(request, "test-model", 0.0).await.unwrap();
 
@@ -1618,6 +1999,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            output_schema: None,
         };
         let result = provider.chat(request, "test-model", 0.0).await.unwrap();
 
@@ -1689,6 +2071,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            output_schema: None,
         };
         let err = provider
             .chat(request, "test", 0.0)
@@ -1743,6 +2126,7 @@ mod tests {
                 text: Some(self.response_text.to_string()),
                 tool_calls: vec![],
                 usage: None,
+                reasoning: None,
             })
         }
     }
@@ -1804,6 +2188,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            output_schema: None,
         };
         let result = provider.chat(request, "claude-opus", 0.0).await.unwrap();
         assert_eq!(result.text.as_deref(), Some("ok from sonnet"));
@@ -1852,6 +2237,7 @@ mod tests {
         let request = ChatRequest {
             messages: &messages,
             tools: None,
+            output_schema: None,
         };
         let result = provider.chat(request, "test", 0.0).await.unwrap();
         assert_eq!(result.text.as_deref(), Some("from fallback"));
@@ -1859,4 +2245,95 @@ mod tests {
         assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
     }
+
+    #[tokio::test]
+    async fn stream_chat_fails_over_before_first_event() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let tool_call = super::super::traits::ToolCall {
+            id: "call_1".to_string(),
+            name: "shell".to_string(),
+            arguments: r#"{"command":"date"}"#.to_string(),
+        };
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(NativeToolMock {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response_text: "never",
+                        tool_calls: vec![],
+                        error: "401 Unauthorized",
+                    }) as Box<dyn Provider>,
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(NativeToolMock {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 1,
+                        response_text: "streamed",
+                        tool_calls: vec![tool_call],
+                        error: "temporary failure",
+                    }) as Box<dyn Provider>,
+                ),
+            ],
+            2,
+            1,
+        );
+
+        let messages = vec![ChatMessage::user("test")];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: None,
+            output_schema: None,
+        };
+        let events: Vec<StreamEvent> = provider
+            .stream_chat(request, "test-model", 0.0)
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(events[0], StreamEvent::TextDelta("streamed".into()));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            StreamEvent::ToolCallStart { name, .. } if name == "shell"
+        )));
+        // Non-retryable 401 moves straight to the fallback, which recovers on retry.
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn stream_chat_reports_aggregated_error_when_all_providers_fail() {
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(NativeToolMock {
+                    calls: Arc::new(AtomicUsize::new(0)),
+                    fail_until_attempt: usize::MAX,
+                    response_text: "never",
+                    tool_calls: vec![],
+                    error: "500 Internal Server Error",
+                }) as Box<dyn Provider>,
+            )],
+            1,
+            1,
+        );
+
+        let messages = vec![ChatMessage::user("test")];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: None,
+            output_schema: None,
+        };
+        let events: Vec<_> = provider
+            .stream_chat(request, "test-model", 0.0)
+            .collect()
+            .await;
+
+        assert_eq!(events.len(), 1);
+        let err = events.into_iter().next().unwrap().unwrap_err();
+        assert!(err.to_string().contains("All providers/models failed"));
+    }
 }
@@ -0,0 +1,470 @@
+//! Record provider traffic to a cassette and replay it offline.
+//!
+//! A cassette is a JSONL file with one [`Interaction`] per line: the request
+//! messages, tool definitions and model, plus the provider's response
+//! (including tool calls and usage). [`RecordingProvider`] wraps a live
+//! provider and appends every successful call; [`ReplayProvider`] serves the
+//! recorded responses back in order without touching the network.
+//!
+//! Recording is enabled for the provider chains built by the factory when
+//! `ZEROCLAW_RECORD_CASSETTE` names a cassette path; `replay:<path>` selects
+//! the replay provider.
+
+use super::traits::{ChatMessage, ChatRequest, ChatResponse, ProviderCapabilities, ToolsPayload};
+use super::Provider;
+use crate::tools::ToolSpec;
+use anyhow::Context;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that turns on cassette recording.
+pub const RECORD_CASSETTE_ENV: &str = "ZEROCLAW_RECORD_CASSETTE";
+
+/// One recorded request/response pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    /// Tool definitions sent with the request (native tool calling only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<serde_json::Value>,
+    pub response: ChatResponse,
+    /// Capabilities of the recorded provider, so replays take the same
+    /// native or prompt-guided tool path.
+    #[serde(default)]
+    pub native_tools: bool,
+    #[serde(default)]
+    pub vision: bool,
+}
+
+/// Read every interaction from a cassette file.
+pub fn load_cassette(path: &Path) -> anyhow::Result<Vec<Interaction>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read cassette: {}", path.display()))?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            serde_json::from_str(line).with_context(|| {
+                format!("Invalid cassette entry at {}:{}", path.display(), idx + 1)
+            })
+        })
+        .collect()
+}
+
+/// Wraps a provider and appends each successful call to a cassette.
+pub struct RecordingProvider {
+    inner: Box<dyn Provider>,
+    path: PathBuf,
+    // Serializes appends from concurrent calls on this provider.
+    write_lock: Mutex<()>,
+}
+
+impl RecordingProvider {
+    pub fn new(inner: Box<dyn Provider>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Wrap `inner` when [`RECORD_CASSETTE_ENV`] is set; otherwise return it
+    /// unchanged.
+    pub fn wrap_from_env(inner: Box<dyn Provider>) -> Box<dyn Provider> {
+        match std::env::var(RECORD_CASSETTE_ENV) {
+            Ok(path) if !path.trim().is_empty() => {
+                tracing::info!(cassette = %path, "Recording provider interactions");
+                Box::new(Self::new(inner, path.trim()))
+            }
+            _ => inner,
+        }
+    }
+
+    fn record(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        tools: Vec<serde_json::Value>,
+        response: &ChatResponse,
+    ) {
+        let interaction = Interaction {
+            model: model.to_string(),
+            messages: messages.to_vec(),
+            tools,
+            response: response.clone(),
+            native_tools: self.inner.supports_native_tools(),
+            vision: self.inner.supports_vision(),
+        };
+        if let Err(e) = self.append(&interaction) {
+            tracing::warn!(cassette = %self.path.display(), "Failed to record interaction: {e}");
+        }
+    }
+
+    fn append(&self, interaction: &Interaction) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(interaction)?;
+        line.push('\n');
+        let _guard = self.write_lock.lock();
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn text_response(text: &str) -> ChatResponse {
+    ChatResponse {
+        text: Some(text.to_string()),
+        tool_calls: Vec::new(),
+        usage: None,
+        reasoning: None,
+    }
+}
+
+fn one_shot_messages(system_prompt: Option<&str>, message: &str) -> Vec<ChatMessage> {
+    let mut messages = Vec::with_capacity(2);
+    if let Some(system) = system_prompt {
+        messages.push(ChatMessage::system(system));
+    }
+    messages.push(ChatMessage::user(message));
+    messages
+}
+
+fn tool_specs_as_json(tools: Option<&[ToolSpec]>) -> Vec<serde_json::Value> {
+    tools
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|spec| serde_json::to_value(spec).ok())
+        .collect()
+}
+
+#[async_trait]
+impl Provider for RecordingProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn supports_native_tools(&self) -> bool {
+        self.inner.supports_native_tools()
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.inner.supports_vision()
+    }
+
+    fn supports_structured_output(&self) -> bool {
+        self.inner.supports_structured_output()
+    }
+
+    fn convert_tools(&self, tools: &[ToolSpec]) -> ToolsPayload {
+        self.inner.convert_tools(tools)
+    }
+
+    async fn warmup(&self) -> anyhow::Result<()> {
+        self.inner.warmup().await
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let text = self
+            .inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await?;
+        let messages = one_shot_messages(system_prompt, message);
+        self.record(model, &messages, Vec::new(), &text_response(&text));
+        Ok(text)
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let text = self
+            .inner
+            .chat_with_history(messages, model, temperature)
+            .await?;
+        self.record(model, messages, Vec::new(), &text_response(&text));
+        Ok(text)
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[serde_json::Value],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let response = self
+            .inner
+            .chat_with_tools(messages, tools, model, temperature)
+            .await?;
+        self.record(model, messages, tools.to_vec(), &response);
+        Ok(response)
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let response = self.inner.chat(request, model, temperature).await?;
+        self.record(
+            model,
+            request.messages,
+            tool_specs_as_json(request.tools),
+            &response,
+        );
+        Ok(response)
+    }
+}
+
+/// Serves recorded responses in order. Requests are not matched against the
+/// recording (system prompts embed dates and workspace paths); a differing
+/// model name or last message is only logged at debug level.
+pub struct ReplayProvider {
+    source: String,
+    capabilities: ProviderCapabilities,
+    interactions: Mutex<VecDeque<Interaction>>,
+    total: usize,
+}
+
+impl ReplayProvider {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let interactions = load_cassette(path)?;
+        Ok(Self::new(path.display().to_string(), interactions))
+    }
+
+    pub fn new(source: impl Into<String>, interactions: Vec<Interaction>) -> Self {
+        let capabilities = interactions
+            .first()
+            .map(|first| ProviderCapabilities {
+                native_tool_calling: first.native_tools,
+                vision: first.vision,
+                structured_output: false,
+            })
+            .unwrap_or_default();
+        Self {
+            source: source.into(),
+            capabilities,
+            total: interactions.len(),
+            interactions: Mutex::new(interactions.into()),
+        }
+    }
+
+    /// Interactions not yet served.
+    pub fn remaining(&self) -> usize {
+        self.interactions.lock().len()
+    }
+
+    fn next(&self, model: &str, messages: &[ChatMessage]) -> anyhow::Result<ChatResponse> {
+        let Some(interaction) = self.interactions.lock().pop_front() else {
+            anyhow::bail!(
+                "Replay cassette {} exhausted after {} interactions",
+                self.source,
+                self.total
+            );
+        };
+        if interaction.model != model {
+            tracing::debug!(
+                recorded = %interaction.model,
+                requested = model,
+                "Replay model differs from recording"
+            );
+        }
+        if interaction.messages.last().map(|m| &m.content) != messages.last().map(|m| &m.content) {
+            tracing::debug!(cassette = %self.source, "Replay request differs from recording");
+        }
+        Ok(interaction.response)
+    }
+}
+
+#[async_trait]
+impl Provider for ReplayProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.capabilities.clone()
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<String> {
+        let messages = one_shot_messages(system_prompt, message);
+        Ok(self.next(model, &messages)?.text.unwrap_or_default())
+    }
+
+    async fn chat_with_history(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok(self.next(model, messages)?.text.unwrap_or_default())
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        _tools: &[serde_json::Value],
+        model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.next(model, messages)
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest<'_>,
+        model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.next(model, request.messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::traits::TokenUsage;
+    use crate::providers::ToolCall;
+    use tempfile::TempDir;
+
+    struct FixedProvider;
+
+    #[async_trait]
+    impl Provider for FixedProvider {
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                native_tool_calling: true,
+                vision: false,
+                structured_output: false,
+            }
+        }
+
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(format!("echo: {message}"))
+        }
+
+        async fn chat(
+            &self,
+            _request: ChatRequest<'_>,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            Ok(ChatResponse {
+                text: None,
+                tool_calls: vec![ToolCall {
+                    id: "call_1".into(),
+                    name: "shell".into(),
+                    arguments: r#"{"command":"ls"}"#.into(),
+                }],
+                usage: Some(TokenUsage {
+                    input_tokens: Some(12),
+                    output_tokens: Some(3),
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
+                }),
+                reasoning: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_cassette_replays_tool_calls_and_usage() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("cassettes").join("run.jsonl");
+        let recorder = RecordingProvider::new(Box::new(FixedProvider), &path);
+
+        let messages = [ChatMessage::user("list files")];
+        let tools = [ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: Some(&tools),
+            output_schema: None,
+        };
+        recorder.chat(request, "model-a", 0.0).await.unwrap();
+        recorder.simple_chat("hi", "model-a", 0.0).await.unwrap();
+
+        let interactions = load_cassette(&path).unwrap();
+        assert_eq!(interactions.len(), 2);
+        assert_eq!(interactions[0].tools[0]["name"], "shell");
+        assert!(interactions[0].native_tools);
+
+        let replay = ReplayProvider::from_file(&path).unwrap();
+        assert!(replay.supports_native_tools());
+        let response = replay.chat(request, "model-a", 0.0).await.unwrap();
+        assert_eq!(response.tool_calls[0].name, "shell");
+        assert_eq!(response.usage.unwrap().input_tokens, Some(12));
+        assert_eq!(
+            replay.simple_chat("hi", "model-a", 0.0).await.unwrap(),
+            "echo: hi"
+        );
+        assert_eq!(replay.remaining(), 0);
+
+        let err = replay
+            .simple_chat("again", "model-a", 0.0)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exhausted after 2 interactions"));
+    }
+
+    #[tokio::test]
+    async fn recorder_reports_capabilities_of_reliable_inner_provider() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("reliable.jsonl");
+        let reliable = crate::providers::reliable::ReliableProvider::new(
+            vec![("fixed".into(), Box::new(FixedProvider))],
+            0,
+            50,
+        );
+        let recorder = RecordingProvider::new(Box::new(reliable), &path);
+        assert!(recorder.supports_native_tools());
+
+        let messages = [ChatMessage::user("list files")];
+        let request = ChatRequest {
+            messages: &messages,
+            tools: None,
+            output_schema: None,
+        };
+        recorder.chat(request, "model-a", 0.0).await.unwrap();
+        assert!(load_cassette(&path).unwrap()[0].native_tools);
+    }
+
+    #[test]
+    fn load_cassette_reports_bad_lines() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("bad.jsonl");
+        std::fs::write(&path, "\nnot json\n").unwrap();
+        let err = load_cassette(&path).unwrap_err();
+        assert!(err.to_string().contains("bad.jsonl:2"));
+    }
+}
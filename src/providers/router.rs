@@ -1,13 +1,170 @@
-use super::traits::{ChatMessage, ChatRequest, ChatResponse};
+use super::catalog;
+use super::traits::{ChatMessage, ChatRequest, ChatResponse, StreamEvent, StreamResult};
 use super::Provider;
+use crate::agent::context;
+use crate::config::schema::{ModelPricing, RoutingPolicyConfig, RoutingPreference};
+use crate::config::Config;
+use crate::cost::CostGuard;
+use crate::observability::{Observer, ObserverEvent};
 use async_trait::async_trait;
+use futures_util::stream;
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Output tokens assumed when comparing route prices for one request.
+const EXPECTED_OUTPUT_TOKENS: f64 = 1_024.0;
 
 /// A single route: maps a task hint to a provider + model combo.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Route {
     pub provider_name: String,
     pub model: String,
+    /// Context window in tokens; inferred from the model name when unset.
+    pub context_window: Option<usize>,
+    /// Vision support; taken from the provider when unset.
+    pub vision: Option<bool>,
+    /// Native tool calling; taken from the provider when unset.
+    pub native_tools: Option<bool>,
+}
+
+/// A route with its provider resolved and capabilities filled in.
+#[derive(Debug, Clone)]
+struct ResolvedRoute {
+    provider_index: usize,
+    model: String,
+    context_window: usize,
+    vision: bool,
+    native_tools: bool,
+}
+
+/// Inputs for `[[routing_policies]]` beyond the route table itself.
+#[derive(Clone, Default)]
+pub struct RoutingOptions {
+    pub policies: Vec<RoutingPolicyConfig>,
+    /// `[cost.prices]`, used by `prefer = "cheapest"`.
+    pub prices: HashMap<String, ModelPricing>,
+    /// Daily budget source for `low_budget_percent`.
+    pub cost_guard: Option<CostGuard>,
+    /// Receives a [`ObserverEvent::RouteSelected`] per policy decision.
+    pub observer: Option<Arc<dyn Observer>>,
+}
+
+impl RoutingOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            policies: config.routing_policies.clone(),
+            prices: config.cost.prices.clone(),
+            cost_guard: CostGuard::from_config(config),
+            observer: None,
+        }
+    }
+
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+}
+
+/// What a request needs from the model that serves it.
+#[derive(Debug, Clone, Copy, Default)]
+struct RequestNeeds {
+    prompt_tokens: usize,
+    vision: bool,
+    native_tools: bool,
+    failed_tool_rounds: usize,
+}
+
+impl RequestNeeds {
+    fn from_prompt(system_prompt: Option<&str>, message: &str) -> Self {
+        Self {
+            prompt_tokens: context::estimate_text_tokens(message)
+                + system_prompt.map_or(0, context::estimate_text_tokens),
+            ..Self::default()
+        }
+    }
+
+    fn from_messages(messages: &[ChatMessage], tool_tokens: Option<usize>) -> Self {
+        Self {
+            prompt_tokens: context::estimate_history_tokens(messages) + tool_tokens.unwrap_or(0),
+            vision: crate::multimodal::contains_image_markers(messages),
+            native_tools: tool_tokens.is_some(),
+            failed_tool_rounds: trailing_failed_tool_rounds(messages),
+        }
+    }
+
+    fn for_request(request: &ChatRequest<'_>) -> Self {
+        let tool_tokens = request
+            .tools
+            .filter(|tools| !tools.is_empty())
+            .map(context::estimate_tool_tokens);
+        Self::from_messages(request.messages, tool_tokens)
+    }
+}
+
+fn is_failed_tool_output(output: &str) -> bool {
+    let output = output.trim_start();
+    output.starts_with("Error") || output.starts_with("Unknown tool")
+}
+
+/// Outputs of one prompt-guided `[Tool results]` message.
+fn prompt_tool_outputs(content: &str) -> Vec<&str> {
+    content
+        .split("<tool_result")
+        .skip(1)
+        .map(|block| {
+            let body = block.split_once('>').map_or(block, |(_, body)| body);
+            body.split("</tool_result>").next().unwrap_or(body)
+        })
+        .collect()
+}
+
+/// Number of most recent tool rounds in which every tool call failed.
+fn trailing_failed_tool_rounds(messages: &[ChatMessage]) -> usize {
+    let mut rounds = 0;
+    let mut rest = messages;
+
+    loop {
+        let native = rest.iter().rev().take_while(|m| m.role == "tool").count();
+        let (outputs, remaining): (Vec<String>, _) = if native > 0 {
+            let (before, results) = rest.split_at(rest.len() - native);
+            let outputs = results
+                .iter()
+                .map(|m| {
+                    serde_json::from_str::<serde_json::Value>(&m.content)
+                        .ok()
+                        .and_then(|v| v.get("content")?.as_str().map(str::to_string))
+                        .unwrap_or_else(|| m.content.clone())
+                })
+                .collect();
+            (outputs, before)
+        } else {
+            match rest.split_last() {
+                Some((last, before))
+                    if last.role == "user" && last.content.starts_with("[Tool results]") =>
+                {
+                    let outputs = prompt_tool_outputs(&last.content)
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect();
+                    (outputs, before)
+                }
+                _ => break,
+            }
+        };
+
+        if outputs.is_empty() || !outputs.iter().all(|o| is_failed_tool_output(o)) {
+            break;
+        }
+        rounds += 1;
+
+        // Step over the assistant message that issued the calls.
+        match remaining.split_last() {
+            Some((call, before)) if call.role == "assistant" => rest = before,
+            _ => break,
+        }
+    }
+
+    rounds
 }
 
 /// Multi-model router — routes requests to different provider+model combos
@@ -16,10 +173,18 @@ pub struct Route {
 /// The model parameter can be:
 /// - A regular model name (e.g. "anthropic/claude-sonnet-4") → uses default provider
 /// - A hint-prefixed string (e.g. "hint:reasoning") → resolves via route table
+/// - A routing policy name (e.g. "hint:vision-cheap") → picks a route per request
+///   from prompt size, required capabilities, price, latency and remaining budget
 ///
 /// This wraps multiple pre-created providers and selects the right one per request.
 pub struct RouterProvider {
-    routes: HashMap<String, (usize, String)>, // hint → (provider_index, model)
+    routes: HashMap<String, ResolvedRoute>,
+    /// Route hints in configuration order.
+    route_order: Vec<String>,
+    policies: HashMap<String, RoutingPolicyConfig>,
+    prices: HashMap<String, ModelPricing>,
+    cost_guard: Option<CostGuard>,
+    observer: Option<Arc<dyn Observer>>,
     providers: Vec<(String, Box<dyn Provider>)>,
     default_index: usize,
     default_model: String,
@@ -43,12 +208,33 @@ impl RouterProvider {
             .collect();
 
         // Resolve routes to provider indices
-        let resolved_routes: HashMap<String, (usize, String)> = routes
+        let mut route_order = Vec::new();
+        let resolved_routes: HashMap<String, ResolvedRoute> = routes
             .into_iter()
             .filter_map(|(hint, route)| {
                 let index = name_to_index.get(route.provider_name.as_str()).copied();
                 match index {
-                    Some(i) => Some((hint, (i, route.model))),
+                    Some(i) => {
+                        let provider = &providers[i].1;
+                        let capabilities = catalog::capabilities_for(
+                            &route.provider_name,
+                            &route.model,
+                            provider.as_ref(),
+                        );
+                        let resolved = ResolvedRoute {
+                            provider_index: i,
+                            context_window: route
+                                .context_window
+                                .unwrap_or_else(|| context::context_window_for_model(&route.model)),
+                            vision: route.vision.unwrap_or(capabilities.vision),
+                            native_tools: route
+                                .native_tools
+                                .unwrap_or(capabilities.native_tool_calling),
+                            model: route.model,
+                        };
+                        route_order.push(hint.clone());
+                        Some((hint, resolved))
+                    }
                     None => {
                         tracing::warn!(
                             hint = hint,
@@ -63,21 +249,62 @@ impl RouterProvider {
 
         Self {
             routes: resolved_routes,
+            route_order,
+            policies: HashMap::new(),
+            prices: HashMap::new(),
+            cost_guard: None,
+            observer: None,
             providers,
             default_index: 0,
             default_model,
         }
     }
 
-    /// Resolve a model parameter to a (provider, actual_model) pair.
+    /// Add routing policies, pricing, budget and observer.
+    pub fn with_routing(mut self, options: RoutingOptions) -> Self {
+        for policy in options.policies {
+            for hint in policy.candidates.iter().chain(&policy.escalate_to) {
+                if !self.routes.contains_key(hint) {
+                    tracing::warn!(
+                        policy = policy.name,
+                        hint = hint.as_str(),
+                        "Routing policy references unknown route"
+                    );
+                }
+            }
+            self.policies.insert(policy.name.clone(), policy);
+        }
+        self.prices = options.prices;
+        self.cost_guard = options.cost_guard;
+        self.observer = options.observer;
+        self
+    }
+
+    /// Resolve a model parameter to a (provider_index, actual_model) pair.
     ///
     /// If the model starts with "hint:", look up the hint in the route table.
     /// Otherwise, use the default provider with the given model name.
-    /// Resolve a model parameter to a (provider_index, actual_model) pair.
     fn resolve(&self, model: &str) -> (usize, String) {
+        self.resolve_for(model, &RequestNeeds::default())
+    }
+
+    /// Like [`Self::resolve`], but routing policies take the request into account.
+    fn resolve_for(&self, model: &str, needs: &RequestNeeds) -> (usize, String) {
         if let Some(hint) = model.strip_prefix("hint:") {
-            if let Some((idx, resolved_model)) = self.routes.get(hint) {
-                return (*idx, resolved_model.clone());
+            if let Some(route) = self.routes.get(hint) {
+                return (route.provider_index, route.model.clone());
+            }
+            if let Some(policy) = self.policies.get(hint) {
+                if let Some((route_hint, reason)) = self.select(policy, needs) {
+                    let route = &self.routes[route_hint];
+                    self.record_decision(hint, route, reason);
+                    return (route.provider_index, route.model.clone());
+                }
+                tracing::warn!(
+                    policy = hint,
+                    "Routing policy has no usable routes, falling back to default provider"
+                );
+                return (self.default_index, self.default_model.clone());
             }
             tracing::warn!(
                 hint = hint,
@@ -88,6 +315,144 @@ impl RouterProvider {
         // Not a hint or hint not found — use default provider with the model as-is
         (self.default_index, model.to_string())
     }
+
+    /// Pick a route for `policy`, returning its hint and the reason it won.
+    fn select<'a>(
+        &'a self,
+        policy: &'a RoutingPolicyConfig,
+        needs: &RequestNeeds,
+    ) -> Option<(&'a str, &'static str)> {
+        if let Some(target) = policy.escalate_to.as_deref() {
+            if needs.failed_tool_rounds >= policy.escalate_after.max(1)
+                && self.routes.contains_key(target)
+            {
+                return Some((target, "escalated"));
+            }
+        }
+
+        let candidates: Vec<&str> = if policy.candidates.is_empty() {
+            self.route_order.iter().map(String::as_str).collect()
+        } else {
+            policy
+                .candidates
+                .iter()
+                .map(String::as_str)
+                .filter(|hint| self.routes.contains_key(*hint))
+                .collect()
+        };
+        let first = *candidates.first()?;
+
+        let needs_vision = policy.require_vision || needs.vision;
+        let needs_tools = policy.require_tools || needs.native_tools;
+        let min_context = policy
+            .min_context_tokens
+            .unwrap_or(0)
+            .max(needs.prompt_tokens);
+        let mut eligible: Vec<&str> = candidates
+            .into_iter()
+            .filter(|hint| {
+                let route = &self.routes[*hint];
+                (!needs_vision || route.vision)
+                    && (!needs_tools || route.native_tools)
+                    && route.context_window >= min_context
+            })
+            .collect();
+        if eligible.is_empty() {
+            tracing::warn!(
+                policy = policy.name,
+                vision = needs_vision,
+                tools = needs_tools,
+                min_context,
+                "No route satisfies routing policy requirements, using first candidate"
+            );
+            return Some((first, "no_match"));
+        }
+
+        let (preference, reason) = if self.budget_is_low(policy) {
+            (RoutingPreference::Cheapest, "low_budget")
+        } else {
+            match policy.prefer {
+                RoutingPreference::First => (RoutingPreference::First, "first"),
+                RoutingPreference::Cheapest => (RoutingPreference::Cheapest, "cheapest"),
+                RoutingPreference::Fastest => (RoutingPreference::Fastest, "fastest"),
+            }
+        };
+
+        // Stable sorts keep configured order among ties; open circuits go last.
+        match preference {
+            RoutingPreference::First => {}
+            RoutingPreference::Cheapest => eligible.sort_by(|a, b| {
+                let cost = |hint: &str| self.estimated_cost(hint, needs.prompt_tokens);
+                cost(a).total_cmp(&cost(b))
+            }),
+            RoutingPreference::Fastest => {
+                eligible.sort_by_key(|hint| self.observed_latency_ms(hint).unwrap_or(u64::MAX));
+            }
+        }
+        eligible.sort_by_key(|hint| self.circuit_open(hint));
+
+        eligible.first().map(|hint| (*hint, reason))
+    }
+
+    fn budget_is_low(&self, policy: &RoutingPolicyConfig) -> bool {
+        let (Some(percent), Some(guard)) = (policy.low_budget_percent, &self.cost_guard) else {
+            return false;
+        };
+        match guard.daily_budget_remaining() {
+            Ok(remaining) => remaining * 100.0 < f64::from(percent),
+            Err(e) => {
+                tracing::debug!("Could not read remaining daily budget: {e}");
+                false
+            }
+        }
+    }
+
+    /// Estimated USD cost of sending the request to a route; unpriced routes
+    /// rank last rather than being treated as free.
+    fn estimated_cost(&self, hint: &str, prompt_tokens: usize) -> f64 {
+        let route = &self.routes[hint];
+        let provider_name = &self.providers[route.provider_index].0;
+        match crate::cost::resolve_pricing(&self.prices, provider_name, &route.model) {
+            #[allow(clippy::cast_precision_loss)]
+            Some(pricing) => {
+                (prompt_tokens as f64 * pricing.input + EXPECTED_OUTPUT_TOKENS * pricing.output)
+                    / 1_000_000.0
+            }
+            None => f64::INFINITY,
+        }
+    }
+
+    fn observed_latency_ms(&self, hint: &str) -> Option<u64> {
+        let route = &self.routes[hint];
+        let provider_name = &self.providers[route.provider_index].0;
+        crate::health::provider_health(provider_name, &route.model)?.latency_ms
+    }
+
+    fn circuit_open(&self, hint: &str) -> bool {
+        let route = &self.routes[hint];
+        let provider_name = &self.providers[route.provider_index].0;
+        crate::health::provider_health(provider_name, &route.model)
+            .is_some_and(|health| health.circuit == "open")
+    }
+
+    fn record_decision(&self, hint: &str, route: &ResolvedRoute, reason: &str) {
+        let provider_name = &self.providers[route.provider_index].0;
+        tracing::info!(
+            policy = hint,
+            provider = provider_name.as_str(),
+            model = route.model.as_str(),
+            reason,
+            "Routing policy selected route"
+        );
+        if let Some(observer) = &self.observer {
+            observer.record_event(&ObserverEvent::RouteSelected {
+                hint: hint.to_string(),
+                provider: provider_name.clone(),
+                model: route.model.clone(),
+                reason: reason.to_string(),
+            });
+        }
+    }
 }
 
 #[async_trait]
@@ -99,7 +464,8 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let needs = RequestNeeds::from_prompt(system_prompt, message);
+        let (provider_idx, resolved_model) = self.resolve_for(model, &needs);
 
         let (provider_name, provider) = &self.providers[provider_idx];
         tracing::info!(
@@ -119,7 +485,8 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let needs = RequestNeeds::from_messages(messages, None);
+        let (provider_idx, resolved_model) = self.resolve_for(model, &needs);
         let (_, provider) = &self.providers[provider_idx];
         provider
             .chat_with_history(messages, &resolved_model, temperature)
@@ -132,11 +499,24 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let needs = RequestNeeds::for_request(&request);
+        let (provider_idx, resolved_model) = self.resolve_for(model, &needs);
         let (_, provider) = &self.providers[provider_idx];
         provider.chat(request, &resolved_model, temperature).await
     }
 
+    fn stream_chat<'a>(
+        &'a self,
+        request: ChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+    ) -> stream::BoxStream<'a, StreamResult<StreamEvent>> {
+        let needs = RequestNeeds::for_request(&request);
+        let (provider_idx, resolved_model) = self.resolve_for(model, &needs);
+        let (_, provider) = &self.providers[provider_idx];
+        provider.stream_chat(request, &resolved_model, temperature)
+    }
+
     async fn chat_with_tools(
         &self,
         messages: &[ChatMessage],
@@ -144,7 +524,14 @@ impl Provider for RouterProvider {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let tool_tokens = (!tools.is_empty()).then(|| {
+            tools
+                .iter()
+                .map(|tool| context::estimate_text_tokens(&tool.to_string()))
+                .sum()
+        });
+        let needs = RequestNeeds::from_messages(messages, tool_tokens);
+        let (provider_idx, resolved_model) = self.resolve_for(model, &needs);
         let (_, provider) = &self.providers[provider_idx];
         provider
             .chat_with_tools(messages, tools, &resolved_model, temperature)
@@ -164,6 +551,13 @@ impl Provider for RouterProvider {
             .any(|(_, provider)| provider.supports_vision())
     }
 
+    fn supports_structured_output(&self) -> bool {
+        self.providers
+            .get(self.default_index)
+            .map(|(_, p)| p.supports_structured_output())
+            .unwrap_or(false)
+    }
+
     async fn warmup(&self) -> anyhow::Result<()> {
         for (name, provider) in &self.providers {
             tracing::info!(provider = name, "Warming up routed provider");
@@ -248,6 +642,7 @@ mod tests {
                     Route {
                         provider_name: provider_name.to_string(),
                         model: model.to_string(),
+                        ..Route::default()
                     },
                 )
             })
@@ -418,6 +813,7 @@ mod tests {
         let messages = vec![ChatMessage {
             role: "user".to_string(),
             content: "use tools".to_string(),
+            parts: Vec::new(),
         }];
         let tools = vec![serde_json::json!({
             "type": "function",
@@ -449,6 +845,7 @@ mod tests {
         let messages = vec![ChatMessage {
             role: "user".to_string(),
             content: "reason about this".to_string(),
+            parts: Vec::new(),
         }];
         let tools = vec![serde_json::json!({"type": "function", "function": {"name": "test"}})];
 
@@ -461,4 +858,223 @@ mod tests {
         assert_eq!(mocks[1].last_model(), "claude-opus");
         assert_eq!(mocks[0].call_count(), 0);
     }
+
+    struct RecordingObserver {
+        events: parking_lot::Mutex<Vec<(String, String, String)>>,
+    }
+
+    impl Observer for RecordingObserver {
+        fn record_event(&self, event: &ObserverEvent) {
+            if let ObserverEvent::RouteSelected {
+                hint,
+                model,
+                reason,
+                ..
+            } = event
+            {
+                self.events
+                    .lock()
+                    .push((hint.clone(), model.clone(), reason.clone()));
+            }
+        }
+
+        fn record_metric(&self, _metric: &crate::observability::traits::ObserverMetric) {}
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn policy(name: &str, prefer: RoutingPreference) -> RoutingPolicyConfig {
+        RoutingPolicyConfig {
+            name: name.into(),
+            candidates: Vec::new(),
+            prefer,
+            require_vision: false,
+            require_tools: false,
+            min_context_tokens: None,
+            low_budget_percent: None,
+            escalate_to: None,
+            escalate_after: 2,
+        }
+    }
+
+    /// Routes: `small` (8k, text only, cheap), `vision` (128k, vision, mid),
+    /// `strong` (200k, vision, expensive).
+    fn make_policy_router(
+        policies: Vec<RoutingPolicyConfig>,
+        observer: Option<Arc<dyn Observer>>,
+    ) -> RouterProvider {
+        let providers: Vec<(String, Box<dyn Provider>)> = vec![
+            ("p-default".into(), Box::new(MockProvider::new("default"))),
+            ("p-small".into(), Box::new(MockProvider::new("small"))),
+            ("p-vision".into(), Box::new(MockProvider::new("vision"))),
+            ("p-strong".into(), Box::new(MockProvider::new("strong"))),
+        ];
+        let route = |provider: &str, model: &str, window: usize, vision: bool| Route {
+            provider_name: provider.into(),
+            model: model.into(),
+            context_window: Some(window),
+            vision: Some(vision),
+            native_tools: Some(true),
+        };
+        let routes = vec![
+            (
+                "strong".into(),
+                route("p-strong", "strong-model", 200_000, true),
+            ),
+            (
+                "vision".into(),
+                route("p-vision", "vision-model", 128_000, true),
+            ),
+            (
+                "small".into(),
+                route("p-small", "small-model", 8_000, false),
+            ),
+        ];
+        let prices = HashMap::from([
+            (
+                "strong-model".to_string(),
+                ModelPricing {
+                    input: 15.0,
+                    output: 75.0,
+                    ..ModelPricing::default()
+                },
+            ),
+            (
+                "vision-model".to_string(),
+                ModelPricing {
+                    input: 2.5,
+                    output: 10.0,
+                    ..ModelPricing::default()
+                },
+            ),
+            (
+                "p-small/small-model".to_string(),
+                ModelPricing {
+                    input: 0.1,
+                    output: 0.4,
+                    ..ModelPricing::default()
+                },
+            ),
+        ]);
+
+        RouterProvider::new(providers, routes, "default-model".into()).with_routing(
+            RoutingOptions {
+                policies,
+                prices,
+                cost_guard: None,
+                observer,
+            },
+        )
+    }
+
+    #[test]
+    fn cheapest_policy_picks_lowest_priced_route_and_emits_event() {
+        let observer = Arc::new(RecordingObserver {
+            events: parking_lot::Mutex::new(Vec::new()),
+        });
+        let router = make_policy_router(
+            vec![policy("cheap", RoutingPreference::Cheapest)],
+            Some(observer.clone()),
+        );
+
+        let (idx, model) = router.resolve("hint:cheap");
+        assert_eq!(idx, 1);
+        assert_eq!(model, "small-model");
+        assert_eq!(
+            observer.events.lock().as_slice(),
+            [(
+                "cheap".to_string(),
+                "small-model".to_string(),
+                "cheapest".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn first_policy_keeps_candidate_order() {
+        let mut first = policy("ordered", RoutingPreference::First);
+        first.candidates = vec!["vision".into(), "strong".into()];
+        let router = make_policy_router(vec![first], None);
+
+        assert_eq!(router.resolve("hint:ordered").1, "vision-model");
+    }
+
+    #[test]
+    fn policy_filters_routes_by_vision_and_prompt_size() {
+        let router = make_policy_router(vec![policy("cheap", RoutingPreference::Cheapest)], None);
+
+        let image = vec![ChatMessage::user(
+            "what is this [IMAGE:data:image/png;base64,iVBORw0KGgo=]",
+        )];
+        let needs = RequestNeeds::from_messages(&image, None);
+        assert_eq!(router.resolve_for("hint:cheap", &needs).1, "vision-model");
+
+        let long = vec![ChatMessage::user("x".repeat(200_000))];
+        let needs = RequestNeeds::from_messages(&long, None);
+        assert_eq!(router.resolve_for("hint:cheap", &needs).1, "vision-model");
+
+        let mut vision_required = policy("cheap-vision", RoutingPreference::Cheapest);
+        vision_required.require_vision = true;
+        vision_required.min_context_tokens = Some(150_000);
+        let router = make_policy_router(vec![vision_required], None);
+        assert_eq!(router.resolve("hint:cheap-vision").1, "strong-model");
+    }
+
+    #[test]
+    fn policy_escalates_after_failed_tool_rounds() {
+        let mut auto = policy("auto", RoutingPreference::Cheapest);
+        auto.escalate_to = Some("strong".into());
+        let router = make_policy_router(vec![auto], None);
+
+        let failed_round = |id: &str| {
+            [
+                ChatMessage::assistant(format!("{{\"tool_calls\":[{{\"id\":\"{id}\"}}]}}")),
+                ChatMessage::tool(
+                    serde_json::json!({"tool_call_id": id, "content": "Error: command failed"})
+                        .to_string(),
+                ),
+            ]
+        };
+        let mut history = vec![ChatMessage::user("fix the build")];
+        history.extend(failed_round("a"));
+        let needs = RequestNeeds::from_messages(&history, None);
+        assert_eq!(router.resolve_for("hint:auto", &needs).1, "small-model");
+
+        history.extend(failed_round("b"));
+        let needs = RequestNeeds::from_messages(&history, None);
+        assert_eq!(router.resolve_for("hint:auto", &needs).1, "strong-model");
+    }
+
+    #[test]
+    fn trailing_failed_tool_rounds_stops_at_successful_round() {
+        let history = vec![
+            ChatMessage::user("task"),
+            ChatMessage::assistant("<tool_call>a</tool_call>"),
+            ChatMessage::user("[Tool results]\n<tool_result name=\"shell\">\nok\n</tool_result>\n"),
+            ChatMessage::assistant("<tool_call>b</tool_call>"),
+            ChatMessage::user(
+                "[Tool results]\n<tool_result name=\"shell\">\nError: boom\n</tool_result>\n\
+                 <tool_result name=\"nope\">\nUnknown tool: nope\n</tool_result>\n",
+            ),
+        ];
+        assert_eq!(trailing_failed_tool_rounds(&history), 1);
+        assert_eq!(trailing_failed_tool_rounds(&history[..3]), 0);
+    }
+
+    #[test]
+    fn unknown_policy_candidates_fall_back_to_default() {
+        let mut broken = policy("broken", RoutingPreference::First);
+        broken.candidates = vec!["missing".into()];
+        let router = make_policy_router(vec![broken], None);
+
+        let (idx, model) = router.resolve("hint:broken");
+        assert_eq!(idx, 0);
+        assert_eq!(model, "default-model");
+    }
 }
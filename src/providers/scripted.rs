@@ -0,0 +1,281 @@
+//! Provider that answers from a fixed script of responses.
+//!
+//! Scripts are YAML (`.yaml` / `.yml`) or JSON files selected with
+//! `scripted:<path>`:
+//!
+//! ```yaml
+//! native_tools: true
+//! responses:
+//!   - tool_calls:
+//!       - name: shell
+//!         arguments: { command: "ls" }
+//!   - text: "The workspace has a README."
+//!   - error: "simulated provider outage"
+//! ```
+//!
+//! Each call consumes the next response; a step with `error` fails that call
+//! instead. Running past the end of the script is an error.
+
+use super::traits::{ChatMessage, ChatRequest, ChatResponse, ProviderCapabilities, TokenUsage};
+use super::{Provider, ToolCall};
+use anyhow::Context;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// A script of canned responses.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Script {
+    /// Report native tool calling, so the agent loop sends tool schemas
+    /// instead of prompt-guided instructions. Default: `true`.
+    #[serde(default = "default_native_tools")]
+    pub native_tools: bool,
+    #[serde(default)]
+    pub vision: bool,
+    #[serde(default)]
+    pub responses: Vec<ScriptStep>,
+}
+
+fn default_native_tools() -> bool {
+    true
+}
+
+/// One scripted reply.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScriptStep {
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<ScriptToolCall>,
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+    /// Fail this call with the given message instead of replying.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A scripted tool call. `arguments` may be an object or a JSON string;
+/// `id` defaults to `call_<step>_<index>`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptToolCall {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+impl Script {
+    /// Load a script, parsing `.yaml` / `.yml` files as YAML and anything
+    /// else as JSON.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read provider script: {}", path.display()))?;
+        let is_yaml = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+        if is_yaml {
+            serde_yaml_ng::from_str(&raw)
+                .with_context(|| format!("Invalid YAML provider script: {}", path.display()))
+        } else {
+            serde_json::from_str(&raw)
+                .with_context(|| format!("Invalid JSON provider script: {}", path.display()))
+        }
+    }
+}
+
+impl ScriptStep {
+    fn into_response(self, step: usize) -> anyhow::Result<ChatResponse> {
+        if let Some(error) = self.error {
+            anyhow::bail!(error);
+        }
+        let tool_calls = self
+            .tool_calls
+            .into_iter()
+            .enumerate()
+            .map(|(idx, call)| ToolCall {
+                id: call.id.unwrap_or_else(|| format!("call_{step}_{idx}")),
+                name: call.name,
+                arguments: match call.arguments {
+                    serde_json::Value::Null => "{}".to_string(),
+                    serde_json::Value::String(raw) => raw,
+                    other => other.to_string(),
+                },
+            })
+            .collect();
+        Ok(ChatResponse {
+            text: self.text,
+            tool_calls,
+            usage: self.usage,
+            reasoning: None,
+        })
+    }
+}
+
+/// Serves [`Script`] responses in order.
+pub struct ScriptedProvider {
+    capabilities: ProviderCapabilities,
+    steps: Mutex<VecDeque<ScriptStep>>,
+    total: usize,
+}
+
+impl ScriptedProvider {
+    pub fn new(script: Script) -> Self {
+        Self {
+            capabilities: ProviderCapabilities {
+                native_tool_calling: script.native_tools,
+                vision: script.vision,
+                structured_output: false,
+            },
+            total: script.responses.len(),
+            steps: Mutex::new(script.responses.into()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        Script::from_file(path).map(Self::new)
+    }
+
+    /// Script of plain text replies.
+    pub fn from_texts<I, S>(texts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::new(Script {
+            native_tools: true,
+            vision: false,
+            responses: texts
+                .into_iter()
+                .map(|text| ScriptStep {
+                    text: Some(text.into()),
+                    ..ScriptStep::default()
+                })
+                .collect(),
+        })
+    }
+
+    /// Responses not yet served.
+    pub fn remaining(&self) -> usize {
+        self.steps.lock().len()
+    }
+
+    fn next(&self) -> anyhow::Result<ChatResponse> {
+        let mut steps = self.steps.lock();
+        let step = self.total - steps.len();
+        let Some(next) = steps.pop_front() else {
+            anyhow::bail!("Scripted provider exhausted after {} responses", self.total);
+        };
+        drop(steps);
+        next.into_response(step)
+    }
+}
+
+#[async_trait]
+impl Provider for ScriptedProvider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.capabilities.clone()
+    }
+
+    async fn chat_with_system(
+        &self,
+        _system_prompt: Option<&str>,
+        _message: &str,
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok(self.next()?.text.unwrap_or_default())
+    }
+
+    async fn chat_with_history(
+        &self,
+        _messages: &[ChatMessage],
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok(self.next()?.text.unwrap_or_default())
+    }
+
+    async fn chat_with_tools(
+        &self,
+        _messages: &[ChatMessage],
+        _tools: &[serde_json::Value],
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.next()
+    }
+
+    async fn chat(
+        &self,
+        _request: ChatRequest<'_>,
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const YAML_SCRIPT: &str = r#"
+native_tools: true
+responses:
+  - tool_calls:
+      - name: shell
+        arguments: { command: "ls" }
+      - id: custom
+        name: file_read
+        arguments: '{"path":"README.md"}'
+    usage: { input_tokens: 10, output_tokens: 2 }
+  - text: "done"
+  - error: "simulated outage"
+"#;
+
+    #[tokio::test]
+    async fn yaml_script_serves_tool_calls_text_and_errors_in_order() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("script.yaml");
+        std::fs::write(&path, YAML_SCRIPT).unwrap();
+        let provider = ScriptedProvider::from_file(&path).unwrap();
+        assert!(provider.supports_native_tools());
+
+        let request = ChatRequest {
+            messages: &[ChatMessage::user("go")],
+            tools: None,
+            output_schema: None,
+        };
+        let first = provider.chat(request, "m", 0.0).await.unwrap();
+        assert_eq!(first.tool_calls[0].id, "call_0_0");
+        assert_eq!(first.tool_calls[0].arguments, r#"{"command":"ls"}"#);
+        assert_eq!(first.tool_calls[1].id, "custom");
+        assert_eq!(first.tool_calls[1].arguments, r#"{"path":"README.md"}"#);
+        assert_eq!(first.usage.unwrap().output_tokens, Some(2));
+
+        assert_eq!(
+            provider.simple_chat("next", "m", 0.0).await.unwrap(),
+            "done"
+        );
+        let err = provider.chat(request, "m", 0.0).await.unwrap_err();
+        assert_eq!(err.to_string(), "simulated outage");
+
+        let err = provider.chat(request, "m", 0.0).await.unwrap_err();
+        assert!(err.to_string().contains("exhausted after 3 responses"));
+    }
+
+    #[test]
+    fn json_script_defaults_to_native_tools() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("script.json");
+        std::fs::write(&path, r#"{"responses":[{"text":"hi"}]}"#).unwrap();
+        let provider = ScriptedProvider::from_file(&path).unwrap();
+        assert!(provider.supports_native_tools());
+        assert_eq!(provider.remaining(), 1);
+    }
+}
@@ -0,0 +1,423 @@
+//! Shared decoding for streamed provider responses.
+//!
+//! [`decode_response`] splits a response body into lines and feeds them to a
+//! [`StreamDecoder`], which turns provider wire events into [`StreamEvent`]s.
+//! [`OpenAiChunkDecoder`] handles the `chat.completion.chunk` SSE format used
+//! by OpenAI and OpenAI-compatible endpoints.
+
+use super::traits::{StreamError, StreamEvent, StreamResult, TokenUsage};
+use futures_util::{stream, StreamExt};
+use serde::Deserialize;
+use std::collections::{BTreeSet, VecDeque};
+use std::future::Future;
+
+/// Turns one line of a streamed response body into events.
+pub(crate) trait StreamDecoder: Send {
+    /// Decode one complete line (without the trailing newline).
+    fn decode_line(&mut self, line: &str) -> StreamResult<Vec<StreamEvent>>;
+
+    /// Called once the body ends; flushes events still pending.
+    fn finish(&mut self) -> Vec<StreamEvent>;
+}
+
+/// Strip the `data:` field prefix from an SSE line. Other fields (`event:`,
+/// `id:`, comments) yield `None`.
+pub(crate) fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim)
+}
+
+struct DecodeState<D> {
+    body: stream::BoxStream<'static, reqwest::Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+    pending: VecDeque<StreamResult<StreamEvent>>,
+    decoder: D,
+    done: bool,
+}
+
+impl<D: StreamDecoder> DecodeState<D> {
+    fn decode_buffered_lines(&mut self) {
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let raw: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&raw);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                continue;
+            }
+            match self.decoder.decode_line(line) {
+                Ok(events) => self.pending.extend(events.into_iter().map(Ok)),
+                Err(e) => {
+                    self.pending.push_back(Err(e));
+                    self.done = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if !self.buffer.is_empty() {
+            self.buffer.push(b'\n');
+            self.decode_buffered_lines();
+        }
+        if !self.done {
+            self.pending
+                .extend(self.decoder.finish().into_iter().map(Ok));
+        }
+        self.done = true;
+    }
+}
+
+/// Decode a successful streaming response body line by line.
+pub(crate) fn decode_response<'a, D>(
+    response: reqwest::Response,
+    decoder: D,
+) -> stream::BoxStream<'a, StreamResult<StreamEvent>>
+where
+    D: StreamDecoder + 'a,
+{
+    let state = DecodeState {
+        body: response
+            .bytes_stream()
+            .map(|chunk| chunk.map(|bytes| bytes.to_vec()))
+            .boxed(),
+        buffer: Vec::new(),
+        pending: VecDeque::new(),
+        decoder,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((event, state));
+            }
+            if state.done {
+                return None;
+            }
+            match state.body.next().await {
+                Some(Ok(bytes)) => {
+                    state.buffer.extend_from_slice(&bytes);
+                    state.decode_buffered_lines();
+                }
+                Some(Err(e)) => {
+                    state.pending.push_back(Err(StreamError::Http(e)));
+                    state.done = true;
+                }
+                None => state.finish(),
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Flatten a future that opens an event stream (sending the request and
+/// checking its status) into the stream itself.
+pub(crate) fn open_stream<'a, F>(open: F) -> stream::BoxStream<'a, StreamResult<StreamEvent>>
+where
+    F: Future<Output = StreamResult<stream::BoxStream<'a, StreamResult<StreamEvent>>>> + Send + 'a,
+{
+    stream::once(open)
+        .flat_map(|opened| match opened {
+            Ok(events) => events,
+            Err(e) => stream::once(async move { Err(e) }).boxed(),
+        })
+        .boxed()
+}
+
+// ── OpenAI chat.completion.chunk ─────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct ChunkResponse {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    #[serde(default)]
+    usage: Option<ChunkUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: Option<ChunkDelta>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+    /// DeepSeek/Qwen-style reasoning stream.
+    #[serde(default)]
+    reasoning_content: Option<String>,
+    /// OpenRouter's name for the same stream.
+    #[serde(default)]
+    reasoning: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ChunkToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkToolCall {
+    #[serde(default)]
+    index: Option<usize>,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: Option<ChunkFunction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkFunction {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkUsage {
+    #[serde(default)]
+    prompt_tokens: Option<u64>,
+    #[serde(default)]
+    completion_tokens: Option<u64>,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+/// Cached prefix tokens, reported as a subset of `prompt_tokens`.
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u64>,
+}
+
+/// Decoder for OpenAI-style `chat.completion.chunk` server-sent events.
+///
+/// Tool calls stay open until a `finish_reason` arrives; usage (sent after
+/// the finish chunk when `stream_options.include_usage` is set) and the
+/// finish event are emitted at `[DONE]` or the end of the body.
+#[derive(Debug, Default)]
+pub(crate) struct OpenAiChunkDecoder {
+    open_calls: BTreeSet<usize>,
+    usage: Option<TokenUsage>,
+    finish_reason: Option<String>,
+    finished: bool,
+}
+
+impl OpenAiChunkDecoder {
+    fn close_calls(&mut self, events: &mut Vec<StreamEvent>) {
+        for index in std::mem::take(&mut self.open_calls) {
+            events.push(StreamEvent::ToolCallEnd { index });
+        }
+    }
+}
+
+impl StreamDecoder for OpenAiChunkDecoder {
+    fn decode_line(&mut self, line: &str) -> StreamResult<Vec<StreamEvent>> {
+        let Some(data) = sse_data(line) else {
+            return Ok(Vec::new());
+        };
+        if data == "[DONE]" {
+            return Ok(self.finish());
+        }
+
+        let chunk: ChunkResponse = serde_json::from_str(data).map_err(StreamError::Json)?;
+        let mut events = Vec::new();
+
+        if let Some(usage) = chunk.usage {
+            self.usage = Some(TokenUsage {
+                input_tokens: usage.prompt_tokens,
+                output_tokens: usage.completion_tokens,
+                cache_read_tokens: usage.prompt_tokens_details.and_then(|d| d.cached_tokens),
+                cache_write_tokens: None,
+            });
+        }
+
+        let Some(choice) = chunk.choices.into_iter().next() else {
+            return Ok(events);
+        };
+
+        if let Some(delta) = choice.delta {
+            if let Some(reasoning) = delta
+                .reasoning_content
+                .or(delta.reasoning)
+                .filter(|r| !r.is_empty())
+            {
+                events.push(StreamEvent::ReasoningDelta(reasoning));
+            }
+            if let Some(content) = delta.content.filter(|c| !c.is_empty()) {
+                events.push(StreamEvent::TextDelta(content));
+            }
+            for (position, call) in delta.tool_calls.into_iter().flatten().enumerate() {
+                let index = call.index.unwrap_or(position);
+                let (name, arguments) = call
+                    .function
+                    .map(|f| (f.name, f.arguments))
+                    .unwrap_or_default();
+                if self.open_calls.insert(index) {
+                    events.push(StreamEvent::ToolCallStart {
+                        index,
+                        id: call.id.unwrap_or_default(),
+                        name: name.unwrap_or_default(),
+                    });
+                }
+                if let Some(arguments) = arguments.filter(|a| !a.is_empty()) {
+                    events.push(StreamEvent::ToolCallDelta { index, arguments });
+                }
+            }
+        }
+
+        if let Some(reason) = choice.finish_reason {
+            self.close_calls(&mut events);
+            self.finish_reason = Some(reason);
+        }
+
+        Ok(events)
+    }
+
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = true;
+
+        let mut events = Vec::new();
+        self.close_calls(&mut events);
+        if let Some(usage) = self.usage.take() {
+            events.push(StreamEvent::Usage(usage));
+        }
+        events.push(StreamEvent::Finish(self.finish_reason.take()));
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::traits::StreamAccumulator;
+
+    fn decode_all(decoder: &mut impl StreamDecoder, lines: &[&str]) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        for line in lines {
+            events.extend(decoder.decode_line(line).unwrap());
+        }
+        events.extend(decoder.finish());
+        events
+    }
+
+    #[test]
+    fn openai_decoder_separates_reasoning_from_text() {
+        let mut decoder = OpenAiChunkDecoder::default();
+        let events = decode_all(
+            &mut decoder,
+            &[
+                r#"data: {"choices":[{"delta":{"reasoning_content":"think "}}]}"#,
+                r#"data: {"choices":[{"delta":{"reasoning":"more"}}]}"#,
+                r#"data: {"choices":[{"delta":{"content":"Answer"},"finish_reason":"stop"}]}"#,
+                "data: [DONE]",
+            ],
+        );
+        let mut acc = StreamAccumulator::default();
+        for event in &events {
+            acc.push(event);
+        }
+        let response = acc.into_response();
+        assert_eq!(response.text.as_deref(), Some("Answer"));
+        assert_eq!(response.reasoning.unwrap().text, "think more");
+    }
+
+    #[test]
+    fn openai_decoder_emits_text_and_finish() {
+        let mut decoder = OpenAiChunkDecoder::default();
+        let events = decode_all(
+            &mut decoder,
+            &[
+                r#"data: {"choices":[{"delta":{"role":"assistant","content":""}}]}"#,
+                r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#,
+                ": keep-alive",
+                r#"data: {"choices":[{"delta":{"content":"lo"},"finish_reason":"stop"}]}"#,
+                "data: [DONE]",
+            ],
+        );
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::TextDelta("Hel".into()),
+                StreamEvent::TextDelta("lo".into()),
+                StreamEvent::Finish(Some("stop".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn openai_decoder_reassembles_parallel_tool_calls_and_usage() {
+        let mut decoder = OpenAiChunkDecoder::default();
+        let events = decode_all(
+            &mut decoder,
+            &[
+                r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_a","type":"function","function":{"name":"shell","arguments":""}}]}}]}"#,
+                r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"command\":"}}]}}]}"#,
+                r#"data: {"choices":[{"delta":{"tool_calls":[{"index":1,"id":"call_b","function":{"name":"file_read","arguments":"{\"path\":\"a\"}"}}]}}]}"#,
+                r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"date\"}"}}]}}]}"#,
+                r#"data: {"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+                r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":7}}"#,
+                "data: [DONE]",
+            ],
+        );
+
+        assert_eq!(
+            events[0],
+            StreamEvent::ToolCallStart {
+                index: 0,
+                id: "call_a".into(),
+                name: "shell".into(),
+            }
+        );
+        assert!(events.contains(&StreamEvent::ToolCallEnd { index: 1 }));
+        assert_eq!(
+            events[events.len() - 2],
+            StreamEvent::Usage(TokenUsage {
+                input_tokens: Some(12),
+                output_tokens: Some(7),
+                cache_read_tokens: None,
+                cache_write_tokens: None,
+            })
+        );
+
+        let mut acc = StreamAccumulator::default();
+        for event in &events {
+            acc.push(event);
+        }
+        assert_eq!(acc.finish_reason(), Some("tool_calls"));
+        let response = acc.into_response();
+        assert!(response.text.is_none());
+        assert_eq!(response.tool_calls.len(), 2);
+        assert_eq!(response.tool_calls[0].id, "call_a");
+        assert_eq!(response.tool_calls[0].arguments, r#"{"command":"date"}"#);
+        assert_eq!(response.tool_calls[1].name, "file_read");
+        assert_eq!(response.usage.unwrap().output_tokens, Some(7));
+    }
+
+    #[test]
+    fn openai_decoder_finishes_once_without_done_sentinel() {
+        let mut decoder = OpenAiChunkDecoder::default();
+        let events = decode_all(
+            &mut decoder,
+            &[r#"data: {"choices":[{"delta":{"content":"hi"},"finish_reason":"length"}]}"#],
+        );
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::Finish(Some("length".into())))
+        );
+        assert!(decoder.finish().is_empty());
+    }
+
+    #[test]
+    fn openai_decoder_rejects_malformed_json() {
+        let mut decoder = OpenAiChunkDecoder::default();
+        assert!(matches!(
+            decoder.decode_line("data: {not json"),
+            Err(StreamError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,598 @@
+//! JSON-schema constrained responses.
+//!
+//! An [`OutputSchema`] travels with a [`ChatRequest`] and asks the provider for
+//! a final answer that is a single JSON document matching the schema.
+//! Providers with native support map it onto their API (OpenAI
+//! `response_format`, Gemini `responseSchema`, Anthropic tool forcing, Ollama
+//! `format`); everything else gets the schema as system-prompt instructions.
+//! Either way callers check the answer with [`OutputSchema::parse_response`]
+//! and send [`OutputSchema::repair_prompt`] back when it does not validate.
+
+use super::traits::{ChatMessage, ChatRequest, Provider};
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::path::Path;
+
+/// How many times a caller re-asks the model after an invalid answer.
+pub const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// A JSON schema the final answer must satisfy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputSchema {
+    /// Identifier sent to providers that require one (`[a-zA-Z0-9_-]`, ≤64 chars).
+    pub name: String,
+    pub schema: Value,
+}
+
+impl OutputSchema {
+    /// Wrap a schema document. The name comes from its `title`, falling back
+    /// to `"response"`.
+    pub fn new(schema: Value) -> Result<Self> {
+        let name = schema
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("response")
+            .to_string();
+        Self::named(name, schema)
+    }
+
+    /// Wrap a schema document under an explicit name.
+    pub fn named(name: impl AsRef<str>, schema: Value) -> Result<Self> {
+        if !schema.is_object() {
+            bail!("Output schema must be a JSON object");
+        }
+        Ok(Self {
+            name: sanitize_name(name.as_ref()),
+            schema,
+        })
+    }
+
+    /// Load a schema from a JSON file. The file stem names it unless the
+    /// schema has a `title`.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read output schema: {}", path.display()))?;
+        let schema: Value = serde_json::from_str(&raw)
+            .with_context(|| format!("Invalid JSON in output schema: {}", path.display()))?;
+        let name = schema
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map(|stem| stem.trim_end_matches(".schema").to_string())
+            })
+            .unwrap_or_else(|| "response".into());
+        Self::named(name, schema)
+    }
+
+    /// System-prompt text for providers without native structured output.
+    pub fn instructions(&self) -> String {
+        let schema = serde_json::to_string_pretty(&self.schema).unwrap_or_default();
+        format!(
+            "## Response Format\n\n\
+             When you give your final answer, reply with a single JSON document that \
+             validates against this JSON schema. Do not wrap it in Markdown or add \
+             any text before or after it.\n\n```json\n{schema}\n```"
+        )
+    }
+
+    /// OpenAI-style `response_format` payload (also accepted by OpenRouter).
+    pub fn openai_response_format(&self) -> Value {
+        serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {"name": self.name, "schema": self.schema},
+        })
+    }
+
+    /// Follow-up user message after an answer failed to parse or validate.
+    pub fn repair_prompt(&self, error: &str) -> String {
+        format!(
+            "Your previous answer does not match the required JSON schema: {error}\n\
+             Reply again with only the corrected JSON document."
+        )
+    }
+
+    /// Extract the JSON document from a model answer and validate it.
+    /// The error string is suitable for [`Self::repair_prompt`].
+    pub fn parse_response(&self, text: &str) -> Result<Value, String> {
+        let value =
+            extract_json(text).ok_or_else(|| "the answer is not a JSON document".to_string())?;
+        let errors = validate(&self.schema, &value);
+        if errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+fn sanitize_name(raw: &str) -> String {
+    let name: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect();
+    if name.is_empty() {
+        "response".into()
+    } else {
+        name
+    }
+}
+
+/// Copy of `messages` with the schema instructions appended to the system
+/// message (or prepended as one).
+pub fn with_schema_instructions(
+    messages: &[ChatMessage],
+    schema: &OutputSchema,
+) -> Vec<ChatMessage> {
+    let instructions = schema.instructions();
+    let mut messages = messages.to_vec();
+    if let Some(system) = messages.iter_mut().find(|m| m.role == "system") {
+        if !system.content.is_empty() {
+            system.content.push_str("\n\n");
+        }
+        system.content.push_str(&instructions);
+    } else {
+        messages.insert(0, ChatMessage::system(instructions));
+    }
+    messages
+}
+
+/// Messages to send for `request` on a provider that overrides `chat`
+/// without native structured output: `None` when no schema is attached.
+pub fn prompt_guided_messages(request: &ChatRequest<'_>) -> Option<Vec<ChatMessage>> {
+    request
+        .output_schema
+        .map(|schema| with_schema_instructions(request.messages, schema))
+}
+
+/// Parse the JSON document in a model answer, tolerating Markdown code fences
+/// and prose around a single object or array.
+pub fn extract_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    if let Some(start) = trimmed.find("```") {
+        let body = &trimmed[start + 3..];
+        let body = body.split_once('\n').map_or(body, |(_, rest)| rest);
+        if let Some(end) = body.find("```") {
+            if let Ok(value) = serde_json::from_str(body[..end].trim()) {
+                return Some(value);
+            }
+        }
+    }
+
+    let start = trimmed.find(['{', '['])?;
+    let end = trimmed.rfind(['}', ']'])?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&trimmed[start..=end]).ok()
+}
+
+/// Send `messages` with `schema` attached and re-ask up to
+/// [`MAX_REPAIR_ATTEMPTS`] times until the answer validates. Returns the raw
+/// answer text and the parsed document.
+pub async fn chat_with_repair(
+    provider: &dyn Provider,
+    messages: &[ChatMessage],
+    schema: &OutputSchema,
+    model: &str,
+    temperature: f64,
+) -> Result<(String, Value)> {
+    let mut history = messages.to_vec();
+    for attempt in 0..=MAX_REPAIR_ATTEMPTS {
+        let response = provider
+            .chat(
+                ChatRequest {
+                    messages: &history,
+                    tools: None,
+                    output_schema: Some(schema),
+                },
+                model,
+                temperature,
+            )
+            .await?;
+        let text = response.text.unwrap_or_default();
+        match schema.parse_response(&text) {
+            Ok(value) => return Ok((text, value)),
+            Err(error) if attempt < MAX_REPAIR_ATTEMPTS => {
+                tracing::debug!(attempt, %error, "Structured answer failed validation; retrying");
+                history.push(ChatMessage::assistant(text));
+                history.push(ChatMessage::user(schema.repair_prompt(&error)));
+            }
+            Err(error) => {
+                bail!(
+                    "Answer does not match output schema '{}': {error}",
+                    schema.name
+                )
+            }
+        }
+    }
+    unreachable!("repair loop always returns")
+}
+
+/// Validate `value` against a JSON schema. Supports the keywords providers
+/// accept for structured output: `type`, `enum`, `const`, `properties`,
+/// `required`, `additionalProperties`, `items`, `anyOf`/`oneOf`/`allOf`,
+/// string length and `pattern`, numeric bounds and array length. `$ref` and
+/// unknown keywords are ignored. Returns one message per violation.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(schema, value, "$", &mut errors);
+    errors
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{path}: no value is allowed here"));
+        }
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::String(name) => type_matches(name, value),
+            Value::Array(names) => names
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|name| type_matches(name, value)),
+            _ => true,
+        };
+        if !matches {
+            errors.push(format!(
+                "{path}: expected {}, got {}",
+                type_label(expected),
+                json_type(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            errors.push(format!(
+                "{path}: {value} is not one of {}",
+                Value::Array(allowed.clone())
+            ));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            errors.push(format!("{path}: expected {constant}"));
+        }
+    }
+
+    for key in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(options)) = schema.get(key) {
+            if !options
+                .iter()
+                .any(|option| validate(option, value).is_empty())
+            {
+                errors.push(format!("{path}: does not match any allowed shape"));
+            }
+        }
+    }
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        for part in all {
+            validate_at(part, value, path, errors);
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(key) {
+                        errors.push(format!("{path}: missing required property '{key}'"));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, item) in map {
+                let child = format!("{path}.{key}");
+                match properties.and_then(|props| props.get(key)) {
+                    Some(property) => validate_at(property, item, &child, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{path}: unexpected property '{key}'"));
+                        }
+                        Some(extra @ Value::Object(_)) => {
+                            validate_at(extra, item, &child, errors);
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if (items.len() as u64) < min {
+                    errors.push(format!("{path}: expected at least {min} items"));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if (items.len() as u64) > max {
+                    errors.push(format!("{path}: expected at most {max} items"));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (idx, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{path}[{idx}]"), errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let len = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if len < min {
+                    errors.push(format!("{path}: shorter than {min} characters"));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if len > max {
+                    errors.push(format!("{path}: longer than {max} characters"));
+                }
+            }
+            if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                if let Ok(re) = regex::Regex::new(pattern) {
+                    if !re.is_match(text) {
+                        errors.push(format!("{path}: does not match pattern {pattern}"));
+                    }
+                }
+            }
+        }
+        Value::Number(number) => {
+            if let Some(n) = number.as_f64() {
+                if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                    if n < min {
+                        errors.push(format!("{path}: {n} is below minimum {min}"));
+                    }
+                }
+                if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                    if n > max {
+                        errors.push(format!("{path}: {n} is above maximum {max}"));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_label(expected: &Value) -> String {
+    match expected {
+        Value::String(name) => name.clone(),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.to_string(),
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Reduce a JSON schema to the OpenAPI subset Gemini's `responseSchema`
+/// accepts: unsupported keywords are dropped and `type: [T, "null"]` becomes
+/// `type: T, nullable: true`.
+pub fn gemini_response_schema(schema: &Value) -> Value {
+    const KEEP: &[&str] = &[
+        "type",
+        "format",
+        "description",
+        "nullable",
+        "enum",
+        "properties",
+        "required",
+        "items",
+        "minItems",
+        "maxItems",
+        "minimum",
+        "maximum",
+        "anyOf",
+        "propertyOrdering",
+    ];
+    let Some(map) = schema.as_object() else {
+        return schema.clone();
+    };
+    let mut out = serde_json::Map::new();
+    for (key, value) in map {
+        if !KEEP.contains(&key.as_str()) {
+            continue;
+        }
+        let value = match key.as_str() {
+            "type" => match value {
+                Value::Array(names) => {
+                    let non_null: Vec<&Value> =
+                        names.iter().filter(|name| *name != "null").collect();
+                    if non_null.len() < names.len() {
+                        out.insert("nullable".into(), Value::Bool(true));
+                    }
+                    non_null
+                        .first()
+                        .map_or(Value::from("string"), |t| (*t).clone())
+                }
+                other => other.clone(),
+            },
+            "properties" => Value::Object(
+                value
+                    .as_object()
+                    .map(|props| {
+                        props
+                            .iter()
+                            .map(|(name, prop)| (name.clone(), gemini_response_schema(prop)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ),
+            "items" => gemini_response_schema(value),
+            "anyOf" => Value::Array(
+                value
+                    .as_array()
+                    .map(|options| options.iter().map(gemini_response_schema).collect())
+                    .unwrap_or_default(),
+            ),
+            _ => value.clone(),
+        };
+        out.insert(key.clone(), value);
+    }
+    Value::Object(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn person() -> OutputSchema {
+        OutputSchema::new(json!({
+            "title": "Person record",
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "role": {"enum": ["admin", "user"]}
+            },
+            "required": ["name", "age"],
+            "additionalProperties": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn name_is_sanitized_from_title() {
+        assert_eq!(person().name, "Person_record");
+        assert!(OutputSchema::new(json!("string")).is_err());
+    }
+
+    #[test]
+    fn validate_reports_each_violation_with_path() {
+        let schema = person();
+        assert!(schema
+            .parse_response(r#"{"name":"Ada","age":36,"tags":["x"],"role":"admin"}"#)
+            .is_ok());
+
+        let err = schema
+            .parse_response(r#"{"name":"","age":-1,"tags":[1],"role":"root","extra":true}"#)
+            .unwrap_err();
+        assert!(err.contains("$.name: shorter than 1"));
+        assert!(err.contains("$.age: -1 is below minimum 0"));
+        assert!(err.contains("$.tags[0]: expected string, got integer"));
+        assert!(err.contains("$.role: \"root\" is not one of"));
+        assert!(err.contains("unexpected property 'extra'"));
+
+        let err = schema.parse_response(r#"{"name":"Ada"}"#).unwrap_err();
+        assert_eq!(err, "$: missing required property 'age'");
+    }
+
+    #[test]
+    fn extract_json_tolerates_fences_and_prose() {
+        assert_eq!(extract_json("{\"a\":1}"), Some(json!({"a": 1})));
+        assert_eq!(
+            extract_json("Here you go:\n```json\n{\"a\": [1, 2]}\n```\nDone."),
+            Some(json!({"a": [1, 2]}))
+        );
+        assert_eq!(
+            extract_json("Result: {\"ok\": true} — hope that helps"),
+            Some(json!({"ok": true}))
+        );
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn schema_instructions_extend_existing_system_message() {
+        let schema = person();
+        let messages = with_schema_instructions(
+            &[ChatMessage::system("Be terse."), ChatMessage::user("hi")],
+            &schema,
+        );
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0]
+            .content
+            .starts_with("Be terse.\n\n## Response Format"));
+
+        let messages = with_schema_instructions(&[ChatMessage::user("hi")], &schema);
+        assert_eq!(messages[0].role, "system");
+    }
+
+    #[test]
+    fn gemini_schema_drops_unsupported_keywords() {
+        let converted = gemini_response_schema(&json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {"note": {"type": ["string", "null"], "pattern": "x"}},
+            "required": ["note"]
+        }));
+        assert_eq!(
+            converted,
+            json!({
+                "type": "object",
+                "properties": {"note": {"type": "string", "nullable": true}},
+                "required": ["note"]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_with_repair_retries_until_valid() {
+        let provider = crate::providers::scripted::ScriptedProvider::from_texts([
+            "not json",
+            r#"{"name":"Ada","age":36}"#,
+        ]);
+        let (text, value) = chat_with_repair(
+            &provider,
+            &[ChatMessage::user("who?")],
+            &person(),
+            "test",
+            0.0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text, r#"{"name":"Ada","age":36}"#);
+        assert_eq!(value["age"], 36);
+
+        let provider = crate::providers::scripted::ScriptedProvider::from_texts(["a", "b", "c"]);
+        let err = chat_with_repair(&provider, &[ChatMessage::user("who?")], &person(), "m", 0.0)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match output schema"));
+    }
+}
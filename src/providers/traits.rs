@@ -1,3 +1,4 @@
+use super::structured::OutputSchema;
 use crate::tools::ToolSpec;
 use async_trait::async_trait;
 use futures_util::{stream, StreamExt};
@@ -5,42 +6,175 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Write;
 
 /// A single message in a conversation.
+///
+/// `content` is always the plain-text form of the message. When `parts` is
+/// non-empty it is the authoritative content and `content` holds its text
+/// rendering (images as `[IMAGE:...]` markers), so text-only consumers and
+/// histories written before typed parts existed keep working.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<ContentPart>,
 }
 
 impl ChatMessage {
     pub fn system(content: impl Into<String>) -> Self {
-        Self {
-            role: "system".into(),
-            content: content.into(),
-        }
+        Self::text("system", content)
     }
 
     pub fn user(content: impl Into<String>) -> Self {
-        Self {
-            role: "user".into(),
-            content: content.into(),
-        }
+        Self::text("user", content)
     }
 
     pub fn assistant(content: impl Into<String>) -> Self {
+        Self::text("assistant", content)
+    }
+
+    pub fn tool(content: impl Into<String>) -> Self {
+        Self::text("tool", content)
+    }
+
+    fn text(role: &str, content: impl Into<String>) -> Self {
         Self {
-            role: "assistant".into(),
+            role: role.into(),
             content: content.into(),
+            parts: Vec::new(),
         }
     }
 
-    pub fn tool(content: impl Into<String>) -> Self {
+    /// Build a message from typed content parts.
+    pub fn with_parts(role: impl Into<String>, parts: Vec<ContentPart>) -> Self {
         Self {
-            role: "tool".into(),
-            content: content.into(),
+            role: role.into(),
+            content: render_parts_as_text(&parts),
+            parts,
+        }
+    }
+
+    /// Content as typed parts. Messages without explicit parts are parsed for
+    /// legacy `[IMAGE:...]` markers.
+    pub fn content_parts(&self) -> Vec<ContentPart> {
+        if self.parts.is_empty() {
+            crate::multimodal::parts_from_markers(&self.content)
+        } else {
+            self.parts.clone()
+        }
+    }
+
+    /// Whether the message carries anything besides text.
+    pub fn has_media(&self) -> bool {
+        self.content_parts()
+            .iter()
+            .any(|part| !matches!(part, ContentPart::Text { .. }))
+    }
+}
+
+/// One typed piece of message content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    Image {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        source: MediaSource,
+    },
+    Audio {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        source: MediaSource,
+    },
+    /// A file the model should know about but that is not inlined.
+    File {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+    },
+}
+
+impl ContentPart {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    pub fn image_base64(mime_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self::Image {
+            mime_type: Some(mime_type.into()),
+            source: MediaSource::Base64(data.into()),
+        }
+    }
+
+    pub fn image_url(url: impl Into<String>) -> Self {
+        Self::Image {
+            mime_type: None,
+            source: MediaSource::Url(url.into()),
+        }
+    }
+
+    /// Placeholder text for providers that cannot take this part natively.
+    pub fn fallback_text(&self) -> String {
+        match self {
+            Self::Text { text } => text.clone(),
+            Self::Image { source, .. } => format!("[IMAGE:{}]", source.describe()),
+            Self::Audio { source, .. } => format!("[AUDIO:{}]", source.describe()),
+            Self::File { path, .. } => format!("[FILE:{path}]"),
         }
     }
 }
 
+/// Where the bytes of an image or audio part live.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaSource {
+    /// Inline payload, base64-encoded.
+    Base64(String),
+    Url(String),
+}
+
+impl MediaSource {
+    /// `data:` URI for inline payloads, the URL otherwise.
+    pub fn to_uri(&self, mime_type: Option<&str>) -> String {
+        match self {
+            Self::Base64(data) => format!(
+                "data:{};base64,{data}",
+                mime_type.unwrap_or("application/octet-stream")
+            ),
+            Self::Url(url) => url.clone(),
+        }
+    }
+
+    fn describe(&self) -> &str {
+        match self {
+            Self::Base64(_) => "inline",
+            Self::Url(url) => url,
+        }
+    }
+}
+
+fn render_parts_as_text(parts: &[ContentPart]) -> String {
+    let mut text = String::new();
+    for part in parts {
+        let piece = match part {
+            ContentPart::Image { mime_type, source } => {
+                format!("[IMAGE:{}]", source.to_uri(mime_type.as_deref()))
+            }
+            other => other.fallback_text(),
+        };
+        if piece.is_empty() {
+            continue;
+        }
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(&piece);
+    }
+    text
+}
+
 /// A tool call requested by the LLM.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
@@ -50,21 +184,44 @@ pub struct ToolCall {
 }
 
 /// Raw token counts from a single LLM API response.
-#[derive(Debug, Clone, Default)]
+///
+/// `input_tokens` is the full prompt size; `cache_read_tokens` and
+/// `cache_write_tokens` are the parts of it served from or written to the
+/// provider's prompt cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TokenUsage {
     pub input_tokens: Option<u64>,
     pub output_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<u64>,
 }
 
 /// An LLM response that may contain text, tool calls, or both.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatResponse {
     /// Text content of the response (may be empty if only tool calls).
     pub text: Option<String>,
     /// Tool calls requested by the LLM.
+    #[serde(default)]
     pub tool_calls: Vec<ToolCall>,
     /// Token usage reported by the provider, if available.
+    #[serde(default)]
     pub usage: Option<TokenUsage>,
+    /// Reasoning the model produced before answering, kept apart from `text`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<Reasoning>,
+}
+
+/// Model reasoning ("thinking") returned alongside an answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reasoning {
+    pub text: String,
+    /// Opaque token the API needs when this reasoning is sent back in a later
+    /// request (Anthropic thinking signatures).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
 impl ChatResponse {
@@ -84,6 +241,8 @@ impl ChatResponse {
 pub struct ChatRequest<'a> {
     pub messages: &'a [ChatMessage],
     pub tools: Option<&'a [ToolSpec]>,
+    /// Schema the final answer must satisfy, if the caller wants structured output.
+    pub output_schema: Option<&'a OutputSchema>,
 }
 
 /// A tool result to feed back to the LLM.
@@ -103,6 +262,9 @@ pub enum ConversationMessage {
     AssistantToolCalls {
         text: Option<String>,
         tool_calls: Vec<ToolCall>,
+        /// Reasoning that accompanied the calls; replayed for providers that require it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reasoning: Option<Reasoning>,
     },
     /// Results of tool executions, fed back to the LLM.
     ToolResults(Vec<ToolResultMessage>),
@@ -201,6 +363,169 @@ pub enum StreamError {
     Io(#[from] std::io::Error),
 }
 
+/// A typed event from a streaming [`Provider::stream_chat`] response.
+///
+/// Tool calls are keyed by `index` so argument fragments of parallel calls
+/// can be reassembled; every started call is closed by a `ToolCallEnd`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// A fragment of assistant text.
+    TextDelta(String),
+    /// A fragment of model reasoning.
+    ReasoningDelta(String),
+    /// Signature for the reasoning streamed so far.
+    ReasoningSignature(String),
+    /// The model started a tool call.
+    ToolCallStart {
+        index: usize,
+        id: String,
+        name: String,
+    },
+    /// A fragment of a tool call's JSON arguments.
+    ToolCallDelta { index: usize, arguments: String },
+    /// The tool call's arguments are complete.
+    ToolCallEnd { index: usize },
+    /// Token usage for the whole response.
+    Usage(TokenUsage),
+    /// The response is complete; carries the provider's finish reason.
+    Finish(Option<String>),
+}
+
+impl ChatResponse {
+    /// Replay a complete response as the events a streaming provider would emit.
+    pub fn into_stream_events(self) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        if let Some(reasoning) = self.reasoning {
+            events.push(StreamEvent::ReasoningDelta(reasoning.text));
+            if let Some(signature) = reasoning.signature {
+                events.push(StreamEvent::ReasoningSignature(signature));
+            }
+        }
+        if let Some(text) = self.text.filter(|t| !t.is_empty()) {
+            events.push(StreamEvent::TextDelta(text));
+        }
+        let finish_reason = if self.tool_calls.is_empty() {
+            "stop"
+        } else {
+            "tool_calls"
+        };
+        for (index, call) in self.tool_calls.into_iter().enumerate() {
+            events.push(StreamEvent::ToolCallStart {
+                index,
+                id: call.id,
+                name: call.name,
+            });
+            events.push(StreamEvent::ToolCallDelta {
+                index,
+                arguments: call.arguments,
+            });
+            events.push(StreamEvent::ToolCallEnd { index });
+        }
+        if let Some(usage) = self.usage {
+            events.push(StreamEvent::Usage(usage));
+        }
+        events.push(StreamEvent::Finish(Some(finish_reason.to_string())));
+        events
+    }
+}
+
+/// Reassembles [`StreamEvent`]s into a [`ChatResponse`].
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    text: String,
+    reasoning: String,
+    reasoning_signature: Option<String>,
+    tool_calls: Vec<(usize, ToolCall)>,
+    usage: Option<TokenUsage>,
+    finish_reason: Option<String>,
+}
+
+impl StreamAccumulator {
+    pub fn push(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::TextDelta(text) => self.text.push_str(text),
+            StreamEvent::ReasoningDelta(text) => self.reasoning.push_str(text),
+            StreamEvent::ReasoningSignature(signature) => {
+                self.reasoning_signature = Some(signature.clone());
+            }
+            StreamEvent::ToolCallStart { index, id, name } => {
+                let id = if id.is_empty() {
+                    uuid::Uuid::new_v4().to_string()
+                } else {
+                    id.clone()
+                };
+                self.tool_calls.push((
+                    *index,
+                    ToolCall {
+                        id,
+                        name: name.clone(),
+                        arguments: String::new(),
+                    },
+                ));
+            }
+            StreamEvent::ToolCallDelta { index, arguments } => {
+                if let Some((_, call)) = self.tool_calls.iter_mut().rfind(|(i, _)| i == index) {
+                    call.arguments.push_str(arguments);
+                }
+            }
+            StreamEvent::ToolCallEnd { .. } => {}
+            StreamEvent::Usage(usage) => self.usage = Some(usage.clone()),
+            StreamEvent::Finish(reason) => self.finish_reason.clone_from(reason),
+        }
+    }
+
+    /// Finish reason reported by the provider, if the stream completed.
+    pub fn finish_reason(&self) -> Option<&str> {
+        self.finish_reason.as_deref()
+    }
+
+    pub fn into_response(self) -> ChatResponse {
+        ChatResponse {
+            text: if self.text.is_empty() {
+                None
+            } else {
+                Some(self.text)
+            },
+            tool_calls: self
+                .tool_calls
+                .into_iter()
+                .map(|(_, mut call)| {
+                    if call.arguments.trim().is_empty() {
+                        call.arguments = "{}".to_string();
+                    }
+                    call
+                })
+                .collect(),
+            usage: self.usage,
+            reasoning: if self.reasoning.is_empty() {
+                None
+            } else {
+                Some(Reasoning {
+                    text: self.reasoning,
+                    signature: self.reasoning_signature,
+                })
+            },
+        }
+    }
+}
+
+/// Turn a pending [`Provider::chat`] call into an event stream, for providers
+/// without native streaming.
+pub fn chat_response_stream<'a, F>(response: F) -> stream::BoxStream<'a, StreamResult<StreamEvent>>
+where
+    F: std::future::Future<Output = anyhow::Result<ChatResponse>> + Send + 'a,
+{
+    stream::once(response)
+        .flat_map(|result| {
+            let events = match result {
+                Ok(response) => response.into_stream_events().into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(StreamError::Provider(e.to_string()))],
+            };
+            stream::iter(events)
+        })
+        .boxed()
+}
+
 /// Structured error returned when a requested capability is not supported.
 #[derive(Debug, Clone, thiserror::Error)]
 #[error("provider_capability_error provider={provider} capability={capability} message={message}")]
@@ -210,6 +535,16 @@ pub struct ProviderCapabilityError {
     pub message: String,
 }
 
+/// Structured error returned when a request does not fit the model's context
+/// window. Retrying elsewhere cannot help; the history has to shrink first.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("context_window_exceeded provider={provider} model={model}: request exceeds the context window; retries and fallbacks were skipped. Attempts:\n{attempts}")]
+pub struct ContextWindowExceededError {
+    pub provider: String,
+    pub model: String,
+    pub attempts: String,
+}
+
 /// Provider capabilities declaration.
 ///
 /// Describes what features a provider supports, enabling intelligent
@@ -225,6 +560,9 @@ pub struct ProviderCapabilities {
     pub native_tool_calling: bool,
     /// Whether the provider supports vision / image inputs.
     pub vision: bool,
+    /// Whether the provider constrains answers to `ChatRequest::output_schema`
+    /// natively. When `false`, the schema is injected into the system prompt.
+    pub structured_output: bool,
 }
 
 /// Provider-specific tool payload formats.
@@ -320,6 +658,17 @@ pub trait Provider: Send + Sync {
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<ChatResponse> {
+        // Without native structured output, the schema travels as instructions.
+        let schema_messages;
+        let mut request = request;
+        if let Some(schema) = request.output_schema {
+            if !self.supports_structured_output() {
+                schema_messages =
+                    super::structured::with_schema_instructions(request.messages, schema);
+                request.messages = &schema_messages;
+            }
+        }
+
         // If tools are provided but provider doesn't support native tools,
         // inject tool instructions into system prompt as fallback.
         if let Some(tools) = request.tools {
@@ -354,6 +703,7 @@ pub trait Provider: Send + Sync {
                     text: Some(text),
                     tool_calls: Vec::new(),
                     usage: None,
+                    reasoning: None,
                 });
             }
         }
@@ -365,6 +715,7 @@ pub trait Provider: Send + Sync {
             text: Some(text),
             tool_calls: Vec::new(),
             usage: None,
+            reasoning: None,
         })
     }
 
@@ -378,6 +729,11 @@ pub trait Provider: Send + Sync {
         self.capabilities().vision
     }
 
+    /// Whether provider honours `ChatRequest::output_schema` natively.
+    fn supports_structured_output(&self) -> bool {
+        self.capabilities().structured_output
+    }
+
     /// Warm up the HTTP connection pool (TLS handshake, DNS, HTTP/2 setup).
     /// Default implementation is a no-op; providers with HTTP clients should override.
     async fn warmup(&self) -> anyhow::Result<()> {
@@ -399,6 +755,7 @@ pub trait Provider: Send + Sync {
             text: Some(text),
             tool_calls: Vec::new(),
             usage: None,
+            reasoning: None,
         })
     }
 
@@ -440,6 +797,19 @@ pub trait Provider: Send + Sync {
         let chunk = StreamChunk::error(format!("{} does not support streaming", provider_name));
         stream::once(async move { Ok(chunk) }).boxed()
     }
+
+    /// Streaming variant of [`Provider::chat`]: yields text, tool-call, usage
+    /// and finish events as they arrive.
+    /// Default implementation replays the result of `chat` as events.
+    fn stream_chat<'a>(
+        &'a self,
+        request: ChatRequest<'a>,
+        model: &str,
+        temperature: f64,
+    ) -> stream::BoxStream<'a, StreamResult<StreamEvent>> {
+        let model = model.to_string();
+        chat_response_stream(async move { self.chat(request, &model, temperature).await })
+    }
 }
 
 /// Build tool instructions text for prompt-guided tool calling.
@@ -487,6 +857,7 @@ mod tests {
             ProviderCapabilities {
                 native_tool_calling: true,
                 vision: true,
+                structured_output: false,
             }
         }
 
@@ -517,12 +888,59 @@ mod tests {
         assert_eq!(tool.role, "tool");
     }
 
+    #[test]
+    fn chat_message_without_parts_keeps_legacy_wire_format() {
+        let legacy: ChatMessage =
+            serde_json::from_str(r#"{"role":"user","content":"hi [IMAGE:/tmp/a.png]"}"#).unwrap();
+        assert!(legacy.parts.is_empty());
+        assert_eq!(
+            legacy.content_parts(),
+            vec![
+                ContentPart::text("hi"),
+                ContentPart::image_url("/tmp/a.png")
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&legacy).unwrap(),
+            serde_json::json!({"role": "user", "content": "hi [IMAGE:/tmp/a.png]"})
+        );
+    }
+
+    #[test]
+    fn chat_message_with_parts_round_trips_and_renders_text() {
+        let message = ChatMessage::with_parts(
+            "user",
+            vec![
+                ContentPart::text("What is this?"),
+                ContentPart::image_base64("image/png", "abcd"),
+                ContentPart::File {
+                    path: "notes.pdf".into(),
+                    mime_type: None,
+                },
+            ],
+        );
+        assert_eq!(
+            message.content,
+            "What is this?\n\n[IMAGE:data:image/png;base64,abcd]\n\n[FILE:notes.pdf]"
+        );
+        assert!(message.has_media());
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            json["parts"][1],
+            serde_json::json!({"type": "image", "mime_type": "image/png", "source": {"base64": "abcd"}})
+        );
+        let decoded: ChatMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.parts, message.parts);
+    }
+
     #[test]
     fn chat_response_helpers() {
         let empty = ChatResponse {
             text: None,
             tool_calls: vec![],
             usage: None,
+            reasoning: None,
         };
         assert!(!empty.has_tool_calls());
         assert_eq!(empty.text_or_empty(), "");
@@ -535,6 +953,7 @@ mod tests {
                 arguments: "{}".into(),
             }],
             usage: None,
+            reasoning: None,
         };
         assert!(with_tools.has_tool_calls());
         assert_eq!(with_tools.text_or_empty(), "Let me check");
@@ -555,7 +974,10 @@ mod tests {
             usage: Some(TokenUsage {
                 input_tokens: Some(100),
                 output_tokens: Some(50),
+                cache_read_tokens: None,
+                cache_write_tokens: None,
             }),
+            reasoning: None,
         };
         assert_eq!(resp.usage.as_ref().unwrap().input_tokens, Some(100));
         assert_eq!(resp.usage.as_ref().unwrap().output_tokens, Some(50));
@@ -599,14 +1021,17 @@ mod tests {
         let caps1 = ProviderCapabilities {
             native_tool_calling: true,
             vision: false,
+            structured_output: false,
         };
         let caps2 = ProviderCapabilities {
             native_tool_calling: true,
             vision: false,
+            structured_output: false,
         };
         let caps3 = ProviderCapabilities {
             native_tool_calling: false,
             vision: false,
+            structured_output: false,
         };
 
         assert_eq!(caps1, caps2);
@@ -766,6 +1191,7 @@ mod tests {
         let request = ChatRequest {
             messages: &[ChatMessage::user("Hello")],
             tools: Some(&tools),
+            output_schema: None,
         };
 
         let response = provider.chat(request, "model", 0.7).await.unwrap();
@@ -783,6 +1209,7 @@ mod tests {
         let request = ChatRequest {
             messages: &[ChatMessage::user("Hello")],
             tools: None,
+            output_schema: None,
         };
 
         let response = provider.chat(request, "model", 0.7).await.unwrap();
@@ -883,6 +1310,7 @@ mod tests {
                 ChatMessage::system("BASE_SYSTEM_PROMPT"),
             ],
             tools: Some(&tools),
+            output_schema: None,
         };
 
         let response = provider.chat(request, "model", 0.7).await.unwrap();
@@ -892,6 +1320,34 @@ mod tests {
         assert!(text.contains("Tool Use Protocol"));
     }
 
+    #[tokio::test]
+    async fn provider_chat_injects_output_schema_without_native_support() {
+        let provider = EchoSystemProvider {
+            supports_native: false,
+        };
+        let schema =
+            OutputSchema::new(serde_json::json!({"type": "object", "required": ["answer"]}))
+                .unwrap();
+
+        let request = ChatRequest {
+            messages: &[
+                ChatMessage::system("BASE_SYSTEM_PROMPT"),
+                ChatMessage::user("Hello"),
+            ],
+            tools: None,
+            output_schema: Some(&schema),
+        };
+
+        let text = provider
+            .chat(request, "model", 0.7)
+            .await
+            .unwrap()
+            .text
+            .unwrap_or_default();
+        assert!(text.starts_with("BASE_SYSTEM_PROMPT\n\n## Response Format"));
+        assert!(text.contains("\"required\": ["));
+    }
+
     #[tokio::test]
     async fn provider_chat_prompt_guided_uses_convert_tools_override() {
         let provider = CustomConvertProvider;
@@ -905,6 +1361,7 @@ mod tests {
         let request = ChatRequest {
             messages: &[ChatMessage::system("BASE"), ChatMessage::user("Hello")],
             tools: Some(&tools),
+            output_schema: None,
         };
 
         let response = provider.chat(request, "model", 0.7).await.unwrap();
@@ -927,6 +1384,7 @@ mod tests {
         let request = ChatRequest {
             messages: &[ChatMessage::user("Hello")],
             tools: Some(&tools),
+            output_schema: None,
         };
 
         let err = provider.chat(request, "model", 0.7).await.unwrap_err();
@@ -934,4 +1392,67 @@ mod tests {
 
         assert!(message.contains("non-prompt-guided"));
     }
+
+    #[test]
+    fn stream_events_round_trip_through_accumulator() {
+        let response = ChatResponse {
+            text: Some("Checking".into()),
+            tool_calls: vec![ToolCall {
+                id: "call_1".into(),
+                name: "shell".into(),
+                arguments: r#"{"command":"date"}"#.into(),
+            }],
+            usage: Some(TokenUsage {
+                input_tokens: Some(10),
+                output_tokens: Some(4),
+                cache_read_tokens: None,
+                cache_write_tokens: None,
+            }),
+            reasoning: None,
+        };
+
+        let events = response.into_stream_events();
+        assert_eq!(
+            events.first(),
+            Some(&StreamEvent::TextDelta("Checking".into()))
+        );
+        assert_eq!(
+            events.last(),
+            Some(&StreamEvent::Finish(Some("tool_calls".into())))
+        );
+
+        let mut acc = StreamAccumulator::default();
+        for event in &events {
+            acc.push(event);
+        }
+        let rebuilt = acc.into_response();
+        assert_eq!(rebuilt.text.as_deref(), Some("Checking"));
+        assert_eq!(rebuilt.tool_calls.len(), 1);
+        assert_eq!(rebuilt.tool_calls[0].arguments, r#"{"command":"date"}"#);
+        assert_eq!(rebuilt.usage.unwrap().output_tokens, Some(4));
+    }
+
+    #[tokio::test]
+    async fn provider_stream_chat_default_replays_chat() {
+        let provider = CapabilityMockProvider;
+        let request = ChatRequest {
+            messages: &[ChatMessage::user("Hello")],
+            tools: None,
+            output_schema: None,
+        };
+
+        let events: Vec<StreamEvent> = provider
+            .stream_chat(request, "model", 0.7)
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::TextDelta("ok".into()),
+                StreamEvent::Finish(Some("stop".into())),
+            ]
+        );
+    }
 }
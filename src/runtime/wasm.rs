@@ -14,6 +14,7 @@
 use super::traits::RuntimeAdapter;
 use crate::config::WasmRuntimeConfig;
 use anyhow::{bail, Context, Result};
+use serde::Deserialize;
 use std::path::{Path, PathBuf};
 
 /// WASM sandbox runtime — executes tool modules in an isolated interpreter.
@@ -34,10 +35,13 @@ pub struct WasmExecutionResult {
     pub exit_code: i32,
     /// Fuel consumed during execution
     pub fuel_consumed: u64,
+    /// Linear memory size at the end of execution, in bytes
+    pub memory_bytes: u64,
 }
 
 /// Capabilities granted to a WASM tool module.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
 pub struct WasmCapabilities {
     /// Allow reading files from workspace
     pub read_workspace: bool,
@@ -129,120 +133,130 @@ impl WasmRuntime {
         mb.saturating_mul(1024 * 1024)
     }
 
-    /// Execute a WASM module from the tools directory.
+    /// Execute a WASM module from the tools directory with empty stdin.
+    pub fn execute_module(
+        &self,
+        module_name: &str,
+        workspace_dir: &Path,
+        caps: &WasmCapabilities,
+    ) -> Result<WasmExecutionResult> {
+        self.execute_module_with_input(module_name, workspace_dir, caps, &[])
+    }
+
+    /// Execute a WASM module from the tools directory, feeding `stdin` to it.
     ///
     /// This is the primary entry point for running sandboxed tool code.
-    /// The module must export a `_start` function (WASI convention) or
-    /// a custom `run` function that takes no arguments and returns i32.
+    /// The module must export a custom `run() -> i32` function or a WASI
+    /// `_start` command entry point. A minimal WASI preview1 surface is
+    /// provided (stdin/stdout/stderr, clocks, random, exit); any other import
+    /// traps when called.
     #[cfg(feature = "runtime-wasm")]
-    pub fn execute_module(
+    pub fn execute_module_with_input(
         &self,
         module_name: &str,
         workspace_dir: &Path,
         caps: &WasmCapabilities,
+        stdin: &[u8],
     ) -> Result<WasmExecutionResult> {
-        use wasmi::{Engine, Linker, Module, Store};
-
-        // Resolve module path
-        let tools_path = self.tools_dir(workspace_dir);
-        let module_path = tools_path.join(format!("{module_name}.wasm"));
-
-        if !module_path.exists() {
-            bail!(
-                "WASM module not found: {} (looked in {})",
-                module_name,
-                tools_path.display()
-            );
-        }
-
-        // Read module bytes
-        let wasm_bytes = std::fs::read(&module_path)
-            .with_context(|| format!("Failed to read WASM module: {}", module_path.display()))?;
+        use wasmi::{Engine, Module, Store, TrapCode};
 
-        // Validate module size (sanity check)
-        if wasm_bytes.len() > 50 * 1024 * 1024 {
-            bail!(
-                "WASM module {} is {} MB — exceeds 50 MB safety limit",
-                module_name,
-                wasm_bytes.len() / (1024 * 1024)
-            );
-        }
+        let wasm_bytes = self.read_module(module_name, workspace_dir)?;
 
         // Configure engine with fuel metering
+        let fuel = self.effective_fuel(caps);
         let mut engine_config = wasmi::Config::default();
-        engine_config.consume_fuel(true);
+        engine_config.consume_fuel(fuel > 0);
         let engine = Engine::new(&engine_config);
 
         // Parse and validate module
         let module = Module::new(&engine, &wasm_bytes[..])
             .with_context(|| format!("Failed to parse WASM module: {module_name}"))?;
 
-        // Create store with fuel budget
-        let mut store = Store::new(&engine, ());
-        let fuel = self.effective_fuel(caps);
+        // Create store with fuel budget and memory ceiling
+        let memory_limit = usize::try_from(self.effective_memory_bytes(caps)).unwrap_or(usize::MAX);
+        let mut store = Store::new(&engine, wasi::WasiState::new(stdin.to_vec(), memory_limit));
+        store.limiter(|state| &mut state.limits);
         if fuel > 0 {
             store.set_fuel(fuel).with_context(|| {
                 format!("Failed to set fuel budget ({fuel}) for module: {module_name}")
             })?;
         }
 
-        // Link host functions (minimal — pure sandboxing)
-        let linker = Linker::new(&engine);
-
-        // Instantiate module
-        let instance = linker
-            .instantiate(&mut store, &module)
-            .and_then(|pre| pre.start(&mut store))
-            .with_context(|| format!("Failed to instantiate WASM module: {module_name}"))?;
-
-        // Look for exported entry point
-        let run_fn = instance
-            .get_typed_func::<(), i32>(&store, "run")
-            .or_else(|_| instance.get_typed_func::<(), i32>(&store, "_start"))
-            .with_context(|| {
-                format!(
-                    "WASM module '{module_name}' must export a 'run() -> i32' or '_start() -> i32' function"
-                )
-            })?;
+        // Link the minimal WASI surface; everything else traps on use
+        let linker = wasi::linker(&engine, &module)?;
+
+        let instance = match linker.instantiate_and_start(&mut store, &module) {
+            Ok(instance) => instance,
+            Err(e) if wasi::is_memory_limit(&e) => {
+                let mut result = store.into_data().into_result(-1, 0, 0);
+                result.stderr = format!(
+                    "WASM module '{module_name}' exceeded memory limit ({memory_limit} bytes)"
+                );
+                return Ok(result);
+            }
+            Err(e) => bail!("Failed to instantiate WASM module '{module_name}': {e}"),
+        };
+
+        let outcome = if let Ok(run) = instance.get_typed_func::<(), i32>(&store, "run") {
+            run.call(&mut store, ())
+        } else if let Ok(start) = instance.get_typed_func::<(), ()>(&store, "_start") {
+            start.call(&mut store, ()).map(|()| 0)
+        } else {
+            bail!("WASM module '{module_name}' must export a 'run() -> i32' or WASI '_start' function");
+        };
 
-        // Execute with fuel accounting
-        let fuel_before = store.get_fuel().unwrap_or(0);
-        let exit_code = match run_fn.call(&mut store, ()) {
+        let fuel_consumed = if fuel > 0 {
+            fuel.saturating_sub(store.get_fuel().unwrap_or(0))
+        } else {
+            0
+        };
+        let memory_bytes = instance
+            .get_memory(&store, "memory")
+            .map_or(0, |memory| memory.data_size(&store) as u64);
+
+        let mut limit_error = None;
+        let exit_code = match outcome {
             Ok(code) => code,
             Err(e) => {
-                // Check if we ran out of fuel (infinite loop protection)
-                let fuel_after = store.get_fuel().unwrap_or(0);
-                if fuel_after == 0 && fuel > 0 {
-                    return Ok(WasmExecutionResult {
-                        stdout: String::new(),
-                        stderr: format!(
-                            "WASM module '{module_name}' exceeded fuel limit ({fuel} ticks) — likely an infinite loop"
-                        ),
-                        exit_code: -1,
-                        fuel_consumed: fuel,
-                    });
+                if let Some(status) = e.i32_exit_status() {
+                    status
+                } else if e.as_trap_code() == Some(TrapCode::OutOfFuel) {
+                    // Infinite loop protection
+                    limit_error = Some(format!(
+                        "WASM module '{module_name}' exceeded fuel limit ({fuel} ticks) — likely an infinite loop"
+                    ));
+                    -1
+                } else if wasi::is_memory_limit(&e) {
+                    limit_error = Some(format!(
+                        "WASM module '{module_name}' exceeded memory limit ({memory_limit} bytes)"
+                    ));
+                    -1
+                } else {
+                    bail!("WASM execution error in '{module_name}': {e}");
                 }
-                bail!("WASM execution error in '{module_name}': {e}");
             }
         };
-        let fuel_after = store.get_fuel().unwrap_or(0);
-        let fuel_consumed = fuel_before.saturating_sub(fuel_after);
 
-        Ok(WasmExecutionResult {
-            stdout: String::new(),  // No WASI stdout yet — pure computation
-            stderr: String::new(),
-            exit_code,
-            fuel_consumed,
-        })
+        let mut result = store
+            .into_data()
+            .into_result(exit_code, fuel_consumed, memory_bytes);
+        if let Some(message) = limit_error {
+            if !result.stderr.is_empty() && !result.stderr.ends_with('\n') {
+                result.stderr.push('\n');
+            }
+            result.stderr.push_str(&message);
+        }
+        Ok(result)
     }
 
     /// Stub for when the `runtime-wasm` feature is not enabled.
     #[cfg(not(feature = "runtime-wasm"))]
-    pub fn execute_module(
+    pub fn execute_module_with_input(
         &self,
         module_name: &str,
         _workspace_dir: &Path,
         _caps: &WasmCapabilities,
+        _stdin: &[u8],
     ) -> Result<WasmExecutionResult> {
         bail!(
             "WASM runtime is not available in this build. \
@@ -251,6 +265,35 @@ impl WasmRuntime {
         )
     }
 
+    /// Read and size-check a module from the tools directory.
+    #[cfg(feature = "runtime-wasm")]
+    fn read_module(&self, module_name: &str, workspace_dir: &Path) -> Result<Vec<u8>> {
+        let tools_path = self.tools_dir(workspace_dir);
+        let module_path = tools_path.join(format!("{module_name}.wasm"));
+
+        if !module_path.exists() {
+            bail!(
+                "WASM module not found: {} (looked in {})",
+                module_name,
+                tools_path.display()
+            );
+        }
+
+        let wasm_bytes = std::fs::read(&module_path)
+            .with_context(|| format!("Failed to read WASM module: {}", module_path.display()))?;
+
+        // Validate module size (sanity check)
+        if wasm_bytes.len() > 50 * 1024 * 1024 {
+            bail!(
+                "WASM module {} is {} MB — exceeds 50 MB safety limit",
+                module_name,
+                wasm_bytes.len() / (1024 * 1024)
+            );
+        }
+
+        Ok(wasm_bytes)
+    }
+
     /// List available WASM tool modules in the tools directory.
     pub fn list_modules(&self, workspace_dir: &Path) -> Result<Vec<String>> {
         let tools_path = self.tools_dir(workspace_dir);
@@ -316,6 +359,336 @@ impl RuntimeAdapter for WasmRuntime {
     }
 }
 
+// ── Minimal WASI preview1 host ──────────────────────────────────
+
+/// Just enough of `wasi_snapshot_preview1` to run command-style tools that
+/// read JSON from stdin and write their answer to stdout. No filesystem,
+/// sockets or environment are exposed; every other import traps when called.
+#[cfg(feature = "runtime-wasm")]
+mod wasi {
+    use super::WasmExecutionResult;
+    use wasmi::errors::{ErrorKind, InstantiationError, MemoryError};
+    use wasmi::{
+        Caller, Engine, Error, Extern, ExternType, Linker, Memory, Module, StoreLimits,
+        StoreLimitsBuilder, TrapCode,
+    };
+
+    const WASI_MODULE: &str = "wasi_snapshot_preview1";
+    /// Cap on captured stdout/stderr per stream; extra output is dropped.
+    const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+    const ERRNO_SUCCESS: i32 = 0;
+    const ERRNO_BADF: i32 = 8;
+    const ERRNO_FAULT: i32 = 21;
+    const ERRNO_INVAL: i32 = 28;
+    const ERRNO_SPIPE: i32 = 70;
+
+    /// Per-invocation host state stored in the wasmi `Store`.
+    pub struct WasiState {
+        stdin: Vec<u8>,
+        stdin_pos: usize,
+        stdout: Vec<u8>,
+        stderr: Vec<u8>,
+        pub limits: StoreLimits,
+    }
+
+    impl WasiState {
+        pub fn new(stdin: Vec<u8>, memory_limit_bytes: usize) -> Self {
+            Self {
+                stdin,
+                stdin_pos: 0,
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(memory_limit_bytes)
+                    .trap_on_grow_failure(true)
+                    .build(),
+            }
+        }
+
+        pub fn into_result(
+            self,
+            exit_code: i32,
+            fuel_consumed: u64,
+            memory_bytes: u64,
+        ) -> WasmExecutionResult {
+            WasmExecutionResult {
+                stdout: String::from_utf8_lossy(&self.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&self.stderr).into_owned(),
+                exit_code,
+                fuel_consumed,
+                memory_bytes,
+            }
+        }
+
+        fn capture(&mut self, fd: i32, bytes: &[u8]) {
+            let sink = if fd == 1 {
+                &mut self.stdout
+            } else {
+                &mut self.stderr
+            };
+            let room = MAX_OUTPUT_BYTES.saturating_sub(sink.len());
+            sink.extend_from_slice(&bytes[..bytes.len().min(room)]);
+        }
+    }
+
+    /// Whether `error` was caused by the store's memory ceiling.
+    pub fn is_memory_limit(error: &Error) -> bool {
+        error.as_trap_code() == Some(TrapCode::GrowthOperationLimited)
+            || matches!(
+                error.kind(),
+                ErrorKind::Memory(MemoryError::ResourceLimiterDeniedAllocation)
+                    | ErrorKind::Instantiation(InstantiationError::FailedToInstantiateMemory(
+                        MemoryError::ResourceLimiterDeniedAllocation
+                    ))
+            )
+    }
+
+    fn memory(caller: &Caller<'_, WasiState>) -> Result<Memory, Error> {
+        caller
+            .get_export("memory")
+            .and_then(Extern::into_memory)
+            .ok_or_else(|| Error::new("WASM module must export 'memory' to use WASI I/O"))
+    }
+
+    fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+        let bytes = data.get(offset..offset.checked_add(4)?)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn write_bytes(data: &mut [u8], offset: i32, bytes: &[u8]) -> i32 {
+        let Ok(offset) = usize::try_from(offset) else {
+            return ERRNO_FAULT;
+        };
+        match offset
+            .checked_add(bytes.len())
+            .and_then(|end| data.get_mut(offset..end))
+        {
+            Some(slot) => {
+                slot.copy_from_slice(bytes);
+                ERRNO_SUCCESS
+            }
+            None => ERRNO_FAULT,
+        }
+    }
+
+    /// Resolve a WASI iovec array into `(offset, len)` pairs within `data`.
+    fn iovecs(data: &[u8], iovs: i32, iovs_len: i32) -> Option<Vec<(usize, usize)>> {
+        let base = usize::try_from(iovs).ok()?;
+        let count = usize::try_from(iovs_len).ok()?;
+        (0..count)
+            .map(|i| {
+                let entry = base.checked_add(i.checked_mul(8)?)?;
+                let ptr = read_u32(data, entry)? as usize;
+                let len = read_u32(data, entry + 4)? as usize;
+                data.get(ptr..ptr.checked_add(len)?)?;
+                Some((ptr, len))
+            })
+            .collect()
+    }
+
+    fn fd_write(
+        mut caller: Caller<'_, WasiState>,
+        fd: i32,
+        iovs: i32,
+        iovs_len: i32,
+        nwritten: i32,
+    ) -> Result<i32, Error> {
+        if fd != 1 && fd != 2 {
+            return Ok(ERRNO_BADF);
+        }
+        let memory = memory(&caller)?;
+        let (data, state) = memory.data_and_store_mut(&mut caller);
+        let Some(chunks) = iovecs(data, iovs, iovs_len) else {
+            return Ok(ERRNO_FAULT);
+        };
+        let mut total = 0usize;
+        for (ptr, len) in chunks {
+            state.capture(fd, &data[ptr..ptr + len]);
+            total += len;
+        }
+        let total = u32::try_from(total).unwrap_or(u32::MAX);
+        Ok(write_bytes(data, nwritten, &total.to_le_bytes()))
+    }
+
+    fn fd_read(
+        mut caller: Caller<'_, WasiState>,
+        fd: i32,
+        iovs: i32,
+        iovs_len: i32,
+        nread: i32,
+    ) -> Result<i32, Error> {
+        if fd != 0 {
+            return Ok(ERRNO_BADF);
+        }
+        let memory = memory(&caller)?;
+        let (data, state) = memory.data_and_store_mut(&mut caller);
+        let Some(chunks) = iovecs(data, iovs, iovs_len) else {
+            return Ok(ERRNO_FAULT);
+        };
+        let mut total = 0usize;
+        for (ptr, len) in chunks {
+            let remaining = &state.stdin[state.stdin_pos..];
+            let n = len.min(remaining.len());
+            data[ptr..ptr + n].copy_from_slice(&remaining[..n]);
+            state.stdin_pos += n;
+            total += n;
+            if n < len {
+                break;
+            }
+        }
+        let total = u32::try_from(total).unwrap_or(u32::MAX);
+        Ok(write_bytes(data, nread, &total.to_le_bytes()))
+    }
+
+    /// Write `(count, buf_size)` = `(0, 0)` for the empty args/environ lists.
+    fn empty_sizes(mut caller: Caller<'_, WasiState>, count: i32, size: i32) -> Result<i32, Error> {
+        let memory = memory(&caller)?;
+        let data = memory.data_mut(&mut caller);
+        let errno = write_bytes(data, count, &0u32.to_le_bytes());
+        if errno != ERRNO_SUCCESS {
+            return Ok(errno);
+        }
+        Ok(write_bytes(data, size, &0u32.to_le_bytes()))
+    }
+
+    fn clock_time_get(
+        mut caller: Caller<'_, WasiState>,
+        _clock_id: i32,
+        _precision: i64,
+        time: i32,
+    ) -> Result<i32, Error> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX));
+        let memory = memory(&caller)?;
+        Ok(write_bytes(
+            memory.data_mut(&mut caller),
+            time,
+            &nanos.to_le_bytes(),
+        ))
+    }
+
+    fn random_get(mut caller: Caller<'_, WasiState>, buf: i32, len: i32) -> Result<i32, Error> {
+        let Ok(len) = usize::try_from(len) else {
+            return Ok(ERRNO_INVAL);
+        };
+        let bytes: Vec<u8> = (0..len).map(|_| rand::random::<u8>()).collect();
+        let memory = memory(&caller)?;
+        Ok(write_bytes(memory.data_mut(&mut caller), buf, &bytes))
+    }
+
+    fn fd_fdstat_get(mut caller: Caller<'_, WasiState>, fd: i32, buf: i32) -> Result<i32, Error> {
+        if !(0..=2).contains(&fd) {
+            return Ok(ERRNO_BADF);
+        }
+        // fdstat: filetype (u8) = character device, flags and rights zeroed
+        let mut stat = [0u8; 24];
+        stat[0] = 2;
+        let memory = memory(&caller)?;
+        Ok(write_bytes(memory.data_mut(&mut caller), buf, &stat))
+    }
+
+    /// Build a linker exposing the sandboxed WASI surface for `module`.
+    pub fn linker(engine: &Engine, module: &Module) -> anyhow::Result<Linker<WasiState>> {
+        let mut linker = Linker::new(engine);
+        linker
+            .func_wrap(WASI_MODULE, "fd_write", fd_write)?
+            .func_wrap(WASI_MODULE, "fd_read", fd_read)?
+            .func_wrap(WASI_MODULE, "args_sizes_get", empty_sizes)?
+            .func_wrap(WASI_MODULE, "environ_sizes_get", empty_sizes)?
+            .func_wrap(
+                WASI_MODULE,
+                "args_get",
+                |_: Caller<'_, WasiState>, _: i32, _: i32| ERRNO_SUCCESS,
+            )?
+            .func_wrap(
+                WASI_MODULE,
+                "environ_get",
+                |_: Caller<'_, WasiState>, _: i32, _: i32| ERRNO_SUCCESS,
+            )?
+            .func_wrap(WASI_MODULE, "clock_time_get", clock_time_get)?
+            .func_wrap(WASI_MODULE, "random_get", random_get)?
+            .func_wrap(WASI_MODULE, "fd_fdstat_get", fd_fdstat_get)?
+            .func_wrap(
+                WASI_MODULE,
+                "fd_close",
+                |_: Caller<'_, WasiState>, fd: i32| {
+                    if (0..=2).contains(&fd) {
+                        ERRNO_SUCCESS
+                    } else {
+                        ERRNO_BADF
+                    }
+                },
+            )?
+            .func_wrap(
+                WASI_MODULE,
+                "fd_seek",
+                |_: Caller<'_, WasiState>, _: i32, _: i64, _: i32, _: i32| ERRNO_SPIPE,
+            )?
+            .func_wrap(
+                WASI_MODULE,
+                "fd_prestat_get",
+                |_: Caller<'_, WasiState>, _: i32, _: i32| ERRNO_BADF,
+            )?
+            .func_wrap(
+                WASI_MODULE,
+                "fd_prestat_dir_name",
+                |_: Caller<'_, WasiState>, _: i32, _: i32, _: i32| ERRNO_BADF,
+            )?
+            .func_wrap(WASI_MODULE, "sched_yield", |_: Caller<'_, WasiState>| {
+                ERRNO_SUCCESS
+            })?
+            .func_wrap(
+                WASI_MODULE,
+                "proc_exit",
+                |_: Caller<'_, WasiState>, code: i32| -> Result<(), Error> {
+                    Err(Error::i32_exit(code))
+                },
+            )?;
+
+        // Anything else the module imports links, but traps if actually called.
+        const PROVIDED: &[&str] = &[
+            "fd_write",
+            "fd_read",
+            "args_sizes_get",
+            "environ_sizes_get",
+            "args_get",
+            "environ_get",
+            "clock_time_get",
+            "random_get",
+            "fd_fdstat_get",
+            "fd_close",
+            "fd_seek",
+            "fd_prestat_get",
+            "fd_prestat_dir_name",
+            "sched_yield",
+            "proc_exit",
+        ];
+        for import in module.imports() {
+            let ExternType::Func(ty) = import.ty() else {
+                continue;
+            };
+            if import.module() == WASI_MODULE && PROVIDED.contains(&import.name()) {
+                continue;
+            }
+            let message = format!(
+                "import '{}::{}' is not available in the WASM sandbox",
+                import.module(),
+                import.name()
+            );
+            linker.func_new(
+                import.module(),
+                import.name(),
+                ty.clone(),
+                move |_, _, _| Err(Error::new(message.clone())),
+            )?;
+        }
+
+        Ok(linker)
+    }
+}
+
 // ── Tests ───────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -379,7 +752,10 @@ mod tests {
         let rt = WasmRuntime::new(default_config());
         let result = rt.build_shell_command("echo hello", Path::new("/tmp"));
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("does not support shell"));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("does not support shell"));
     }
 
     #[test]
@@ -391,7 +767,10 @@ mod tests {
     #[test]
     fn wasm_storage_path_with_workspace() {
         let rt = WasmRuntime::with_workspace(default_config(), PathBuf::from("/home/user/project"));
-        assert_eq!(rt.storage_path(), PathBuf::from("/home/user/project/.zeroclaw"));
+        assert_eq!(
+            rt.storage_path(),
+            PathBuf::from("/home/user/project/.zeroclaw")
+        );
     }
 
     // ── Config validation ──────────────────────────────────────
@@ -636,10 +1015,7 @@ mod tests {
         let rt = WasmRuntime::new(default_config());
         let caps = WasmCapabilities::default();
         let mem_bytes = rt.effective_memory_bytes(&caps);
-        assert!(
-            mem_bytes > 0,
-            "default memory limit must be > 0"
-        );
+        assert!(mem_bytes > 0, "default memory limit must be > 0");
         assert!(
             mem_bytes <= 4096 * 1024 * 1024,
             "default memory must not exceed 4 GB safety limit"
@@ -684,4 +1060,140 @@ mod tests {
             assert!(result.unwrap_err().to_string().contains("not available"));
         }
     }
+
+    // ── Execution with the runtime-wasm feature ────────────────
+
+    #[cfg(feature = "runtime-wasm")]
+    fn write_wat(dir: &Path, name: &str, wat: &str) {
+        let tools_dir = dir.join("tools/wasm");
+        std::fs::create_dir_all(&tools_dir).unwrap();
+        std::fs::write(
+            tools_dir.join(format!("{name}.wasm")),
+            wat::parse_str(wat).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "runtime-wasm")]
+    #[test]
+    fn execute_module_pipes_stdin_to_stdout() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wat(
+            dir.path(),
+            "echo",
+            r#"(module
+  (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (func (export "run") (result i32)
+    (i32.store (i32.const 0) (i32.const 1024))
+    (i32.store (i32.const 4) (i32.const 4096))
+    (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+    (i32.store (i32.const 4) (i32.load (i32.const 8)))
+    (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+    (i32.const 0)))"#,
+        );
+
+        let rt = WasmRuntime::new(default_config());
+        let result = rt
+            .execute_module_with_input("echo", dir.path(), &WasmCapabilities::default(), b"ping")
+            .unwrap();
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.stdout, "ping");
+        assert!(result.fuel_consumed > 0);
+        assert_eq!(result.memory_bytes, 64 * 1024);
+    }
+
+    #[cfg(feature = "runtime-wasm")]
+    #[test]
+    fn execute_module_reports_proc_exit_code() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wat(
+            dir.path(),
+            "exit",
+            r#"(module
+  (import "wasi_snapshot_preview1" "proc_exit" (func $exit (param i32)))
+  (memory (export "memory") 1)
+  (func (export "_start") (call $exit (i32.const 3))))"#,
+        );
+
+        let rt = WasmRuntime::new(default_config());
+        let result = rt
+            .execute_module("exit", dir.path(), &WasmCapabilities::default())
+            .unwrap();
+        assert_eq!(result.exit_code, 3);
+    }
+
+    #[cfg(feature = "runtime-wasm")]
+    #[test]
+    fn execute_module_stops_at_fuel_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wat(
+            dir.path(),
+            "spin",
+            r#"(module (func (export "run") (result i32) (loop $l (br $l)) (i32.const 0)))"#,
+        );
+
+        let rt = WasmRuntime::new(default_config());
+        let caps = WasmCapabilities {
+            fuel_override: 10_000,
+            ..Default::default()
+        };
+        let result = rt.execute_module("spin", dir.path(), &caps).unwrap();
+        assert_eq!(result.exit_code, -1);
+        assert_eq!(result.fuel_consumed, 10_000);
+        assert!(result.stderr.contains("exceeded fuel limit"));
+    }
+
+    #[cfg(feature = "runtime-wasm")]
+    #[test]
+    fn execute_module_stops_at_memory_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wat(
+            dir.path(),
+            "grow",
+            r#"(module
+  (memory (export "memory") 1)
+  (func (export "run") (result i32) (memory.grow (i32.const 32))))"#,
+        );
+        write_wat(
+            dir.path(),
+            "big",
+            r#"(module (memory (export "memory") 64))"#,
+        );
+
+        let rt = WasmRuntime::new(default_config());
+        let caps = WasmCapabilities {
+            memory_override_mb: 1,
+            ..Default::default()
+        };
+
+        let result = rt.execute_module("grow", dir.path(), &caps).unwrap();
+        assert_eq!(result.exit_code, -1);
+        assert!(result.stderr.contains("exceeded memory limit"));
+        assert_eq!(result.memory_bytes, 64 * 1024);
+
+        let result = rt.execute_module("big", dir.path(), &caps).unwrap();
+        assert_eq!(result.exit_code, -1);
+        assert!(result.stderr.contains("exceeded memory limit"));
+    }
+
+    #[cfg(feature = "runtime-wasm")]
+    #[test]
+    fn execute_module_traps_on_unsupported_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        write_wat(
+            dir.path(),
+            "net",
+            r#"(module
+  (import "wasi_snapshot_preview1" "sock_open" (func $sock (param i32) (result i32)))
+  (func (export "run") (result i32) (call $sock (i32.const 0))))"#,
+        );
+
+        let rt = WasmRuntime::new(default_config());
+        let err = rt
+            .execute_module("net", dir.path(), &WasmCapabilities::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("sock_open"), "{err}");
+    }
 }
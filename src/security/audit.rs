@@ -1,6 +1,6 @@
 //! Audit logging for security events
 
-use crate::config::AuditConfig;
+use crate::config::{AuditConfig, Config};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
 use uuid::Uuid;
 
 /// Audit event types
@@ -21,6 +22,8 @@ pub enum AuditEventType {
     AuthFailure,
     PolicyViolation,
     SecurityEvent,
+    ApprovalDecision,
+    Checkpoint,
 }
 
 /// Actor information (who performed the action)
@@ -258,6 +261,21 @@ impl AuditLogger {
     }
 }
 
+/// Audit logger writing next to `config.toml` with the default audit settings.
+///
+/// `[security.audit]` is not yet part of `Config`, so this is the logger every
+/// subsystem shares. Returns `None` when the config path has no parent.
+pub fn default_logger(config: &Config) -> Option<Arc<AuditLogger>> {
+    let dir = config.config_path.parent()?;
+    match AuditLogger::new(AuditConfig::default(), dir.to_path_buf()) {
+        Ok(logger) => Some(Arc::new(logger)),
+        Err(e) => {
+            tracing::warn!("Audit log unavailable: {e}");
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
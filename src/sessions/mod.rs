@@ -0,0 +1,427 @@
+//! Persistent conversation sessions.
+//!
+//! Channel conversation history is kept in memory for fast access and written
+//! through to a SQLite store so it survives daemon restarts. Named CLI agent
+//! sessions (`zeroclaw agent --session <name>`) live in the same store under
+//! `agent:<name>` keys. This module owns that store and the `zeroclaw sessions`
+//! management commands.
+
+use crate::config::Config;
+use crate::providers::ChatMessage;
+use anyhow::{bail, Context, Result};
+use console::style;
+use std::fmt::Write as _;
+use std::path::Path;
+
+mod store;
+
+pub use store::SessionStore;
+
+/// Key prefix for sessions created by `zeroclaw agent`. Channel keys are
+/// `<channel>_<sender>`, so a `:` after the first word can never come from
+/// a channel (including the `cli` channel's `cli_user`).
+const CLI_SESSION_PREFIX: &str = "agent:";
+
+/// A named CLI agent session, saved after every turn so it can be resumed.
+pub struct CliSession {
+    store: SessionStore,
+    name: String,
+}
+
+impl CliSession {
+    /// Resolve the `--session` / `--resume` flags of `zeroclaw agent`.
+    ///
+    /// `--session <name>` creates or continues a named session; `--resume`
+    /// alone continues the most recently active one, and together with
+    /// `--session` requires that session to exist already.
+    pub fn from_flags(config: &Config, name: Option<&str>, resume: bool) -> Result<Option<Self>> {
+        if name.is_none() && !resume {
+            return Ok(None);
+        }
+        let store = open_cli_store(config)?;
+        let name = match name {
+            Some(name) => {
+                validate_session_name(name)?;
+                if resume && !store.exists(&cli_session_key(name))? {
+                    bail!("No CLI session named '{name}' to resume");
+                }
+                name.to_string()
+            }
+            None => store
+                .list()?
+                .into_iter()
+                .find_map(|s| s.key.strip_prefix(CLI_SESSION_PREFIX).map(str::to_string))
+                .context("No CLI sessions to resume; start one with --session <name>")?,
+        };
+        Ok(Some(Self { store, name }))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Saved conversation, without the system prompt (rebuilt each run).
+    pub fn load(&self) -> Result<Vec<ChatMessage>> {
+        self.store.load(&cli_session_key(&self.name))
+    }
+
+    /// Persist `history`, skipping system messages.
+    pub fn save(&self, history: &[ChatMessage]) -> Result<()> {
+        let turns: Vec<ChatMessage> = history
+            .iter()
+            .filter(|m| m.role != "system")
+            .cloned()
+            .collect();
+        self.store.replace(&cli_session_key(&self.name), &turns)
+    }
+
+    /// Continue under another name; the next `save` writes there, leaving
+    /// the previous session as it was.
+    pub fn rename(&mut self, name: &str) -> Result<()> {
+        validate_session_name(name)?;
+        self.name = name.to_string();
+        Ok(())
+    }
+
+    /// Open the named session store without loading it, for `/save <name>`
+    /// when no session is active yet.
+    pub fn create(config: &Config, name: &str) -> Result<Self> {
+        validate_session_name(name)?;
+        Ok(Self {
+            store: open_cli_store(config)?,
+            name: name.to_string(),
+        })
+    }
+}
+
+fn cli_session_key(name: &str) -> String {
+    format!("{CLI_SESSION_PREFIX}{name}")
+}
+
+fn validate_session_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        bail!("Invalid session name '{name}': use letters, digits, '-', '_' or '.'");
+    }
+    Ok(())
+}
+
+/// Open the workspace session store for CLI management operations.
+fn open_cli_store(config: &Config) -> Result<SessionStore> {
+    SessionStore::open(&config.workspace_dir, &config.sessions)
+}
+
+pub fn handle_command(command: crate::SessionCommands, config: &Config) -> Result<()> {
+    match command {
+        crate::SessionCommands::List => handle_list(config),
+        crate::SessionCommands::Show { key } => handle_show(config, &key),
+        crate::SessionCommands::Export {
+            key,
+            format,
+            output,
+        } => handle_export(config, &key, &format, output.as_deref()),
+        crate::SessionCommands::Delete { key, yes } => handle_clear(config, Some(&key), yes),
+        crate::SessionCommands::Clear { key, yes } => handle_clear(config, key.as_deref(), yes),
+    }
+}
+
+fn handle_list(config: &Config) -> Result<()> {
+    let store = open_cli_store(config)?;
+    let sessions = store.list()?;
+
+    if sessions.is_empty() {
+        println!("No persisted sessions.");
+        return Ok(());
+    }
+
+    println!("Sessions ({}):\n", sessions.len());
+    for session in &sessions {
+        println!(
+            "- {} ({} messages, last active {})",
+            style(&session.key).white().bold(),
+            session.message_count,
+            session.updated_at,
+        );
+    }
+
+    Ok(())
+}
+
+/// Resolve a session key by exact match or unique prefix.
+fn resolve_key(store: &SessionStore, key: &str) -> Result<String> {
+    if store.exists(key)? {
+        return Ok(key.to_string());
+    }
+
+    let matches: Vec<_> = store
+        .list()?
+        .into_iter()
+        .filter(|s| s.key.starts_with(key))
+        .collect();
+
+    match matches.len() {
+        0 => bail!("No session found for key: {key}"),
+        1 => Ok(matches[0].key.clone()),
+        n => {
+            let keys: Vec<_> = matches.iter().map(|s| s.key.as_str()).collect();
+            bail!(
+                "Prefix '{key}' matched {n} sessions: {}. Specify a longer prefix.",
+                keys.join(", ")
+            )
+        }
+    }
+}
+
+fn handle_show(config: &Config, key: &str) -> Result<()> {
+    let store = open_cli_store(config)?;
+    let key = resolve_key(&store, key)?;
+    let messages = store.load(&key)?;
+
+    println!(
+        "Session {} ({} messages):\n",
+        style(&key).white().bold(),
+        messages.len()
+    );
+    for message in &messages {
+        println!("[{}]", style(&message.role).cyan().bold());
+        println!("{}\n", message.content);
+    }
+
+    Ok(())
+}
+
+fn handle_export(config: &Config, key: &str, format: &str, output: Option<&Path>) -> Result<()> {
+    let store = open_cli_store(config)?;
+    let key = resolve_key(&store, key)?;
+    let messages = store.load(&key)?;
+    let rendered = match format.trim().to_ascii_lowercase().as_str() {
+        "markdown" | "md" => render_markdown(&key, &messages),
+        "jsonl" => render_jsonl(&messages)?,
+        other => bail!("Unknown export format '{other}'. Use markdown or jsonl."),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, rendered)
+                .with_context(|| format!("Failed to write transcript: {}", path.display()))?;
+            println!(
+                "{} Exported {} messages from {key} to {}",
+                style("✓").green().bold(),
+                messages.len(),
+                path.display()
+            );
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+/// Render a transcript for human review. Native tool calls and results are
+/// stored as JSON payloads and are shown as fenced JSON blocks.
+fn render_markdown(key: &str, messages: &[ChatMessage]) -> String {
+    let mut out = format!("# Session `{key}`\n");
+    for message in messages {
+        let heading = match message.role.as_str() {
+            "user" => "User",
+            "assistant" => "Assistant",
+            "tool" => "Tool result",
+            "system" => "System",
+            other => other,
+        };
+        let _ = write!(out, "\n## {heading}\n\n");
+        let content = message.content.trim();
+        match serde_json::from_str::<serde_json::Value>(content) {
+            Ok(value) if value.is_object() => {
+                let pretty = serde_json::to_string_pretty(&value).unwrap_or_default();
+                let _ = writeln!(out, "```json\n{pretty}\n```");
+            }
+            _ => {
+                let _ = writeln!(out, "{content}");
+            }
+        }
+    }
+    out
+}
+
+/// One JSON-serialized message per line, readable back as `ChatMessage`.
+fn render_jsonl(messages: &[ChatMessage]) -> Result<String> {
+    let mut out = String::new();
+    for message in messages {
+        out.push_str(&serde_json::to_string(message)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn handle_clear(config: &Config, key: Option<&str>, yes: bool) -> Result<()> {
+    let store = open_cli_store(config)?;
+
+    let (prompt, target) = match key {
+        Some(key) => {
+            let key = resolve_key(&store, key)?;
+            (format!("  Clear session '{key}'?"), Some(key))
+        }
+        None => {
+            let count = store.list()?.len();
+            if count == 0 {
+                println!("No sessions to clear.");
+                return Ok(());
+            }
+            (format!("  Clear all {count} sessions?"), None)
+        }
+    };
+
+    if !yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    match target {
+        Some(key) => {
+            store.clear(&key)?;
+            println!("{} Cleared session {key}.", style("✓").green().bold());
+        }
+        None => {
+            let removed = store.clear_all()?;
+            println!("{} Cleared {removed} sessions.", style("✓").green().bold());
+        }
+    }
+
+    println!("  Restart the daemon to drop in-memory history for running channels.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_config(tmp: &TempDir) -> Config {
+        Config {
+            workspace_dir: tmp.path().join("workspace"),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn cli_session_saves_without_system_prompt_and_resumes_latest() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        assert!(CliSession::from_flags(&config, None, false)
+            .unwrap()
+            .is_none());
+        assert!(CliSession::from_flags(&config, None, true).is_err());
+
+        let session = CliSession::from_flags(&config, Some("work"), false)
+            .unwrap()
+            .unwrap();
+        session
+            .save(&[
+                ChatMessage::system("system prompt"),
+                ChatMessage::user("hello"),
+                ChatMessage::assistant("hi"),
+            ])
+            .unwrap();
+
+        let resumed = CliSession::from_flags(&config, None, true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(resumed.name(), "work");
+        let history = resumed.load().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].content, "hello");
+        assert!(CliSession::from_flags(&config, Some("missing"), true).is_err());
+    }
+
+    #[test]
+    fn cli_channel_history_is_not_a_cli_session() {
+        let tmp = TempDir::new().unwrap();
+        let mut config = test_config(&tmp);
+        config.sessions.max_sessions = 1;
+        let store = open_cli_store(&config).unwrap();
+        store
+            .append("cli_user", &ChatMessage::user("from the cli channel"))
+            .unwrap();
+
+        assert!(CliSession::from_flags(&config, None, true).is_err());
+        let user = CliSession::create(&config, "user").unwrap();
+        assert!(user.load().unwrap().is_empty());
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        user.save(&[ChatMessage::user("saved work")]).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        store
+            .append("telegram_a", &ChatMessage::user("hello"))
+            .unwrap();
+
+        // Only channel sessions count toward the cap; cli_user is the oldest.
+        assert_eq!(store.prune().unwrap(), vec!["cli_user"]);
+        assert_eq!(user.load().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn renamed_session_leaves_original_intact() {
+        let tmp = TempDir::new().unwrap();
+        let config = test_config(&tmp);
+        let mut session = CliSession::create(&config, "main").unwrap();
+        session.save(&[ChatMessage::user("one")]).unwrap();
+
+        session.rename("experiment").unwrap();
+        session
+            .save(&[ChatMessage::user("one"), ChatMessage::user("two")])
+            .unwrap();
+
+        let main = CliSession::create(&config, "main").unwrap();
+        assert_eq!(main.load().unwrap().len(), 1);
+        assert_eq!(session.load().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn session_names_are_restricted_to_safe_characters() {
+        assert!(validate_session_name("refactor-auth_v2.1").is_ok());
+        assert!(validate_session_name("").is_err());
+        assert!(validate_session_name("has space").is_err());
+        assert!(validate_session_name("../escape").is_err());
+    }
+
+    #[test]
+    fn markdown_export_renders_roles_and_tool_payloads() {
+        let messages = vec![
+            ChatMessage::user("list files"),
+            ChatMessage::assistant(
+                r#"{"content":null,"tool_calls":[{"id":"c1","name":"shell","arguments":"{}"}]}"#,
+            ),
+            ChatMessage::tool(r#"{"tool_call_id":"c1","content":"README.md"}"#),
+            ChatMessage::assistant("There is a README."),
+        ];
+
+        let md = render_markdown("agent:work", &messages);
+        assert!(md.starts_with("# Session `agent:work`"));
+        assert!(md.contains("## User\n\nlist files"));
+        assert!(md.contains("## Tool result\n\n```json"));
+        assert!(md.contains("\"tool_call_id\": \"c1\""));
+        assert!(md.contains("## Assistant\n\nThere is a README."));
+    }
+
+    #[test]
+    fn jsonl_export_round_trips_messages() {
+        let messages = vec![ChatMessage::user("hi"), ChatMessage::assistant("hello")];
+        let jsonl = render_jsonl(&messages).unwrap();
+        let parsed: Vec<ChatMessage> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].content, "hello");
+    }
+}
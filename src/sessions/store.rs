@@ -0,0 +1,526 @@
+use crate::config::SessionsConfig;
+use crate::providers::ChatMessage;
+use anyhow::{Context, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Summary row for a persisted session, used by `zeroclaw sessions list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSummary {
+    pub key: String,
+    pub message_count: usize,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// SQLite-backed store for conversation sessions.
+///
+/// Lives at `<workspace>/memory/sessions.db`, next to `brain.db`. Every
+/// mutation is written through immediately so a crash never loses more than
+/// the in-flight turn.
+pub struct SessionStore {
+    conn: Mutex<Connection>,
+    db_path: PathBuf,
+    max_messages_per_session: usize,
+    max_sessions: usize,
+    retention_days: u32,
+}
+
+impl SessionStore {
+    pub fn open(workspace_dir: &Path, config: &SessionsConfig) -> Result<Self> {
+        let db_path = workspace_dir.join("memory").join("sessions.db");
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create sessions directory: {}", parent.display())
+            })?;
+        }
+
+        let conn = Connection::open(&db_path)
+            .with_context(|| format!("Failed to open sessions DB: {}", db_path.display()))?;
+
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous  = NORMAL;
+             PRAGMA foreign_keys = ON;
+             CREATE TABLE IF NOT EXISTS sessions (
+                key        TEXT PRIMARY KEY,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_sessions_updated_at ON sessions(updated_at);
+
+             CREATE TABLE IF NOT EXISTS session_messages (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_key TEXT NOT NULL,
+                role        TEXT NOT NULL,
+                content     TEXT NOT NULL,
+                created_at  TEXT NOT NULL,
+                FOREIGN KEY (session_key) REFERENCES sessions(key) ON DELETE CASCADE
+             );
+             CREATE INDEX IF NOT EXISTS idx_session_messages_key ON session_messages(session_key, id);",
+        )
+        .context("Failed to initialize sessions schema")?;
+
+        // Migration: typed content parts (images, audio) as JSON, NULL for
+        // plain-text messages.
+        let has_parts: bool = conn
+            .prepare(
+                "SELECT sql FROM sqlite_master WHERE type='table' AND name='session_messages'",
+            )?
+            .query_row([], |row| row.get::<_, String>(0))?
+            .contains("parts");
+        if !has_parts {
+            conn.execute_batch("ALTER TABLE session_messages ADD COLUMN parts TEXT;")
+                .context("Failed to migrate sessions schema")?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            db_path,
+            max_messages_per_session: config.max_messages_per_session.max(1),
+            max_sessions: config.max_sessions,
+            retention_days: config.retention_days,
+        })
+    }
+
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
+    /// Append one turn to a session, trimming the oldest messages beyond the
+    /// per-session cap.
+    pub fn append(&self, key: &str, message: &ChatMessage) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let now = Utc::now().to_rfc3339();
+        touch_session(&tx, key, &now)?;
+        tx.execute(
+            "INSERT INTO session_messages (session_key, role, content, parts, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                key,
+                message.role,
+                message.content,
+                encode_parts(message)?,
+                now
+            ],
+        )
+        .context("Failed to insert session message")?;
+        trim_session(&tx, key, self.max_messages_per_session)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace the full history of a session (used after compaction).
+    pub fn replace(&self, key: &str, messages: &[ChatMessage]) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM session_messages WHERE session_key = ?1",
+            params![key],
+        )?;
+        if messages.is_empty() {
+            tx.execute("DELETE FROM sessions WHERE key = ?1", params![key])?;
+            tx.commit()?;
+            return Ok(());
+        }
+
+        let now = Utc::now().to_rfc3339();
+        touch_session(&tx, key, &now)?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO session_messages (session_key, role, content, parts, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for message in messages {
+                stmt.execute(params![
+                    key,
+                    message.role,
+                    message.content,
+                    encode_parts(message)?,
+                    now
+                ])?;
+            }
+        }
+        trim_session(&tx, key, self.max_messages_per_session)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove a session and all of its messages. Returns `true` if it existed.
+    pub fn clear(&self, key: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "DELETE FROM session_messages WHERE session_key = ?1",
+            params![key],
+        )?;
+        let removed = conn.execute("DELETE FROM sessions WHERE key = ?1", params![key])?;
+        Ok(removed > 0)
+    }
+
+    /// Remove every session. Returns the number of sessions deleted.
+    pub fn clear_all(&self) -> Result<usize> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM session_messages", [])?;
+        let removed = conn.execute("DELETE FROM sessions", [])?;
+        Ok(removed)
+    }
+
+    /// Load the messages of one session in chronological order.
+    pub fn load(&self, key: &str) -> Result<Vec<ChatMessage>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT role, content, parts FROM session_messages
+             WHERE session_key = ?1 ORDER BY id ASC",
+        )?;
+        let rows = stmt.query_map(params![key], |row| message_from_row(row, 0))?;
+
+        let mut messages = Vec::new();
+        for row in rows {
+            messages.push(row?);
+        }
+        Ok(messages)
+    }
+
+    /// Load every session, keyed by session key. Used to rehydrate channel
+    /// history on startup.
+    pub fn load_all(&self) -> Result<HashMap<String, Vec<ChatMessage>>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT session_key, role, content, parts FROM session_messages
+             ORDER BY session_key, id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, message_from_row(row, 1)?))
+        })?;
+
+        let mut sessions: HashMap<String, Vec<ChatMessage>> = HashMap::new();
+        for row in rows {
+            let (key, message) = row?;
+            sessions.entry(key).or_default().push(message);
+        }
+        Ok(sessions)
+    }
+
+    /// List sessions, most recently active first.
+    pub fn list(&self) -> Result<Vec<SessionSummary>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT s.key, s.created_at, s.updated_at, COUNT(m.id)
+             FROM sessions s LEFT JOIN session_messages m ON m.session_key = s.key
+             GROUP BY s.key ORDER BY s.updated_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(SessionSummary {
+                key: row.get(0)?,
+                created_at: row.get(1)?,
+                updated_at: row.get(2)?,
+                message_count: usize::try_from(row.get::<_, i64>(3)?).unwrap_or(0),
+            })
+        })?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            sessions.push(row?);
+        }
+        Ok(sessions)
+    }
+
+    /// Apply retention limits: drop sessions idle past `retention_days`, then
+    /// the least recently active sessions beyond `max_sessions`.
+    /// Returns the keys of the removed sessions.
+    ///
+    /// Named CLI sessions (`agent:<name>`) are saved work rather than chat
+    /// history, so they are exempt and only removed by `zeroclaw sessions
+    /// delete`.
+    pub fn prune(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock();
+        let mut removed = Vec::new();
+        let prefix = super::CLI_SESSION_PREFIX;
+        let select_keys = |query: &str, params: &[&dyn rusqlite::ToSql]| -> Result<Vec<String>> {
+            let mut stmt = conn.prepare(query)?;
+            let keys = stmt
+                .query_map(params, |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(keys)
+        };
+
+        if self.retention_days > 0 {
+            let cutoff =
+                (Utc::now() - ChronoDuration::days(i64::from(self.retention_days))).to_rfc3339();
+            let expired = "SELECT key FROM sessions
+                           WHERE updated_at < ?1 AND substr(key, 1, length(?2)) <> ?2";
+            removed.extend(select_keys(expired, params![cutoff, prefix])?);
+            conn.execute(
+                &format!("DELETE FROM session_messages WHERE session_key IN ({expired})"),
+                params![cutoff, prefix],
+            )?;
+            conn.execute(
+                &format!("DELETE FROM sessions WHERE key IN ({expired})"),
+                params![cutoff, prefix],
+            )?;
+        }
+
+        if self.max_sessions > 0 {
+            let keep = i64::try_from(self.max_sessions).unwrap_or(i64::MAX);
+            let overflow = "SELECT key FROM sessions WHERE substr(key, 1, length(?2)) <> ?2
+                            ORDER BY updated_at DESC LIMIT -1 OFFSET ?1";
+            removed.extend(select_keys(overflow, params![keep, prefix])?);
+            conn.execute(
+                &format!("DELETE FROM session_messages WHERE session_key IN ({overflow})"),
+                params![keep, prefix],
+            )?;
+            conn.execute(
+                &format!("DELETE FROM sessions WHERE key IN ({overflow})"),
+                params![keep, prefix],
+            )?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Returns `true` if a session with this exact key exists.
+    pub fn exists(&self, key: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        let found = conn
+            .query_row(
+                "SELECT 1 FROM sessions WHERE key = ?1",
+                params![key],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+}
+
+fn encode_parts(message: &ChatMessage) -> Result<Option<String>> {
+    if message.parts.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(&message.parts)
+        .map(Some)
+        .context("Failed to encode message content parts")
+}
+
+/// Build a message from `role, content, parts` columns starting at `first`.
+/// Unreadable parts fall back to the text rendering in `content`.
+fn message_from_row(row: &rusqlite::Row<'_>, first: usize) -> rusqlite::Result<ChatMessage> {
+    let parts = row
+        .get::<_, Option<String>>(first + 2)?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    Ok(ChatMessage {
+        role: row.get(first)?,
+        content: row.get(first + 1)?,
+        parts,
+    })
+}
+
+fn touch_session(conn: &Connection, key: &str, now: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO sessions (key, created_at, updated_at) VALUES (?1, ?2, ?2)
+         ON CONFLICT(key) DO UPDATE SET updated_at = excluded.updated_at",
+        params![key, now],
+    )
+    .context("Failed to upsert session")?;
+    Ok(())
+}
+
+fn trim_session(conn: &Connection, key: &str, max_messages: usize) -> Result<()> {
+    let keep = i64::try_from(max_messages).unwrap_or(i64::MAX);
+    conn.execute(
+        "DELETE FROM session_messages WHERE session_key = ?1 AND id NOT IN
+            (SELECT id FROM session_messages WHERE session_key = ?1 ORDER BY id DESC LIMIT ?2)",
+        params![key, keep],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store_with(config: &SessionsConfig) -> (TempDir, SessionStore) {
+        let tmp = TempDir::new().unwrap();
+        let store = SessionStore::open(tmp.path(), config).unwrap();
+        (tmp, store)
+    }
+
+    #[test]
+    fn append_and_load_round_trip() {
+        let (_tmp, store) = store_with(&SessionsConfig::default());
+        store
+            .append("telegram_alice", &ChatMessage::user("hello"))
+            .unwrap();
+        store
+            .append("telegram_alice", &ChatMessage::assistant("hi there"))
+            .unwrap();
+
+        let history = store.load("telegram_alice").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].role, "user");
+        assert_eq!(history[1].content, "hi there");
+        assert!(store.load("telegram_bob").unwrap().is_empty());
+    }
+
+    #[test]
+    fn history_survives_reopen() {
+        let tmp = TempDir::new().unwrap();
+        let config = SessionsConfig::default();
+        {
+            let store = SessionStore::open(tmp.path(), &config).unwrap();
+            store
+                .append("discord_u1", &ChatMessage::user("remember me"))
+                .unwrap();
+        }
+
+        let reopened = SessionStore::open(tmp.path(), &config).unwrap();
+        let all = reopened.load_all().unwrap();
+        assert_eq!(all["discord_u1"][0].content, "remember me");
+        assert!(tmp.path().join("memory").join("sessions.db").exists());
+    }
+
+    #[test]
+    fn append_trims_to_max_messages() {
+        let config = SessionsConfig {
+            max_messages_per_session: 3,
+            ..SessionsConfig::default()
+        };
+        let (_tmp, store) = store_with(&config);
+        for idx in 0..5 {
+            store
+                .append("slack_u1", &ChatMessage::user(format!("msg-{idx}")))
+                .unwrap();
+        }
+
+        let history = store.load("slack_u1").unwrap();
+        let contents: Vec<_> = history.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["msg-2", "msg-3", "msg-4"]);
+    }
+
+    #[test]
+    fn replace_and_clear_update_store() {
+        let (_tmp, store) = store_with(&SessionsConfig::default());
+        store
+            .append("telegram_u1", &ChatMessage::user("old"))
+            .unwrap();
+        store
+            .replace("telegram_u1", &[ChatMessage::user("compacted")])
+            .unwrap();
+        assert_eq!(store.load("telegram_u1").unwrap()[0].content, "compacted");
+
+        assert!(store.clear("telegram_u1").unwrap());
+        assert!(!store.exists("telegram_u1").unwrap());
+        assert!(!store.clear("telegram_u1").unwrap());
+    }
+
+    #[test]
+    fn content_parts_survive_round_trip() {
+        use crate::providers::ContentPart;
+
+        let (_tmp, store) = store_with(&SessionsConfig::default());
+        let message = ChatMessage::with_parts(
+            "user",
+            vec![
+                ContentPart::text("what is this?"),
+                ContentPart::image_url("https://example.com/cat.png"),
+            ],
+        );
+        store.append("agent:work", &message).unwrap();
+        store
+            .append("agent:work", &ChatMessage::assistant("a cat"))
+            .unwrap();
+
+        let history = store.load("agent:work").unwrap();
+        assert_eq!(history[0].parts, message.parts);
+        assert!(history[1].parts.is_empty());
+        assert_eq!(
+            store.load_all().unwrap()["agent:work"][0].parts,
+            message.parts
+        );
+    }
+
+    #[test]
+    fn open_migrates_stores_without_parts_column() {
+        let tmp = TempDir::new().unwrap();
+        let db_dir = tmp.path().join("memory");
+        std::fs::create_dir_all(&db_dir).unwrap();
+        let conn = Connection::open(db_dir.join("sessions.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE sessions (key TEXT PRIMARY KEY, created_at TEXT NOT NULL, updated_at TEXT NOT NULL);
+             CREATE TABLE session_messages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, session_key TEXT NOT NULL,
+                role TEXT NOT NULL, content TEXT NOT NULL, created_at TEXT NOT NULL);
+             INSERT INTO sessions VALUES ('telegram_u1', 't', 't');
+             INSERT INTO session_messages (session_key, role, content, created_at)
+                VALUES ('telegram_u1', 'user', 'old row', 't');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = SessionStore::open(tmp.path(), &SessionsConfig::default()).unwrap();
+        assert_eq!(store.load("telegram_u1").unwrap()[0].content, "old row");
+        store
+            .append("telegram_u1", &ChatMessage::assistant("new row"))
+            .unwrap();
+        assert_eq!(store.load("telegram_u1").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn prune_enforces_max_sessions() {
+        let config = SessionsConfig {
+            max_sessions: 2,
+            ..SessionsConfig::default()
+        };
+        let (_tmp, store) = store_with(&config);
+        for key in ["a", "b", "c"] {
+            store.append(key, &ChatMessage::user(key)).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert_eq!(store.prune().unwrap(), vec!["a".to_string()]);
+        let keys: Vec<_> = store.list().unwrap().into_iter().map(|s| s.key).collect();
+        assert_eq!(keys, vec!["c".to_string(), "b".to_string()]);
+        assert!(store.load("a").unwrap().is_empty());
+    }
+
+    #[test]
+    fn prune_never_removes_cli_sessions() {
+        let config = SessionsConfig {
+            max_sessions: 1,
+            retention_days: 1,
+            ..SessionsConfig::default()
+        };
+        let (_tmp, store) = store_with(&config);
+        for key in ["agent:work", "telegram_a", "telegram_b"] {
+            store.append(key, &ChatMessage::user(key)).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        store
+            .append("agent:old", &ChatMessage::user("old"))
+            .unwrap();
+        store
+            .append("discord_old", &ChatMessage::user("old"))
+            .unwrap();
+        {
+            let conn = store.conn.lock();
+            conn.execute(
+                "UPDATE sessions SET updated_at = '2000-01-01T00:00:00+00:00'
+                 WHERE key IN ('agent:old', 'discord_old')",
+                [],
+            )
+            .unwrap();
+        }
+
+        // discord_old expires; telegram_a is over the channel session cap.
+        assert_eq!(store.prune().unwrap(), vec!["discord_old", "telegram_a"]);
+        let mut keys: Vec<_> = store.list().unwrap().into_iter().map(|s| s.key).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["agent:old", "agent:work", "telegram_b"]);
+        assert_eq!(store.load("agent:work").unwrap().len(), 1);
+    }
+}
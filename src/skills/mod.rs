@@ -37,8 +37,10 @@ pub struct SkillTool {
     pub description: String,
     /// "shell", "http", "script"
     pub kind: String,
-    /// The command/URL/script to execute
+    /// The command/URL/script to execute. `{{name}}` placeholders are filled
+    /// from the declared `args` when the tool is called.
     pub command: String,
+    /// Declared parameters exposed to the model (name -> description).
     #[serde(default)]
     pub args: HashMap<String, String>,
 }
@@ -568,8 +570,12 @@ pub fn init_skills_dir(workspace_dir: &Path) -> Result<()> {
              name = \"my_tool\"\n\
              description = \"What this tool does\"\n\
              kind = \"shell\"\n\
-             command = \"echo hello\"\n\
+             command = \"echo hello {{name}}\"\n\
+             args = { name = \"Who to greet\" }\n\
              ```\n\n\
+             Tool kinds: `shell` (command policy applies), `http` (`[METHOD ]URL`, \
+             uses the http_request allowlist), `script` (path relative to the skill).\n\
+             `args` declares parameters that fill `{{name}}` placeholders.\n\n\
              ## SKILL.md format (simpler)\n\n\
              Just write a markdown file with instructions for the agent.\n\
              The agent will read it and follow the instructions.\n\n\
@@ -0,0 +1,1386 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// How many context lines may be dropped from each end of a hunk when it
+/// does not apply as written (the same idea as `patch --fuzz=2`).
+const MAX_CONTEXT_FUZZ: usize = 2;
+const MAX_FILE_SIZE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Apply a multi-file change set atomically.
+///
+/// Accepts either a unified diff (`git diff` / `diff -u` output, including
+/// new, deleted and renamed files) or a JSON `edits` envelope. Every path is
+/// validated against the security policy and every hunk is matched against
+/// the current file contents before anything is written; if any hunk fails,
+/// no file is touched and the per-hunk report explains why.
+pub struct ApplyPatchTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl ApplyPatchTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Context,
+    Removed,
+    Added,
+}
+
+#[derive(Debug, Clone)]
+struct Hunk {
+    header: String,
+    old_start: usize,
+    lines: Vec<(LineKind, String)>,
+    /// `\ No newline at end of file` followed the new side's last line.
+    new_no_eol: bool,
+}
+
+impl Hunk {
+    fn old_side(&self, skip_head: usize, skip_tail: usize) -> Vec<&str> {
+        self.lines[skip_head..self.lines.len() - skip_tail]
+            .iter()
+            .filter(|(kind, _)| *kind != LineKind::Added)
+            .map(|(_, line)| line.as_str())
+            .collect()
+    }
+
+    fn leading_context(&self) -> usize {
+        self.lines
+            .iter()
+            .take_while(|(kind, _)| *kind == LineKind::Context)
+            .count()
+    }
+
+    fn trailing_context(&self) -> usize {
+        self.lines
+            .iter()
+            .rev()
+            .take_while(|(kind, _)| *kind == LineKind::Context)
+            .count()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Edit {
+    Hunk(Hunk),
+    Replace { old: String, new: String },
+}
+
+#[derive(Debug, Clone)]
+enum FileChange {
+    Create {
+        path: String,
+        content: String,
+    },
+    Delete {
+        path: String,
+    },
+    Modify {
+        path: String,
+        new_path: Option<String>,
+        edits: Vec<Edit>,
+    },
+}
+
+// ── Unified diff parsing ─────────────────────────────────────────────
+
+#[derive(Default)]
+struct PendingFile {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    git_old: Option<String>,
+    git_new: Option<String>,
+    created: bool,
+    deleted: bool,
+    seen_headers: bool,
+    hunks: Vec<Hunk>,
+}
+
+impl PendingFile {
+    fn finish(self) -> Result<Option<FileChange>, String> {
+        let old = self.old_path.or(self.git_old);
+        let new = self.new_path.or(self.git_new);
+        let change = match (old, new) {
+            (None, None) => return Ok(None),
+            (None, Some(path)) => FileChange::Create {
+                content: created_content(&path, &self.hunks)?,
+                path,
+            },
+            (Some(path), _) if self.deleted => FileChange::Delete { path },
+            (Some(path), None) => FileChange::Delete { path },
+            (Some(_), Some(path)) if self.created => FileChange::Create {
+                content: created_content(&path, &self.hunks)?,
+                path,
+            },
+            (Some(path), Some(new_path)) => FileChange::Modify {
+                new_path: (new_path != path).then_some(new_path),
+                path,
+                edits: self.hunks.into_iter().map(Edit::Hunk).collect(),
+            },
+        };
+        Ok(Some(change))
+    }
+}
+
+fn created_content(path: &str, hunks: &[Hunk]) -> Result<String, String> {
+    let mut lines = Vec::new();
+    let mut no_eol = false;
+    for hunk in hunks {
+        for (kind, line) in &hunk.lines {
+            if *kind != LineKind::Added {
+                return Err(format!("New file {path} has context or removed lines"));
+            }
+            lines.push(line.as_str());
+        }
+        no_eol = hunk.new_no_eol;
+    }
+    let mut content = lines.join("\n");
+    if !lines.is_empty() && !no_eol {
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+/// Strip the `a/` / `b/` prefixes git adds and any trailing timestamp.
+/// Returns `None` for `/dev/null`.
+fn header_path(raw: &str, prefix: &str) -> Option<String> {
+    let raw = raw.split('\t').next().unwrap_or(raw).trim();
+    let raw = raw
+        .strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .unwrap_or(raw);
+    if raw == "/dev/null" {
+        return None;
+    }
+    Some(raw.strip_prefix(prefix).unwrap_or(raw).to_string())
+}
+
+fn parse_range(raw: &str) -> Option<(usize, usize)> {
+    let (start, count) = match raw.split_once(',') {
+        Some((start, count)) => (start, count.parse().ok()?),
+        None => (raw, 1),
+    };
+    Some((start.parse().ok()?, count))
+}
+
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let (old_start, old_count) = parse_range(old)?;
+    let (_, new_count) = parse_range(new)?;
+    // Pure insertions name the line *after which* to insert.
+    let old_start = if old_count == 0 {
+        old_start + 1
+    } else {
+        old_start
+    };
+    Some((old_start, old_count, new_count))
+}
+
+fn parse_unified_diff(text: &str) -> Result<Vec<FileChange>, String> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    let mut changes = Vec::new();
+    let mut pending: Option<PendingFile> = None;
+    let mut idx = 0;
+
+    let flush = |pending: &mut Option<PendingFile>, changes: &mut Vec<FileChange>| {
+        if let Some(file) = pending.take() {
+            if let Some(change) = file.finish()? {
+                changes.push(change);
+            }
+        }
+        Ok::<(), String>(())
+    };
+
+    while idx < lines.len() {
+        let line = lines[idx];
+        idx += 1;
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            flush(&mut pending, &mut changes)?;
+            let mut file = PendingFile::default();
+            if let Some((old, new)) = rest.split_once(" b/") {
+                file.git_old = header_path(old, "a/");
+                file.git_new = Some(new.to_string());
+            }
+            pending = Some(file);
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            if let Some(file) = pending.as_mut() {
+                file.old_path = Some(path.to_string());
+            }
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            if let Some(file) = pending.as_mut() {
+                file.new_path = Some(path.to_string());
+            }
+        } else if line.starts_with("new file mode") {
+            if let Some(file) = pending.as_mut() {
+                file.created = true;
+            }
+        } else if line.starts_with("deleted file mode") {
+            if let Some(file) = pending.as_mut() {
+                file.deleted = true;
+            }
+        } else if let Some(raw) = line.strip_prefix("--- ") {
+            let starts_new = pending
+                .as_ref()
+                .is_none_or(|file| file.seen_headers || !file.hunks.is_empty());
+            if starts_new {
+                flush(&mut pending, &mut changes)?;
+                pending = Some(PendingFile::default());
+            }
+            let file = pending.get_or_insert_with(PendingFile::default);
+            file.seen_headers = true;
+            file.old_path = header_path(raw, "a/");
+            file.git_old = None;
+            if file.old_path.is_none() {
+                file.created = true;
+            }
+        } else if let Some(raw) = line.strip_prefix("+++ ") {
+            let Some(file) = pending.as_mut() else {
+                return Err(format!("'+++' header without '---' header: {line}"));
+            };
+            file.new_path = header_path(raw, "b/");
+            file.git_new = None;
+            if file.new_path.is_none() {
+                file.deleted = true;
+            }
+        } else if line.starts_with("@@") {
+            let Some(file) = pending.as_mut() else {
+                return Err(format!("Hunk without file header: {line}"));
+            };
+            let (old_start, mut old_left, mut new_left) =
+                parse_hunk_header(line).ok_or_else(|| format!("Malformed hunk header: {line}"))?;
+            let mut hunk = Hunk {
+                header: line.to_string(),
+                old_start,
+                lines: Vec::new(),
+                new_no_eol: false,
+            };
+            while old_left > 0 || new_left > 0 {
+                let Some(&body) = lines.get(idx) else {
+                    return Err(format!("Hunk '{line}' ends before all lines were read"));
+                };
+                idx += 1;
+                let (kind, content) = match body.chars().next() {
+                    Some(' ') => (LineKind::Context, &body[1..]),
+                    // Some editors strip the space from blank context lines.
+                    None => (LineKind::Context, ""),
+                    Some('-') => (LineKind::Removed, &body[1..]),
+                    Some('+') => (LineKind::Added, &body[1..]),
+                    Some('\\') => continue,
+                    _ => return Err(format!("Unexpected line in hunk '{line}': {body}")),
+                };
+                match kind {
+                    LineKind::Context if old_left > 0 && new_left > 0 => {
+                        old_left -= 1;
+                        new_left -= 1;
+                    }
+                    LineKind::Removed if old_left > 0 => old_left -= 1,
+                    LineKind::Added if new_left > 0 => new_left -= 1,
+                    _ => return Err(format!("Hunk '{line}' has more lines than its header")),
+                }
+                hunk.lines.push((kind, content.to_string()));
+            }
+            if lines
+                .get(idx)
+                .is_some_and(|next| next.starts_with("\\ No newline"))
+            {
+                idx += 1;
+                hunk.new_no_eol = hunk
+                    .lines
+                    .last()
+                    .is_some_and(|(kind, _)| *kind != LineKind::Removed);
+                // A marker after removed lines may still be followed by one
+                // for the added side.
+                if lines
+                    .get(idx)
+                    .is_some_and(|next| next.starts_with("\\ No newline"))
+                {
+                    idx += 1;
+                    hunk.new_no_eol = true;
+                }
+            }
+            file.hunks.push(hunk);
+        }
+    }
+    flush(&mut pending, &mut changes)?;
+
+    if changes.is_empty() {
+        return Err("No file changes found in patch".into());
+    }
+    Ok(changes)
+}
+
+// ── JSON edit envelope ───────────────────────────────────────────────
+
+fn parse_edits(edits: &[serde_json::Value]) -> Result<Vec<FileChange>, String> {
+    let mut changes = Vec::new();
+    for (idx, edit) in edits.iter().enumerate() {
+        let field = |name: &str| edit.get(name).and_then(|v| v.as_str());
+        let required =
+            |name: &str| field(name).ok_or_else(|| format!("Edit {}: missing '{name}'", idx + 1));
+        let path = required("path")?.to_string();
+        let action = field("action").unwrap_or("update");
+        let change = match action {
+            "create" => FileChange::Create {
+                path,
+                content: required("content")?.to_string(),
+            },
+            "delete" => FileChange::Delete { path },
+            "rename" => FileChange::Modify {
+                path,
+                new_path: Some(required("new_path")?.to_string()),
+                edits: Vec::new(),
+            },
+            "update" => {
+                let old = required("old_string")?.to_string();
+                if old.is_empty() {
+                    return Err(format!("Edit {}: old_string must not be empty", idx + 1));
+                }
+                FileChange::Modify {
+                    path,
+                    new_path: field("new_path").map(str::to_string),
+                    edits: vec![Edit::Replace {
+                        old,
+                        new: required("new_string")?.to_string(),
+                    }],
+                }
+            }
+            other => {
+                return Err(format!(
+                    "Edit {}: unknown action '{other}' (expected create, update, delete or rename)",
+                    idx + 1
+                ))
+            }
+        };
+        changes.push(change);
+    }
+    if changes.is_empty() {
+        return Err("'edits' must contain at least one edit".into());
+    }
+    Ok(changes)
+}
+
+/// Paths a call with `args` would write, for checkpointing. Empty when the
+/// arguments do not parse.
+pub(crate) fn touched_paths(args: &serde_json::Value) -> Vec<String> {
+    let changes = match (
+        args.get("patch").and_then(|v| v.as_str()),
+        args.get("edits").and_then(|v| v.as_array()),
+    ) {
+        (Some(patch), None) => parse_unified_diff(patch),
+        (None, Some(edits)) => parse_edits(edits),
+        _ => return Vec::new(),
+    };
+    let mut paths = Vec::new();
+    for change in changes.unwrap_or_default() {
+        match change {
+            FileChange::Create { path, .. } | FileChange::Delete { path } => paths.push(path),
+            FileChange::Modify { path, new_path, .. } => {
+                paths.push(path);
+                paths.extend(new_path);
+            }
+        }
+    }
+    paths
+}
+
+// ── Applying hunks ───────────────────────────────────────────────────
+
+struct Text {
+    lines: Vec<String>,
+    eol: &'static str,
+    trailing_newline: bool,
+}
+
+impl Text {
+    fn parse(content: &str) -> Self {
+        Self {
+            lines: content.lines().map(str::to_string).collect(),
+            eol: if content.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            },
+            trailing_newline: content.ends_with('\n'),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = self.lines.join(self.eol);
+        if self.trailing_newline && !self.lines.is_empty() {
+            out.push_str(self.eol);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Whitespace {
+    Exact,
+    IgnoreTrailing,
+    IgnoreSurrounding,
+}
+
+fn lines_equal(a: &str, b: &str, mode: Whitespace) -> bool {
+    match mode {
+        Whitespace::Exact => a == b,
+        Whitespace::IgnoreTrailing => a.trim_end() == b.trim_end(),
+        Whitespace::IgnoreSurrounding => a.trim() == b.trim(),
+    }
+}
+
+/// Find `old` in `lines` at or after `min_pos`, preferring the position
+/// closest to `expected`.
+fn find_block(
+    lines: &[String],
+    old: &[&str],
+    expected: usize,
+    min_pos: usize,
+    mode: Whitespace,
+) -> Option<usize> {
+    if old.len() > lines.len() {
+        return None;
+    }
+    let last = lines.len() - old.len();
+    if min_pos > last {
+        return None;
+    }
+    let expected = expected.clamp(min_pos, last);
+    let matches_at = |pos: usize| {
+        lines[pos..pos + old.len()]
+            .iter()
+            .zip(old)
+            .all(|(line, want)| lines_equal(line, want, mode))
+    };
+    for distance in 0..=(last - min_pos) {
+        if let Some(pos) = expected.checked_add(distance).filter(|p| *p <= last) {
+            if matches_at(pos) {
+                return Some(pos);
+            }
+        }
+        if distance > 0 {
+            if let Some(pos) = expected.checked_sub(distance).filter(|p| *p >= min_pos) {
+                if matches_at(pos) {
+                    return Some(pos);
+                }
+            }
+        }
+    }
+    None
+}
+
+struct Placement {
+    pos: usize,
+    skip_head: usize,
+    skip_tail: usize,
+    mode: Whitespace,
+}
+
+fn place_hunk(lines: &[String], hunk: &Hunk, expected: usize, min_pos: usize) -> Option<Placement> {
+    let head_ctx = hunk.leading_context();
+    let tail_ctx = hunk.trailing_context();
+    if hunk.lines.iter().all(|(kind, _)| *kind == LineKind::Added) {
+        return Some(Placement {
+            pos: expected.clamp(min_pos, lines.len().max(min_pos)),
+            skip_head: 0,
+            skip_tail: 0,
+            mode: Whitespace::Exact,
+        });
+    }
+    for fuzz in 0..=MAX_CONTEXT_FUZZ {
+        let skip_head = fuzz.min(head_ctx);
+        let skip_tail = fuzz.min(tail_ctx);
+        if fuzz > 0 && skip_head + skip_tail == 0 {
+            break;
+        }
+        let old = hunk.old_side(skip_head, skip_tail);
+        if old.is_empty() {
+            break;
+        }
+        for mode in [
+            Whitespace::Exact,
+            Whitespace::IgnoreTrailing,
+            Whitespace::IgnoreSurrounding,
+        ] {
+            if let Some(pos) = find_block(lines, &old, expected + skip_head, min_pos, mode) {
+                return Some(Placement {
+                    pos,
+                    skip_head,
+                    skip_tail,
+                    mode,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Apply hunks in order, returning one report line per hunk. Hunks that
+/// cannot be placed are reported and leave `text` in an unspecified state;
+/// callers discard it on failure.
+fn apply_hunks(text: &mut Text, hunks: &[&Hunk], report: &mut Vec<String>) -> bool {
+    let mut ok = true;
+    // Shift between original line numbers and positions in `text.lines`.
+    let mut delta: isize = 0;
+    let mut min_pos = 0;
+    for (number, hunk) in hunks.iter().enumerate() {
+        let number = number + 1;
+        let original = hunk.old_start.saturating_sub(1);
+        let expected = original.saturating_add_signed(delta);
+        let Some(placement) = place_hunk(&text.lines, hunk, expected, min_pos) else {
+            report.push(format!(
+                "  hunk {number} {}: FAILED (context not found)",
+                hunk.header
+            ));
+            ok = false;
+            continue;
+        };
+
+        let body = &hunk.lines[placement.skip_head..hunk.lines.len() - placement.skip_tail];
+        let mut cursor = placement.pos;
+        let mut replacement = Vec::new();
+        for (kind, line) in body {
+            match kind {
+                LineKind::Context => {
+                    // Keep the file's own version of fuzzily matched context.
+                    replacement.push(text.lines[cursor].clone());
+                    cursor += 1;
+                }
+                LineKind::Removed => cursor += 1,
+                LineKind::Added => replacement.push(line.clone()),
+            }
+        }
+        let removed = cursor - placement.pos;
+        let reaches_eof = cursor == text.lines.len();
+        let added = replacement.len();
+        text.lines.splice(placement.pos..cursor, replacement);
+        if reaches_eof && placement.skip_tail == 0 && added > 0 {
+            text.trailing_newline = !hunk.new_no_eol;
+        }
+
+        let start_in_file = placement.pos - placement.skip_head;
+        let offset = start_in_file as isize - expected as isize;
+        let mut notes = Vec::new();
+        if offset != 0 {
+            notes.push(format!("offset {offset:+}"));
+        }
+        if placement.skip_head + placement.skip_tail > 0 {
+            notes.push(format!(
+                "fuzz {}",
+                placement.skip_head.max(placement.skip_tail)
+            ));
+        }
+        match placement.mode {
+            Whitespace::Exact => {}
+            Whitespace::IgnoreTrailing => notes.push("ignoring trailing whitespace".into()),
+            Whitespace::IgnoreSurrounding => notes.push("ignoring indentation".into()),
+        }
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", notes.join(", "))
+        };
+        report.push(format!(
+            "  hunk {number} {}: applied at line {}{notes}",
+            hunk.header,
+            start_in_file + 1
+        ));
+
+        min_pos = placement.pos + added;
+        delta += added as isize - removed as isize + offset;
+    }
+    ok
+}
+
+fn apply_replace(content: &str, old: &str, new: &str) -> Result<String, String> {
+    match content.matches(old).count() {
+        0 => Err("old_string not found in file".into()),
+        1 => Ok(content.replacen(old, new, 1)),
+        n => Err(format!(
+            "old_string matches {n} times; must match exactly once"
+        )),
+    }
+}
+
+// ── Planning and committing ──────────────────────────────────────────
+
+struct FileState {
+    target: PathBuf,
+    original: Option<String>,
+    current: Option<String>,
+}
+
+struct Planner<'a> {
+    security: &'a SecurityPolicy,
+    files: BTreeMap<String, FileState>,
+    report: Vec<String>,
+    failures: usize,
+}
+
+/// Normalize a patch path to a workspace-relative form (`./a//b` → `a/b`).
+fn normalize(path: &str) -> String {
+    Path::new(path)
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl Planner<'_> {
+    /// Validate a path against policy and return where it lives on disk.
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        if path.is_empty() || !self.security.is_path_allowed(path) {
+            return Err(format!("Path not allowed by security policy: {path}"));
+        }
+        let full = self.security.workspace_dir.join(path);
+        let mut existing = full.as_path();
+        let mut rest = Vec::new();
+        while !existing.exists() {
+            let Some(name) = existing.file_name() else {
+                return Err(format!("Invalid path: {path}"));
+            };
+            rest.push(name.to_os_string());
+            existing = existing
+                .parent()
+                .ok_or_else(|| format!("Invalid path: {path}"))?;
+        }
+        if rest.is_empty() {
+            if let Ok(meta) = std::fs::symlink_metadata(existing) {
+                if meta.file_type().is_symlink() {
+                    return Err(format!("Refusing to patch through symlink: {path}"));
+                }
+            }
+            let parent = existing
+                .parent()
+                .ok_or_else(|| format!("Invalid path: {path}"))?;
+            rest.push(
+                existing
+                    .file_name()
+                    .ok_or_else(|| format!("Invalid path: {path}"))?
+                    .to_os_string(),
+            );
+            existing = parent;
+        }
+        let resolved = std::fs::canonicalize(existing)
+            .map_err(|e| format!("Failed to resolve path {path}: {e}"))?;
+        if !self.security.is_resolved_path_allowed(&resolved) {
+            return Err(format!(
+                "Resolved path escapes workspace: {}",
+                resolved.display()
+            ));
+        }
+        Ok(rest.iter().rev().fold(resolved, |acc, part| acc.join(part)))
+    }
+
+    /// Load the planned state of a file, reading it from disk on first use.
+    fn state(&mut self, path: &str) -> Result<&mut FileState, String> {
+        if !self.files.contains_key(path) {
+            let target = self.resolve(path)?;
+            let original = if target.is_file() {
+                let size = std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0);
+                if size > MAX_FILE_SIZE_BYTES {
+                    return Err(format!("{path}: file too large ({size} bytes)"));
+                }
+                let bytes =
+                    std::fs::read(&target).map_err(|e| format!("{path}: failed to read: {e}"))?;
+                Some(
+                    String::from_utf8(bytes)
+                        .map_err(|_| format!("{path}: not a UTF-8 text file"))?,
+                )
+            } else if target.exists() {
+                return Err(format!("{path}: not a regular file"));
+            } else {
+                None
+            };
+            self.files.insert(
+                path.to_string(),
+                FileState {
+                    target,
+                    current: original.clone(),
+                    original,
+                },
+            );
+        }
+        Ok(self.files.get_mut(path).expect("state inserted above"))
+    }
+
+    fn fail(&mut self, message: String) {
+        self.report.push(message);
+        self.failures += 1;
+    }
+
+    fn plan(&mut self, change: &FileChange) {
+        if let Err(message) = self.try_plan(change) {
+            self.fail(message);
+        }
+    }
+
+    fn try_plan(&mut self, change: &FileChange) -> Result<(), String> {
+        match change {
+            FileChange::Create { path, content } => {
+                let path = normalize(path);
+                let state = self.state(&path)?;
+                if state.current.is_some() {
+                    return Err(format!("A {path}: FAILED (file already exists)"));
+                }
+                state.current = Some(content.clone());
+                self.report.push(format!("A {path}"));
+            }
+            FileChange::Delete { path } => {
+                let path = normalize(path);
+                let state = self.state(&path)?;
+                if state.current.take().is_none() {
+                    return Err(format!("D {path}: FAILED (file does not exist)"));
+                }
+                self.report.push(format!("D {path}"));
+            }
+            FileChange::Modify {
+                path,
+                new_path,
+                edits,
+            } => {
+                let path = normalize(path);
+                let new_path = new_path.as_deref().map(normalize);
+                let Some(content) = self.state(&path)?.current.clone() else {
+                    return Err(format!("M {path}: FAILED (file does not exist)"));
+                };
+
+                let (updated, lines, ok) = apply_edits(&content, edits);
+                let heading = match &new_path {
+                    Some(to) => format!("R {path} -> {to}"),
+                    None => format!("M {path}"),
+                };
+                if ok {
+                    self.report.push(heading);
+                } else {
+                    self.report.push(format!("{heading}: FAILED"));
+                    self.failures += 1;
+                }
+                self.report.extend(lines);
+                if !ok {
+                    return Ok(());
+                }
+
+                match new_path {
+                    Some(to) => {
+                        let target = self.state(&to)?;
+                        if target.current.is_some() {
+                            return Err(format!("R {path} -> {to}: FAILED (target exists)"));
+                        }
+                        target.current = Some(updated);
+                        self.state(&path)?.current = None;
+                    }
+                    None => self.state(&path)?.current = Some(updated),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn apply_edits(content: &str, edits: &[Edit]) -> (String, Vec<String>, bool) {
+    let mut report = Vec::new();
+    let mut content = content.to_string();
+    let mut ok = true;
+
+    // Consecutive hunks are applied together so line numbers stay relative
+    // to the same original text.
+    let mut idx = 0;
+    while idx < edits.len() {
+        match &edits[idx] {
+            Edit::Replace { old, new } => {
+                match apply_replace(&content, old, new) {
+                    Ok(updated) => {
+                        content = updated;
+                        report.push("  replaced 1 occurrence".into());
+                    }
+                    Err(e) => {
+                        report.push(format!("  replace: FAILED ({e})"));
+                        ok = false;
+                    }
+                }
+                idx += 1;
+            }
+            Edit::Hunk(_) => {
+                let hunks: Vec<&Hunk> = edits[idx..]
+                    .iter()
+                    .map_while(|edit| match edit {
+                        Edit::Hunk(hunk) => Some(hunk),
+                        Edit::Replace { .. } => None,
+                    })
+                    .collect();
+                idx += hunks.len();
+                let mut text = Text::parse(&content);
+                if apply_hunks(&mut text, &hunks, &mut report) {
+                    content = text.render();
+                } else {
+                    ok = false;
+                }
+            }
+        }
+    }
+    (content, report, ok)
+}
+
+/// Write every changed file, restoring the originals if any write fails.
+fn commit(files: &BTreeMap<String, FileState>) -> Result<usize, String> {
+    let mut written: Vec<&FileState> = Vec::new();
+    let mut failure = None;
+    for (path, state) in files {
+        if state.current == state.original {
+            continue;
+        }
+        let result = match &state.current {
+            Some(content) => write_atomic(&state.target, content),
+            None => std::fs::remove_file(&state.target),
+        };
+        match result {
+            Ok(()) => written.push(state),
+            Err(e) => {
+                failure = Some(format!("Failed to write {path}: {e}"));
+                break;
+            }
+        }
+    }
+
+    let Some(failure) = failure else {
+        return Ok(written.len());
+    };
+    for state in written {
+        let _ = match &state.original {
+            Some(content) => write_atomic(&state.target, content),
+            None => std::fs::remove_file(&state.target),
+        };
+    }
+    Err(format!("{failure}; all changes were rolled back"))
+}
+
+fn write_atomic(target: &Path, content: &str) -> std::io::Result<()> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tmp_name = target
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    tmp_name.push(".zeroclaw-patch.tmp");
+    let tmp = target.with_file_name(tmp_name);
+    std::fs::write(&tmp, content)?;
+    if let Ok(meta) = std::fs::metadata(target) {
+        let _ = std::fs::set_permissions(&tmp, meta.permissions());
+    }
+    std::fs::rename(&tmp, target).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
+
+#[async_trait]
+impl Tool for ApplyPatchTool {
+    fn name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Apply a multi-file change atomically: either a unified diff (git diff format, supports new, \
+         deleted and renamed files) or an 'edits' list of create/update/delete/rename operations. \
+         Hunks tolerate shifted line numbers and small context drift. If any hunk fails, no file \
+         is changed and a per-hunk report is returned."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "patch": {
+                    "type": "string",
+                    "description": "Unified diff text with paths relative to the workspace ('a/' and 'b/' prefixes are accepted)"
+                },
+                "edits": {
+                    "type": "array",
+                    "description": "Alternative to 'patch': list of file operations applied in order",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "action": {
+                                "type": "string",
+                                "enum": ["create", "update", "delete", "rename"],
+                                "description": "Operation (default: update)"
+                            },
+                            "path": { "type": "string", "description": "Relative file path" },
+                            "content": { "type": "string", "description": "Full content for create" },
+                            "old_string": { "type": "string", "description": "Exact text to replace for update (must appear once)" },
+                            "new_string": { "type": "string", "description": "Replacement text for update" },
+                            "new_path": { "type": "string", "description": "Destination for rename (or update-and-move)" }
+                        },
+                        "required": ["path"]
+                    }
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Validate and report without writing (default: false)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let patch = args.get("patch").and_then(|v| v.as_str());
+        let edits = args.get("edits").and_then(|v| v.as_array());
+        let dry_run = args
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let changes = match (patch, edits) {
+            (Some(patch), None) => parse_unified_diff(patch),
+            (None, Some(edits)) => parse_edits(edits),
+            (Some(_), Some(_)) => {
+                anyhow::bail!("Provide either 'patch' or 'edits', not both")
+            }
+            (None, None) => anyhow::bail!("Missing 'patch' or 'edits' parameter"),
+        };
+        let changes = match changes {
+            Ok(changes) => changes,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Invalid patch: {e}")),
+                });
+            }
+        };
+
+        if !self.security.can_act() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+            });
+        }
+
+        if self.security.is_rate_limited() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+            });
+        }
+
+        if !self.security.record_action() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Rate limit exceeded: action budget exhausted".into()),
+            });
+        }
+
+        let security = self.security.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut planner = Planner {
+                security: &security,
+                files: BTreeMap::new(),
+                report: Vec::new(),
+                failures: 0,
+            };
+            for change in &changes {
+                planner.plan(change);
+            }
+            let mut output = planner.report.join("\n");
+
+            if planner.failures > 0 {
+                return ToolResult {
+                    success: false,
+                    output,
+                    error: Some(format!(
+                        "Patch not applied: {} problem(s) found; no files were changed",
+                        planner.failures
+                    )),
+                };
+            }
+            if dry_run {
+                let _ = write!(
+                    output,
+                    "\n\nDry run: patch applies cleanly; no files written"
+                );
+                return ToolResult {
+                    success: true,
+                    output,
+                    error: None,
+                };
+            }
+            match commit(&planner.files) {
+                Ok(count) => {
+                    let _ = write!(output, "\n\nApplied patch: {count} file(s) changed");
+                    ToolResult {
+                        success: true,
+                        output,
+                        error: None,
+                    }
+                }
+                Err(e) => ToolResult {
+                    success: false,
+                    output,
+                    error: Some(e),
+                },
+            }
+        })
+        .await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn test_security(workspace: PathBuf) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: workspace,
+            ..SecurityPolicy::default()
+        })
+    }
+
+    fn write(dir: &TempDir, rel: &str, content: &str) {
+        let path = dir.path().join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn read(dir: &TempDir, rel: &str) -> String {
+        std::fs::read_to_string(dir.path().join(rel)).unwrap()
+    }
+
+    #[test]
+    fn apply_patch_name_and_schema() {
+        let tool = ApplyPatchTool::new(test_security(std::env::temp_dir()));
+        assert_eq!(tool.name(), "apply_patch");
+        let schema = tool.parameters_schema();
+        assert!(schema["properties"]["patch"].is_object());
+        assert!(schema["properties"]["edits"].is_object());
+    }
+
+    #[tokio::test]
+    async fn applies_multi_file_unified_diff() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "src/a.rs",
+            "fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n",
+        );
+        write(&dir, "src/old.rs", "pub const X: u8 = 1;\n");
+        write(&dir, "gone.txt", "bye\n");
+
+        let patch = "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,3 +1,3 @@
+ fn a() {
+-    1
++    10
+ }
+@@ -5,3 +5,4 @@
+ fn b() {
+     2
++    + 3
+ }
+diff --git a/src/old.rs b/src/new.rs
+similarity index 50%
+rename from src/old.rs
+rename to src/new.rs
+--- a/src/old.rs
++++ b/src/new.rs
+@@ -1 +1 @@
+-pub const X: u8 = 1;
++pub const X: u8 = 2;
+diff --git a/docs/new.md b/docs/new.md
+new file mode 100644
+--- /dev/null
++++ b/docs/new.md
+@@ -0,0 +1,2 @@
++# Title
++body
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool.execute(json!({ "patch": patch })).await.unwrap();
+
+        assert!(result.success, "{:?}\n{}", result.error, result.output);
+        assert_eq!(
+            read(&dir, "src/a.rs"),
+            "fn a() {\n    10\n}\n\nfn b() {\n    2\n    + 3\n}\n"
+        );
+        assert!(!dir.path().join("src/old.rs").exists());
+        assert_eq!(read(&dir, "src/new.rs"), "pub const X: u8 = 2;\n");
+        assert_eq!(read(&dir, "docs/new.md"), "# Title\nbody\n");
+        assert!(!dir.path().join("gone.txt").exists());
+        assert!(result.output.contains("R src/old.rs -> src/new.rs"));
+        assert!(result.output.contains("A docs/new.md"));
+        assert!(result.output.contains("D gone.txt"));
+        assert!(result.output.contains("5 file(s) changed"));
+    }
+
+    #[tokio::test]
+    async fn tolerates_offsets_whitespace_and_context_drift() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "a.txt",
+            "header\nextra\none\n  two  \nthree\nfour\nfive\nsix\n",
+        );
+
+        // Line numbers are off by two, "two" has different whitespace and
+        // the last context line no longer matches.
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,5 +1,5 @@
+ one
+ two
+-three
++THREE
+ four
+ changed-in-file
+";
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool.execute(json!({ "patch": patch })).await.unwrap();
+
+        assert!(result.success, "{:?}\n{}", result.error, result.output);
+        assert_eq!(
+            read(&dir, "a.txt"),
+            "header\nextra\none\n  two  \nTHREE\nfour\nfive\nsix\n"
+        );
+        assert!(result.output.contains("applied at line 3"));
+        assert!(result.output.contains("offset +2"));
+        assert!(result.output.contains("fuzz 1"));
+    }
+
+    #[tokio::test]
+    async fn failed_hunk_leaves_every_file_untouched() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "a.txt", "alpha\n");
+        write(&dir, "b.txt", "beta\n");
+
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-alpha
++ALPHA
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-gamma
++GAMMA
+";
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool.execute(json!({ "patch": patch })).await.unwrap();
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("no files were changed"));
+        assert!(result
+            .output
+            .contains("M a.txt\n  hunk 1 @@ -1 +1 @@: applied at line 1"));
+        assert!(result.output.contains("M b.txt: FAILED"));
+        assert!(result.output.contains("context not found"));
+        assert_eq!(read(&dir, "a.txt"), "alpha\n");
+        assert_eq!(read(&dir, "b.txt"), "beta\n");
+    }
+
+    #[tokio::test]
+    async fn edits_envelope_supports_all_operations() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "config.toml", "name = \"old\"\n");
+        write(&dir, "tmp.txt", "x");
+        write(&dir, "move.txt", "m");
+
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool
+            .execute(json!({
+                "edits": [
+                    { "action": "update", "path": "config.toml", "old_string": "old", "new_string": "new" },
+                    { "action": "create", "path": "src/lib.rs", "content": "pub fn f() {}\n" },
+                    { "action": "delete", "path": "tmp.txt" },
+                    { "action": "rename", "path": "move.txt", "new_path": "moved/move.txt" }
+                ]
+            }))
+            .await
+            .unwrap();
+
+        assert!(result.success, "{:?}\n{}", result.error, result.output);
+        assert_eq!(read(&dir, "config.toml"), "name = \"new\"\n");
+        assert_eq!(read(&dir, "src/lib.rs"), "pub fn f() {}\n");
+        assert!(!dir.path().join("tmp.txt").exists());
+        assert!(!dir.path().join("move.txt").exists());
+        assert_eq!(read(&dir, "moved/move.txt"), "m");
+
+        let result = tool
+            .execute(json!({
+                "edits": [
+                    { "action": "create", "path": "config.toml", "content": "" }
+                ]
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.output.contains("already exists"));
+    }
+
+    #[tokio::test]
+    async fn preserves_crlf_and_missing_trailing_newline() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "win.txt", "a\r\nb\r\nc\r\n");
+        write(&dir, "tail.txt", "x\ny");
+
+        let patch = "\
+--- a/win.txt
++++ b/win.txt
+@@ -1,3 +1,3 @@
+ a
+-b
++B
+ c
+--- a/tail.txt
++++ b/tail.txt
+@@ -1,2 +1,3 @@
+ x
+-y
+\\ No newline at end of file
++y
++z
+\\ No newline at end of file
+";
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool.execute(json!({ "patch": patch })).await.unwrap();
+
+        assert!(result.success, "{:?}\n{}", result.error, result.output);
+        assert_eq!(read(&dir, "win.txt"), "a\r\nB\r\nc\r\n");
+        assert_eq!(read(&dir, "tail.txt"), "x\ny\nz");
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_without_writing() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "a.txt", "one\n");
+
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool
+            .execute(json!({
+                "patch": "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-one\n+two\n",
+                "dry_run": true
+            }))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(result.output.contains("Dry run"));
+        assert_eq!(read(&dir, "a.txt"), "one\n");
+    }
+
+    #[tokio::test]
+    async fn rejects_disallowed_paths_and_read_only_mode() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "a.txt", "one\n");
+
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool
+            .execute(json!({
+                "edits": [
+                    { "action": "update", "path": "a.txt", "old_string": "one", "new_string": "two" },
+                    { "action": "create", "path": "../escape.txt", "content": "x" }
+                ]
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.output.contains("not allowed"));
+        assert_eq!(read(&dir, "a.txt"), "one\n");
+        assert!(!dir.path().parent().unwrap().join("escape.txt").exists());
+
+        let read_only = ApplyPatchTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: dir.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        }));
+        let result = read_only
+            .execute(json!({
+                "edits": [{ "path": "a.txt", "old_string": "one", "new_string": "two" }]
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+        assert_eq!(read(&dir, "a.txt"), "one\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn refuses_to_patch_through_symlinks() {
+        let outside = TempDir::new().unwrap();
+        write(&outside, "target.txt", "secret\n");
+        let dir = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(
+            outside.path().join("target.txt"),
+            dir.path().join("link.txt"),
+        )
+        .unwrap();
+
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool
+            .execute(json!({
+                "patch": "--- a/link.txt\n+++ b/link.txt\n@@ -1 +1 @@\n-secret\n+owned\n"
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.output.contains("symlink"));
+        assert_eq!(read(&outside, "target.txt"), "secret\n");
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_input() {
+        let dir = TempDir::new().unwrap();
+        let tool = ApplyPatchTool::new(test_security(dir.path().to_path_buf()));
+
+        assert!(tool.execute(json!({})).await.is_err());
+
+        let result = tool
+            .execute(json!({ "patch": "just some prose" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("No file changes"));
+
+        let result = tool
+            .execute(json!({ "patch": "--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n-a\n" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("ends before"));
+    }
+}
@@ -0,0 +1,827 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::policy::ToolOperation;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use regex::{Regex, RegexBuilder};
+use serde_json::json;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const DEFAULT_MAX_RESULTS: usize = 100;
+const MAX_RESULTS: usize = 1000;
+const MAX_CONTEXT_LINES: usize = 10;
+const MAX_FILE_SIZE_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_LINE_CHARS: usize = 300;
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// Extensions searched for each `type` filter value.
+const FILE_TYPES: &[(&str, &[&str])] = &[
+    ("c", &["c", "h"]),
+    ("cpp", &["cpp", "cc", "cxx", "hpp", "hh", "hxx", "h"]),
+    ("css", &["css", "scss", "sass", "less"]),
+    ("go", &["go"]),
+    ("html", &["html", "htm"]),
+    ("java", &["java"]),
+    ("js", &["js", "mjs", "cjs", "jsx"]),
+    ("json", &["json", "jsonc"]),
+    ("kotlin", &["kt", "kts"]),
+    ("md", &["md", "markdown"]),
+    ("py", &["py", "pyi"]),
+    ("rb", &["rb"]),
+    ("rust", &["rs"]),
+    ("sh", &["sh", "bash", "zsh"]),
+    ("sql", &["sql"]),
+    ("swift", &["swift"]),
+    ("toml", &["toml"]),
+    ("ts", &["ts", "tsx", "mts", "cts"]),
+    ("yaml", &["yaml", "yml"]),
+];
+
+/// Search file contents by regex or literal text within the workspace.
+pub struct ContentSearchTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl ContentSearchTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+/// One `.gitignore` line.
+struct IgnoreRule {
+    pattern: glob::Pattern,
+    negated: bool,
+    dir_only: bool,
+    /// Rules containing a `/` match against the path relative to the
+    /// `.gitignore` directory; others match any path component name.
+    anchored: bool,
+}
+
+/// Rules loaded from a single `.gitignore`, relative to the workspace root.
+struct IgnoreFile {
+    base: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    fn load(workspace: &Path, base: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(workspace.join(base).join(".gitignore")).ok()?;
+        let rules: Vec<IgnoreRule> = content.lines().filter_map(parse_ignore_line).collect();
+        (!rules.is_empty()).then(|| Self {
+            base: base.to_path_buf(),
+            rules,
+        })
+    }
+}
+
+fn parse_ignore_line(line: &str) -> Option<IgnoreRule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let line = line.strip_prefix('/').unwrap_or(line);
+    if line.is_empty() {
+        return None;
+    }
+    Some(IgnoreRule {
+        pattern: glob::Pattern::new(line).ok()?,
+        negated,
+        dir_only,
+        anchored,
+    })
+}
+
+fn path_match_options() -> glob::MatchOptions {
+    glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    }
+}
+
+/// Last matching rule wins, with deeper `.gitignore` files applied after
+/// their parents, mirroring git's precedence.
+fn is_ignored(stack: &[IgnoreFile], rel: &Path, is_dir: bool) -> bool {
+    let options = path_match_options();
+    let mut ignored = false;
+    for file in stack {
+        let Ok(local) = rel.strip_prefix(&file.base) else {
+            continue;
+        };
+        let name = local
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        let local = local.to_string_lossy();
+        for rule in &file.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let target = if rule.anchored { &local } else { &name };
+            if rule.pattern.matches_with(target, options) {
+                ignored = !rule.negated;
+            }
+        }
+    }
+    ignored
+}
+
+struct SearchOptions {
+    regex: Regex,
+    glob: Option<glob::Pattern>,
+    extensions: Option<&'static [&'static str]>,
+    context: usize,
+    max_results: usize,
+    include_ignored: bool,
+}
+
+#[derive(Default)]
+struct SearchState {
+    output: String,
+    matches: usize,
+    files_matched: usize,
+    binary_skipped: usize,
+    truncated: bool,
+}
+
+struct Searcher<'a> {
+    security: &'a SecurityPolicy,
+    workspace: PathBuf,
+    options: SearchOptions,
+    state: SearchState,
+}
+
+impl Searcher<'_> {
+    fn walk_dir(&mut self, dir: &Path, rel: &Path, stack: &mut Vec<IgnoreFile>) {
+        let pushed = if self.options.include_ignored {
+            false
+        } else if let Some(file) = IgnoreFile::load(&self.workspace, rel) {
+            stack.push(file);
+            true
+        } else {
+            false
+        };
+
+        let mut entries: Vec<_> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries.filter_map(Result::ok).collect(),
+            Err(_) => Vec::new(),
+        };
+        entries.sort_by_key(std::fs::DirEntry::file_name);
+
+        for entry in entries {
+            if self.state.truncated {
+                break;
+            }
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
+            if name_str == ".git" {
+                continue;
+            }
+            if !self.options.include_ignored && name_str.starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let child_rel = rel.join(&name);
+            let is_dir = file_type.is_dir();
+            if !self.security.is_path_allowed(&child_rel.to_string_lossy()) {
+                continue;
+            }
+            if !self.options.include_ignored && is_ignored(stack, &child_rel, is_dir) {
+                continue;
+            }
+
+            if is_dir {
+                self.walk_dir(&entry.path(), &child_rel, stack);
+            } else if file_type.is_file() {
+                self.search_file(&entry.path(), &child_rel);
+            } else if file_type.is_symlink() {
+                // Follow file symlinks only when their target stays inside the
+                // workspace and is itself allowed; directory symlinks are never
+                // followed to avoid cycles.
+                let Ok(resolved) = std::fs::canonicalize(entry.path()) else {
+                    continue;
+                };
+                let Ok(target_rel) = resolved.strip_prefix(&self.workspace) else {
+                    continue;
+                };
+                if resolved.is_file()
+                    && self.security.is_resolved_path_allowed(&resolved)
+                    && self.security.is_path_allowed(&target_rel.to_string_lossy())
+                {
+                    self.search_file(&resolved, &child_rel);
+                }
+            }
+        }
+
+        if pushed {
+            stack.pop();
+        }
+    }
+
+    fn wants_file(&self, rel: &Path) -> bool {
+        if let Some(extensions) = self.options.extensions {
+            let ext = rel
+                .extension()
+                .map(|e| e.to_string_lossy().to_ascii_lowercase())
+                .unwrap_or_default();
+            if !extensions.contains(&ext.as_str()) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.options.glob {
+            let matched = if pattern.as_str().contains('/') {
+                pattern.matches_path_with(rel, path_match_options())
+            } else {
+                rel.file_name()
+                    .is_some_and(|name| pattern.matches(&name.to_string_lossy()))
+            };
+            if !matched {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn search_file(&mut self, path: &Path, rel: &Path) {
+        if !self.wants_file(rel) {
+            return;
+        }
+        match std::fs::metadata(path) {
+            Ok(meta) if meta.len() <= MAX_FILE_SIZE_BYTES => {}
+            _ => return,
+        }
+        let Ok(bytes) = std::fs::read(path) else {
+            return;
+        };
+        if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+            self.state.binary_skipped += 1;
+            return;
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = text.lines().collect();
+        let hits: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| self.options.regex.is_match(line))
+            .map(|(idx, _)| idx)
+            .collect();
+        if hits.is_empty() {
+            return;
+        }
+
+        let remaining = self.options.max_results - self.state.matches;
+        let shown = &hits[..hits.len().min(remaining)];
+        if shown.len() < hits.len() {
+            self.state.truncated = true;
+        }
+
+        let display = rel.to_string_lossy();
+        if !self.state.output.is_empty() {
+            self.state.output.push('\n');
+        }
+        let context = self.options.context;
+        let mut last_printed: Option<usize> = None;
+        for &hit in shown {
+            let start = hit.saturating_sub(context);
+            let end = (hit + context).min(lines.len() - 1);
+            let from = match last_printed {
+                Some(last) if start <= last + 1 => last + 1,
+                Some(_) => {
+                    self.state.output.push_str("--\n");
+                    start
+                }
+                None => start,
+            };
+            for (idx, line) in lines.iter().enumerate().take(end + 1).skip(from) {
+                let separator = if shown.binary_search(&idx).is_ok() {
+                    ':'
+                } else {
+                    '-'
+                };
+                let _ = writeln!(
+                    self.state.output,
+                    "{display}{separator}{}{separator}{}",
+                    idx + 1,
+                    truncate_line(line)
+                );
+            }
+            last_printed = Some(end.max(last_printed.unwrap_or(0)));
+        }
+
+        self.state.matches += shown.len();
+        self.state.files_matched += 1;
+        if self.state.matches >= self.options.max_results {
+            self.state.truncated = true;
+        }
+    }
+}
+
+fn truncate_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((idx, _)) => format!("{}…", &line[..idx]),
+        None => line.to_string(),
+    }
+}
+
+fn failure(message: impl Into<String>) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(message.into()),
+    }
+}
+
+#[async_trait]
+impl Tool for ContentSearchTool {
+    fn name(&self) -> &str {
+        "content_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search file contents within the workspace by regex or literal text. \
+         Returns matching lines as 'path:line:text' (context lines use '-'). \
+         Skips binary files, hidden files and anything matched by .gitignore unless include_ignored is set. \
+         Prefer this over running grep through the shell."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        let types: Vec<&str> = FILE_TYPES.iter().map(|(name, _)| *name).collect();
+        json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Regex to search for (Rust regex syntax), or plain text when literal=true"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Relative file or directory to search (default: workspace root)"
+                },
+                "glob": {
+                    "type": "string",
+                    "description": "Only search files matching this glob, e.g. '*.rs' (file name) or 'src/**/*.ts' (relative path)"
+                },
+                "type": {
+                    "type": "string",
+                    "enum": types,
+                    "description": "Only search files of this type"
+                },
+                "literal": {
+                    "type": "boolean",
+                    "description": "Treat pattern as plain text instead of a regex (default: false)"
+                },
+                "ignore_case": {
+                    "type": "boolean",
+                    "description": "Case-insensitive matching (default: false)"
+                },
+                "context": {
+                    "type": "integer",
+                    "description": "Lines of context to show around each match (default: 0, max: 10)"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum matching lines to return (default: 100, max: 1000)"
+                },
+                "include_ignored": {
+                    "type": "boolean",
+                    "description": "Also search hidden and .gitignore'd files (default: false)"
+                }
+            },
+            "required": ["pattern"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let pattern = args
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'pattern' parameter"))?;
+        let path = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let flag = |name: &str| args.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+        let number = |name: &str| {
+            args.get(name)
+                .and_then(|v| v.as_u64())
+                .and_then(|n| usize::try_from(n).ok())
+        };
+
+        if let Err(error) = self
+            .security
+            .enforce_tool_operation(ToolOperation::Read, "content_search")
+        {
+            return Ok(failure(error));
+        }
+
+        if self.security.is_rate_limited() {
+            return Ok(failure(
+                "Rate limit exceeded: too many actions in the last hour",
+            ));
+        }
+
+        if !self.security.is_path_allowed(path) {
+            return Ok(failure(format!(
+                "Path not allowed by security policy: {path}"
+            )));
+        }
+
+        let source = if flag("literal") {
+            regex::escape(pattern)
+        } else {
+            pattern.to_string()
+        };
+        let regex = match RegexBuilder::new(&source)
+            .case_insensitive(flag("ignore_case"))
+            .build()
+        {
+            Ok(regex) => regex,
+            Err(e) => return Ok(failure(format!("Invalid regex pattern: {e}"))),
+        };
+
+        let glob = match args.get("glob").and_then(|v| v.as_str()) {
+            Some(raw) => match glob::Pattern::new(raw) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => return Ok(failure(format!("Invalid glob pattern: {e}"))),
+            },
+            None => None,
+        };
+
+        let extensions = match args.get("type").and_then(|v| v.as_str()) {
+            Some(name) => match FILE_TYPES.iter().find(|(candidate, _)| *candidate == name) {
+                Some((_, extensions)) => Some(*extensions),
+                None => {
+                    let known: Vec<&str> = FILE_TYPES.iter().map(|(name, _)| *name).collect();
+                    return Ok(failure(format!(
+                        "Unknown file type '{name}'. Known types: {}",
+                        known.join(", ")
+                    )));
+                }
+            },
+            None => None,
+        };
+
+        // Record action before touching the filesystem so that probing for
+        // path existence still consumes rate limit budget.
+        if !self.security.record_action() {
+            return Ok(failure("Rate limit exceeded: action budget exhausted"));
+        }
+
+        let workspace = match std::fs::canonicalize(&self.security.workspace_dir) {
+            Ok(p) => p,
+            Err(e) => {
+                return Ok(failure(format!("Cannot resolve workspace directory: {e}")));
+            }
+        };
+
+        let target = match std::fs::canonicalize(workspace.join(path)) {
+            Ok(p) => p,
+            Err(e) => return Ok(failure(format!("Failed to resolve search path: {e}"))),
+        };
+        if !self.security.is_resolved_path_allowed(&target) {
+            return Ok(failure(format!(
+                "Resolved path escapes workspace: {}",
+                target.display()
+            )));
+        }
+
+        let options = SearchOptions {
+            regex,
+            glob,
+            extensions,
+            context: number("context").unwrap_or(0).min(MAX_CONTEXT_LINES),
+            max_results: number("max_results")
+                .unwrap_or(DEFAULT_MAX_RESULTS)
+                .clamp(1, MAX_RESULTS),
+            include_ignored: flag("include_ignored"),
+        };
+
+        let security = self.security.clone();
+        let state = tokio::task::spawn_blocking(move || {
+            let rel = target
+                .strip_prefix(&workspace)
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            let mut searcher = Searcher {
+                security: &security,
+                workspace: workspace.clone(),
+                options,
+                state: SearchState::default(),
+            };
+
+            if target.is_file() {
+                // An explicitly named file is searched even if ignored.
+                searcher.search_file(&target, &rel);
+            } else {
+                // Apply .gitignore files from the workspace root down to the
+                // search directory before walking it.
+                let mut stack = Vec::new();
+                if !searcher.options.include_ignored {
+                    let mut ancestors: Vec<&Path> = rel.ancestors().skip(1).collect();
+                    ancestors.reverse();
+                    stack.extend(
+                        ancestors
+                            .into_iter()
+                            .filter_map(|base| IgnoreFile::load(&workspace, base)),
+                    );
+                }
+                searcher.walk_dir(&target, &rel, &mut stack);
+            }
+            searcher.state
+        })
+        .await?;
+
+        let mut output = if state.matches == 0 {
+            format!("No matches for '{pattern}' found in {path}.")
+        } else {
+            state.output
+        };
+        if state.truncated {
+            let _ = write!(
+                output,
+                "\n[Results truncated at {} matches; narrow the search with path, glob or type]",
+                state.matches
+            );
+        }
+        if state.matches > 0 {
+            let _ = write!(
+                output,
+                "\nTotal: {} matches in {} files",
+                state.matches, state.files_matched
+            );
+        }
+        if state.binary_skipped > 0 {
+            let _ = write!(output, " ({} binary files skipped)", state.binary_skipped);
+        }
+
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn test_security(workspace: PathBuf) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: workspace,
+            ..SecurityPolicy::default()
+        })
+    }
+
+    fn write(dir: &TempDir, rel: &str, content: &[u8]) {
+        let path = dir.path().join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn content_search_name_and_schema() {
+        let tool = ContentSearchTool::new(test_security(std::env::temp_dir()));
+        assert_eq!(tool.name(), "content_search");
+
+        let schema = tool.parameters_schema();
+        assert!(schema["properties"]["pattern"].is_object());
+        assert!(schema["properties"]["type"]["enum"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("rust")));
+        assert!(schema["required"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("pattern")));
+    }
+
+    #[tokio::test]
+    async fn content_search_regex_literal_and_case() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "src/lib.rs", b"fn alpha() {}\nfn beta() {}\n// a.b\n");
+        write(&dir, "notes.txt", b"Alpha release\n");
+
+        let tool = ContentSearchTool::new(test_security(dir.path().to_path_buf()));
+
+        let result = tool
+            .execute(json!({"pattern": r"fn \w+\(\)"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("src/lib.rs:1:fn alpha() {}"));
+        assert!(result.output.contains("src/lib.rs:2:fn beta() {}"));
+        assert!(result.output.contains("Total: 2 matches in 1 files"));
+
+        let result = tool
+            .execute(json!({"pattern": "a.b", "literal": true}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("src/lib.rs:3:// a.b"));
+        assert!(!result.output.contains("alpha"));
+
+        let result = tool
+            .execute(json!({"pattern": "alpha", "ignore_case": true, "type": "rust"}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("src/lib.rs:1:"));
+        assert!(!result.output.contains("notes.txt"));
+
+        let result = tool
+            .execute(json!({"pattern": "alpha", "ignore_case": true, "glob": "*.txt"}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("notes.txt:1:Alpha release"));
+        assert!(!result.output.contains("lib.rs"));
+    }
+
+    #[tokio::test]
+    async fn content_search_shows_context_with_separators() {
+        let dir = TempDir::new().unwrap();
+        let body: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        write(&dir, "a.txt", body.as_bytes());
+
+        let tool = ContentSearchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool
+            .execute(json!({"pattern": "^line (3|5|15)$", "context": 1}))
+            .await
+            .unwrap();
+
+        let expected = "a.txt-2-line 2\na.txt:3:line 3\na.txt-4-line 4\na.txt:5:line 5\n\
+                        a.txt-6-line 6\n--\na.txt-14-line 14\na.txt:15:line 15\na.txt-16-line 16\n";
+        assert!(
+            result.output.starts_with(expected),
+            "unexpected output:\n{}",
+            result.output
+        );
+    }
+
+    #[tokio::test]
+    async fn content_search_respects_gitignore_hidden_and_binary() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, ".gitignore", b"target/\n*.log\n!keep.log\n");
+        write(&dir, "target/debug/out.rs", b"needle\n");
+        write(&dir, "build.log", b"needle\n");
+        write(&dir, "keep.log", b"needle\n");
+        write(&dir, ".hidden/conf", b"needle\n");
+        write(&dir, "sub/.gitignore", b"/generated.rs\n");
+        write(&dir, "sub/generated.rs", b"needle\n");
+        write(&dir, "sub/real.rs", b"needle\n");
+        write(&dir, "blob.bin", b"needle\0\x01\x02");
+
+        let tool = ContentSearchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool.execute(json!({"pattern": "needle"})).await.unwrap();
+
+        assert!(result.success);
+        assert!(result.output.contains("keep.log:1:needle"));
+        assert!(result.output.contains("sub/real.rs:1:needle"));
+        assert!(!result.output.contains("target/"));
+        assert!(!result.output.contains("build.log"));
+        assert!(!result.output.contains(".hidden"));
+        assert!(!result.output.contains("generated.rs"));
+        assert!(!result.output.contains("blob.bin"));
+        assert!(result.output.contains("1 binary files skipped"));
+
+        // Nested .gitignore still applies when searching inside its directory.
+        let result = tool
+            .execute(json!({"pattern": "needle", "path": "sub"}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("sub/real.rs"));
+        assert!(!result.output.contains("generated.rs"));
+
+        let result = tool
+            .execute(json!({"pattern": "needle", "include_ignored": true}))
+            .await
+            .unwrap();
+        assert!(result.output.contains("target/debug/out.rs"));
+        assert!(result.output.contains(".hidden/conf"));
+        assert!(result.output.contains("sub/generated.rs"));
+    }
+
+    #[tokio::test]
+    async fn content_search_caps_results() {
+        let dir = TempDir::new().unwrap();
+        let body = "hit\n".repeat(50);
+        write(&dir, "a.txt", body.as_bytes());
+        write(&dir, "b.txt", body.as_bytes());
+
+        let tool = ContentSearchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool
+            .execute(json!({"pattern": "hit", "max_results": 60}))
+            .await
+            .unwrap();
+
+        assert!(result.output.contains("a.txt:50:hit"));
+        assert!(result.output.contains("b.txt:10:hit"));
+        assert!(!result.output.contains("b.txt:11:hit"));
+        assert!(result.output.contains("Results truncated at 60 matches"));
+    }
+
+    #[tokio::test]
+    async fn content_search_filters_forbidden_and_escaping_paths() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "secrets/key.txt", b"needle\n");
+        write(&dir, "public/readme.txt", b"needle\n");
+
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: dir.path().to_path_buf(),
+            forbidden_paths: vec!["secrets".into()],
+            ..SecurityPolicy::default()
+        });
+        let tool = ContentSearchTool::new(security);
+
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(
+            dir.path().join("secrets/key.txt"),
+            dir.path().join("link.txt"),
+        )
+        .unwrap();
+
+        let result = tool.execute(json!({"pattern": "needle"})).await.unwrap();
+        assert!(result.output.contains("public/readme.txt"));
+        assert!(!result.output.contains("secrets"));
+        assert!(!result.output.contains("link.txt"));
+
+        let result = tool
+            .execute(json!({"pattern": "needle", "path": "secrets"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+
+        let result = tool
+            .execute(json!({"pattern": "needle", "path": "../"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("not allowed"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn content_search_skips_symlink_escapes() {
+        let outside = TempDir::new().unwrap();
+        write(&outside, "leak.txt", b"needle\n");
+        let dir = TempDir::new().unwrap();
+        write(&dir, "inside.txt", b"needle\n");
+        std::os::unix::fs::symlink(outside.path().join("leak.txt"), dir.path().join("link.txt"))
+            .unwrap();
+
+        let tool = ContentSearchTool::new(test_security(dir.path().to_path_buf()));
+        let result = tool.execute(json!({"pattern": "needle"})).await.unwrap();
+        assert!(result.output.contains("inside.txt"));
+        assert!(!result.output.contains("link.txt"));
+    }
+
+    #[tokio::test]
+    async fn content_search_works_in_read_only_mode() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "a.txt", b"needle\n");
+
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: dir.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tool = ContentSearchTool::new(security);
+        let result = tool.execute(json!({"pattern": "needle"})).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("a.txt:1:needle"));
+    }
+
+    #[tokio::test]
+    async fn content_search_rejects_bad_input() {
+        let dir = TempDir::new().unwrap();
+        let tool = ContentSearchTool::new(test_security(dir.path().to_path_buf()));
+
+        assert!(tool.execute(json!({})).await.is_err());
+
+        let result = tool.execute(json!({"pattern": "("})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Invalid regex"));
+
+        let result = tool
+            .execute(json!({"pattern": "x", "type": "cobol"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Unknown file type"));
+
+        let result = tool.execute(json!({"pattern": "zzz"})).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("No matches"));
+    }
+}
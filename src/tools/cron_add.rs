@@ -73,6 +73,10 @@ impl Tool for CronAddTool {
                 "model": { "type": "string" },
                 "delivery": { "type": "object" },
                 "delete_after_run": { "type": "boolean" },
+                "output_schema": {
+                    "type": "object",
+                    "description": "JSON schema the agent job's final answer must validate against"
+                },
                 "approved": {
                     "type": "boolean",
                     "description": "Set true to explicitly approve medium/high-risk shell commands in supervised mode",
@@ -218,6 +222,18 @@ impl Tool for CronAddTool {
                     None => None,
                 };
 
+                let output_schema = match args.get("output_schema") {
+                    Some(v) if v.is_object() => Some(v.clone()),
+                    Some(_) => {
+                        return Ok(ToolResult {
+                            success: false,
+                            output: String::new(),
+                            error: Some("Invalid output_schema: expected a JSON object".into()),
+                        });
+                    }
+                    None => None,
+                };
+
                 if let Some(blocked) = self.enforce_mutation_allowed("cron_add") {
                     return Ok(blocked);
                 }
@@ -231,6 +247,7 @@ impl Tool for CronAddTool {
                     model,
                     delivery,
                     delete_after_run,
+                    output_schema,
                 )
             }
         };
@@ -438,4 +455,39 @@ mod tests {
             .unwrap_or_default()
             .contains("Missing 'prompt'"));
     }
+
+    #[tokio::test]
+    async fn agent_job_stores_output_schema() {
+        let tmp = TempDir::new().unwrap();
+        let cfg = test_config(&tmp).await;
+        let tool = CronAddTool::new(cfg.clone(), test_security(&cfg));
+        let schema = json!({"type": "object", "required": ["summary"]});
+
+        let result = tool
+            .execute(json!({
+                "schedule": { "kind": "cron", "expr": "0 9 * * *" },
+                "prompt": "Summarize the inbox",
+                "output_schema": schema
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let id = serde_json::from_str::<serde_json::Value>(&result.output).unwrap()["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let job = cron::get_job(&cfg, &id).unwrap();
+        assert_eq!(job.output_schema, Some(schema));
+
+        let result = tool
+            .execute(json!({
+                "schedule": { "kind": "cron", "expr": "0 9 * * *" },
+                "prompt": "Summarize the inbox",
+                "output_schema": "not a schema"
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+    }
 }
@@ -33,6 +33,8 @@ pub struct DelegateTool {
     parent_tools: Arc<Vec<Arc<dyn Tool>>>,
     /// Inherited multimodal handling config for sub-agent loops.
     multimodal_config: crate::config::MultimodalConfig,
+    /// Cost budget shared with the parent agent, if cost tracking is enabled.
+    cost_guard: Option<crate::cost::CostGuard>,
 }
 
 impl DelegateTool {
@@ -63,6 +65,7 @@ impl DelegateTool {
             depth: 0,
             parent_tools: Arc::new(Vec::new()),
             multimodal_config: crate::config::MultimodalConfig::default(),
+            cost_guard: None,
         }
     }
 
@@ -99,6 +102,7 @@ impl DelegateTool {
             depth,
             parent_tools: Arc::new(Vec::new()),
             multimodal_config: crate::config::MultimodalConfig::default(),
+            cost_guard: None,
         }
     }
 
@@ -113,6 +117,12 @@ impl DelegateTool {
         self.multimodal_config = config;
         self
     }
+
+    /// Attach the parent's cost guard so sub-agent calls count against the same budget.
+    pub fn with_cost_guard(mut self, cost_guard: Option<crate::cost::CostGuard>) -> Self {
+        self.cost_guard = cost_guard;
+        self
+    }
 }
 
 #[async_trait]
@@ -265,6 +275,10 @@ impl Tool for DelegateTool {
                 });
             }
         };
+        let provider = match self.cost_guard.as_ref() {
+            Some(guard) => guard.wrap(&agent_config.provider, provider),
+            None => provider,
+        };
 
         // Build the message
         let full_prompt = if context.is_empty() {
@@ -406,10 +420,12 @@ impl DelegateTool {
                 None,
                 "delegate",
                 &self.multimodal_config,
+                None,
                 agent_config.max_iterations,
                 None,
                 None,
                 None,
+                None,
             ),
         )
         .await;
@@ -600,6 +616,7 @@ mod tests {
                     text: Some("done".to_string()),
                     tool_calls: Vec::new(),
                     usage: None,
+                    reasoning: None,
                 })
             } else {
                 Ok(ChatResponse {
@@ -610,6 +627,7 @@ mod tests {
                         arguments: "{\"value\":\"ping\"}".to_string(),
                     }],
                     usage: None,
+                    reasoning: None,
                 })
             }
         }
@@ -643,6 +661,7 @@ mod tests {
                     arguments: "{\"value\":\"x\"}".to_string(),
                 }],
                 usage: None,
+                reasoning: None,
             })
         }
     }
@@ -709,6 +709,8 @@ mod tests {
                     return Ok(ChatResponse {
                         text: Some("done".into()),
                         tool_calls: vec![],
+                        usage: None,
+                        reasoning: None,
                     });
                 }
                 Ok(guard.remove(0))
@@ -767,11 +769,15 @@ mod tests {
                     name: "file_read".into(),
                     arguments: r#"{"path": "report.pdf"}"#.into(),
                 }],
+                usage: None,
+                reasoning: None,
             },
             // Turn 1 continued: provider sees tool result and answers
             ChatResponse {
                 text: Some("The PDF contains a greeting: Hello PDF".into()),
                 tool_calls: vec![],
+                usage: None,
+                reasoning: None,
             },
         ]);
 
@@ -856,10 +862,14 @@ mod tests {
                     name: "file_read".into(),
                     arguments: r#"{"path": "data.bin"}"#.into(),
                 }],
+                usage: None,
+                reasoning: None,
             },
             ChatResponse {
                 text: Some("The file appears to be binary data.".into()),
                 tool_calls: vec![],
+                usage: None,
+                reasoning: None,
             },
         ]);
 
@@ -15,9 +15,11 @@
 //! To add a new tool, implement [`Tool`] in a new submodule and register it in
 //! [`all_tools_with_runtime`]. See `AGENTS.md` §7.3 for the full change playbook.
 
+pub mod apply_patch;
 pub mod browser;
 pub mod browser_open;
 pub mod composio;
+pub mod content_search;
 pub mod cron_add;
 pub mod cron_list;
 pub mod cron_remove;
@@ -39,18 +41,24 @@ pub mod memory_forget;
 pub mod memory_recall;
 pub mod memory_store;
 pub mod pdf_read;
+pub mod process;
 pub mod proxy_config;
 pub mod pushover;
 pub mod schedule;
 pub mod schema;
 pub mod screenshot;
 pub mod shell;
+pub mod skill_tool;
 pub mod traits;
+pub mod wasm_tool;
+pub mod web_fetch;
 pub mod web_search_tool;
 
+pub use apply_patch::ApplyPatchTool;
 pub use browser::{BrowserTool, ComputerUseConfig};
 pub use browser_open::BrowserOpenTool;
 pub use composio::ComposioTool;
+pub use content_search::ContentSearchTool;
 pub use cron_add::CronAddTool;
 pub use cron_list::CronListTool;
 pub use cron_remove::CronRemoveTool;
@@ -72,6 +80,7 @@ pub use memory_forget::MemoryForgetTool;
 pub use memory_recall::MemoryRecallTool;
 pub use memory_store::MemoryStoreTool;
 pub use pdf_read::PdfReadTool;
+pub use process::ProcessTool;
 pub use proxy_config::ProxyConfigTool;
 pub use pushover::PushoverTool;
 pub use schedule::ScheduleTool;
@@ -79,9 +88,14 @@ pub use schedule::ScheduleTool;
 pub use schema::{CleaningStrategy, SchemaCleanr};
 pub use screenshot::ScreenshotTool;
 pub use shell::ShellTool;
+#[allow(unused_imports)]
+pub use skill_tool::SkillCommandTool;
 pub use traits::Tool;
 #[allow(unused_imports)]
 pub use traits::{ToolResult, ToolSpec};
+#[allow(unused_imports)]
+pub use wasm_tool::WasmModuleTool;
+pub use web_fetch::WebFetchTool;
 pub use web_search_tool::WebSearchTool;
 
 use crate::config::{Config, DelegateAgentConfig};
@@ -141,7 +155,9 @@ pub fn default_tools_with_runtime(
         Box::new(FileReadTool::new(security.clone())),
         Box::new(FileWriteTool::new(security.clone())),
         Box::new(FileEditTool::new(security.clone())),
-        Box::new(GlobSearchTool::new(security)),
+        Box::new(ApplyPatchTool::new(security.clone())),
+        Box::new(GlobSearchTool::new(security.clone())),
+        Box::new(ContentSearchTool::new(security)),
     ]
 }
 
@@ -193,11 +209,13 @@ pub fn all_tools_with_runtime(
     root_config: &crate::config::Config,
 ) -> Vec<Box<dyn Tool>> {
     let mut tool_arcs: Vec<Arc<dyn Tool>> = vec![
-        Arc::new(ShellTool::new(security.clone(), runtime)),
+        Arc::new(ShellTool::new(security.clone(), runtime.clone())),
         Arc::new(FileReadTool::new(security.clone())),
         Arc::new(FileWriteTool::new(security.clone())),
         Arc::new(FileEditTool::new(security.clone())),
+        Arc::new(ApplyPatchTool::new(security.clone())),
         Arc::new(GlobSearchTool::new(security.clone())),
+        Arc::new(ContentSearchTool::new(security.clone())),
         Arc::new(CronAddTool::new(config.clone(), security.clone())),
         Arc::new(CronListTool::new(config.clone())),
         Arc::new(CronRemoveTool::new(config.clone(), security.clone())),
@@ -219,6 +237,14 @@ pub fn all_tools_with_runtime(
         )),
     ];
 
+    if root_config.process.enabled {
+        tool_arcs.push(Arc::new(ProcessTool::new(
+            security.clone(),
+            runtime.clone(),
+            root_config.process.clone(),
+        )));
+    }
+
     if browser_config.enabled {
         // Add legacy browser_open tool for simple URL opening
         tool_arcs.push(Arc::new(BrowserOpenTool::new(
@@ -265,6 +291,15 @@ pub fn all_tools_with_runtime(
         )));
     }
 
+    // Web page reader (shares the http_request domain allowlist)
+    if root_config.web_fetch.enabled {
+        tool_arcs.push(Arc::new(WebFetchTool::new(
+            security.clone(),
+            http_config.allowed_domains.clone(),
+            root_config.web_fetch.clone(),
+        )));
+    }
+
     // PDF extraction (feature-gated at compile time via rag-pdf)
     tool_arcs.push(Arc::new(PdfReadTool::new(security.clone())));
 
@@ -282,6 +317,40 @@ pub fn all_tools_with_runtime(
         }
     }
 
+    // Skill-defined tools (`[[tools]]` in SKILL.toml); built-in names take precedence.
+    let reserved_names: Vec<String> = tool_arcs
+        .iter()
+        .map(|tool| tool.name().to_string())
+        .collect();
+    tool_arcs.extend(skill_tool::skill_tools(
+        &crate::skills::load_skills_with_config(workspace_dir, root_config),
+        &reserved_names,
+        workspace_dir,
+        security,
+        &runtime,
+        http_config,
+    ));
+
+    // WASM tool modules from `runtime.wasm.tools_dir` (requires `runtime-wasm`).
+    let reserved_names: Vec<String> = tool_arcs
+        .iter()
+        .map(|tool| tool.name().to_string())
+        .collect();
+    tool_arcs.extend(wasm_tool::wasm_tools(
+        &root_config.runtime.wasm,
+        workspace_dir,
+        security,
+        &reserved_names,
+    ));
+
+    // Save pre-change file contents so a turn can be undone.
+    if root_config.checkpoints.enabled {
+        let store =
+            crate::checkpoint::CheckpointStore::new(workspace_dir, &root_config.checkpoints)
+                .with_audit(crate::security::audit::default_logger(root_config));
+        tool_arcs = crate::checkpoint::wrap_tools(tool_arcs, Arc::new(store));
+    }
+
     // Add delegation tool when agents are configured
     if !agents.is_empty() {
         let delegate_agents: HashMap<String, DelegateAgentConfig> = agents
@@ -305,10 +374,12 @@ pub fn all_tools_with_runtime(
                     .map(std::path::PathBuf::from),
                 secrets_encrypt: root_config.secrets.encrypt,
                 reasoning_enabled: root_config.runtime.reasoning_enabled,
+                reasoning_budget_tokens: root_config.runtime.reasoning_budget_tokens,
             },
         )
         .with_parent_tools(parent_tools)
-        .with_multimodal_config(root_config.multimodal.clone());
+        .with_multimodal_config(root_config.multimodal.clone())
+        .with_cost_guard(crate::cost::CostGuard::from_config(root_config));
         tool_arcs.push(Arc::new(delegate_tool));
     }
 
@@ -333,7 +404,7 @@ mod tests {
     fn default_tools_has_expected_count() {
         let security = Arc::new(SecurityPolicy::default());
         let tools = default_tools(security);
-        assert_eq!(tools.len(), 5);
+        assert_eq!(tools.len(), 7);
     }
 
     #[test]
@@ -424,7 +495,9 @@ mod tests {
         assert!(names.contains(&"file_read"));
         assert!(names.contains(&"file_write"));
         assert!(names.contains(&"file_edit"));
+        assert!(names.contains(&"apply_patch"));
         assert!(names.contains(&"glob_search"));
+        assert!(names.contains(&"content_search"));
     }
 
     #[test]
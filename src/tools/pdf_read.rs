@@ -159,73 +159,66 @@ impl Tool for PdfReadTool {
             }
         };
 
-        // pdf_extract is a blocking CPU-bound operation; keep it off the async executor.
-        #[cfg(feature = "rag-pdf")]
-        {
-            let text = match tokio::task::spawn_blocking(move || {
-                pdf_extract::extract_text_from_mem(&bytes)
-            })
-            .await
-            {
-                Ok(Ok(t)) => t,
-                Ok(Err(e)) => {
-                    return Ok(ToolResult {
-                        success: false,
-                        output: String::new(),
-                        error: Some(format!("PDF extraction failed: {e}")),
-                    });
-                }
-                Err(e) => {
-                    return Ok(ToolResult {
-                        success: false,
-                        output: String::new(),
-                        error: Some(format!("PDF extraction task panicked: {e}")),
-                    });
-                }
-            };
-
-            if text.trim().is_empty() {
+        let text = match extract_pdf_text(bytes).await {
+            Ok(text) => text,
+            Err(e) => {
                 return Ok(ToolResult {
-                    success: true,
-                    // Agent dispatchers currently forward `error` only when `success=false`.
-                    // Keep this as successful execution and expose the warning in `output`.
-                    output: "PDF contains no extractable text (may be image-only or encrypted)"
-                        .into(),
-                    error: None,
+                    success: false,
+                    output: String::new(),
+                    error: Some(e),
                 });
             }
+        };
 
-            let output = if text.chars().count() > max_chars {
-                let mut truncated: String = text.chars().take(max_chars).collect();
-                use std::fmt::Write as _;
-                let _ = write!(truncated, "\n\n... [truncated at {max_chars} chars]");
-                truncated
-            } else {
-                text
-            };
-
+        if text.trim().is_empty() {
             return Ok(ToolResult {
                 success: true,
-                output,
+                // Agent dispatchers currently forward `error` only when `success=false`.
+                // Keep this as successful execution and expose the warning in `output`.
+                output: "PDF contains no extractable text (may be image-only or encrypted)".into(),
                 error: None,
             });
         }
 
-        #[cfg(not(feature = "rag-pdf"))]
+        let output = if text.chars().count() > max_chars {
+            let mut truncated: String = text.chars().take(max_chars).collect();
+            use std::fmt::Write as _;
+            let _ = write!(truncated, "\n\n... [truncated at {max_chars} chars]");
+            truncated
+        } else {
+            text
+        };
+
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+/// Extract the text of an in-memory PDF. Shared with `web_fetch` for PDF
+/// responses; fails with an actionable message without the `rag-pdf` feature.
+#[cfg_attr(not(feature = "rag-pdf"), allow(clippy::unused_async))]
+pub(super) async fn extract_pdf_text(bytes: Vec<u8>) -> Result<String, String> {
+    // pdf_extract is a blocking CPU-bound operation; keep it off the async executor.
+    #[cfg(feature = "rag-pdf")]
+    {
+        match tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem(&bytes)).await
         {
-            let _ = bytes;
-            let _ = max_chars;
-            Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(
-                    "PDF extraction is not enabled. \
-                     Rebuild with: cargo build --features rag-pdf"
-                        .into(),
-                ),
-            })
+            Ok(Ok(text)) => Ok(text),
+            Ok(Err(e)) => Err(format!("PDF extraction failed: {e}")),
+            Err(e) => Err(format!("PDF extraction task panicked: {e}")),
         }
     }
+
+    #[cfg(not(feature = "rag-pdf"))]
+    {
+        let _ = bytes;
+        Err("PDF extraction is not enabled. \
+             Rebuild with: cargo build --features rag-pdf"
+            .into())
+    }
 }
 
 #[cfg(test)]
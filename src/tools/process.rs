@@ -0,0 +1,1017 @@
+//! Background processes for builds, test suites, dev servers and other
+//! commands that outlive `shell`'s timeout.
+//!
+//! Commands pass the same [`SecurityPolicy`] checks as `shell` and are built
+//! by the same [`RuntimeAdapter`] with a scrubbed environment, but keep
+//! running after the tool call returns. Output is buffered per stream with
+//! byte offsets so the agent can read it incrementally. On Unix each process
+//! leads its own process group so signals reach the whole tree.
+//!
+//! Channels share one tool registry across senders, so processes are owned by
+//! the session running the turn ([`with_session`]): limits, `list` and the
+//! per-process actions only see the caller's own processes. Processes are
+//! killed when their session ends ([`end_session`]), when the tool registry
+//! is dropped, when they exceed `[process] max_runtime_secs`, and on daemon
+//! shutdown ([`terminate_all`]).
+
+use super::shell::build_sandboxed_command;
+use super::traits::{Tool, ToolResult};
+use crate::config::ProcessConfig;
+use crate::runtime::RuntimeAdapter;
+use crate::security::policy::ToolOperation;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::ChildStdin;
+use tokio::sync::{mpsc, watch};
+
+/// Default and maximum time `wait` blocks.
+const DEFAULT_WAIT_SECS: u64 = 30;
+const MAX_WAIT_SECS: u64 = 600;
+/// Default and maximum bytes returned per stream by one read.
+const DEFAULT_READ_BYTES: usize = 16 * 1024;
+const MAX_READ_BYTES: usize = 256 * 1024;
+/// Exited processes kept for `output` and `list`; older ones are forgotten.
+const MAX_EXITED_KEPT: usize = 16;
+/// Pipes are drained this long after exit; background grandchildren may
+/// hold them open indefinitely.
+const DRAIN_AFTER_EXIT: Duration = Duration::from_secs(2);
+
+/// Session that owns processes started outside [`with_session`].
+const DEFAULT_SESSION: &str = "default";
+
+tokio::task_local! {
+    static CURRENT_SESSION: String;
+}
+
+/// Run one agent turn so processes it starts belong to `session` and only
+/// processes of `session` are visible to it.
+pub async fn with_session<F: Future>(session: &str, fut: F) -> F::Output {
+    CURRENT_SESSION.scope(session.to_string(), fut).await
+}
+
+fn current_session() -> String {
+    CURRENT_SESSION
+        .try_with(Clone::clone)
+        .unwrap_or_else(|_| DEFAULT_SESSION.to_string())
+}
+
+/// Process-group leaders of every running background process and the session
+/// that started them, for [`end_session`] and [`terminate_all`].
+static RUNNING_GROUPS: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+
+fn running_groups() -> &'static Mutex<HashMap<u32, String>> {
+    RUNNING_GROUPS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Kill every background process started by `session`. Called when a
+/// conversation is reset or expires.
+pub fn end_session(session: &str) {
+    let groups: Vec<u32> = {
+        let mut running = running_groups().lock();
+        let pids = running
+            .iter()
+            .filter(|(_, owner)| owner.as_str() == session)
+            .map(|(pid, _)| *pid)
+            .collect::<Vec<_>>();
+        for pid in &pids {
+            running.remove(pid);
+        }
+        pids
+    };
+    for pid in groups {
+        signal_group(pid, Signal::Kill);
+    }
+}
+
+/// Kill every background process started by any session. Called on daemon
+/// shutdown, where registries may not be dropped before exit.
+pub fn terminate_all() {
+    let groups: Vec<u32> = running_groups()
+        .lock()
+        .drain()
+        .map(|(pid, _)| pid)
+        .collect();
+    for pid in groups {
+        signal_group(pid, Signal::Kill);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    Interrupt,
+    Terminate,
+    Kill,
+}
+
+impl Signal {
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().trim_start_matches("sig") {
+            "int" => Some(Self::Interrupt),
+            "term" => Some(Self::Terminate),
+            "kill" => Some(Self::Kill),
+            _ => None,
+        }
+    }
+}
+
+/// Send `signal` to the process group led by `pid`.
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: Signal) -> bool {
+    let Ok(pgid) = i32::try_from(pid) else {
+        return false;
+    };
+    let signal = match signal {
+        Signal::Interrupt => libc::SIGINT,
+        Signal::Terminate => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+    };
+    // SAFETY: kill(2) has no memory-safety preconditions; a negative pid
+    // addresses the process group.
+    unsafe { libc::kill(-pgid, signal) == 0 }
+}
+
+#[cfg(not(unix))]
+fn signal_group(_pid: u32, _signal: Signal) -> bool {
+    false
+}
+
+/// Output of one stream. Only the newest `limit` bytes are kept; offsets
+/// count every byte ever written.
+#[derive(Debug)]
+struct OutputBuffer {
+    data: Vec<u8>,
+    dropped: u64,
+    limit: usize,
+}
+
+impl OutputBuffer {
+    fn new(limit: usize) -> Self {
+        Self {
+            data: Vec::new(),
+            dropped: 0,
+            limit: limit.max(1),
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+        if self.data.len() > self.limit {
+            let excess = self.data.len() - self.limit;
+            self.data.drain(..excess);
+            self.dropped += excess as u64;
+        }
+    }
+
+    fn end(&self) -> u64 {
+        self.dropped + self.data.len() as u64
+    }
+
+    /// Read up to `max` bytes from `offset`. Returns the text, the offset to
+    /// read from next, and how many requested bytes were already dropped.
+    fn read(&self, offset: u64, max: usize) -> (String, u64, u64) {
+        let start = offset.clamp(self.dropped, self.end());
+        let missed = start - offset.min(start);
+        let from = usize::try_from(start - self.dropped).unwrap_or(self.data.len());
+        let to = from.saturating_add(max).min(self.data.len());
+        let bytes = &self.data[from..to];
+        // Hold back a multi-byte character split by `max` or still being
+        // written; the next read picks it up.
+        let consumed = match std::str::from_utf8(bytes) {
+            Err(e) if e.error_len().is_none() && e.valid_up_to() > 0 => e.valid_up_to(),
+            _ => bytes.len(),
+        };
+        let text = String::from_utf8_lossy(&bytes[..consumed]).into_owned();
+        (text, start + consumed as u64, missed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ExitState {
+    code: Option<i32>,
+    description: String,
+    elapsed_secs: u64,
+}
+
+struct ManagedProcess {
+    id: String,
+    command: String,
+    pid: Option<u32>,
+    started_at: Instant,
+    stdout: Mutex<OutputBuffer>,
+    stderr: Mutex<OutputBuffer>,
+    stdin: tokio::sync::Mutex<Option<ChildStdin>>,
+    exit: watch::Receiver<Option<ExitState>>,
+    kill: mpsc::UnboundedSender<()>,
+}
+
+impl ManagedProcess {
+    fn exit_state(&self) -> Option<ExitState> {
+        self.exit.borrow().clone()
+    }
+
+    fn is_running(&self) -> bool {
+        self.exit.borrow().is_none()
+    }
+
+    fn signal(&self, signal: Signal) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        let delivered = self.pid.is_some_and(|pid| signal_group(pid, signal));
+        // Without process groups only a hard kill is possible.
+        if !delivered && signal == Signal::Kill {
+            return self.kill.send(()).is_ok();
+        }
+        delivered
+    }
+
+    fn summary(&self) -> Value {
+        let exit = self.exit_state();
+        json!({
+            "id": self.id,
+            "pid": self.pid,
+            "command": self.command,
+            "status": if exit.is_some() { "exited" } else { "running" },
+            "exit_code": exit.as_ref().and_then(|state| state.code),
+            "elapsed_secs": exit
+                .as_ref()
+                .map_or_else(|| self.started_at.elapsed().as_secs(), |state| state.elapsed_secs),
+            "exit": exit.map(|state| state.description),
+        })
+    }
+
+    fn output(&self, stdout_offset: u64, stderr_offset: u64, max_bytes: usize) -> Value {
+        let (stdout, stdout_next, stdout_missed) =
+            self.stdout.lock().read(stdout_offset, max_bytes);
+        let (stderr, stderr_next, stderr_missed) =
+            self.stderr.lock().read(stderr_offset, max_bytes);
+        let mut value = self.summary();
+        value["stdout"] = json!(stdout);
+        value["stderr"] = json!(stderr);
+        value["stdout_offset"] = json!(stdout_next);
+        value["stderr_offset"] = json!(stderr_next);
+        value["more_output"] =
+            json!(stdout_next < self.stdout.lock().end() || stderr_next < self.stderr.lock().end());
+        if stdout_missed + stderr_missed > 0 {
+            value["dropped_bytes"] = json!(stdout_missed + stderr_missed);
+        }
+        value
+    }
+}
+
+async fn pump(mut reader: impl AsyncRead + Unpin, process: Arc<ManagedProcess>, stderr: bool) {
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let buffer = if stderr {
+                    &process.stderr
+                } else {
+                    &process.stdout
+                };
+                buffer.lock().push(&buf[..n]);
+            }
+        }
+    }
+}
+
+fn describe_exit(status: std::process::ExitStatus) -> ExitState {
+    if let Some(code) = status.code() {
+        return ExitState {
+            code: Some(code),
+            description: format!("exited with code {code}"),
+            elapsed_secs: 0,
+        };
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return ExitState {
+                code: None,
+                description: format!("killed by signal {signal}"),
+                elapsed_secs: 0,
+            };
+        }
+    }
+    ExitState {
+        code: None,
+        description: "exited".into(),
+        elapsed_secs: 0,
+    }
+}
+
+/// Background processes of every session, keyed by session.
+struct ProcessManager {
+    config: ProcessConfig,
+    processes: Mutex<HashMap<String, Vec<Arc<ManagedProcess>>>>,
+    next_id: AtomicU64,
+}
+
+impl ProcessManager {
+    fn new(config: ProcessConfig) -> Self {
+        Self {
+            config,
+            processes: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn get(&self, session: &str, id: &str) -> Option<Arc<ManagedProcess>> {
+        self.processes
+            .lock()
+            .get(session)?
+            .iter()
+            .find(|process| process.id == id)
+            .cloned()
+    }
+
+    fn list(&self, session: &str) -> Vec<Arc<ManagedProcess>> {
+        self.processes
+            .lock()
+            .get(session)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn running_count(&self, session: &str) -> usize {
+        self.processes.lock().get(session).map_or(0, |processes| {
+            processes
+                .iter()
+                .filter(|process| process.is_running())
+                .count()
+        })
+    }
+
+    fn spawn(
+        &self,
+        session: &str,
+        mut cmd: tokio::process::Command,
+        command: &str,
+    ) -> anyhow::Result<Value> {
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        let mut child = cmd.spawn()?;
+        let pid = child.id();
+        let (exit_tx, exit_rx) = watch::channel(None);
+        let (kill_tx, mut kill_rx) = mpsc::unbounded_channel();
+        let process = Arc::new(ManagedProcess {
+            id: format!("p{}", self.next_id.fetch_add(1, Ordering::Relaxed)),
+            command: command.to_string(),
+            pid,
+            started_at: Instant::now(),
+            stdout: Mutex::new(OutputBuffer::new(self.config.output_limit_bytes)),
+            stderr: Mutex::new(OutputBuffer::new(self.config.output_limit_bytes)),
+            stdin: tokio::sync::Mutex::new(child.stdin.take()),
+            exit: exit_rx,
+            kill: kill_tx,
+        });
+        if let Some(pid) = pid {
+            running_groups().lock().insert(pid, session.to_string());
+        }
+
+        let pumps: Vec<_> = [
+            child
+                .stdout
+                .take()
+                .map(|out| tokio::spawn(pump(out, process.clone(), false))),
+            child
+                .stderr
+                .take()
+                .map(|err| tokio::spawn(pump(err, process.clone(), true))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let max_runtime = (self.config.max_runtime_secs > 0)
+            .then(|| Duration::from_secs(self.config.max_runtime_secs));
+        let waiter = process.clone();
+        tokio::spawn(async move {
+            let deadline = async {
+                match max_runtime {
+                    Some(limit) => tokio::time::sleep(limit).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::pin!(deadline);
+            let mut timed_out = false;
+            let status = loop {
+                tokio::select! {
+                    status = child.wait() => break status,
+                    Some(()) = kill_rx.recv() => {
+                        let _ = child.start_kill();
+                    }
+                    () = &mut deadline, if !timed_out => {
+                        timed_out = true;
+                        if !waiter.pid.is_some_and(|pid| signal_group(pid, Signal::Kill)) {
+                            let _ = child.start_kill();
+                        }
+                    }
+                }
+            };
+            if let Some(pid) = waiter.pid {
+                running_groups().lock().remove(&pid);
+            }
+            let _ =
+                tokio::time::timeout(DRAIN_AFTER_EXIT, futures_util::future::join_all(pumps)).await;
+
+            let elapsed_secs = waiter.started_at.elapsed().as_secs();
+            let state = match status {
+                Ok(_) if timed_out => ExitState {
+                    code: None,
+                    description: format!(
+                        "killed after exceeding max runtime of {}s",
+                        max_runtime.map_or(0, |limit| limit.as_secs())
+                    ),
+                    elapsed_secs,
+                },
+                Ok(status) => ExitState {
+                    elapsed_secs,
+                    ..describe_exit(status)
+                },
+                Err(e) => ExitState {
+                    code: None,
+                    description: format!("wait failed: {e}"),
+                    elapsed_secs,
+                },
+            };
+            *waiter.stdin.lock().await = None;
+            let _ = exit_tx.send(Some(state));
+        });
+
+        let summary = process.summary();
+        let mut sessions = self.processes.lock();
+        let processes = sessions.entry(session.to_string()).or_default();
+        processes.push(process);
+        let exited = processes.iter().filter(|p| !p.is_running()).count();
+        if exited > MAX_EXITED_KEPT {
+            let mut excess = exited - MAX_EXITED_KEPT;
+            processes.retain(|p| {
+                if excess > 0 && !p.is_running() {
+                    excess -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        Ok(summary)
+    }
+}
+
+impl Drop for ProcessManager {
+    fn drop(&mut self) {
+        for process in self.processes.get_mut().values().flatten() {
+            process.signal(Signal::Kill);
+        }
+    }
+}
+
+/// Start, read, wait on, write to, signal and list background processes.
+pub struct ProcessTool {
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+    manager: ProcessManager,
+}
+
+impl ProcessTool {
+    pub fn new(
+        security: Arc<SecurityPolicy>,
+        runtime: Arc<dyn RuntimeAdapter>,
+        config: ProcessConfig,
+    ) -> Self {
+        Self {
+            security,
+            runtime,
+            manager: ProcessManager::new(config),
+        }
+    }
+
+    fn process(&self, args: &Value) -> anyhow::Result<Result<Arc<ManagedProcess>, ToolResult>> {
+        let id = args
+            .get("id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'id' parameter"))?;
+        Ok(self
+            .manager
+            .get(&current_session(), id)
+            .ok_or_else(|| failure(format!("Unknown process '{id}'. Use action 'list'."))))
+    }
+
+    fn start(&self, args: &Value) -> anyhow::Result<ToolResult> {
+        let command = args
+            .get("command")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'command' parameter for start action"))?;
+        let approved = args
+            .get("approved")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        if !self.runtime.supports_long_running() {
+            return Ok(failure(format!(
+                "Runtime '{}' does not support long-running processes; use the shell tool",
+                self.runtime.name()
+            )));
+        }
+        if self.security.is_rate_limited() {
+            return Ok(failure(
+                "Rate limit exceeded: too many actions in the last hour",
+            ));
+        }
+        if let Err(reason) = self.security.validate_command_execution(command, approved) {
+            return Ok(failure(reason));
+        }
+        let session = current_session();
+        let running = self.manager.running_count(&session);
+        if running >= self.manager.config.max_running {
+            return Ok(failure(format!(
+                "{running} processes already running (limit {}); wait for or kill one first",
+                self.manager.config.max_running
+            )));
+        }
+        if !self.security.record_action() {
+            return Ok(failure("Rate limit exceeded: action budget exhausted"));
+        }
+
+        let cmd = match build_sandboxed_command(
+            self.runtime.as_ref(),
+            command,
+            &self.security.workspace_dir,
+        ) {
+            Ok(cmd) => cmd,
+            Err(e) => return Ok(failure(format!("Failed to build runtime command: {e}"))),
+        };
+        match self.manager.spawn(&session, cmd, command) {
+            Ok(summary) => Ok(success(&summary)),
+            Err(e) => Ok(failure(format!("Failed to start process: {e}"))),
+        }
+    }
+
+    fn output(&self, process: &ManagedProcess, args: &Value) -> ToolResult {
+        let offset = |key: &str| args.get(key).and_then(Value::as_u64).unwrap_or(0);
+        let max_bytes = args
+            .get("max_bytes")
+            .and_then(Value::as_u64)
+            .and_then(|n| usize::try_from(n).ok())
+            .unwrap_or(DEFAULT_READ_BYTES)
+            .clamp(1, MAX_READ_BYTES);
+        success(&process.output(offset("stdout_offset"), offset("stderr_offset"), max_bytes))
+    }
+
+    async fn wait(&self, process: &ManagedProcess, args: &Value) -> ToolResult {
+        let timeout = args
+            .get("timeout_secs")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_WAIT_SECS)
+            .min(MAX_WAIT_SECS);
+        let mut exit = process.exit.clone();
+        let _ = tokio::time::timeout(Duration::from_secs(timeout), exit.wait_for(Option::is_some))
+            .await;
+        self.output(process, args)
+    }
+
+    async fn write(&self, process: &ManagedProcess, args: &Value) -> anyhow::Result<ToolResult> {
+        let input = args.get("input").and_then(Value::as_str).unwrap_or("");
+        let close = args
+            .get("close_stdin")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if let Err(reason) = self
+            .security
+            .enforce_tool_operation(ToolOperation::Act, "process.write")
+        {
+            return Ok(failure(reason));
+        }
+
+        let mut stdin = process.stdin.lock().await;
+        let Some(pipe) = stdin.as_mut() else {
+            return Ok(failure(format!(
+                "stdin of process '{}' is closed",
+                process.id
+            )));
+        };
+        if let Err(e) = async {
+            pipe.write_all(input.as_bytes()).await?;
+            pipe.flush().await
+        }
+        .await
+        {
+            *stdin = None;
+            return Ok(failure(format!("Failed to write to stdin: {e}")));
+        }
+        if close {
+            *stdin = None;
+        }
+        Ok(success(&json!({
+            "id": process.id,
+            "bytes_written": input.len(),
+            "stdin_closed": stdin.is_none(),
+        })))
+    }
+
+    fn kill(&self, process: &ManagedProcess, args: &Value) -> anyhow::Result<ToolResult> {
+        let name = args.get("signal").and_then(Value::as_str).unwrap_or("term");
+        let Some(signal) = Signal::parse(name) else {
+            return Ok(failure(format!(
+                "Unknown signal '{name}'. Use term, int or kill."
+            )));
+        };
+        if let Err(reason) = self
+            .security
+            .enforce_tool_operation(ToolOperation::Act, "process.kill")
+        {
+            return Ok(failure(reason));
+        }
+        if !process.is_running() {
+            return Ok(success(&process.summary()));
+        }
+        if !process.signal(signal) {
+            return Ok(failure(format!(
+                "Could not send {name} to process '{}'",
+                process.id
+            )));
+        }
+        Ok(success(
+            &json!({ "id": process.id, "signal": name, "sent": true }),
+        ))
+    }
+}
+
+fn success(value: &Value) -> ToolResult {
+    ToolResult {
+        success: true,
+        output: serde_json::to_string_pretty(value).unwrap_or_default(),
+        error: None,
+    }
+}
+
+fn failure(message: impl Into<String>) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(message.into()),
+    }
+}
+
+#[async_trait]
+impl Tool for ProcessTool {
+    fn name(&self) -> &str {
+        "process"
+    }
+
+    fn description(&self) -> &str {
+        "Run long-running commands (builds, test suites, servers) in the background. Actions: start/output/wait/write/kill/list. Read output incrementally by passing back stdout_offset/stderr_offset."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["start", "output", "wait", "write", "kill", "list"],
+                    "description": "Action to perform"
+                },
+                "command": {
+                    "type": "string",
+                    "description": "Shell command to run. Required for start."
+                },
+                "approved": {
+                    "type": "boolean",
+                    "description": "Set true to explicitly approve medium/high-risk commands in supervised mode",
+                    "default": false
+                },
+                "id": {
+                    "type": "string",
+                    "description": "Process ID returned by start. Required for output/wait/write/kill."
+                },
+                "stdout_offset": {
+                    "type": "integer",
+                    "description": "Byte offset to read stdout from (output/wait). Use the value returned by the previous read.",
+                    "default": 0
+                },
+                "stderr_offset": {
+                    "type": "integer",
+                    "description": "Byte offset to read stderr from (output/wait).",
+                    "default": 0
+                },
+                "max_bytes": {
+                    "type": "integer",
+                    "description": "Maximum bytes returned per stream (output/wait).",
+                    "default": DEFAULT_READ_BYTES
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "How long wait blocks for the process to exit (max 600).",
+                    "default": DEFAULT_WAIT_SECS
+                },
+                "input": {
+                    "type": "string",
+                    "description": "Text to write to stdin (write)."
+                },
+                "close_stdin": {
+                    "type": "boolean",
+                    "description": "Close stdin after writing (write).",
+                    "default": false
+                },
+                "signal": {
+                    "type": "string",
+                    "enum": ["term", "int", "kill"],
+                    "description": "Signal to send (kill).",
+                    "default": "term"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
+        let action = args
+            .get("action")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'action' parameter"))?;
+
+        match action {
+            "start" => self.start(&args),
+            "list" => Ok(success(&json!(self
+                .manager
+                .list(&current_session())
+                .iter()
+                .map(|process| process.summary())
+                .collect::<Vec<_>>()))),
+            "output" | "wait" | "write" | "kill" => {
+                let process = match self.process(&args)? {
+                    Ok(process) => process,
+                    Err(unknown) => return Ok(unknown),
+                };
+                match action {
+                    "output" => Ok(self.output(&process, &args)),
+                    "wait" => Ok(self.wait(&process, &args).await),
+                    "write" => self.write(&process, &args).await,
+                    _ => self.kill(&process, &args),
+                }
+            }
+            other => Ok(failure(format!(
+                "Unknown action '{other}'. Use start/output/wait/write/kill/list."
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::NativeRuntime;
+    use crate::security::AutonomyLevel;
+
+    fn test_tool(autonomy: AutonomyLevel, config: ProcessConfig) -> ProcessTool {
+        let mut security = SecurityPolicy {
+            autonomy,
+            workspace_dir: std::env::temp_dir(),
+            ..SecurityPolicy::default()
+        };
+        security.allowed_commands.push("sleep".into());
+        let security = Arc::new(security);
+        ProcessTool::new(security, Arc::new(NativeRuntime::new()), config)
+    }
+
+    fn parse(result: &ToolResult) -> Value {
+        assert!(result.success, "tool failed: {:?}", result.error);
+        serde_json::from_str(&result.output).unwrap()
+    }
+
+    async fn start(tool: &ProcessTool, command: &str) -> String {
+        let result = tool
+            .execute(json!({"action": "start", "command": command}))
+            .await
+            .unwrap();
+        parse(&result)["id"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn output_buffer_keeps_offsets_across_dropped_bytes() {
+        let mut buffer = OutputBuffer::new(8);
+        buffer.push(b"0123456789");
+        assert_eq!(buffer.end(), 10);
+
+        let (text, next, missed) = buffer.read(0, 100);
+        assert_eq!(text, "23456789");
+        assert_eq!(next, 10);
+        assert_eq!(missed, 2);
+
+        let (text, next, _) = buffer.read(4, 3);
+        assert_eq!(text, "456");
+        assert_eq!(next, 7);
+    }
+
+    #[test]
+    fn output_buffer_holds_back_split_characters() {
+        let mut buffer = OutputBuffer::new(64);
+        buffer.push("aé".as_bytes());
+        let (text, next, _) = buffer.read(0, 2);
+        assert_eq!(text, "a");
+        assert_eq!(next, 1);
+        let (text, next, _) = buffer.read(next, 2);
+        assert_eq!(text, "é");
+        assert_eq!(next, 3);
+    }
+
+    #[tokio::test]
+    async fn start_wait_and_read_incrementally() {
+        let tool = test_tool(AutonomyLevel::Full, ProcessConfig::default());
+        let id = start(&tool, "echo one; ls zeroclaw-missing-file; echo three").await;
+
+        let waited = parse(
+            &tool
+                .execute(json!({"action": "wait", "id": id, "timeout_secs": 10}))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(waited["status"], "exited");
+        assert_eq!(waited["exit_code"], 0);
+        assert_eq!(waited["stdout"], "one\nthree\n");
+        assert!(waited["stderr"]
+            .as_str()
+            .unwrap()
+            .contains("zeroclaw-missing-file"));
+
+        let again = parse(
+            &tool
+                .execute(json!({
+                    "action": "output",
+                    "id": id,
+                    "stdout_offset": waited["stdout_offset"],
+                    "stderr_offset": waited["stderr_offset"],
+                }))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(again["stdout"], "");
+        assert_eq!(again["more_output"], false);
+    }
+
+    #[tokio::test]
+    async fn write_to_stdin_and_close() {
+        let tool = test_tool(AutonomyLevel::Full, ProcessConfig::default());
+        let id = start(&tool, "cat").await;
+
+        let written = tool
+            .execute(json!({"action": "write", "id": id, "input": "hello\n", "close_stdin": true}))
+            .await
+            .unwrap();
+        assert!(written.success, "{:?}", written.error);
+
+        let waited = parse(
+            &tool
+                .execute(json!({"action": "wait", "id": id, "timeout_secs": 10}))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(waited["stdout"], "hello\n");
+        assert_eq!(waited["exit_code"], 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn kill_terminates_the_process_group() {
+        let tool = test_tool(AutonomyLevel::Full, ProcessConfig::default());
+        let id = start(&tool, "sleep 30; echo done").await;
+
+        let killed = tool
+            .execute(json!({"action": "kill", "id": id}))
+            .await
+            .unwrap();
+        assert!(killed.success, "{:?}", killed.error);
+
+        let waited = parse(
+            &tool
+                .execute(json!({"action": "wait", "id": id, "timeout_secs": 10}))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(waited["status"], "exited");
+        assert_eq!(
+            waited["exit"],
+            format!("killed by signal {}", libc::SIGTERM)
+        );
+    }
+
+    #[tokio::test]
+    async fn max_runtime_kills_long_processes() {
+        let config = ProcessConfig {
+            max_runtime_secs: 1,
+            ..ProcessConfig::default()
+        };
+        let tool = test_tool(AutonomyLevel::Full, config);
+        let id = start(&tool, "sleep 30").await;
+
+        let waited = parse(
+            &tool
+                .execute(json!({"action": "wait", "id": id, "timeout_secs": 10}))
+                .await
+                .unwrap(),
+        );
+        assert!(waited["exit"]
+            .as_str()
+            .unwrap()
+            .contains("max runtime of 1s"));
+    }
+
+    #[tokio::test]
+    async fn running_limit_and_policy_are_enforced() {
+        let config = ProcessConfig {
+            max_running: 1,
+            ..ProcessConfig::default()
+        };
+        let tool = test_tool(AutonomyLevel::Full, config);
+        let id = start(&tool, "sleep 30").await;
+
+        let second = tool
+            .execute(json!({"action": "start", "command": "sleep 30"}))
+            .await
+            .unwrap();
+        assert!(!second.success);
+        assert!(second.error.unwrap().contains("limit 1"));
+
+        let listed = parse(&tool.execute(json!({"action": "list"})).await.unwrap());
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["id"], id.as_str());
+
+        let readonly = test_tool(AutonomyLevel::ReadOnly, ProcessConfig::default());
+        let blocked = readonly
+            .execute(json!({"action": "start", "command": "ls"}))
+            .await
+            .unwrap();
+        assert!(!blocked.success);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn processes_are_scoped_to_their_session() {
+        let config = ProcessConfig {
+            max_running: 1,
+            ..ProcessConfig::default()
+        };
+        let tool = test_tool(AutonomyLevel::Full, config);
+        let id = with_session("telegram_alice", start(&tool, "sleep 30")).await;
+
+        with_session("telegram_bob", async {
+            let listed = parse(&tool.execute(json!({"action": "list"})).await.unwrap());
+            assert!(listed.as_array().unwrap().is_empty());
+
+            let killed = tool
+                .execute(json!({"action": "kill", "id": id}))
+                .await
+                .unwrap();
+            assert!(!killed.success);
+            assert!(killed.error.unwrap().contains("Unknown process"));
+
+            // The running limit counts only this session's processes.
+            start(&tool, "sleep 30").await;
+        })
+        .await;
+
+        end_session("telegram_alice");
+        with_session("telegram_alice", async {
+            let waited = parse(
+                &tool
+                    .execute(json!({"action": "wait", "id": id, "timeout_secs": 10}))
+                    .await
+                    .unwrap(),
+            );
+            assert_eq!(
+                waited["exit"],
+                format!("killed by signal {}", libc::SIGKILL)
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn unknown_process_id_is_reported() {
+        let tool = test_tool(AutonomyLevel::Full, ProcessConfig::default());
+        let result = tool
+            .execute(json!({"action": "output", "id": "p99"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Unknown process 'p99'"));
+    }
+}
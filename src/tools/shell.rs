@@ -3,6 +3,7 @@ use crate::runtime::RuntimeAdapter;
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -12,7 +13,7 @@ const SHELL_TIMEOUT_SECS: u64 = 60;
 const MAX_OUTPUT_BYTES: usize = 1_048_576;
 /// Environment variables safe to pass to shell commands.
 /// Only functional variables are included — never API keys or secrets.
-const SAFE_ENV_VARS: &[&str] = &[
+pub(crate) const SAFE_ENV_VARS: &[&str] = &[
     "PATH", "HOME", "TERM", "LANG", "LC_ALL", "LC_CTYPE", "USER", "SHELL", "TMPDIR",
 ];
 
@@ -93,71 +94,88 @@ impl Tool for ShellTool {
             });
         }
 
-        // Execute with timeout to prevent hanging commands.
-        // Clear the environment to prevent leaking API keys and other secrets
-        // (CWE-200), then re-add only safe, functional variables.
-        let mut cmd = match self
-            .runtime
-            .build_shell_command(command, &self.security.workspace_dir)
-        {
-            Ok(cmd) => cmd,
-            Err(e) => {
-                return Ok(ToolResult {
-                    success: false,
-                    output: String::new(),
-                    error: Some(format!("Failed to build runtime command: {e}")),
-                });
-            }
-        };
-        cmd.env_clear();
+        run_sandboxed_command(self.runtime.as_ref(), command, &self.security.workspace_dir).await
+    }
+}
 
-        for var in SAFE_ENV_VARS {
-            if let Ok(val) = std::env::var(var) {
-                cmd.env(var, val);
-            }
+/// Build a policy-approved command through the runtime adapter with a
+/// scrubbed environment.
+pub(crate) fn build_sandboxed_command(
+    runtime: &dyn RuntimeAdapter,
+    command: &str,
+    working_dir: &Path,
+) -> anyhow::Result<tokio::process::Command> {
+    // Clear the environment to prevent leaking API keys and other secrets
+    // (CWE-200), then re-add only safe, functional variables.
+    let mut cmd = runtime.build_shell_command(command, working_dir)?;
+    cmd.env_clear();
+
+    for var in SAFE_ENV_VARS {
+        if let Ok(val) = std::env::var(var) {
+            cmd.env(var, val);
         }
+    }
+    Ok(cmd)
+}
 
-        let result =
-            tokio::time::timeout(Duration::from_secs(SHELL_TIMEOUT_SECS), cmd.output()).await;
+/// Run a policy-approved command through the runtime adapter with a scrubbed
+/// environment, a timeout, and bounded output.
+pub(crate) async fn run_sandboxed_command(
+    runtime: &dyn RuntimeAdapter,
+    command: &str,
+    working_dir: &Path,
+) -> anyhow::Result<ToolResult> {
+    // Execute with timeout to prevent hanging commands.
+    let mut cmd = match build_sandboxed_command(runtime, command, working_dir) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Failed to build runtime command: {e}")),
+            });
+        }
+    };
 
-        match result {
-            Ok(Ok(output)) => {
-                let mut stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let result = tokio::time::timeout(Duration::from_secs(SHELL_TIMEOUT_SECS), cmd.output()).await;
 
-                // Truncate output to prevent OOM
-                if stdout.len() > MAX_OUTPUT_BYTES {
-                    stdout.truncate(stdout.floor_char_boundary(MAX_OUTPUT_BYTES));
-                    stdout.push_str("\n... [output truncated at 1MB]");
-                }
-                if stderr.len() > MAX_OUTPUT_BYTES {
-                    stderr.truncate(stderr.floor_char_boundary(MAX_OUTPUT_BYTES));
-                    stderr.push_str("\n... [stderr truncated at 1MB]");
-                }
+    match result {
+        Ok(Ok(output)) => {
+            let mut stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
-                Ok(ToolResult {
-                    success: output.status.success(),
-                    output: stdout,
-                    error: if stderr.is_empty() {
-                        None
-                    } else {
-                        Some(stderr)
-                    },
-                })
+            // Truncate output to prevent OOM
+            if stdout.len() > MAX_OUTPUT_BYTES {
+                stdout.truncate(stdout.floor_char_boundary(MAX_OUTPUT_BYTES));
+                stdout.push_str("\n... [output truncated at 1MB]");
             }
-            Ok(Err(e)) => Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!("Failed to execute command: {e}")),
-            }),
-            Err(_) => Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "Command timed out after {SHELL_TIMEOUT_SECS}s and was killed"
-                )),
-            }),
+            if stderr.len() > MAX_OUTPUT_BYTES {
+                stderr.truncate(stderr.floor_char_boundary(MAX_OUTPUT_BYTES));
+                stderr.push_str("\n... [stderr truncated at 1MB]");
+            }
+
+            Ok(ToolResult {
+                success: output.status.success(),
+                output: stdout,
+                error: if stderr.is_empty() {
+                    None
+                } else {
+                    Some(stderr)
+                },
+            })
         }
+        Ok(Err(e)) => Ok(ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(format!("Failed to execute command: {e}")),
+        }),
+        Err(_) => Ok(ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(format!(
+                "Command timed out after {SHELL_TIMEOUT_SECS}s and was killed"
+            )),
+        }),
     }
 }
 
@@ -0,0 +1,732 @@
+use super::http_request::HttpRequestTool;
+use super::shell::run_sandboxed_command;
+use super::traits::{Tool, ToolResult};
+use crate::runtime::RuntimeAdapter;
+use crate::security::SecurityPolicy;
+use crate::skills::{Skill, SkillTool};
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Maximum tool name length accepted by provider function-calling APIs.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SkillToolKind {
+    Shell,
+    Http,
+    Script,
+}
+
+impl SkillToolKind {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "shell" => Some(Self::Shell),
+            "http" => Some(Self::Http),
+            "script" => Some(Self::Script),
+            _ => None,
+        }
+    }
+}
+
+/// A `[[tools]]` entry from an installed skill, exposed as a callable tool.
+///
+/// - `shell`: the templated command runs through the runtime adapter after
+///   the same command policy checks as the `shell` tool.
+/// - `http`: `command` is `[METHOD ]URL`; requests go through the
+///   `http_request` domain allowlist and limits.
+/// - `script`: `command` is a script path relative to the skill directory
+///   followed by optional arguments; it runs with the skill directory as cwd.
+///
+/// Declared `args` become required string parameters and are substituted into
+/// `{{name}}` placeholders (shell-quoted for shell/script, URL-encoded for http).
+pub struct SkillCommandTool {
+    name: String,
+    description: String,
+    kind: SkillToolKind,
+    command: String,
+    parameters: BTreeMap<String, String>,
+    skill_dir: PathBuf,
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+    http: Option<HttpRequestTool>,
+}
+
+impl SkillCommandTool {
+    pub fn new(
+        skill: &Skill,
+        tool: &SkillTool,
+        workspace_dir: &Path,
+        security: Arc<SecurityPolicy>,
+        runtime: Arc<dyn RuntimeAdapter>,
+        http_config: &crate::config::HttpRequestConfig,
+    ) -> anyhow::Result<Self> {
+        let kind = SkillToolKind::parse(&tool.kind).ok_or_else(|| {
+            anyhow::anyhow!(
+                "unsupported kind '{}' (expected shell, http, or script)",
+                tool.kind
+            )
+        })?;
+
+        let name = sanitize_tool_name(&tool.name);
+        if name.is_empty() {
+            anyhow::bail!("tool name '{}' has no usable characters", tool.name);
+        }
+
+        if tool.command.trim().is_empty() {
+            anyhow::bail!("command is empty");
+        }
+
+        let parameters: BTreeMap<String, String> = tool
+            .args
+            .iter()
+            .map(|(k, v)| (k.trim().to_string(), v.clone()))
+            .collect();
+        for placeholder in placeholders(&tool.command) {
+            if !parameters.contains_key(&placeholder) {
+                anyhow::bail!("command references undeclared argument '{{{{{placeholder}}}}}'");
+            }
+        }
+
+        let skill_dir = skill
+            .location
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| workspace_dir.join("skills").join(&skill.name));
+
+        let http = (kind == SkillToolKind::Http).then(|| {
+            HttpRequestTool::new(
+                security.clone(),
+                http_config.allowed_domains.clone(),
+                http_config.max_response_size,
+                http_config.timeout_secs,
+            )
+        });
+
+        let description = if tool.description.trim().is_empty() {
+            format!("Tool provided by the '{}' skill", skill.name)
+        } else {
+            format!("{} (from skill '{}')", tool.description.trim(), skill.name)
+        };
+
+        Ok(Self {
+            name,
+            description,
+            kind,
+            command: tool.command.trim().to_string(),
+            parameters,
+            skill_dir,
+            security,
+            runtime,
+            http,
+        })
+    }
+
+    /// Collect declared argument values from the call, stringifying scalars.
+    fn collect_values(&self, args: &serde_json::Value) -> Result<BTreeMap<String, String>, String> {
+        let mut values = BTreeMap::new();
+        for name in self.parameters.keys() {
+            let value = match args.get(name) {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(serde_json::Value::Number(n)) => n.to_string(),
+                Some(serde_json::Value::Bool(b)) => b.to_string(),
+                Some(serde_json::Value::Null) | None => {
+                    return Err(format!("Missing required parameter '{name}'"));
+                }
+                Some(_) => return Err(format!("Parameter '{name}' must be a string")),
+            };
+            values.insert(name.clone(), value);
+        }
+        Ok(values)
+    }
+
+    async fn execute_shell(
+        &self,
+        values: &BTreeMap<String, String>,
+        approved: bool,
+    ) -> anyhow::Result<ToolResult> {
+        let command = render_template(&self.command, values, shell_quote);
+
+        if self.security.is_rate_limited() {
+            return Ok(failure(
+                "Rate limit exceeded: too many actions in the last hour",
+            ));
+        }
+
+        if let Err(reason) = self.security.validate_command_execution(&command, approved) {
+            return Ok(failure(reason));
+        }
+
+        if !self.security.record_action() {
+            return Ok(failure("Rate limit exceeded: action budget exhausted"));
+        }
+
+        run_sandboxed_command(
+            self.runtime.as_ref(),
+            &command,
+            &self.security.workspace_dir,
+        )
+        .await
+    }
+
+    async fn execute_script(
+        &self,
+        values: &BTreeMap<String, String>,
+        approved: bool,
+    ) -> anyhow::Result<ToolResult> {
+        let (script, rest) = match self.command.split_once(char::is_whitespace) {
+            Some((script, rest)) => (script, rest.trim()),
+            None => (self.command.as_str(), ""),
+        };
+
+        let script_path = match resolve_script_path(&self.skill_dir, script) {
+            Ok(path) => path,
+            Err(e) => return Ok(failure(e.to_string())),
+        };
+
+        // Scripts run from the skill directory, so invoke them by relative
+        // path; the allowlist then matches on the script's file name.
+        let mut command = shell_word(&format!("./{}", script_path.to_string_lossy()));
+        if !rest.is_empty() {
+            command.push(' ');
+            command.push_str(&render_template(rest, values, shell_quote));
+        }
+
+        if self.security.is_rate_limited() {
+            return Ok(failure(
+                "Rate limit exceeded: too many actions in the last hour",
+            ));
+        }
+
+        if let Err(reason) = self.security.validate_command_execution(&command, approved) {
+            return Ok(failure(reason));
+        }
+
+        if !self.security.record_action() {
+            return Ok(failure("Rate limit exceeded: action budget exhausted"));
+        }
+
+        run_sandboxed_command(self.runtime.as_ref(), &command, &self.skill_dir).await
+    }
+
+    async fn execute_http(&self, values: &BTreeMap<String, String>) -> anyhow::Result<ToolResult> {
+        let Some(http) = self.http.as_ref() else {
+            return Ok(failure("HTTP skill tool is not initialized"));
+        };
+
+        let (method, url_template) = split_http_method(&self.command);
+        let url = render_template(url_template, values, |v| {
+            urlencoding::encode(v).into_owned()
+        });
+
+        let mut request = json!({ "url": url, "method": method });
+
+        // Arguments not consumed by the URL are sent as a JSON body for
+        // methods that carry one.
+        if !matches!(method, "GET" | "HEAD" | "DELETE" | "OPTIONS") {
+            let used = placeholders(url_template);
+            let body: serde_json::Map<String, serde_json::Value> = values
+                .iter()
+                .filter(|(name, _)| !used.contains(*name))
+                .map(|(name, value)| (name.clone(), json!(value)))
+                .collect();
+            if !body.is_empty() {
+                request["body"] = json!(serde_json::Value::Object(body).to_string());
+                request["headers"] = json!({ "Content-Type": "application/json" });
+            }
+        }
+
+        http.execute(request).await
+    }
+}
+
+#[async_trait]
+impl Tool for SkillCommandTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        for (name, description) in &self.parameters {
+            properties.insert(
+                name.clone(),
+                json!({ "type": "string", "description": description }),
+            );
+        }
+        if matches!(self.kind, SkillToolKind::Shell | SkillToolKind::Script) {
+            properties.insert(
+                "approved".into(),
+                json!({
+                    "type": "boolean",
+                    "description": "Set true to explicitly approve medium/high-risk commands in supervised mode",
+                    "default": false
+                }),
+            );
+        }
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": self.parameters.keys().collect::<Vec<_>>()
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let values = match self.collect_values(&args) {
+            Ok(values) => values,
+            Err(e) => return Ok(failure(e)),
+        };
+
+        let approved = args
+            .get("approved")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        match self.kind {
+            SkillToolKind::Shell => self.execute_shell(&values, approved).await,
+            SkillToolKind::Script => self.execute_script(&values, approved).await,
+            SkillToolKind::Http => self.execute_http(&values).await,
+        }
+    }
+}
+
+/// Build callable tools for every `[[tools]]` entry across installed skills.
+///
+/// Entries that fail validation or whose name is already in `reserved_names`
+/// are skipped with a warning.
+pub fn skill_tools(
+    skills: &[Skill],
+    reserved_names: &[String],
+    workspace_dir: &Path,
+    security: &Arc<SecurityPolicy>,
+    runtime: &Arc<dyn RuntimeAdapter>,
+    http_config: &crate::config::HttpRequestConfig,
+) -> Vec<Arc<dyn Tool>> {
+    let mut taken: HashSet<String> = reserved_names.iter().cloned().collect();
+    let mut tools: Vec<Arc<dyn Tool>> = Vec::new();
+
+    for skill in skills {
+        for tool in &skill.tools {
+            match SkillCommandTool::new(
+                skill,
+                tool,
+                workspace_dir,
+                security.clone(),
+                runtime.clone(),
+                http_config,
+            ) {
+                Ok(built) => {
+                    if !taken.insert(built.name.clone()) {
+                        tracing::warn!(
+                            skill = %skill.name,
+                            tool = %built.name,
+                            "Skipping skill tool: name already registered"
+                        );
+                        continue;
+                    }
+                    tools.push(Arc::new(built));
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        skill = %skill.name,
+                        tool = %tool.name,
+                        "Skipping skill tool: {e}"
+                    );
+                }
+            }
+        }
+    }
+
+    tools
+}
+
+fn failure(message: impl Into<String>) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(message.into()),
+    }
+}
+
+pub(crate) fn sanitize_tool_name(raw: &str) -> String {
+    raw.trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_TOOL_NAME_LEN)
+        .collect::<String>()
+        .trim_matches('_')
+        .to_string()
+}
+
+/// Names of `{{name}}` placeholders in a template.
+fn placeholders(template: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        names.insert(after[..end].trim().to_string());
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// Replace `{{name}}` placeholders with escaped values.
+fn render_template(
+    template: &str,
+    values: &BTreeMap<String, String>,
+    escape: impl Fn(&str) -> String,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        match values.get(after[..end].trim()) {
+            Some(value) => out.push_str(&escape(value)),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Quote a value as a single POSIX shell word.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Like [`shell_quote`], but leaves words made only of safe characters bare
+/// so the command allowlist sees the plain program name.
+fn shell_word(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/'))
+    {
+        value.to_string()
+    } else {
+        shell_quote(value)
+    }
+}
+
+fn split_http_method(command: &str) -> (&'static str, &str) {
+    const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+    if let Some((first, rest)) = command.split_once(char::is_whitespace) {
+        if let Some(method) = METHODS
+            .iter()
+            .find(|m| m.eq_ignore_ascii_case(first.trim()))
+        {
+            return (method, rest.trim());
+        }
+    }
+    ("GET", command.trim())
+}
+
+/// Resolve a script inside the skill directory to its path relative to that
+/// directory, rejecting escapes.
+fn resolve_script_path(skill_dir: &Path, script: &str) -> anyhow::Result<PathBuf> {
+    let relative = Path::new(script);
+    if relative.is_absolute() {
+        anyhow::bail!("Skill script path must be relative to the skill directory: {script}");
+    }
+
+    let skill_root = skill_dir
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Skill directory unavailable: {e}"))?;
+    let resolved = skill_root
+        .join(relative)
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Skill script not found: {script} ({e})"))?;
+
+    if !resolved.starts_with(&skill_root) {
+        anyhow::bail!("Skill script escapes the skill directory: {script}");
+    }
+    if !resolved.is_file() {
+        anyhow::bail!("Skill script is not a file: {script}");
+    }
+
+    Ok(resolved
+        .strip_prefix(&skill_root)
+        .map(Path::to_path_buf)
+        .unwrap_or(resolved))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::NativeRuntime;
+    use crate::security::AutonomyLevel;
+    use std::collections::HashMap;
+
+    fn test_security(workspace: &Path) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    fn skill_with(dir: &Path, tool: SkillTool) -> Skill {
+        Skill {
+            name: "demo".into(),
+            description: "Demo skill".into(),
+            version: "0.1.0".into(),
+            author: None,
+            tags: vec![],
+            tools: vec![tool],
+            prompts: vec![],
+            location: Some(dir.join("SKILL.toml")),
+        }
+    }
+
+    fn tool(kind: &str, command: &str, args: &[(&str, &str)]) -> SkillTool {
+        SkillTool {
+            name: "greet".into(),
+            description: "Say hello".into(),
+            kind: kind.into(),
+            command: command.into(),
+            args: args
+                .iter()
+                .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    fn build_tool(dir: &Path, skill_tool: SkillTool) -> anyhow::Result<SkillCommandTool> {
+        build_tool_with_security(dir, skill_tool, test_security(dir))
+    }
+
+    fn build_tool_with_security(
+        dir: &Path,
+        skill_tool: SkillTool,
+        security: Arc<SecurityPolicy>,
+    ) -> anyhow::Result<SkillCommandTool> {
+        let skill = skill_with(dir, skill_tool.clone());
+        SkillCommandTool::new(
+            &skill,
+            &skill_tool,
+            dir,
+            security,
+            Arc::new(NativeRuntime::new()),
+            &crate::config::HttpRequestConfig::default(),
+        )
+    }
+
+    #[test]
+    fn schema_exposes_declared_args_as_required_strings() {
+        let dir = tempfile::tempdir().unwrap();
+        let built = build_tool(
+            dir.path(),
+            tool("shell", "echo {{who}}", &[("who", "Name to greet")]),
+        )
+        .unwrap();
+
+        let schema = built.parameters_schema();
+        assert_eq!(schema["properties"]["who"]["type"], "string");
+        assert_eq!(schema["required"], json!(["who"]));
+        assert_eq!(built.name(), "greet");
+        assert!(built.description().contains("from skill 'demo'"));
+    }
+
+    #[test]
+    fn rejects_unknown_kind_and_undeclared_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(build_tool(dir.path(), tool("wasm", "x", &[])).is_err());
+        assert!(build_tool(dir.path(), tool("shell", "echo {{missing}}", &[])).is_err());
+    }
+
+    #[test]
+    fn render_template_quotes_values() {
+        let values: BTreeMap<String, String> =
+            [("who".to_string(), "it's; rm -rf /".to_string())].into();
+        let rendered = render_template("echo {{ who }}", &values, shell_quote);
+        assert_eq!(rendered, r"echo 'it'\''s; rm -rf /'");
+    }
+
+    #[test]
+    fn split_http_method_defaults_to_get() {
+        assert_eq!(
+            split_http_method("https://api.example.com/x"),
+            ("GET", "https://api.example.com/x")
+        );
+        assert_eq!(
+            split_http_method("post https://api.example.com/x"),
+            ("POST", "https://api.example.com/x")
+        );
+    }
+
+    #[tokio::test]
+    async fn shell_tool_substitutes_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let built = build_tool(
+            dir.path(),
+            tool("shell", "echo hello {{who}}", &[("who", "Name")]),
+        )
+        .unwrap();
+
+        let result = built.execute(json!({ "who": "world" })).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output.trim(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn missing_argument_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let built = build_tool(
+            dir.path(),
+            tool("shell", "echo {{who}}", &[("who", "Name")]),
+        )
+        .unwrap();
+
+        let result = built.execute(json!({})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("who"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn script_tool_runs_from_skill_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\necho \"arg=$1 cwd=$(basename \"$PWD\")\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: dir.path().to_path_buf(),
+            allowed_commands: vec!["run.sh".into()],
+            ..SecurityPolicy::default()
+        });
+        let built = build_tool_with_security(
+            dir.path(),
+            tool("script", "run.sh {{value}}", &[("value", "Value")]),
+            security,
+        )
+        .unwrap();
+
+        let result = built.execute(json!({ "value": "a b" })).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let expected_dir = dir.path().file_name().unwrap().to_string_lossy();
+        assert_eq!(result.output.trim(), format!("arg=a b cwd={expected_dir}"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn script_tool_goes_through_command_policy() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("fetch.sh");
+        std::fs::write(&script, "#!/bin/sh\necho ran > ran.txt\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Default allowlist does not include the script.
+        let built = build_tool(dir.path(), tool("script", "fetch.sh", &[])).unwrap();
+        assert_eq!(
+            built.parameters_schema()["properties"]["approved"]["type"],
+            "boolean"
+        );
+        let result = built.execute(json!({})).await.unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error
+            .unwrap()
+            .contains("not allowed by security policy"));
+        assert!(!dir.path().join("ran.txt").exists());
+
+        let read_only = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: dir.path().to_path_buf(),
+            allowed_commands: vec!["fetch.sh".into()],
+            ..SecurityPolicy::default()
+        });
+        let built =
+            build_tool_with_security(dir.path(), tool("script", "fetch.sh", &[]), read_only)
+                .unwrap();
+        let result = built.execute(json!({ "approved": true })).await.unwrap();
+        assert!(!result.success);
+        assert!(!dir.path().join("ran.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn script_tool_rejects_paths_outside_skill_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill_dir = dir.path().join("skill");
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        std::fs::write(dir.path().join("outside.sh"), "echo nope").unwrap();
+
+        let built = build_tool(&skill_dir, tool("script", "../outside.sh", &[])).unwrap();
+        let result = built.execute(json!({})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("escapes"));
+    }
+
+    #[tokio::test]
+    async fn http_tool_uses_http_request_allowlist() {
+        let dir = tempfile::tempdir().unwrap();
+        let built = build_tool(
+            dir.path(),
+            tool(
+                "http",
+                "GET https://api.example.com/items/{{id}}",
+                &[("id", "Item ID")],
+            ),
+        )
+        .unwrap();
+
+        // No allowed_domains configured, so the request is refused before any I/O.
+        let result = built.execute(json!({ "id": "1" })).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("allowed_domains"));
+    }
+
+    #[test]
+    fn skill_tools_skip_reserved_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut shell_named = tool("shell", "echo hi", &[]);
+        shell_named.name = "shell".into();
+        let skills = vec![
+            skill_with(dir.path(), tool("shell", "echo hi", &[])),
+            skill_with(dir.path(), shell_named),
+        ];
+
+        let reserved = vec!["shell".to_string()];
+        let tools = skill_tools(
+            &skills,
+            &reserved,
+            dir.path(),
+            &test_security(dir.path()),
+            &(Arc::new(NativeRuntime::new()) as Arc<dyn RuntimeAdapter>),
+            &crate::config::HttpRequestConfig::default(),
+        );
+
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name(), "greet");
+    }
+}
@@ -0,0 +1,463 @@
+use super::skill_tool::sanitize_tool_name;
+use super::traits::{Tool, ToolResult};
+use crate::config::WasmRuntimeConfig;
+use crate::runtime::wasm::{WasmCapabilities, WasmExecutionResult, WasmRuntime};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Optional `<module>.json` manifest next to a `<module>.wasm` file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct WasmToolManifest {
+    name: Option<String>,
+    description: Option<String>,
+    parameters: Option<serde_json::Value>,
+    capabilities: WasmCapabilities,
+}
+
+/// A WASM module from `runtime.wasm.tools_dir`, exposed as a callable tool.
+///
+/// Tool arguments are serialized as JSON and fed to the module on stdin;
+/// whatever the module writes to stdout becomes the tool output. A non-zero
+/// exit code, a trap, or exhausting the fuel/memory budget fails the call.
+pub struct WasmModuleTool {
+    name: String,
+    module: String,
+    description: String,
+    parameters: serde_json::Value,
+    capabilities: WasmCapabilities,
+    runtime: WasmRuntime,
+    workspace_dir: PathBuf,
+    security: Arc<SecurityPolicy>,
+}
+
+impl WasmModuleTool {
+    /// Build the tool for `module`, reading its manifest if one exists.
+    pub fn load(
+        runtime: &WasmRuntime,
+        config: &WasmRuntimeConfig,
+        module: &str,
+        workspace_dir: &Path,
+        security: Arc<SecurityPolicy>,
+    ) -> anyhow::Result<Self> {
+        let manifest_path = runtime
+            .tools_dir(workspace_dir)
+            .join(format!("{module}.json"));
+        let manifest: WasmToolManifest = if manifest_path.exists() {
+            let raw = std::fs::read_to_string(&manifest_path)?;
+            serde_json::from_str(&raw)
+                .map_err(|e| anyhow::anyhow!("invalid manifest {}: {e}", manifest_path.display()))?
+        } else {
+            WasmToolManifest::default()
+        };
+
+        let name = sanitize_tool_name(manifest.name.as_deref().unwrap_or(module));
+        if name.is_empty() {
+            anyhow::bail!("tool name for module '{module}' has no usable characters");
+        }
+
+        let parameters = manifest
+            .parameters
+            .unwrap_or_else(|| json!({ "type": "object" }));
+        if !parameters.is_object() {
+            anyhow::bail!("manifest parameters must be a JSON schema object");
+        }
+
+        Ok(Self {
+            name,
+            module: module.to_string(),
+            description: manifest
+                .description
+                .unwrap_or_else(|| format!("WASM tool module '{module}'")),
+            parameters,
+            capabilities: restrict_capabilities(module, manifest.capabilities, config),
+            runtime: runtime.clone(),
+            workspace_dir: workspace_dir.to_path_buf(),
+            security,
+        })
+    }
+
+    fn to_tool_result(&self, result: &WasmExecutionResult) -> ToolResult {
+        tracing::debug!(
+            tool = %self.name,
+            exit_code = result.exit_code,
+            fuel_consumed = result.fuel_consumed,
+            memory_bytes = result.memory_bytes,
+            "WASM tool finished"
+        );
+
+        if result.exit_code == 0 {
+            return ToolResult {
+                success: true,
+                output: result.stdout.clone(),
+                error: None,
+            };
+        }
+
+        let fuel_limit = self.runtime.effective_fuel(&self.capabilities);
+        let stderr = result.stderr.trim();
+        let mut error = format!("WASM module exited with code {}", result.exit_code);
+        if !stderr.is_empty() {
+            error.push_str(": ");
+            error.push_str(stderr);
+        }
+        let _ = write!(
+            error,
+            " (fuel used: {}/{fuel_limit}, memory: {} bytes)",
+            result.fuel_consumed, result.memory_bytes
+        );
+
+        ToolResult {
+            success: false,
+            output: result.stdout.clone(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Manifests may tighten the configured fuel/memory budgets but never raise
+/// them. Filesystem and network capabilities are not exposed to modules yet,
+/// so requests for them are dropped with a warning.
+fn restrict_capabilities(
+    module: &str,
+    requested: WasmCapabilities,
+    config: &WasmRuntimeConfig,
+) -> WasmCapabilities {
+    if requested.read_workspace || requested.write_workspace || !requested.allowed_hosts.is_empty()
+    {
+        tracing::warn!(
+            module,
+            "WASM tool requests filesystem/network capabilities, which the sandbox does not provide; ignoring"
+        );
+    }
+
+    let fuel_override = if requested.fuel_override > config.fuel_limit {
+        tracing::warn!(
+            module,
+            "WASM tool fuel_override exceeds runtime.wasm.fuel_limit; clamping"
+        );
+        config.fuel_limit
+    } else {
+        requested.fuel_override
+    };
+
+    let memory_override_mb = if requested.memory_override_mb > config.memory_limit_mb {
+        tracing::warn!(
+            module,
+            "WASM tool memory_override_mb exceeds runtime.wasm.memory_limit_mb; clamping"
+        );
+        config.memory_limit_mb
+    } else {
+        requested.memory_override_mb
+    };
+
+    WasmCapabilities {
+        fuel_override,
+        memory_override_mb,
+        ..WasmCapabilities::default()
+    }
+}
+
+#[async_trait]
+impl Tool for WasmModuleTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.parameters.clone()
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if !self.security.can_act() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+            });
+        }
+
+        if !self.security.record_action() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Rate limit exceeded: action budget exhausted".into()),
+            });
+        }
+
+        let input = serde_json::to_vec(&args)?;
+        let runtime = self.runtime.clone();
+        let module = self.module.clone();
+        let workspace_dir = self.workspace_dir.clone();
+        let capabilities = self.capabilities.clone();
+
+        let outcome = tokio::task::spawn_blocking(move || {
+            runtime.execute_module_with_input(&module, &workspace_dir, &capabilities, &input)
+        })
+        .await?;
+
+        match outcome {
+            Ok(result) => Ok(self.to_tool_result(&result)),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("{e:#}")),
+            }),
+        }
+    }
+}
+
+/// Build callable tools for every `*.wasm` module in `runtime.wasm.tools_dir`.
+///
+/// Modules whose name is already in `reserved_names` or whose manifest is
+/// invalid are skipped with a warning. Builds without the `runtime-wasm`
+/// feature register nothing.
+pub fn wasm_tools(
+    config: &WasmRuntimeConfig,
+    workspace_dir: &Path,
+    security: &Arc<SecurityPolicy>,
+    reserved_names: &[String],
+) -> Vec<Arc<dyn Tool>> {
+    let runtime = WasmRuntime::with_workspace(config.clone(), workspace_dir.to_path_buf());
+    if let Err(e) = runtime.validate_config() {
+        tracing::warn!("Skipping WASM tools: {e}");
+        return Vec::new();
+    }
+
+    let modules = match runtime.list_modules(workspace_dir) {
+        Ok(modules) => modules,
+        Err(e) => {
+            tracing::warn!("Skipping WASM tools: {e:#}");
+            return Vec::new();
+        }
+    };
+    if modules.is_empty() {
+        return Vec::new();
+    }
+
+    if !WasmRuntime::is_available() {
+        tracing::warn!(
+            count = modules.len(),
+            "Found WASM tool modules but this build lacks the `runtime-wasm` feature; skipping"
+        );
+        return Vec::new();
+    }
+
+    let mut taken: HashSet<String> = reserved_names.iter().cloned().collect();
+    let mut tools: Vec<Arc<dyn Tool>> = Vec::new();
+
+    for module in modules {
+        match WasmModuleTool::load(&runtime, config, &module, workspace_dir, security.clone()) {
+            Ok(tool) => {
+                if !taken.insert(tool.name.clone()) {
+                    tracing::warn!(
+                        module = %module,
+                        tool = %tool.name,
+                        "Skipping WASM tool: name already registered"
+                    );
+                    continue;
+                }
+                tools.push(Arc::new(tool));
+            }
+            Err(e) => tracing::warn!(module = %module, "Skipping WASM tool: {e:#}"),
+        }
+    }
+
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+
+    fn security(dir: &Path) -> Arc<SecurityPolicy> {
+        Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Full,
+            workspace_dir: dir.to_path_buf(),
+            ..SecurityPolicy::default()
+        })
+    }
+
+    fn tools_dir(dir: &Path) -> PathBuf {
+        let path = dir.join("tools/wasm");
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn load(dir: &Path, module: &str) -> anyhow::Result<WasmModuleTool> {
+        let config = WasmRuntimeConfig::default();
+        let runtime = WasmRuntime::new(config.clone());
+        WasmModuleTool::load(&runtime, &config, module, dir, security(dir))
+    }
+
+    #[test]
+    fn load_without_manifest_uses_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(tools_dir(dir.path()).join("word count.wasm"), b"").unwrap();
+
+        let tool = load(dir.path(), "word count").unwrap();
+        assert_eq!(tool.name(), "word_count");
+        assert!(tool.description().contains("word count"));
+        assert_eq!(tool.parameters_schema(), json!({ "type": "object" }));
+    }
+
+    #[test]
+    fn load_reads_manifest_and_clamps_capabilities() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = tools_dir(dir.path());
+        std::fs::write(tools.join("calc.wasm"), b"").unwrap();
+        std::fs::write(
+            tools.join("calc.json"),
+            json!({
+                "name": "calculator",
+                "description": "Evaluate arithmetic",
+                "parameters": {
+                    "type": "object",
+                    "properties": { "expr": { "type": "string" } },
+                    "required": ["expr"]
+                },
+                "capabilities": {
+                    "fuel_override": 500,
+                    "memory_override_mb": 100_000,
+                    "read_workspace": true,
+                    "allowed_hosts": ["example.com"]
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let tool = load(dir.path(), "calc").unwrap();
+        assert_eq!(tool.name(), "calculator");
+        assert_eq!(tool.description(), "Evaluate arithmetic");
+        assert_eq!(tool.parameters_schema()["required"], json!(["expr"]));
+        assert_eq!(tool.capabilities.fuel_override, 500);
+        assert_eq!(tool.capabilities.memory_override_mb, 64);
+        assert!(!tool.capabilities.read_workspace);
+        assert!(tool.capabilities.allowed_hosts.is_empty());
+    }
+
+    #[test]
+    fn load_rejects_invalid_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = tools_dir(dir.path());
+        std::fs::write(tools.join("bad.json"), r#"{"parameters": "nope"}"#).unwrap();
+        assert!(load(dir.path(), "bad").is_err());
+
+        std::fs::write(tools.join("bad.json"), "{not json").unwrap();
+        assert!(load(dir.path(), "bad").is_err());
+    }
+
+    #[test]
+    fn wasm_tools_empty_when_dir_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = wasm_tools(
+            &WasmRuntimeConfig::default(),
+            dir.path(),
+            &security(dir.path()),
+            &[],
+        );
+        assert!(tools.is_empty());
+    }
+
+    #[cfg(not(feature = "runtime-wasm"))]
+    #[test]
+    fn wasm_tools_skipped_without_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(tools_dir(dir.path()).join("echo.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        let tools = wasm_tools(
+            &WasmRuntimeConfig::default(),
+            dir.path(),
+            &security(dir.path()),
+            &[],
+        );
+        assert!(tools.is_empty());
+    }
+
+    #[cfg(feature = "runtime-wasm")]
+    const ECHO_WAT: &str = r#"(module
+  (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (func (export "_start")
+    (i32.store (i32.const 0) (i32.const 1024))
+    (i32.store (i32.const 4) (i32.const 4096))
+    (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+    (i32.store (i32.const 4) (i32.load (i32.const 8)))
+    (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))))"#;
+
+    #[cfg(feature = "runtime-wasm")]
+    #[tokio::test]
+    async fn wasm_tool_echoes_json_args_from_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = tools_dir(dir.path());
+        std::fs::write(tools.join("echo.wasm"), wat::parse_str(ECHO_WAT).unwrap()).unwrap();
+        std::fs::write(tools.join("shell.wasm"), wat::parse_str(ECHO_WAT).unwrap()).unwrap();
+
+        let registered = wasm_tools(
+            &WasmRuntimeConfig::default(),
+            dir.path(),
+            &security(dir.path()),
+            &["shell".to_string()],
+        );
+        let names: Vec<&str> = registered.iter().map(|t| t.name()).collect();
+        assert_eq!(names, vec!["echo"]);
+
+        let result = registered[0]
+            .execute(json!({ "text": "hi" }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, r#"{"text":"hi"}"#);
+    }
+
+    #[cfg(feature = "runtime-wasm")]
+    #[tokio::test]
+    async fn wasm_tool_reports_fuel_exhaustion() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = tools_dir(dir.path());
+        let spin = r#"(module (func (export "run") (result i32) (loop $l (br $l)) (i32.const 0)))"#;
+        std::fs::write(tools.join("spin.wasm"), wat::parse_str(spin).unwrap()).unwrap();
+        std::fs::write(
+            tools.join("spin.json"),
+            r#"{"capabilities": {"fuel_override": 1000}}"#,
+        )
+        .unwrap();
+
+        let tool = load(dir.path(), "spin").unwrap();
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("fuel limit"), "{error}");
+        assert!(error.contains("fuel used: 1000/1000"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn read_only_autonomy_blocks_wasm_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(tools_dir(dir.path()).join("echo.wasm"), b"").unwrap();
+        let config = WasmRuntimeConfig::default();
+        let runtime = WasmRuntime::new(config.clone());
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: dir.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tool = WasmModuleTool::load(&runtime, &config, "echo", dir.path(), security).unwrap();
+
+        let result = tool.execute(json!({})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+}
@@ -0,0 +1,936 @@
+//! HTML to Markdown conversion for `web_fetch`.
+//!
+//! Parsing is left to `scraper` (html5ever), which handles entities, implied
+//! end tags and malformed markup the way browsers do. This module only picks
+//! the part of the page worth reading and renders it as Markdown.
+
+use ego_tree::{NodeId, NodeRef};
+use reqwest::Url;
+use scraper::{Html, Node};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// Main-content candidates with less text than this fall back to the body.
+const MIN_MAIN_CONTENT_CHARS: usize = 200;
+/// Nesting rendered as Markdown structure; deeper subtrees become plain text
+/// so hostile pages cannot exhaust the stack.
+const MAX_RENDER_DEPTH: usize = 96;
+
+/// Elements that never contain readable page text.
+const NON_CONTENT_TAGS: &[&str] = &[
+    "head", "script", "style", "title", "textarea", "noscript", "template", "svg", "math",
+    "canvas", "iframe", "object", "embed", "select", "button", "input", "dialog", "map", "audio",
+    "video",
+];
+/// Page furniture dropped when extracting the main content.
+const BOILERPLATE_TAGS: &[&str] = &["nav", "aside", "footer", "form", "menu"];
+/// class/id fragments that mark page furniture...
+const NEGATIVE_HINTS: &[&str] = &[
+    "advert",
+    "banner",
+    "breadcrumb",
+    "comment",
+    "cookie",
+    "footer",
+    "menu",
+    "modal",
+    "navbar",
+    "newsletter",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sidebar",
+    "social",
+    "sponsor",
+    "subscribe",
+    "toolbar",
+];
+/// ...unless they also mark the content itself.
+const POSITIVE_HINTS: &[&str] = &["article", "content", "entry", "main", "post", "story"];
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "body",
+    "caption",
+    "center",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "html",
+    "legend",
+    "main",
+    "nav",
+    "p",
+    "section",
+    "summary",
+];
+
+/// An HTML page rendered as Markdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownPage {
+    /// From `<title>`, then `og:title`, then the first `<h1>`.
+    pub title: Option<String>,
+    pub markdown: String,
+}
+
+/// Convert an HTML document to Markdown, resolving links against `base_url`.
+///
+/// With `main_content`, navigation, sidebars, footers and similar furniture
+/// are dropped and only the main article is rendered; when no article stands
+/// out, or it renders empty, the whole body is used instead.
+pub fn html_to_markdown(html: &str, base_url: &str, main_content: bool) -> MarkdownPage {
+    let document = Html::parse_document(html);
+    let root = document.tree.root();
+    let base = Url::parse(base_url).ok();
+
+    let hidden = hidden_nodes(root, main_content);
+    let body = walk(root)
+        .into_iter()
+        .map(|(node, _)| node)
+        .find(|node| tag(*node) == Some("body"))
+        .unwrap_or(root);
+    let start = if main_content {
+        main_content_root(root, &hidden).unwrap_or(body)
+    } else {
+        body
+    };
+
+    let renderer = Renderer {
+        hidden: &hidden,
+        base: base.as_ref(),
+    };
+    let mut markdown = renderer.render_root(start);
+    if markdown.is_empty() && start.id() != body.id() {
+        markdown = renderer.render_root(body);
+    }
+    MarkdownPage {
+        title: title(root),
+        markdown,
+    }
+}
+
+fn tag(node: NodeRef<'_, Node>) -> Option<&str> {
+    node.value().as_element().map(|element| element.name())
+}
+
+fn attr<'a>(node: NodeRef<'a, Node>, name: &str) -> Option<&'a str> {
+    node.value()
+        .as_element()
+        .and_then(|element| element.attr(name))
+}
+
+/// Pre-order walk over `root` and everything below it, with each node's
+/// parent (`None` for `root`).
+///
+/// Only child links are followed: ego-tree 0.10 leaves stale parent links on
+/// nodes html5ever re-parents while fixing up misnested markup, which derails
+/// its own `descendants()` and `ancestors()`.
+fn walk(root: NodeRef<'_, Node>) -> Vec<(NodeRef<'_, Node>, Option<NodeRef<'_, Node>>)> {
+    let mut out = Vec::new();
+    let mut stack = vec![(root, None)];
+    while let Some((node, parent)) = stack.pop() {
+        out.push((node, parent));
+        let children: Vec<_> = node.children().collect();
+        stack.extend(children.into_iter().rev().map(|child| (child, Some(node))));
+    }
+    out
+}
+
+fn text_content(node: NodeRef<'_, Node>) -> String {
+    walk(node)
+        .into_iter()
+        .filter_map(|(n, _)| n.value().as_text())
+        .map(|text| &**text)
+        .collect()
+}
+
+fn title(root: NodeRef<'_, Node>) -> Option<String> {
+    let nodes = walk(root);
+    let from_tag = |name: &str| {
+        nodes
+            .iter()
+            .find(|(node, _)| tag(*node) == Some(name))
+            .map(|(node, _)| single_line(&text_content(*node)))
+    };
+    let og_title = nodes
+        .iter()
+        .find(|(node, _)| tag(*node) == Some("meta") && attr(*node, "property") == Some("og:title"))
+        .and_then(|(node, _)| attr(*node, "content"))
+        .map(single_line);
+    [from_tag("title"), og_title, from_tag("h1")]
+        .into_iter()
+        .flatten()
+        .find(|title| !title.is_empty())
+}
+
+/// Nodes (and everything below them) that are never rendered.
+fn hidden_nodes(root: NodeRef<'_, Node>, main_content: bool) -> HashSet<NodeId> {
+    let mut hidden = HashSet::new();
+    let mut in_article = HashSet::new();
+    // Pre-order, so a parent is always decided before its children.
+    for (node, parent) in walk(root) {
+        if let Some(parent) = parent {
+            if hidden.contains(&parent.id()) {
+                hidden.insert(node.id());
+                continue;
+            }
+            if in_article.contains(&parent.id()) || matches!(tag(parent), Some("article" | "main"))
+            {
+                in_article.insert(node.id());
+            }
+        }
+        let Some(name) = tag(node) else {
+            continue;
+        };
+        let explicitly_hidden = attr(node, "hidden").is_some()
+            || attr(node, "aria-hidden") == Some("true")
+            || attr(node, "style").is_some_and(|style| {
+                let style = style.replace(' ', "").to_ascii_lowercase();
+                style.contains("display:none") || style.contains("visibility:hidden")
+            });
+        let boilerplate = main_content
+            && (is_boilerplate(node, name)
+                || (name == "header" && !in_article.contains(&node.id())));
+        if NON_CONTENT_TAGS.contains(&name) || explicitly_hidden || boilerplate {
+            hidden.insert(node.id());
+        }
+    }
+    hidden
+}
+
+fn is_boilerplate(node: NodeRef<'_, Node>, name: &str) -> bool {
+    if BOILERPLATE_TAGS.contains(&name) {
+        return true;
+    }
+    if matches!(name, "body" | "html" | "article" | "main" | "header") {
+        return false;
+    }
+    if matches!(
+        attr(node, "role"),
+        Some("navigation" | "banner" | "contentinfo" | "complementary" | "dialog")
+    ) {
+        return true;
+    }
+    let names = format!(
+        "{} {}",
+        attr(node, "class").unwrap_or_default(),
+        attr(node, "id").unwrap_or_default()
+    )
+    .to_ascii_lowercase();
+    NEGATIVE_HINTS.iter().any(|hint| names.contains(hint))
+        && !POSITIVE_HINTS.iter().any(|hint| names.contains(hint))
+}
+
+/// Pick the element holding the page's main content: the largest `<article>`
+/// or `<main>`, otherwise the container whose paragraphs score highest
+/// (readability-style: long, comma-rich, few links).
+fn main_content_root<'a>(
+    root: NodeRef<'a, Node>,
+    hidden: &HashSet<NodeId>,
+) -> Option<NodeRef<'a, Node>> {
+    let nodes: Vec<_> = walk(root)
+        .into_iter()
+        .filter(|(node, _)| !hidden.contains(&node.id()))
+        .collect();
+    let parents: HashMap<NodeId, NodeRef<'a, Node>> = nodes
+        .iter()
+        .filter_map(|(node, parent)| parent.map(|parent| (node.id(), parent)))
+        .collect();
+
+    // Reverse pre-order visits children before parents, so lengths can be
+    // summed upwards without recursion.
+    let mut text_len: HashMap<NodeId, usize> = HashMap::new();
+    let mut link_len: HashMap<NodeId, usize> = HashMap::new();
+    for (node, parent) in nodes.iter().rev() {
+        let id = node.id();
+        if let Some(text) = node.value().as_text() {
+            let len = text
+                .split_whitespace()
+                .map(|word| word.chars().count() + 1)
+                .sum();
+            text_len.insert(id, len);
+        }
+        let own_text = text_len.get(&id).copied().unwrap_or(0);
+        if tag(*node) == Some("a") {
+            link_len.insert(id, own_text);
+        }
+        let own_links = link_len.get(&id).copied().unwrap_or(0);
+        if let Some(parent) = parent {
+            *text_len.entry(parent.id()).or_default() += own_text;
+            *link_len.entry(parent.id()).or_default() += own_links;
+        }
+    }
+    let text_of = |node: &NodeRef<'a, Node>| text_len.get(&node.id()).copied().unwrap_or(0);
+
+    let semantic = nodes
+        .iter()
+        .map(|(node, _)| *node)
+        .filter(|node| {
+            matches!(tag(*node), Some("article" | "main")) || attr(*node, "role") == Some("main")
+        })
+        .max_by_key(|node| text_of(node));
+    if let Some(node) = semantic.filter(|node| text_of(node) >= MIN_MAIN_CONTENT_CHARS) {
+        return Some(node);
+    }
+
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    for (node, parent) in &nodes {
+        if !matches!(tag(*node), Some("p" | "pre" | "td" | "blockquote")) || text_of(node) < 25 {
+            continue;
+        }
+        let commas = text_content(*node).matches([',', '，']).count();
+        let score = 1.0 + commas as f64 + (text_of(node) / 100).min(3) as f64;
+        if let Some(parent) = parent {
+            *scores.entry(parent.id()).or_default() += score;
+            if let Some(grandparent) = parents.get(&parent.id()) {
+                *scores.entry(grandparent.id()).or_default() += score / 2.0;
+            }
+        }
+    }
+    nodes
+        .iter()
+        .map(|(node, _)| *node)
+        .filter(|node| node.value().is_element() && text_of(node) >= MIN_MAIN_CONTENT_CHARS)
+        .filter_map(|node| {
+            let score = scores.get(&node.id()).copied()?;
+            let links = link_len.get(&node.id()).copied().unwrap_or(0);
+            let link_density = links as f64 / text_of(&node) as f64;
+            Some((node, score * (1.0 - link_density)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(node, _)| node)
+}
+
+struct Renderer<'a> {
+    hidden: &'a HashSet<NodeId>,
+    base: Option<&'a Url>,
+}
+
+impl Renderer<'_> {
+    fn render_root(&self, root: NodeRef<'_, Node>) -> String {
+        let mut out = String::new();
+        self.render(root, &mut out, 0);
+        tidy_markdown(&out)
+    }
+
+    fn render_children(&self, node: NodeRef<'_, Node>, out: &mut String, depth: usize) {
+        for child in node.children() {
+            self.render(child, out, depth + 1);
+        }
+    }
+
+    fn render_inline(&self, node: NodeRef<'_, Node>, depth: usize) -> String {
+        let mut inner = String::new();
+        self.render_children(node, &mut inner, depth);
+        inner
+    }
+
+    /// Visible text below `node`, ignoring markup.
+    fn visible_text(&self, node: NodeRef<'_, Node>) -> String {
+        walk(node)
+            .into_iter()
+            .filter(|(n, _)| !self.hidden.contains(&n.id()))
+            .filter_map(|(n, _)| n.value().as_text())
+            .map(|text| &**text)
+            .collect()
+    }
+
+    fn resolve(&self, href: &str) -> String {
+        self.base
+            .and_then(|base| base.join(href).ok())
+            .map_or_else(|| href.replace(' ', "%20"), String::from)
+    }
+
+    fn render(&self, node: NodeRef<'_, Node>, out: &mut String, depth: usize) {
+        if self.hidden.contains(&node.id()) {
+            return;
+        }
+        let name = match node.value() {
+            Node::Text(text) => {
+                push_text(out, &collapse_whitespace(text));
+                return;
+            }
+            Node::Element(element) => element.name(),
+            Node::Document | Node::Fragment => {
+                self.render_children(node, out, depth);
+                return;
+            }
+            _ => return,
+        };
+        if depth > MAX_RENDER_DEPTH {
+            push_text(out, &collapse_whitespace(&self.visible_text(node)));
+            return;
+        }
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                let text = single_line(&self.render_inline(node, depth));
+                if !text.is_empty() {
+                    block_break(out);
+                    let _ = write!(out, "{} {text}", "#".repeat(level));
+                    block_break(out);
+                }
+            }
+            "br" => {
+                trim_trailing_spaces(out);
+                out.push('\n');
+            }
+            "hr" => {
+                block_break(out);
+                out.push_str("---");
+                block_break(out);
+            }
+            "strong" | "b" => wrap_inline(out, &self.render_inline(node, depth), "**"),
+            "em" | "i" | "cite" => wrap_inline(out, &self.render_inline(node, depth), "*"),
+            "del" | "s" | "strike" => wrap_inline(out, &self.render_inline(node, depth), "~~"),
+            "code" | "kbd" | "samp" | "tt" => {
+                let text = collapse_whitespace(&self.visible_text(node));
+                let fence = if text.contains('`') { "``" } else { "`" };
+                wrap_inline(out, &text, fence);
+            }
+            "a" => self.render_link(node, out, depth),
+            "img" => self.render_image(node, out),
+            "pre" => self.render_pre(node, out),
+            "blockquote" => {
+                let inner = tidy_markdown(&self.render_inline(node, depth));
+                if !inner.is_empty() {
+                    block_break(out);
+                    let quoted: Vec<String> = inner
+                        .lines()
+                        .map(|line| {
+                            if line.is_empty() {
+                                ">".to_string()
+                            } else {
+                                format!("> {line}")
+                            }
+                        })
+                        .collect();
+                    out.push_str(&quoted.join("\n"));
+                    block_break(out);
+                }
+            }
+            "ul" | "ol" => self.render_list(node, name == "ol", out, depth),
+            "table" => self.render_table(node, out, depth),
+            "li" => {
+                // A stray item outside a list.
+                block_break(out);
+                out.push_str("- ");
+                self.render_children(node, out, depth);
+                block_break(out);
+            }
+            "dt" => {
+                block_break(out);
+                wrap_inline(out, &single_line(&self.render_inline(node, depth)), "**");
+                out.push('\n');
+            }
+            _ if BLOCK_TAGS.contains(&name) => {
+                block_break(out);
+                self.render_children(node, out, depth);
+                block_break(out);
+            }
+            _ => self.render_children(node, out, depth),
+        }
+    }
+
+    fn render_link(&self, node: NodeRef<'_, Node>, out: &mut String, depth: usize) {
+        let text = self.render_inline(node, depth);
+        let label = single_line(&text);
+        let href = attr(node, "href").unwrap_or_default().trim();
+        if label.is_empty()
+            || href.is_empty()
+            || href.starts_with('#')
+            || href.to_ascii_lowercase().starts_with("javascript:")
+        {
+            push_text(out, &text);
+            return;
+        }
+        if text.starts_with(' ') {
+            push_text(out, " ");
+        }
+        let _ = write!(
+            out,
+            "[{}]({})",
+            label.replace(']', "\\]"),
+            self.resolve(href)
+        );
+        if text.ends_with(' ') {
+            out.push(' ');
+        }
+    }
+
+    fn render_image(&self, node: NodeRef<'_, Node>, out: &mut String) {
+        let src = attr(node, "src")
+            .or_else(|| attr(node, "data-src"))
+            .unwrap_or_default()
+            .trim();
+        if src.is_empty() || src.to_ascii_lowercase().starts_with("data:") {
+            return;
+        }
+        let alt = single_line(attr(node, "alt").unwrap_or_default());
+        let _ = write!(out, "![{}]({})", alt.replace(']', "\\]"), self.resolve(src));
+    }
+
+    fn render_pre(&self, node: NodeRef<'_, Node>, out: &mut String) {
+        let text = self.visible_text(node);
+        let language = std::iter::once(node)
+            .chain(node.first_child())
+            .filter_map(|n| attr(n, "class"))
+            .flat_map(str::split_whitespace)
+            .find_map(|class| {
+                class
+                    .strip_prefix("language-")
+                    .or_else(|| class.strip_prefix("lang-"))
+            })
+            .unwrap_or_default();
+        block_break(out);
+        let fence = if text.contains("```") { "~~~~" } else { "```" };
+        let _ = write!(
+            out,
+            "{fence}{language}\n{}\n{fence}",
+            text.trim_matches('\n')
+        );
+        block_break(out);
+    }
+
+    fn render_list(&self, node: NodeRef<'_, Node>, ordered: bool, out: &mut String, depth: usize) {
+        let mut number = attr(node, "start")
+            .and_then(|start| start.trim().parse::<i64>().ok())
+            .unwrap_or(1);
+        let mut items = Vec::new();
+        for child in node.children() {
+            if self.hidden.contains(&child.id()) {
+                continue;
+            }
+            if tag(child) != Some("li") {
+                // Text or markup between items; keep anything readable.
+                let mut stray = String::new();
+                self.render(child, &mut stray, depth + 1);
+                let stray = tidy_markdown(&stray);
+                if !stray.is_empty() {
+                    items.push(stray);
+                }
+                continue;
+            }
+            let content = tidy_markdown(&self.render_inline(child, depth + 1));
+            let marker = if ordered {
+                let marker = format!("{number}. ");
+                number = number.saturating_add(1);
+                marker
+            } else {
+                "- ".to_string()
+            };
+            let indent = " ".repeat(marker.len());
+            let mut item = marker;
+            for (idx, line) in content.lines().filter(|line| !line.is_empty()).enumerate() {
+                if idx > 0 {
+                    item.push('\n');
+                    item.push_str(&indent);
+                }
+                item.push_str(line);
+            }
+            items.push(item);
+        }
+        if !items.is_empty() {
+            block_break(out);
+            out.push_str(&items.join("\n"));
+            block_break(out);
+        }
+    }
+
+    fn render_table(&self, node: NodeRef<'_, Node>, out: &mut String, depth: usize) {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut layout = false;
+        self.collect_rows(node, &mut rows, &mut layout, depth);
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        // Tables used for page layout read better as plain blocks.
+        if layout || columns < 2 || rows.len() < 2 {
+            block_break(out);
+            self.render_children(node, out, depth);
+            block_break(out);
+            return;
+        }
+
+        block_break(out);
+        for (idx, row) in rows.iter().enumerate() {
+            let mut cells = row.clone();
+            cells.resize(columns, String::new());
+            let _ = writeln!(out, "| {} |", cells.join(" | "));
+            if idx == 0 {
+                let _ = writeln!(out, "|{}", " --- |".repeat(columns));
+            }
+        }
+        block_break(out);
+    }
+
+    fn collect_rows(
+        &self,
+        node: NodeRef<'_, Node>,
+        rows: &mut Vec<Vec<String>>,
+        layout: &mut bool,
+        depth: usize,
+    ) {
+        for child in node.children() {
+            if self.hidden.contains(&child.id()) {
+                continue;
+            }
+            match tag(child) {
+                Some("thead" | "tbody" | "tfoot") => self.collect_rows(child, rows, layout, depth),
+                Some("tr") => {
+                    let mut cells = Vec::new();
+                    for cell in child.children() {
+                        if self.hidden.contains(&cell.id())
+                            || !matches!(tag(cell), Some("td" | "th"))
+                        {
+                            continue;
+                        }
+                        if walk(cell).into_iter().skip(1).any(|(n, _)| {
+                            matches!(
+                                tag(n),
+                                Some(
+                                    "table"
+                                        | "ul"
+                                        | "ol"
+                                        | "pre"
+                                        | "blockquote"
+                                        | "h1"
+                                        | "h2"
+                                        | "h3"
+                                        | "div"
+                                )
+                            )
+                        }) {
+                            *layout = true;
+                        }
+                        let text = single_line(&self.render_inline(cell, depth + 2));
+                        cells.push(text.replace('|', "\\|"));
+                    }
+                    if !cells.is_empty() {
+                        rows.push(cells);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+/// Append collapsed text, dropping leading spaces at line starts and
+/// doubled spaces between inline runs.
+fn push_text(out: &mut String, text: &str) {
+    let text = if out.is_empty() || out.ends_with(['\n', ' ']) {
+        text.trim_start()
+    } else {
+        text
+    };
+    out.push_str(text);
+}
+
+/// Emphasis-style wrapper that keeps surrounding spaces outside the markers.
+fn wrap_inline(out: &mut String, inner: &str, marker: &str) {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        push_text(out, inner);
+        return;
+    }
+    if inner.starts_with(' ') {
+        push_text(out, " ");
+    }
+    let _ = write!(out, "{marker}{trimmed}{marker}");
+    if inner.ends_with(' ') {
+        out.push(' ');
+    }
+}
+
+fn single_line(text: &str) -> String {
+    collapse_whitespace(text).trim().to_string()
+}
+
+fn trim_trailing_spaces(out: &mut String) {
+    let trimmed = out.trim_end_matches(' ').len();
+    out.truncate(trimmed);
+}
+
+fn block_break(out: &mut String) {
+    trim_trailing_spaces(out);
+    if out.is_empty() {
+        return;
+    }
+    while !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+/// Trim line ends and collapse runs of blank lines, leaving fenced code
+/// blocks untouched.
+fn tidy_markdown(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut fence: Option<&str> = None;
+    let mut blank_run = 0;
+    for line in input.lines() {
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            out.push_str(line);
+            out.push('\n');
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = ["```", "~~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            fence = Some(open);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markdown(html: &str, main_content: bool) -> String {
+        html_to_markdown(html, "https://example.com/docs/guide.html", main_content).markdown
+    }
+
+    #[test]
+    fn converts_common_markup_to_markdown() {
+        let html = r#"<html><body>
+            <h1>Getting   started</h1>
+            <p>Read the <a href="../api/index.html">API docs</a> or <a href="https://other.org/x">elsewhere</a>.
+            Some <strong>bold</strong> and <em>italic</em> text with <code>inline()</code>.</p>
+            <ul><li>First<li>Second <b>item</b></ul>
+            <ol start="3"><li>Three</li><li>Four<ul><li>Nested</li></ul></li></ol>
+            <pre><code class="language-rust">fn main() {
+    println!("hi");
+}</code></pre>
+            <blockquote><p>Quoted &amp; escaped &lt;tag&gt;</p></blockquote>
+            <table><tr><th>Key</th><th>Value</th></tr><tr><td>a|b</td><td>1</td></tr></table>
+            <p>Line<br>break <img src="/logo.png" alt="Logo"></p>
+        </body></html>"#;
+        assert_eq!(
+            markdown(html, false),
+            "# Getting started\n\n\
+             Read the [API docs](https://example.com/api/index.html) or [elsewhere](https://other.org/x). \
+             Some **bold** and *italic* text with `inline()`.\n\n\
+             - First\n- Second **item**\n\n\
+             3. Three\n4. Four\n   - Nested\n\n\
+             ```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\n\
+             > Quoted & escaped <tag>\n\n\
+             | Key | Value |\n| --- | --- |\n| a\\|b | 1 |\n\n\
+             Line\nbreak ![Logo](https://example.com/logo.png)"
+        );
+    }
+
+    #[test]
+    fn main_content_drops_navigation_sidebars_and_scripts() {
+        let body = "This paragraph is the actual article text, long enough to matter, \
+                    with several commas, clauses, and details that a reader wants to see. ";
+        let html = format!(
+            r#"<html><head><title>Release notes &mdash; Example</title>
+            <script>var tracking = "<p>not content</p>";</script><style>p {{ color: red }}</style></head>
+            <body>
+              <header><a href="/">Home</a> <a href="/blog">Blog</a></header>
+              <nav><ul><li><a href="/a">A</a></li><li><a href="/b">B</a></li></ul></nav>
+              <div class="sidebar-widget"><p>Subscribe to our newsletter, now, today, please!</p></div>
+              <div id="content"><h2>Version 2.0</h2><p>{body}</p><p>{body}</p></div>
+              <div class="cookie-banner" style="display: none">We use cookies</div>
+              <footer>Copyright</footer>
+            </body></html>"#
+        );
+        let main = html_to_markdown(&html, "https://example.com/", true);
+        assert_eq!(main.title.as_deref(), Some("Release notes — Example"));
+        assert!(
+            main.markdown
+                .starts_with("## Version 2.0\n\nThis paragraph"),
+            "{}",
+            main.markdown
+        );
+        for noise in [
+            "Home",
+            "Subscribe",
+            "cookies",
+            "Copyright",
+            "tracking",
+            "color",
+        ] {
+            assert!(!main.markdown.contains(noise), "{noise} leaked");
+        }
+
+        let full = html_to_markdown(&html, "https://example.com/", false).markdown;
+        assert!(full.contains("[Blog](https://example.com/blog)"));
+        assert!(full.contains("Copyright"));
+        assert!(!full.contains("tracking"));
+    }
+
+    #[test]
+    fn article_element_wins_over_paragraph_scoring() {
+        let text =
+            "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod. ".repeat(4);
+        let html = format!(
+            "<body><div><p>Teaser, {text}</p></div><article><h1>Story</h1><p>{text}</p><p>{text}</p></article></body>"
+        );
+        let md = markdown(&html, true);
+        assert!(md.starts_with("# Story\n\n"), "{md}");
+        assert!(!md.contains("Teaser"), "{md}");
+    }
+
+    #[test]
+    fn malformed_markup_and_entities_are_handled_like_a_browser() {
+        let html =
+            "<p>5 < 6 &amp;&amp; 7 > 3 &#x41;&#66; &unknown; &</p><p>unclosed <b>bold<p>next";
+        // html5ever reopens the unclosed <b> in the following paragraph.
+        assert_eq!(
+            markdown(html, false),
+            "5 < 6 && 7 > 3 AB &unknown; &\n\nunclosed **bold**\n\n**next**"
+        );
+        assert_eq!(markdown("", false), "");
+    }
+
+    #[test]
+    fn links_skip_fragments_and_scripts_and_resolve_relative_forms() {
+        let html = r##"<p><a href="#top">Top</a> <a href="javascript:alert(1)">Run</a>
+            <a href="//cdn.example.net/x">CDN</a> <a href="?page=2">Next</a>
+            <a href="mailto:a@example.com">Mail</a> <img src="data:image/png;base64,AA" alt="inline"></p>"##;
+        assert_eq!(
+            markdown(html, false),
+            "Top Run [CDN](https://cdn.example.net/x) \
+             [Next](https://example.com/docs/guide.html?page=2) [Mail](mailto:a@example.com)"
+        );
+    }
+
+    #[test]
+    fn deeply_nested_markup_is_flattened_instead_of_recursing() {
+        let depth = 1_000;
+        let html = format!(
+            "{}<p>deep text</p>{}",
+            "<div><span>".repeat(depth),
+            "</span></div>".repeat(depth)
+        );
+        assert_eq!(markdown(&html, false), "deep text");
+        assert_eq!(markdown(&html, true), "deep text");
+    }
+
+    /// Random tag soup never panics, never leaks script/style bodies and
+    /// always produces tidy output.
+    #[test]
+    fn random_tag_soup_renders_without_leaking_hidden_content() {
+        const FRAGMENTS: &[&str] = &[
+            "<p>",
+            "</p>",
+            "<div class=\"sidebar\">",
+            "<div>",
+            "</div>",
+            "<a href=\"/x\">",
+            "<a href=\"javascript:x\">",
+            "</a>",
+            "<b>",
+            "</b>",
+            "<i>",
+            "<ul>",
+            "<ol start=\"-2\">",
+            "<li>",
+            "</ul>",
+            "<table>",
+            "<tr>",
+            "<td>",
+            "<th>",
+            "</table>",
+            "<pre>",
+            "</pre>",
+            "<code>",
+            "<blockquote>",
+            "</blockquote>",
+            "<h2>",
+            "</h2>",
+            "<br>",
+            "<hr>",
+            "<img src=\"a b.png\" alt=\"]\">",
+            "<script>SCRIPTBODY</script>",
+            "<style>STYLEBODY</style>",
+            "<!-- c -->",
+            "&amp;",
+            "&#0;",
+            "&#x110000;",
+            "text, more",
+            "```",
+            "|",
+            "\n",
+            "  ",
+            "<",
+            ">",
+            "<article>",
+            "</article>",
+            "<nav>x</nav>",
+            "<svg><text>SVGBODY</text></svg>",
+            "<textarea>",
+            "<noscript>",
+            "\u{a0}",
+            "é",
+        ];
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..300 {
+            let len = (next() % 60) as usize;
+            let html: String = (0..len)
+                .map(|_| FRAGMENTS[(next() % FRAGMENTS.len() as u64) as usize])
+                .collect();
+            for main_content in [false, true] {
+                let page = html_to_markdown(&html, "https://example.com/", main_content);
+                let md = &page.markdown;
+                for secret in ["SCRIPTBODY", "STYLEBODY", "SVGBODY"] {
+                    assert!(!md.contains(secret), "{secret} leaked from {html:?}");
+                }
+                assert_eq!(md.trim(), md, "untrimmed output for {html:?}");
+                assert!(
+                    md.lines()
+                        .all(|line| line == line.trim_end() || md.contains("```")),
+                    "trailing whitespace for {html:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,612 @@
+use super::http_request::{
+    extract_host, host_matches_allowlist, is_private_or_local_host, normalize_allowed_domains,
+};
+use super::traits::{Tool, ToolResult};
+use crate::config::WebFetchConfig;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+mod html;
+
+pub use html::{html_to_markdown, MarkdownPage};
+
+/// Redirect hops followed before giving up; every hop is re-validated.
+const MAX_REDIRECTS: usize = 5;
+/// Hard ceiling on `max_chars` regardless of what the caller requests.
+const MAX_CHARS_LIMIT: usize = 100_000;
+
+/// Fetch a web page and return its readable content as Markdown.
+///
+/// HTML is reduced to the main article (navigation, sidebars and footers
+/// are dropped) and converted with links preserved; PDFs go through the
+/// `pdf_read` extraction path. Long pages are read in chunks with `offset`,
+/// served from a short-lived in-memory cache so paging does not refetch.
+pub struct WebFetchTool {
+    security: Arc<SecurityPolicy>,
+    allowed_domains: Vec<String>,
+    config: WebFetchConfig,
+    cache: Mutex<HashMap<String, CachedPage>>,
+}
+
+/// A fetched page after conversion.
+#[derive(Debug)]
+struct Page {
+    /// URL after redirects.
+    url: String,
+    title: Option<String>,
+    content: String,
+    /// The download stopped at `max_response_size`.
+    truncated: bool,
+}
+
+struct CachedPage {
+    page: Arc<Page>,
+    fetched_at: Instant,
+}
+
+impl WebFetchTool {
+    pub fn new(
+        security: Arc<SecurityPolicy>,
+        allowed_domains: Vec<String>,
+        config: WebFetchConfig,
+    ) -> Self {
+        Self {
+            security,
+            allowed_domains: normalize_allowed_domains(allowed_domains),
+            config,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn validate_url(&self, raw_url: &str) -> Result<String, String> {
+        let url = raw_url.trim();
+        if url.is_empty() {
+            return Err("URL cannot be empty".into());
+        }
+        if url.chars().any(char::is_whitespace) {
+            return Err("URL cannot contain whitespace".into());
+        }
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err("Only http:// and https:// URLs are allowed".into());
+        }
+        if self.allowed_domains.is_empty() {
+            return Err(
+                "web_fetch is enabled but no allowed domains are configured. Add [http_request].allowed_domains in config.toml"
+                    .into(),
+            );
+        }
+
+        let host = extract_host(url).map_err(|e| e.to_string())?;
+        if is_private_or_local_host(&host) {
+            return Err(format!("Blocked local/private host: {host}"));
+        }
+        if !host_matches_allowlist(&host, &self.allowed_domains) {
+            return Err(format!(
+                "Host '{host}' is not in http_request.allowed_domains"
+            ));
+        }
+
+        // Fragments never reach the server; dropping them keeps cache keys stable.
+        let url = url.split('#').next().unwrap_or(url);
+        Ok(url.to_string())
+    }
+
+    fn cached(&self, key: &str) -> Option<Arc<Page>> {
+        let ttl = Duration::from_secs(self.config.cache_ttl_secs);
+        let mut cache = self.cache.lock();
+        cache.retain(|_, entry| entry.fetched_at.elapsed() < ttl);
+        cache.get(key).map(|entry| entry.page.clone())
+    }
+
+    fn store(&self, key: String, page: Arc<Page>) {
+        if self.config.cache_ttl_secs == 0 || self.config.cache_max_entries == 0 {
+            return;
+        }
+        let mut cache = self.cache.lock();
+        while cache.len() >= self.config.cache_max_entries {
+            let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.fetched_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            cache.remove(&oldest);
+        }
+        cache.insert(
+            key,
+            CachedPage {
+                page,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+
+    async fn fetch(&self, url: &str, main_content: bool) -> Result<Page, String> {
+        let builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.config.timeout_secs))
+            .connect_timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(concat!(
+                "zeroclaw/",
+                env!("CARGO_PKG_VERSION"),
+                " (web_fetch)"
+            ));
+        let builder = crate::config::apply_runtime_proxy_to_builder(builder, "tool.web_fetch");
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+
+        let mut current = url.to_string();
+        for _ in 0..=MAX_REDIRECTS {
+            let mut response = client
+                .get(&current)
+                .header(
+                    reqwest::header::ACCEPT,
+                    "text/html,application/xhtml+xml,application/pdf;q=0.9,text/plain;q=0.8,*/*;q=0.5",
+                )
+                .send()
+                .await
+                .map_err(|e| format!("Request failed: {e}"))?;
+
+            let status = response.status();
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|v| v.to_str().ok())
+                    .ok_or_else(|| {
+                        format!(
+                            "HTTP {} redirect without a Location header",
+                            status.as_u16()
+                        )
+                    })?;
+                let next = reqwest::Url::parse(&current)
+                    .and_then(|base| base.join(location))
+                    .map_err(|e| format!("Invalid redirect location '{location}': {e}"))?;
+                current = self.validate_url(next.as_str())?;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(format!(
+                    "HTTP {} {} from {current}",
+                    status.as_u16(),
+                    status.canonical_reason().unwrap_or("Unknown")
+                ));
+            }
+
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+
+            let mut body = Vec::new();
+            let mut truncated = false;
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| format!("Failed to read response body: {e}"))?
+            {
+                let room = self.config.max_response_size.saturating_sub(body.len());
+                if chunk.len() > room {
+                    body.extend_from_slice(&chunk[..room]);
+                    truncated = true;
+                    break;
+                }
+                body.extend_from_slice(&chunk);
+            }
+
+            return convert(current, &content_type, body, truncated, main_content).await;
+        }
+        Err(format!("Too many redirects (more than {MAX_REDIRECTS})"))
+    }
+}
+
+/// Turn a response body into a [`Page`] according to its content type.
+async fn convert(
+    url: String,
+    content_type: &str,
+    body: Vec<u8>,
+    truncated: bool,
+    main_content: bool,
+) -> Result<Page, String> {
+    if content_type.contains("application/pdf") || body.starts_with(b"%PDF-") {
+        if truncated {
+            return Err(
+                "PDF is larger than [web_fetch].max_response_size and cannot be extracted".into(),
+            );
+        }
+        let text = super::pdf_read::extract_pdf_text(body).await?;
+        return Ok(Page {
+            url,
+            title: None,
+            content: text.trim().to_string(),
+            truncated,
+        });
+    }
+
+    let text = String::from_utf8_lossy(&body);
+    let looks_like_html = {
+        let head: String = text
+            .chars()
+            .take(1024)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        head.contains("<!doctype html") || head.contains("<html")
+    };
+    if content_type.contains("html") || (content_type.is_empty() && looks_like_html) {
+        let MarkdownPage { title, markdown } = html_to_markdown(&text, &url, main_content);
+        return Ok(Page {
+            url,
+            title,
+            content: markdown,
+            truncated,
+        });
+    }
+
+    let textual = content_type.is_empty()
+        || content_type.starts_with("text/")
+        || ["json", "xml", "javascript", "yaml", "csv"]
+            .iter()
+            .any(|kind| content_type.contains(kind));
+    if !textual || body.contains(&0) {
+        return Err(format!(
+            "Unsupported content type '{content_type}'; web_fetch reads HTML, PDF and text pages"
+        ));
+    }
+    Ok(Page {
+        url,
+        title: None,
+        content: text.trim().to_string(),
+        truncated,
+    })
+}
+
+/// Render one chunk of a page for the model.
+fn render_page(page: &Page, offset: usize, max_chars: usize) -> Result<String, String> {
+    let total = page.content.chars().count();
+    if offset > 0 && offset >= total {
+        return Err(format!(
+            "offset {offset} is past the end of the page ({total} characters)"
+        ));
+    }
+    let chunk: String = page.content.chars().skip(offset).take(max_chars).collect();
+    let end = offset + chunk.chars().count();
+
+    let mut out = String::new();
+    if let Some(title) = &page.title {
+        let _ = writeln!(out, "# {title}\n");
+    }
+    let _ = writeln!(out, "Source: {}", page.url);
+    if offset > 0 || end < total {
+        let _ = write!(out, "Characters {offset}-{end} of {total}.");
+        if end < total {
+            let _ = write!(out, " Call web_fetch again with offset={end} for more.");
+        }
+        out.push('\n');
+    }
+    if page.truncated {
+        out.push_str(
+            "Download stopped at [web_fetch].max_response_size; the end of the page is missing.\n",
+        );
+    }
+    out.push('\n');
+    if chunk.is_empty() {
+        out.push_str("(no readable text; the page may need JavaScript to render)");
+    } else {
+        out.push_str(&chunk);
+    }
+    Ok(out)
+}
+
+#[async_trait]
+impl Tool for WebFetchTool {
+    fn name(&self) -> &str {
+        "web_fetch"
+    }
+
+    fn description(&self) -> &str {
+        "Fetch a web page and return its main content as Markdown with links preserved. \
+         Handles HTML, PDF and plain-text responses. Long pages are returned in chunks; \
+         pass the offset from the previous result to continue. Allowlisted domains only."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "HTTP or HTTPS URL of the page to read"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Character offset to start from, for reading long pages in chunks (default: 0)",
+                    "minimum": 0
+                },
+                "max_chars": {
+                    "type": "integer",
+                    "description": "Maximum characters to return (default from [web_fetch].max_chars, max: 100000)",
+                    "minimum": 1,
+                    "maximum": MAX_CHARS_LIMIT
+                },
+                "main_content": {
+                    "type": "boolean",
+                    "description": "Extract only the main article, dropping navigation, sidebars and footers (default: true)",
+                    "default": true
+                },
+                "refresh": {
+                    "type": "boolean",
+                    "description": "Fetch again even if the page is cached (default: false)",
+                    "default": false
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'url' parameter"))?;
+        let offset = args
+            .get("offset")
+            .and_then(|v| v.as_u64())
+            .map_or(0, |n| usize::try_from(n).unwrap_or(usize::MAX));
+        let max_chars = args
+            .get("max_chars")
+            .and_then(|v| v.as_u64())
+            .map_or(self.config.max_chars, |n| {
+                usize::try_from(n).unwrap_or(MAX_CHARS_LIMIT)
+            })
+            .clamp(1, MAX_CHARS_LIMIT);
+        let main_content = args
+            .get("main_content")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let refresh = args
+            .get("refresh")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if self.security.is_rate_limited() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Rate limit exceeded: too many actions in the last hour".into()),
+            });
+        }
+
+        let url = match self.validate_url(url) {
+            Ok(url) => url,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e),
+                })
+            }
+        };
+
+        let key = format!("{}:{url}", if main_content { "main" } else { "full" });
+        let page = match self.cached(&key).filter(|_| !refresh) {
+            Some(page) => page,
+            None => {
+                if !self.security.record_action() {
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some("Rate limit exceeded: action budget exhausted".into()),
+                    });
+                }
+                match self.fetch(&url, main_content).await {
+                    Ok(page) => {
+                        let page = Arc::new(page);
+                        self.store(key, page.clone());
+                        page
+                    }
+                    Err(e) => {
+                        return Ok(ToolResult {
+                            success: false,
+                            output: String::new(),
+                            error: Some(e),
+                        })
+                    }
+                }
+            }
+        };
+
+        match render_page(&page, offset, max_chars) {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::{AutonomyLevel, SecurityPolicy};
+
+    fn test_tool(allowed_domains: Vec<&str>) -> WebFetchTool {
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            ..SecurityPolicy::default()
+        });
+        WebFetchTool::new(
+            security,
+            allowed_domains.into_iter().map(String::from).collect(),
+            WebFetchConfig::default(),
+        )
+    }
+
+    #[test]
+    fn validate_url_enforces_allowlist_and_blocks_private_hosts() {
+        let tool = test_tool(vec!["example.com"]);
+        assert_eq!(
+            tool.validate_url("https://docs.example.com/page#section")
+                .unwrap(),
+            "https://docs.example.com/page"
+        );
+        assert!(tool
+            .validate_url("https://evil.com/")
+            .unwrap_err()
+            .contains("not in http_request.allowed_domains"));
+        assert!(tool.validate_url("ftp://example.com/").is_err());
+
+        let local = test_tool(vec!["localhost", "127.0.0.1"]);
+        assert!(local
+            .validate_url("http://127.0.0.1:8080/")
+            .unwrap_err()
+            .contains("local/private"));
+
+        let unconfigured = test_tool(vec![]);
+        assert!(unconfigured
+            .validate_url("https://example.com/")
+            .unwrap_err()
+            .contains("[http_request].allowed_domains"));
+    }
+
+    #[tokio::test]
+    async fn cached_pages_are_paged_by_offset_without_refetching() {
+        let tool = test_tool(vec!["example.com"]);
+        let content: String = (0..50).map(|i| format!("line {i:02}\n")).collect();
+        let total = content.chars().count();
+        tool.store(
+            "main:https://example.com/long".into(),
+            Arc::new(Page {
+                url: "https://example.com/long".into(),
+                title: Some("Long page".into()),
+                content,
+                truncated: false,
+            }),
+        );
+
+        let first = tool
+            .execute(json!({"url": "https://example.com/long", "max_chars": 100}))
+            .await
+            .unwrap();
+        assert!(first.success, "{:?}", first.error);
+        assert!(first
+            .output
+            .starts_with("# Long page\n\nSource: https://example.com/long\n"));
+        assert!(first.output.contains(&format!(
+            "Characters 0-100 of {total}. Call web_fetch again with offset=100 for more."
+        )));
+        assert!(first.output.ends_with("line 11\nline"));
+
+        let last = tool
+            .execute(
+                json!({"url": "https://example.com/long#frag", "offset": 350, "max_chars": 100}),
+            )
+            .await
+            .unwrap();
+        assert!(last
+            .output
+            .contains(&format!("Characters 350-{total} of {total}.\n")));
+        assert!(!last.output.contains("Call web_fetch again"));
+
+        let past_end = tool
+            .execute(json!({"url": "https://example.com/long", "offset": total}))
+            .await
+            .unwrap();
+        assert!(!past_end.success);
+        assert!(past_end.error.unwrap().contains("past the end"));
+    }
+
+    #[test]
+    fn cache_evicts_oldest_entry_when_full() {
+        let mut tool = test_tool(vec!["example.com"]);
+        tool.config.cache_max_entries = 2;
+        for name in ["a", "b", "c"] {
+            tool.store(
+                name.into(),
+                Arc::new(Page {
+                    url: name.into(),
+                    title: None,
+                    content: String::new(),
+                    truncated: false,
+                }),
+            );
+        }
+        assert!(tool.cached("a").is_none());
+        assert!(tool.cached("b").is_some());
+        assert!(tool.cached("c").is_some());
+    }
+
+    #[tokio::test]
+    async fn convert_handles_text_and_rejects_binary() {
+        let text = convert(
+            "https://example.com/a.txt".into(),
+            "text/plain; charset=utf-8",
+            b"  hello\n".to_vec(),
+            false,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text.content, "hello");
+        assert!(text.title.is_none());
+
+        let sniffed = convert(
+            "https://example.com/".into(),
+            "",
+            b"<!DOCTYPE html><title>T</title><p>Body</p>".to_vec(),
+            false,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(sniffed.title.as_deref(), Some("T"));
+        assert_eq!(sniffed.content, "Body");
+
+        let binary = convert(
+            "https://example.com/x.png".into(),
+            "image/png",
+            vec![0x89, b'P', b'N', b'G', 0],
+            false,
+            true,
+        )
+        .await
+        .unwrap_err();
+        assert!(binary.contains("Unsupported content type 'image/png'"));
+    }
+
+    #[cfg(not(feature = "rag-pdf"))]
+    #[tokio::test]
+    async fn pdf_responses_use_pdf_read_extraction() {
+        let err = convert(
+            "https://example.com/paper".into(),
+            "application/octet-stream",
+            b"%PDF-1.7\n".to_vec(),
+            false,
+            true,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("rag-pdf"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn missing_url_is_an_error() {
+        let tool = test_tool(vec!["example.com"]);
+        assert!(tool.execute(json!({})).await.is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Integration tests for the offline providers: a scripted run is recorded
+//! to a cassette, then replayed through a fresh agent with no live provider.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use zeroclaw::agent::agent::Agent;
+use zeroclaw::agent::dispatcher::NativeToolDispatcher;
+use zeroclaw::config::MemoryConfig;
+use zeroclaw::memory;
+use zeroclaw::observability::NoopObserver;
+use zeroclaw::providers::replay::{load_cassette, RecordingProvider, ReplayProvider};
+use zeroclaw::providers::scripted::ScriptedProvider;
+use zeroclaw::providers::Provider;
+use zeroclaw::tools::{Tool, ToolResult};
+
+const SCRIPT: &str = r#"
+responses:
+  - tool_calls:
+      - name: echo
+        arguments: { message: "from the script" }
+  - text: "The tool said: from the script"
+"#;
+
+/// Echo tool that counts how often it runs.
+struct EchoTool {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Tool for EchoTool {
+    fn name(&self) -> &str {
+        "echo"
+    }
+    fn description(&self) -> &str {
+        "Echoes the input message"
+    }
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": { "message": {"type": "string"} }
+        })
+    }
+    async fn execute(&self, args: serde_json::Value) -> Result<ToolResult> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(ToolResult {
+            success: true,
+            output: args["message"].as_str().unwrap_or_default().to_string(),
+            error: None,
+        })
+    }
+}
+
+fn build_agent(provider: Box<dyn Provider>, calls: &Arc<AtomicUsize>) -> Agent {
+    let memory_config = MemoryConfig {
+        backend: "none".into(),
+        ..MemoryConfig::default()
+    };
+    Agent::builder()
+        .provider(provider)
+        .tools(vec![Box::new(EchoTool {
+            calls: Arc::clone(calls),
+        })])
+        .memory(Arc::from(
+            memory::create_memory(&memory_config, &std::env::temp_dir(), None).unwrap(),
+        ))
+        .observer(Arc::new(NoopObserver {}))
+        .tool_dispatcher(Box::new(NativeToolDispatcher))
+        .workspace_dir(std::env::temp_dir())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn scripted_run_records_and_replays_offline() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let script_path = tmp.path().join("script.yaml");
+    let cassette_path = tmp.path().join("run.jsonl");
+    std::fs::write(&script_path, SCRIPT).unwrap();
+
+    // Record: scripted provider behind the recorder.
+    let recorded_calls = Arc::new(AtomicUsize::new(0));
+    let scripted = ScriptedProvider::from_file(&script_path).unwrap();
+    let recorder = RecordingProvider::new(Box::new(scripted), &cassette_path);
+    let mut agent = build_agent(Box::new(recorder), &recorded_calls);
+    let recorded = agent.turn("echo something").await.unwrap();
+
+    assert_eq!(recorded, "The tool said: from the script");
+    assert_eq!(recorded_calls.load(Ordering::SeqCst), 1);
+
+    let interactions = load_cassette(&cassette_path).unwrap();
+    assert_eq!(interactions.len(), 2);
+    assert_eq!(interactions[0].response.tool_calls[0].name, "echo");
+    assert_eq!(interactions[0].tools[0]["name"], "echo");
+    assert!(interactions[1]
+        .messages
+        .iter()
+        .any(|m| m.role == "tool" && m.content.contains("from the script")));
+
+    // Replay: same turn, no script, same tool execution and answer.
+    let replayed_calls = Arc::new(AtomicUsize::new(0));
+    let replay = ReplayProvider::from_file(&cassette_path).unwrap();
+    let mut agent = build_agent(Box::new(replay), &replayed_calls);
+    let replayed = agent.turn("echo something").await.unwrap();
+
+    assert_eq!(replayed, recorded);
+    assert_eq!(replayed_calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn factory_builds_offline_providers_from_paths() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let script_path = tmp.path().join("script.json");
+    std::fs::write(&script_path, r#"{"responses":[{"text":"scripted hello"}]}"#).unwrap();
+
+    let provider =
+        zeroclaw::providers::create_provider(&format!("scripted:{}", script_path.display()), None)
+            .unwrap();
+    let reply = provider.simple_chat("hi", "any-model", 0.0).await.unwrap();
+    assert_eq!(reply, "scripted hello");
+
+    let missing = tmp.path().join("missing.jsonl");
+    assert!(
+        zeroclaw::providers::create_provider(&format!("replay:{}", missing.display()), None)
+            .is_err()
+    );
+}